@@ -27,13 +27,7 @@ fn main() -> Result<()> {
             (s, t)
         };
 
-        let aux = paillier_zk::paillier_encryption_in_range::Aux {
-            s,
-            t,
-            rsa_modulo: n,
-            multiexp: None,
-            crt: None,
-        };
+        let aux = paillier_zk::paillier_encryption_in_range::Aux::new(s, t, n);
 
         let aux_json = serde_json::to_vec_pretty(&aux).context("serialzie aux")?;
         std::fs::write("./test-data/verifier_aux.json", aux_json).context("save aux")?;
@@ -3,7 +3,13 @@
 macro_rules! load_pregenerated_data {
     ($($name:ident: $type:ty),+$(,)?) => {$(
         pub fn $name() -> $type {
-            const JSON: &str = include_str!(concat!("../test-data/", stringify!($name), ".json"));
+            // Relative to the manifest, as doctests of nested modules live deeper in `src`
+            const JSON: &str = include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/test-data/",
+                stringify!($name),
+                ".json"
+            ));
             serde_json::from_str(JSON).unwrap()
         }
     )+};
@@ -0,0 +1,493 @@
+//! Long-term archival of proofs
+//!
+//! Proofs kept for years must remain verifiable after parameters and the library
+//! change. [`ArchivedProof`] is a self-describing envelope: along with prover's
+//! messages it embeds the security parameters the proof was verified with, the
+//! digest of ring-pedersen parameters, the curve, and the version of the wire
+//! format. [`reverify_archived`] reads the version and verifies the proof under the
+//! rules of that version.
+//!
+//! Wire format version [`WIRE_VERSION`] covers encoding of the envelope, of the
+//! embedded parameters and messages, and derivation of challenges. Whenever any of
+//! them changes, the version is bumped, and proofs archived under previous versions
+//! keep being verified by the rules they were made under.
+//!
+//! Statement and shared state are not embedded: they're defined by the protocol and
+//! must be retained by the caller along with the envelope.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{archive, paillier_encryption_in_range as p};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     commitment: &p::Commitment,
+//! #     security: &p::SecurityParams,
+//! #     proof: &p::Proof,
+//! # ) -> Result<(), archive::ArchiveError> {
+//! use paillier_zk::encoding::{Decode, Encode};
+//!
+//! // Proof is archived once it's verified
+//! let archived = archive::archive::<_, sha2::Sha256>(&data, aux, security, commitment, proof);
+//! let bytes = archived.to_canonical_bytes();
+//!
+//! // Years later
+//! let archived = archive::ArchivedProof::from_canonical_bytes(&bytes)
+//!     .ok_or(archive::ArchiveError::Malformed)?;
+//! archive::reverify_archived(sha2::Sha256::default(), aux, &data, &archived)?;
+//! # Ok(()) }
+//! ```
+
+use digest::Digest;
+use rug::Integer;
+
+use crate::common::encoding::{encode_len, split_len_prefixed, Decode, Encode};
+use crate::common::{Aux, InvalidProof};
+use crate::policy::Statement;
+
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+use crate::group_element_vs_paillier_encryption_in_range as log_star;
+#[cfg(feature = "paillier-affine-operation-in-range")]
+use crate::paillier_affine_operation_in_range as aff_g;
+#[cfg(feature = "paillier-encryption-in-range")]
+use crate::paillier_encryption_in_range as enc;
+
+/// Version of the wire format produced by this version of the library
+pub const WIRE_VERSION: u32 = 1;
+
+/// Proof along with everything needed to verify it in the future
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArchivedProof {
+    /// Version of the wire format, see [`WIRE_VERSION`]
+    pub wire_version: u32,
+    /// Name of the proof module, see [`Statement::PROOF`]
+    pub proof: String,
+    /// Name of the curve, if the statement involves one
+    pub curve: Option<String>,
+    /// Encoded security parameters
+    pub security: Vec<u8>,
+    /// Digest of ring-pedersen parameters of the verifier, see [`Aux::digest`]
+    pub aux_digest: Vec<u8>,
+    /// Encoded commitment and proof
+    pub messages: Vec<u8>,
+}
+
+/// Proof that can be archived, implemented for its statement
+pub trait Archivable: Statement {
+    /// Security parameters of the proof
+    type SecurityParams;
+    /// Prover's first message
+    type Commitment: Encode + Decode;
+    /// Prover's response
+    type Proof: Encode + Decode;
+
+    /// Encodes security parameters in [current](WIRE_VERSION) wire format
+    fn encode_security(security: &Self::SecurityParams, out: &mut Vec<u8>);
+
+    /// Decodes security parameters encoded in wire format `version`
+    fn decode_security(version: u32, bytes: &[u8]) -> Option<Self::SecurityParams>;
+
+    /// Verifies the proof under rules of wire format `version`
+    fn verify<D: Digest>(
+        &self,
+        version: u32,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof>;
+}
+
+/// Puts the proof into the envelope
+///
+/// `D` is the hash function used to compute digest of `aux`
+pub fn archive<S: Archivable, D: Digest>(
+    statement: &S,
+    aux: &Aux,
+    security: &S::SecurityParams,
+    commitment: &S::Commitment,
+    proof: &S::Proof,
+) -> ArchivedProof {
+    let mut encoded_security = vec![];
+    S::encode_security(security, &mut encoded_security);
+    ArchivedProof {
+        wire_version: WIRE_VERSION,
+        proof: S::PROOF.to_owned(),
+        curve: statement.curve().map(ToOwned::to_owned),
+        security: encoded_security,
+        aux_digest: aux.digest::<D>().to_vec(),
+        messages: (commitment, proof).to_canonical_bytes(),
+    }
+}
+
+/// Verifies the archived proof about `statement` under the rules of its wire version
+///
+/// Besides verifying the proof, checks that the envelope was made for this kind of
+/// statement and curve, and that `aux` has the archived digest computed with `D`.
+pub fn reverify_archived<S: Archivable, D: Digest>(
+    shared_state: D,
+    aux: &Aux,
+    statement: &S,
+    archived: &ArchivedProof,
+) -> Result<(), ArchiveError> {
+    let version = archived.wire_version;
+    if version != 1 {
+        return Err(ArchiveError::UnsupportedVersion(version));
+    }
+    if archived.proof != S::PROOF {
+        return Err(ArchiveError::ProofMismatch {
+            expected: S::PROOF,
+            found: archived.proof.clone(),
+        });
+    }
+    if archived.curve.as_deref() != statement.curve() {
+        return Err(ArchiveError::CurveMismatch);
+    }
+    if archived.aux_digest[..] != aux.digest::<D>()[..] {
+        return Err(ArchiveError::AuxMismatch);
+    }
+    let security =
+        S::decode_security(version, &archived.security).ok_or(ArchiveError::Malformed)?;
+    let (commitment, proof) = <(S::Commitment, S::Proof)>::from_canonical_bytes(&archived.messages)
+        .ok_or(ArchiveError::Malformed)?;
+    statement.verify(version, shared_state, aux, &security, &commitment, &proof)?;
+    Ok(())
+}
+
+/// Error returned by [`reverify_archived`]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ArchiveError {
+    /// Wire format is not known to this version of the library
+    #[error("unsupported wire version {0}")]
+    UnsupportedVersion(u32),
+    /// Envelope contains another proof
+    #[error("expected proof {expected}, found {found}")]
+    ProofMismatch {
+        expected: &'static str,
+        found: String,
+    },
+    /// Envelope was made for statement on another curve
+    #[error("curve mismatch")]
+    CurveMismatch,
+    /// Digest of ring-pedersen parameters doesn't match the archived one
+    #[error("ring-pedersen parameters don't match the archived digest")]
+    AuxMismatch,
+    /// Envelope or its contents are not canonically encoded
+    #[error("malformed archive")]
+    Malformed,
+    /// Archived proof is invalid
+    #[error(transparent)]
+    InvalidProof(#[from] InvalidProof),
+}
+
+/// Envelope is encoded as wire version (`u32` in big-endian), followed by the fields
+/// in order of declaration. Strings and byte strings are prefixed with their length,
+/// absent curve is encoded as an empty string.
+impl Encode for ArchivedProof {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.wire_version.to_be_bytes());
+        for field in [
+            self.proof.as_bytes(),
+            self.curve.as_deref().unwrap_or_default().as_bytes(),
+            &self.security,
+            &self.aux_digest,
+            &self.messages,
+        ] {
+            encode_len(field.len(), out);
+            out.extend_from_slice(field);
+        }
+    }
+}
+
+impl Decode for ArchivedProof {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let (version, mut rest) = bytes.split_first_chunk::<4>()?;
+        let mut field = || {
+            let (field, tail) = split_len_prefixed(rest)?;
+            rest = tail;
+            Some(field.to_vec())
+        };
+        let proof = String::from_utf8(field()?).ok()?;
+        let curve = String::from_utf8(field()?).ok()?;
+        let archived = Self {
+            wire_version: u32::from_be_bytes(*version),
+            proof,
+            curve: (!curve.is_empty()).then_some(curve),
+            security: field()?,
+            aux_digest: field()?,
+            messages: field()?,
+        };
+        *bytes = rest;
+        Some(archived)
+    }
+}
+
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn encode_usize(x: usize, out: &mut Vec<u8>) {
+    Integer::from(x).encode(out)
+}
+
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn decode_usize(bytes: &mut &[u8]) -> Option<usize> {
+    Integer::decode(bytes)?.to_usize()
+}
+
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn encode_aux_digest(aux_digest: &Option<Vec<u8>>, out: &mut Vec<u8>) {
+    aux_digest.is_some().encode(out);
+    if let Some(aux_digest) = aux_digest {
+        encode_len(aux_digest.len(), out);
+        out.extend_from_slice(aux_digest);
+    }
+}
+
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn decode_aux_digest(bytes: &mut &[u8]) -> Option<Option<Vec<u8>>> {
+    if !bool::decode(bytes)? {
+        return Some(None);
+    }
+    let (aux_digest, rest) = split_len_prefixed(bytes)?;
+    *bytes = rest;
+    Some(Some(aux_digest.to_vec()))
+}
+
+/// Domain separation tag is appended to parameters of version 1 only when it's set,
+/// so parameters without the tag are encoded the same as before tags were introduced
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn encode_domain_tag(domain_tag: &Option<Vec<u8>>, out: &mut Vec<u8>) {
+    if let Some(domain_tag) = domain_tag {
+        encode_len(domain_tag.len(), out);
+        out.extend_from_slice(domain_tag);
+    }
+}
+
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn decode_domain_tag(bytes: &mut &[u8]) -> Option<Option<Vec<u8>>> {
+    if bytes.is_empty() {
+        return Some(None);
+    }
+    let (domain_tag, rest) = split_len_prefixed(bytes)?;
+    *bytes = rest;
+    Some(Some(domain_tag.to_vec()))
+}
+
+#[cfg(feature = "paillier-encryption-in-range")]
+impl Archivable for enc::Data<'_> {
+    type SecurityParams = enc::SecurityParams;
+    type Commitment = enc::Commitment;
+    type Proof = enc::Proof;
+
+    fn encode_security(security: &Self::SecurityParams, out: &mut Vec<u8>) {
+        encode_usize(security.l, out);
+        encode_usize(security.epsilon, out);
+        security.q.encode(out);
+        encode_aux_digest(&security.aux_digest, out);
+        security.reject_weak_aux.encode(out);
+        encode_domain_tag(&security.domain_tag, out);
+    }
+
+    fn decode_security(version: u32, mut bytes: &[u8]) -> Option<Self::SecurityParams> {
+        if version != 1 {
+            return None;
+        }
+        let bytes = &mut bytes;
+        let security = enc::SecurityParams {
+            l: decode_usize(bytes)?,
+            epsilon: decode_usize(bytes)?,
+            q: Integer::decode(bytes)?,
+            aux_digest: decode_aux_digest(bytes)?,
+            reject_weak_aux: bool::decode(bytes)?,
+            domain_tag: decode_domain_tag(bytes)?,
+        };
+        bytes.is_empty().then_some(security)
+    }
+
+    fn verify<D: Digest>(
+        &self,
+        _version: u32,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        enc::non_interactive::verify(shared_state, aux, *self, commitment, security, proof)
+    }
+}
+
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+impl<C: generic_ec::Curve> Archivable for log_star::Data<'_, C> {
+    type SecurityParams = log_star::SecurityParams;
+    type Commitment = log_star::Commitment<C>;
+    type Proof = log_star::Proof;
+
+    fn encode_security(security: &Self::SecurityParams, out: &mut Vec<u8>) {
+        encode_usize(security.l, out);
+        encode_usize(security.epsilon, out);
+        security.q.encode(out);
+        encode_aux_digest(&security.aux_digest, out);
+        security.reject_weak_aux.encode(out);
+    }
+
+    fn decode_security(version: u32, mut bytes: &[u8]) -> Option<Self::SecurityParams> {
+        if version != 1 {
+            return None;
+        }
+        let bytes = &mut bytes;
+        let security = log_star::SecurityParams {
+            l: decode_usize(bytes)?,
+            epsilon: decode_usize(bytes)?,
+            q: Integer::decode(bytes)?,
+            aux_digest: decode_aux_digest(bytes)?,
+            reject_weak_aux: bool::decode(bytes)?,
+            // Only affects the prover
+            require_bound_fits_curve: false,
+        };
+        bytes.is_empty().then_some(security)
+    }
+
+    fn verify<D: Digest>(
+        &self,
+        _version: u32,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        log_star::non_interactive::verify(shared_state, aux, *self, commitment, security, proof)
+    }
+}
+
+#[cfg(feature = "paillier-affine-operation-in-range")]
+impl<C: generic_ec::Curve> Archivable for aff_g::Data<'_, C> {
+    type SecurityParams = aff_g::SecurityParams;
+    type Commitment = aff_g::Commitment<C>;
+    type Proof = aff_g::Proof;
+
+    fn encode_security(security: &Self::SecurityParams, out: &mut Vec<u8>) {
+        encode_usize(security.l_x, out);
+        encode_usize(security.l_y, out);
+        encode_usize(security.epsilon, out);
+        security.q.encode(out);
+        encode_aux_digest(&security.aux_digest, out);
+        security.reject_weak_aux.encode(out);
+        security.require_distinct_keys.encode(out);
+        encode_domain_tag(&security.domain_tag, out);
+    }
+
+    fn decode_security(version: u32, mut bytes: &[u8]) -> Option<Self::SecurityParams> {
+        if version != 1 {
+            return None;
+        }
+        let bytes = &mut bytes;
+        let security = aff_g::SecurityParams {
+            l_x: decode_usize(bytes)?,
+            l_y: decode_usize(bytes)?,
+            epsilon: decode_usize(bytes)?,
+            q: Integer::decode(bytes)?,
+            aux_digest: decode_aux_digest(bytes)?,
+            reject_weak_aux: bool::decode(bytes)?,
+            require_distinct_keys: bool::decode(bytes)?,
+            // Only affects the prover
+            require_bound_fits_curve: false,
+            domain_tag: decode_domain_tag(bytes)?,
+        };
+        bytes.is_empty().then_some(security)
+    }
+
+    fn verify<D: Digest>(
+        &self,
+        _version: u32,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        aff_g::non_interactive::verify(shared_state, aux, *self, commitment, security, proof)
+    }
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::{Decode, Encode};
+    use crate::paillier_encryption_in_range as p;
+
+    use super::{ArchiveError, ArchivedProof};
+
+    #[test]
+    fn archived_proof() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete())
+            .with_weak_aux_rejected();
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) =
+            p::non_interactive::prove(shared_state.clone(), &aux, data, pdata, &security, &mut rng)
+                .unwrap();
+
+        let archived =
+            super::archive::<_, sha2::Sha256>(&data, &aux, &security, &commitment, &proof);
+        assert_eq!(archived.wire_version, super::WIRE_VERSION);
+        let bytes = archived.to_canonical_bytes();
+        let archived = ArchivedProof::from_canonical_bytes(&bytes).unwrap();
+        super::reverify_archived(shared_state.clone(), &aux, &data, &archived)
+            .expect("archived proof failed");
+
+        let reverify = |archived: &ArchivedProof| {
+            super::reverify_archived(shared_state.clone(), &aux, &data, archived)
+        };
+        let r = reverify(&ArchivedProof {
+            wire_version: 2,
+            ..archived.clone()
+        });
+        assert!(matches!(r, Err(ArchiveError::UnsupportedVersion(2))));
+        let r = reverify(&ArchivedProof {
+            proof: "paillier_blum_modulus".into(),
+            ..archived.clone()
+        });
+        assert!(matches!(r, Err(ArchiveError::ProofMismatch { .. })));
+        let r = reverify(&ArchivedProof {
+            curve: Some("secp256k1".into()),
+            ..archived.clone()
+        });
+        assert!(matches!(r, Err(ArchiveError::CurveMismatch)));
+        let r = reverify(&ArchivedProof {
+            messages: archived.messages[1..].to_vec(),
+            ..archived.clone()
+        });
+        assert!(matches!(r, Err(ArchiveError::Malformed)));
+
+        // Proof is verified under the archived parameters
+        let mut other_security = security.clone();
+        other_security.q += 1;
+        let r = reverify(&super::archive::<_, sha2::Sha256>(
+            &data,
+            &aux,
+            &other_security,
+            &commitment,
+            &proof,
+        ));
+        assert!(matches!(r, Err(ArchiveError::InvalidProof(_))));
+
+        let other_aux = crate::common::test::aux(&mut rng);
+        let r = super::reverify_archived(shared_state.clone(), &other_aux, &data, &archived);
+        assert!(matches!(r, Err(ArchiveError::AuxMismatch)));
+
+        // Domain separation tag is archived along with other parameters
+        let tagged = security.with_domain_tag(b"app".as_slice());
+        let (commitment, proof) =
+            p::non_interactive::prove(shared_state.clone(), &aux, data, pdata, &tagged, &mut rng)
+                .unwrap();
+        let archived = super::archive::<_, sha2::Sha256>(&data, &aux, &tagged, &commitment, &proof);
+        reverify(&archived).expect("archived proof failed");
+    }
+}
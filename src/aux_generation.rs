@@ -0,0 +1,209 @@
+//! Generation of ring-pedersen parameters
+//!
+//! Generating [`Aux`] requires two safe primes, which takes from seconds to minutes
+//! for production-sized moduli. [`AuxGenerator`] does the work in chunks of bounded
+//! size, so it can run on an async runtime without blocking the executor: each call
+//! to [`AuxGenerator::poll_generate`] tests at most the given amount of prime
+//! candidates and returns [`Poll::Pending`] if the parameters aren't ready yet.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use std::task::Poll;
+//! use paillier_zk::aux_generation::AuxGenerator;
+//!
+//! # async fn yield_now() {}
+//! # async fn doc_fn() -> Result<(), paillier_zk::aux_generation::AuxGenError> {
+//! let mut rng = rand_core::OsRng;
+//! let mut generator = AuxGenerator::new(2048)?;
+//! let (aux, secrets) = loop {
+//!     match generator.poll_generate(&mut rng, 100) {
+//!         Poll::Ready(generated) => break generated,
+//!         // Give other tasks a chance to run
+//!         Poll::Pending => yield_now().await,
+//!     }
+//! };
+//! # Ok(()) }
+//! ```
+
+use std::task::Poll;
+
+use rand_core::{CryptoRng, RngCore};
+use rug::{integer::IsPrime, Complete, Integer};
+
+use crate::common::{Aux, IntegerExt, MIN_AUX_MODULUS_BITS};
+
+/// Amount of Miller-Rabin rounds, same as used in `mpz_nextprime`
+const PRIMALITY_REPS: u32 = 25;
+
+/// Generates [`Aux`] in chunks, see [module level docs](self)
+#[derive(Debug, Clone)]
+pub struct AuxGenerator {
+    modulus_bits: u32,
+    p: Option<Integer>,
+}
+
+/// Secrets behind the generated [`Aux`]
+///
+/// Needed to prove that the parameters were generated honestly, and to speed up
+/// exponentiations with [`Aux::with_crt`]. Must be kept private.
+#[derive(Clone)]
+pub struct AuxSecrets {
+    /// Safe prime, factor of `N^`
+    pub p: Integer,
+    /// Safe prime, factor of `N^`
+    pub q: Integer,
+    /// Discrete logarithm of `s` base `t`
+    pub lambda: Integer,
+}
+
+impl AuxGenerator {
+    /// Starts generation of ring-pedersen parameters with `N^` of `modulus_bits` bits
+    ///
+    /// `modulus_bits` must be even and at least [`MIN_AUX_MODULUS_BITS`]
+    pub fn new(modulus_bits: u32) -> Result<Self, AuxGenError> {
+        if modulus_bits < MIN_AUX_MODULUS_BITS {
+            return Err(AuxGenError::SmallModulus(modulus_bits));
+        }
+        if modulus_bits % 2 == 1 {
+            return Err(AuxGenError::OddModulusBits(modulus_bits));
+        }
+        Ok(Self {
+            modulus_bits,
+            p: None,
+        })
+    }
+
+    /// Tests at most `max_candidates` prime candidates, returns the parameters once
+    /// they're generated
+    ///
+    /// Once parameters are returned, subsequent calls start generation of new ones.
+    pub fn poll_generate<R: RngCore + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+        max_candidates: usize,
+    ) -> Poll<(Aux, AuxSecrets)> {
+        let prime_bits = self.modulus_bits / 2;
+        for _ in 0..max_candidates {
+            let Some(prime) = safe_prime_candidate(rng, prime_bits) else {
+                continue;
+            };
+            match self.p.take() {
+                None => self.p = Some(prime),
+                Some(p) if p == prime => self.p = Some(p),
+                Some(p) => return Poll::Ready(finish(rng, p, prime)),
+            }
+        }
+        Poll::Pending
+    }
+
+    /// Generates the parameters, blocking until they're ready
+    pub fn generate<R: RngCore + CryptoRng>(mut self, rng: &mut R) -> (Aux, AuxSecrets) {
+        loop {
+            if let Poll::Ready(generated) = self.poll_generate(rng, usize::MAX) {
+                return generated;
+            }
+        }
+    }
+}
+
+/// Samples a candidate and returns it if it's a safe prime of exactly `bits` bits
+///
+/// Two most significant bits of the prime are set, so product of two such primes
+/// has exactly `2 bits` bits
+fn safe_prime_candidate(rng: &mut impl RngCore, bits: u32) -> Option<Integer> {
+    let mut rand = fast_paillier::utils::external_rand(rng);
+    let mut x = Integer::from(Integer::random_bits(bits - 1, &mut rand));
+    x.set_bit(bits - 2, true);
+    x.set_bit(bits - 3, true);
+    x |= 1u32;
+    if x.is_probably_prime(PRIMALITY_REPS) == IsPrime::No {
+        return None;
+    }
+    let p = (x << 1u32) + 1u32;
+    if p.is_probably_prime(PRIMALITY_REPS) == IsPrime::No {
+        return None;
+    }
+    Some(p)
+}
+
+/// Computes `s`, `t` modulo `N^ = p q`
+fn finish<R: RngCore + CryptoRng>(rng: &mut R, p: Integer, q: Integer) -> (Aux, AuxSecrets) {
+    let n = (&p * &q).complete();
+    let phi_n = (&p - Integer::ONE).complete() * (&q - Integer::ONE).complete();
+    loop {
+        let r = Integer::gen_invertible(&n, rng);
+        let t = r.square().modulo(&n);
+        let lambda = phi_n
+            .random_below_ref(&mut fast_paillier::utils::external_rand(rng))
+            .into();
+        let Some(s) = t.pow_mod_ref(&lambda, &n).map(Integer::from) else {
+            continue;
+        };
+        let aux = Aux::new(s, t, n.clone());
+        // Fails with negligible probability, e.g. if `lambda = 1`
+        if aux.check_sanity().is_ok() {
+            return (aux, AuxSecrets { p, q, lambda });
+        }
+    }
+}
+
+/// Error indicating that ring-pedersen parameters can't be generated
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[non_exhaustive]
+pub enum AuxGenError {
+    /// Requested `N^` is too small. Parameterized by its bit size
+    #[error("N^ of {0} bits requested, at least {MIN_AUX_MODULUS_BITS} bits expected")]
+    SmallModulus(u32),
+    /// Requested bit size of `N^` is odd, so it can't be a product of equally sized
+    /// primes
+    #[error("N^ of {0} bits requested, bit size must be even")]
+    OddModulusBits(u32),
+}
+
+#[cfg(test)]
+mod test {
+    use std::task::Poll;
+
+    use rug::{integer::IsPrime, Complete, Integer};
+
+    use super::{AuxGenError, AuxGenerator};
+
+    #[test]
+    fn generates_in_chunks() {
+        let mut rng = rand_dev::DevRng::new();
+        let mut generator = AuxGenerator::new(1024).unwrap();
+        let mut polls = 0;
+        let (aux, secrets) = loop {
+            polls += 1;
+            if let Poll::Ready(generated) = generator.poll_generate(&mut rng, 10) {
+                break generated;
+            }
+        };
+        assert!(polls > 1);
+
+        aux.check_sanity().unwrap();
+        assert_eq!(aux.rsa_modulo.significant_bits(), 1024);
+        assert_eq!(aux.rsa_modulo, (&secrets.p * &secrets.q).complete());
+        for p in [&secrets.p, &secrets.q] {
+            let p_prime = (p - Integer::ONE).complete() >> 1u32;
+            assert_ne!(p.is_probably_prime(25), IsPrime::No);
+            assert_ne!(p_prime.is_probably_prime(25), IsPrime::No);
+        }
+        assert_eq!(
+            aux.s,
+            Integer::from(aux.t.pow_mod_ref(&secrets.lambda, &aux.rsa_modulo).unwrap())
+        );
+    }
+
+    #[test]
+    fn rejects_bad_sizes() {
+        assert_eq!(
+            AuxGenerator::new(512).unwrap_err(),
+            AuxGenError::SmallModulus(512)
+        );
+        assert_eq!(
+            AuxGenerator::new(2049).unwrap_err(),
+            AuxGenError::OddModulusBits(2049)
+        );
+    }
+}
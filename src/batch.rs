@@ -0,0 +1,203 @@
+//! Canonical ordering of batch inputs
+//!
+//! Parties that batch-verify or hash several proofs must agree on their order,
+//! otherwise two honest parties receiving the same proofs in different order compute
+//! different digests. Inputs are therefore put in canonical order: entries are sorted
+//! by [digest of their statement](statement_digest), entries with equal statements
+//! are sorted by [canonical encoding](crate::encoding) of prover's messages.
+//!
+//! [`bundle_digest`] commits to a set of proofs regardless of the order they were
+//! received in. When the order is meaningful, use [`ProofChain`](crate::chain::ProofChain)
+//! which is explicitly ordered.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{batch, paillier_encryption_in_range as p};
+//! # fn doc_fn(entries: &mut [(p::Data, (p::Commitment, p::Proof))]) {
+//! batch::sort_canonically::<sha2::Sha256, _, _>(entries);
+//! let digest = batch::bundle_digest::<sha2::Sha256, _, _>(entries);
+//! # }
+//! ```
+
+use digest::{Digest, Output};
+
+use crate::common::encoding::Encode;
+use crate::policy::Statement;
+
+const STATEMENT_TAG: &[u8] = b"paillier_zk.batch.statement";
+const BUNDLE_TAG: &[u8] = b"paillier_zk.batch.bundle";
+
+/// Digest identifying the statement within a batch
+///
+/// Commits to the proof the statement belongs to, see [`Statement::PROOF`], and to
+/// the canonical encoding of the statement
+pub fn statement_digest<D: Digest, S: Statement + Encode>(statement: &S) -> Output<D> {
+    let mut hash = D::new()
+        .chain_update(STATEMENT_TAG)
+        .chain_update((S::PROOF.len() as u64).to_be_bytes())
+        .chain_update(S::PROOF);
+    update_with_value(&mut hash, statement);
+    hash.finalize()
+}
+
+/// Returns indices of `entries` in canonical order
+///
+/// Each entry is a statement along with prover's messages, i.e. commitment and proof.
+pub fn canonical_order<D, S, M>(entries: &[(S, M)]) -> Vec<usize>
+where
+    D: Digest,
+    S: Statement + Encode,
+    M: Encode,
+{
+    let keys = entries
+        .iter()
+        .map(|(statement, messages)| {
+            (
+                statement_digest::<D, _>(statement),
+                messages.to_canonical_bytes(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut order = (0..entries.len()).collect::<Vec<_>>();
+    order.sort_by(|&i, &j| keys[i].cmp(&keys[j]));
+    order
+}
+
+/// Sorts `entries` into canonical order
+///
+/// Each entry is a statement along with prover's messages, i.e. commitment and proof.
+pub fn sort_canonically<D, S, M>(entries: &mut [(S, M)])
+where
+    D: Digest,
+    S: Statement + Encode,
+    M: Encode,
+{
+    entries.sort_by_cached_key(|(statement, messages)| {
+        (
+            statement_digest::<D, _>(statement),
+            messages.to_canonical_bytes(),
+        )
+    })
+}
+
+/// Digest of the set of proofs which doesn't depend on their order
+///
+/// Each entry is a statement along with prover's messages, i.e. commitment and proof.
+/// Entries are absorbed in [canonical order](canonical_order).
+pub fn bundle_digest<D, S, M>(entries: &[(S, M)]) -> Output<D>
+where
+    D: Digest,
+    S: Statement + Encode,
+    M: Encode,
+{
+    let mut hash = D::new()
+        .chain_update(BUNDLE_TAG)
+        .chain_update((entries.len() as u64).to_be_bytes());
+    for i in canonical_order::<D, _, _>(entries) {
+        let (statement, messages) = &entries[i];
+        hash.update(statement_digest::<D, _>(statement));
+        update_with_value(&mut hash, messages);
+    }
+    hash.finalize()
+}
+
+/// Hashes length-prefixed canonical encoding of the value, without encoding it into
+/// one buffer
+fn update_with_value<D: Digest>(hash: &mut D, value: &impl Encode) {
+    hash.update((value.encoded_len() as u64).to_be_bytes());
+    value.encode_chunks(&mut |chunk| hash.update(chunk));
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::IntegerExt;
+    use crate::paillier_encryption_in_range as p;
+
+    #[test]
+    fn permutation_invariance() {
+        type D = sha2::Sha256;
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+
+        let ciphertexts = (0..4)
+            .map(|_| {
+                let plaintext =
+                    Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+                let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+                (ciphertext, plaintext, nonce)
+            })
+            .collect::<Vec<_>>();
+        let mut entries = ciphertexts
+            .iter()
+            .map(|(ciphertext, plaintext, nonce)| {
+                let data = p::Data { key, ciphertext };
+                let pdata = p::PrivateData { plaintext, nonce };
+                let messages = p::non_interactive::prove(
+                    sha2::Sha256::default(),
+                    &aux,
+                    data,
+                    pdata,
+                    &security,
+                    &mut rng,
+                )
+                .unwrap();
+                (data, messages)
+            })
+            .collect::<Vec<_>>();
+        // Same statement proven twice
+        let duplicate = (
+            entries[0].0,
+            p::non_interactive::prove(
+                sha2::Sha256::default(),
+                &aux,
+                entries[0].0,
+                p::PrivateData {
+                    plaintext: &ciphertexts[0].1,
+                    nonce: &ciphertexts[0].2,
+                },
+                &security,
+                &mut rng,
+            )
+            .unwrap(),
+        );
+        entries.push(duplicate);
+
+        let digest = super::bundle_digest::<D, _, _>(&entries);
+        let mut sorted = entries.clone();
+        super::sort_canonically::<D, _, _>(&mut sorted);
+        let canonical = super::canonical_order::<D, _, _>(&entries)
+            .into_iter()
+            .map(|i| crate::common::encoding::Encode::to_canonical_bytes(&entries[i]))
+            .collect::<Vec<_>>();
+
+        for _ in 0..10 {
+            use rand_core::RngCore;
+            let mut permuted = entries.clone();
+            for i in (1..permuted.len()).rev() {
+                let j = (rng.next_u32() as usize) % (i + 1);
+                permuted.swap(i, j);
+            }
+            assert_eq!(super::bundle_digest::<D, _, _>(&permuted), digest);
+
+            super::sort_canonically::<D, _, _>(&mut permuted);
+            let permuted = permuted
+                .iter()
+                .map(crate::common::encoding::Encode::to_canonical_bytes)
+                .collect::<Vec<_>>();
+            assert_eq!(permuted, canonical);
+        }
+        assert_eq!(super::bundle_digest::<D, _, _>(&sorted), digest);
+
+        // Digest commits to every entry
+        assert_ne!(super::bundle_digest::<D, _, _>(&entries[1..]), digest);
+        assert_ne!(
+            super::statement_digest::<D, _>(&entries[0].0),
+            super::statement_digest::<D, _>(&entries[1].0)
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Commitment and proof stored and sent together
+//!
+//! Non-interactive proofs consist of two prover's messages, commitment and proof,
+//! which are always kept together. [`ProofBundle`] holds both of them, and is
+//! encoded the same as the tuple `(commitment, proof)`.
+//!
+//! ## Migration of legacy proofs
+//! Earlier releases had callers persist proofs as tuples `(commitment, challenge,
+//! proof)`, where the challenge was derived with `non_interactive::challenge` of the
+//! proof. The challenge is redundant: verifier derives it from the transcript anyway.
+//! [`LegacyProof`] is obtained from such tuple and converted into [`ProofBundle`]
+//! once the stored challenge is [checked](LegacyProof::into_bundle) to match the one
+//! derived from the transcript, so the stored proofs don't need to be re-computed.
+//! Tuple with a challenge that doesn't match was not produced by the non-interactive
+//! prover, and is rejected.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::bundle::{LegacyProof, ProofBundle};
+//! use paillier_zk::paillier_encryption_in_range as p;
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     security: &p::SecurityParams,
+//! #     stored: (p::Commitment, p::Challenge, p::Proof),
+//! # ) -> Result<(), paillier_zk::InvalidProof> {
+//! let shared_state = sha2::Sha256::default();
+//!
+//! let legacy = LegacyProof::from(stored);
+//! let challenge =
+//!     p::non_interactive::challenge(shared_state.clone(), aux, data, &legacy.commitment, security);
+//! let bundle: ProofBundle<p::Commitment, p::Proof> = legacy.into_bundle(&challenge)?;
+//!
+//! p::non_interactive::verify(
+//!     shared_state,
+//!     aux,
+//!     data,
+//!     &bundle.commitment,
+//!     security,
+//!     &bundle.proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+
+use rug::Integer;
+
+use crate::common::encoding::{Decode, Encode};
+use crate::common::{InvalidProof, InvalidProofReason};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Commitment and proof of a non-interactive proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProofBundle<C, P> {
+    /// Prover's first message
+    pub commitment: C,
+    /// Prover's response
+    pub proof: P,
+}
+
+impl<C, P> ProofBundle<C, P> {
+    /// Returns commitment and proof
+    pub fn into_parts(self) -> (C, P) {
+        (self.commitment, self.proof)
+    }
+}
+
+impl<C, P> From<(C, P)> for ProofBundle<C, P> {
+    fn from((commitment, proof): (C, P)) -> Self {
+        Self { commitment, proof }
+    }
+}
+
+impl<C: Encode, P: Encode> Encode for ProofBundle<C, P> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.commitment.encode(out);
+        self.proof.encode(out);
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.commitment.encoded_len() + self.proof.encoded_len()
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        self.commitment.encode_chunks(sink);
+        self.proof.encode_chunks(sink);
+    }
+}
+
+impl<C: Decode, P: Decode> Decode for ProofBundle<C, P> {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        Some(Self {
+            commitment: C::decode(bytes)?,
+            proof: P::decode(bytes)?,
+        })
+    }
+}
+
+/// Proof persisted as a tuple `(commitment, challenge, proof)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LegacyProof<C, P> {
+    /// Prover's first message
+    pub commitment: C,
+    /// Challenge the proof was computed for
+    pub challenge: Integer,
+    /// Prover's response
+    pub proof: P,
+}
+
+impl<C, P> From<(C, Integer, P)> for LegacyProof<C, P> {
+    fn from((commitment, challenge, proof): (C, Integer, P)) -> Self {
+        Self {
+            commitment,
+            challenge,
+            proof,
+        }
+    }
+}
+
+impl<C, P> LegacyProof<C, P> {
+    /// Checks that the stored challenge is the one derived from the transcript, and
+    /// drops it
+    ///
+    /// `derived_challenge` must be obtained with `non_interactive::challenge` of the
+    /// proof from the stored commitment. Returns
+    /// [`ChallengeMismatch`](InvalidProofReason::ChallengeMismatch) error if it
+    /// differs from the stored one. The proof itself is not verified.
+    pub fn into_bundle(
+        self,
+        derived_challenge: &Integer,
+    ) -> Result<ProofBundle<C, P>, InvalidProof> {
+        if self.challenge != *derived_challenge {
+            return Err(InvalidProofReason::ChallengeMismatch.into());
+        }
+        Ok(ProofBundle {
+            commitment: self.commitment,
+            proof: self.proof,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "paillier-encryption-of-zero"))]
+mod test {
+    use rug::Integer;
+
+    use crate::common::encoding::{Decode, Encode};
+    use crate::common::InvalidProofReason;
+    use crate::paillier_encryption_of_zero as p;
+
+    use super::{LegacyProof, ProofBundle};
+
+    #[test]
+    fn legacy_migration() {
+        let mut rng = rand_dev::DevRng::new();
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (c, nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+        let data = p::Data { key, c: &c };
+        let pdata = p::PrivateData { nonce: &nonce };
+        let security = p::SecurityParams::new(Integer::from(u64::MAX));
+        let shared_state = sha2::Sha256::default();
+
+        let (commitment, proof) =
+            p::non_interactive::prove(shared_state.clone(), data, pdata, &security, &mut rng)
+                .unwrap();
+        let challenge =
+            p::non_interactive::challenge(shared_state.clone(), data, &commitment, &security);
+        let stored = (commitment, challenge.clone(), proof);
+
+        let bundle = LegacyProof::from(stored.clone())
+            .into_bundle(&challenge)
+            .unwrap();
+        p::non_interactive::verify(
+            shared_state.clone(),
+            data,
+            &bundle.commitment,
+            &security,
+            &bundle.proof,
+        )
+        .expect("proof failed");
+
+        // Bundle is encoded as the tuple of commitment and proof
+        let bytes = bundle.to_canonical_bytes();
+        assert_eq!(
+            bytes,
+            (&bundle.commitment, &bundle.proof).to_canonical_bytes()
+        );
+        let decoded = ProofBundle::<p::Commitment, p::Proof>::from_canonical_bytes(&bytes).unwrap();
+        assert_eq!(decoded.commitment.a, bundle.commitment.a);
+
+        // Challenge that wasn't derived from the transcript is rejected
+        let mut tampered = LegacyProof::from(stored);
+        tampered.challenge += 1;
+        let err = tampered.into_bundle(&challenge).unwrap_err();
+        assert_eq!(err.reason(), InvalidProofReason::ChallengeMismatch);
+    }
+}
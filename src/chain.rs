@@ -0,0 +1,187 @@
+//! Hash-chain linking of sequential proofs in a session
+//!
+//! [`ProofChain`] derives the shared state for each next proof in a session from the
+//! digest of all previous proofs. A verifier that mirrors the chain therefore accepts
+//! the proofs only in the order they were produced, and the chain [head](ProofChain::head)
+//! commits to the whole sequence, which is handy for audit logs of long-running sessions.
+//!
+//! Proofs are absorbed into the chain in their [canonical encoding](crate::encoding).
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{chain::ProofChain, paillier_encryption_in_range as p};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: [p::Data; 2],
+//! #     security: &p::SecurityParams,
+//! #     proofs: &[(p::Commitment, p::Proof); 2],
+//! # ) -> Result<(), p::InvalidProof> {
+//! let mut chain = ProofChain::<sha2::Sha256>::new(b"session id");
+//! for (data, (commitment, proof)) in data.into_iter().zip(proofs) {
+//!     let shared_state = chain.shared_state();
+//!     p::non_interactive::verify(shared_state, aux, data, commitment, security, proof)?;
+//!     chain.append(commitment, proof);
+//! }
+//! // Head of the chain commits to all the proofs in the session
+//! let head = chain.head();
+//! # Ok(()) }
+//! ```
+//!
+//! Prover maintains the same chain, obtaining shared state for `prove` from
+//! [`ProofChain::shared_state`] and appending each produced proof.
+
+use digest::{Digest, Output};
+
+use crate::common::encoding::Encode;
+
+const INIT_TAG: &[u8] = b"paillier_zk.chain.init";
+const LINK_TAG: &[u8] = b"paillier_zk.chain.link";
+const STATE_TAG: &[u8] = b"paillier_zk.chain.state";
+
+/// Chain of proofs produced within one session
+#[derive(Debug, Clone)]
+pub struct ProofChain<D: Digest> {
+    head: Output<D>,
+    len: u64,
+}
+
+impl<D: Digest> ProofChain<D> {
+    /// Starts a new chain
+    ///
+    /// `session_id` must be unique per session, otherwise chains of different sessions
+    /// can't be told apart
+    pub fn new(session_id: &[u8]) -> Self {
+        let head = D::new()
+            .chain_update(INIT_TAG)
+            .chain_update((session_id.len() as u64).to_be_bytes())
+            .chain_update(session_id)
+            .finalize();
+        Self { head, len: 0 }
+    }
+
+    /// Shared state to be used for the next proof in the chain
+    pub fn shared_state(&self) -> D {
+        D::new()
+            .chain_update(STATE_TAG)
+            .chain_update(self.len.to_be_bytes())
+            .chain_update(&self.head)
+    }
+
+    /// Appends the proof to the chain
+    ///
+    /// Must be called once the proof, obtained with [`shared_state`](Self::shared_state),
+    /// is produced or verified
+    pub fn append(&mut self, commitment: &impl Encode, proof: &impl Encode) {
+        let mut bytes = vec![];
+        commitment.encode(&mut bytes);
+        proof.encode(&mut bytes);
+        self.head = D::new()
+            .chain_update(LINK_TAG)
+            .chain_update(&self.head)
+            .chain_update(bytes)
+            .finalize();
+        self.len += 1;
+    }
+
+    /// Digest committing to the session id and all proofs in the chain
+    pub fn head(&self) -> &Output<D> {
+        &self.head
+    }
+
+    /// Amount of proofs in the chain
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Checks whether the chain has no proofs
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::IntegerExt;
+    use crate::paillier_encryption_in_range as p;
+
+    type ProofChain = super::ProofChain<sha2::Sha256>;
+
+    #[test]
+    fn chained_proofs() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams {
+            l: 1024,
+            epsilon: 256,
+            q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+
+        let statements = (0..2)
+            .map(|_| {
+                let plaintext =
+                    Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+                let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+                (plaintext, ciphertext, nonce)
+            })
+            .collect::<Vec<_>>();
+
+        let mut prover_chain = ProofChain::new(b"session");
+        let proofs = statements
+            .iter()
+            .map(|(plaintext, ciphertext, nonce)| {
+                let data = p::Data { key, ciphertext };
+                let pdata = p::PrivateData { plaintext, nonce };
+                let (commitment, proof) = p::non_interactive::prove(
+                    prover_chain.shared_state(),
+                    &aux,
+                    data,
+                    pdata,
+                    &security,
+                    &mut rng,
+                )
+                .unwrap();
+                prover_chain.append(&commitment, &proof);
+                (commitment, proof)
+            })
+            .collect::<Vec<_>>();
+
+        let verify = |chain: &mut ProofChain, i: usize| {
+            let data = p::Data {
+                key,
+                ciphertext: &statements[i].1,
+            };
+            let (commitment, proof) = &proofs[i];
+            p::non_interactive::verify(
+                chain.shared_state(),
+                &aux,
+                data,
+                commitment,
+                &security,
+                proof,
+            )?;
+            chain.append(commitment, proof);
+            Ok::<_, crate::InvalidProof>(())
+        };
+
+        let mut verifier_chain = ProofChain::new(b"session");
+        verify(&mut verifier_chain, 0).unwrap();
+        verify(&mut verifier_chain, 1).unwrap();
+        assert_eq!(verifier_chain.head(), prover_chain.head());
+        assert_eq!(verifier_chain.len(), 2);
+
+        // Proofs don't verify out of order
+        let mut verifier_chain = ProofChain::new(b"session");
+        verify(&mut verifier_chain, 1).unwrap_err();
+
+        // Proofs don't verify within another session
+        let mut verifier_chain = ProofChain::new(b"another session");
+        verify(&mut verifier_chain, 0).unwrap_err();
+    }
+}
@@ -1,12 +1,40 @@
+pub mod encoding;
+#[cfg(feature = "research")]
+pub mod extract;
+#[cfg(feature = "key-fingerprints")]
+pub mod fingerprint;
 pub mod rng;
+#[cfg(feature = "paillier-blum-modulus")]
 pub mod sqrt;
+#[cfg(feature = "statement-ids")]
+pub mod statement_id;
 
 use std::sync::Arc;
 
+use digest::{Digest, Output};
 use generic_ec::Scalar;
 use rug::{Complete, Integer};
 
+use crate::common::encoding::Encode;
+
 /// Auxiliary data known to both prover and verifier
+///
+/// Constructed with [`Aux::new`]. Aux intentionally doesn't implement `Default`:
+/// zero parameters are meaningless, and `..Default::default()` would silently leave
+/// some of them uninitialized.
+///
+/// ```rust,compile_fail
+/// let aux = paillier_zk::utils::ring_pedersen::Aux::default();
+/// ```
+///
+/// ## Sharing across threads
+/// Aux is `Send + Sync` and is never mutated by proofs, so a single instance (e.g.
+/// behind `Arc` or `&'static`) can be used by any number of provers and verifiers
+/// running concurrently. Precomputations are read-only as well: the
+/// multiexponentiation table is shared by `Arc` between clones of aux, and no lock
+/// is taken while proving or verifying with it. Statements and prepared statements
+/// borrow the encryption key as `dyn AnyEncryptionKey`, which isn't `Sync`, so they
+/// have to be built by each thread.
 #[cfg_attr(
     feature = "__internal_doctest",
     derive(serde::Serialize, serde::Deserialize)
@@ -28,8 +56,50 @@ pub struct Aux {
     pub crt: Option<fast_paillier::utils::CrtExp>,
 }
 
+// Sharing aux between threads is documented, see above
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+    #[allow(dead_code)]
+    fn assert_aux() {
+        assert_send_sync::<Aux>();
+    }
+};
+
 impl Aux {
+    /// Constructs aux data from ring-pedersen parameters without precomputations
+    ///
+    /// Precomputations can be attached with [`Aux::with_multiexp`] and
+    /// [`Aux::with_crt`].
+    pub fn new(s: Integer, t: Integer, rsa_modulo: Integer) -> Self {
+        Self {
+            s,
+            t,
+            rsa_modulo,
+            multiexp: None,
+            crt: None,
+        }
+    }
+
+    /// Attaches precomputed multiexponentiation table, see [`Aux::multiexp`]
+    pub fn with_multiexp(self, table: Arc<crate::multiexp::MultiexpTable>) -> Self {
+        Self {
+            multiexp: Some(table),
+            ..self
+        }
+    }
+
+    /// Attaches CRT parameters for exponentiations modulo `rsa_modulo`, see
+    /// [`Aux::crt`]
+    pub fn with_crt(self, crt: fast_paillier::utils::CrtExp) -> Self {
+        Self {
+            crt: Some(crt),
+            ..self
+        }
+    }
+
     /// Returns `s^x t^y mod rsa_modulo`
+    ///
+    /// `x` and `y` may be negative
     pub fn combine(&self, x: &Integer, y: &Integer) -> Result<Integer, BadExponent> {
         if let Some(table) = &self.multiexp {
             match table.prod_exp(x, y) {
@@ -51,7 +121,22 @@ impl Aux {
         self.rsa_modulo.combine(&self.s, x, &self.t, y)
     }
 
+    /// Returns a copy of aux data with precomputations (multiexponentiation
+    /// table and CRT) stripped off
+    ///
+    /// Exponentiations with returned aux are evaluated naively.
+    pub fn without_precomputations(&self) -> Self {
+        Self {
+            multiexp: None,
+            crt: None,
+            ..self.clone()
+        }
+    }
+
     /// Returns `x^e mod rsa_modulo`
+    ///
+    /// `e` may be negative, in which case `x` must be invertible modulo `rsa_modulo`,
+    /// otherwise an error is returned
     pub fn pow_mod(&self, x: &Integer, e: &Integer) -> Result<Integer, BadExponent> {
         match &self.crt {
             Some(crt) => {
@@ -64,20 +149,182 @@ impl Aux {
                 .into()),
         }
     }
+
+    /// Digest of ring-pedersen parameters `s`, `t`, `N^` in their
+    /// [canonical encoding](crate::encoding)
+    ///
+    /// Can be agreed on by counterparties once and absorbed into challenges instead of
+    /// the parameters themselves, see e.g.
+    /// [`SecurityParams::with_aux_digest`](crate::paillier_encryption_in_range::SecurityParams::with_aux_digest)
+    pub fn digest<D: Digest>(&self) -> Output<D> {
+        D::new()
+            .chain_update(AUX_DIGEST_TAG)
+            .chain_update(self.to_canonical_bytes())
+            .finalize()
+    }
+
+    /// Checks that parameters don't look like toy ones
+    ///
+    /// Catches obvious mistakes only, such as hardcoded small `s` and `t` copied
+    /// from examples, or a small `N^`. Passing the check doesn't mean the parameters
+    /// are secure: they still must be generated honestly and come with a proof that
+    /// `s` is in the group generated by `t`.
+    pub fn check_sanity(&self) -> Result<(), WeakAux> {
+        let n = &self.rsa_modulo;
+        if n.significant_bits() < MIN_AUX_MODULUS_BITS {
+            return Err(WeakAux::SmallModulus(n.significant_bits()));
+        }
+        if n.is_even() {
+            return Err(WeakAux::EvenModulus);
+        }
+        for x in [&self.s, &self.t] {
+            if x.cmp0().is_lt() || x >= n {
+                return Err(WeakAux::OutOfRange);
+            }
+            // Random element of `Z_{N^}` is that small with probability 2^-128
+            if x.significant_bits() + 128 < n.significant_bits() {
+                return Err(WeakAux::SmallParameter);
+            }
+        }
+        if self.s == self.t {
+            return Err(WeakAux::EqualParameters);
+        }
+        Ok(())
+    }
+}
+
+encoding::impl_encode! { Aux { s, t, rsa_modulo } }
+
+/// Decodes aux without precomputations
+impl encoding::Decode for Aux {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        Some(Self::new(
+            encoding::Decode::decode(bytes)?,
+            encoding::Decode::decode(bytes)?,
+            encoding::Decode::decode(bytes)?,
+        ))
+    }
+}
+
+/// Minimal bit size of `N^` accepted by [`Aux::check_sanity`]
+pub const MIN_AUX_MODULUS_BITS: u32 = 1024;
+
+/// Reason why [`Aux`] looks like toy parameters, see [`Aux::check_sanity`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[non_exhaustive]
+pub enum WeakAux {
+    /// `N^` is too small. Parameterized by its bit size
+    #[error("N^ has {0} bits, at least {MIN_AUX_MODULUS_BITS} bits expected")]
+    SmallModulus(u32),
+    /// `N^` is even
+    #[error("N^ is even")]
+    EvenModulus,
+    /// `s` or `t` is not in `[0; N^)`
+    #[error("s or t is out of range [0; N^)")]
+    OutOfRange,
+    /// `s` or `t` is too small to be sampled at random
+    #[error("s or t is too small")]
+    SmallParameter,
+    /// `s` equals to `t`
+    #[error("s equals to t")]
+    EqualParameters,
+}
+
+/// Checks aux at prove time
+///
+/// Weak aux is rejected if `reject_weak` is set or debug assertions are enabled.
+/// Otherwise, the check is skipped.
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+pub(crate) fn check_aux(aux: &Aux, reject_weak: bool) -> Result<(), crate::Error> {
+    if !reject_weak && !cfg!(debug_assertions) {
+        return Ok(());
+    }
+    aux.check_sanity()
+        .map_err(|err| crate::ErrorReason::WeakAux(err).into())
+}
+
+const AUX_DIGEST_TAG: &[u8] = b"paillier_zk.aux.digest";
+
+/// Ciphertext of a statement prepared for homomorphic multiplication by a scalar
+///
+/// Validates that ciphertext belongs to `Z*_{N^2}` and precomputes its inverse once,
+/// so that multiplying it by negative scalars (e.g. challenges) doesn't require
+/// validation and inversion each time.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+pub struct PreparedCiphertext {
+    ciphertext: Integer,
+    inverse: Integer,
+}
+
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+impl PreparedCiphertext {
+    /// Prepares the ciphertext encrypted under `key`
+    ///
+    /// Returns [`InvalidProofReason::PaillierOp`] if ciphertext is negative, and
+    /// [`InvalidProofReason::NotCoprime`] if it's not invertible modulo `N^2`
+    pub fn new(
+        key: &dyn fast_paillier::AnyEncryptionKey,
+        ciphertext: &Integer,
+    ) -> Result<Self, InvalidProofReason> {
+        if ciphertext.cmp0().is_lt() {
+            return Err(InvalidProofReason::PaillierOp);
+        }
+        let inverse = ciphertext
+            .invert_ref(key.nn())
+            .ok_or(InvalidProofReason::NotCoprime)?
+            .into();
+        Ok(Self {
+            ciphertext: ciphertext.clone(),
+            inverse,
+        })
+    }
+
+    /// Homomorphic multiplication of the ciphertext by `scalar`, same as
+    /// [`AnyEncryptionKey::omul`](fast_paillier::AnyEncryptionKey::omul)
+    ///
+    /// `key` must be the same as was used to prepare the ciphertext. Returns
+    /// [`InvalidProofReason::NotCoprime`] if `scalar` is not coprime with `N`.
+    pub fn omul(
+        &self,
+        key: &dyn fast_paillier::AnyEncryptionKey,
+        scalar: &Integer,
+    ) -> Result<Integer, InvalidProofReason> {
+        if !fast_paillier::utils::in_mult_group_abs(scalar, key.n()) {
+            return Err(InvalidProofReason::NotCoprime);
+        }
+        let base = if scalar.cmp0().is_lt() {
+            &self.inverse
+        } else {
+            &self.ciphertext
+        };
+        Ok(base
+            .pow_mod_ref(&scalar.as_abs(), key.nn())
+            .ok_or(InvalidProofReason::PaillierOp)?
+            .into())
+    }
 }
 
 /// Error indicating that proof is invalid
+///
+/// With `key-fingerprints` feature, the error carries a [fingerprint](InvalidProof::key_fingerprint)
+/// of the paillier key of the statement, which is also included in the error message.
+/// Similarly, with `statement-ids` feature, it carries [id](InvalidProof::statement_id)
+/// of the statement.
 #[derive(Debug, Clone, thiserror::Error)]
-#[error("invalid proof")]
-pub struct InvalidProof(
+pub struct InvalidProof {
     #[source]
-    #[from]
-    InvalidProofReason,
-);
+    reason: InvalidProofReason,
+    #[cfg(feature = "key-fingerprints")]
+    key: Option<fingerprint::KeyFingerprint>,
+    #[cfg(feature = "statement-ids")]
+    statement: Option<statement_id::StatementId>,
+}
 
 /// Reason for failure. If the proof failes, you should only be interested in a
 /// reason for debugging purposes
 #[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[non_exhaustive]
 pub enum InvalidProofReason {
     /// One equality doesn't hold. Parameterized by equality index
     #[error("equality check failed {0}")]
@@ -107,12 +354,135 @@ pub enum InvalidProofReason {
     /// Proof's x value in 4-th power does not equal commitment value
     #[error("incorrect 4th root")]
     IncorrectFourthRoot,
+    /// Challenge is zero, which makes the checks vacuous
+    #[error("challenge is zero")]
+    ZeroChallenge,
+    /// Commitment is a degenerate value, e.g. zero, one or identity point
+    #[error("degenerate commitment")]
+    DegenerateCommitment,
+    /// Amount of commitments doesn't match amount of verifiers, or the verifier is
+    /// not among them
+    #[error("commitments don't match verifiers")]
+    VerifiersMismatch,
+    /// Statement uses the same key where distinct keys are required
+    #[error("keys are not distinct")]
+    SameKeys,
+    /// Value supplied by prover is not coprime with the modulus, so it's not
+    /// invertible
+    #[error("value is not coprime with the modulus")]
+    NotCoprime,
+    /// Amount of values in the statement or in the proof doesn't match, or the
+    /// statement is empty
+    #[error("amount of values doesn't match the statement")]
+    LengthMismatch,
+    /// Authentication tag of a stored challenge doesn't match
+    #[error("challenge tag doesn't match")]
+    ChallengeTag,
+    /// Stored challenge doesn't match the one derived from the transcript
+    #[error("challenge doesn't match the transcript")]
+    ChallengeMismatch,
+    /// Proof has less iterations than verifier requires. Parameterized by amount of
+    /// iterations in the proof
+    #[error("proof has too few iterations: {0}")]
+    TooFewIterations(usize),
+    /// Non-residue `w` of Пmod doesn't have Jacobi symbol `-1`
+    #[error("w doesn't have Jacobi symbol -1")]
+    WrongJacobiSymbol,
+    /// Challenge is not in the challenge set of the protocol, e.g. `[-q; q]`
+    #[error("challenge is out of range")]
+    ChallengeOutOfRange,
+    /// Range check of a response doesn't hold. Names the witness the response is
+    /// computed from, and reports bit length of the response, never its value
+    #[error("range check failed {check}: response for {witness} has {bits} bits, bound is {bound_bits} bits")]
+    ResponseOutOfRange {
+        /// Index of the check, same as in [`RangeCheck`](Self::RangeCheck)
+        check: usize,
+        /// Name of the witness, e.g. `"x"`
+        witness: &'static str,
+        /// Bit length of the bound, e.g. `l_x + epsilon`
+        bound_bits: usize,
+        /// Bit length of the response
+        bits: u32,
+    },
 }
 
 impl InvalidProof {
-    #[cfg(test)]
     pub(crate) fn reason(&self) -> InvalidProofReason {
-        self.0
+        self.reason
+    }
+
+    /// Fingerprint of the paillier key of the statement that failed to verify
+    ///
+    /// `None` for statements that don't involve paillier keys
+    #[cfg(feature = "key-fingerprints")]
+    pub fn key_fingerprint(&self) -> Option<fingerprint::KeyFingerprint> {
+        self.key
+    }
+
+    /// Attributes the error to the key with modulus `n`, unless it's already
+    /// attributed. No-op without `key-fingerprints` feature.
+    #[allow(unused_variables)]
+    #[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+    pub(crate) fn with_key(self, n: &Integer) -> Self {
+        #[cfg(feature = "key-fingerprints")]
+        if self.key.is_none() {
+            return Self {
+                key: Some(fingerprint::KeyFingerprint::of(n)),
+                ..self
+            };
+        }
+        self
+    }
+
+    /// Id of the statement that failed to verify
+    ///
+    /// Attached by [`verify_with_policy`](crate::policy::verify_with_policy) and
+    /// [`InvalidProof::with_statement`]
+    #[cfg(feature = "statement-ids")]
+    pub fn statement_id(&self) -> Option<statement_id::StatementId> {
+        self.statement
+    }
+
+    /// Attributes the error to the `statement`, unless it's already attributed
+    #[cfg(feature = "statement-ids")]
+    pub fn with_statement<S>(self, statement: &S) -> Self
+    where
+        S: crate::policy::Statement + Encode,
+    {
+        if self.statement.is_some() {
+            return self;
+        }
+        Self {
+            statement: Some(statement_id::StatementId::of(statement)),
+            ..self
+        }
+    }
+}
+
+impl std::fmt::Display for InvalidProof {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("invalid proof")?;
+        #[cfg(feature = "key-fingerprints")]
+        if let Some(key) = &self.key {
+            write!(f, " (key {key})")?;
+        }
+        #[cfg(feature = "statement-ids")]
+        if let Some(statement) = &self.statement {
+            write!(f, " (statement {statement})")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<InvalidProofReason> for InvalidProof {
+    fn from(reason: InvalidProofReason) -> Self {
+        Self {
+            reason,
+            #[cfg(feature = "key-fingerprints")]
+            key: None,
+            #[cfg(feature = "statement-ids")]
+            statement: None,
+        }
     }
 }
 
@@ -124,7 +494,7 @@ impl From<BadExponent> for InvalidProof {
 
 impl From<PaillierError> for InvalidProof {
     fn from(_err: PaillierError) -> Self {
-        InvalidProof(InvalidProofReason::Encryption)
+        InvalidProofReason::Encryption.into()
     }
 }
 
@@ -133,14 +503,32 @@ impl From<PaillierError> for InvalidProof {
 #[error("paillier encryption failed")]
 pub struct PaillierError;
 
-pub trait IntegerExt: Sized {
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for rug::Integer {}
+}
+
+/// Extension methods for [`Integer`]
+///
+/// The trait is sealed and cannot be implemented outside of this crate, so new
+/// methods can be added without breaking changes
+pub trait IntegerExt: sealed::Sealed + Sized {
     /// Generate element in Zm*. Does so by trial.
     fn gen_invertible<R: rand_core::RngCore>(modulo: &Self, rng: &mut R) -> Self;
 
     /// Compute l^le * r^re modulo self
+    ///
+    /// Exponents may be negative, in which case the corresponding base must be
+    /// invertible modulo self, otherwise an error is returned
     fn combine(&self, l: &Self, le: &Self, r: &Self, re: &Self) -> Result<Self, BadExponent>;
 
     /// Embed BigInt into chosen scalar type
+    ///
+    /// This is reduction modulo the curve order `q`, applied to the absolute
+    /// value and then negated for negative numbers, so it is a ring
+    /// homomorphism `Z -> Z_q`: sums, products and signs are preserved. It is
+    /// not injective, so a verifier comparing curve points only learns the
+    /// integer modulo `q`; proofs must bound the integer by other means
     fn to_scalar<C: generic_ec::Curve>(&self) -> Scalar<C>;
 
     /// Returns prime order of curve C
@@ -216,7 +604,7 @@ impl IntegerExt for Integer {
 
 /// Error indicating that computation cannot be evaluated because of bad exponent
 ///
-/// Returned by [`BigNumberExt::powmod`] and other functions that do exponentiation internally
+/// Returned by [`Aux::pow_mod`] and other functions that do exponentiation internally
 #[derive(Clone, Copy, Debug, thiserror::Error)]
 #[error(transparent)]
 pub struct BadExponent(#[from] BadExponentReason);
@@ -239,7 +627,32 @@ enum BadExponentReason {
     },
 }
 
+/// Attributes elapsed time to the stage of proving or verification if `timings`
+/// feature is enabled, see [`crate::timings`]
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_macros))]
+macro_rules! lap {
+    ($($stage:tt)+) => {
+        #[cfg(feature = "timings")]
+        $crate::timings::lap($crate::timings::Stage::$($stage)+);
+    };
+}
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_imports))]
+pub(crate) use lap;
+
+/// Fails if commitment randomness was recently seen and `entropy-check` feature is
+/// enabled, see [`crate::entropy_check`]
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_macros))]
+macro_rules! observe_randomness {
+    ($($value:expr),+ $(,)?) => {
+        #[cfg(feature = "entropy-check")]
+        $crate::entropy_check::observe(&[$($value),+])?;
+    };
+}
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_imports))]
+pub(crate) use observe_randomness;
+
 /// Returns `Err(err)` if `assertion` is false
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
 pub fn fail_if<E>(err: E, assertion: bool) -> Result<(), E> {
     if assertion {
         Ok(())
@@ -249,6 +662,7 @@ pub fn fail_if<E>(err: E, assertion: bool) -> Result<(), E> {
 }
 
 /// Returns `Err(err)` if `lhs != rhs`
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
 pub fn fail_if_ne<T: PartialEq, E>(err: E, lhs: T, rhs: T) -> Result<(), E> {
     if lhs == rhs {
         Ok(())
@@ -257,6 +671,70 @@ pub fn fail_if_ne<T: PartialEq, E>(err: E, lhs: T, rhs: T) -> Result<(), E> {
     }
 }
 
+/// Returns [`InvalidProofReason::ZeroChallenge`] if `challenge` is zero
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+pub fn fail_if_zero_challenge(challenge: &Integer) -> Result<(), InvalidProofReason> {
+    fail_if(InvalidProofReason::ZeroChallenge, challenge.cmp0().is_ne())
+}
+
+/// Returns [`InvalidProofReason::ChallengeOutOfRange`] if `challenge` is not in
+/// `[-q; q]`
+///
+/// Challenges derived by the library are always in range. The check rejects
+/// challenges given to interactive verification from elsewhere, e.g. wider hash
+/// outputs, for which soundness of the protocol isn't analyzed.
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+pub fn fail_if_challenge_out_of_range(
+    challenge: &Integer,
+    q: &Integer,
+) -> Result<(), InvalidProofReason> {
+    fail_if(
+        InvalidProofReason::ChallengeOutOfRange,
+        challenge.is_in_pm(q),
+    )
+}
+
+/// Returns [`InvalidProofReason::DegenerateCommitment`] if any of `xs` is zero or one
+/// modulo `n`, and [`InvalidProofReason::NotCoprime`] if any of `xs` is not coprime
+/// with `n`
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+pub fn fail_if_degenerate<'a>(
+    n: &Integer,
+    xs: impl IntoIterator<Item = &'a Integer>,
+) -> Result<(), InvalidProofReason> {
+    for x in xs {
+        let x = x.modulo_ref(n).complete();
+        fail_if(
+            InvalidProofReason::DegenerateCommitment,
+            x.cmp0().is_ne() && x != *Integer::ONE,
+        )?;
+        fail_if(
+            InvalidProofReason::NotCoprime,
+            x.gcd_ref(n).complete() == *Integer::ONE,
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns [`InvalidProofReason::NotCoprime`] if any of `xs` is not coprime with `n`
+///
+/// Values supplied by prover must be checked before they're inverted or used as
+/// paillier nonces: otherwise a value sharing a factor with `n` fails in an obscure
+/// way deep in the arithmetic.
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+pub fn fail_if_not_coprime<'a>(
+    n: &Integer,
+    xs: impl IntoIterator<Item = &'a Integer>,
+) -> Result<(), InvalidProofReason> {
+    for x in xs {
+        fail_if(
+            InvalidProofReason::NotCoprime,
+            x.gcd_ref(n).complete() == *Integer::ONE,
+        )?;
+    }
+    Ok(())
+}
+
 /// A common logic shared across tests and doctests
 #[cfg(test)]
 pub mod test {
@@ -271,11 +749,18 @@ pub mod test {
     }
 
     pub fn aux<R: rand_core::RngCore>(rng: &mut R) -> super::Aux {
+        aux_with_secrets(rng).0
+    }
+
+    /// Same as [`aux`], but also returns secrets behind the parameters
+    pub fn aux_with_secrets<R: rand_core::RngCore>(
+        rng: &mut R,
+    ) -> (super::Aux, crate::aux_generation::AuxSecrets) {
         let p = generate_blum_prime(rng, 1024);
         let q = generate_blum_prime(rng, 1024);
         let n = (&p * &q).complete();
 
-        let (s, t) = {
+        let (s, t, lambda) = {
             let phi_n = (p.clone() - 1u8) * (q.clone() - 1u8);
             let r = Integer::gen_invertible(&n, rng);
             let lambda = phi_n.random_below(&mut fast_paillier::utils::external_rand(rng));
@@ -283,15 +768,66 @@ pub mod test {
             let t = r.square().modulo(&n);
             let s = t.pow_mod_ref(&lambda, &n).unwrap().into();
 
-            (s, t)
+            (s, t, lambda)
         };
 
-        super::Aux {
-            s,
-            t,
-            rsa_modulo: n,
-            multiexp: None,
-            crt: None,
+        let secrets = crate::aux_generation::AuxSecrets { p, q, lambda };
+        (super::Aux::new(s, t, n), secrets)
+    }
+
+    /// Пenc statement along with its witness: plaintext encrypted under a random key
+    #[cfg(feature = "paillier-encryption-in-range")]
+    pub struct EncStatement {
+        pub key: fast_paillier::EncryptionKey,
+        pub plaintext: Integer,
+        pub nonce: Integer,
+        pub ciphertext: Integer,
+    }
+
+    #[cfg(feature = "paillier-encryption-in-range")]
+    impl EncStatement {
+        /// Encrypts random plaintext in `[-2^l; 2^l]`
+        pub fn random<R: rand_core::RngCore + rand_core::CryptoRng>(rng: &mut R, l: usize) -> Self {
+            let key = random_key(rng).unwrap().encryption_key().clone();
+            let plaintext = Integer::from_rng_pm(&(Integer::ONE << l).complete(), rng);
+            Self::encrypt(rng, key, plaintext)
+        }
+
+        /// Encrypts given `plaintext`
+        pub fn new<R: rand_core::RngCore + rand_core::CryptoRng>(
+            rng: &mut R,
+            plaintext: Integer,
+        ) -> Self {
+            let key = random_key(rng).unwrap().encryption_key().clone();
+            Self::encrypt(rng, key, plaintext)
+        }
+
+        fn encrypt<R: rand_core::RngCore + rand_core::CryptoRng>(
+            rng: &mut R,
+            key: fast_paillier::EncryptionKey,
+            plaintext: Integer,
+        ) -> Self {
+            let (ciphertext, nonce) = key.encrypt_with_random(rng, &plaintext).unwrap();
+            Self {
+                key,
+                plaintext,
+                nonce,
+                ciphertext,
+            }
+        }
+
+        pub fn data(&self) -> crate::paillier_encryption_in_range::Data<'_> {
+            crate::paillier_encryption_in_range::Data {
+                key: &self.key,
+                ciphertext: &self.ciphertext,
+            }
+        }
+
+        pub fn pdata(&self) -> crate::paillier_encryption_in_range::PrivateData<'_> {
+            crate::paillier_encryption_in_range::PrivateData {
+                plaintext: &self.plaintext,
+                nonce: &self.nonce,
+            }
         }
     }
 
@@ -315,7 +851,7 @@ pub mod test {
 
 #[cfg(test)]
 mod _test {
-    use rug::Integer;
+    use rug::{Complete, Integer};
 
     use super::IntegerExt;
 
@@ -334,9 +870,15 @@ mod _test {
         let curve_order = Integer::curve_order::<E>();
         assert_eq!(curve_order.to_scalar(), generic_ec::Scalar::<E>::zero());
         assert_eq!(
-            (curve_order - 1u8).to_scalar(),
+            (&curve_order - 1u8).complete().to_scalar(),
             -generic_ec::Scalar::<E>::one()
         );
+
+        // Reduction is consistent with sign and with shifts by the curve order
+        let five = generic_ec::Scalar::<E>::from(5u8);
+        assert_eq!((&curve_order + 5u8).complete().to_scalar(), five);
+        assert_eq!((-(&curve_order + 5u8).complete()).to_scalar(), -five);
+        assert_eq!((5u8 - (&curve_order * 3u8).complete()).to_scalar(), five);
     }
 
     #[test]
@@ -360,6 +902,71 @@ mod _test {
         assert_eq!(Integer::from(3).signed_modulo(&n), -1);
     }
 
+    #[test]
+    fn aux_sanity() {
+        use super::WeakAux;
+
+        let mut rng = rand_dev::DevRng::new();
+        let aux = super::test::aux(&mut rng);
+        aux.check_sanity().unwrap();
+
+        let weak = |f: fn(&mut super::Aux)| {
+            let mut aux = aux.clone();
+            f(&mut aux);
+            aux.check_sanity().unwrap_err()
+        };
+        assert_eq!(
+            weak(|aux| {
+                aux.s = Integer::from(123);
+                aux.t = Integer::from(321);
+            }),
+            WeakAux::SmallParameter
+        );
+        assert_eq!(
+            weak(|aux| aux.rsa_modulo = Integer::from(187)),
+            WeakAux::SmallModulus(8)
+        );
+        assert_eq!(weak(|aux| aux.rsa_modulo += 1), WeakAux::EvenModulus);
+        assert_eq!(weak(|aux| aux.s += &aux.rsa_modulo), WeakAux::OutOfRange);
+        assert_eq!(weak(|aux| aux.s = aux.t.clone()), WeakAux::EqualParameters);
+    }
+
+    #[test]
+    fn pow_mod_negative_exponent() {
+        let mut rng = rand_dev::DevRng::new();
+        let p = super::test::generate_blum_prime(&mut rng, 512);
+        let q = super::test::generate_blum_prime(&mut rng, 512);
+        let mut aux = super::test::aux(&mut rng);
+        aux.rsa_modulo = (&p * &q).complete();
+        let aux_crt = aux
+            .clone()
+            .with_crt(fast_paillier::utils::CrtExp::build_n(&p, &q).unwrap());
+
+        let x = Integer::gen_invertible(&aux.rsa_modulo, &mut rng);
+        for _ in 0..20 {
+            let e = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+            let neg_e = (-&e).complete();
+
+            let x_to_e = aux.pow_mod(&x, &e).unwrap();
+            let x_to_neg_e = aux.pow_mod(&x, &neg_e).unwrap();
+            assert_eq!((&x_to_e * &x_to_neg_e).complete() % &aux.rsa_modulo, 1);
+            assert_eq!(aux_crt.pow_mod(&x, &e).unwrap(), x_to_e);
+            assert_eq!(aux_crt.pow_mod(&x, &neg_e).unwrap(), x_to_neg_e);
+
+            let combined = aux.rsa_modulo.combine(&x, &neg_e, &x, &e).unwrap();
+            assert_eq!(combined, 1);
+        }
+
+        // Non-invertible base cannot be raised to negative power
+        let minus_one = Integer::from(-1);
+        assert!(aux.pow_mod(&p, &minus_one).is_err());
+        assert!(aux_crt.pow_mod(&p, &minus_one).is_err());
+        assert!(aux
+            .rsa_modulo
+            .combine(&p, &minus_one, &x, Integer::ONE)
+            .is_err());
+    }
+
     #[test]
     fn multiexp() {
         let mut rng = rand_dev::DevRng::new();
@@ -408,4 +1015,46 @@ mod _test {
             assert_eq!(actual, expected);
         }
     }
+
+    #[test]
+    fn non_invertible_values() {
+        use super::{InvalidProofReason, PreparedCiphertext};
+
+        let mut rng = rand_dev::DevRng::new();
+        let private_key = super::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let p = private_key.p();
+
+        // Ciphertext sharing a factor with N^2 can't be prepared
+        let ciphertext = (p * key.n()).complete();
+        assert_eq!(
+            PreparedCiphertext::new(key, &ciphertext).err(),
+            Some(InvalidProofReason::NotCoprime)
+        );
+
+        // Scalar sharing a factor with N can't be used for multiplication, regardless
+        // of its sign
+        let (ciphertext, _) = key
+            .encrypt_with_random(&mut rng, &Integer::from(5))
+            .unwrap();
+        let prepared = PreparedCiphertext::new(key, &ciphertext).unwrap();
+        assert_eq!(
+            prepared.omul(key, &(p * 3u32).complete()).err(),
+            Some(InvalidProofReason::NotCoprime)
+        );
+        assert_eq!(
+            prepared.omul(key, &(-p).complete()).err(),
+            Some(InvalidProofReason::NotCoprime)
+        );
+
+        assert_eq!(
+            super::fail_if_not_coprime(key.n(), [&ciphertext, &(p * 2u32).complete()]),
+            Err(InvalidProofReason::NotCoprime)
+        );
+        assert_eq!(
+            super::fail_if_degenerate(key.nn(), [&ciphertext, p]),
+            Err(InvalidProofReason::NotCoprime)
+        );
+        super::fail_if_degenerate(key.nn(), [&ciphertext]).unwrap();
+    }
 }
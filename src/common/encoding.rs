@@ -0,0 +1,675 @@
+//! Canonical binary encoding of proofs
+//!
+//! Values are encoded as follows:
+//! * Integer: one byte of sign (`0` for non-negative, `1` for negative), followed by
+//!   the length of absolute value in bytes as `u32` in big-endian, followed by
+//!   absolute value in big-endian without leading zeroes
+//! * Point: length of its compressed form as `u32` in big-endian, followed by
+//!   compressed point, see [`PointEncoding`](crate::transcript::PointEncoding)
+//! * Scalar: big-endian bytes of fixed size, reduced modulo the curve order
+//! * Boolean: a single byte, `0` or `1`
+//! * Paillier encryption key: its modulus `N` as integer
+//! * Sequence: amount of elements as `u32` in big-endian, followed by the encoded
+//!   elements
+//! * Structures: concatenation of encoded fields in the order of declaration
+//!
+//! Encoded values are decoded back with [`Decode`], which accepts canonical encodings
+//! only.
+//!
+//! Encoding of each proof is described by a machine-readable [`Schema`], obtained e.g.
+//! from [`Commitment::schema`](crate::paillier_encryption_in_range::Commitment::schema)
+//! and [`Proof::schema`](crate::paillier_encryption_in_range::Proof::schema). Schema
+//! lists fields in the order they are encoded, along with upper bounds on their size
+//! for given security parameters, so implementations in other languages and fuzzers
+//! can be generated from it.
+
+use fast_paillier::AnyEncryptionKey;
+use generic_ec::{Curve, Point, Scalar};
+use rug::Integer;
+
+/// Value that has canonical binary encoding
+pub trait Encode {
+    /// Appends encoded value to `out`
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Returns encoded value
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.encode(&mut out);
+        out
+    }
+
+    /// Size of encoded value in bytes
+    ///
+    /// Sequences and structures compute it without encoding the value
+    fn encoded_len(&self) -> usize {
+        self.to_canonical_bytes().len()
+    }
+
+    /// Passes encoded value to `sink` in chunks, concatenation of which is the
+    /// encoded value
+    ///
+    /// Sequences and structures pass their elements one by one, so encoding large
+    /// values, e.g. to absorb them into a [transcript](crate::transcript), never
+    /// requires a buffer larger than the largest element.
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        sink(&self.to_canonical_bytes())
+    }
+}
+
+/// Value that can be decoded from its canonical encoding
+pub trait Decode: Sized {
+    /// Decodes value from the beginning of `bytes`, advancing `bytes` past it
+    ///
+    /// Returns `None` if `bytes` don't start with a canonical encoding of the value
+    fn decode(bytes: &mut &[u8]) -> Option<Self>;
+
+    /// Decodes value that takes the whole `bytes`
+    fn from_canonical_bytes(mut bytes: &[u8]) -> Option<Self> {
+        let value = Self::decode(&mut bytes)?;
+        bytes.is_empty().then_some(value)
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (**self).encode(out)
+    }
+
+    fn encoded_len(&self) -> usize {
+        (**self).encoded_len()
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        (**self).encode_chunks(sink)
+    }
+}
+
+impl Encode for Integer {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(self.cmp0().is_lt()));
+        let bytes = self.to_digits::<u8>(rug::integer::Order::Msf);
+        encode_len(bytes.len(), out);
+        out.extend_from_slice(&bytes);
+    }
+
+    fn encoded_len(&self) -> usize {
+        1 + 4 + (self.significant_bits() as usize).div_ceil(8)
+    }
+}
+
+impl<C: Curve> Encode for Point<C> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let bytes = crate::transcript::PointEncoding::CURRENT.encode(self);
+        encode_len(bytes.len(), out);
+        out.extend_from_slice(&bytes);
+    }
+}
+
+impl<C: Curve> Encode for Scalar<C> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_be_bytes())
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self))
+    }
+}
+
+impl Encode for dyn AnyEncryptionKey + '_ {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.n().encode(out)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.n().encoded_len()
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+        self.1.encode(out);
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.0.encoded_len() + self.1.encoded_len()
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        self.0.encode_chunks(sink);
+        self.1.encode_chunks(sink);
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        encode_len(self.len(), out);
+        for x in self {
+            x.encode(out)
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        sequence_len(self)
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        encode_sequence_chunks(self, sink)
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_slice().encode(out)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.as_slice().encoded_len()
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        self.as_slice().encode_chunks(sink)
+    }
+}
+
+impl Decode for Integer {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let (&sign, rest) = bytes.split_first()?;
+        let (value, rest) = split_len_prefixed(rest)?;
+        match (sign, value.first()) {
+            (0, None) => (),
+            // Leading zeroes and negative zero are not canonical
+            (_, None | Some(0)) => return None,
+            (0 | 1, Some(_)) => (),
+            _ => return None,
+        }
+        let value = Integer::from_digits(value, rug::integer::Order::Msf);
+        *bytes = rest;
+        Some(if sign == 1 { -value } else { value })
+    }
+}
+
+impl<C: Curve> Decode for Point<C> {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let (value, rest) = split_len_prefixed(bytes)?;
+        let point = Point::from_bytes(value).ok()?;
+        // Other SEC1 forms decode to the same point, but aren't canonical
+        if crate::transcript::PointEncoding::CURRENT.encode(&point) != value {
+            return None;
+        }
+        *bytes = rest;
+        Some(point)
+    }
+}
+
+impl<C: Curve> Decode for Scalar<C> {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let size = scalar_size::<C>();
+        if bytes.len() < size {
+            return None;
+        }
+        let (value, rest) = bytes.split_at(size);
+        let scalar = Scalar::from_be_bytes(value).ok()?;
+        *bytes = rest;
+        Some(scalar)
+    }
+}
+
+impl Decode for bool {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let (&value, rest) = bytes.split_first()?;
+        let value = match value {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        *bytes = rest;
+        Some(value)
+    }
+}
+
+impl<A: Decode, B: Decode> Decode for (A, B) {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let mut rest = *bytes;
+        let a = A::decode(&mut rest)?;
+        let b = B::decode(&mut rest)?;
+        *bytes = rest;
+        Some((a, b))
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let (len, mut rest) = split_len(bytes)?;
+        // Every element takes at least one byte, which bounds the allocation
+        if len > rest.len() {
+            return None;
+        }
+        let mut values = Vec::with_capacity(len);
+        for _ in 0..len {
+            values.push(T::decode(&mut rest)?);
+        }
+        *bytes = rest;
+        Some(values)
+    }
+}
+
+/// Schema of canonical encoding of a structure
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Schema {
+    /// Fields in the order they are encoded
+    pub fields: Vec<Field>,
+}
+
+/// Field of a [`Schema`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Field {
+    /// Name of the field, same as in Rust structure
+    pub name: &'static str,
+    /// Type of the field
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub ty: FieldType,
+}
+
+/// Type of a [`Field`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+#[non_exhaustive]
+pub enum FieldType {
+    /// Integer which absolute value has at most `max_bits` bits
+    Integer { max_bits: usize },
+    /// Point on the `curve` which compressed form takes `size` bytes
+    Point { curve: &'static str, size: usize },
+    /// Scalar of the `curve` which takes exactly `size` bytes
+    Scalar { curve: &'static str, size: usize },
+    /// Boolean
+    Bool,
+    /// Sequence of exactly `len` structures
+    Sequence { len: usize, element: Schema },
+}
+
+impl Schema {
+    /// Upper bound on size of encoded structure
+    pub fn max_size(&self) -> usize {
+        self.fields.iter().map(|f| f.ty.max_size()).sum()
+    }
+
+    /// Checks whether `bytes` is an encoding that conforms to the schema
+    ///
+    /// Checks the structure and that the values fit into their bounds, but not the
+    /// values themselves, e.g. that a point is on the curve.
+    pub fn conforms(&self, bytes: &[u8]) -> bool {
+        matches!(self.consume(bytes), Some(rest) if rest.is_empty())
+    }
+
+    fn consume<'b>(&self, mut bytes: &'b [u8]) -> Option<&'b [u8]> {
+        for field in &self.fields {
+            bytes = field.ty.consume(bytes)?;
+        }
+        Some(bytes)
+    }
+}
+
+// Builders of the proofs' schemas
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+impl Schema {
+    pub(crate) fn integer(self, name: &'static str, max_bits: usize) -> Self {
+        self.field(name, FieldType::Integer { max_bits })
+    }
+
+    pub(crate) fn point<C: Curve>(self, name: &'static str) -> Self {
+        let size = point_size::<C>() - LEN_SIZE;
+        self.field(
+            name,
+            FieldType::Point {
+                curve: C::CURVE_NAME,
+                size,
+            },
+        )
+    }
+
+    pub(crate) fn scalar<C: Curve>(self, name: &'static str) -> Self {
+        self.field(
+            name,
+            FieldType::Scalar {
+                curve: C::CURVE_NAME,
+                size: scalar_size::<C>(),
+            },
+        )
+    }
+
+    pub(crate) fn bool(self, name: &'static str) -> Self {
+        self.field(name, FieldType::Bool)
+    }
+
+    pub(crate) fn sequence(self, name: &'static str, len: usize, element: Schema) -> Self {
+        self.field(name, FieldType::Sequence { len, element })
+    }
+
+    fn field(mut self, name: &'static str, ty: FieldType) -> Self {
+        self.fields.push(Field { name, ty });
+        self
+    }
+}
+
+impl FieldType {
+    /// Upper bound on size of encoded field
+    pub fn max_size(&self) -> usize {
+        match self {
+            Self::Integer { max_bits } => integer_size(*max_bits),
+            Self::Point { size, .. } => LEN_SIZE + size,
+            Self::Scalar { size, .. } => *size,
+            Self::Bool => BOOL_SIZE,
+            Self::Sequence { len, element } => LEN_SIZE + len * element.max_size(),
+        }
+    }
+
+    fn consume<'b>(&self, bytes: &'b [u8]) -> Option<&'b [u8]> {
+        match self {
+            Self::Integer { max_bits } => {
+                let (&sign, bytes) = bytes.split_first()?;
+                if sign > 1 {
+                    return None;
+                }
+                let (value, bytes) = split_len_prefixed(bytes)?;
+                let significant_bits = match value.first() {
+                    None if sign == 0 => 0,
+                    // Leading zeroes and negative zero are not canonical
+                    None | Some(0) => return None,
+                    Some(first) => 8 * value.len() - first.leading_zeros() as usize,
+                };
+                (significant_bits <= *max_bits).then_some(bytes)
+            }
+            Self::Point { size, .. } => {
+                let (value, bytes) = split_len_prefixed(bytes)?;
+                // Point at infinity may be encoded shorter
+                (value.len() <= *size).then_some(bytes)
+            }
+            Self::Scalar { size, .. } => bytes.get(*size..),
+            Self::Bool => match bytes.split_first()? {
+                (0 | 1, bytes) => Some(bytes),
+                _ => None,
+            },
+            Self::Sequence { len, element } => {
+                let (amount, mut bytes) = split_len(bytes)?;
+                if amount != *len {
+                    return None;
+                }
+                for _ in 0..amount {
+                    bytes = element.consume(bytes)?;
+                }
+                Some(bytes)
+            }
+        }
+    }
+}
+
+fn split_len(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    if bytes.len() < LEN_SIZE {
+        return None;
+    }
+    let (len, bytes) = bytes.split_at(LEN_SIZE);
+    let len = u32::from_be_bytes(len.try_into().ok()?);
+    Some((usize::try_from(len).ok()?, bytes))
+}
+
+pub(crate) fn split_len_prefixed(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (len, bytes) = split_len(bytes)?;
+    (len <= bytes.len()).then(|| bytes.split_at(len))
+}
+
+/// Implements [`Encode`] for a structure by encoding its fields in the listed order
+macro_rules! impl_encode {
+    ($ty:ident<$lt:lifetime $(, $generic:ident: $bound:path)*> { $($field:ident),+ $(,)? }) => {
+        impl<$lt $(, $generic: $bound)*> $crate::common::encoding::Encode for $ty<$lt $(, $generic)*> {
+            fn encode(&self, out: &mut Vec<u8>) {
+                $($crate::common::encoding::Encode::encode(&self.$field, out);)+
+            }
+
+            fn encoded_len(&self) -> usize {
+                0 $(+ $crate::common::encoding::Encode::encoded_len(&self.$field))+
+            }
+
+            fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+                $($crate::common::encoding::Encode::encode_chunks(&self.$field, sink);)+
+            }
+        }
+    };
+    ($ty:ident $(<$($generic:ident: $bound:path),+>)? { $($field:ident),+ $(,)? }) => {
+        impl$(<$($generic: $bound),+>)? $crate::common::encoding::Encode for $ty$(<$($generic),+>)? {
+            fn encode(&self, out: &mut Vec<u8>) {
+                $($crate::common::encoding::Encode::encode(&self.$field, out);)+
+            }
+
+            fn encoded_len(&self) -> usize {
+                0 $(+ $crate::common::encoding::Encode::encoded_len(&self.$field))+
+            }
+
+            fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+                $($crate::common::encoding::Encode::encode_chunks(&self.$field, sink);)+
+            }
+        }
+    };
+}
+pub(crate) use impl_encode;
+
+/// Implements [`Decode`] for a structure by decoding its fields in the listed order
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_macros))]
+macro_rules! impl_decode {
+    ($ty:ident $(<$($generic:ident: $bound:path),+>)? { $($field:ident),+ $(,)? }) => {
+        impl$(<$($generic: $bound),+>)? $crate::common::encoding::Decode for $ty$(<$($generic),+>)? {
+            fn decode(bytes: &mut &[u8]) -> Option<Self> {
+                Some(Self {
+                    $($field: $crate::common::encoding::Decode::decode(bytes)?,)+
+                })
+            }
+        }
+    };
+}
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_imports))]
+pub(crate) use impl_decode;
+
+/// Size of encoded integer which absolute value has at most `bits` bits
+pub(crate) fn integer_size(bits: usize) -> usize {
+    1 + LEN_SIZE + bits.div_ceil(8)
+}
+
+/// Size of encoded point
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+pub(crate) fn point_size<C: Curve>() -> usize {
+    use generic_ec::core::ByteArray;
+    LEN_SIZE + C::CompressedPointArray::zeroes().as_ref().len()
+}
+
+/// Size of encoded scalar
+pub(crate) fn scalar_size<C: Curve>() -> usize {
+    Scalar::<C>::zero().to_be_bytes().len()
+}
+
+/// Size of encoded boolean
+pub(crate) const BOOL_SIZE: usize = 1;
+
+/// Size of encoded length prefix
+pub(crate) const LEN_SIZE: usize = 4;
+
+pub(crate) fn encode_len(len: usize, out: &mut Vec<u8>) {
+    // Lengths of encoded values never exceed `u32::MAX`, unless the values are
+    // unreasonably large
+    let len = u32::try_from(len).unwrap_or(u32::MAX);
+    out.extend_from_slice(&len.to_be_bytes());
+}
+
+/// [`Encode::encoded_len`] of a sequence given by iterator over its elements
+pub(crate) fn sequence_len<T: Encode>(items: impl IntoIterator<Item = T>) -> usize {
+    items.into_iter().fold(4, |len, x| len + x.encoded_len())
+}
+
+/// [`Encode::encode_chunks`] of a sequence given by iterator over its elements
+pub(crate) fn encode_sequence_chunks<T: Encode>(
+    items: impl IntoIterator<Item = T, IntoIter = impl ExactSizeIterator<Item = T>>,
+    sink: &mut dyn FnMut(&[u8]),
+) {
+    let items = items.into_iter();
+    let mut len = Vec::with_capacity(4);
+    encode_len(items.len(), &mut len);
+    sink(&len);
+    for x in items {
+        x.encode_chunks(sink)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::Integer;
+
+    use super::Encode;
+
+    #[test]
+    fn integer_encoding() {
+        assert_eq!(Integer::ZERO.to_canonical_bytes(), [0, 0, 0, 0, 0]);
+        assert_eq!(
+            Integer::from(0x1234).to_canonical_bytes(),
+            [0, 0, 0, 0, 2, 0x12, 0x34]
+        );
+        assert_eq!(
+            Integer::from(-0x1234).to_canonical_bytes(),
+            [1, 0, 0, 0, 2, 0x12, 0x34]
+        );
+    }
+
+    #[test]
+    fn scalar_encoding() {
+        use generic_ec::{curves::Secp256k1, Scalar};
+
+        let bytes = Scalar::<Secp256k1>::from(0x1234).to_canonical_bytes();
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(bytes[30..], [0x12, 0x34]);
+        let schema = super::Schema::default().scalar::<Secp256k1>("x");
+        assert_eq!(schema.max_size(), 32);
+        assert!(schema.conforms(&bytes));
+        assert!(!schema.conforms(&bytes[1..]));
+    }
+
+    #[test]
+    fn schema_conformance() {
+        use super::Schema;
+
+        let point = Schema::default().integer("x", 16).bool("b");
+        let schema = Schema::default()
+            .integer("a", 16)
+            .sequence("points", 2, point.clone());
+        let encode = |a: i64, points: &[(i64, bool)]| {
+            let mut out = Integer::from(a).to_canonical_bytes();
+            super::encode_len(points.len(), &mut out);
+            for (x, b) in points {
+                Integer::from(*x).encode(&mut out);
+                b.encode(&mut out);
+            }
+            out
+        };
+
+        assert!(schema.conforms(&encode(-0xffff, &[(0, true), (0x1234, false)])));
+        assert_eq!(schema.max_size(), (1 + 4 + 2) + 4 + 2 * (1 + 4 + 2 + 1));
+        // Integer exceeds the bound
+        assert!(!schema.conforms(&encode(0x10000, &[(0, true), (0, true)])));
+        // Wrong amount of elements
+        assert!(!schema.conforms(&encode(1, &[(0, true)])));
+        // Trailing bytes
+        let mut bytes = encode(1, &[(0, true), (0, true)]);
+        bytes.push(0);
+        assert!(!schema.conforms(&bytes));
+        // Truncated bytes
+        assert!(!schema.conforms(&bytes[..bytes.len() - 2]));
+        // Leading zeroes are not canonical
+        assert!(!point.conforms(&[0, 0, 0, 0, 2, 0, 1, 0]));
+        // Boolean other than 0 or 1
+        assert!(!point.conforms(&[0, 0, 0, 0, 1, 1, 2]));
+    }
+
+    #[test]
+    fn sequence_encoding() {
+        let seq = [Integer::from(1), Integer::from(-2)];
+        assert_eq!(
+            seq.to_canonical_bytes(),
+            [0, 0, 0, 2, 0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn chunked_encoding() {
+        use generic_ec::{curves::Secp256k1, Point, Scalar};
+
+        fn assert_consistent(value: &(impl Encode + ?Sized)) {
+            let bytes = value.to_canonical_bytes();
+            assert_eq!(value.encoded_len(), bytes.len());
+            let mut chunks = vec![];
+            value.encode_chunks(&mut |chunk| chunks.extend_from_slice(chunk));
+            assert_eq!(chunks, bytes);
+        }
+
+        for x in [0, 1, -1, 0xff, 0x100, -0x1234] {
+            assert_consistent(&Integer::from(x));
+        }
+        let seq = vec![Integer::from(1), Integer::from(-0x1234), Integer::ZERO];
+        assert_consistent(&seq);
+        assert_consistent(&Vec::<Integer>::new());
+        assert_consistent(&(Integer::from(7), seq.as_slice()));
+        let point = Point::<Secp256k1>::generator() * Scalar::from(42);
+        assert_consistent(&[(point, Scalar::<Secp256k1>::from(42))][..]);
+        assert_consistent(&vec![vec![true], vec![false, true]]);
+    }
+
+    #[test]
+    fn decoding() {
+        use generic_ec::{curves::Secp256k1, Point, Scalar};
+
+        use super::Decode;
+
+        for x in [0, 1, -1, 0x1234, -0x1234] {
+            let x = Integer::from(x);
+            assert_eq!(
+                Integer::from_canonical_bytes(&x.to_canonical_bytes()),
+                Some(x)
+            );
+        }
+        let seq = vec![Integer::from(1), Integer::from(-2)];
+        assert_eq!(
+            Vec::<Integer>::from_canonical_bytes(&seq[..].to_canonical_bytes()),
+            Some(seq)
+        );
+        let point = Point::<Secp256k1>::generator() * Scalar::from(42);
+        assert_eq!(
+            Point::from_canonical_bytes(&point.to_canonical_bytes()),
+            Some(point)
+        );
+        let scalar = Scalar::<Secp256k1>::from(42);
+        assert_eq!(
+            Scalar::from_canonical_bytes(&scalar.to_canonical_bytes()),
+            Some(scalar)
+        );
+        assert_eq!(bool::from_canonical_bytes(&[1]), Some(true));
+
+        // Non-canonical encodings are rejected
+        assert_eq!(Integer::from_canonical_bytes(&[0, 0, 0, 0, 2, 0, 1]), None);
+        assert_eq!(Integer::from_canonical_bytes(&[1, 0, 0, 0, 0]), None);
+        assert_eq!(Integer::from_canonical_bytes(&[2, 0, 0, 0, 1, 1]), None);
+        assert_eq!(bool::from_canonical_bytes(&[2]), None);
+        let uncompressed = point.to_bytes(false);
+        let mut encoded = (uncompressed.len() as u32).to_be_bytes().to_vec();
+        encoded.extend_from_slice(&uncompressed);
+        assert_eq!(Point::<Secp256k1>::from_canonical_bytes(&encoded), None);
+        // Trailing and missing bytes
+        assert_eq!(Integer::from_canonical_bytes(&[0, 0, 0, 0, 0, 0]), None);
+        assert_eq!(Integer::from_canonical_bytes(&[0, 0, 0, 0, 2, 1]), None);
+        assert_eq!(Vec::<bool>::from_canonical_bytes(&[0, 0, 0, 2, 1]), None);
+    }
+}
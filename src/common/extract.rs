@@ -0,0 +1,104 @@
+//! Helpers for special-soundness extractors
+
+use rug::{Complete, Integer};
+
+/// Error indicating that witness cannot be extracted from given transcripts
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("witness extraction failed")]
+pub struct ExtractionError(#[from] ExtractionErrorReason);
+
+/// Reason for extraction failure
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[non_exhaustive]
+pub enum ExtractionErrorReason {
+    /// Transcripts have the same challenge
+    #[error("challenges are equal")]
+    SameChallenge,
+    /// Difference of responses is not divisible by difference of challenges
+    #[error("difference of responses is not divisible by difference of challenges")]
+    NotDivisible,
+    /// Difference of challenges is not co-prime with the modulus
+    #[error("difference of challenges is not co-prime with the modulus")]
+    NotCoprime,
+    /// Extracted witness doesn't satisfy the statement. Indicates that at least one of
+    /// the transcripts is not accepting, or that they have different commitments
+    #[error("extracted witness doesn't satisfy the statement")]
+    WitnessMismatch,
+}
+
+/// Returns `e1 - e2`, ensuring that it's non-zero
+pub fn challenge_diff(e1: &Integer, e2: &Integer) -> Result<Integer, ExtractionError> {
+    let diff = (e1 - e2).complete();
+    if diff.cmp0().is_eq() {
+        return Err(ExtractionErrorReason::SameChallenge.into());
+    }
+    Ok(diff)
+}
+
+/// Given responses `z_i = alpha + e_i w`, returns `w = (z1 - z2) / (e1 - e2)`
+pub fn linear_response(
+    z1: &Integer,
+    z2: &Integer,
+    e_diff: &Integer,
+) -> Result<Integer, ExtractionError> {
+    let (w, rem) = (z1 - z2).complete().div_rem(e_diff.clone());
+    if rem.cmp0().is_ne() {
+        return Err(ExtractionErrorReason::NotDivisible.into());
+    }
+    Ok(w)
+}
+
+/// Given responses `w_i = r rho^(e_i) mod N` and ciphertext `c = (1 + N)^m rho^N mod N^2`,
+/// returns `rho`
+///
+/// `rho^(e1 - e2)` and `rho^N` are known, so `rho` is obtained with Shamir's trick,
+/// provided that `e1 - e2` is co-prime with `N`
+pub fn paillier_nonce(
+    n: &Integer,
+    c: &Integer,
+    w1: &Integer,
+    w2: &Integer,
+    e_diff: &Integer,
+) -> Result<Integer, ExtractionError> {
+    let (gcd, a, b) = e_diff.clone().extended_gcd(n.clone(), Integer::new());
+    if gcd != *Integer::ONE {
+        return Err(ExtractionErrorReason::NotCoprime.into());
+    }
+    let mismatch = || ExtractionError::from(ExtractionErrorReason::WitnessMismatch);
+
+    // rho^(e1 - e2)
+    let w2_inv = w2.invert_ref(n).ok_or_else(mismatch)?;
+    let rho_to_e_diff = (w1 * Integer::from(w2_inv)).modulo(n);
+    // rho^N
+    let rho_to_n = c.modulo_ref(n).complete();
+
+    let lhs: Integer = rho_to_e_diff
+        .pow_mod_ref(&a, n)
+        .ok_or_else(mismatch)?
+        .into();
+    let rhs: Integer = rho_to_n.pow_mod_ref(&b, n).ok_or_else(mismatch)?.into();
+    Ok((lhs * rhs).modulo(n))
+}
+
+/// Homomorphic multiplication `c^x mod N^2` of ciphertext `c` at extracted `x`
+///
+/// Unlike `omul`, accepts `x = 0`, which is a legitimate witness: `c^0` is an
+/// encryption of zero with nonce 1
+pub fn omul(
+    key: &dyn fast_paillier::AnyEncryptionKey,
+    x: &Integer,
+    c: &Integer,
+) -> Result<Integer, ExtractionError> {
+    c.pow_mod_ref(x, key.nn())
+        .map(Integer::from)
+        .ok_or(ExtractionErrorReason::WitnessMismatch.into())
+}
+
+/// Returns [`ExtractionErrorReason::WitnessMismatch`] if `assertion` is false
+pub fn ensure(assertion: bool) -> Result<(), ExtractionError> {
+    if assertion {
+        Ok(())
+    } else {
+        Err(ExtractionErrorReason::WitnessMismatch.into())
+    }
+}
@@ -0,0 +1,63 @@
+//! Short fingerprints of paillier keys for attributing verification failures
+
+use std::fmt;
+
+use rug::Integer;
+use sha2::{Digest, Sha256};
+
+/// Short fingerprint of a paillier key
+///
+/// First 8 bytes of SHA-256 of the big-endian encoding of `N`. Not collision
+/// resistant against adversarially chosen keys, and only meant to tell apart keys
+/// of the parties in logs and error messages. Displayed as 16 hex digits.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyFingerprint([u8; 8]);
+
+impl KeyFingerprint {
+    /// Computes fingerprint of the key with modulus `n`
+    pub fn of(n: &Integer) -> Self {
+        let hash = Sha256::digest(n.to_digits::<u8>(rug::integer::Order::Msf));
+        let mut fingerprint = [0u8; 8];
+        fingerprint.copy_from_slice(&hash[..8]);
+        Self(fingerprint)
+    }
+
+    /// Bytes of the fingerprint
+    pub fn as_bytes(&self) -> &[u8; 8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for KeyFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
+
+impl fmt::Debug for KeyFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KeyFingerprint({self})")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::Integer;
+
+    use super::KeyFingerprint;
+
+    #[test]
+    fn distinct_keys() {
+        let mut rng = rand_dev::DevRng::new();
+        let key0 = crate::common::test::random_key(&mut rng).unwrap();
+        let key1 = crate::common::test::random_key(&mut rng).unwrap();
+        let f0 = KeyFingerprint::of(key0.n());
+        assert_eq!(f0, KeyFingerprint::of(key0.n()));
+        assert_ne!(f0, KeyFingerprint::of(key1.n()));
+        assert_eq!(f0.to_string().len(), 16);
+        assert_eq!(
+            KeyFingerprint::of(&Integer::from(1)).to_string(),
+            "4bf5122f344554c5"
+        );
+    }
+}
@@ -1,5 +1,14 @@
 use digest::Digest;
 
+/// Minimal entropy, in bits, of the seed of RNG given to provers
+///
+/// Prover's randomness must be unpredictable and must never repeat across proofs,
+/// otherwise the witness leaks. Use a cryptographically secure RNG, e.g.
+/// `rand_core::OsRng`, or a CSPRNG seeded from the OS with at least this many bits.
+/// Obviously broken RNGs can be caught with `entropy-check` feature, see
+/// `entropy_check` module.
+pub const MIN_SEED_ENTROPY: u32 = 256;
+
 /// Pseudo-random generateur that obtains values by hashing the provided values
 /// salted with an internal counter. The counter is prepended to conserve
 /// entropy.
@@ -70,6 +79,102 @@ where
     }
 }
 
+/// Post-processing of prover's randomness
+///
+/// Some compliance profiles, e.g. FIPS, require randomness to pass through an
+/// approved DRBG step before it's used. Wrap the RNG given to provers into
+/// [`PostProcessed`], and every value sampled by `commit` of any proof is derived
+/// from bytes that went through [`process`](Self::process): provers sample
+/// randomness only from the RNG they're given.
+///
+/// Output of the processor must be as unpredictable as its input, otherwise the
+/// witness leaks, see [`MIN_SEED_ENTROPY`]. [`PostProcessed`] is `CryptoRng` only if
+/// the processor is [`EntropyPreserving`].
+pub trait RandomnessPostProcessor {
+    /// Replaces freshly generated `bytes` with processed ones
+    fn process(&mut self, bytes: &mut [u8]);
+}
+
+/// Post-processor that doesn't make randomness predictable
+///
+/// # Safety
+/// Output of [`process`](RandomnessPostProcessor::process) must be as unpredictable
+/// as its input: any processor that loses entropy, e.g. zeroes or repeats bytes, makes
+/// [`PostProcessed`] falsely claim to be `CryptoRng`, and provers using it leak the
+/// witness.
+pub unsafe trait EntropyPreserving: RandomnessPostProcessor {}
+
+/// Leaves randomness as is
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Identity;
+
+impl RandomnessPostProcessor for Identity {
+    fn process(&mut self, _bytes: &mut [u8]) {}
+}
+
+// SAFETY: output is the input
+unsafe impl EntropyPreserving for Identity {}
+
+/// RNG with every output passed through the [post-processor](RandomnessPostProcessor)
+///
+/// By default, the processor is [`Identity`], and the output is the same as of the
+/// inner RNG.
+#[derive(Debug, Clone)]
+pub struct PostProcessed<R, P = Identity> {
+    rng: R,
+    processor: P,
+}
+
+impl<R> PostProcessed<R> {
+    /// Wraps `rng` without post-processing
+    pub fn new(rng: R) -> Self {
+        Self::with_processor(rng, Identity)
+    }
+}
+
+impl<R, P> PostProcessed<R, P> {
+    /// Wraps `rng`, passing its output through `processor`
+    pub fn with_processor(rng: R, processor: P) -> Self {
+        Self { rng, processor }
+    }
+
+    /// Returns the inner RNG and the processor
+    pub fn into_inner(self) -> (R, P) {
+        (self.rng, self.processor)
+    }
+}
+
+impl<R, P> rand_core::RngCore for PostProcessed<R, P>
+where
+    R: rand_core::RngCore,
+    P: RandomnessPostProcessor,
+{
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+        self.processor.process(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.rng.try_fill_bytes(dest)?;
+        self.processor.process(dest);
+        Ok(())
+    }
+}
+
+impl<R: rand_core::CryptoRng, P: EntropyPreserving> rand_core::CryptoRng for PostProcessed<R, P> {}
+
 #[cfg(test)]
 mod test {
     use rand_core::RngCore;
@@ -87,4 +192,75 @@ mod test {
             rng.fill_bytes(&mut buffer);
         }
     }
+
+    #[test]
+    fn post_processing() {
+        struct Xor(u8);
+        impl super::RandomnessPostProcessor for Xor {
+            fn process(&mut self, bytes: &mut [u8]) {
+                bytes.iter_mut().for_each(|b| *b ^= self.0);
+            }
+        }
+
+        let hasher = |d: sha2::Sha256| d.chain_update("foobar").finalize();
+        let mut expected = super::HashRng::new(hasher);
+        let mut identity = super::PostProcessed::new(super::HashRng::new(hasher));
+        let mut xor = super::PostProcessed::with_processor(super::HashRng::new(hasher), Xor(0xff));
+
+        for size in [1, 4, 7, 64] {
+            let mut bytes = vec![0; size];
+            expected.fill_bytes(&mut bytes);
+            let mut processed = vec![0; size];
+            identity.fill_bytes(&mut processed);
+            assert_eq!(processed, bytes);
+            xor.fill_bytes(&mut processed);
+            assert!(processed.iter().zip(&bytes).all(|(p, b)| *p == !b));
+        }
+        let next = expected.next_u64();
+        assert_eq!(identity.next_u64(), next);
+        assert_eq!(xor.next_u64(), !next);
+    }
+
+    #[test]
+    fn crypto_rng_with_entropy_preserving_processor() {
+        fn assert_crypto_rng<R: rand_core::CryptoRng>(_rng: &R) {}
+        assert_crypto_rng(&super::PostProcessed::new(rand_dev::DevRng::new()));
+    }
+
+    #[cfg(feature = "paillier-encryption-in-range")]
+    #[test]
+    fn proof_with_post_processed_randomness() {
+        use crate::paillier_encryption_in_range as p;
+        use rug::{Complete, Integer};
+
+        struct Counter(usize);
+        impl super::RandomnessPostProcessor for Counter {
+            fn process(&mut self, bytes: &mut [u8]) {
+                self.0 += bytes.len();
+            }
+        }
+
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let mut processed = super::PostProcessed::with_processor(&mut rng, Counter(0));
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = p::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut processed,
+        )
+        .unwrap();
+        p::non_interactive::verify(shared_state, &aux, data, &commitment, &security, &proof)
+            .expect("proof failed");
+        // All commitment randomness went through the processor
+        let (_, Counter(processed_bytes)) = processed.into_inner();
+        assert!(processed_bytes > 0);
+    }
 }
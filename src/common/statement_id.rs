@@ -0,0 +1,72 @@
+//! Short identifiers of statements for correlating logs of prover and verifier
+
+use std::fmt;
+
+use sha2::Sha256;
+
+use crate::common::encoding::Encode;
+use crate::policy::Statement;
+use crate::transcript::Transcript;
+
+/// Short identifier of a statement
+///
+/// First 8 bytes squeezed from a [`Transcript`] over SHA-256 labeled
+/// `paillier_zk.statement_id`, which absorbs the name of the proof
+/// ([`Statement::PROOF`]) and the statement in its [canonical encoding](crate::encoding).
+/// Prover and verifier obtain the same id for the same statement, so it can be logged
+/// on both sides. Like [`KeyFingerprint`](crate::KeyFingerprint), it's not collision
+/// resistant against adversarially chosen statements. Displayed as 16 hex digits.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StatementId([u8; 8]);
+
+impl StatementId {
+    /// Computes id of the `statement`
+    pub fn of<S: Statement + Encode>(statement: &S) -> Self {
+        let mut transcript = Transcript::<Sha256>::new(b"paillier_zk.statement_id");
+        transcript.absorb(b"proof", S::PROOF.as_bytes());
+        transcript.absorb_value(b"statement", statement);
+        let mut id = [0u8; 8];
+        transcript.challenge_bytes(b"id", &mut id);
+        Self(id)
+    }
+
+    /// Bytes of the id
+    pub fn as_bytes(&self) -> &[u8; 8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for StatementId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
+
+impl fmt::Debug for StatementId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StatementId({self})")
+    }
+}
+
+#[cfg(all(test, feature = "paillier-encryption-of-zero"))]
+mod test {
+    use rug::Integer;
+
+    use super::StatementId;
+    use crate::paillier_encryption_of_zero as p;
+    use crate::policy::Statement;
+
+    #[test]
+    fn distinct_statements() {
+        let mut rng = rand_dev::DevRng::new();
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (c0, _) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+        let (c1, _) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+
+        let id = p::Data { key, c: &c0 }.statement_id();
+        assert_eq!(id, StatementId::of(&p::Data { key, c: &c0 }));
+        assert_ne!(id, p::Data { key, c: &c1 }.statement_id());
+        assert_eq!(id.to_string().len(), 16);
+    }
+}
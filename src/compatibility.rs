@@ -0,0 +1,428 @@
+//! Pre-flight check of configuration shared by the proofs
+//!
+//! Proofs rely on size relationships between security parameters, paillier keys,
+//! ring-pedersen parameters and the curve. When they don't hold, honest proofs fail
+//! to verify at random, long after the session was set up. [`compatibility_check`]
+//! validates all of them in one call, it's meant to be run once at session setup
+//! for every key, so later failures can't be caused by configuration mismatch.
+//! [`validate_parameters`] additionally checks the parameters against a particular
+//! statement, including all of its paillier moduli and its curve.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{compatibility, paillier_encryption_in_range as p};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     key: &fast_paillier::EncryptionKey,
+//! # ) -> Result<(), compatibility::Incompatible> {
+//! let security = p::SecurityParams::new(1024, 300, (rug::Integer::ONE << 128_u32).into());
+//! compatibility::compatibility_check::<generic_ec::curves::Secp256k1>(aux, key, &security)?;
+//! # Ok(()) }
+//! ```
+
+use fast_paillier::AnyEncryptionKey;
+use generic_ec::Curve;
+use rug::Integer;
+
+use crate::common::{Aux, IntegerExt, WeakAux};
+use crate::policy::Statement;
+
+/// Security parameters of a proof that bounds plaintexts to a range
+pub trait RangeSecurity {
+    /// l in paper, bit size of plaintexts. For proofs with several ranges, the
+    /// largest of them
+    fn l(&self) -> usize;
+    /// Epsilon in paper, slackness parameter
+    fn epsilon(&self) -> usize;
+    /// q in paper, bound on challenge
+    fn q(&self) -> &Integer;
+}
+
+/// Checks that `aux`, paillier `key`, `security` parameters and curve `C` can be
+/// used together
+///
+/// Checks that:
+/// * `q < 2^l`
+/// * Order of curve `C` is less than `2^l`, so scalars fit into plaintexts
+/// * `N` is large enough for responses of honest prover to not wrap around it,
+///   see [`min_modulus_bits`]
+/// * `aux` passes [`Aux::check_sanity`], which includes size of `N^`
+pub fn compatibility_check<C: Curve>(
+    aux: &Aux,
+    key: &dyn AnyEncryptionKey,
+    security: &impl RangeSecurity,
+) -> Result<(), Incompatible> {
+    let l = security.l();
+    let q_bits = security.q().significant_bits();
+    if q_bits as usize > l {
+        return Err(Incompatible::LargeChallenge { q_bits, l });
+    }
+    let curve_bits = (Integer::curve_order::<C>() - 1u8).significant_bits();
+    if curve_bits as usize > l {
+        return Err(Incompatible::SmallRange { l, curve_bits });
+    }
+    let bits = key.n().significant_bits();
+    let min_bits = min_modulus_bits(security);
+    if (bits as usize) < min_bits {
+        return Err(Incompatible::SmallModulus { bits, min_bits });
+    }
+    aux.check_sanity()?;
+    Ok(())
+}
+
+/// Validates security parameters against the statement they're used for
+///
+/// In addition to the checks of [`compatibility_check`], checks that:
+/// * The statement involves curve `C`, if it involves any
+/// * Challenges are small enough for masks to hide them: `q * 2^l < 2^(l + epsilon)`.
+///   For statements involving a curve, the same must hold for the order of the curve
+/// * Every paillier modulus of the statement is at least [`min_modulus_bits`]
+/// * `N^` of `aux` is at least [`min_modulus_bits`]
+///
+/// Returned error tells which of the relations is violated.
+pub fn validate_parameters<C: Curve, S: Statement>(
+    statement: &S,
+    aux: &Aux,
+    security: &impl RangeSecurity,
+) -> Result<(), Incompatible> {
+    let curve_bits = (Integer::curve_order::<C>() - 1u8).significant_bits();
+    validate_statement(statement, Some((C::CURVE_NAME, curve_bits)), aux, security)
+}
+
+/// Same as [`validate_parameters`], `curve` is the name and bit size of the order of
+/// the expected curve
+///
+/// Checks involving the curve are skipped if `curve` is `None`
+pub(crate) fn validate_statement<S: Statement>(
+    statement: &S,
+    curve: Option<(&'static str, u32)>,
+    aux: &Aux,
+    security: &impl RangeSecurity,
+) -> Result<(), Incompatible> {
+    let l = security.l();
+    let epsilon = security.epsilon();
+    let q_bits = security.q().significant_bits();
+    if q_bits as usize > l {
+        return Err(Incompatible::LargeChallenge { q_bits, l });
+    }
+    if q_bits as usize > epsilon {
+        return Err(Incompatible::SmallSlackness {
+            bound_bits: q_bits,
+            epsilon,
+        });
+    }
+    if let (Some(actual), Some((expected, curve_bits))) = (statement.curve(), curve) {
+        if actual != expected {
+            return Err(Incompatible::WrongCurve { expected, actual });
+        }
+        if curve_bits as usize > l {
+            return Err(Incompatible::SmallRange { l, curve_bits });
+        }
+        if curve_bits as usize > epsilon {
+            return Err(Incompatible::SmallSlackness {
+                bound_bits: curve_bits,
+                epsilon,
+            });
+        }
+    }
+    let min_bits = min_modulus_bits(security);
+    for n in statement.moduli() {
+        let bits = n.significant_bits();
+        if (bits as usize) < min_bits {
+            return Err(Incompatible::SmallModulus { bits, min_bits });
+        }
+    }
+    let bits = aux.rsa_modulo.significant_bits();
+    if (bits as usize) < min_bits {
+        return Err(Incompatible::SmallAuxModulus { bits, min_bits });
+    }
+    aux.check_sanity()?;
+    Ok(())
+}
+
+/// Checks that witnesses of at most `l` bits fit into scalars of curve `C`
+///
+/// Backs `assert_bound_fits_curve` of security parameters of the proofs whose
+/// witness is a scalar of the curve
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+pub(crate) fn bound_fits_curve<C: Curve>(l: usize) -> Result<(), Incompatible> {
+    let curve_bits = (Integer::curve_order::<C>() - 1u8).significant_bits();
+    if l > curve_bits as usize {
+        return Err(Incompatible::LargeRange { l, curve_bits });
+    }
+    Ok(())
+}
+
+/// Checks the bound at prove time if `required`, see [`bound_fits_curve`]
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+pub(crate) fn check_bound_fits_curve<C: Curve>(
+    l: usize,
+    required: bool,
+) -> Result<(), crate::Error> {
+    if !required {
+        return Ok(());
+    }
+    bound_fits_curve::<C>(l).map_err(|err| crate::ErrorReason::BoundExceedsCurve(err).into())
+}
+
+/// Minimal bit size of paillier modulus `N` that fits responses of honest prover
+///
+/// Responses are at most `max(l + epsilon, bits(q) + l) + 1` bits and must be less
+/// than `N/2` in absolute value
+pub fn min_modulus_bits(security: &impl RangeSecurity) -> usize {
+    let l = security.l();
+    let q = security.q().significant_bits() as usize;
+    (l + security.epsilon()).max(q + l) + 2
+}
+
+/// Reason why configuration can't be used, returned by [`compatibility_check`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[non_exhaustive]
+pub enum Incompatible {
+    /// Challenge bound `q` is not less than `2^l`
+    #[error("q has {q_bits} bits, at most l = {l} bits expected")]
+    LargeChallenge {
+        /// Bit size of `q`
+        q_bits: u32,
+        /// `l` of security parameters
+        l: usize,
+    },
+    /// Scalars of the curve don't fit into `l` bits
+    #[error("l = {l} is less than {curve_bits} bits of curve order")]
+    SmallRange {
+        /// `l` of security parameters
+        l: usize,
+        /// Bit size of the largest scalar
+        curve_bits: u32,
+    },
+    /// Witness bound `l` is larger than scalars of the curve, which is likely a
+    /// configuration bug when the witness is used as a scalar
+    #[error("l = {l} exceeds {curve_bits} bits of curve order")]
+    LargeRange {
+        /// `l` of security parameters
+        l: usize,
+        /// Bit size of the largest scalar
+        curve_bits: u32,
+    },
+    /// Paillier modulus is too small for the security parameters
+    #[error("N has {bits} bits, at least {min_bits} bits expected")]
+    SmallModulus {
+        /// Bit size of `N`
+        bits: u32,
+        /// Minimal bit size, see [`min_modulus_bits`]
+        min_bits: usize,
+    },
+    /// Slackness `epsilon` is too small to hide challenges, i.e.
+    /// `q * 2^l >= 2^(l + epsilon)`, where `q` is the challenge bound or the curve
+    /// order
+    #[error("bound has {bound_bits} bits, epsilon = {epsilon} is too small to hide it")]
+    SmallSlackness {
+        /// Bit size of the challenge bound or the curve order
+        bound_bits: u32,
+        /// `epsilon` of security parameters
+        epsilon: usize,
+    },
+    /// Statement involves another curve
+    #[error("statement is over {actual}, expected {expected}")]
+    WrongCurve {
+        /// Curve the parameters are validated for
+        expected: &'static str,
+        /// Curve of the statement
+        actual: &'static str,
+    },
+    /// Ring-pedersen modulus `N^` is too small for the security parameters
+    #[error("N^ has {bits} bits, at least {min_bits} bits expected")]
+    SmallAuxModulus {
+        /// Bit size of `N^`
+        bits: u32,
+        /// Minimal bit size, see [`min_modulus_bits`]
+        min_bits: usize,
+    },
+    /// Ring-pedersen parameters look like toy parameters
+    #[error("ring-pedersen parameters are weak")]
+    WeakAux(
+        #[source]
+        #[from]
+        WeakAux,
+    ),
+}
+
+macro_rules! impl_range_security {
+    ($(#[$attr:meta] $module:ident => |$security:ident| $l:expr;)+) => {$(
+        #[$attr]
+        impl RangeSecurity for crate::$module::SecurityParams {
+            fn l(&self) -> usize {
+                let $security = self;
+                $l
+            }
+            fn epsilon(&self) -> usize {
+                self.epsilon
+            }
+            fn q(&self) -> &Integer {
+                &self.q
+            }
+        }
+    )+};
+}
+
+impl_range_security! {
+    #[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+    group_element_vs_paillier_encryption_in_range => |s| s.l;
+    #[cfg(feature = "group-element-vs-paillier-multiplication")]
+    group_element_vs_paillier_multiplication => |s| s.l;
+    #[cfg(feature = "joint-enc-log-star")]
+    joint_enc_log_star => |s| s.l;
+    #[cfg(feature = "paillier-affine-operation-in-range")]
+    paillier_affine_operation_in_range => |s| s.l_x.max(s.l_y);
+    #[cfg(feature = "paillier-affine-operation-with-paillier-commitment")]
+    paillier_affine_operation_with_paillier_commitment => |s| s.l_x.max(s.l_y);
+    #[cfg(feature = "paillier-broadcast-encryption")]
+    paillier_broadcast_encryption => |s| s.l;
+    #[cfg(feature = "paillier-decryption-modulo-q")]
+    paillier_decryption_modulo_q => |s| s.l;
+    #[cfg(feature = "paillier-encryption-in-range")]
+    paillier_encryption_in_range => |s| s.l;
+    #[cfg(feature = "paillier-encryption-in-range-with-elgamal")]
+    paillier_encryption_in_range_with_elgamal => |s| s.l;
+    #[cfg(feature = "paillier-plaintext-equality")]
+    paillier_plaintext_equality => |s| s.l;
+    #[cfg(feature = "paillier-scalar-multiplication-in-range")]
+    paillier_scalar_multiplication_in_range => |s| s.l;
+    #[cfg(feature = "pedersen-commitment-vs-paillier-encryption-in-range")]
+    pedersen_commitment_vs_paillier_encryption_in_range => |s| s.l;
+    #[cfg(feature = "witness-escrow")]
+    witness_escrow => |s| s.l;
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::paillier_encryption_in_range::SecurityParams;
+    use crate::policy::Statement;
+
+    use super::Incompatible;
+
+    type E = generic_ec::curves::Secp256k1;
+
+    #[test]
+    fn compatibility() {
+        let mut rng = rand_dev::DevRng::new();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let q = (Integer::ONE << 128_u32).complete();
+        let check =
+            |security: &SecurityParams| super::compatibility_check::<E>(&aux, key, security);
+
+        check(&SecurityParams::new(1024, 256, q.clone())).expect("compatible");
+
+        assert_eq!(
+            check(&SecurityParams::new(100, 256, q.clone())),
+            Err(Incompatible::LargeChallenge {
+                q_bits: 129,
+                l: 100
+            })
+        );
+        assert_eq!(
+            check(&SecurityParams::new(200, 256, q.clone())),
+            Err(Incompatible::SmallRange {
+                l: 200,
+                curve_bits: 256
+            })
+        );
+        let bits = key.n().significant_bits();
+        assert_eq!(
+            check(&SecurityParams::new(1024, 1024, q.clone())),
+            Err(Incompatible::SmallModulus {
+                bits,
+                min_bits: 2050
+            })
+        );
+
+        let mut weak = aux.clone();
+        weak.t = weak.s.clone();
+        assert_eq!(
+            super::compatibility_check::<E>(&weak, key, &SecurityParams::new(1024, 256, q)),
+            Err(Incompatible::WeakAux(crate::WeakAux::EqualParameters))
+        );
+    }
+
+    #[test]
+    fn validate_parameters() {
+        let mut rng = rand_dev::DevRng::new();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (ciphertext, _) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+        let data = crate::paillier_encryption_in_range::Data {
+            key,
+            ciphertext: &ciphertext,
+        };
+        let q = (Integer::ONE << 128_u32).complete();
+        validate(&aux, &data, &SecurityParams::new(1024, 256, q.clone())).expect("valid");
+        assert_eq!(
+            validate(&aux, &data, &SecurityParams::new(1024, 100, q.clone())),
+            Err(Incompatible::SmallSlackness {
+                bound_bits: 129,
+                epsilon: 100
+            })
+        );
+        let bits = key.n().significant_bits();
+        assert_eq!(
+            validate(&aux, &data, &SecurityParams::new(1024, 1024, q.clone())),
+            Err(Incompatible::SmallModulus {
+                bits,
+                min_bits: 2050
+            })
+        );
+
+        // Statement over a curve, without paillier moduli
+        let statement = CurveStatement("test-curve");
+        assert_eq!(
+            validate(&aux, &statement, &SecurityParams::new(1024, 256, q.clone())),
+            Err(Incompatible::WrongCurve {
+                expected: <E as generic_ec::Curve>::CURVE_NAME,
+                actual: "test-curve"
+            })
+        );
+        let statement = CurveStatement(<E as generic_ec::Curve>::CURVE_NAME);
+        validate(&aux, &statement, &SecurityParams::new(1024, 256, q.clone())).expect("valid");
+        assert_eq!(
+            validate(&aux, &statement, &SecurityParams::new(1024, 200, q.clone())),
+            Err(Incompatible::SmallSlackness {
+                bound_bits: 256,
+                epsilon: 200
+            })
+        );
+        assert_eq!(
+            validate(&aux, &statement, &SecurityParams::new(1024, 1024, q)),
+            Err(Incompatible::SmallAuxModulus {
+                bits: aux.rsa_modulo.significant_bits(),
+                min_bits: 2050
+            })
+        );
+    }
+
+    fn validate(
+        aux: &crate::common::Aux,
+        statement: &impl Statement,
+        security: &SecurityParams,
+    ) -> Result<(), Incompatible> {
+        super::validate_parameters::<E, _>(statement, aux, security)
+    }
+
+    struct CurveStatement(&'static str);
+
+    impl Statement for CurveStatement {
+        const PROOF: &'static str = "test";
+
+        fn curve(&self) -> Option<&'static str> {
+            Some(self.0)
+        }
+
+        fn moduli(&self) -> Vec<&Integer> {
+            vec![]
+        }
+    }
+}
@@ -0,0 +1,1181 @@
+//! Composition of sigma protocols under a single challenge
+//!
+//! ## Description
+//! Several statements are often proven together, e.g. that a ciphertext is in range
+//! and that another ciphertext encrypts zero. Proving them one by one derives a
+//! separate challenge for each proof, and nothing binds the proofs to each other
+//! unless the caller takes care of it.
+//!
+//! This module proves a conjunction of statements as one sigma protocol: the prover
+//! commits for every statement, a single challenge is derived over all statements and
+//! commitments, and the prover responds to that challenge in every proof. Verifier
+//! accepts only if all proofs are valid for the same challenge.
+//!
+//! Proofs taking part in composition implement [`SigmaProtocol`]: each proof is
+//! represented by an instance, which is the statement along with parameters of the
+//! proof, e.g. [`Enc`] for [Пenc](crate::paillier_encryption_in_range). Instances are
+//! combined with [`And`], which is a sigma protocol itself, so any number of them can
+//! be nested. Commitment and proof of the composition are tuples of commitments and
+//! proofs of the parts.
+//!
+//! The challenge is sampled in `±q`, where `q` is the smallest of the bounds on
+//! challenges of the parts, so every proof is checked with a challenge in its range.
+//!
+//! Disjunction of statements is expressed with [`Or`]: the prover knows a witness
+//! for one of the branches and simulates the other one. Branches of [`Or`] implement
+//! [`Simulate`], e.g. "`C` encrypts a value in range" ([`Enc`]) or "`C` encrypts
+//! the known value `v`" ([`Opening`]). Instances of [`And`] and [`Or`] can be
+//! nested into each other.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::compose::{self, And, Enc, EncZero};
+//! use paillier_zk::{paillier_encryption_in_range as enc, paillier_encryption_of_zero as enc_zero};
+//! # fn doc_fn(
+//! #     aux: &enc::Aux,
+//! #     enc_data: enc::Data,
+//! #     enc_pdata: enc::PrivateData,
+//! #     enc_security: &enc::SecurityParams,
+//! #     zero_data: enc_zero::Data,
+//! #     zero_pdata: enc_zero::PrivateData,
+//! #     zero_security: &enc_zero::SecurityParams,
+//! # ) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut rng = rand_core::OsRng;
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//!
+//! let protocol = And(
+//!     Enc { aux, data: enc_data, security: enc_security },
+//!     EncZero { data: zero_data, security: zero_security },
+//! );
+//!
+//! // Prover proves both statements with one challenge
+//! let (commitment, proof) = compose::prove(
+//!     shared_state_prover,
+//!     &protocol,
+//!     (enc_pdata, zero_pdata),
+//!     &mut rng,
+//! )?;
+//!
+//! // Verifier checks both of them
+//! compose::verify(shared_state_verifier, &protocol, &commitment, &proof)?;
+//! # Ok(()) }
+//! ```
+
+use digest::Digest;
+use rand_core::RngCore;
+use rug::{Complete, Integer};
+
+use crate::common::encoding::{impl_decode, impl_encode, Decode, Encode};
+use crate::common::{fail_if, IntegerExt, InvalidProof, InvalidProofReason};
+use crate::transcript::Transcript;
+use crate::{Error, ErrorReason};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+use crate::group_element_vs_paillier_encryption_in_range as log_star;
+#[cfg(feature = "paillier-affine-operation-in-range")]
+use crate::paillier_affine_operation_in_range as aff_g;
+#[cfg(feature = "paillier-encryption-in-range")]
+use crate::paillier_encryption_in_range as enc;
+#[cfg(feature = "paillier-encryption-of-zero")]
+use crate::paillier_encryption_of_zero as enc_zero;
+#[cfg(feature = "paillier-multiplication")]
+use crate::paillier_multiplication as mul;
+
+pub use crate::common::Aux;
+
+/// Sigma protocol with integer challenges that can take part in composition
+///
+/// Implementors are instances of a proof: the statement along with everything
+/// needed to prove and verify it, except for the witness.
+pub trait SigmaProtocol {
+    /// Private data of prover
+    type Witness: Copy;
+    /// Prover's first message
+    type Commitment: Encode;
+    /// Randomness of the commitment, kept by prover
+    type PrivateCommitment;
+    /// Prover's response to the challenge
+    type Proof: Encode + Clone;
+
+    /// Challenges are sampled in `±challenge_bound()`
+    fn challenge_bound(&self) -> &Integer;
+
+    /// Absorbs the statement and parameters of the proof
+    ///
+    /// Must absorb everything the verifier relies on, except for the commitment
+    fn absorb_statement<D: Digest>(&self, transcript: &mut Transcript<D>);
+
+    /// Creates random commitment
+    fn commit<R: RngCore>(
+        &self,
+        witness: Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::PrivateCommitment), Error>;
+
+    /// Computes the response to the challenge
+    fn respond(
+        &self,
+        witness: Self::Witness,
+        private_commitment: &Self::PrivateCommitment,
+        challenge: &Integer,
+    ) -> Result<Self::Proof, Error>;
+
+    /// Verifies the proof for given challenge
+    fn check(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Integer,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof>;
+}
+
+/// Conjunction of two sigma protocols, proven with a single challenge
+///
+/// Witness, commitment and proof are pairs of those of `A` and `B`.
+#[derive(Debug, Clone, Copy)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<A: SigmaProtocol, B: SigmaProtocol> SigmaProtocol for And<A, B> {
+    type Witness = (A::Witness, B::Witness);
+    type Commitment = (A::Commitment, B::Commitment);
+    type PrivateCommitment = (A::PrivateCommitment, B::PrivateCommitment);
+    type Proof = (A::Proof, B::Proof);
+
+    fn challenge_bound(&self) -> &Integer {
+        std::cmp::min(self.0.challenge_bound(), self.1.challenge_bound())
+    }
+
+    fn absorb_statement<D: Digest>(&self, transcript: &mut Transcript<D>) {
+        transcript.label(b"paillier_zk.compose.and");
+        self.0.absorb_statement(transcript);
+        self.1.absorb_statement(transcript);
+    }
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::PrivateCommitment), Error> {
+        let (comm_a, pcomm_a) = self.0.commit(witness.0, rng)?;
+        let (comm_b, pcomm_b) = self.1.commit(witness.1, rng)?;
+        Ok(((comm_a, comm_b), (pcomm_a, pcomm_b)))
+    }
+
+    fn respond(
+        &self,
+        witness: Self::Witness,
+        private_commitment: &Self::PrivateCommitment,
+        challenge: &Integer,
+    ) -> Result<Self::Proof, Error> {
+        Ok((
+            self.0
+                .respond(witness.0, &private_commitment.0, challenge)?,
+            self.1
+                .respond(witness.1, &private_commitment.1, challenge)?,
+        ))
+    }
+
+    fn check(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Integer,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        self.0.check(&commitment.0, challenge, &proof.0)?;
+        self.1.check(&commitment.1, challenge, &proof.1)
+    }
+}
+
+/// Sigma protocol with a simulator, required to take part in [`Or`]
+///
+/// Simulator produces commitment and proof that are valid for the given challenge
+/// without knowing the witness. Simulated transcripts must be distributed as the
+/// honest ones.
+pub trait Simulate: SigmaProtocol {
+    /// Simulates commitment and proof for the `challenge`
+    fn simulate<R: RngCore>(
+        &self,
+        challenge: &Integer,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Proof), Error>;
+}
+
+impl<A: Simulate, B: Simulate> Simulate for And<A, B> {
+    fn simulate<R: RngCore>(
+        &self,
+        challenge: &Integer,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Proof), Error> {
+        let (comm_a, proof_a) = self.0.simulate(challenge, rng)?;
+        let (comm_b, proof_b) = self.1.simulate(challenge, rng)?;
+        Ok(((comm_a, comm_b), (proof_a, proof_b)))
+    }
+}
+
+/// Disjunction of two sigma protocols: prover knows a witness for at least one of them
+///
+/// The challenge `e` is split into challenges of the branches, `e_left + e_right = e`
+/// modulo `2q + 1`, where `q` is the smallest of the bounds on challenges of the
+/// branches. Prover simulates the branch it has no witness for with a challenge of
+/// its choice, and gets the challenge of the real branch from `e`. Verifier checks
+/// both branches and doesn't learn which one is real.
+///
+/// Commitment is a pair of commitments of `A` and `B`, witness is [`Branch`].
+#[derive(Debug, Clone, Copy)]
+pub struct Or<A, B>(pub A, pub B);
+
+/// Witness of [`Or`]: witness of one of the branches
+#[derive(Clone, Copy)]
+pub enum Branch<L, R> {
+    /// Prover knows witness of the left branch
+    Left(L),
+    /// Prover knows witness of the right branch
+    Right(R),
+}
+
+/// Proof of [`Or`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OrProof<L, R> {
+    /// Challenge of the left branch, challenge of the right one is derived from it
+    pub left_challenge: Integer,
+    pub left: L,
+    pub right: R,
+}
+
+impl_encode! { OrProof<L: Encode, R: Encode> { left_challenge, left, right } }
+impl_decode! { OrProof<L: Decode, R: Decode> { left_challenge, left, right } }
+
+/// Private commitment of [`Or`]: private commitment of the real branch along with
+/// the simulated one
+pub struct OrPrivateCommitment<A: SigmaProtocol, B: SigmaProtocol>(OrState<A, B>);
+
+enum OrState<A: SigmaProtocol, B: SigmaProtocol> {
+    Left {
+        pcomm: A::PrivateCommitment,
+        right_challenge: Integer,
+        right: B::Proof,
+    },
+    Right {
+        pcomm: B::PrivateCommitment,
+        left_challenge: Integer,
+        left: A::Proof,
+    },
+}
+
+impl<A: SigmaProtocol, B: SigmaProtocol> Or<A, B> {
+    /// Challenge of one branch given the challenge of the other one
+    fn other_challenge(&self, challenge: &Integer, known: &Integer) -> Integer {
+        let q = std::cmp::min(self.0.challenge_bound(), self.1.challenge_bound());
+        let modulus = (q * 2u8).complete() + 1u8;
+        (challenge - known).complete().signed_modulo(&modulus)
+    }
+}
+
+impl<A: Simulate, B: Simulate> SigmaProtocol for Or<A, B> {
+    type Witness = Branch<A::Witness, B::Witness>;
+    type Commitment = (A::Commitment, B::Commitment);
+    type PrivateCommitment = OrPrivateCommitment<A, B>;
+    type Proof = OrProof<A::Proof, B::Proof>;
+
+    fn challenge_bound(&self) -> &Integer {
+        std::cmp::min(self.0.challenge_bound(), self.1.challenge_bound())
+    }
+
+    fn absorb_statement<D: Digest>(&self, transcript: &mut Transcript<D>) {
+        transcript.label(b"paillier_zk.compose.or");
+        self.0.absorb_statement(transcript);
+        self.1.absorb_statement(transcript);
+    }
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::PrivateCommitment), Error> {
+        let simulated_challenge = Integer::from_rng_pm(self.challenge_bound(), rng);
+        match witness {
+            Branch::Left(witness) => {
+                let (comm_a, pcomm) = self.0.commit(witness, rng)?;
+                let (comm_b, right) = self.1.simulate(&simulated_challenge, rng)?;
+                let state = OrState::Left {
+                    pcomm,
+                    right_challenge: simulated_challenge,
+                    right,
+                };
+                Ok(((comm_a, comm_b), OrPrivateCommitment(state)))
+            }
+            Branch::Right(witness) => {
+                let (comm_a, left) = self.0.simulate(&simulated_challenge, rng)?;
+                let (comm_b, pcomm) = self.1.commit(witness, rng)?;
+                let state = OrState::Right {
+                    pcomm,
+                    left_challenge: simulated_challenge,
+                    left,
+                };
+                Ok(((comm_a, comm_b), OrPrivateCommitment(state)))
+            }
+        }
+    }
+
+    fn respond(
+        &self,
+        witness: Self::Witness,
+        private_commitment: &Self::PrivateCommitment,
+        challenge: &Integer,
+    ) -> Result<Self::Proof, Error> {
+        match (witness, &private_commitment.0) {
+            (
+                Branch::Left(witness),
+                OrState::Left {
+                    pcomm,
+                    right_challenge,
+                    right,
+                },
+            ) => {
+                let left_challenge = self.other_challenge(challenge, right_challenge);
+                let left = self.0.respond(witness, pcomm, &left_challenge)?;
+                Ok(OrProof {
+                    left_challenge,
+                    left,
+                    right: right.clone(),
+                })
+            }
+            (
+                Branch::Right(witness),
+                OrState::Right {
+                    pcomm,
+                    left_challenge,
+                    left,
+                },
+            ) => {
+                let right_challenge = self.other_challenge(challenge, left_challenge);
+                let right = self.1.respond(witness, pcomm, &right_challenge)?;
+                Ok(OrProof {
+                    left_challenge: left_challenge.clone(),
+                    left: left.clone(),
+                    right,
+                })
+            }
+            _ => Err(ErrorReason::WitnessMismatch.into()),
+        }
+    }
+
+    fn check(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Integer,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        fail_if(
+            InvalidProofReason::RangeCheck(1),
+            proof.left_challenge.is_in_pm(self.challenge_bound()),
+        )?;
+        let right_challenge = self.other_challenge(challenge, &proof.left_challenge);
+        self.0
+            .check(&commitment.0, &proof.left_challenge, &proof.left)?;
+        self.1.check(&commitment.1, &right_challenge, &proof.right)
+    }
+}
+
+impl<A: Simulate, B: Simulate> Simulate for Or<A, B> {
+    fn simulate<R: RngCore>(
+        &self,
+        challenge: &Integer,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Proof), Error> {
+        let left_challenge = Integer::from_rng_pm(self.challenge_bound(), rng);
+        let right_challenge = self.other_challenge(challenge, &left_challenge);
+        let (comm_a, left) = self.0.simulate(&left_challenge, rng)?;
+        let (comm_b, right) = self.1.simulate(&right_challenge, rng)?;
+        let proof = OrProof {
+            left_challenge,
+            left,
+            right,
+        };
+        Ok(((comm_a, comm_b), proof))
+    }
+}
+
+/// Computes proof of the `protocol`, producing random commitment and deriving
+/// determenistic challenge
+pub fn prove<P: SigmaProtocol, D: Digest, R: RngCore>(
+    shared_state: D,
+    protocol: &P,
+    witness: P::Witness,
+    rng: &mut R,
+) -> Result<(P::Commitment, P::Proof), Error> {
+    let mut transcript = Transcript::from_shared_state(shared_state);
+    prove_with_transcript(&mut transcript, protocol, witness, rng)
+}
+
+/// Computes proof of the `protocol` within the protocol transcript
+///
+/// Statement and commitment are absorbed into the `transcript`, so subsequent
+/// challenges derived from it are bound to the proof.
+pub fn prove_with_transcript<P: SigmaProtocol, D: Digest, R: RngCore>(
+    transcript: &mut Transcript<D>,
+    protocol: &P,
+    witness: P::Witness,
+    rng: &mut R,
+) -> Result<(P::Commitment, P::Proof), Error> {
+    Ok(commit(protocol, witness, rng)?
+        .challenge(transcript)
+        .respond()?
+        .into_parts())
+}
+
+/// Verifies proof of the `protocol`, deriving challenge independently from same data
+pub fn verify<P: SigmaProtocol, D: Digest>(
+    shared_state: D,
+    protocol: &P,
+    commitment: &P::Commitment,
+    proof: &P::Proof,
+) -> Result<(), InvalidProof> {
+    let mut transcript = Transcript::from_shared_state(shared_state);
+    verify_with_transcript(&mut transcript, protocol, commitment, proof)
+}
+
+/// Verifies proof of the `protocol` within the protocol transcript, deriving
+/// challenge independently from same data
+pub fn verify_with_transcript<P: SigmaProtocol, D: Digest>(
+    transcript: &mut Transcript<D>,
+    protocol: &P,
+    commitment: &P::Commitment,
+    proof: &P::Proof,
+) -> Result<(), InvalidProof> {
+    let challenge = challenge_with_transcript(transcript, protocol, commitment);
+    crate::common::lap!(Challenge);
+    protocol.check(commitment, &challenge, proof)
+}
+
+/// Deterministically computes the single challenge of the `protocol` from the
+/// protocol transcript
+///
+/// Absorbs statements of all parts, and then the commitment, into the `transcript`
+pub fn challenge_with_transcript<P: SigmaProtocol, D: Digest>(
+    transcript: &mut Transcript<D>,
+    protocol: &P,
+    commitment: &P::Commitment,
+) -> Integer {
+    transcript.label(b"paillier_zk.compose");
+    protocol.absorb_statement(transcript);
+    transcript.absorb_value(b"commitment", commitment);
+    let mut rng = transcript.challenge_rng(b"e");
+    Integer::from_rng_pm(protocol.challenge_bound(), &mut rng)
+}
+
+/// Commits to the proof of the `protocol`, starting the type-state flow of the prover
+///
+/// Non-interactive proof can only be finished in order: commitment is followed by
+/// deriving the challenge ([`Committed::challenge`]), which is followed by the
+/// response ([`Challenged::respond`]). Each step consumes the previous state, and
+/// the commitment is only accessible by reference, so it can't be changed after
+/// the challenge was derived from it, and the response can't be computed for a
+/// challenge derived from another commitment:
+///
+/// ```rust,compile_fail
+/// # use paillier_zk::compose::{self, SigmaProtocol};
+/// # fn doc_fn<P: SigmaProtocol>(protocol: &P, witness: P::Witness) -> Result<(), paillier_zk::Error> {
+/// let committed = compose::commit(protocol, witness, &mut rand_core::OsRng)?;
+/// // Response without the challenge doesn't compile
+/// let proven = committed.respond()?;
+/// # Ok(()) }
+/// ```
+///
+/// [`prove_with_transcript`] is the shortcut doing all the steps at once.
+pub fn commit<'p, P: SigmaProtocol, R: RngCore>(
+    protocol: &'p P,
+    witness: P::Witness,
+    rng: &mut R,
+) -> Result<Committed<'p, P>, Error> {
+    let (commitment, private_commitment) = protocol.commit(witness, rng)?;
+    crate::common::lap!(Commit);
+    Ok(Committed {
+        protocol,
+        witness,
+        commitment,
+        private_commitment,
+    })
+}
+
+/// Prover that has committed, but hasn't derived the challenge yet
+///
+/// Obtained from [`commit`]
+pub struct Committed<'p, P: SigmaProtocol> {
+    protocol: &'p P,
+    witness: P::Witness,
+    commitment: P::Commitment,
+    private_commitment: P::PrivateCommitment,
+}
+
+impl<'p, P: SigmaProtocol> Committed<'p, P> {
+    /// Commitment to be sent to verifier
+    pub fn commitment(&self) -> &P::Commitment {
+        &self.commitment
+    }
+
+    /// Derives the challenge from the protocol transcript
+    ///
+    /// Same as [`challenge_with_transcript`] for the commitment of the prover
+    pub fn challenge<D: Digest>(self, transcript: &mut Transcript<D>) -> Challenged<'p, P> {
+        let challenge = challenge_with_transcript(transcript, self.protocol, &self.commitment);
+        crate::common::lap!(Challenge);
+        Challenged {
+            committed: self,
+            challenge,
+        }
+    }
+}
+
+/// Prover that has derived the challenge from its commitment
+///
+/// Obtained from [`Committed::challenge`]
+pub struct Challenged<'p, P: SigmaProtocol> {
+    committed: Committed<'p, P>,
+    challenge: Integer,
+}
+
+impl<'p, P: SigmaProtocol> Challenged<'p, P> {
+    /// Commitment to be sent to verifier
+    pub fn commitment(&self) -> &P::Commitment {
+        &self.committed.commitment
+    }
+
+    /// Challenge derived from the commitment
+    pub fn challenge(&self) -> &Integer {
+        &self.challenge
+    }
+
+    /// Computes the response to the challenge
+    pub fn respond(self) -> Result<Proven<P>, Error> {
+        let Committed {
+            protocol,
+            witness,
+            commitment,
+            private_commitment,
+        } = self.committed;
+        let proof = protocol.respond(witness, &private_commitment, &self.challenge)?;
+        crate::common::lap!(Respond);
+        Ok(Proven { commitment, proof })
+    }
+}
+
+/// Finished proof
+///
+/// Obtained from [`Challenged::respond`]
+pub struct Proven<P: SigmaProtocol> {
+    commitment: P::Commitment,
+    proof: P::Proof,
+}
+
+impl<P: SigmaProtocol> Proven<P> {
+    /// Commitment to be sent to verifier
+    pub fn commitment(&self) -> &P::Commitment {
+        &self.commitment
+    }
+
+    /// Proof to be sent to verifier
+    pub fn proof(&self) -> &P::Proof {
+        &self.proof
+    }
+
+    /// Returns commitment and proof to be sent to verifier
+    pub fn into_parts(self) -> (P::Commitment, P::Proof) {
+        (self.commitment, self.proof)
+    }
+}
+
+/// [Пenc](crate::paillier_encryption_in_range) as a sigma protocol
+#[cfg(feature = "paillier-encryption-in-range")]
+#[derive(Debug, Clone, Copy)]
+pub struct Enc<'a> {
+    /// Ring-pedersen parameters of the verifier
+    pub aux: &'a Aux,
+    pub data: enc::Data<'a>,
+    pub security: &'a enc::SecurityParams,
+}
+
+#[cfg(feature = "paillier-encryption-in-range")]
+impl<'a> SigmaProtocol for Enc<'a> {
+    type Witness = enc::PrivateData<'a>;
+    type Commitment = enc::Commitment;
+    type PrivateCommitment = enc::PrivateCommitment;
+    type Proof = enc::Proof;
+
+    fn challenge_bound(&self) -> &Integer {
+        &self.security.q
+    }
+
+    fn absorb_statement<D: Digest>(&self, transcript: &mut Transcript<D>) {
+        transcript.label(b"paillier_zk.paillier_encryption_in_range");
+        transcript.absorb_aux(self.aux, self.security.aux_digest.as_deref());
+        transcript.absorb(b"l", &(self.security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(self.security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"data", &self.data);
+    }
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::PrivateCommitment), Error> {
+        enc::interactive::commit(self.aux, self.data, witness, self.security, rng)
+    }
+
+    fn respond(
+        &self,
+        witness: Self::Witness,
+        private_commitment: &Self::PrivateCommitment,
+        challenge: &Integer,
+    ) -> Result<Self::Proof, Error> {
+        enc::interactive::prove(self.data, witness, private_commitment, challenge)
+    }
+
+    fn check(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Integer,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        enc::interactive::verify(
+            self.aux,
+            self.data,
+            commitment,
+            self.security,
+            challenge,
+            proof,
+        )
+    }
+}
+
+#[cfg(feature = "paillier-encryption-in-range")]
+impl<'a> Simulate for Enc<'a> {
+    fn simulate<R: RngCore>(
+        &self,
+        challenge: &Integer,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Proof), Error> {
+        let key = self.data.key;
+        let two_to_l = (Integer::ONE << self.security.l).complete();
+        let two_to_l_plus_e =
+            (Integer::ONE << (self.security.l + self.security.epsilon)).complete();
+        let hat_n_at_two_to_l = (&self.aux.rsa_modulo * &two_to_l).complete();
+        let hat_n_at_two_to_l_plus_e = (&self.aux.rsa_modulo * &two_to_l_plus_e).complete();
+
+        let z1 = Integer::from_rng_pm(&two_to_l_plus_e, rng);
+        let z2 = Integer::gen_invertible(key.n(), rng);
+        let z3 = Integer::from_rng_pm(&hat_n_at_two_to_l_plus_e, rng);
+        let s = self.aux.combine(
+            &Integer::from_rng_pm(&two_to_l, rng),
+            &Integer::from_rng_pm(&hat_n_at_two_to_l, rng),
+        )?;
+
+        // A = enc(z1, z2) - e K, C = s^z1 t^z3 S^-e, so that the checks hold
+        let a = key.osub(
+            &key.encrypt_with(&z1, &z2)?,
+            &key.omul(challenge, self.data.ciphertext)?,
+        )?;
+        let s_to_minus_e = self.aux.pow_mod(&s, &(-challenge).complete())?;
+        let c = (self.aux.combine(&z1, &z3)? * s_to_minus_e).modulo(&self.aux.rsa_modulo);
+
+        Ok((enc::Commitment { s, a, c }, enc::Proof { z1, z2, z3 }))
+    }
+}
+
+/// [Пlog*](crate::group_element_vs_paillier_encryption_in_range) as a sigma protocol
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+#[derive(Debug, Clone, Copy)]
+pub struct LogStar<'a, C: generic_ec::Curve> {
+    /// Ring-pedersen parameters of the verifier
+    pub aux: &'a Aux,
+    pub data: log_star::Data<'a, C>,
+    pub security: &'a log_star::SecurityParams,
+}
+
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+impl<'a, C: generic_ec::Curve> SigmaProtocol for LogStar<'a, C> {
+    type Witness = log_star::PrivateData<'a>;
+    type Commitment = log_star::Commitment<C>;
+    type PrivateCommitment = log_star::PrivateCommitment;
+    type Proof = log_star::Proof;
+
+    fn challenge_bound(&self) -> &Integer {
+        &self.security.q
+    }
+
+    fn absorb_statement<D: Digest>(&self, transcript: &mut Transcript<D>) {
+        transcript.label(b"paillier_zk.group_element_vs_paillier_encryption_in_range");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_aux(self.aux, self.security.aux_digest.as_deref());
+        transcript.absorb(b"l", &(self.security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(self.security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"data", &self.data);
+    }
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::PrivateCommitment), Error> {
+        log_star::interactive::commit(self.aux, self.data, witness, self.security, rng)
+    }
+
+    fn respond(
+        &self,
+        witness: Self::Witness,
+        private_commitment: &Self::PrivateCommitment,
+        challenge: &Integer,
+    ) -> Result<Self::Proof, Error> {
+        log_star::interactive::prove(self.data, witness, private_commitment, challenge)
+    }
+
+    fn check(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Integer,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        log_star::interactive::verify(
+            self.aux,
+            self.data,
+            commitment,
+            self.security,
+            challenge,
+            proof,
+        )
+    }
+}
+
+/// [Пaff-g](crate::paillier_affine_operation_in_range) as a sigma protocol
+#[cfg(feature = "paillier-affine-operation-in-range")]
+#[derive(Debug, Clone, Copy)]
+pub struct AffG<'a, C: generic_ec::Curve> {
+    /// Ring-pedersen parameters of the verifier
+    pub aux: &'a Aux,
+    pub data: aff_g::Data<'a, C>,
+    pub security: &'a aff_g::SecurityParams,
+}
+
+#[cfg(feature = "paillier-affine-operation-in-range")]
+impl<'a, C: generic_ec::Curve> SigmaProtocol for AffG<'a, C> {
+    type Witness = aff_g::PrivateData<'a>;
+    type Commitment = aff_g::Commitment<C>;
+    type PrivateCommitment = aff_g::PrivateCommitment;
+    type Proof = aff_g::Proof;
+
+    fn challenge_bound(&self) -> &Integer {
+        &self.security.q
+    }
+
+    fn absorb_statement<D: Digest>(&self, transcript: &mut Transcript<D>) {
+        transcript.label(b"paillier_zk.paillier_affine_operation_in_range");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_aux(self.aux, self.security.aux_digest.as_deref());
+        transcript.absorb(b"l_x", &(self.security.l_x as u64).to_be_bytes());
+        transcript.absorb(b"l_y", &(self.security.l_y as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(self.security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"data", &self.data);
+    }
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::PrivateCommitment), Error> {
+        aff_g::interactive::commit(self.aux, self.data, witness, self.security, rng)
+    }
+
+    fn respond(
+        &self,
+        witness: Self::Witness,
+        private_commitment: &Self::PrivateCommitment,
+        challenge: &Integer,
+    ) -> Result<Self::Proof, Error> {
+        aff_g::interactive::prove(self.data, witness, private_commitment, challenge)
+    }
+
+    fn check(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Integer,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        aff_g::interactive::verify(
+            self.aux,
+            self.data,
+            commitment,
+            self.security,
+            challenge,
+            proof,
+        )
+    }
+}
+
+/// [Encryption of zero](crate::paillier_encryption_of_zero) as a sigma protocol
+#[cfg(feature = "paillier-encryption-of-zero")]
+#[derive(Debug, Clone, Copy)]
+pub struct EncZero<'a> {
+    pub data: enc_zero::Data<'a>,
+    pub security: &'a enc_zero::SecurityParams,
+}
+
+#[cfg(feature = "paillier-encryption-of-zero")]
+impl<'a> SigmaProtocol for EncZero<'a> {
+    type Witness = enc_zero::PrivateData<'a>;
+    type Commitment = enc_zero::Commitment;
+    type PrivateCommitment = enc_zero::PrivateCommitment;
+    type Proof = enc_zero::Proof;
+
+    fn challenge_bound(&self) -> &Integer {
+        &self.security.q
+    }
+
+    fn absorb_statement<D: Digest>(&self, transcript: &mut Transcript<D>) {
+        transcript.label(b"paillier_zk.paillier_encryption_of_zero");
+        transcript.absorb_value(b"data", &self.data);
+    }
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::PrivateCommitment), Error> {
+        enc_zero::interactive::commit(self.data, witness, self.security, rng)
+    }
+
+    fn respond(
+        &self,
+        witness: Self::Witness,
+        private_commitment: &Self::PrivateCommitment,
+        challenge: &Integer,
+    ) -> Result<Self::Proof, Error> {
+        enc_zero::interactive::prove(self.data, witness, private_commitment, challenge)
+    }
+
+    fn check(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Integer,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        enc_zero::interactive::verify(self.data, commitment, self.security, challenge, proof)
+    }
+}
+
+#[cfg(feature = "paillier-encryption-of-zero")]
+impl<'a> Simulate for EncZero<'a> {
+    fn simulate<R: RngCore>(
+        &self,
+        challenge: &Integer,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Proof), Error> {
+        let key = self.data.key;
+        let w = Integer::gen_invertible(key.n(), rng);
+        // a = enc(0, w) - e c, so that the check holds
+        let a = key.osub(
+            &key.encrypt_with(&Integer::ZERO, &w)?,
+            &key.omul(challenge, self.data.c)?,
+        )?;
+        Ok((enc_zero::Commitment { a }, enc_zero::Proof { w }))
+    }
+}
+
+/// [Opening](crate::paillier_encryption_of_zero::opening) of a ciphertext to a public
+/// value as a sigma protocol
+#[cfg(feature = "paillier-encryption-of-zero")]
+#[derive(Debug, Clone, Copy)]
+pub struct Opening<'a> {
+    pub data: enc_zero::opening::Data<'a>,
+    pub security: &'a enc_zero::SecurityParams,
+}
+
+#[cfg(feature = "paillier-encryption-of-zero")]
+impl<'a> SigmaProtocol for Opening<'a> {
+    type Witness = enc_zero::opening::PrivateData<'a>;
+    type Commitment = enc_zero::Commitment;
+    type PrivateCommitment = enc_zero::PrivateCommitment;
+    type Proof = enc_zero::Proof;
+
+    fn challenge_bound(&self) -> &Integer {
+        &self.security.q
+    }
+
+    fn absorb_statement<D: Digest>(&self, transcript: &mut Transcript<D>) {
+        transcript.label(b"paillier_zk.paillier_encryption_of_zero.opening");
+        transcript.absorb_value(b"data", &self.data);
+    }
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::PrivateCommitment), Error> {
+        enc_zero::opening::interactive::commit(self.data, witness, self.security, rng)
+    }
+
+    fn respond(
+        &self,
+        witness: Self::Witness,
+        private_commitment: &Self::PrivateCommitment,
+        challenge: &Integer,
+    ) -> Result<Self::Proof, Error> {
+        enc_zero::opening::interactive::prove(self.data, witness, private_commitment, challenge)
+    }
+
+    fn check(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Integer,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        enc_zero::opening::interactive::verify(
+            self.data,
+            commitment,
+            self.security,
+            challenge,
+            proof,
+        )
+    }
+}
+
+#[cfg(feature = "paillier-encryption-of-zero")]
+impl<'a> Simulate for Opening<'a> {
+    fn simulate<R: RngCore>(
+        &self,
+        challenge: &Integer,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Proof), Error> {
+        // Opening is the encryption of zero proof about `C - enc(v, 1)`
+        let difference = enc_zero::opening::difference(self.data)?;
+        let zero = EncZero {
+            data: enc_zero::Data {
+                key: self.data.key,
+                c: &difference,
+            },
+            security: self.security,
+        };
+        zero.simulate(challenge, rng)
+    }
+}
+
+/// [Пmul](crate::paillier_multiplication) as a sigma protocol
+#[cfg(feature = "paillier-multiplication")]
+#[derive(Debug, Clone, Copy)]
+pub struct Mul<'a> {
+    pub data: mul::Data<'a>,
+    pub security: &'a mul::SecurityParams,
+}
+
+#[cfg(feature = "paillier-multiplication")]
+impl<'a> SigmaProtocol for Mul<'a> {
+    type Witness = mul::PrivateData<'a>;
+    type Commitment = mul::Commitment;
+    type PrivateCommitment = mul::PrivateCommitment;
+    type Proof = mul::Proof;
+
+    fn challenge_bound(&self) -> &Integer {
+        &self.security.q
+    }
+
+    fn absorb_statement<D: Digest>(&self, transcript: &mut Transcript<D>) {
+        transcript.label(b"paillier_zk.paillier_multiplication");
+        transcript.absorb_value(b"data", &self.data);
+    }
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::PrivateCommitment), Error> {
+        mul::interactive::commit(self.data, witness, self.security, rng)
+    }
+
+    fn respond(
+        &self,
+        witness: Self::Witness,
+        private_commitment: &Self::PrivateCommitment,
+        challenge: &Integer,
+    ) -> Result<Self::Proof, Error> {
+        mul::interactive::prove(self.data, witness, private_commitment, challenge)
+    }
+
+    fn check(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Integer,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        mul::interactive::verify(self.data, commitment, self.security, challenge, proof)
+    }
+}
+
+#[cfg(all(
+    test,
+    feature = "paillier-encryption-in-range",
+    feature = "paillier-encryption-of-zero"
+))]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::test::{aux, random_key, EncStatement};
+    use crate::common::IntegerExt;
+    use crate::transcript::Transcript;
+    use crate::{paillier_encryption_in_range as enc, paillier_encryption_of_zero as enc_zero};
+
+    use super::{And, Branch, Enc, EncZero, Opening, Or, SigmaProtocol};
+
+    #[test]
+    fn conjunction() {
+        let mut rng = rand_dev::DevRng::new();
+        let enc_security =
+            enc::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let zero_security = enc_zero::SecurityParams::new((Integer::ONE << 64_u32).complete());
+        let aux = aux(&mut rng);
+        let statement = EncStatement::random(&mut rng, enc_security.l);
+        let key = &statement.key;
+        let (zero, zero_nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+        let (not_zero, _) = key.encrypt_with_random(&mut rng, Integer::ONE).unwrap();
+
+        let enc = Enc {
+            aux: &aux,
+            data: statement.data(),
+            security: &enc_security,
+        };
+        let enc_pdata = statement.pdata();
+        let zero_protocol = |c| EncZero {
+            data: enc_zero::Data { key, c },
+            security: &zero_security,
+        };
+        let zero_pdata = enc_zero::PrivateData { nonce: &zero_nonce };
+
+        let protocol = And(And(enc, zero_protocol(&zero)), enc);
+        let witness = ((enc_pdata, zero_pdata), enc_pdata);
+        let shared_state = sha2::Sha256::default();
+        let prove = |rng: &mut rand_dev::DevRng| {
+            super::prove(shared_state.clone(), &protocol, witness, rng).unwrap()
+        };
+
+        let (commitment, proof) = prove(&mut rng);
+        super::verify(shared_state.clone(), &protocol, &commitment, &proof).expect("proof failed");
+
+        // Challenge is the smallest of the bounds of the parts
+        assert_eq!(protocol.challenge_bound(), &zero_security.q);
+
+        // Every statement is bound by the challenge
+        let other = And(And(enc, zero_protocol(&not_zero)), enc);
+        super::verify(shared_state.clone(), &other, &commitment, &proof)
+            .expect_err("false statement should not pass");
+
+        // Parts of different proofs can't be mixed
+        let (commitment2, proof2) = prove(&mut rng);
+        let mixed_commitment = (commitment.0.clone(), commitment2.1);
+        let mixed_proof = (proof.0.clone(), proof2.1);
+        super::verify(shared_state, &protocol, &mixed_commitment, &mixed_proof)
+            .expect_err("mixed proof should not pass");
+    }
+
+    #[test]
+    fn type_state_flow() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = enc_zero::SecurityParams::new((Integer::ONE << 64_u32).complete());
+        let private_key = random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (c, nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+        let protocol = EncZero {
+            data: enc_zero::Data { key, c: &c },
+            security: &security,
+        };
+        let witness = enc_zero::PrivateData { nonce: &nonce };
+        let shared_state = sha2::Sha256::default();
+
+        let mut transcript = Transcript::from_shared_state(shared_state.clone());
+        let challenged = super::commit(&protocol, witness, &mut rng)
+            .unwrap()
+            .challenge(&mut transcript);
+        // Challenge is the one verifier derives from the commitment
+        let mut verifier_transcript = Transcript::from_shared_state(shared_state.clone());
+        let expected = super::challenge_with_transcript(
+            &mut verifier_transcript,
+            &protocol,
+            challenged.commitment(),
+        );
+        assert_eq!(challenged.challenge(), &expected);
+
+        let (commitment, proof) = challenged.respond().unwrap().into_parts();
+        super::verify(shared_state, &protocol, &commitment, &proof).expect("proof failed");
+    }
+
+    #[test]
+    fn disjunction() {
+        let mut rng = rand_dev::DevRng::new();
+        let enc_security =
+            enc::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let zero_security = enc_zero::SecurityParams::new((Integer::ONE << 64_u32).complete());
+        let aux = aux(&mut rng);
+        let private_key = random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+
+        let known = Integer::from(42);
+        let in_range = Integer::from_rng_pm(&(Integer::ONE << enc_security.l).complete(), &mut rng);
+        let out_of_range = (Integer::ONE << (enc_security.l + 1)).complete() + &known;
+
+        // "`C` encrypts a value in range or `C` encrypts 42"
+        let protocol = |ciphertext| {
+            Or(
+                Enc {
+                    aux: &aux,
+                    data: enc::Data { key, ciphertext },
+                    security: &enc_security,
+                },
+                Opening {
+                    data: enc_zero::opening::Data {
+                        key,
+                        c: ciphertext,
+                        v: &known,
+                    },
+                    security: &zero_security,
+                },
+            )
+        };
+        let shared_state = sha2::Sha256::default();
+
+        // Prover knows a value in range
+        let (c, nonce) = key.encrypt_with_random(&mut rng, &in_range).unwrap();
+        let witness = Branch::Left(enc::PrivateData {
+            plaintext: &in_range,
+            nonce: &nonce,
+        });
+        let (commitment, proof) =
+            super::prove(shared_state.clone(), &protocol(&c), witness, &mut rng).unwrap();
+        super::verify(shared_state.clone(), &protocol(&c), &commitment, &proof)
+            .expect("proof failed");
+        assert_eq!(protocol(&c).challenge_bound(), &zero_security.q);
+
+        // Challenges of the branches are bound by the single challenge
+        let mut tampered = proof.clone();
+        tampered.left_challenge += 1;
+        super::verify(shared_state.clone(), &protocol(&c), &commitment, &tampered)
+            .expect_err("tampered challenge should not pass");
+
+        // Prover knows the opening to 42
+        let (c, nonce) = key.encrypt_with_random(&mut rng, &known).unwrap();
+        let witness = Branch::Right(enc_zero::opening::PrivateData { nonce: &nonce });
+        let (commitment, proof) =
+            super::prove(shared_state.clone(), &protocol(&c), witness, &mut rng).unwrap();
+        super::verify(shared_state.clone(), &protocol(&c), &commitment, &proof)
+            .expect("proof failed");
+
+        // Neither of the statements is true
+        let (c, nonce) = key.encrypt_with_random(&mut rng, &out_of_range).unwrap();
+        let witness = Branch::Right(enc_zero::opening::PrivateData { nonce: &nonce });
+        let (commitment, proof) =
+            super::prove(shared_state.clone(), &protocol(&c), witness, &mut rng).unwrap();
+        super::verify(shared_state, &protocol(&c), &commitment, &proof)
+            .expect_err("false statement should not pass");
+    }
+}
@@ -0,0 +1,583 @@
+//! ZK-proof of consistency of discrete logarithm with ElGamal commitment. Called
+//! Пelog or Relog in the revised (2024) CGGMP paper.
+//!
+//! ## Description
+//!
+//! A party P has committed to a number y with an ElGamal commitment
+//! `(L, M) = (λ G, y G + λ X)` under public key X, G being a generator of curve `E`.
+//! P shares L, M, X and `Y = y H` with V and wants to prove that Y has the same
+//! discrete logarithm base H as the committed value base G.
+//!
+//! Given:
+//! - Curve `E`
+//! - `L = λ G`, `M = y G + λ X`, `Y = y H` - data to obtain proof about
+//!
+//! Prove:
+//! - P knows `λ` and `y` such that equations above hold
+//!
+//! Disclosing only: `L`, `M`, `X`, `Y`, `H`
+//!
+//! `y = 0` (then `Y` is the identity point and `M = λ X`) and `λ = 0` (then `L` is
+//! the identity point and `M = y G`) are valid witnesses.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use generic_ec::{Point, Scalar, curves::Secp256k1 as E};
+//! use paillier_zk::dlog_vs_elgamal_commitment as p;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 1. Setup: prover has secret `y`, commits to it under ElGamal key `X`, and
+//! //    computes `Y` base `H`
+//!
+//! let X = Point::<E>::generator() * Scalar::random(&mut rng);
+//! let H = Point::<E>::generator() * Scalar::random(&mut rng);
+//! let y = Scalar::<E>::random(&mut rng);
+//! let lambda = Scalar::<E>::random(&mut rng);
+//! let L = Point::generator() * lambda;
+//! let M = Point::generator() * y + X * lambda;
+//! let Y = H * y;
+//!
+//! // 2. Prover computes a non-interactive proof:
+//!
+//! let data = p::Data {
+//!     l: &L,
+//!     m: &M,
+//!     x: &X,
+//!     y: &Y,
+//!     h: &H,
+//! };
+//! let pdata = p::PrivateData {
+//!     y: &y,
+//!     lambda: &lambda,
+//! };
+//! let (commitment, proof) =
+//!     p::non_interactive::prove(shared_state_prover, data, pdata, &mut rng);
+//!
+//! // 3. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data<E>, _: &p::Commitment<E>, _: &p::Proof<E>) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 4. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(shared_state_verifier, data, &commitment, &proof)?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use generic_ec::{Curve, Point, Scalar};
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a, C: Curve> {
+    /// L in paper, obtained as `λ G`
+    pub l: &'a Point<C>,
+    /// M in paper, obtained as `y G + λ X`
+    pub m: &'a Point<C>,
+    /// X in paper, ElGamal public key
+    pub x: &'a Point<C>,
+    /// Y in paper, obtained as `y H`
+    pub y: &'a Point<C>,
+    /// h in paper, base of Y
+    pub h: &'a Point<C>,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a, C: Curve> {
+    /// y in paper, committed value
+    pub y: &'a Scalar<C>,
+    /// λ in paper, randomness of the commitment
+    pub lambda: &'a Scalar<C>,
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Commitment<C: Curve> {
+    pub a: Point<C>,
+    pub n: Point<C>,
+    pub b: Point<C>,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment<C: Curve> {
+    pub alpha: Scalar<C>,
+    pub m: Scalar<C>,
+}
+
+crate::transcript::assert_private!(<C: Curve> PrivateData<'static, C>, PrivateCommitment<C>);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge<C> = Scalar<C>;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Proof<C: Curve> {
+    pub z: Scalar<C>,
+    pub u: Scalar<C>,
+}
+
+impl_encode! { Data<'a, C: Curve> { l, m, x, y, h } }
+impl_encode! { Commitment<C: Curve> { a, n, b } }
+impl_decode! { Commitment<C: Curve> { a, n, b } }
+impl_encode! { Proof<C: Curve> { z, u } }
+impl_decode! { Proof<C: Curve> { z, u } }
+
+impl<C: Curve> Commitment<C> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    pub fn max_serialized_size() -> usize {
+        Self::schema().max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    pub fn schema() -> Schema {
+        Schema::default()
+            .point::<C>("a")
+            .point::<C>("n")
+            .point::<C>("b")
+    }
+}
+
+impl<C: Curve> Proof<C> {
+    /// Size of [canonically encoded](crate::encoding) proof
+    pub fn max_serialized_size() -> usize {
+        Self::schema().max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof
+    pub fn schema() -> Schema {
+        Schema::default().scalar::<C>("z").scalar::<C>("u")
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use generic_ec::{traits::IsZero, Curve, Point, Scalar};
+    use rand_core::RngCore;
+
+    use crate::common::{fail_if, fail_if_ne, InvalidProofReason};
+    use crate::InvalidProof;
+
+    use super::{Challenge, Commitment, Data, PrivateCommitment, PrivateData, Proof};
+
+    /// Create random commitment
+    pub fn commit<C: Curve, R: RngCore>(
+        data: Data<C>,
+        rng: &mut R,
+    ) -> (Commitment<C>, PrivateCommitment<C>) {
+        let alpha = Scalar::random(rng);
+        let m = Scalar::random(rng);
+        let commitment = Commitment {
+            a: Point::generator() * alpha,
+            n: Point::generator() * m + data.x * alpha,
+            b: data.h * m,
+        };
+        (commitment, PrivateCommitment { alpha, m })
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove<C: Curve>(
+        pdata: PrivateData<C>,
+        pcomm: &PrivateCommitment<C>,
+        challenge: &Challenge<C>,
+    ) -> Proof<C> {
+        Proof {
+            z: pcomm.alpha + challenge * pdata.lambda,
+            u: pcomm.m + challenge * pdata.y,
+        }
+    }
+
+    /// Verify the proof
+    pub fn verify<C: Curve>(
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        challenge: &Challenge<C>,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof> {
+        fail_if(InvalidProofReason::ZeroChallenge, !challenge.is_zero())?;
+        fail_if(
+            InvalidProofReason::DegenerateCommitment,
+            !commitment.a.is_zero() && !commitment.n.is_zero() && !commitment.b.is_zero(),
+        )?;
+        {
+            let lhs = Point::generator() * proof.z;
+            let rhs = commitment.a + data.l * challenge;
+            fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("commitment randomness"));
+        {
+            let lhs = Point::generator() * proof.u + data.x * proof.z;
+            let rhs = commitment.n + data.m * challenge;
+            fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("elgamal commitment"));
+        {
+            let lhs = data.h * proof.u;
+            let rhs = commitment.b + data.y * challenge;
+            fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("group element"));
+        Ok(())
+    }
+
+    /// Generate random challenge
+    pub fn challenge<C: Curve, R>(rng: &mut R) -> Challenge<C>
+    where
+        R: RngCore,
+    {
+        Scalar::random(rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::InvalidProof;
+
+    use super::{Challenge, Commitment, Data, PrivateData, Proof};
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<C: Curve, R: RngCore, D: Digest>(
+        shared_state: D,
+        data: Data<C>,
+        pdata: PrivateData<C>,
+        rng: &mut R,
+    ) -> (Commitment<C>, Proof<C>) {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<C: Curve, R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data<C>,
+        pdata: PrivateData<C>,
+        rng: &mut R,
+    ) -> (Commitment<C>, Proof<C>) {
+        let (comm, pcomm) = super::interactive::commit(data, rng);
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, data, &comm);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(pdata, &pcomm, &challenge);
+        crate::common::lap!(Respond);
+        (comm, proof)
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<C: Curve, D: Digest>(
+        shared_state: D,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, commitment, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, data, commitment);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(data, commitment, &challenge, proof)
+    }
+
+    /// Internal function for deriving challenge from protocol values
+    /// deterministically
+    pub fn challenge<C: Curve, D: Digest>(
+        shared_state: D,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+    ) -> Challenge<C> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data, commitment)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<C: Curve, D: Digest>(
+        shared_state: D,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, data, commitment);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+    ) -> Challenge<C> {
+        transcript.label(b"paillier_zk.dlog_vs_elgamal_commitment");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_point(b"L", data.l);
+        transcript.absorb_point(b"M", data.m);
+        transcript.absorb_point(b"X", data.x);
+        transcript.absorb_point(b"Y", data.y);
+        transcript.absorb_point(b"h", data.h);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(&mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use generic_ec::{Curve, NonZero, Point, Scalar};
+
+    use crate::common::extract::{ensure, ExtractionErrorReason};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness<C: Curve> {
+        pub y: Scalar<C>,
+        pub lambda: Scalar<C>,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract<C: Curve>(
+        data: Data<C>,
+        challenge1: &Challenge<C>,
+        proof1: &Proof<C>,
+        challenge2: &Challenge<C>,
+        proof2: &Proof<C>,
+    ) -> Result<Witness<C>, ExtractionError> {
+        let e_diff_inv = NonZero::from_scalar(challenge1 - challenge2)
+            .ok_or(ExtractionErrorReason::SameChallenge)?
+            .invert();
+        let lambda = (proof1.z - proof2.z) * e_diff_inv;
+        let y = (proof1.u - proof2.u) * e_diff_inv;
+        ensure(Point::generator() * lambda == *data.l)?;
+        ensure(Point::generator() * y + data.x * lambda == *data.m)?;
+        ensure(data.h * y == *data.y)?;
+        Ok(Witness { y, lambda })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generic_ec::{Curve, Point, Scalar};
+
+    use crate::common::InvalidProofReason;
+
+    struct Statement<C: Curve> {
+        l: Point<C>,
+        m: Point<C>,
+        x: Point<C>,
+        y: Point<C>,
+        h: Point<C>,
+        y_log: Scalar<C>,
+        lambda: Scalar<C>,
+    }
+
+    impl<C: Curve> Statement<C> {
+        fn random(rng: &mut rand_dev::DevRng) -> Self {
+            let x = Point::generator() * Scalar::random(rng);
+            let h = Point::generator() * Scalar::random(rng);
+            let y_log = Scalar::random(rng);
+            let lambda = Scalar::random(rng);
+            Self {
+                l: Point::generator() * lambda,
+                m: Point::generator() * y_log + x * lambda,
+                x,
+                y: h * y_log,
+                h,
+                y_log,
+                lambda,
+            }
+        }
+
+        fn data(&self) -> super::Data<'_, C> {
+            super::Data {
+                l: &self.l,
+                m: &self.m,
+                x: &self.x,
+                y: &self.y,
+                h: &self.h,
+            }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_, C> {
+            super::PrivateData {
+                y: &self.y_log,
+                lambda: &self.lambda,
+            }
+        }
+    }
+
+    fn run<C: Curve>(statement: &Statement<C>) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            statement.data(),
+            statement.pdata(),
+            &mut rng,
+        );
+        super::non_interactive::verify(shared_state, statement.data(), &commitment, &proof)
+    }
+
+    fn passing_test<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        run(&Statement::<C>::random(&mut rng)).expect("proof failed");
+    }
+
+    fn zero_witness<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        // y = 0: Y is the identity point, M = λ X
+        let mut statement = Statement::<C>::random(&mut rng);
+        statement.y_log = Scalar::zero();
+        statement.y = Point::zero();
+        statement.m = statement.x * statement.lambda;
+        run(&statement).expect("proof failed");
+
+        // λ = 0: L is the identity point, M = y G
+        let mut statement = Statement::<C>::random(&mut rng);
+        statement.lambda = Scalar::zero();
+        statement.l = Point::zero();
+        statement.m = Point::generator() * statement.y_log;
+        run(&statement).expect("proof failed");
+    }
+
+    fn failing_test<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        // Y has different logarithm than the committed value
+        let mut statement = Statement::<C>::random(&mut rng);
+        statement.y += statement.h;
+        let r = run(&statement).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::EqualityCheck(3));
+
+        // M commits to a different value
+        let mut statement = Statement::<C>::random(&mut rng);
+        statement.m += Point::generator();
+        let r = run(&statement).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::EqualityCheck(2));
+    }
+
+    fn degenerate_transcript<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let statement = Statement::<C>::random(&mut rng);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcommitment) = super::interactive::commit(data, &mut rng);
+        // Zero challenge makes the checks vacuous
+        let challenge = Scalar::zero();
+        let proof = super::interactive::prove(pdata, &pcommitment, &challenge);
+        let r = super::interactive::verify(data, &commitment, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Identity point in commitment is rejected
+        let challenge = super::interactive::challenge(&mut rng);
+        let proof = super::interactive::prove(pdata, &pcommitment, &challenge);
+        let commitment = super::Commitment {
+            b: Point::zero(),
+            ..commitment
+        };
+        let r = super::interactive::verify(data, &commitment, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[test]
+    fn passing_p256() {
+        passing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn zero_witness_p256() {
+        zero_witness::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn failing_p256() {
+        failing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn degenerate_transcript_p256() {
+        degenerate_transcript::<generic_ec::curves::Secp256r1>()
+    }
+
+    #[test]
+    fn passing_million() {
+        passing_test::<crate::curve::C>()
+    }
+    #[test]
+    fn failing_million() {
+        failing_test::<crate::curve::C>()
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let statement = Statement::<C>::random(&mut rng);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (_commitment, pcommitment) = super::interactive::commit(data, &mut rng);
+        let e1 = super::interactive::challenge(&mut rng);
+        let e2 = super::interactive::challenge(&mut rng);
+        let proof1 = super::interactive::prove(pdata, &pcommitment, &e1);
+        let proof2 = super::interactive::prove(pdata, &pcommitment, &e2);
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.y, statement.y_log);
+        assert_eq!(witness.lambda, statement.lambda);
+
+        assert!(super::extract::extract(data, &e1, &proof1, &e1, &proof1).is_err());
+    }
+}
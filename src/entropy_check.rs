@@ -0,0 +1,153 @@
+//! Detection of broken RNG
+//!
+//! Prover's randomness must be fresh for every proof: two proofs sharing commitment
+//! randomness (e.g. `alpha` of [Пenc](crate::paillier_encryption_in_range)) but
+//! answering different challenges reveal the witness. Provers must be given a
+//! cryptographically secure RNG seeded with at least
+//! [`MIN_SEED_ENTROPY`](crate::rng::MIN_SEED_ENTROPY) bits of entropy.
+//!
+//! With `entropy-check` feature enabled, provers remember salted hashes of the last
+//! [`CAPACITY`] values of commitment randomness generated in the process. If freshly
+//! sampled randomness was already seen, the proof fails with an error at commitment
+//! stage, before the response leaking the witness is computed. It's meant to catch
+//! obviously broken RNGs (e.g. RNG state cloned or reseeded with a constant) in
+//! debug and staging builds.
+//!
+//! Checked randomness:
+//! - `alpha` of [Пenc](crate::paillier_encryption_in_range),
+//!   [Пlog*](crate::group_element_vs_paillier_encryption_in_range) and
+//!   [Пmul](crate::paillier_multiplication)
+//! - `alpha` and `beta` of [Пaff-g](crate::paillier_affine_operation_in_range)
+//! - `r` of [encryption of zero](crate::paillier_encryption_of_zero) and
+//!   [knowledge of factorization](crate::factorization_knowledge)
+//!
+//! ## Limitations
+//! * Only exact repetitions are detected. RNG with little but non-zero entropy
+//!   produces distinct values and passes unnoticed.
+//! * Only the last [`CAPACITY`] values within the same process are remembered. RNG
+//!   state repeating across processes, e.g. in VM snapshots, isn't detected.
+//! * Memory is constant, but every proof takes a process-wide lock and scans the
+//!   whole buffer.
+//! * Provers that derive randomness deterministically from a seed legitimately
+//!   repeat it when proving the same statement twice, which is reported as well.
+//!   Call [`reset`] if it's intended. [FFI](crate::ffi) bindings derive randomness
+//!   from the seed and all the inputs, so it repeats only along with the proof, and
+//!   are not checked.
+//! * Fingerprints are 64 bits long, so false positives happen with probability
+//!   around `CAPACITY^2 / 2^64`.
+
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::Mutex;
+
+use rug::Integer;
+
+use crate::{Error, ErrorReason};
+
+/// Number of recent values remembered by the detector
+pub const CAPACITY: usize = 1024;
+
+/// Forgets all the values seen so far
+pub fn reset() {
+    *lock() = None;
+}
+
+/// Runs `f` without checking randomness of proofs computed within it
+pub(crate) fn exempt<T>(f: impl FnOnce() -> T) -> T {
+    let outer = EXEMPT.with(|e| e.replace(true));
+    let result = f();
+    EXEMPT.with(|e| e.set(outer));
+    result
+}
+
+/// Fails if any of the `values` was recently seen, remembers them otherwise
+pub(crate) fn observe(values: &[&Integer]) -> Result<(), Error> {
+    if EXEMPT.with(|e| e.get()) {
+        return Ok(());
+    }
+    let mut buffer = lock();
+    let buffer = buffer.get_or_insert_with(RingBuffer::new);
+    for value in values {
+        // Salt is random per process, so fingerprints reveal nothing about the values
+        let fingerprint = buffer.salt.hash_one(value);
+        if buffer.fingerprints[..buffer.len].contains(&fingerprint) {
+            return Err(ErrorReason::RepeatedRandomness.into());
+        }
+        buffer.fingerprints[buffer.next] = fingerprint;
+        buffer.next = (buffer.next + 1) % CAPACITY;
+        buffer.len = (buffer.len + 1).min(CAPACITY);
+    }
+    Ok(())
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<RingBuffer>> {
+    // Buffer stays consistent even if other thread panicked while holding the lock
+    BUFFER.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+struct RingBuffer {
+    salt: RandomState,
+    fingerprints: [u64; CAPACITY],
+    /// Index where the next fingerprint is written
+    next: usize,
+    /// Number of fingerprints written, at most `CAPACITY`
+    len: usize,
+}
+
+impl RingBuffer {
+    fn new() -> Self {
+        Self {
+            salt: RandomState::new(),
+            fingerprints: [0; CAPACITY],
+            next: 0,
+            len: 0,
+        }
+    }
+}
+
+static BUFFER: Mutex<Option<RingBuffer>> = Mutex::new(None);
+
+thread_local! {
+    static EXEMPT: Cell<bool> = const { Cell::new(false) };
+}
+
+#[cfg(test)]
+mod test {
+    use sha2::Digest;
+
+    use crate::common::test::random_key;
+    use crate::paillier_encryption_of_zero as p;
+    use crate::rng::HashRng;
+
+    #[test]
+    fn repeated_randomness() {
+        let mut rng = rand_dev::DevRng::new();
+        let private_key = random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (c, nonce) = key
+            .encrypt_with_random(&mut rng, &rug::Integer::ZERO)
+            .unwrap();
+        let data = p::Data { key, c: &c };
+        let pdata = p::PrivateData { nonce: &nonce };
+        let security = p::SecurityParams::new(rug::Integer::from(u64::MAX));
+
+        // RNG that's reseeded with the same constant for every proof
+        let broken_rng = || HashRng::new(|d: sha2::Sha256| d.chain_update("seed").finalize());
+        let prove = || {
+            p::non_interactive::prove(
+                sha2::Sha256::default(),
+                data,
+                pdata,
+                &security,
+                &mut broken_rng(),
+            )
+        };
+        prove().expect("first proof is fine");
+        prove().expect_err("repeated randomness should be detected");
+
+        // Fresh randomness is fine
+        p::non_interactive::prove(sha2::Sha256::default(), data, pdata, &security, &mut rng)
+            .expect("proof failed");
+    }
+}
@@ -0,0 +1,489 @@
+//! ZK-proof that paillier ciphertext encrypts a value in the range exactly, without
+//! slack
+//!
+//! ## Description
+//!
+//! A party P has a ciphertext `C = key.encrypt_with(x, rho)` encrypted under paillier
+//! key `N`. P wants to prove that `0 <= x < 2^l`, without disclosing `x`.
+//!
+//! Range proofs like [Пenc](crate::paillier_encryption_in_range) only guarantee that
+//! `x` is in `±2^(l+ε)`, even though honest prover has `x` in `±2^l`. This proof has
+//! no slack: if it verifies, `x` is in `[0, 2^l)`. Use it when the slack is
+//! unacceptable.
+//!
+//! Prover encrypts each bit `b_j` of `x` into `C_j`, and proves that each `C_j`
+//! encrypts a bit, see [encryption of bit](crate::paillier_encryption_of_bit). Then it
+//! proves that `C (prod C_j^(2^j))^-1` encrypts zero, see [encryption of
+//! zero](crate::paillier_encryption_of_zero), so `x = sum 2^j b_j`.
+//!
+//! Proof size and proving time are linear in `l`, which makes the proof much slower
+//! and larger than Пenc. `l` must be smaller than bit size of `N`.
+//!
+//! Given:
+//! - `key` - public key in paillier cryptosystem
+//! - `C = key.encrypt_with(x, rho)` - data to obtain proof about
+//!
+//! Prove:
+//! - `decrypt(C) ∈ [0, 2^l)`
+//!
+//! Disclosing only: `key`, `C`
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use paillier_zk::exact_range as p;
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! let security = p::SecurityParams::new(64, (Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover prepares the paillier keys
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key = private_key.encryption_key();
+//!
+//! // 2. Setup: prover encrypts some value in the range
+//!
+//! let plaintext = Integer::from(42);
+//! let (C, nonce) = key.encrypt_with_random(&mut rng, &plaintext)?;
+//!
+//! // 3. Prover computes a non-interactive proof that plaintext is in the range:
+//!
+//! let data = p::Data { key, c: &C };
+//! let proof = p::non_interactive::prove(
+//!     shared_state_prover,
+//!     data,
+//!     p::PrivateData { plaintext: &plaintext, nonce: &nonce },
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data, _: &p::non_interactive::Proof) {  }
+//! send(&data, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, proof);
+//! let (data, proof) = recv();
+//! p::non_interactive::verify(shared_state_verifier, data, &security, &proof)?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use rug::{Complete, Integer};
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::{paillier_encryption_of_bit as bit, paillier_encryption_of_zero as zero};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// l, plaintext is proven to be in `[0, 2^l)`
+    pub l: usize,
+    /// q, security parameter for challenges of the underlying proofs
+    pub q: Integer,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, q: Integer) -> Self {
+        Self { l, q }
+    }
+
+    fn bit(&self) -> bit::SecurityParams {
+        bit::SecurityParams::new(self.q.clone())
+    }
+
+    fn zero(&self) -> zero::SecurityParams {
+        zero::SecurityParams::new(self.q.clone())
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N, public key that C was encrypted on
+    pub key: &'a dyn AnyEncryptionKey,
+    /// C, ciphertext which plaintext is in the range
+    pub c: &'a Ciphertext,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// x, plaintext of C
+    pub plaintext: &'a Integer,
+    /// rho, nonce in encryption x -> C
+    pub nonce: &'a Nonce,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>);
+
+impl_encode! { Data<'a> { key, c } }
+
+/// Encryption of one bit of the plaintext along with the proof that it's a bit
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BitProof {
+    /// C_j, encryption of the bit
+    pub c: Ciphertext,
+    pub commitment: bit::Commitment,
+    pub proof: bit::Proof,
+}
+
+impl_encode! { BitProof { c, commitment, proof } }
+impl_decode! { BitProof { c, commitment, proof } }
+
+/// Combines encryptions of bits: returns `C (prod C_j^(2^j))^-1`, which encrypts zero
+/// if bits add up to the plaintext of `C`
+fn difference(
+    key: &dyn AnyEncryptionKey,
+    c: &Ciphertext,
+    bits: &[BitProof],
+) -> Result<Ciphertext, fast_paillier::Error> {
+    // Encryption of zero with nonce 1
+    let mut sum = Integer::from(1);
+    for (j, bit) in bits.iter().enumerate() {
+        let weight = (Integer::ONE << j).complete();
+        sum = key.oadd(&sum, &key.omul(&weight, &bit.c)?)?;
+    }
+    key.osub(c, &sum)
+}
+
+/// Non-interactive version of the proof
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{fail_if, IntegerExt, InvalidProofReason};
+    use crate::transcript::Transcript;
+    use crate::{BadExponent, Error, ErrorReason, InvalidProof};
+
+    use super::{bit, zero, BitProof, Data, PrivateData, Schema, SecurityParams};
+
+    /// The ZK proof, computed by [`prove`]
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Proof {
+        /// Proof for each bit of the plaintext, least significant bit first
+        pub bits: Vec<BitProof>,
+        pub zero_commitment: zero::Commitment,
+        pub zero_proof: zero::Proof,
+    }
+
+    crate::common::encoding::impl_encode! { Proof { bits, zero_commitment, zero_proof } }
+    crate::common::encoding::impl_decode! { Proof { bits, zero_commitment, zero_proof } }
+
+    impl Proof {
+        /// Upper bound on size of [canonically encoded](crate::encoding) proof produced
+        /// by honest prover
+        ///
+        /// `modulus_bits` is an upper bound on bit size of `N`
+        pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+            Self::schema(security, modulus_bits).max_size()
+        }
+
+        /// [Schema](crate::encoding::Schema) of canonically encoded proof produced
+        /// by honest prover
+        ///
+        /// `modulus_bits` is an upper bound on bit size of `N`. Commitment and proof
+        /// fields of the underlying proofs follow each other, see their schemas.
+        pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+            let mut bit_schema = Schema::default().integer("c", 2 * modulus_bits);
+            bit_schema
+                .fields
+                .extend(bit::Commitment::schema(&security.bit(), modulus_bits).fields);
+            bit_schema
+                .fields
+                .extend(bit::Proof::schema(&security.bit(), modulus_bits).fields);
+
+            let mut schema = Schema::default().sequence("bits", security.l, bit_schema);
+            schema
+                .fields
+                .extend(zero::Commitment::schema(&security.zero(), modulus_bits).fields);
+            schema
+                .fields
+                .extend(zero::Proof::schema(&security.zero(), modulus_bits).fields);
+            schema
+        }
+    }
+
+    /// Compute proof for the given data, producing random commitments and
+    /// deriving determenistic challenges.
+    pub fn prove<R: RngCore, D: Digest>(
+        shared_state: D,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<Proof, Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitments are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<Proof, Error> {
+        let n = data.key.n();
+        if pdata.plaintext.cmp0().is_lt()
+            || pdata.plaintext.significant_bits() as usize > security.l
+            || security.l >= n.significant_bits() as usize
+        {
+            return Err(ErrorReason::OutOfInterval.into());
+        }
+        absorb_statement(transcript, data, security);
+
+        let bit_security = security.bit();
+        let mut bits = Vec::with_capacity(security.l);
+        // prod rho_j^(2^j) mod N, nonce of encryption of the bits sum
+        let mut sum_nonce = Integer::from(1);
+        for j in 0..security.l {
+            let b = pdata.plaintext.get_bit(j as u32);
+            let nonce = Integer::gen_invertible(n, rng);
+            let c = data.key.encrypt_with(&Integer::from(u8::from(b)), &nonce)?;
+            let (commitment, proof) = bit::non_interactive::prove_with_transcript(
+                transcript,
+                bit::Data {
+                    key: data.key,
+                    c: &c,
+                },
+                bit::PrivateData {
+                    bit: b,
+                    nonce: &nonce,
+                },
+                &bit_security,
+                rng,
+            )?;
+
+            let weight = (Integer::ONE << j).complete();
+            let nonce_to_weight: Integer = nonce
+                .pow_mod_ref(&weight, n)
+                .ok_or(BadExponent::undefined())?
+                .into();
+            sum_nonce = (sum_nonce * nonce_to_weight).modulo(n);
+            bits.push(BitProof {
+                c,
+                commitment,
+                proof,
+            });
+        }
+
+        let difference = super::difference(data.key, data.c, &bits)?;
+        let sum_nonce_inv = sum_nonce.invert(n).map_err(|_| BadExponent::undefined())?;
+        let zero_nonce = (pdata.nonce * sum_nonce_inv).modulo(n);
+        let (zero_commitment, zero_proof) = zero::non_interactive::prove_with_transcript(
+            transcript,
+            zero::Data {
+                key: data.key,
+                c: &difference,
+            },
+            zero::PrivateData { nonce: &zero_nonce },
+            &security.zero(),
+            rng,
+        )?;
+
+        Ok(Proof {
+            bits,
+            zero_commitment,
+            zero_proof,
+        })
+    }
+
+    /// Verify the proof, deriving challenges independently from same data
+    pub fn verify<D: Digest>(
+        shared_state: D,
+        data: Data,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenges
+    /// independently from same data
+    pub fn verify_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let n = data.key.n();
+        fail_if(
+            InvalidProofReason::RangeCheck(1),
+            security.l < n.significant_bits() as usize,
+        )
+        .map_err(|err| InvalidProof::from(err).with_key(n))?;
+        fail_if(
+            InvalidProofReason::LengthMismatch,
+            proof.bits.len() == security.l,
+        )
+        .map_err(|err| InvalidProof::from(err).with_key(n))?;
+        absorb_statement(transcript, data, security);
+
+        let bit_security = security.bit();
+        for bit in &proof.bits {
+            bit::non_interactive::verify_with_transcript(
+                transcript,
+                bit::Data {
+                    key: data.key,
+                    c: &bit.c,
+                },
+                &bit.commitment,
+                &bit_security,
+                &bit.proof,
+            )?;
+        }
+
+        let difference = super::difference(data.key, data.c, &proof.bits)
+            .map_err(|_| InvalidProof::from(InvalidProofReason::PaillierOp).with_key(n))?;
+        zero::non_interactive::verify_with_transcript(
+            transcript,
+            zero::Data {
+                key: data.key,
+                c: &difference,
+            },
+            &proof.zero_commitment,
+            &security.zero(),
+            &proof.zero_proof,
+        )
+    }
+
+    fn absorb_statement<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        security: &SecurityParams,
+    ) {
+        transcript.label(b"paillier_zk.exact_range");
+        transcript.absorb_value(b"N", data.key.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_value(b"l", &Integer::from(security.l));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::Encode;
+    use crate::common::test::random_key;
+    use crate::common::InvalidProofReason;
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(32, (Integer::ONE << 128_u32).complete())
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let key = random_key(&mut rng).unwrap().encryption_key().clone();
+        let shared_state = sha2::Sha256::default();
+
+        // Both ends of the range are accepted
+        for x in [Integer::ZERO, (Integer::ONE << security.l).complete() - 1u8] {
+            let (c, nonce) = key.encrypt_with_random(&mut rng, &x).unwrap();
+            let data = super::Data { key: &key, c: &c };
+            let pdata = super::PrivateData {
+                plaintext: &x,
+                nonce: &nonce,
+            };
+            let proof = super::non_interactive::prove(
+                shared_state.clone(),
+                data,
+                pdata,
+                &security,
+                &mut rng,
+            )
+            .unwrap();
+
+            let modulus_bits = key.n().significant_bits() as usize;
+            assert!(
+                super::non_interactive::Proof::schema(&security, modulus_bits)
+                    .conforms(&proof.to_canonical_bytes())
+            );
+
+            super::non_interactive::verify(shared_state.clone(), data, &security, &proof)
+                .expect("proof failed");
+        }
+    }
+
+    #[test]
+    fn failing() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let key = random_key(&mut rng).unwrap().encryption_key().clone();
+        let shared_state = sha2::Sha256::default();
+
+        // Honest prover refuses to prove plaintext out of the range
+        for x in [Integer::from(-1), (Integer::ONE << security.l).complete()] {
+            let (c, nonce) = key.encrypt_with_random(&mut rng, &x).unwrap();
+            let data = super::Data { key: &key, c: &c };
+            let pdata = super::PrivateData {
+                plaintext: &x,
+                nonce: &nonce,
+            };
+            super::non_interactive::prove(shared_state.clone(), data, pdata, &security, &mut rng)
+                .expect_err("plaintext is out of the range");
+        }
+
+        // Proof doesn't verify for other ciphertext
+        let x = Integer::from(42);
+        let (c, nonce) = key.encrypt_with_random(&mut rng, &x).unwrap();
+        let data = super::Data { key: &key, c: &c };
+        let pdata = super::PrivateData {
+            plaintext: &x,
+            nonce: &nonce,
+        };
+        let mut proof =
+            super::non_interactive::prove(shared_state.clone(), data, pdata, &security, &mut rng)
+                .unwrap();
+        let other_c = key.oadd(&c, &c).unwrap();
+        let other_data = super::Data {
+            key: &key,
+            c: &other_c,
+        };
+        super::non_interactive::verify(shared_state.clone(), other_data, &security, &proof)
+            .expect_err("proof should fail");
+
+        // Dropping the most significant bit is detected
+        proof.bits.pop();
+        let r = super::non_interactive::verify(shared_state, data, &security, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::LengthMismatch)
+        );
+    }
+}
@@ -0,0 +1,671 @@
+//! ZK-proof of knowledge of factorization of RSA modulus, due to Poupard and Stern
+//!
+//! ## Description
+//!
+//! A party P has a modulus `N = pq`. P wants to prove to a verifier V that it knows
+//! the factorization of `N`, without disclosing `p` and `q`.
+//!
+//! Prover knows `N - φ(N) = p + q - 1`. Given random bases `z_i`, prover commits to
+//! `x_i = z_i^r mod N` and responds with `y = r + (N - φ(N)) e` computed over
+//! integers. Verifier checks that `y` is small and that `z_i^(y - N e) = x_i mod N`.
+//!
+//! The proof complements [Пfac](crate::no_small_factor): Пfac shows that factors of
+//! `N` are not small, while this proof shows that the prover actually knows them. It
+//! doesn't require ring-pedersen parameters of the verifier.
+//!
+//! Given:
+//! - `N` - RSA modulus
+//!
+//! Prove:
+//! - prover knows `p` and `q` such that `N = pq`
+//!
+//! Disclosing only: `N`
+//!
+//! Honest prover must have factors of similar size: `p + q` must fit into
+//! `|N|/2 + 1` bits, otherwise the proof doesn't verify.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use paillier_zk::factorization_knowledge as p;
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! let security = p::SecurityParams::new(128, 80, 1);
+//!
+//! // 1. Setup: prover prepares the paillier keys
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//!
+//! // 2. Prover computes a non-interactive proof that it knows the factorization:
+//!
+//! let data = p::Data { n: private_key.n() };
+//! let (commitment, proof) = p::non_interactive::prove(
+//!     shared_state_prover,
+//!     data,
+//!     p::PrivateData { p: private_key.p(), q: private_key.q() },
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // 3. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data, _: &p::Commitment, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 4. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// Bit size of the challenge
+    pub k: usize,
+    /// Statistical security parameter: response `y` hides `N - φ(N)` up to statistical
+    /// distance `2^-statistical`
+    pub statistical: usize,
+    /// Amount of random bases `z_i`. Each base costs an exponentiation for both
+    /// prover and verifier
+    pub bases: usize,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(k: usize, statistical: usize, bases: usize) -> Self {
+        Self {
+            k,
+            statistical,
+            bases,
+        }
+    }
+
+    /// Bit size of prover's mask `r`, and upper bound on bit size of response `y`
+    fn mask_bits(&self, modulus_bits: usize) -> usize {
+        modulus_bits.div_ceil(2) + 1 + self.k + self.statistical
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N - rsa modulus
+    pub n: &'a Integer,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    pub p: &'a Integer,
+    pub q: &'a Integer,
+}
+
+/// Random bases `z_i`. Obtained by [`interactive::bases`] or derived
+/// deterministically in the non-interactive proof
+pub type Bases = Vec<Integer>;
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment {
+    /// `x_i = z_i^r mod N` for each base
+    pub x: Vec<Integer>,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub r: Integer,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub y: Integer,
+}
+
+impl_encode! { Data<'a> { n } }
+impl_encode! { Commitment { x } }
+impl_decode! { Commitment { x } }
+impl_encode! { Proof { y } }
+impl_decode! { Proof { y } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default().sequence(
+            "x",
+            security.bases,
+            Schema::default().integer("x", modulus_bits),
+        )
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default().integer("y", security.mask_bits(modulus_bits))
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 4 rounds:
+/// verifier sends random bases, prover commits to data, verifier responds with a
+/// random challenge, and prover gives proof with commitment and challenge.
+pub mod interactive {
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_degenerate, fail_if_ne, fail_if_not_coprime, fail_if_zero_challenge,
+        IntegerExt, InvalidProofReason,
+    };
+    use crate::{BadExponent, Error, InvalidProof};
+
+    use super::{
+        Challenge, Commitment, Data, PrivateCommitment, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Generate random bases
+    ///
+    /// Bases are chosen by verifier before prover commits
+    pub fn bases<R: RngCore>(data: Data, security: &SecurityParams, rng: &mut R) -> super::Bases {
+        (0..security.bases)
+            .map(|_| Integer::gen_invertible(data.n, rng))
+            .collect()
+    }
+
+    /// Create random commitment
+    pub fn commit<R: RngCore>(
+        data: Data,
+        bases: &[Integer],
+        _pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        let mask_bits = security.mask_bits(data.n.significant_bits() as usize);
+        let r = (Integer::ONE << mask_bits)
+            .complete()
+            .random_below(&mut fast_paillier::utils::external_rand(rng));
+        crate::common::observe_randomness!(&r);
+        let x = bases
+            .iter()
+            .map(|z| {
+                z.pow_mod_ref(&r, data.n)
+                    .map(Integer::from)
+                    .ok_or(BadExponent::undefined())
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((Commitment { x }, PrivateCommitment { r }))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        _data: Data,
+        pdata: PrivateData,
+        private_commitment: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        // N - φ(N) = p + q - 1
+        let n_minus_phi = (pdata.p + pdata.q).complete() - 1u8;
+        let y = &private_commitment.r + (n_minus_phi * challenge);
+        Ok(Proof { y })
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        data: Data,
+        bases: &[Integer],
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check(data, bases, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(data.n))
+    }
+
+    fn check(
+        data: Data,
+        bases: &[Integer],
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let n = data.n;
+        fail_if(InvalidProofReason::ModulusIsEven, n.is_odd())?;
+        fail_if(
+            InvalidProofReason::LengthMismatch,
+            !bases.is_empty() && bases.len() == security.bases && commitment.x.len() == bases.len(),
+        )?;
+        fail_if_zero_challenge(challenge)?;
+        fail_if(
+            InvalidProofReason::ChallengeOutOfRange,
+            challenge.cmp0().is_ge() && challenge.significant_bits() as usize <= security.k,
+        )?;
+        fail_if_not_coprime(n, bases)?;
+        fail_if_degenerate(n, &commitment.x)?;
+
+        {
+            let mask_bits = security.mask_bits(n.significant_bits() as usize);
+            fail_if(
+                InvalidProofReason::RangeCheck(1),
+                proof.y.cmp0().is_ge() && proof.y.significant_bits() as usize <= mask_bits,
+            )?;
+        }
+        crate::common::lap!(Check("range"));
+
+        // z^(y - N e) = z^(r - φ(N) e) = z^r
+        let exponent = &proof.y - (n * challenge).complete();
+        for (i, (z, x)) in bases.iter().zip(&commitment.x).enumerate() {
+            let lhs: Integer = z
+                .pow_mod_ref(&exponent, n)
+                .ok_or(InvalidProofReason::ModPow)?
+                .into();
+            fail_if_ne(InvalidProofReason::EqualityCheck(i + 1), &lhs, x)?;
+        }
+        crate::common::lap!(Check("factorization knowledge"));
+
+        Ok(())
+    }
+
+    /// Generate random challenge
+    ///
+    /// `security` parameter is used to generate challenge in correct range
+    pub fn challenge<R: RngCore>(security: &SecurityParams, rng: &mut R) -> Challenge {
+        (Integer::ONE << security.k)
+            .complete()
+            .random_below(&mut fast_paillier::utils::external_rand(rng))
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{Bases, Challenge, Commitment, Data, PrivateData, Proof, SecurityParams};
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic bases and challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<R: RngCore, D: Digest>(
+        shared_state: D,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error> {
+        let bases = bases_with_transcript(transcript, data, security);
+        let (comm, pcomm) = super::interactive::commit(data, &bases, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_after_bases(transcript, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving bases and challenge independently from same data
+    pub fn verify<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving bases and challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let bases = bases_with_transcript(transcript, data, security);
+        let challenge = challenge_after_bases(transcript, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(data, &bases, commitment, security, &challenge, proof)
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        bases_with_transcript(transcript, data, security);
+        challenge_after_bases(transcript, commitment, security)
+    }
+
+    /// Deterministically compute bases from the protocol transcript
+    ///
+    /// Absorbs statement into the `transcript`
+    pub fn bases_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        security: &SecurityParams,
+    ) -> Bases {
+        transcript.label(b"paillier_zk.factorization_knowledge");
+        transcript.absorb_value(b"N", data.n);
+        let mut rng = transcript.challenge_rng(b"z");
+        super::interactive::bases(data, security, &mut rng)
+    }
+
+    fn challenge_after_bases<D: Digest>(
+        transcript: &mut Transcript<D>,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use rug::{Complete, Integer};
+
+    use crate::common::extract::{challenge_diff, ensure, linear_response};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        /// Smaller factor
+        pub p: Integer,
+        /// Larger factor
+        pub q: Integer,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    ///
+    /// Succeeds if the prover responded with `N - φ(N)`. Prover responding with
+    /// other multiple of the order of the bases isn't covered.
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        // y1 - y2 = (e1 - e2) (N - φ(N))
+        let n_minus_phi = linear_response(&proof1.y, &proof2.y, &e_diff)?;
+        // p and q are roots of `X^2 - (p + q) X + N`
+        let sum = n_minus_phi + 1u8;
+        let discriminant = (sum.square_ref().complete() - (data.n * 4u8).complete()).abs();
+        let root = discriminant.sqrt_ref().complete();
+        let p = (&sum - &root).complete() >> 1u32;
+        let q = (&sum + &root).complete() >> 1u32;
+
+        ensure(p > 1 && (&p * &q).complete() == *data.n)?;
+        Ok(Witness { p, q })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::Encode;
+    use crate::common::test::generate_blum_prime;
+    use crate::common::InvalidProofReason;
+
+    struct Statement {
+        n: Integer,
+        p: Integer,
+        q: Integer,
+    }
+
+    impl Statement {
+        fn data(&self) -> super::Data<'_> {
+            super::Data { n: &self.n }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_> {
+            super::PrivateData {
+                p: &self.p,
+                q: &self.q,
+            }
+        }
+    }
+
+    fn statement(rng: &mut rand_dev::DevRng) -> Statement {
+        let p = generate_blum_prime(rng, 512);
+        let q = generate_blum_prime(rng, 512);
+        let n = (&p * &q).complete();
+        Statement { n, p, q }
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(128, 80, 2)
+    }
+
+    fn run(statement: &Statement) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        let modulus_bits = statement.n.significant_bits() as usize;
+        assert!(super::Commitment::schema(&security, modulus_bits)
+            .conforms(&commitment.to_canonical_bytes()));
+        assert!(super::Proof::schema(&security, modulus_bits).conforms(&proof.to_canonical_bytes()));
+
+        super::non_interactive::verify(
+            shared_state,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let statement = statement(&mut rng);
+        run(&statement).expect("proof failed");
+    }
+
+    #[test]
+    fn failing() {
+        let mut rng = rand_dev::DevRng::new();
+        let mut statement = statement(&mut rng);
+        // Prover doesn't know the factorization
+        statement.p += 2;
+        let r = run(&statement).map_err(|e| e.reason());
+        assert_eq!(r, Err(InvalidProofReason::EqualityCheck(1)));
+    }
+
+    #[test]
+    fn degenerate_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let bases = super::interactive::bases(data, &security, &mut rng);
+        let (commitment, pcommitment) =
+            super::interactive::commit(data, &bases, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r =
+            super::interactive::verify(data, &bases, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Challenge outside of `[0; 2^k)` is rejected even for an honest proof
+        let two_to_k = (Integer::ONE << security.k).complete();
+        for challenge in [Integer::from(-1), two_to_k] {
+            let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+            let r = super::interactive::verify(
+                data,
+                &bases,
+                &commitment,
+                &security,
+                &challenge,
+                &proof,
+            );
+            assert_eq!(
+                r.map_err(|e| e.reason()).err(),
+                Some(InvalidProofReason::ChallengeOutOfRange)
+            );
+        }
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let bases = super::interactive::bases(data, &security, &mut rng);
+        let (_commitment, pcommitment) =
+            super::interactive::commit(data, &bases, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        let mut expected = [statement.p, statement.q];
+        expected.sort();
+        assert_eq!([witness.p, witness.q], expected);
+    }
+}
@@ -0,0 +1,531 @@
+//! FFI-friendly representation of errors
+//!
+//! Errors of the crate carry rich reasons which can't cross an FFI boundary. This
+//! module flattens them into [`FfiError`], a `#[repr(C)]` pair of a stable
+//! [`ErrorCode`] and a detail specific to the code (e.g. index of the failed check).
+//!
+//! Codes are stable: a code is never renumbered or reused for a different error, new
+//! errors get new codes. Code `0` always means success. Codes in `1xx` are returned
+//! by verification ([`InvalidProof`]), codes in `2xx` are returned by proving
+//! ([`Error`]), codes in `3xx` are returned by `extern "C"` functions of `bindings`
+//! module and JS functions of `wasm` module, available with `ffi` and
+//! `wasm-bindings` features respectively.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{ffi::FfiError, paillier_encryption_in_range as p};
+//! # fn doc_fn(aux: &p::Aux, data: p::Data, security: &p::SecurityParams,
+//! #     commitment: &p::Commitment, proof: &p::Proof) -> FfiError {
+//! let result = p::non_interactive::verify(
+//!     sha2::Sha256::default(),
+//!     aux,
+//!     data,
+//!     commitment,
+//!     security,
+//!     proof,
+//! );
+//! match result {
+//!     Ok(()) => FfiError::OK,
+//!     Err(err) => FfiError::from(&err),
+//! }
+//! # }
+//! ```
+
+use crate::common::{InvalidProof, InvalidProofReason, WeakAux};
+use crate::{Error, ErrorReason};
+
+#[cfg(feature = "ffi")]
+pub mod bindings;
+#[cfg(feature = "wasm-bindings")]
+pub mod wasm;
+
+/// Stable error code
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// No error
+    Ok = 0,
+
+    /// Equality check failed, detail is the index of the check
+    EqualityCheck = 100,
+    /// Range check failed, detail is the index of the check
+    RangeCheck = 101,
+    /// Encryption of supplied data failed when attempting to verify
+    VerifyEncryption = 102,
+    /// Paillier encryption failed when attempting to verify
+    PaillierEnc = 103,
+    /// Paillier homomorphic operation failed when attempting to verify
+    PaillierOp = 104,
+    /// Failed to evaluate powmod when attempting to verify
+    VerifyModPow = 105,
+    /// Paillier-Blum modulus is prime
+    ModulusIsPrime = 106,
+    /// Paillier-Blum modulus is even
+    ModulusIsEven = 107,
+    /// Incorrect n-th root
+    IncorrectNthRoot = 108,
+    /// Incorrect 4-th root
+    IncorrectFourthRoot = 109,
+    /// Challenge is zero
+    ZeroChallenge = 110,
+    /// Commitment is a degenerate value
+    DegenerateCommitment = 111,
+    /// Commitments don't match verifiers
+    VerifiersMismatch = 112,
+    /// Statement uses the same key where distinct keys are required
+    SameKeys = 113,
+    /// Value supplied by prover is not coprime with the modulus
+    NotCoprime = 114,
+    /// Amount of values doesn't match the statement
+    LengthMismatch = 115,
+    /// Authentication tag of a stored challenge doesn't match
+    ChallengeTag = 116,
+    /// Stored challenge doesn't match the one derived from the transcript
+    ChallengeMismatch = 117,
+    /// Proof has less iterations than required, detail is amount of iterations in
+    /// the proof
+    TooFewIterations = 118,
+    /// Non-residue doesn't have Jacobi symbol -1
+    WrongJacobiSymbol = 119,
+    /// Challenge is out of the challenge set of the protocol
+    ChallengeOutOfRange = 120,
+
+    /// Failed to evaluate powmod when attempting to prove
+    ProveModPow = 200,
+    /// Couldn't find residue
+    FindResidue = 201,
+    /// Couldn't encrypt a message
+    ProveEncryption = 202,
+    /// Can't find multiplicative inverse
+    Invert = 203,
+    /// Paillier error
+    Paillier = 204,
+    /// Internal bug: vec has unexpected length
+    Length = 205,
+    /// Ring-pedersen `N^` is too small, detail is its bit size
+    WeakAuxSmallModulus = 206,
+    /// Ring-pedersen `N^` is even
+    WeakAuxEvenModulus = 207,
+    /// Ring-pedersen `s` or `t` is out of range
+    WeakAuxOutOfRange = 208,
+    /// Ring-pedersen `s` or `t` is too small
+    WeakAuxSmallParameter = 209,
+    /// Ring-pedersen `s` equals to `t`
+    WeakAuxEqualParameters = 210,
+    /// Produced proof doesn't verify, detail is the code of verification error
+    SelfCheck = 211,
+    /// Plaintext is not in the interval it's proven to be in
+    OutOfInterval = 212,
+    /// Witness is for another branch than the private commitment
+    WitnessMismatch = 213,
+    /// Commitment randomness repeats, RNG is broken
+    RepeatedRandomness = 214,
+    /// External storage of the witness failed
+    WitnessStorage = 215,
+    /// Witness bound exceeds bit size of the curve order
+    BoundExceedsCurve = 216,
+    /// Security parameters are incompatible with the statement
+    IncompatibleParams = 217,
+
+    /// Input isn't a canonical encoding of the expected value
+    InvalidEncoding = 300,
+    /// Output buffer is too small
+    BufferTooSmall = 301,
+    /// Input is encoded correctly but isn't acceptable, e.g. null pointer
+    InvalidArgument = 302,
+    /// Internal bug: unexpected panic
+    Panic = 303,
+}
+
+impl ErrorCode {
+    const ALL: &'static [ErrorCode] = &[
+        Self::Ok,
+        Self::EqualityCheck,
+        Self::RangeCheck,
+        Self::VerifyEncryption,
+        Self::PaillierEnc,
+        Self::PaillierOp,
+        Self::VerifyModPow,
+        Self::ModulusIsPrime,
+        Self::ModulusIsEven,
+        Self::IncorrectNthRoot,
+        Self::IncorrectFourthRoot,
+        Self::ZeroChallenge,
+        Self::DegenerateCommitment,
+        Self::VerifiersMismatch,
+        Self::SameKeys,
+        Self::NotCoprime,
+        Self::LengthMismatch,
+        Self::ChallengeTag,
+        Self::ChallengeMismatch,
+        Self::TooFewIterations,
+        Self::WrongJacobiSymbol,
+        Self::ChallengeOutOfRange,
+        Self::ProveModPow,
+        Self::FindResidue,
+        Self::ProveEncryption,
+        Self::Invert,
+        Self::Paillier,
+        Self::Length,
+        Self::WeakAuxSmallModulus,
+        Self::WeakAuxEvenModulus,
+        Self::WeakAuxOutOfRange,
+        Self::WeakAuxSmallParameter,
+        Self::WeakAuxEqualParameters,
+        Self::SelfCheck,
+        Self::OutOfInterval,
+        Self::WitnessMismatch,
+        Self::RepeatedRandomness,
+        Self::WitnessStorage,
+        Self::BoundExceedsCurve,
+        Self::IncompatibleParams,
+        Self::InvalidEncoding,
+        Self::BufferTooSmall,
+        Self::InvalidArgument,
+        Self::Panic,
+    ];
+}
+
+impl From<ErrorCode> for u32 {
+    fn from(code: ErrorCode) -> Self {
+        code as u32
+    }
+}
+
+impl TryFrom<u32> for ErrorCode {
+    type Error = UnknownErrorCode;
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|c| u32::from(*c) == code)
+            .ok_or(UnknownErrorCode(code))
+    }
+}
+
+/// Error code is not known to this version of the library
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("unknown error code {0}")]
+pub struct UnknownErrorCode(pub u32);
+
+/// Flattened error that can be passed through FFI
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FfiError {
+    /// [`ErrorCode`] as integer
+    pub code: u32,
+    /// Detail specific to the code, zero if the code has no detail
+    pub detail: u32,
+}
+
+impl FfiError {
+    /// No error
+    pub const OK: Self = Self::new(ErrorCode::Ok, 0);
+
+    const fn new(code: ErrorCode, detail: u32) -> Self {
+        Self {
+            code: code as u32,
+            detail,
+        }
+    }
+
+    /// Error code, if known to this version of the library
+    pub fn code(&self) -> Result<ErrorCode, UnknownErrorCode> {
+        ErrorCode::try_from(self.code)
+    }
+
+    /// Checks whether it's [`FfiError::OK`]
+    pub fn is_ok(&self) -> bool {
+        self.code == ErrorCode::Ok as u32
+    }
+}
+
+fn index(i: usize) -> u32 {
+    u32::try_from(i).unwrap_or(u32::MAX)
+}
+
+impl From<&InvalidProof> for FfiError {
+    fn from(err: &InvalidProof) -> Self {
+        match err.reason() {
+            InvalidProofReason::EqualityCheck(i) => Self::new(ErrorCode::EqualityCheck, index(i)),
+            InvalidProofReason::RangeCheck(i) => Self::new(ErrorCode::RangeCheck, index(i)),
+            InvalidProofReason::Encryption => Self::new(ErrorCode::VerifyEncryption, 0),
+            InvalidProofReason::PaillierEnc => Self::new(ErrorCode::PaillierEnc, 0),
+            InvalidProofReason::PaillierOp => Self::new(ErrorCode::PaillierOp, 0),
+            InvalidProofReason::ModPow => Self::new(ErrorCode::VerifyModPow, 0),
+            InvalidProofReason::ModulusIsPrime => Self::new(ErrorCode::ModulusIsPrime, 0),
+            InvalidProofReason::ModulusIsEven => Self::new(ErrorCode::ModulusIsEven, 0),
+            InvalidProofReason::IncorrectNthRoot => Self::new(ErrorCode::IncorrectNthRoot, 0),
+            InvalidProofReason::IncorrectFourthRoot => Self::new(ErrorCode::IncorrectFourthRoot, 0),
+            InvalidProofReason::ZeroChallenge => Self::new(ErrorCode::ZeroChallenge, 0),
+            InvalidProofReason::DegenerateCommitment => {
+                Self::new(ErrorCode::DegenerateCommitment, 0)
+            }
+            InvalidProofReason::VerifiersMismatch => Self::new(ErrorCode::VerifiersMismatch, 0),
+            InvalidProofReason::SameKeys => Self::new(ErrorCode::SameKeys, 0),
+            InvalidProofReason::NotCoprime => Self::new(ErrorCode::NotCoprime, 0),
+            InvalidProofReason::LengthMismatch => Self::new(ErrorCode::LengthMismatch, 0),
+            InvalidProofReason::ChallengeTag => Self::new(ErrorCode::ChallengeTag, 0),
+            InvalidProofReason::ChallengeMismatch => Self::new(ErrorCode::ChallengeMismatch, 0),
+            InvalidProofReason::TooFewIterations(m) => {
+                Self::new(ErrorCode::TooFewIterations, index(m))
+            }
+            InvalidProofReason::WrongJacobiSymbol => Self::new(ErrorCode::WrongJacobiSymbol, 0),
+            InvalidProofReason::ChallengeOutOfRange => Self::new(ErrorCode::ChallengeOutOfRange, 0),
+            // Flattened to the index of the check
+            InvalidProofReason::ResponseOutOfRange { check, .. } => {
+                Self::new(ErrorCode::RangeCheck, index(check))
+            }
+        }
+    }
+}
+
+impl From<InvalidProof> for FfiError {
+    fn from(err: InvalidProof) -> Self {
+        Self::from(&err)
+    }
+}
+
+impl From<&Error> for FfiError {
+    fn from(err: &Error) -> Self {
+        Self::from_reason(&err.0)
+    }
+}
+
+impl FfiError {
+    fn from_reason(reason: &ErrorReason) -> Self {
+        match reason {
+            ErrorReason::ModPow(_) => Self::new(ErrorCode::ProveModPow, 0),
+            ErrorReason::FindResidue => Self::new(ErrorCode::FindResidue, 0),
+            ErrorReason::Encryption => Self::new(ErrorCode::ProveEncryption, 0),
+            ErrorReason::Invert => Self::new(ErrorCode::Invert, 0),
+            ErrorReason::Paillier(_) => Self::new(ErrorCode::Paillier, 0),
+            ErrorReason::Length => Self::new(ErrorCode::Length, 0),
+            ErrorReason::WeakAux(WeakAux::SmallModulus(bits)) => {
+                Self::new(ErrorCode::WeakAuxSmallModulus, *bits)
+            }
+            ErrorReason::WeakAux(WeakAux::EvenModulus) => {
+                Self::new(ErrorCode::WeakAuxEvenModulus, 0)
+            }
+            ErrorReason::WeakAux(WeakAux::OutOfRange) => Self::new(ErrorCode::WeakAuxOutOfRange, 0),
+            ErrorReason::WeakAux(WeakAux::SmallParameter) => {
+                Self::new(ErrorCode::WeakAuxSmallParameter, 0)
+            }
+            ErrorReason::WeakAux(WeakAux::EqualParameters) => {
+                Self::new(ErrorCode::WeakAuxEqualParameters, 0)
+            }
+            ErrorReason::MalformedMessage(err) => Self::from(err),
+            ErrorReason::SelfCheck(err) => Self::new(ErrorCode::SelfCheck, Self::from(err).code),
+            ErrorReason::OutOfInterval => Self::new(ErrorCode::OutOfInterval, 0),
+            ErrorReason::WitnessMismatch => Self::new(ErrorCode::WitnessMismatch, 0),
+            ErrorReason::RepeatedRandomness => Self::new(ErrorCode::RepeatedRandomness, 0),
+            ErrorReason::Witness(_) => Self::new(ErrorCode::WitnessStorage, 0),
+            ErrorReason::BoundExceedsCurve(_) => Self::new(ErrorCode::BoundExceedsCurve, 0),
+            ErrorReason::IncompatibleParams(_) => Self::new(ErrorCode::IncompatibleParams, 0),
+            // Same as the verification error itself, tags are not preserved
+            ErrorReason::InvalidProof(err) => Self::from(err),
+            ErrorReason::InProof(_, reason) => Self::from_reason(reason),
+        }
+    }
+}
+
+impl From<Error> for FfiError {
+    fn from(err: Error) -> Self {
+        Self::from(&err)
+    }
+}
+
+/// Restores verification error from its flattened form
+///
+/// Fails if the code is unknown or isn't a verification error.
+impl TryFrom<FfiError> for InvalidProof {
+    type Error = UnknownErrorCode;
+    fn try_from(err: FfiError) -> Result<Self, Self::Error> {
+        let detail = usize::try_from(err.detail).map_err(|_| UnknownErrorCode(err.code))?;
+        let reason = match err.code()? {
+            ErrorCode::EqualityCheck => InvalidProofReason::EqualityCheck(detail),
+            ErrorCode::RangeCheck => InvalidProofReason::RangeCheck(detail),
+            ErrorCode::VerifyEncryption => InvalidProofReason::Encryption,
+            ErrorCode::PaillierEnc => InvalidProofReason::PaillierEnc,
+            ErrorCode::PaillierOp => InvalidProofReason::PaillierOp,
+            ErrorCode::VerifyModPow => InvalidProofReason::ModPow,
+            ErrorCode::ModulusIsPrime => InvalidProofReason::ModulusIsPrime,
+            ErrorCode::ModulusIsEven => InvalidProofReason::ModulusIsEven,
+            ErrorCode::IncorrectNthRoot => InvalidProofReason::IncorrectNthRoot,
+            ErrorCode::IncorrectFourthRoot => InvalidProofReason::IncorrectFourthRoot,
+            ErrorCode::ZeroChallenge => InvalidProofReason::ZeroChallenge,
+            ErrorCode::DegenerateCommitment => InvalidProofReason::DegenerateCommitment,
+            ErrorCode::VerifiersMismatch => InvalidProofReason::VerifiersMismatch,
+            ErrorCode::SameKeys => InvalidProofReason::SameKeys,
+            ErrorCode::NotCoprime => InvalidProofReason::NotCoprime,
+            ErrorCode::LengthMismatch => InvalidProofReason::LengthMismatch,
+            ErrorCode::ChallengeTag => InvalidProofReason::ChallengeTag,
+            ErrorCode::ChallengeMismatch => InvalidProofReason::ChallengeMismatch,
+            ErrorCode::TooFewIterations => InvalidProofReason::TooFewIterations(detail),
+            ErrorCode::WrongJacobiSymbol => InvalidProofReason::WrongJacobiSymbol,
+            ErrorCode::ChallengeOutOfRange => InvalidProofReason::ChallengeOutOfRange,
+            _ => return Err(UnknownErrorCode(err.code)),
+        };
+        Ok(reason.into())
+    }
+}
+
+/// Restores ring-pedersen sanity error from its flattened form
+///
+/// Fails if the code is unknown or isn't a weak aux error.
+impl TryFrom<FfiError> for WeakAux {
+    type Error = UnknownErrorCode;
+    fn try_from(err: FfiError) -> Result<Self, Self::Error> {
+        match err.code()? {
+            ErrorCode::WeakAuxSmallModulus => Ok(WeakAux::SmallModulus(err.detail)),
+            ErrorCode::WeakAuxEvenModulus => Ok(WeakAux::EvenModulus),
+            ErrorCode::WeakAuxOutOfRange => Ok(WeakAux::OutOfRange),
+            ErrorCode::WeakAuxSmallParameter => Ok(WeakAux::SmallParameter),
+            ErrorCode::WeakAuxEqualParameters => Ok(WeakAux::EqualParameters),
+            _ => Err(UnknownErrorCode(err.code)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ErrorCode, FfiError, UnknownErrorCode};
+    use crate::common::{InvalidProof, InvalidProofReason, WeakAux};
+    use crate::{Error, ErrorReason};
+
+    #[test]
+    fn codes_round_trip() {
+        for &code in ErrorCode::ALL {
+            assert_eq!(ErrorCode::try_from(u32::from(code)), Ok(code));
+        }
+        let mut codes = ErrorCode::ALL.iter().map(|c| *c as u32).collect::<Vec<_>>();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), ErrorCode::ALL.len());
+
+        assert_eq!(ErrorCode::try_from(1), Err(UnknownErrorCode(1)));
+        // Codes are stable
+        assert_eq!(ErrorCode::EqualityCheck as u32, 100);
+        assert_eq!(ErrorCode::SameKeys as u32, 113);
+        assert_eq!(ErrorCode::ProveModPow as u32, 200);
+        assert_eq!(ErrorCode::WeakAuxEqualParameters as u32, 210);
+        assert_eq!(ErrorCode::InvalidEncoding as u32, 300);
+        assert_eq!(ErrorCode::Panic as u32, 303);
+    }
+
+    #[test]
+    fn invalid_proof_round_trip() {
+        let reasons = [
+            InvalidProofReason::EqualityCheck(3),
+            InvalidProofReason::RangeCheck(7),
+            InvalidProofReason::Encryption,
+            InvalidProofReason::PaillierEnc,
+            InvalidProofReason::PaillierOp,
+            InvalidProofReason::ModPow,
+            InvalidProofReason::ModulusIsPrime,
+            InvalidProofReason::ModulusIsEven,
+            InvalidProofReason::IncorrectNthRoot,
+            InvalidProofReason::IncorrectFourthRoot,
+            InvalidProofReason::ZeroChallenge,
+            InvalidProofReason::DegenerateCommitment,
+            InvalidProofReason::VerifiersMismatch,
+            InvalidProofReason::SameKeys,
+            InvalidProofReason::NotCoprime,
+            InvalidProofReason::LengthMismatch,
+            InvalidProofReason::ChallengeTag,
+            InvalidProofReason::ChallengeMismatch,
+            InvalidProofReason::TooFewIterations(8),
+            InvalidProofReason::WrongJacobiSymbol,
+            InvalidProofReason::ChallengeOutOfRange,
+        ];
+        for reason in reasons {
+            let ffi = FfiError::from(&InvalidProof::from(reason));
+            assert!(!ffi.is_ok());
+            assert!((100..200).contains(&ffi.code));
+            let restored = InvalidProof::try_from(ffi).unwrap();
+            assert_eq!(restored.reason(), reason);
+
+            // Converted into general error, tagged or not, it has the same code
+            let err = Error::from(InvalidProof::from(reason));
+            assert_eq!(FfiError::from(&err), ffi);
+            let err = err.in_proof("paillier_encryption_in_range");
+            assert_eq!(err.proof(), Some("paillier_encryption_in_range"));
+            assert_eq!(err.invalid_proof().map(|e| e.reason()), Some(reason));
+            assert_eq!(FfiError::from(&err), ffi);
+        }
+
+        // Range checks naming the witness are flattened to the index of the check
+        let reason = InvalidProofReason::ResponseOutOfRange {
+            check: 6,
+            witness: "x",
+            bound_bits: 1324,
+            bits: 1400,
+        };
+        let ffi = FfiError::from(&InvalidProof::from(reason));
+        assert_eq!(ffi, FfiError::new(ErrorCode::RangeCheck, 6));
+
+        // Prove errors aren't verification errors
+        let ffi = FfiError::from(&Error::from(ErrorReason::Invert));
+        assert!(InvalidProof::try_from(ffi).is_err());
+        assert!(InvalidProof::try_from(FfiError::OK).is_err());
+    }
+
+    #[test]
+    fn prove_error_round_trip() {
+        let weak = [
+            WeakAux::SmallModulus(512),
+            WeakAux::EvenModulus,
+            WeakAux::OutOfRange,
+            WeakAux::SmallParameter,
+            WeakAux::EqualParameters,
+        ];
+        for weak in weak {
+            let ffi = FfiError::from(&Error::from(ErrorReason::WeakAux(weak)));
+            assert!((200..300).contains(&ffi.code));
+            assert_eq!(WeakAux::try_from(ffi), Ok(weak));
+        }
+
+        let errors = [
+            (ErrorReason::ModPow(crate::BadExponent::undefined()), 200),
+            (ErrorReason::FindResidue, 201),
+            (ErrorReason::Encryption, 202),
+            (ErrorReason::Invert, 203),
+            (ErrorReason::Length, 205),
+            (ErrorReason::OutOfInterval, 212),
+            (ErrorReason::WitnessMismatch, 213),
+            (ErrorReason::RepeatedRandomness, 214),
+            (
+                ErrorReason::Witness(crate::witness::WitnessError::new("offline")),
+                215,
+            ),
+            (
+                ErrorReason::BoundExceedsCurve(crate::compatibility::Incompatible::LargeRange {
+                    l: 1024,
+                    curve_bits: 256,
+                }),
+                216,
+            ),
+            (
+                ErrorReason::IncompatibleParams(
+                    crate::compatibility::Incompatible::LargeChallenge {
+                        q_bits: 257,
+                        l: 256,
+                    },
+                ),
+                217,
+            ),
+        ];
+        for (reason, code) in errors {
+            let ffi = FfiError::from(&Error::from(reason));
+            assert_eq!(ffi.code, code);
+            assert_eq!(u32::from(ffi.code().unwrap()), code);
+            assert!(WeakAux::try_from(ffi).is_err());
+        }
+
+        let err = Error::from(ErrorReason::Invert).in_proof("no_small_factor");
+        assert_eq!(err.proof(), Some("no_small_factor"));
+        assert!(err.invalid_proof().is_none());
+        assert_eq!(FfiError::from(&err).code, 203);
+    }
+}
@@ -0,0 +1,1024 @@
+//! `extern "C"` functions for proving and verification
+//!
+//! Available with `ffi` feature. Proofs are instantiated with secp256k1 curve and
+//! SHA-256, all inputs and outputs are passed in [canonical encoding](crate::encoding):
+//!
+//! * `shared_state` is an arbitrary byte string, it's hashed into the transcript
+//! * `aux` is encoded [`Aux`]
+//! * `security` is encoded security parameters of the proof: `l, epsilon, q`, or
+//!   `l_x, l_y, epsilon, q` for Пaff-g, or `q` for Пmul. Sizes are encoded as integers
+//!   and must not exceed [`MAX_SIZE`] bits
+//! * `statement` and `witness` are encoded fields of `Data` and `PrivateData` of
+//!   the proof in the order of declaration. Paillier keys are given by their modulus
+//! * proof is encoded commitment followed by encoded proof. Пfac has no separate
+//!   commitment, its proof is encoded
+//!   [`non_interactive::Proof`](crate::no_small_factor::non_interactive::Proof)
+//!
+//! Пmod is instantiated with `M = 128` and ignores `aux` and `security`, Пmul
+//! ignores `aux`. Weak `aux` is always rejected when proving.
+//!
+//! Each function returns [`FfiError`]. Prove functions write the proof into `out`
+//! whose capacity is read from `*out_len`. On success, `*out_len` is set to the
+//! length of the proof. If capacity is insufficient, [`ErrorCode::BufferTooSmall`]
+//! is returned and `*out_len` is set to the required capacity.
+//!
+//! Prove functions take 32 bytes `seed` which must be freshly sampled from a
+//! cryptographically secure source. Seed is hashed together with the inputs to
+//! derive randomness of the prover.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use digest::Digest;
+use generic_ec::{curves::Secp256k1, Point, Scalar};
+use rand_core::RngCore;
+use rug::Integer;
+use sha2::Sha256;
+
+use super::{ErrorCode, FfiError};
+use crate::common::encoding::{impl_decode, impl_encode, Decode, Encode};
+use crate::common::rng::HashRng;
+use crate::common::Aux;
+use crate::fast_paillier::EncryptionKey;
+use crate::{
+    group_element_vs_paillier_encryption_in_range as log_star, no_small_factor as fac,
+    paillier_affine_operation_in_range as aff_g, paillier_blum_modulus as pmod,
+    paillier_decryption_modulo_q as dec, paillier_encryption_in_range as enc,
+    paillier_multiplication as mul, witness_escrow as escrow,
+};
+
+type E = Secp256k1;
+
+/// Number of iterations of Пmod
+const MOD_M: usize = 128;
+
+/// Maximal size in bits accepted in `security`, larger values are rejected with
+/// [`ErrorCode::InvalidArgument`] before any integer of that size is built
+pub const MAX_SIZE: usize = 1 << 16;
+
+const INVALID_ENCODING: FfiError = FfiError::new(ErrorCode::InvalidEncoding, 0);
+const INVALID_ARGUMENT: FfiError = FfiError::new(ErrorCode::InvalidArgument, 0);
+
+struct RangeSecurity {
+    l: Integer,
+    epsilon: Integer,
+    q: Integer,
+}
+impl_encode! { RangeSecurity { l, epsilon, q } }
+impl_decode! { RangeSecurity { l, epsilon, q } }
+
+impl RangeSecurity {
+    fn into_parts(self) -> Result<(usize, usize, Integer), FfiError> {
+        Ok((size(&self.l)?, size(&self.epsilon)?, self.q))
+    }
+}
+
+struct AffGSecurity {
+    l_x: Integer,
+    l_y: Integer,
+    epsilon: Integer,
+    q: Integer,
+}
+impl_encode! { AffGSecurity { l_x, l_y, epsilon, q } }
+impl_decode! { AffGSecurity { l_x, l_y, epsilon, q } }
+
+struct MulSecurity {
+    q: Integer,
+}
+impl_encode! { MulSecurity { q } }
+impl_decode! { MulSecurity { q } }
+
+struct EncStatement {
+    n0: Integer,
+    k: Integer,
+}
+impl_encode! { EncStatement { n0, k } }
+impl_decode! { EncStatement { n0, k } }
+
+struct EncWitness {
+    plaintext: Integer,
+    nonce: Integer,
+}
+impl_encode! { EncWitness { plaintext, nonce } }
+impl_decode! { EncWitness { plaintext, nonce } }
+
+struct LogStarStatement {
+    n0: Integer,
+    c: Integer,
+    b: Point<E>,
+    x: Point<E>,
+}
+impl_encode! { LogStarStatement { n0, c, b, x } }
+impl_decode! { LogStarStatement { n0, c, b, x } }
+
+struct LogStarWitness {
+    x: Integer,
+    nonce: Integer,
+}
+impl_encode! { LogStarWitness { x, nonce } }
+impl_decode! { LogStarWitness { x, nonce } }
+
+struct AffGStatement {
+    n0: Integer,
+    n1: Integer,
+    c: Integer,
+    d: Integer,
+    y: Integer,
+    x: Point<E>,
+}
+impl_encode! { AffGStatement { n0, n1, c, d, y, x } }
+impl_decode! { AffGStatement { n0, n1, c, d, y, x } }
+
+struct AffGWitness {
+    x: Integer,
+    y: Integer,
+    nonce: Integer,
+    nonce_y: Integer,
+}
+impl_encode! { AffGWitness { x, y, nonce, nonce_y } }
+impl_decode! { AffGWitness { x, y, nonce, nonce_y } }
+
+struct ModStatement {
+    n: Integer,
+}
+impl_encode! { ModStatement { n } }
+impl_decode! { ModStatement { n } }
+
+struct FactorsWitness {
+    p: Integer,
+    q: Integer,
+}
+impl_encode! { FactorsWitness { p, q } }
+impl_decode! { FactorsWitness { p, q } }
+
+struct FacStatement {
+    n: Integer,
+    n_root: Integer,
+}
+impl_encode! { FacStatement { n, n_root } }
+impl_decode! { FacStatement { n, n_root } }
+
+struct EscrowStatement {
+    n0: Integer,
+    c0: Integer,
+    n1: Integer,
+    c1: Integer,
+    b: Point<E>,
+    x: Point<E>,
+}
+impl_encode! { EscrowStatement { n0, c0, n1, c1, b, x } }
+impl_decode! { EscrowStatement { n0, c0, n1, c1, b, x } }
+
+struct EscrowWitness {
+    x: Integer,
+    nonce0: Integer,
+    nonce1: Integer,
+}
+impl_encode! { EscrowWitness { x, nonce0, nonce1 } }
+impl_decode! { EscrowWitness { x, nonce0, nonce1 } }
+
+struct DecStatement {
+    n0: Integer,
+    c: Integer,
+    x: Scalar<E>,
+}
+impl_encode! { DecStatement { n0, c, x } }
+impl_decode! { DecStatement { n0, c, x } }
+
+struct DecWitness {
+    y: Integer,
+    nonce: Integer,
+}
+impl_encode! { DecWitness { y, nonce } }
+impl_decode! { DecWitness { y, nonce } }
+
+struct MulStatement {
+    n: Integer,
+    x: Integer,
+    y: Integer,
+    c: Integer,
+}
+impl_encode! { MulStatement { n, x, y, c } }
+impl_decode! { MulStatement { n, x, y, c } }
+
+struct MulWitness {
+    x: Integer,
+    nonce: Integer,
+    nonce_x: Integer,
+}
+impl_encode! { MulWitness { x, nonce, nonce_x } }
+impl_decode! { MulWitness { x, nonce, nonce_x } }
+
+/// Inputs shared by prover and verifier
+pub(super) struct Inputs<'a> {
+    pub(super) shared_state: &'a [u8],
+    pub(super) aux: &'a [u8],
+    pub(super) security: &'a [u8],
+    pub(super) statement: &'a [u8],
+}
+
+impl Inputs<'_> {
+    fn shared_state(&self) -> Sha256 {
+        Sha256::new().chain_update(self.shared_state)
+    }
+
+    fn aux(&self) -> Result<Aux, FfiError> {
+        decode(self.aux)
+    }
+}
+
+fn decode<T: Decode>(bytes: &[u8]) -> Result<T, FfiError> {
+    T::from_canonical_bytes(bytes).ok_or(INVALID_ENCODING)
+}
+
+fn decode_proof<C: Decode, P: Decode>(mut bytes: &[u8]) -> Result<(C, P), FfiError> {
+    let commitment = C::decode(&mut bytes).ok_or(INVALID_ENCODING)?;
+    Ok((commitment, decode(bytes)?))
+}
+
+fn encode_proof(commitment: &impl Encode, proof: &impl Encode) -> Vec<u8> {
+    let mut out = commitment.to_canonical_bytes();
+    proof.encode(&mut out);
+    out
+}
+
+fn size(x: &Integer) -> Result<usize, FfiError> {
+    x.to_usize()
+        .filter(|&x| x <= MAX_SIZE)
+        .ok_or(INVALID_ARGUMENT)
+}
+
+/// Paillier key with modulus `n`, which must be odd and greater than 1
+fn key(n: Integer) -> Result<EncryptionKey, FfiError> {
+    if n <= 1 || n.is_even() {
+        return Err(INVALID_ARGUMENT);
+    }
+    Ok(EncryptionKey::from_n(n))
+}
+
+/// Derives prover randomness from the seed and all the inputs
+fn rng(seed: &[u8], inputs: &Inputs, witness: &[u8]) -> Result<impl RngCore, FfiError> {
+    let seed: [u8; 32] = seed.try_into().map_err(|_| INVALID_ARGUMENT)?;
+    let mut hash = Sha256::new()
+        .chain_update(b"paillier_zk.ffi.rng")
+        .chain_update(seed);
+    for input in [
+        inputs.shared_state,
+        inputs.aux,
+        inputs.security,
+        inputs.statement,
+        witness,
+    ] {
+        hash.update((input.len() as u64).to_be_bytes());
+        hash.update(input);
+    }
+    let key = hash.finalize();
+    Ok(HashRng::new(move |d: Sha256| {
+        d.chain_update(key).finalize()
+    }))
+}
+
+/// # Safety
+/// `ptr` must be valid for reads of `len` bytes, or `len` must be zero
+unsafe fn slice<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], FfiError> {
+    if len == 0 {
+        Ok(&[])
+    } else if ptr.is_null() {
+        Err(INVALID_ARGUMENT)
+    } else {
+        Ok(std::slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// # Safety
+/// `out_len` must be valid for reads and writes, `out` must be valid for writes
+/// of `*out_len` bytes
+unsafe fn write_out(out: *mut u8, out_len: *mut usize, bytes: &[u8]) -> Result<(), FfiError> {
+    if out_len.is_null() {
+        return Err(INVALID_ARGUMENT);
+    }
+    let capacity = *out_len;
+    *out_len = bytes.len();
+    if capacity < bytes.len() {
+        return Err(FfiError::new(ErrorCode::BufferTooSmall, 0));
+    }
+    if out.is_null() {
+        return Err(INVALID_ARGUMENT);
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len());
+    Ok(())
+}
+
+/// Runs `f`, making sure that panic doesn't unwind across FFI boundary
+pub(super) fn run(f: impl FnOnce() -> Result<(), FfiError>) -> FfiError {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => FfiError::OK,
+        Ok(Err(err)) => err,
+        Err(_) => FfiError::new(ErrorCode::Panic, 0),
+    }
+}
+
+fn enc_prove(inputs: &Inputs, witness: &[u8], rng: &mut impl RngCore) -> Result<Vec<u8>, FfiError> {
+    let (l, epsilon, q) = decode::<RangeSecurity>(inputs.security)?.into_parts()?;
+    let security = enc::SecurityParams::new(l, epsilon, q).with_weak_aux_rejected();
+    let statement: EncStatement = decode(inputs.statement)?;
+    let witness: EncWitness = decode(witness)?;
+    let key0 = key(statement.n0)?;
+    let data = enc::Data {
+        key: &key0,
+        ciphertext: &statement.k,
+    };
+    let pdata = enc::PrivateData {
+        plaintext: &witness.plaintext,
+        nonce: &witness.nonce,
+    };
+    let (commitment, proof) = enc::non_interactive::prove(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        pdata,
+        &security,
+        rng,
+    )?;
+    Ok(encode_proof(&commitment, &proof))
+}
+
+pub(super) fn enc_verify(inputs: &Inputs, proof: &[u8]) -> Result<(), FfiError> {
+    let (l, epsilon, q) = decode::<RangeSecurity>(inputs.security)?.into_parts()?;
+    let security = enc::SecurityParams::new(l, epsilon, q);
+    let statement: EncStatement = decode(inputs.statement)?;
+    let (commitment, proof) = decode_proof(proof)?;
+    let key0 = key(statement.n0)?;
+    let data = enc::Data {
+        key: &key0,
+        ciphertext: &statement.k,
+    };
+    enc::non_interactive::verify(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )?;
+    Ok(())
+}
+
+fn log_star_prove(
+    inputs: &Inputs,
+    witness: &[u8],
+    rng: &mut impl RngCore,
+) -> Result<Vec<u8>, FfiError> {
+    let (l, epsilon, q) = decode::<RangeSecurity>(inputs.security)?.into_parts()?;
+    let security = log_star::SecurityParams::new(l, epsilon, q).with_weak_aux_rejected();
+    let statement: LogStarStatement = decode(inputs.statement)?;
+    let witness: LogStarWitness = decode(witness)?;
+    let key0 = key(statement.n0)?;
+    let data = log_star::Data {
+        key0: &key0,
+        c: &statement.c,
+        b: &statement.b,
+        x: &statement.x,
+    };
+    let pdata = log_star::PrivateData {
+        x: &witness.x,
+        nonce: &witness.nonce,
+    };
+    let (commitment, proof) = log_star::non_interactive::prove(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        pdata,
+        &security,
+        rng,
+    )?;
+    Ok(encode_proof(&commitment, &proof))
+}
+
+pub(super) fn log_star_verify(inputs: &Inputs, proof: &[u8]) -> Result<(), FfiError> {
+    let (l, epsilon, q) = decode::<RangeSecurity>(inputs.security)?.into_parts()?;
+    let security = log_star::SecurityParams::new(l, epsilon, q);
+    let statement: LogStarStatement = decode(inputs.statement)?;
+    let (commitment, proof) = decode_proof::<log_star::Commitment<E>, _>(proof)?;
+    let key0 = key(statement.n0)?;
+    let data = log_star::Data {
+        key0: &key0,
+        c: &statement.c,
+        b: &statement.b,
+        x: &statement.x,
+    };
+    log_star::non_interactive::verify(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )?;
+    Ok(())
+}
+
+fn aff_g_security(inputs: &Inputs) -> Result<aff_g::SecurityParams, FfiError> {
+    let security: AffGSecurity = decode(inputs.security)?;
+    Ok(aff_g::SecurityParams::new(
+        size(&security.l_x)?,
+        size(&security.l_y)?,
+        size(&security.epsilon)?,
+        security.q,
+    ))
+}
+
+fn aff_g_prove(
+    inputs: &Inputs,
+    witness: &[u8],
+    rng: &mut impl RngCore,
+) -> Result<Vec<u8>, FfiError> {
+    let security = aff_g_security(inputs)?.with_weak_aux_rejected();
+    let statement: AffGStatement = decode(inputs.statement)?;
+    let witness: AffGWitness = decode(witness)?;
+    let key0 = key(statement.n0)?;
+    let key1 = key(statement.n1)?;
+    let data = aff_g::Data {
+        key0: &key0,
+        key1: &key1,
+        c: &statement.c,
+        d: &statement.d,
+        y: &statement.y,
+        x: &statement.x,
+    };
+    let pdata = aff_g::PrivateData {
+        x: &witness.x,
+        y: &witness.y,
+        nonce: &witness.nonce,
+        nonce_y: &witness.nonce_y,
+    };
+    let (commitment, proof) = aff_g::non_interactive::prove(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        pdata,
+        &security,
+        rng,
+    )?;
+    Ok(encode_proof(&commitment, &proof))
+}
+
+pub(super) fn aff_g_verify(inputs: &Inputs, proof: &[u8]) -> Result<(), FfiError> {
+    let security = aff_g_security(inputs)?;
+    let statement: AffGStatement = decode(inputs.statement)?;
+    let (commitment, proof) = decode_proof::<aff_g::Commitment<E>, _>(proof)?;
+    let key0 = key(statement.n0)?;
+    let key1 = key(statement.n1)?;
+    let data = aff_g::Data {
+        key0: &key0,
+        key1: &key1,
+        c: &statement.c,
+        d: &statement.d,
+        y: &statement.y,
+        x: &statement.x,
+    };
+    aff_g::non_interactive::verify(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )?;
+    Ok(())
+}
+
+fn mod_prove(inputs: &Inputs, witness: &[u8], rng: &mut impl RngCore) -> Result<Vec<u8>, FfiError> {
+    let statement: ModStatement = decode(inputs.statement)?;
+    let witness: FactorsWitness = decode(witness)?;
+    let data = pmod::Data { n: statement.n };
+    let pdata = pmod::PrivateData {
+        p: witness.p,
+        q: witness.q,
+    };
+    let (commitment, proof) =
+        pmod::non_interactive::prove::<MOD_M, _, _>(inputs.shared_state(), &data, &pdata, rng)?;
+    Ok(encode_proof(&commitment, &proof))
+}
+
+pub(super) fn mod_verify(inputs: &Inputs, proof: &[u8]) -> Result<(), FfiError> {
+    let statement: ModStatement = decode(inputs.statement)?;
+    let (commitment, proof) = decode_proof::<_, pmod::Proof<MOD_M>>(proof)?;
+    let data = pmod::Data { n: statement.n };
+    pmod::non_interactive::verify(inputs.shared_state(), &data, &commitment, &proof)?;
+    Ok(())
+}
+
+fn fac_prove(inputs: &Inputs, witness: &[u8], rng: &mut impl RngCore) -> Result<Vec<u8>, FfiError> {
+    let (l, epsilon, q) = decode::<RangeSecurity>(inputs.security)?.into_parts()?;
+    let security = fac::SecurityParams::new(l, epsilon, q).with_weak_aux_rejected();
+    let statement: FacStatement = decode(inputs.statement)?;
+    let witness: FactorsWitness = decode(witness)?;
+    let data = fac::Data {
+        n: &statement.n,
+        n_root: &statement.n_root,
+    };
+    let pdata = fac::PrivateData {
+        p: &witness.p,
+        q: &witness.q,
+    };
+    let proof = fac::non_interactive::prove(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        pdata,
+        &security,
+        rng,
+    )?;
+    Ok(proof.to_canonical_bytes())
+}
+
+pub(super) fn fac_verify(inputs: &Inputs, proof: &[u8]) -> Result<(), FfiError> {
+    let (l, epsilon, q) = decode::<RangeSecurity>(inputs.security)?.into_parts()?;
+    let security = fac::SecurityParams::new(l, epsilon, q);
+    let statement: FacStatement = decode(inputs.statement)?;
+    let proof = decode(proof)?;
+    let data = fac::Data {
+        n: &statement.n,
+        n_root: &statement.n_root,
+    };
+    fac::non_interactive::verify(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        &security,
+        &proof,
+    )?;
+    Ok(())
+}
+
+fn escrow_prove(
+    inputs: &Inputs,
+    witness: &[u8],
+    rng: &mut impl RngCore,
+) -> Result<Vec<u8>, FfiError> {
+    let (l, epsilon, q) = decode::<RangeSecurity>(inputs.security)?.into_parts()?;
+    let security = escrow::SecurityParams::new(l, epsilon, q).with_weak_aux_rejected();
+    let statement: EscrowStatement = decode(inputs.statement)?;
+    let witness: EscrowWitness = decode(witness)?;
+    let key0 = key(statement.n0)?;
+    let key1 = key(statement.n1)?;
+    let data = escrow::Data {
+        key0: &key0,
+        c0: &statement.c0,
+        key1: &key1,
+        c1: &statement.c1,
+        b: &statement.b,
+        x: &statement.x,
+    };
+    let pdata = escrow::PrivateData {
+        x: &witness.x,
+        nonce0: &witness.nonce0,
+        nonce1: &witness.nonce1,
+    };
+    let (commitment, proof) = escrow::non_interactive::prove(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        pdata,
+        &security,
+        rng,
+    )?;
+    Ok(encode_proof(&commitment, &proof))
+}
+
+pub(super) fn escrow_verify(inputs: &Inputs, proof: &[u8]) -> Result<(), FfiError> {
+    let (l, epsilon, q) = decode::<RangeSecurity>(inputs.security)?.into_parts()?;
+    let security = escrow::SecurityParams::new(l, epsilon, q);
+    let statement: EscrowStatement = decode(inputs.statement)?;
+    let (commitment, proof) = decode_proof::<escrow::Commitment<E>, _>(proof)?;
+    let key0 = key(statement.n0)?;
+    let key1 = key(statement.n1)?;
+    let data = escrow::Data {
+        key0: &key0,
+        c0: &statement.c0,
+        key1: &key1,
+        c1: &statement.c1,
+        b: &statement.b,
+        x: &statement.x,
+    };
+    escrow::non_interactive::verify(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )?;
+    Ok(())
+}
+
+fn dec_prove(inputs: &Inputs, witness: &[u8], rng: &mut impl RngCore) -> Result<Vec<u8>, FfiError> {
+    let (l, epsilon, q) = decode::<RangeSecurity>(inputs.security)?.into_parts()?;
+    let security = dec::SecurityParams::new(l, epsilon, q).with_weak_aux_rejected();
+    let statement: DecStatement = decode(inputs.statement)?;
+    let witness: DecWitness = decode(witness)?;
+    let key0 = key(statement.n0)?;
+    let data = dec::Data {
+        key0: &key0,
+        c: &statement.c,
+        x: &statement.x,
+    };
+    let pdata = dec::PrivateData {
+        y: &witness.y,
+        nonce: &witness.nonce,
+    };
+    let (commitment, proof) = dec::non_interactive::prove(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        pdata,
+        &security,
+        rng,
+    )?;
+    Ok(encode_proof(&commitment, &proof))
+}
+
+pub(super) fn dec_verify(inputs: &Inputs, proof: &[u8]) -> Result<(), FfiError> {
+    let (l, epsilon, q) = decode::<RangeSecurity>(inputs.security)?.into_parts()?;
+    let security = dec::SecurityParams::new(l, epsilon, q);
+    let statement: DecStatement = decode(inputs.statement)?;
+    let (commitment, proof) = decode_proof::<dec::Commitment<E>, _>(proof)?;
+    let key0 = key(statement.n0)?;
+    let data = dec::Data {
+        key0: &key0,
+        c: &statement.c,
+        x: &statement.x,
+    };
+    dec::non_interactive::verify(
+        inputs.shared_state(),
+        &inputs.aux()?,
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )?;
+    Ok(())
+}
+
+fn mul_prove(inputs: &Inputs, witness: &[u8], rng: &mut impl RngCore) -> Result<Vec<u8>, FfiError> {
+    let security = mul::SecurityParams::new(decode::<MulSecurity>(inputs.security)?.q);
+    let statement: MulStatement = decode(inputs.statement)?;
+    let witness: MulWitness = decode(witness)?;
+    let key = key(statement.n)?;
+    let data = mul::Data {
+        key: &key,
+        x: &statement.x,
+        y: &statement.y,
+        c: &statement.c,
+    };
+    let pdata = mul::PrivateData {
+        x: &witness.x,
+        nonce: &witness.nonce,
+        nonce_x: &witness.nonce_x,
+    };
+    let (commitment, proof) =
+        mul::non_interactive::prove(inputs.shared_state(), data, pdata, &security, rng)?;
+    Ok(encode_proof(&commitment, &proof))
+}
+
+pub(super) fn mul_verify(inputs: &Inputs, proof: &[u8]) -> Result<(), FfiError> {
+    let security = mul::SecurityParams::new(decode::<MulSecurity>(inputs.security)?.q);
+    let statement: MulStatement = decode(inputs.statement)?;
+    let (commitment, proof) = decode_proof(proof)?;
+    let key = key(statement.n)?;
+    let data = mul::Data {
+        key: &key,
+        x: &statement.x,
+        y: &statement.y,
+        c: &statement.c,
+    };
+    mul::non_interactive::verify(inputs.shared_state(), data, &commitment, &security, &proof)?;
+    Ok(())
+}
+
+macro_rules! bindings {
+    ($($proof:literal: $prove:ident => $prove_impl:ident, $verify:ident => $verify_impl:ident;)+) => {$(
+        #[doc = concat!("Computes ", $proof, " proof, see [module-level docs](self)")]
+        ///
+        /// # Safety
+        /// Each pointer must be valid for reads of the length passed along with it,
+        /// or the length must be zero. `out_len` must be valid for reads and writes,
+        /// `out` must be valid for writes of `*out_len` bytes.
+        #[no_mangle]
+        #[allow(clippy::too_many_arguments)]
+        pub unsafe extern "C" fn $prove(
+            shared_state: *const u8,
+            shared_state_len: usize,
+            aux: *const u8,
+            aux_len: usize,
+            security: *const u8,
+            security_len: usize,
+            statement: *const u8,
+            statement_len: usize,
+            witness: *const u8,
+            witness_len: usize,
+            seed: *const u8,
+            seed_len: usize,
+            out: *mut u8,
+            out_len: *mut usize,
+        ) -> FfiError {
+            run(|| {
+                let inputs = Inputs {
+                    shared_state: slice(shared_state, shared_state_len)?,
+                    aux: slice(aux, aux_len)?,
+                    security: slice(security, security_len)?,
+                    statement: slice(statement, statement_len)?,
+                };
+                let witness = slice(witness, witness_len)?;
+                let mut rng = rng(slice(seed, seed_len)?, &inputs, witness)?;
+                // Randomness repeats only along with the inputs, and so the proof
+                #[cfg(feature = "entropy-check")]
+                let proof =
+                    crate::entropy_check::exempt(|| $prove_impl(&inputs, witness, &mut rng))?;
+                #[cfg(not(feature = "entropy-check"))]
+                let proof = $prove_impl(&inputs, witness, &mut rng)?;
+                write_out(out, out_len, &proof)
+            })
+        }
+
+        #[doc = concat!("Verifies ", $proof, " proof, see [module-level docs](self)")]
+        ///
+        /// # Safety
+        /// Each pointer must be valid for reads of the length passed along with it,
+        /// or the length must be zero.
+        #[no_mangle]
+        #[allow(clippy::too_many_arguments)]
+        pub unsafe extern "C" fn $verify(
+            shared_state: *const u8,
+            shared_state_len: usize,
+            aux: *const u8,
+            aux_len: usize,
+            security: *const u8,
+            security_len: usize,
+            statement: *const u8,
+            statement_len: usize,
+            proof: *const u8,
+            proof_len: usize,
+        ) -> FfiError {
+            run(|| {
+                let inputs = Inputs {
+                    shared_state: slice(shared_state, shared_state_len)?,
+                    aux: slice(aux, aux_len)?,
+                    security: slice(security, security_len)?,
+                    statement: slice(statement, statement_len)?,
+                };
+                $verify_impl(&inputs, slice(proof, proof_len)?)
+            })
+        }
+    )+};
+}
+
+bindings! {
+    "Пenc": paillier_zk_enc_prove => enc_prove, paillier_zk_enc_verify => enc_verify;
+    "Пlog*": paillier_zk_log_star_prove => log_star_prove, paillier_zk_log_star_verify => log_star_verify;
+    "Пaff-g": paillier_zk_aff_g_prove => aff_g_prove, paillier_zk_aff_g_verify => aff_g_verify;
+    "Пmod": paillier_zk_mod_prove => mod_prove, paillier_zk_mod_verify => mod_verify;
+    "Пfac": paillier_zk_fac_prove => fac_prove, paillier_zk_fac_verify => fac_verify;
+    "witness escrow": paillier_zk_escrow_prove => escrow_prove, paillier_zk_escrow_verify => escrow_verify;
+    "Пdec": paillier_zk_dec_prove => dec_prove, paillier_zk_dec_verify => dec_verify;
+    "Пmul": paillier_zk_mul_prove => mul_prove, paillier_zk_mul_verify => mul_verify;
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use super::{EncStatement, EncWitness, MulSecurity, MulStatement, MulWitness, RangeSecurity};
+    use crate::common::encoding::Encode;
+    use crate::common::test::{aux, random_key};
+    use crate::common::IntegerExt;
+    use crate::ffi::{ErrorCode, FfiError};
+
+    struct Case {
+        aux: Vec<u8>,
+        security: Vec<u8>,
+        statement: Vec<u8>,
+        witness: Vec<u8>,
+    }
+
+    fn prove(
+        case: &Case,
+        seed: &[u8],
+        capacity: usize,
+        f: unsafe extern "C" fn(
+            *const u8,
+            usize,
+            *const u8,
+            usize,
+            *const u8,
+            usize,
+            *const u8,
+            usize,
+            *const u8,
+            usize,
+            *const u8,
+            usize,
+            *mut u8,
+            *mut usize,
+        ) -> FfiError,
+    ) -> (FfiError, Vec<u8>) {
+        let shared_state = b"shared state";
+        let mut out = vec![0u8; capacity];
+        let mut out_len = out.len();
+        let err = unsafe {
+            f(
+                shared_state.as_ptr(),
+                shared_state.len(),
+                case.aux.as_ptr(),
+                case.aux.len(),
+                case.security.as_ptr(),
+                case.security.len(),
+                case.statement.as_ptr(),
+                case.statement.len(),
+                case.witness.as_ptr(),
+                case.witness.len(),
+                seed.as_ptr(),
+                seed.len(),
+                out.as_mut_ptr(),
+                &mut out_len,
+            )
+        };
+        out.resize(out_len, 0);
+        (err, out)
+    }
+
+    fn verify(
+        case: &Case,
+        proof: &[u8],
+        f: unsafe extern "C" fn(
+            *const u8,
+            usize,
+            *const u8,
+            usize,
+            *const u8,
+            usize,
+            *const u8,
+            usize,
+            *const u8,
+            usize,
+        ) -> FfiError,
+    ) -> FfiError {
+        let shared_state = b"shared state";
+        unsafe {
+            f(
+                shared_state.as_ptr(),
+                shared_state.len(),
+                case.aux.as_ptr(),
+                case.aux.len(),
+                case.security.as_ptr(),
+                case.security.len(),
+                case.statement.as_ptr(),
+                case.statement.len(),
+                proof.as_ptr(),
+                proof.len(),
+            )
+        }
+    }
+
+    fn enc_case() -> Case {
+        let mut rng = rand_dev::DevRng::new();
+        let key = random_key(&mut rng).unwrap().encryption_key().clone();
+        let security = RangeSecurity {
+            l: 1024.into(),
+            epsilon: 256.into(),
+            q: (Integer::ONE << 128_u32).complete() - 1,
+        };
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << 1024_u32).complete(), &mut rng);
+        let (k, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        Case {
+            aux: aux(&mut rng).to_canonical_bytes(),
+            security: security.to_canonical_bytes(),
+            statement: EncStatement {
+                n0: key.n().clone(),
+                k,
+            }
+            .to_canonical_bytes(),
+            witness: EncWitness { plaintext, nonce }.to_canonical_bytes(),
+        }
+    }
+
+    #[test]
+    fn enc_round_trip() {
+        let case = enc_case();
+        let (err, proof) = prove(&case, &[1; 32], 10_000, super::paillier_zk_enc_prove);
+        assert_eq!(err, FfiError::OK);
+        assert_eq!(
+            verify(&case, &proof, super::paillier_zk_enc_verify),
+            FfiError::OK
+        );
+
+        // Same seed and inputs give the same proof
+        let (_, proof2) = prove(&case, &[1; 32], 10_000, super::paillier_zk_enc_prove);
+        assert_eq!(proof, proof2);
+
+        let mut tampered = case.statement.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        let tampered = Case {
+            statement: tampered,
+            ..case
+        };
+        let err = verify(&tampered, &proof, super::paillier_zk_enc_verify);
+        assert_eq!(err.code(), Ok(ErrorCode::EqualityCheck));
+    }
+
+    #[test]
+    fn enc_bad_inputs() {
+        let case = enc_case();
+
+        let (err, proof) = prove(&case, &[1; 32], 10, super::paillier_zk_enc_prove);
+        assert_eq!(err.code(), Ok(ErrorCode::BufferTooSmall));
+        assert!(proof.len() > 10);
+        let required = proof.len();
+        let (err, proof) = prove(&case, &[1; 32], required, super::paillier_zk_enc_prove);
+        assert_eq!(err, FfiError::OK);
+        assert_eq!(proof.len(), required);
+
+        let (err, _) = prove(&case, &[1; 31], 10_000, super::paillier_zk_enc_prove);
+        assert_eq!(err.code(), Ok(ErrorCode::InvalidArgument));
+
+        let err = verify(&case, &proof[1..], super::paillier_zk_enc_verify);
+        assert_eq!(err.code(), Ok(ErrorCode::InvalidEncoding));
+        let mut trailing = proof.clone();
+        trailing.push(0);
+        let err = verify(&case, &trailing, super::paillier_zk_enc_verify);
+        assert_eq!(err.code(), Ok(ErrorCode::InvalidEncoding));
+
+        let oversized = Case {
+            aux: case.aux.clone(),
+            security: RangeSecurity {
+                l: (super::MAX_SIZE + 1).into(),
+                epsilon: 256.into(),
+                q: (Integer::ONE << 128_u32).complete() - 1,
+            }
+            .to_canonical_bytes(),
+            statement: case.statement.clone(),
+            witness: case.witness.clone(),
+        };
+        let (err, _) = prove(&oversized, &[1; 32], 10_000, super::paillier_zk_enc_prove);
+        assert_eq!(err.code(), Ok(ErrorCode::InvalidArgument));
+        let err = verify(&oversized, &proof, super::paillier_zk_enc_verify);
+        assert_eq!(err.code(), Ok(ErrorCode::InvalidArgument));
+
+        let even_key = Case {
+            statement: EncStatement {
+                n0: 100.into(),
+                k: 1.into(),
+            }
+            .to_canonical_bytes(),
+            ..case
+        };
+        let err = verify(&even_key, &proof, super::paillier_zk_enc_verify);
+        assert_eq!(err.code(), Ok(ErrorCode::InvalidArgument));
+    }
+
+    #[test]
+    fn mul_round_trip() {
+        let mut rng = rand_dev::DevRng::new();
+        let key = random_key(&mut rng).unwrap().encryption_key().clone();
+        let x = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+        let (cx, nonce_x) = key.encrypt_with_random(&mut rng, &x).unwrap();
+        let (cy, _) = key.encrypt_with_random(&mut rng, &y).unwrap();
+        let nonce = Integer::gen_invertible(key.n(), &mut rng);
+        let c = key
+            .oadd(
+                &key.omul(&x, &cy).unwrap(),
+                &key.encrypt_with(&Integer::ZERO, &nonce).unwrap(),
+            )
+            .unwrap();
+        let case = Case {
+            aux: vec![],
+            security: MulSecurity {
+                q: (Integer::ONE << 128_u32).complete(),
+            }
+            .to_canonical_bytes(),
+            statement: MulStatement {
+                n: key.n().clone(),
+                x: cx,
+                y: cy,
+                c,
+            }
+            .to_canonical_bytes(),
+            witness: MulWitness { x, nonce, nonce_x }.to_canonical_bytes(),
+        };
+
+        let (err, proof) = prove(&case, &[2; 32], 10_000, super::paillier_zk_mul_prove);
+        assert_eq!(err, FfiError::OK);
+        assert_eq!(
+            verify(&case, &proof, super::paillier_zk_mul_verify),
+            FfiError::OK
+        );
+
+        let (_, other) = prove(&case, &[3; 32], 10_000, super::paillier_zk_mul_prove);
+        assert_ne!(proof, other);
+        assert_eq!(
+            verify(&case, &other, super::paillier_zk_mul_verify),
+            FfiError::OK
+        );
+    }
+}
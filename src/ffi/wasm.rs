@@ -0,0 +1,119 @@
+//! `wasm-bindgen` functions for verification in browsers
+//!
+//! Available with `wasm-bindings` feature. Functions take `Uint8Array`s in the same
+//! encoding as [`bindings`](super::bindings) and throw [`WasmError`] carrying
+//! [`ErrorCode`](super::ErrorCode) if the proof is invalid. Challenges are derived
+//! exactly as in the rest of the crate, so the proofs produced by the crate verify
+//! in browser as they are.
+//!
+//! Building for wasm requires GMP, which backs [`rug`], to be built for the target.
+//!
+//! ```js
+//! import { verifyEnc } from "paillier-zk";
+//! try {
+//!     verifyEnc(sharedState, aux, security, statement, proof);
+//! } catch (err) {
+//!     console.log(`invalid proof: ${err.code} ${err.detail}`);
+//! }
+//! ```
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use super::bindings::{self, Inputs};
+use super::FfiError;
+
+/// Error thrown by verification functions
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WasmError(FfiError);
+
+#[wasm_bindgen]
+impl WasmError {
+    /// [`ErrorCode`](super::ErrorCode) as integer
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> u32 {
+        self.0.code
+    }
+
+    /// Detail specific to the code, zero if the code has no detail
+    #[wasm_bindgen(getter)]
+    pub fn detail(&self) -> u32 {
+        self.0.detail
+    }
+
+    /// Human-readable description of the error
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        match self.0.code() {
+            Ok(code) => format!("{code:?} ({})", self.0.detail),
+            Err(err) => err.to_string(),
+        }
+    }
+}
+
+impl From<WasmError> for FfiError {
+    fn from(err: WasmError) -> Self {
+        err.0
+    }
+}
+
+fn verify(
+    f: fn(&Inputs, &[u8]) -> Result<(), FfiError>,
+    shared_state: &[u8],
+    aux: &[u8],
+    security: &[u8],
+    statement: &[u8],
+    proof: &[u8],
+) -> Result<(), WasmError> {
+    let inputs = Inputs {
+        shared_state,
+        aux,
+        security,
+        statement,
+    };
+    let result = bindings::run(|| f(&inputs, proof));
+    if result.is_ok() {
+        Ok(())
+    } else {
+        Err(WasmError(result))
+    }
+}
+
+macro_rules! wasm_bindings {
+    ($($proof:literal: $verify:ident as $js_name:ident => $verify_impl:ident;)+) => {$(
+        #[doc = concat!("Verifies ", $proof, " proof, see [module-level docs](self)")]
+        #[wasm_bindgen(js_name = $js_name)]
+        pub fn $verify(
+            shared_state: &[u8],
+            aux: &[u8],
+            security: &[u8],
+            statement: &[u8],
+            proof: &[u8],
+        ) -> Result<(), WasmError> {
+            verify(bindings::$verify_impl, shared_state, aux, security, statement, proof)
+        }
+    )+};
+}
+
+wasm_bindings! {
+    "Пenc": verify_enc as verifyEnc => enc_verify;
+    "Пlog*": verify_log_star as verifyLogStar => log_star_verify;
+    "Пaff-g": verify_aff_g as verifyAffG => aff_g_verify;
+    "Пmod": verify_mod as verifyMod => mod_verify;
+    "Пfac": verify_fac as verifyFac => fac_verify;
+    "witness escrow": verify_escrow as verifyEscrow => escrow_verify;
+    "Пdec": verify_dec as verifyDec => dec_verify;
+    "Пmul": verify_mul as verifyMul => mul_verify;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ffi::{ErrorCode, FfiError};
+
+    #[test]
+    fn invalid_encoding_is_thrown() {
+        let err = super::verify_enc(b"shared state", &[], &[], &[], &[]).unwrap_err();
+        assert_eq!(FfiError::from(err).code(), Ok(ErrorCode::InvalidEncoding));
+        assert_eq!(err.code(), ErrorCode::InvalidEncoding as u32);
+    }
+}
@@ -18,6 +18,8 @@
 //!
 //! Disclosing only: `key0`, `C`, `X`
 //!
+//! `x = 0` is a valid witness: `X` is then the identity point and `C` encrypts zero.
+//!
 //! ## Example
 //!
 //! ```rust
@@ -42,11 +44,7 @@
 //! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
 //!
 //! let aux: p::Aux = pregenerated::verifier_aux();
-//! let security = p::SecurityParams {
-//!     l: 1024,
-//!     epsilon: 300,
-//!     q: (Integer::ONE << 128_u32).complete(),
-//! };
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
 //!
 //! // 1. Setup: prover prepares the paillier keys
 //!
@@ -104,6 +102,10 @@ use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
 use generic_ec::{Curve, Point};
 use rug::Integer;
 
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{InvalidProof, PreparedCiphertext};
+use crate::compatibility::Incompatible;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -113,6 +115,7 @@ pub use crate::common::Aux;
 /// speed and chance of rejecting a valid proof or accepting an invalid proof
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
 pub struct SecurityParams {
     /// l in paper, bit size of +-plaintext
     pub l: usize,
@@ -120,6 +123,89 @@ pub struct SecurityParams {
     pub epsilon: usize,
     /// q in paper. Security parameter for challenge
     pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+    /// Return an error at prove time if the bound on `x` exceeds the curve order
+    ///
+    /// Disabled by default, see [`SecurityParams::with_bound_checked_against_curve`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub require_bound_fits_curve: bool,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_bound_fits_curve: false,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// Absorbing ring-pedersen parameters (kilobytes) into every challenge is redundant
+    /// when counterparties agree on them once, e.g. per epoch. `aux_digest` must be
+    /// obtained with [`Aux::digest`] from the same aux that is used to prove and verify.
+    /// Prover and verifier must both enable it, otherwise the proof doesn't verify.
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+
+    /// Makes proving fail if `x` bound `l` has more bits than the curve order
+    ///
+    /// When `x` is ultimately used as a scalar of the curve, proving a bound
+    /// larger than `q` is usually a configuration bug. See
+    /// [`SecurityParams::assert_bound_fits_curve`]
+    pub fn with_bound_checked_against_curve(self) -> Self {
+        Self {
+            require_bound_fits_curve: true,
+            ..self
+        }
+    }
+
+    /// Checks that `x` bound `l` doesn't exceed bit size of the order of curve `C`
+    pub fn assert_bound_fits_curve<C: Curve>(&self) -> Result<(), Incompatible> {
+        crate::compatibility::bound_fits_curve::<C>(self.l)
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 512, (Integer::ONE << 128_u32).complete())
+    }
 }
 
 /// Public data that both parties know
@@ -136,14 +222,49 @@ pub struct Data<'a, C: Curve> {
 }
 
 /// Private data of prover
-#[derive(Clone, Copy)]
-pub struct PrivateData<'a> {
+///
+/// `x` can be kept in external storage, see [`crate::witness`].
+pub struct PrivateData<'a, W: ?Sized = Integer> {
     /// x in paper, logarithm of X and plaintext of C
-    pub x: &'a Integer,
+    pub x: &'a W,
     /// rho in paper, nonce in encryption x -> C
     pub nonce: &'a Nonce,
 }
 
+impl<W: ?Sized> Clone for PrivateData<'_, W> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<W: ?Sized> Copy for PrivateData<'_, W> {}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a, C: Curve> {
+    data: Data<'a, C>,
+    c: PreparedCiphertext,
+}
+
+impl<'a, C: Curve> PreparedStatement<'a, C> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a, C>) -> Result<Self, InvalidProof> {
+        let c = PreparedCiphertext::new(data.key0, data.c)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, c })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a, C> {
+        self.data
+    }
+}
+
 /// Prover's first message, obtained by [`interactive::commit`]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
@@ -164,6 +285,8 @@ pub struct PrivateCommitment {
     pub gamma: Integer,
 }
 
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
 /// Verifier's challenge to prover. Can be obtained deterministically by
 /// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
 pub type Challenge = Integer;
@@ -178,6 +301,57 @@ pub struct Proof {
     pub z3: Integer,
 }
 
+impl_encode! { Data<'a, C: Curve> { key0, c, b, x } }
+impl_encode! { Commitment<C: Curve> { s, a, y, d } }
+impl_decode! { Commitment<C: Curve> { s, a, y, d } }
+impl_encode! { Proof { z1, z2, z3 } }
+impl_decode! { Proof { z1, z2, z3 } }
+
+impl<C: Curve> Commitment<C> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("s", modulus_bits)
+            .integer("a", 2 * modulus_bits)
+            .point::<C>("y")
+            .integer("d", modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        let z3 = z1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .integer("z2", modulus_bits)
+            .integer("z3", z3)
+    }
+}
+
 /// The interactive version of the ZK proof. Should be completed in 3 rounds:
 /// prover commits to data, verifier responds with a random challenge, and
 /// prover gives proof with commitment and challenge.
@@ -186,21 +360,31 @@ pub mod interactive {
     use rand_core::RngCore;
     use rug::{Complete, Integer};
 
-    use crate::common::{fail_if, fail_if_ne, IntegerExt, InvalidProofReason};
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProofReason,
+    };
+    use crate::witness::Witness;
     use crate::{Error, InvalidProof};
 
     use super::{
-        Aux, Challenge, Commitment, Data, PrivateCommitment, PrivateData, Proof, SecurityParams,
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
     };
 
     /// Create random commitment
-    pub fn commit<C: Curve, R: RngCore>(
+    pub fn commit<C: Curve, W: Witness + ?Sized, R: RngCore>(
         aux: &Aux,
         data: Data<C>,
-        pdata: PrivateData,
+        pdata: PrivateData<W>,
         security: &SecurityParams,
         mut rng: R,
     ) -> Result<(Commitment<C>, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        crate::compatibility::check_bound_fits_curve::<C>(
+            security.l,
+            security.require_bound_fits_curve,
+        )?;
         let two_to_l_e = (Integer::ONE << (security.l + security.epsilon)).complete();
         let hat_n_at_two_to_l = &aux.rsa_modulo * (Integer::ONE << security.l).complete();
         let hat_n_at_two_to_l_e = (&aux.rsa_modulo * &two_to_l_e).complete();
@@ -209,9 +393,10 @@ pub mod interactive {
         let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, &mut rng);
         let r = Integer::gen_invertible(data.key0.n(), &mut rng);
         let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_e, &mut rng);
+        crate::common::observe_randomness!(&alpha);
 
         let commitment = Commitment {
-            s: aux.combine(pdata.x, &mu)?,
+            s: pdata.x.ring_pedersen_commit(aux, &mu)?,
             a: data.key0.encrypt_with(&alpha, &r)?,
             y: data.b * alpha.to_scalar(),
             d: aux.combine(&alpha, &gamma)?,
@@ -226,14 +411,14 @@ pub mod interactive {
     }
 
     /// Compute proof for given data and prior protocol values
-    pub fn prove<C: Curve>(
+    pub fn prove<C: Curve, W: Witness + ?Sized>(
         data: Data<C>,
-        pdata: PrivateData,
+        pdata: PrivateData<W>,
         pcomm: &PrivateCommitment,
         challenge: &Challenge,
     ) -> Result<Proof, Error> {
         Ok(Proof {
-            z1: (&pcomm.alpha + challenge * pdata.x).complete(),
+            z1: pdata.x.linear_response(&pcomm.alpha, challenge)?,
             z2: data
                 .key0
                 .n()
@@ -251,39 +436,81 @@ pub mod interactive {
         challenge: &Challenge,
         proof: &Proof,
     ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key0.n()))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key0.n()))
+    }
+
+    fn check_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(&aux.rsa_modulo, [&commitment.s, &commitment.d])?;
+        fail_if_degenerate(data.key0.nn(), [&commitment.a])?;
+        crate::common::fail_if_not_coprime(data.key0.n(), [&proof.z2])?;
+        fail_if(
+            InvalidProofReason::DegenerateCommitment,
+            !commitment.y.is_zero(),
+        )?;
+        // The range of z1 is checked before anything else uses it. Check (2)
+        // only sees z1 modulo q, and any z1' = z1 + k q with k != 0 satisfies
+        // it equally well. Checks (1) and (3) bind z1 as an integer, and
+        // bounding it first makes all three checks talk about the same
+        // (l+ε)-bit number, which is what extraction relies on. It also keeps
+        // oversized values away from the curve and paillier arithmetic.
+        fail_if(
+            InvalidProofReason::RangeCheck(4),
+            proof
+                .z1
+                .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete()),
+        )?;
+        crate::common::lap!(Check("range of z1"));
         {
             let lhs = data
                 .key0
                 .encrypt_with(&proof.z1, &proof.z2)
                 .map_err(|_| InvalidProofReason::PaillierEnc)?;
             let rhs = {
-                let e_at_c = data
-                    .key0
-                    .omul(challenge, data.c)
-                    .map_err(|_| InvalidProofReason::PaillierOp)?;
+                let e_at_c = statement.c.omul(data.key0, challenge)?;
                 data.key0
                     .oadd(&commitment.a, &e_at_c)
                     .map_err(|_| InvalidProofReason::PaillierOp)?
             };
             fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
         }
+        crate::common::lap!(Check("paillier encryption"));
         {
             let lhs = data.b * proof.z1.to_scalar();
             let rhs = commitment.y + data.x * challenge.to_scalar();
             fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
         }
+        crate::common::lap!(Check("group element"));
         {
             let lhs = aux.combine(&proof.z1, &proof.z3)?;
             let s_to_e = aux.pow_mod(&commitment.s, challenge)?;
             let rhs = (&commitment.d * s_to_e).modulo(&aux.rsa_modulo);
             fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
         }
-        fail_if(
-            InvalidProofReason::RangeCheck(4),
-            proof
-                .z1
-                .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete()),
-        )?;
+        crate::common::lap!(Check("ring-pedersen"));
 
         Ok(())
     }
@@ -302,32 +529,52 @@ pub mod interactive {
 /// The non-interactive version of proof. Completed in one round, for example
 /// see the documentation of parent module.
 pub mod non_interactive {
-    use digest::{typenum::U32, Digest};
+    use digest::Digest;
     use generic_ec::Curve;
     use rand_core::RngCore;
 
+    use crate::transcript::Transcript;
+    use crate::witness::Witness;
     use crate::{Error, InvalidProof};
 
-    use super::{Aux, Challenge, Commitment, Data, PrivateData, Proof, SecurityParams};
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
 
     /// Compute proof for the given data, producing random commitment and
     /// deriving determenistic challenge.
     ///
     /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
-    pub fn prove<C: Curve, R: RngCore, D: Digest>(
+    pub fn prove<C: Curve, W: Witness + ?Sized, R: RngCore, D: Digest>(
         shared_state: D,
         aux: &Aux,
         data: Data<C>,
-        pdata: PrivateData,
+        pdata: PrivateData<W>,
         security: &SecurityParams,
         rng: &mut R,
-    ) -> Result<(Commitment<C>, Proof), Error>
-    where
-        D: Digest<OutputSize = U32>,
-    {
+    ) -> Result<(Commitment<C>, Proof), Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<C: Curve, W: Witness + ?Sized, R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData<W>,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof), Error> {
         let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
-        let challenge = challenge(shared_state, aux, data, &comm, security);
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
         let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
         Ok((comm, proof))
     }
 
@@ -339,14 +586,41 @@ pub mod non_interactive {
         commitment: &Commitment<C>,
         security: &SecurityParams,
         proof: &Proof,
-    ) -> Result<(), InvalidProof>
-    where
-        D: Digest<OutputSize = U32>,
-    {
-        let challenge = challenge(shared_state, aux, data, commitment, security);
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
         super::interactive::verify(aux, data, commitment, security, &challenge, proof)
     }
 
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+
     /// Internal function for deriving challenge from protocol values
     /// deterministically
     pub fn challenge<C: Curve, D: Digest>(
@@ -355,36 +629,248 @@ pub mod non_interactive {
         data: Data<C>,
         commitment: &Commitment<C>,
         security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<C: Curve, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
     ) -> Challenge
     where
         D: Digest,
     {
-        let shared_state = shared_state.finalize();
-        let hash = |d: D| {
-            let order = rug::integer::Order::Msf;
-            d.chain_update(&shared_state)
-                .chain_update(C::CURVE_NAME)
-                .chain_update(aux.s.to_digits::<u8>(order))
-                .chain_update(aux.t.to_digits::<u8>(order))
-                .chain_update(aux.rsa_modulo.to_digits::<u8>(order))
-                .chain_update((security.l as u64).to_le_bytes())
-                .chain_update((security.epsilon as u64).to_le_bytes())
-                .chain_update(data.key0.n().to_digits::<u8>(order))
-                .chain_update(data.c.to_digits::<u8>(order))
-                .chain_update(data.x.to_bytes(true))
-                .chain_update(data.b.to_bytes(true))
-                .chain_update(commitment.s.to_digits::<u8>(order))
-                .chain_update(commitment.a.to_digits::<u8>(order))
-                .chain_update(commitment.y.to_bytes(true))
-                .chain_update(commitment.d.to_digits::<u8>(order))
-                .finalize()
-        };
+        transcript.label(b"paillier_zk.group_element_vs_paillier_encryption_in_range");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb(b"l", &(security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"N0", data.key0.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_point(b"X", data.x);
+        transcript.absorb_point(b"g", data.b);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
 
-        let mut rng = crate::common::rng::HashRng::new(hash);
+/// Proving the same statement to several verifiers with different [`Aux`]
+///
+/// In the broadcast setting, prover proves the same statement to each of `n`
+/// verifiers, every verifier having its own ring-pedersen parameters. Ciphertext `A`
+/// and point `Y` of the commitment don't depend on the verifier, so [`prove`] computes
+/// them once and shares them among all the proofs, which saves `n - 1` Paillier
+/// encryptions and scalar multiplications.
+///
+/// Sharing the commitment is only safe when all proofs are answered with the same
+/// challenge: responses to two different challenges with the same commitment reveal
+/// the witness (see special soundness). So the challenge is derived from the statement
+/// and the commitments to all verifiers, and each verifier needs all the commitments
+/// and everyone's [`Aux`] to check its own proof.
+pub mod multi_verifier {
+    use digest::Digest;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{fail_if, IntegerExt, InvalidProofReason};
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{Aux, Challenge, Commitment, Data, PrivateData, Proof, SecurityParams};
+
+    /// Compute proofs of the statement for each verifier, `auxes[i]` being ring-pedersen
+    /// parameters of `i`-th verifier
+    ///
+    /// Returns commitment and proof for each verifier in the same order. All the
+    /// commitments need to be delivered to every verifier.
+    pub fn prove<C: Curve, R: RngCore, D>(
+        shared_state: D,
+        auxes: &[&Aux],
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<Vec<(Commitment<C>, Proof)>, Error>
+    where
+        D: Digest,
+    {
+        crate::compatibility::check_bound_fits_curve::<C>(
+            security.l,
+            security.require_bound_fits_curve,
+        )?;
+        let two_to_l_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let alpha = Integer::from_rng_pm(&two_to_l_e, rng);
+        let r = Integer::gen_invertible(data.key0.n(), rng);
+        let a = data.key0.encrypt_with(&alpha, &r)?;
+        let y = data.b * alpha.to_scalar();
+
+        let mut commitments = Vec::with_capacity(auxes.len());
+        let mut private_commitments = Vec::with_capacity(auxes.len());
+        for aux in auxes {
+            crate::common::check_aux(aux, security.reject_weak_aux)?;
+            let hat_n_at_two_to_l = &aux.rsa_modulo * (Integer::ONE << security.l).complete();
+            let hat_n_at_two_to_l_e = (&aux.rsa_modulo * &two_to_l_e).complete();
+            let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, rng);
+            let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_e, rng);
+            commitments.push(Commitment {
+                s: aux.combine(pdata.x, &mu)?,
+                a: a.clone(),
+                y,
+                d: aux.combine(&alpha, &gamma)?,
+            });
+            private_commitments.push((mu, gamma));
+        }
+        crate::common::lap!(Commit);
+
+        let challenge = challenge(shared_state, auxes, data, &commitments, security);
+        crate::common::lap!(Challenge);
+
+        let z1 = (&alpha + &challenge * pdata.x).complete();
+        let z2 = data
+            .key0
+            .n()
+            .combine(&r, Integer::ONE, pdata.nonce, &challenge)?;
+        let proofs = commitments
+            .into_iter()
+            .zip(private_commitments)
+            .map(|(commitment, (mu, gamma))| {
+                let proof = Proof {
+                    z1: z1.clone(),
+                    z2: z2.clone(),
+                    z3: gamma + &challenge * mu,
+                };
+                (commitment, proof)
+            })
+            .collect();
+        crate::common::lap!(Respond);
+        Ok(proofs)
+    }
+
+    /// Verify the proof addressed to `verifier`-th verifier
+    ///
+    /// `auxes` and `commitments` must be the same as given to and returned by
+    /// [`prove`], including the ones of other verifiers
+    pub fn verify<C: Curve, D>(
+        shared_state: D,
+        auxes: &[&Aux],
+        data: Data<C>,
+        commitments: &[Commitment<C>],
+        security: &SecurityParams,
+        verifier: usize,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        fail_if(
+            InvalidProofReason::VerifiersMismatch,
+            auxes.len() == commitments.len(),
+        )?;
+        let (aux, commitment) = auxes
+            .get(verifier)
+            .zip(commitments.get(verifier))
+            .ok_or(InvalidProofReason::VerifiersMismatch)?;
+        let challenge = challenge(shared_state, auxes, data, commitments, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Deterministically compute challenge shared by proofs to all verifiers
+    ///
+    /// [`SecurityParams::aux_digest`] is ignored as it's a digest of a single aux,
+    /// all `auxes` are absorbed as they are
+    pub fn challenge<C: Curve, D: Digest>(
+        shared_state: D,
+        auxes: &[&Aux],
+        data: Data<C>,
+        commitments: &[Commitment<C>],
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        transcript
+            .label(b"paillier_zk.group_element_vs_paillier_encryption_in_range.multi_verifier");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb(b"l", &(security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"N0", data.key0.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_point(b"X", data.x);
+        transcript.absorb_point(b"g", data.b);
+        transcript.absorb(b"verifiers", &(auxes.len() as u64).to_be_bytes());
+        for (aux, commitment) in auxes.iter().zip(commitments) {
+            transcript.absorb_aux(aux, None);
+            transcript.absorb_value(b"commitment", commitment);
+        }
+        let mut rng = transcript.challenge_rng(b"e");
         super::interactive::challenge(security, &mut rng)
     }
 }
 
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use generic_ec::Curve;
+    use rug::Integer;
+
+    use crate::common::extract::{challenge_diff, ensure, linear_response, paillier_nonce};
+    use crate::common::IntegerExt;
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub x: Integer,
+        pub nonce: Nonce,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract<C: Curve>(
+        data: Data<C>,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let x = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        let nonce = paillier_nonce(data.key0.n(), data.c, &proof1.z2, &proof2.z2, &e_diff)?;
+
+        let c = data.key0.encrypt_with(&x, &nonce).ok();
+        ensure(c.as_ref() == Some(data.c))?;
+        ensure(data.b * x.to_scalar() == *data.x)?;
+        Ok(Witness { x, nonce })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use generic_ec::{Curve, Point, Scalar};
@@ -439,6 +925,9 @@ mod test {
             l: 1024,
             epsilon: 300,
             q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_bound_fits_curve: false,
         };
         let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
         run::<_, C>(rng, security, plaintext).expect("proof failed");
@@ -450,6 +939,9 @@ mod test {
             l: 1024,
             epsilon: 300,
             q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_bound_fits_curve: false,
         };
         let plaintext = (Integer::ONE << (security.l + security.epsilon + 1)).complete();
         let r = run::<_, C>(rng, security, plaintext).expect_err("proof should not pass");
@@ -459,14 +951,327 @@ mod test {
         }
     }
 
+    #[test]
+    fn bound_checked_against_curve() {
+        type C = generic_ec::curves::Secp256k1;
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+        assert_eq!(
+            security.assert_bound_fits_curve::<C>(),
+            Err(crate::compatibility::Incompatible::LargeRange {
+                l: 1024,
+                curve_bits: 256
+            })
+        );
+        let fitting = super::SecurityParams::new(256, 300, (Integer::ONE << 128_u32).complete());
+        fitting.assert_bound_fits_curve::<C>().unwrap();
+
+        let private_key0 = random_key(&mut rng).unwrap();
+        let key0 = private_key0.encryption_key().clone();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+        let (ciphertext, nonce) = key0.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let b = Point::<C>::generator() * Scalar::random(&mut rng);
+        let x = b * plaintext.to_scalar();
+        let data = super::Data {
+            key0: &key0,
+            c: &ciphertext,
+            x: &x,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            x: &plaintext,
+            nonce: &nonce,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        // The check is opt-in
+        super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+
+        let security = security.with_bound_checked_against_curve();
+        let r = super::interactive::commit(&aux, data, pdata, &security, &mut rng);
+        assert!(matches!(
+            r.map(|_| ()).map_err(|e| e.0),
+            Err(crate::ErrorReason::BoundExceedsCurve(_))
+        ));
+    }
+
+    fn zero_witness<C: Curve>() {
+        let rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+        // X is the identity point, C encrypts zero
+        run::<_, C>(rng, security, Integer::ZERO).expect("proof failed");
+    }
+
+    fn negative_challenge<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l: 1024,
+            epsilon: 300,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_bound_fits_curve: false,
+        };
+        let private_key0 = random_key(&mut rng).unwrap();
+        let key0 = private_key0.encryption_key().clone();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let (ciphertext, nonce) = key0.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let b = Point::<C>::generator() * Scalar::random(&mut rng);
+        let x = b * plaintext.to_scalar();
+        let data = super::Data {
+            key0: &key0,
+            c: &ciphertext,
+            x: &x,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            x: &plaintext,
+            nonce: &nonce,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let challenge = -super::interactive::challenge(&security, &mut rng).abs() - 1;
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof)
+            .expect("proof with negative challenge failed");
+
+        // Proof is bound to the sign of the challenge
+        let r = super::interactive::verify(
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &(-&challenge).complete(),
+            &proof,
+        );
+        assert!(r.is_err());
+    }
+
+    fn degenerate_transcript<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l: 1024,
+            epsilon: 300,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_bound_fits_curve: false,
+        };
+        let private_key0 = random_key(&mut rng).unwrap();
+        let key0 = private_key0.encryption_key().clone();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let (ciphertext, nonce) = key0.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let b = Point::<C>::generator() * Scalar::random(&mut rng);
+        let x = b * plaintext.to_scalar();
+        let data = super::Data {
+            key0: &key0,
+            c: &ciphertext,
+            x: &x,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            x: &plaintext,
+            nonce: &nonce,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Commitment equal to one is rejected
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let mut degenerate = commitment.clone();
+        degenerate.d = Integer::from(1);
+        let r = super::interactive::verify(&aux, data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+
+        // Identity point in commitment is rejected
+        let mut commitment = commitment;
+        commitment.y = Point::zero();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    fn z1_reduction<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+        let private_key0 = random_key(&mut rng).unwrap();
+        let key0 = private_key0.encryption_key().clone();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let (ciphertext, nonce) = key0.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let b = Point::<C>::generator() * Scalar::random(&mut rng);
+        let x = b * plaintext.to_scalar();
+        let data = super::Data {
+            key0: &key0,
+            c: &ciphertext,
+            x: &x,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            x: &plaintext,
+            nonce: &nonce,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let verify = |proof: &super::Proof| {
+            super::interactive::verify(&aux, data, &commitment, &security, &challenge, proof)
+                .map_err(|e| e.reason())
+        };
+        verify(&proof).expect("proof failed");
+
+        let q = Integer::curve_order::<C>();
+        let bound = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let curve_check =
+            |z1: &Integer| b * z1.to_scalar() == commitment.y + x * challenge.to_scalar();
+
+        // Shifting z1 by a multiple of q past the bound satisfies the curve
+        // check, and is rejected by the range check before it is reached
+        let k = (&bound / &q).complete() * 2u8 + 1u8;
+        let mut shifted = proof.clone();
+        shifted.z1 += &q * k;
+        assert!(curve_check(&shifted.z1));
+        assert_eq!(
+            verify(&shifted).err(),
+            Some(InvalidProofReason::RangeCheck(4))
+        );
+
+        // Shifting by q within the bound also satisfies the curve check, but
+        // the paillier check binds z1 as an integer
+        let mut shifted = proof.clone();
+        if shifted.z1.cmp0().is_ge() {
+            shifted.z1 -= &q;
+        } else {
+            shifted.z1 += &q;
+        }
+        assert!(shifted.z1.is_in_pm(&bound));
+        assert!(curve_check(&shifted.z1));
+        assert_eq!(
+            verify(&shifted).err(),
+            Some(InvalidProofReason::EqualityCheck(1))
+        );
+    }
+
+    #[test]
+    fn multi_verifier() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+        let private_key0 = random_key(&mut rng).unwrap();
+        let key0 = private_key0.encryption_key().clone();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let (ciphertext, nonce) = key0.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let b = Point::<C>::generator() * Scalar::random(&mut rng);
+        let x = b * plaintext.to_scalar();
+        let data = super::Data {
+            key0: &key0,
+            c: &ciphertext,
+            x: &x,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            x: &plaintext,
+            nonce: &nonce,
+        };
+        let auxes = (0..3)
+            .map(|_| crate::common::test::aux(&mut rng))
+            .collect::<Vec<_>>();
+        let auxes = auxes.iter().collect::<Vec<_>>();
+
+        let shared_state = sha2::Sha256::default();
+        let (commitments, proofs): (Vec<_>, Vec<_>) = super::multi_verifier::prove(
+            shared_state.clone(),
+            &auxes,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap()
+        .into_iter()
+        .unzip();
+        assert_eq!(commitments.len(), 3);
+
+        let verify =
+            |commitments: &[super::Commitment<C>], verifier: usize, proof: &super::Proof| {
+                super::multi_verifier::verify(
+                    shared_state.clone(),
+                    &auxes,
+                    data,
+                    commitments,
+                    &security,
+                    verifier,
+                    proof,
+                )
+            };
+        for (i, proof) in proofs.iter().enumerate() {
+            verify(&commitments, i, proof).expect("proof failed");
+        }
+        // Proof is addressed to a specific verifier
+        assert!(verify(&commitments, 1, &proofs[0]).is_err());
+        // Proofs are bound to commitments to all verifiers
+        let mut tampered = commitments.clone();
+        tampered[2].s = tampered[1].s.clone();
+        assert!(verify(&tampered, 0, &proofs[0]).is_err());
+        // Commitments must match verifiers
+        assert_eq!(
+            verify(&commitments[..2], 0, &proofs[0])
+                .map_err(|e| e.reason())
+                .err(),
+            Some(InvalidProofReason::VerifiersMismatch)
+        );
+        assert_eq!(
+            verify(&commitments, 3, &proofs[0])
+                .map_err(|e| e.reason())
+                .err(),
+            Some(InvalidProofReason::VerifiersMismatch)
+        );
+    }
+
     #[test]
     fn passing_p256() {
         passing_test::<generic_ec::curves::Secp256r1>()
     }
     #[test]
+    fn zero_witness_p256() {
+        zero_witness::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
     fn failing_p256() {
         failing_test::<generic_ec::curves::Secp256r1>()
     }
+    #[test]
+    fn negative_challenge_p256() {
+        negative_challenge::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn degenerate_transcript_p256() {
+        degenerate_transcript::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn z1_reduction_p256() {
+        z1_reduction::<generic_ec::curves::Secp256r1>()
+    }
 
     #[test]
     fn passing_million() {
@@ -476,4 +1281,61 @@ mod test {
     fn failing_million() {
         failing_test::<crate::curve::C>()
     }
+    #[test]
+    fn negative_challenge_million() {
+        negative_challenge::<crate::curve::C>()
+    }
+    #[test]
+    fn degenerate_transcript_million() {
+        degenerate_transcript::<crate::curve::C>()
+    }
+    #[test]
+    fn z1_reduction_million() {
+        z1_reduction::<crate::curve::C>()
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l: 1024,
+            epsilon: 300,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_bound_fits_curve: false,
+        };
+        let private_key0 = random_key(&mut rng).unwrap();
+        let key0 = private_key0.encryption_key().clone();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let (ciphertext, nonce) = key0.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let b = Point::<C>::generator() * Scalar::random(&mut rng);
+        let x = b * plaintext.to_scalar();
+        let data = super::Data {
+            key0: &key0,
+            c: &ciphertext,
+            x: &x,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            x: &plaintext,
+            nonce: &nonce,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.x, plaintext);
+        assert_eq!(witness.nonce, nonce);
+
+        assert!(super::extract::extract(data, &e1, &proof1, &e1, &proof1).is_err());
+    }
 }
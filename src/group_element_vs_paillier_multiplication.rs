@@ -0,0 +1,966 @@
+//! ZK-proof of paillier multiplication by a logarithm of a group element. Called
+//! Пmul* or Rmul* in the CGGMP21 paper.
+//!
+//! ## Description
+//!
+//! A party P has a number `X = x G`, with G being a generator of curve `E`. P
+//! obtains `D` by multiplying some ciphertext `C` at `x` homomorphically and
+//! rerandomizing it. P shares X, C and D with V and wants to prove that `D`
+//! encrypts the product of plaintext of `C` and logarithm of X, and that x is at
+//! most l bits.
+//!
+//! Given:
+//! - `key0`, `pkey0` - pair of public and private keys in paillier cryptosystem
+//! - Curve `E`
+//! - `C` - some ciphertext encrypted on `key0`
+//! - `X = x G` and `D = C^x rho^N0 mod N0^2` - data to obtain proof about
+//!
+//! Prove:
+//! - `decrypt(D) = decrypt(C) * log X mod N0`
+//! - `bitsize(x) <= l`
+//!
+//! Disclosing only: `key0`, `C`, `D`, `X`
+//!
+//! `x = 0` is a valid witness: `X` is then the identity point and `D = rho^N0` encrypts
+//! zero. Note that `omul` rejects zero scalar, so `D` has to be obtained with
+//! `encrypt_with(0, rho)` in this case.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use generic_ec::{Point, curves::Secp256k1 as E};
+//! use fast_paillier::AnyEncryptionKey;
+//! use paillier_zk::{group_element_vs_paillier_multiplication as p, IntegerExt};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover prepares the paillier keys
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key0 = private_key.encryption_key();
+//!
+//! // 2. Setup: prover has some `x`, computes `X`, and multiplies some `C` at `x`
+//!
+//! let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+//! let X = Point::<E>::generator() * x.to_scalar();
+//! let (C, _) = key0.encrypt_with_random(&mut rng, &Integer::from(42))?;
+//! let (rerandomization, nonce) = key0.encrypt_with_random(&mut rng, &Integer::ZERO)?;
+//! let D = key0.oadd(&key0.omul(&x, &C)?, &rerandomization)?;
+//!
+//! // 3. Prover computes a non-interactive proof that `D` encrypts the product:
+//!
+//! let data = p::Data { key0, c: &C, d: &D, x: &X };
+//! let (commitment, proof) = p::non_interactive::prove(
+//!     shared_state_prover,
+//!     &aux,
+//!     data,
+//!     p::PrivateData { x: &x, nonce: &nonce },
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data<E>, _: &p::Commitment<E>, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use generic_ec::{Curve, Point};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{InvalidProof, PreparedCiphertext};
+use crate::compatibility::Incompatible;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::Aux;
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// l in paper, bit size of +-x
+    pub l: usize,
+    /// Epsilon in paper, slackness parameter
+    pub epsilon: usize,
+    /// q in paper. Security parameter for challenge
+    pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+    /// Return an error at prove time if the bound on `x` exceeds the curve order
+    ///
+    /// Disabled by default, see [`SecurityParams::with_bound_checked_against_curve`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub require_bound_fits_curve: bool,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_bound_fits_curve: false,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// See [`paillier_encryption_in_range::SecurityParams::with_aux_digest`](crate::paillier_encryption_in_range::SecurityParams::with_aux_digest)
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+
+    /// Makes proving fail if `x` bound `l` has more bits than the curve order
+    ///
+    /// When `x` is ultimately used as a scalar of the curve, proving a bound
+    /// larger than `q` is usually a configuration bug. See
+    /// [`SecurityParams::assert_bound_fits_curve`]
+    pub fn with_bound_checked_against_curve(self) -> Self {
+        Self {
+            require_bound_fits_curve: true,
+            ..self
+        }
+    }
+
+    /// Checks that `x` bound `l` doesn't exceed bit size of the order of curve `C`
+    pub fn assert_bound_fits_curve<C: Curve>(&self) -> Result<(), Incompatible> {
+        crate::compatibility::bound_fits_curve::<C>(self.l)
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 512, (Integer::ONE << 128_u32).complete())
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a, C: Curve> {
+    /// N0 in paper, public key that C and D were encrypted on
+    pub key0: &'a dyn AnyEncryptionKey,
+    /// C in paper, ciphertext multiplied at x
+    pub c: &'a Ciphertext,
+    /// D in paper, product of C and x rerandomized with nonce rho
+    pub d: &'a Ciphertext,
+    /// X in paper, exponent of x
+    pub x: &'a Point<C>,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// x in paper, logarithm of X
+    pub x: &'a Integer,
+    /// rho in paper, nonce rerandomizing D
+    pub nonce: &'a Nonce,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a, C: Curve> {
+    data: Data<'a, C>,
+    c: PreparedCiphertext,
+    d: PreparedCiphertext,
+}
+
+impl<'a, C: Curve> PreparedStatement<'a, C> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a, C>) -> Result<Self, InvalidProof> {
+        let c = PreparedCiphertext::new(data.key0, data.c)?;
+        let d = PreparedCiphertext::new(data.key0, data.d)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, c, d })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a, C> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Commitment<C: Curve> {
+    pub a: Ciphertext,
+    pub b_x: Point<C>,
+    pub e: Integer,
+    pub s: Integer,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub alpha: Integer,
+    pub r: Nonce,
+    pub gamma: Integer,
+    pub m: Integer,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub z1: Integer,
+    pub z2: Integer,
+    pub w: Integer,
+}
+
+impl_encode! { Data<'a, C: Curve> { key0, c, d, x } }
+impl_encode! { Commitment<C: Curve> { a, b_x, e, s } }
+impl_decode! { Commitment<C: Curve> { a, b_x, e, s } }
+impl_encode! { Proof { z1, z2, w } }
+impl_decode! { Proof { z1, z2, w } }
+
+impl<C: Curve> Commitment<C> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("a", 2 * modulus_bits)
+            .point::<C>("b_x")
+            .integer("e", modulus_bits)
+            .integer("s", modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        let z2 = z1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .integer("z2", z2)
+            .integer("w", modulus_bits)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use generic_ec::{Curve, Point};
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProofReason,
+    };
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<C: Curve, R: RngCore>(
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        mut rng: R,
+    ) -> Result<(Commitment<C>, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        crate::compatibility::check_bound_fits_curve::<C>(
+            security.l,
+            security.require_bound_fits_curve,
+        )?;
+        let two_to_l_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let hat_n_at_two_to_l = &aux.rsa_modulo * (Integer::ONE << security.l).complete();
+        let hat_n_at_two_to_l_e = (&aux.rsa_modulo * &two_to_l_e).complete();
+
+        let alpha = Integer::from_rng_pm(&two_to_l_e, &mut rng);
+        let r = Integer::gen_invertible(data.key0.n(), &mut rng);
+        let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_e, &mut rng);
+        let m = Integer::from_rng_pm(&hat_n_at_two_to_l, &mut rng);
+
+        let commitment = Commitment {
+            a: data.key0.oadd(
+                &data.key0.omul(&alpha, data.c)?,
+                &data.key0.encrypt_with(&Integer::ZERO, &r)?,
+            )?,
+            b_x: Point::generator() * alpha.to_scalar(),
+            e: aux.combine(&alpha, &gamma)?,
+            s: aux.combine(pdata.x, &m)?,
+        };
+        let private_commitment = PrivateCommitment { alpha, r, gamma, m };
+        Ok((commitment, private_commitment))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove<C: Curve>(
+        data: Data<C>,
+        pdata: PrivateData,
+        pcomm: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        Ok(Proof {
+            z1: (&pcomm.alpha + challenge * pdata.x).complete(),
+            z2: (&pcomm.gamma + challenge * &pcomm.m).complete(),
+            w: data
+                .key0
+                .n()
+                .combine(&pcomm.r, Integer::ONE, pdata.nonce, challenge)?,
+        })
+    }
+
+    /// Verify the proof
+    pub fn verify<C: Curve>(
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key0.n()))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key0.n()))
+    }
+
+    fn check_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(&aux.rsa_modulo, [&commitment.e, &commitment.s])?;
+        fail_if_degenerate(data.key0.nn(), [&commitment.a])?;
+        crate::common::fail_if_not_coprime(data.key0.n(), [&proof.w])?;
+        fail_if(
+            InvalidProofReason::DegenerateCommitment,
+            !commitment.b_x.is_zero(),
+        )?;
+        {
+            let lhs = {
+                let z1_at_c = statement.c.omul(data.key0, &proof.z1)?;
+                let w_to_n = data
+                    .key0
+                    .encrypt_with(&Integer::ZERO, &proof.w)
+                    .map_err(|_| InvalidProofReason::PaillierEnc)?;
+                data.key0
+                    .oadd(&z1_at_c, &w_to_n)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            let rhs = {
+                let e_at_d = statement.d.omul(data.key0, challenge)?;
+                data.key0
+                    .oadd(&commitment.a, &e_at_d)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("multiplication"));
+        {
+            let lhs = Point::generator() * proof.z1.to_scalar();
+            let rhs = commitment.b_x + data.x * challenge.to_scalar();
+            fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("group element"));
+        {
+            let lhs = aux.combine(&proof.z1, &proof.z2)?;
+            let s_to_e = aux.pow_mod(&commitment.s, challenge)?;
+            let rhs = (&commitment.e * s_to_e).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen"));
+        fail_if(
+            InvalidProofReason::RangeCheck(4),
+            proof
+                .z1
+                .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete()),
+        )?;
+        crate::common::lap!(Check("range of z1"));
+
+        Ok(())
+    }
+
+    /// Generate random challenge
+    pub fn challenge<R>(security: &SecurityParams, rng: &mut R) -> Integer
+    where
+        R: RngCore,
+    {
+        Integer::from_rng_pm(&security.q, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<C: Curve, R: RngCore, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof), Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<C: Curve, R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof), Error> {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+
+    /// Internal function for deriving challenge from protocol values
+    /// deterministically
+    pub fn challenge<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge {
+        transcript.label(b"paillier_zk.group_element_vs_paillier_multiplication");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb(b"l", &(security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"N0", data.key0.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_value(b"D", data.d);
+        transcript.absorb_point(b"X", data.x);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use generic_ec::{Curve, Point};
+    use rug::{Complete, Integer};
+
+    use crate::common::extract::{
+        challenge_diff, ensure, linear_response, omul, paillier_nonce, ExtractionErrorReason,
+    };
+    use crate::common::IntegerExt;
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub x: Integer,
+        pub nonce: Nonce,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract<C: Curve>(
+        data: Data<C>,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let x = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        ensure(Point::generator() * x.to_scalar::<C>() == *data.x)?;
+
+        // D C^-x = rho^N0 mod N0^2
+        let minus_x_at_c = omul(data.key0, &(-&x).complete(), data.c)?;
+        let rho_to_n = data
+            .key0
+            .oadd(data.d, &minus_x_at_c)
+            .map_err(|_| ExtractionErrorReason::WitnessMismatch)?;
+        let nonce = paillier_nonce(data.key0.n(), &rho_to_n, &proof1.w, &proof2.w, &e_diff)?;
+
+        let x_at_c = omul(data.key0, &x, data.c)?;
+        let d = data
+            .key0
+            .encrypt_with(&Integer::ZERO, &nonce)
+            .and_then(|rerandomization| data.key0.oadd(&x_at_c, &rerandomization))
+            .ok();
+        ensure(d.as_ref() == Some(data.d))?;
+        Ok(Witness { x, nonce })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fast_paillier::EncryptionKey;
+    use generic_ec::{Curve, Point};
+    use rug::{Complete, Integer};
+
+    use crate::common::test::random_key;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    struct Statement<C: Curve> {
+        key0: EncryptionKey,
+        c: Integer,
+        d: Integer,
+        x: Point<C>,
+        x_plain: Integer,
+        nonce: Integer,
+    }
+
+    impl<C: Curve> Statement<C> {
+        fn data(&self) -> super::Data<'_, C> {
+            super::Data {
+                key0: &self.key0,
+                c: &self.c,
+                d: &self.d,
+                x: &self.x,
+            }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_> {
+            super::PrivateData {
+                x: &self.x_plain,
+                nonce: &self.nonce,
+            }
+        }
+    }
+
+    fn statement<C: Curve, R: rand_core::RngCore + rand_core::CryptoRng>(
+        rng: &mut R,
+        x: Integer,
+    ) -> Statement<C> {
+        let key0 = random_key(rng).unwrap().encryption_key().clone();
+        let plaintext = Integer::from_rng_pm(key0.half_n(), rng);
+        let (c, _) = key0.encrypt_with_random(rng, &plaintext).unwrap();
+        let nonce = Integer::gen_invertible(key0.n(), rng);
+        // `omul` rejects x = 0
+        let x_at_c = Integer::from(c.pow_mod_ref(&x, key0.nn()).unwrap());
+        let d = key0
+            .oadd(&x_at_c, &key0.encrypt_with(&Integer::ZERO, &nonce).unwrap())
+            .unwrap();
+        Statement {
+            x: Point::generator() * x.to_scalar(),
+            key0,
+            c,
+            d,
+            x_plain: x,
+            nonce,
+        }
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete())
+    }
+
+    fn run<C: Curve>(x: Integer) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement::<C, _>(&mut rng, x);
+        let aux = crate::common::test::aux(&mut rng);
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::non_interactive::verify(
+            shared_state,
+            &aux,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+    }
+
+    fn passing_test<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security().l).complete(), &mut rng);
+        run::<C>(x).expect("proof failed");
+    }
+
+    fn zero_witness<C: Curve>() {
+        // X is the identity point, D encrypts zero
+        run::<C>(Integer::ZERO).expect("proof failed");
+    }
+
+    fn failing_test<C: Curve>() {
+        let security = security();
+        let x = (Integer::ONE << (security.l + security.epsilon + 1)).complete();
+        let r = run::<C>(x).expect_err("proof should not pass");
+        match r.reason() {
+            InvalidProofReason::RangeCheck(_) => (),
+            e => panic!("proof should not fail with: {e:?}"),
+        }
+    }
+
+    fn wrong_product<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let mut statement = statement::<C, _>(&mut rng, x);
+        statement.d = statement.key0.oadd(&statement.d, &statement.c).unwrap();
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) = super::interactive::commit(
+            &aux,
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(
+            statement.data(),
+            statement.pdata(),
+            &pcommitment,
+            &challenge,
+        )
+        .unwrap();
+        let r = super::interactive::verify(
+            &aux,
+            statement.data(),
+            &commitment,
+            &security,
+            &challenge,
+            &proof,
+        );
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::EqualityCheck(1))
+        );
+    }
+
+    fn degenerate_transcript<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let statement = statement::<C, _>(&mut rng, x);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Negative challenge is fine
+        let challenge = -super::interactive::challenge(&security, &mut rng).abs() - 1;
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof)
+            .expect("proof with negative challenge failed");
+
+        // Commitment equal to one is rejected
+        let mut degenerate = commitment.clone();
+        degenerate.e = Integer::from(1);
+        let r = super::interactive::verify(&aux, data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+
+        // Identity point in commitment is rejected
+        let mut commitment = commitment;
+        commitment.b_x = Point::zero();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[test]
+    fn passing_p256() {
+        passing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn zero_witness_p256() {
+        zero_witness::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn failing_p256() {
+        failing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn wrong_product_p256() {
+        wrong_product::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn degenerate_transcript_p256() {
+        degenerate_transcript::<generic_ec::curves::Secp256r1>()
+    }
+
+    #[test]
+    fn passing_million() {
+        passing_test::<crate::curve::C>()
+    }
+    #[test]
+    fn failing_million() {
+        failing_test::<crate::curve::C>()
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        // Zero is a legitimate witness
+        for x in [x, Integer::ZERO] {
+            let statement = statement::<C, _>(&mut rng, x);
+            let (data, pdata) = (statement.data(), statement.pdata());
+            let aux = crate::common::test::aux(&mut rng);
+
+            let (_commitment, pcommitment) =
+                super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+            let e1 = super::interactive::challenge(&security, &mut rng);
+            let e2 = super::interactive::challenge(&security, &mut rng);
+            let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+            let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+            let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+            assert_eq!(witness.x, statement.x_plain);
+            assert_eq!(witness.nonce, statement.nonce);
+
+            assert!(super::extract::extract(data, &e1, &proof1, &e1, &proof1).is_err());
+        }
+    }
+}
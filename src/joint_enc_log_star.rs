@@ -0,0 +1,926 @@
+//! Joint Пenc and Пlog* proof of the same plaintext
+//!
+//! ## Description
+//!
+//! A party P has encrypted x twice, as K for Пenc and as C for Пlog*, and has
+//! `X = x B`. P needs to prove both statements to the same verifier. Proving them
+//! independently commits to x twice. This proof answers both statements with a
+//! single challenge and shares everything that depends on x only: ring-pedersen
+//! commitments `S`, `D`, and responses `z1`, `z3`. Compared to two independent
+//! proofs, it saves two integers modulo `N^` in the commitment, two responses, and
+//! two multiexponentiations modulo `N^` for prover and verifier.
+//!
+//! Given:
+//! - `key_enc`, `key0` - public keys in paillier cryptosystem, may be the same key
+//! - Curve `E` and basepoint `B`
+//! - `K = key_enc.encrypt(x)`, `C = key0.encrypt(x)`, `X = x B` - data to obtain
+//!   proof about
+//!
+//! Prove:
+//! - `decrypt(K) = decrypt(C) = log_B X`
+//! - `bitsize(x) <= l`
+//!
+//! Disclosing only: `key_enc`, `key0`, `K`, `C`, `X`
+//!
+//! `x = 0` is a valid witness: `X` is then the identity point, `K` and `C` encrypt zero.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use generic_ec::{Point, curves::Secp256k1 as E};
+//! use paillier_zk::{
+//!     joint_enc_log_star as p, paillier_encryption_in_range as enc,
+//!     group_element_vs_paillier_encryption_in_range as log_star, IntegerExt,
+//! };
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover prepares the paillier keys
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key0 = private_key.encryption_key();
+//!
+//! // 2. Setup: prover encrypts `x` twice and computes `X`
+//!
+//! let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+//! let (K, nonce_enc) = key0.encrypt_with_random(&mut rng, &x)?;
+//! let (C, nonce_log_star) = key0.encrypt_with_random(&mut rng, &x)?;
+//! let X = Point::<E>::generator() * x.to_scalar();
+//!
+//! // 3. Prover computes a non-interactive proof of both statements:
+//!
+//! let data = p::Data {
+//!     enc: enc::Data { key: key0, ciphertext: &K },
+//!     log_star: log_star::Data {
+//!         key0,
+//!         c: &C,
+//!         x: &X,
+//!         b: &Point::<E>::generator().into(),
+//!     },
+//! };
+//! let pdata = p::PrivateData { x: &x, nonce_enc: &nonce_enc, nonce_log_star: &nonce_log_star };
+//! let (commitment, proof) =
+//!     p::non_interactive::prove(shared_state_prover, &aux, data, pdata, &security, &mut rng)?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data<E>, _: &p::Commitment<E>, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{Ciphertext, Nonce};
+use generic_ec::{Curve, Point};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{InvalidProof, PreparedCiphertext};
+use crate::{
+    group_element_vs_paillier_encryption_in_range as log_star, paillier_encryption_in_range as enc,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::Aux;
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// l in paper, bit size of +-plaintext
+    pub l: usize,
+    /// Epsilon in paper, slackness parameter
+    pub epsilon: usize,
+    /// q in paper. Security parameter for challenge
+    pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// See [`paillier_encryption_in_range::SecurityParams::with_aux_digest`](crate::paillier_encryption_in_range::SecurityParams::with_aux_digest)
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 512, (Integer::ONE << 128_u32).complete())
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a, C: Curve> {
+    /// Statement of Пenc
+    pub enc: enc::Data<'a>,
+    /// Statement of Пlog*
+    pub log_star: log_star::Data<'a, C>,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// x in paper, plaintext of K and C and logarithm of X
+    pub x: &'a Integer,
+    /// rho in Пenc, nonce in encryption x -> K
+    pub nonce_enc: &'a Nonce,
+    /// rho in Пlog*, nonce in encryption x -> C
+    pub nonce_log_star: &'a Nonce,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a, C: Curve> {
+    data: Data<'a, C>,
+    k: PreparedCiphertext,
+    c: PreparedCiphertext,
+}
+
+impl<'a, C: Curve> PreparedStatement<'a, C> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a, C>) -> Result<Self, InvalidProof> {
+        let k = PreparedCiphertext::new(data.enc.key, data.enc.ciphertext)?;
+        let c = PreparedCiphertext::new(data.log_star.key0, data.log_star.c)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, k, c })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a, C> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Commitment<C: Curve> {
+    pub s: Integer,
+    pub a_enc: Ciphertext,
+    pub a_log_star: Ciphertext,
+    pub y: Point<C>,
+    pub d: Integer,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub alpha: Integer,
+    pub mu: Integer,
+    pub r_enc: Nonce,
+    pub r_log_star: Nonce,
+    pub gamma: Integer,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub z1: Integer,
+    pub z2_enc: Integer,
+    pub z2_log_star: Integer,
+    pub z3: Integer,
+}
+
+impl_encode! { Data<'a, C: Curve> { enc, log_star } }
+impl_encode! { Commitment<C: Curve> { s, a_enc, a_log_star, y, d } }
+impl_decode! { Commitment<C: Curve> { s, a_enc, a_log_star, y, d } }
+impl_encode! { Proof { z1, z2_enc, z2_log_star, z3 } }
+impl_decode! { Proof { z1, z2_enc, z2_log_star, z3 } }
+
+impl<C: Curve> Commitment<C> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of both paillier moduli and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of both paillier moduli and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("s", modulus_bits)
+            .integer("a_enc", 2 * modulus_bits)
+            .integer("a_log_star", 2 * modulus_bits)
+            .point::<C>("y")
+            .integer("d", modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of both paillier moduli and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of both paillier moduli and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        let z3 = z1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .integer("z2_enc", modulus_bits)
+            .integer("z2_log_star", modulus_bits)
+            .integer("z3", z3)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProofReason,
+    };
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<C: Curve, R: RngCore>(
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        mut rng: R,
+    ) -> Result<(Commitment<C>, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        let two_to_l_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let hat_n_at_two_to_l = &aux.rsa_modulo * (Integer::ONE << security.l).complete();
+        let hat_n_at_two_to_l_e = (&aux.rsa_modulo * &two_to_l_e).complete();
+
+        let alpha = Integer::from_rng_pm(&two_to_l_e, &mut rng);
+        let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, &mut rng);
+        let r_enc = Integer::gen_invertible(data.enc.key.n(), &mut rng);
+        let r_log_star = Integer::gen_invertible(data.log_star.key0.n(), &mut rng);
+        let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_e, &mut rng);
+
+        let commitment = Commitment {
+            s: aux.combine(pdata.x, &mu)?,
+            a_enc: data.enc.key.encrypt_with(&alpha, &r_enc)?,
+            a_log_star: data.log_star.key0.encrypt_with(&alpha, &r_log_star)?,
+            y: data.log_star.b * alpha.to_scalar(),
+            d: aux.combine(&alpha, &gamma)?,
+        };
+        let private_commitment = PrivateCommitment {
+            alpha,
+            mu,
+            r_enc,
+            r_log_star,
+            gamma,
+        };
+        Ok((commitment, private_commitment))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove<C: Curve>(
+        data: Data<C>,
+        pdata: PrivateData,
+        pcomm: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        Ok(Proof {
+            z1: (&pcomm.alpha + challenge * pdata.x).complete(),
+            z2_enc: data.enc.key.n().combine(
+                &pcomm.r_enc,
+                Integer::ONE,
+                pdata.nonce_enc,
+                challenge,
+            )?,
+            z2_log_star: data.log_star.key0.n().combine(
+                &pcomm.r_log_star,
+                Integer::ONE,
+                pdata.nonce_log_star,
+                challenge,
+            )?,
+            z3: (&pcomm.gamma + challenge * &pcomm.mu).complete(),
+        })
+    }
+
+    /// Verify the proof
+    pub fn verify<C: Curve>(
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement =
+            PreparedStatement::new(data).map_err(|err| err.with_key(data.enc.key.n()))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.enc.key.n()))
+    }
+
+    fn check_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let Data { enc, log_star } = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(&aux.rsa_modulo, [&commitment.s, &commitment.d])?;
+        fail_if_degenerate(enc.key.nn(), [&commitment.a_enc])?;
+        fail_if_degenerate(log_star.key0.nn(), [&commitment.a_log_star])?;
+        crate::common::fail_if_not_coprime(enc.key.n(), [&proof.z2_enc])?;
+        crate::common::fail_if_not_coprime(log_star.key0.n(), [&proof.z2_log_star])?;
+        fail_if(
+            InvalidProofReason::DegenerateCommitment,
+            !commitment.y.is_zero(),
+        )?;
+        {
+            let lhs = enc
+                .key
+                .encrypt_with(&proof.z1, &proof.z2_enc)
+                .map_err(|_| InvalidProofReason::PaillierEnc)?;
+            let rhs = {
+                let e_at_k = statement.k.omul(enc.key, challenge)?;
+                enc.key
+                    .oadd(&commitment.a_enc, &e_at_k)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        }
+        {
+            let lhs = log_star
+                .key0
+                .encrypt_with(&proof.z1, &proof.z2_log_star)
+                .map_err(|_| InvalidProofReason::PaillierEnc)?;
+            let rhs = {
+                let e_at_c = statement.c.omul(log_star.key0, challenge)?;
+                log_star
+                    .key0
+                    .oadd(&commitment.a_log_star, &e_at_c)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("paillier encryption"));
+        {
+            let lhs = log_star.b * proof.z1.to_scalar();
+            let rhs = commitment.y + log_star.x * challenge.to_scalar();
+            fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("group element"));
+        {
+            let lhs = aux.combine(&proof.z1, &proof.z3)?;
+            let s_to_e = aux.pow_mod(&commitment.s, challenge)?;
+            let rhs = (&commitment.d * s_to_e).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(4), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen"));
+        fail_if(
+            InvalidProofReason::RangeCheck(5),
+            proof
+                .z1
+                .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete()),
+        )?;
+        crate::common::lap!(Check("range of z1"));
+
+        Ok(())
+    }
+
+    /// Generate random challenge
+    pub fn challenge<R>(security: &SecurityParams, rng: &mut R) -> Integer
+    where
+        R: RngCore,
+    {
+        Integer::from_rng_pm(&security.q, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<C: Curve, R: RngCore, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof), Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<C: Curve, R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof), Error> {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+
+    /// Internal function for deriving challenge from protocol values
+    /// deterministically
+    pub fn challenge<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge {
+        transcript.label(b"paillier_zk.joint_enc_log_star");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb(b"l", &(security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"N_enc", data.enc.key.n());
+        transcript.absorb_value(b"K", data.enc.ciphertext);
+        transcript.absorb_value(b"N0", data.log_star.key0.n());
+        transcript.absorb_value(b"C", data.log_star.c);
+        transcript.absorb_point(b"X", data.log_star.x);
+        transcript.absorb_point(b"g", data.log_star.b);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fast_paillier::EncryptionKey;
+    use generic_ec::{Curve, Point, Scalar};
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::Encode;
+    use crate::common::test::random_key;
+    use crate::common::{IntegerExt, InvalidProofReason};
+    use crate::{
+        group_element_vs_paillier_encryption_in_range as log_star,
+        paillier_encryption_in_range as enc,
+    };
+
+    struct Statement<C: Curve> {
+        key_enc: EncryptionKey,
+        key0: EncryptionKey,
+        k: Integer,
+        c: Integer,
+        b: Point<C>,
+        x: Point<C>,
+        plaintext: Integer,
+        nonce_enc: Integer,
+        nonce_log_star: Integer,
+    }
+
+    impl<C: Curve> Statement<C> {
+        fn data(&self) -> super::Data<'_, C> {
+            super::Data {
+                enc: enc::Data {
+                    key: &self.key_enc,
+                    ciphertext: &self.k,
+                },
+                log_star: log_star::Data {
+                    key0: &self.key0,
+                    c: &self.c,
+                    b: &self.b,
+                    x: &self.x,
+                },
+            }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_> {
+            super::PrivateData {
+                x: &self.plaintext,
+                nonce_enc: &self.nonce_enc,
+                nonce_log_star: &self.nonce_log_star,
+            }
+        }
+    }
+
+    fn statement<C: Curve, R: rand_core::RngCore + rand_core::CryptoRng>(
+        rng: &mut R,
+        plaintext: Integer,
+        plaintext_enc: Integer,
+    ) -> Statement<C> {
+        let key_enc = random_key(rng).unwrap().encryption_key().clone();
+        let key0 = random_key(rng).unwrap().encryption_key().clone();
+        let (k, nonce_enc) = key_enc.encrypt_with_random(rng, &plaintext_enc).unwrap();
+        let (c, nonce_log_star) = key0.encrypt_with_random(rng, &plaintext).unwrap();
+        let b = Point::<C>::generator() * Scalar::random(rng);
+        Statement {
+            x: b * plaintext.to_scalar(),
+            key_enc,
+            key0,
+            k,
+            c,
+            b,
+            plaintext,
+            nonce_enc,
+            nonce_log_star,
+        }
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete())
+    }
+
+    fn run<C: Curve>(
+        plaintext: Integer,
+        plaintext_enc: Integer,
+    ) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement::<C, _>(&mut rng, plaintext, plaintext_enc);
+        let aux = crate::common::test::aux(&mut rng);
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::non_interactive::verify(
+            shared_state,
+            &aux,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+    }
+
+    fn passing_test<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security().l).complete(), &mut rng);
+        run::<C>(x.clone(), x).expect("proof failed");
+    }
+
+    fn zero_witness<C: Curve>() {
+        // X is the identity point, both ciphertexts encrypt zero
+        run::<C>(Integer::ZERO, Integer::ZERO).expect("proof failed");
+    }
+
+    fn failing_test<C: Curve>() {
+        let security = security();
+        let x = (Integer::ONE << (security.l + security.epsilon + 1)).complete();
+        let r = run::<C>(x.clone(), x).expect_err("proof should not pass");
+        match r.reason() {
+            InvalidProofReason::RangeCheck(_) => (),
+            e => panic!("proof should not fail with: {e:?}"),
+        }
+    }
+
+    fn different_plaintexts<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security().l).complete(), &mut rng);
+        let r = run::<C>(x.clone(), x + 1).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::EqualityCheck(1));
+    }
+
+    fn degenerate_transcript<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let statement = statement::<C, _>(&mut rng, x.clone(), x);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Negative challenge is fine
+        let challenge = -super::interactive::challenge(&security, &mut rng).abs() - 1;
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof)
+            .expect("proof with negative challenge failed");
+
+        // Commitment equal to one is rejected
+        let mut degenerate = commitment.clone();
+        degenerate.a_log_star = Integer::from(1);
+        let r = super::interactive::verify(&aux, data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[test]
+    fn passing_p256() {
+        passing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn zero_witness_p256() {
+        zero_witness::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn failing_p256() {
+        failing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn different_plaintexts_p256() {
+        different_plaintexts::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn degenerate_transcript_p256() {
+        degenerate_transcript::<generic_ec::curves::Secp256r1>()
+    }
+
+    #[test]
+    fn passing_million() {
+        passing_test::<crate::curve::C>()
+    }
+    #[test]
+    fn failing_million() {
+        failing_test::<crate::curve::C>()
+    }
+
+    #[test]
+    fn smaller_than_independent_proofs() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let statement = statement::<C, _>(&mut rng, x.clone(), x);
+        let aux = crate::common::test::aux(&mut rng);
+        let shared_state = sha2::Sha256::default();
+
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let joint = commitment.to_canonical_bytes().len() + proof.to_canonical_bytes().len();
+
+        let enc_security =
+            enc::SecurityParams::new(security.l, security.epsilon, security.q.clone());
+        let (enc_commitment, enc_proof) = enc::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            statement.data().enc,
+            enc::PrivateData {
+                plaintext: &statement.plaintext,
+                nonce: &statement.nonce_enc,
+            },
+            &enc_security,
+            &mut rng,
+        )
+        .unwrap();
+        let log_star_security =
+            log_star::SecurityParams::new(security.l, security.epsilon, security.q.clone());
+        let (log_star_commitment, log_star_proof) = log_star::non_interactive::prove(
+            shared_state,
+            &aux,
+            statement.data().log_star,
+            log_star::PrivateData {
+                x: &statement.plaintext,
+                nonce: &statement.nonce_log_star,
+            },
+            &log_star_security,
+            &mut rng,
+        )
+        .unwrap();
+        let independent = enc_commitment.to_canonical_bytes().len()
+            + enc_proof.to_canonical_bytes().len()
+            + log_star_commitment.to_canonical_bytes().len()
+            + log_star_proof.to_canonical_bytes().len();
+
+        assert!(joint < independent, "{joint} >= {independent}");
+    }
+}
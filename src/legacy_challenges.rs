@@ -0,0 +1,578 @@
+//! INSECURE: verification of proofs with challenges derived before transcripts
+//!
+//! Before [`Transcript`](crate::transcript::Transcript) was introduced, non-interactive
+//! proofs derived the challenge by hashing the shared state, security parameters,
+//! statement and commitment concatenated as is, without labels and lengths. Such
+//! encoding is ambiguous: bytes of one value can be moved into its neighbour without
+//! changing the hash, so different statements and commitments may share a challenge.
+//! Proofs made that way must not be accepted in new protocol runs.
+//!
+//! This module exists solely to re-verify proofs that were made and accepted back then,
+//! e.g. ones kept in an archive for audit. It derives the challenge exactly like the
+//! old versions did, and checks the proof with the current
+//! [`interactive::verify`](crate::paillier_encryption_in_range::interactive::verify)
+//! of the proof. There's deliberately no way to prove with the old derivation.
+//!
+//! Available with the `legacy-challenges` feature, which is off by default. Only the
+//! proofs that existed before transcripts are supported: Пenc, Пlog*, Пaff-g, Пmod and
+//! Пfac.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::legacy_challenges;
+//! use paillier_zk::paillier_encryption_in_range as p;
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     commitment: &p::Commitment,
+//! #     security: &p::SecurityParams,
+//! #     proof: &p::Proof,
+//! # ) -> Result<(), paillier_zk::InvalidProof> {
+//! // Shared state must be the same as the one the proof was made with
+//! let shared_state = sha2::Sha256::default();
+//! legacy_challenges::paillier_encryption_in_range::verify(
+//!     shared_state,
+//!     aux,
+//!     data,
+//!     commitment,
+//!     security,
+//!     proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+
+use digest::Digest;
+use rug::Integer;
+
+use crate::common::Aux;
+
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+const AUX_ABSORB_TAG: &[u8] = b"paillier_zk.aux.absorb";
+
+/// Bytes of the integer as the old derivation hashed them
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn digits(x: &Integer) -> Vec<u8> {
+    x.to_digits::<u8>(rug::integer::Order::Msf)
+}
+
+/// Absorbs ring-pedersen parameters, or their digest if it's set
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn chain_aux<D: Digest>(d: D, aux: &Aux, aux_digest: Option<&[u8]>) -> D {
+    match aux_digest {
+        Some(aux_digest) => d
+            .chain_update(AUX_ABSORB_TAG)
+            .chain_update((aux_digest.len() as u64).to_be_bytes())
+            .chain_update(aux_digest),
+        None => d
+            .chain_update(digits(&aux.s))
+            .chain_update(digits(&aux.t))
+            .chain_update(digits(&aux.rsa_modulo)),
+    }
+}
+
+/// Legacy verification of Пenc
+#[cfg(feature = "paillier-encryption-in-range")]
+pub mod paillier_encryption_in_range {
+    use digest::Digest;
+
+    use crate::paillier_encryption_in_range::{
+        interactive, Aux, Challenge, Commitment, Data, Proof, SecurityParams,
+    };
+    use crate::InvalidProof;
+
+    use super::{chain_aux, digits};
+
+    /// Verify the proof, deriving challenge the legacy way
+    pub fn verify<D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, aux, data, commitment, security);
+        interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    pub(super) fn challenge<D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let shared_state = shared_state.finalize();
+        let hash = |d: D| {
+            let d = chain_aux(
+                d.chain_update(&shared_state),
+                aux,
+                security.aux_digest.as_deref(),
+            );
+            d.chain_update(digits(data.key.n()))
+                .chain_update(digits(data.ciphertext))
+                .chain_update(digits(&commitment.s))
+                .chain_update(digits(&commitment.a))
+                .chain_update(digits(&commitment.c))
+                .finalize()
+        };
+        let mut rng = crate::common::rng::HashRng::new(hash);
+        interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Legacy verification of Пlog*
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+pub mod group_element_vs_paillier_encryption_in_range {
+    use digest::Digest;
+    use generic_ec::Curve;
+
+    use crate::group_element_vs_paillier_encryption_in_range::{
+        interactive, Aux, Challenge, Commitment, Data, Proof, SecurityParams,
+    };
+    use crate::InvalidProof;
+
+    use super::{chain_aux, digits};
+
+    /// Verify the proof, deriving challenge the legacy way
+    pub fn verify<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, aux, data, commitment, security);
+        interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    fn challenge<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let shared_state = shared_state.finalize();
+        let hash = |d: D| {
+            let d = chain_aux(
+                d.chain_update(&shared_state).chain_update(C::CURVE_NAME),
+                aux,
+                security.aux_digest.as_deref(),
+            );
+            d.chain_update((security.l as u64).to_le_bytes())
+                .chain_update((security.epsilon as u64).to_le_bytes())
+                .chain_update(digits(data.key0.n()))
+                .chain_update(digits(data.c))
+                .chain_update(data.x.to_bytes(true))
+                .chain_update(data.b.to_bytes(true))
+                .chain_update(digits(&commitment.s))
+                .chain_update(digits(&commitment.a))
+                .chain_update(commitment.y.to_bytes(true))
+                .chain_update(digits(&commitment.d))
+                .finalize()
+        };
+        let mut rng = crate::common::rng::HashRng::new(hash);
+        interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Legacy verification of Пaff-g
+#[cfg(feature = "paillier-affine-operation-in-range")]
+pub mod paillier_affine_operation_in_range {
+    use digest::Digest;
+    use generic_ec::Curve;
+
+    use crate::paillier_affine_operation_in_range::{
+        interactive, Aux, Challenge, Commitment, Data, Proof, SecurityParams,
+    };
+    use crate::InvalidProof;
+
+    use super::{chain_aux, digits};
+
+    /// Verify the proof, deriving challenge the legacy way
+    pub fn verify<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, aux, data, commitment, security);
+        interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    fn challenge<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let shared_state = shared_state.finalize();
+        let hash = |d: D| {
+            let d = chain_aux(
+                d.chain_update(&shared_state),
+                aux,
+                security.aux_digest.as_deref(),
+            );
+            d.chain_update((security.l_x as u64).to_le_bytes())
+                .chain_update((security.l_y as u64).to_le_bytes())
+                .chain_update((security.epsilon as u64).to_le_bytes())
+                .chain_update(digits(data.key0.n()))
+                .chain_update(digits(data.key1.n()))
+                .chain_update(digits(data.c))
+                .chain_update(digits(data.d))
+                .chain_update(digits(data.y))
+                .chain_update(data.x.to_bytes(true))
+                .chain_update(digits(&commitment.a))
+                .chain_update(commitment.b_x.to_bytes(true))
+                .chain_update(digits(&commitment.b_y))
+                .chain_update(digits(&commitment.e))
+                .chain_update(digits(&commitment.s))
+                .chain_update(digits(&commitment.f))
+                .chain_update(digits(&commitment.t))
+                .finalize()
+        };
+        let mut rng = crate::common::rng::HashRng::new(hash);
+        interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Legacy verification of Пmod
+#[cfg(feature = "paillier-blum-modulus")]
+pub mod paillier_blum_modulus {
+    use digest::Digest;
+
+    use crate::paillier_blum_modulus::{interactive, Challenge, Commitment, Data, Proof};
+    use crate::InvalidProof;
+
+    use super::digits;
+
+    /// Verify the proof, deriving challenge the legacy way
+    pub fn verify<const M: usize, D: Digest>(
+        shared_state: D,
+        data: &Data,
+        commitment: &Commitment,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, data, commitment);
+        interactive::verify(data, commitment, &challenge, proof)
+    }
+
+    pub(super) fn challenge<const M: usize, D: Digest>(
+        shared_state: D,
+        data: &Data,
+        commitment: &Commitment,
+    ) -> Challenge<M> {
+        let shared_state = shared_state.finalize();
+        let hash = |d: D| {
+            d.chain_update(&shared_state)
+                .chain_update(digits(&data.n))
+                .chain_update(digits(&commitment.w))
+                .finalize()
+        };
+        let mut rng = crate::common::rng::HashRng::new(hash);
+        interactive::challenge(data, &mut rng)
+    }
+}
+
+/// Legacy verification of Пfac
+#[cfg(feature = "no-small-factor")]
+pub mod no_small_factor {
+    use digest::Digest;
+
+    use crate::no_small_factor::{
+        interactive, Aux, Challenge, Commitment, Data, Proof, SecurityParams,
+    };
+    use crate::InvalidProof;
+
+    use super::{chain_aux, digits};
+
+    /// Verify the proof, deriving challenge the legacy way
+    pub fn verify<D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, aux, data, commitment, security);
+        interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    fn challenge<D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let shared_state = shared_state.finalize();
+        let hash = |d: D| {
+            let d = chain_aux(
+                d.chain_update(&shared_state),
+                aux,
+                security.aux_digest.as_deref(),
+            );
+            d.chain_update(digits(data.n))
+                .chain_update(digits(data.n_root))
+                .chain_update(digits(&commitment.p))
+                .chain_update(digits(&commitment.q))
+                .chain_update(digits(&commitment.a))
+                .chain_update(digits(&commitment.b))
+                .chain_update(digits(&commitment.t))
+                .chain_update(digits(&commitment.sigma))
+                .finalize()
+        };
+        let mut rng = crate::common::rng::HashRng::new(hash);
+        interactive::challenge(security, &mut rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg_attr(not(feature = "all-proofs"), allow(unused_imports))]
+    use rug::{Complete, Integer};
+
+    #[cfg(feature = "paillier-encryption-in-range")]
+    #[test]
+    fn archived_enc_proof() {
+        use crate::paillier_encryption_in_range as p;
+
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let shared_state = sha2::Sha256::default();
+
+        // Proof made the way old versions did
+        let (commitment, pcomm) =
+            p::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let challenge = super::paillier_encryption_in_range::challenge(
+            shared_state.clone(),
+            &aux,
+            data,
+            &commitment,
+            &security,
+        );
+        let proof = p::interactive::prove(data, pdata, &pcomm, &challenge).unwrap();
+
+        super::paillier_encryption_in_range::verify(
+            shared_state.clone(),
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        )
+        .expect("proof failed");
+        p::non_interactive::verify(
+            shared_state.clone(),
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        )
+        .expect_err("legacy proof should not pass current verification");
+
+        // And vice versa
+        let (commitment, proof) =
+            p::non_interactive::prove(shared_state.clone(), &aux, data, pdata, &security, &mut rng)
+                .unwrap();
+        super::paillier_encryption_in_range::verify(
+            shared_state,
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        )
+        .expect_err("current proof should not pass legacy verification");
+    }
+
+    #[cfg(feature = "paillier-blum-modulus")]
+    #[test]
+    fn archived_mod_proof() {
+        use crate::paillier_blum_modulus as p;
+
+        let mut rng = rand_dev::DevRng::new();
+        let pr = crate::common::test::generate_blum_prime(&mut rng, 256);
+        let qr = crate::common::test::generate_blum_prime(&mut rng, 256);
+        let data = p::Data {
+            n: (&pr * &qr).complete(),
+        };
+        let pdata = p::PrivateData { p: pr, q: qr };
+        let shared_state = sha2::Sha256::default();
+
+        let commitment = p::interactive::commit(&data, &mut rng);
+        let challenge: p::Challenge<32> =
+            super::paillier_blum_modulus::challenge(shared_state.clone(), &data, &commitment);
+        let proof = p::interactive::prove(&data, &pdata, &commitment, &challenge).unwrap();
+
+        super::paillier_blum_modulus::verify(shared_state.clone(), &data, &commitment, &proof)
+            .expect("proof failed");
+        p::non_interactive::verify(shared_state, &data, &commitment, &proof)
+            .expect_err("legacy proof should not pass current verification");
+    }
+
+    /// Known answer tests: proofs made by `non_interactive::prove` of the release
+    /// that derived challenges the legacy way, with the statements they're made for
+    #[cfg(feature = "serde")]
+    mod known_answers {
+        use sha2::Digest;
+
+        /// Shared state the proofs were made with
+        #[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+        fn shared_state() -> sha2::Sha256 {
+            sha2::Sha256::new_with_prefix(b"paillier-zk legacy challenges known answer test")
+        }
+
+        #[cfg_attr(not(feature = "all-proofs"), allow(unused_macros))]
+        macro_rules! load {
+            ($name:literal) => {{
+                let json = include_str!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/test-data/legacy_challenges/",
+                    $name,
+                    ".json"
+                ));
+                serde_json::from_str::<serde_json::Value>(json).unwrap()
+            }};
+        }
+
+        fn field<T: serde::de::DeserializeOwned>(kat: &serde_json::Value, name: &str) -> T {
+            serde_json::from_value(kat[name].clone()).unwrap()
+        }
+
+        #[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+        fn aux(kat: &serde_json::Value) -> crate::common::Aux {
+            let aux = &kat["aux"];
+            crate::common::Aux::new(field(aux, "s"), field(aux, "t"), field(aux, "rsa_modulo"))
+        }
+
+        #[cfg(feature = "paillier-encryption-in-range")]
+        #[test]
+        fn enc() {
+            use crate::paillier_encryption_in_range as p;
+
+            let kat = load!("paillier_encryption_in_range");
+            let key: fast_paillier::EncryptionKey = field(&kat, "key");
+            let ciphertext = field(&kat, "ciphertext");
+            let data = p::Data {
+                key: &key,
+                ciphertext: &ciphertext,
+            };
+            super::super::paillier_encryption_in_range::verify(
+                shared_state(),
+                &aux(&kat),
+                data,
+                &field(&kat, "commitment"),
+                &field(&kat, "security"),
+                &field(&kat, "proof"),
+            )
+            .expect("known answer rejected");
+        }
+
+        #[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+        #[test]
+        fn log_star() {
+            use crate::group_element_vs_paillier_encryption_in_range as p;
+            use generic_ec::{curves::Secp256k1, Point};
+
+            let kat = load!("group_element_vs_paillier_encryption_in_range");
+            let key0: fast_paillier::EncryptionKey = field(&kat, "key0");
+            let c = field(&kat, "c");
+            let x: Point<Secp256k1> = field(&kat, "x");
+            let b = field(&kat, "b");
+            let data = p::Data {
+                key0: &key0,
+                c: &c,
+                x: &x,
+                b: &b,
+            };
+            super::super::group_element_vs_paillier_encryption_in_range::verify(
+                shared_state(),
+                &aux(&kat),
+                data,
+                &field(&kat, "commitment"),
+                &field(&kat, "security"),
+                &field(&kat, "proof"),
+            )
+            .expect("known answer rejected");
+        }
+
+        #[cfg(feature = "paillier-affine-operation-in-range")]
+        #[test]
+        fn aff_g() {
+            use crate::paillier_affine_operation_in_range as p;
+            use generic_ec::{curves::Secp256k1, Point};
+
+            let kat = load!("paillier_affine_operation_in_range");
+            let key0: fast_paillier::EncryptionKey = field(&kat, "key0");
+            let key1: fast_paillier::EncryptionKey = field(&kat, "key1");
+            let (c, d, y) = (field(&kat, "c"), field(&kat, "d"), field(&kat, "y"));
+            let x: Point<Secp256k1> = field(&kat, "x");
+            let data = p::Data {
+                key0: &key0,
+                key1: &key1,
+                c: &c,
+                d: &d,
+                y: &y,
+                x: &x,
+            };
+            super::super::paillier_affine_operation_in_range::verify(
+                shared_state(),
+                &aux(&kat),
+                data,
+                &field(&kat, "commitment"),
+                &field(&kat, "security"),
+                &field(&kat, "proof"),
+            )
+            .expect("known answer rejected");
+        }
+
+        #[cfg(feature = "paillier-blum-modulus")]
+        #[test]
+        fn mod_() {
+            use crate::paillier_blum_modulus as p;
+
+            let kat = load!("paillier_blum_modulus");
+            let proof: p::Proof<32> = field(&kat, "proof");
+            super::super::paillier_blum_modulus::verify(
+                shared_state(),
+                &field(&kat, "data"),
+                &field(&kat, "commitment"),
+                &proof,
+            )
+            .expect("known answer rejected");
+        }
+
+        #[cfg(feature = "no-small-factor")]
+        #[test]
+        fn fac() {
+            use crate::no_small_factor as p;
+
+            let kat = load!("no_small_factor");
+            let (n, n_root) = (field(&kat, "n"), field(&kat, "n_root"));
+            let data = p::Data {
+                n: &n,
+                n_root: &n_root,
+            };
+            super::super::no_small_factor::verify(
+                shared_state(),
+                &aux(&kat),
+                data,
+                &field(&kat["proof"], "commitment"),
+                &field(&kat, "security"),
+                &field(&kat["proof"], "proof"),
+            )
+            .expect("known answer rejected");
+        }
+    }
+}
@@ -3,33 +3,178 @@
 
 use thiserror::Error;
 
+pub mod archive;
+pub mod aux_generation;
+pub mod batch;
+pub mod bundle;
+pub mod chain;
 mod common;
+pub mod compatibility;
+pub mod compose;
+#[cfg(feature = "dlog-vs-elgamal-commitment")]
+pub mod dlog_vs_elgamal_commitment;
+#[cfg(feature = "entropy-check")]
+pub mod entropy_check;
+#[cfg(feature = "exact-range")]
+pub mod exact_range;
+#[cfg(feature = "factorization-knowledge")]
+pub mod factorization_knowledge;
+pub mod ffi;
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
 pub mod group_element_vs_paillier_encryption_in_range;
+#[cfg(feature = "group-element-vs-paillier-multiplication")]
+pub mod group_element_vs_paillier_multiplication;
+#[cfg(feature = "joint-enc-log-star")]
+pub mod joint_enc_log_star;
+#[cfg(feature = "legacy-challenges")]
+pub mod legacy_challenges;
+pub mod memory;
 pub mod multiexp;
+#[cfg(feature = "no-small-factor")]
 pub mod no_small_factor;
+#[cfg(feature = "paillier-affine-operation-in-range")]
 pub mod paillier_affine_operation_in_range;
+#[cfg(feature = "paillier-affine-operation-with-paillier-commitment")]
+pub mod paillier_affine_operation_with_paillier_commitment;
+#[cfg(feature = "paillier-blum-modulus")]
 pub mod paillier_blum_modulus;
+#[cfg(feature = "paillier-broadcast-encryption")]
+pub mod paillier_broadcast_encryption;
+#[cfg(feature = "paillier-decryption-modulo-q")]
+pub mod paillier_decryption_modulo_q;
+#[cfg(feature = "paillier-encryption-in-range")]
 pub mod paillier_encryption_in_range;
+#[cfg(feature = "paillier-encryption-in-range-with-elgamal")]
+pub mod paillier_encryption_in_range_with_elgamal;
+#[cfg(feature = "paillier-encryption-of-bit")]
+pub mod paillier_encryption_of_bit;
+#[cfg(feature = "paillier-encryption-of-zero")]
+pub mod paillier_encryption_of_zero;
+#[cfg(feature = "paillier-multiplication")]
+pub mod paillier_multiplication;
+#[cfg(feature = "paillier-plaintext-equality")]
+pub mod paillier_plaintext_equality;
+#[cfg(feature = "paillier-plaintext-knowledge")]
+pub mod paillier_plaintext_knowledge;
+#[cfg(feature = "paillier-scalar-multiplication-in-range")]
+pub mod paillier_scalar_multiplication_in_range;
+#[cfg(feature = "pedersen-commitment-vs-paillier-encryption-in-range")]
+pub mod pedersen_commitment_vs_paillier_encryption_in_range;
+pub mod policy;
+#[cfg(feature = "presigning-proofs")]
+pub mod presigning_proofs;
+pub mod redundant;
+pub mod reprove;
+#[cfg(feature = "ring-pedersen-parameters")]
+pub mod ring_pedersen_parameters;
+#[cfg(feature = "schnorr-pok")]
+pub mod schnorr_pok;
+pub mod security_level;
+pub mod self_test;
+#[cfg(feature = "setup-proofs")]
+pub mod setup_proofs;
+#[cfg(feature = "timings")]
+pub mod timings;
+pub mod transcript;
+pub mod typed;
+pub mod utils;
+pub mod validated;
+pub mod witness;
+#[cfg(feature = "witness-escrow")]
+pub mod witness_escrow;
+#[cfg(feature = "zengo-interop")]
+pub mod zengo_interop;
 
 #[cfg(test)]
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
 mod curve;
 
 #[cfg(all(doctest, not(feature = "__internal_doctest")))]
 compile_error!("doctest require that `__internal_doctest` feature is turned on");
 
+#[cfg(all(feature = "insecure-small-keys", not(debug_assertions)))]
+compile_error!(
+    "`insecure-small-keys` feature makes proofs insecure and must not be enabled in \
+     release builds; enable it for dev-dependencies only"
+);
+
 #[cfg(feature = "__internal_doctest")]
 pub mod _doctest;
 
+#[cfg(feature = "research")]
+pub use common::extract::ExtractionError;
+#[cfg(feature = "key-fingerprints")]
+pub use common::fingerprint::KeyFingerprint;
+#[cfg(feature = "statement-ids")]
+pub use common::statement_id::StatementId;
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_imports))]
 use common::InvalidProofReason;
-pub use common::{rng, BadExponent, IntegerExt, InvalidProof, PaillierError};
+pub use common::{encoding, rng, BadExponent, IntegerExt, InvalidProof, PaillierError, WeakAux};
+pub use self_test::self_test;
 pub use {fast_paillier, rug, rug::Integer};
 
 /// Library general error type
+///
+/// Returned by proving functions of every proof. Verification errors
+/// ([`InvalidProof`]) convert into it as well, so an application that proves and
+/// verifies several kinds of proofs can propagate all the errors with `?`, and
+/// still tell them apart with [`Error::invalid_proof`] and [`Error::proof`].
+///
+/// ```rust,no_run
+/// use paillier_zk::{no_small_factor as nsf, paillier_encryption_in_range as enc};
+/// use paillier_zk::policy::Statement;
+/// # fn doc_fn(
+/// #     enc: (&enc::Aux, enc::Data, &enc::Commitment, &enc::SecurityParams, &enc::Proof),
+/// #     nsf: (&nsf::Aux, nsf::Data, &nsf::SecurityParams, &nsf::non_interactive::Proof),
+/// # ) -> Result<(), paillier_zk::Error> {
+/// let (aux, data, commitment, security, proof) = enc;
+/// enc::non_interactive::verify(sha2::Sha256::default(), aux, data, commitment, security, proof)
+///     .map_err(|err| paillier_zk::Error::from(err).in_proof(enc::Data::PROOF))?;
+/// let (aux, data, security, proof) = nsf;
+/// nsf::non_interactive::verify(sha2::Sha256::default(), aux, data, security, proof)?;
+/// # Ok(()) }
+/// ```
 #[derive(Debug, Error)]
 #[error(transparent)]
 pub struct Error(#[from] ErrorReason);
 
+impl Error {
+    /// Tags the error with the proof it was returned by
+    ///
+    /// `proof` is the name of the proof module, i.e. [`Statement::PROOF`](policy::Statement::PROOF)
+    /// of its statement. Returned by [`Error::proof`].
+    pub fn in_proof(self, proof: &'static str) -> Self {
+        Self(ErrorReason::InProof(proof, Box::new(self.0)))
+    }
+
+    /// Name of the proof module that the error was [tagged](Error::in_proof) with
+    pub fn proof(&self) -> Option<&'static str> {
+        match &self.0 {
+            ErrorReason::InProof(proof, _) => Some(proof),
+            _ => None,
+        }
+    }
+
+    /// Verification error, if the error was converted from [`InvalidProof`]
+    pub fn invalid_proof(&self) -> Option<&InvalidProof> {
+        match self.reason() {
+            ErrorReason::InvalidProof(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Reason of the error, regardless of the tags
+    fn reason(&self) -> &ErrorReason {
+        let mut reason = &self.0;
+        while let ErrorReason::InProof(_, source) = reason {
+            reason = source;
+        }
+        reason
+    }
+}
+
 #[derive(Debug, Error)]
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
 enum ErrorReason {
     #[error("couldn't evaluate modpow")]
     ModPow(
@@ -47,6 +192,41 @@ enum ErrorReason {
     Paillier(#[source] fast_paillier::Error),
     #[error("bug: vec has unexpected length")]
     Length,
+    #[error("other prover sent malformed message")]
+    MalformedMessage(#[source] InvalidProof),
+    #[error("insecure ring-pedersen parameters")]
+    WeakAux(#[source] common::WeakAux),
+    #[error("produced proof doesn't verify")]
+    SelfCheck(#[source] InvalidProof),
+    #[error("plaintext is not in the interval")]
+    OutOfInterval,
+    #[error("witness doesn't match the private commitment")]
+    WitnessMismatch,
+    #[error("commitment randomness repeats, RNG is broken")]
+    #[cfg_attr(not(feature = "entropy-check"), allow(dead_code))]
+    RepeatedRandomness,
+    #[error("couldn't compute with the witness")]
+    Witness(#[source] witness::WitnessError),
+    #[error("witness bound doesn't fit the curve")]
+    BoundExceedsCurve(#[source] compatibility::Incompatible),
+    #[error("security parameters are incompatible with the statement")]
+    IncompatibleParams(#[source] compatibility::Incompatible),
+    #[error("proof is invalid")]
+    InvalidProof(#[source] InvalidProof),
+    #[error("{0} failed")]
+    InProof(&'static str, #[source] Box<ErrorReason>),
+}
+
+impl From<InvalidProof> for Error {
+    fn from(err: InvalidProof) -> Self {
+        Error(ErrorReason::InvalidProof(err))
+    }
+}
+
+impl From<witness::WitnessError> for Error {
+    fn from(err: witness::WitnessError) -> Self {
+        Error(ErrorReason::Witness(err))
+    }
 }
 
 impl From<BadExponent> for Error {
@@ -0,0 +1,150 @@
+//! Memory budget of batch verification
+//!
+//! Batch proofs, e.g. [aggregated Пenc](crate::paillier_encryption_in_range::batch),
+//! precompute values for every element of the batch, so memory used by verification
+//! grows with the size of the batch. Verifiers with little memory, e.g. in wasm or on
+//! embedded devices, can bound it with [`MemoryBudget`]: elements are then processed
+//! in chunks small enough for the intermediate values of a chunk to fit in the budget.
+//! Result of verification doesn't depend on the budget.
+//!
+//! Functions taking the budget return [`MemoryReport`], accounting for how the batch
+//! was split and how much memory it took. Sizes are estimated from bit sizes of the
+//! moduli, an integer of `b` bits is counted as `b / 8` bytes rounded up. Values owned
+//! by the caller, i.e. statement, commitment, proof and, in interactive verification,
+//! the challenge, are not counted, neither is overhead of the allocator.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::memory::MemoryBudget;
+//! use paillier_zk::paillier_encryption_in_range::batch;
+//! # fn doc_fn(
+//! #     aux: &batch::Aux,
+//! #     data: batch::Data,
+//! #     commitment: &batch::Commitment,
+//! #     security: &batch::SecurityParams,
+//! #     proof: &batch::Proof,
+//! # ) -> Result<(), batch::InvalidProof> {
+//! let report = batch::non_interactive::verify_with_budget(
+//!     sha2::Sha256::default(),
+//!     aux,
+//!     data,
+//!     commitment,
+//!     security,
+//!     proof,
+//!     MemoryBudget::bytes(1 << 20),
+//! )?;
+//! assert!(report.within_budget());
+//! # Ok(()) }
+//! ```
+
+use rug::Integer;
+
+/// Upper bound on memory used by batch verification
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryBudget {
+    max_bytes: Option<usize>,
+}
+
+impl MemoryBudget {
+    /// All elements of the batch are processed at once
+    pub const UNLIMITED: Self = Self { max_bytes: None };
+
+    /// At most `max_bytes` bytes
+    ///
+    /// If even a single element doesn't fit in the budget, elements are processed one
+    /// by one, and the report shows that the budget was exceeded.
+    pub fn bytes(max_bytes: usize) -> Self {
+        Self {
+            max_bytes: Some(max_bytes),
+        }
+    }
+
+    /// Maximum amount of bytes, `None` if unlimited
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
+
+    /// Splits `items` elements into chunks that fit in the budget
+    ///
+    /// Each element takes `item_bytes` while its chunk is processed, and
+    /// `fixed_bytes` are taken regardless of the size of the chunk.
+    #[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+    pub(crate) fn plan(&self, items: usize, item_bytes: usize, fixed_bytes: usize) -> MemoryReport {
+        let chunk_size = match self.max_bytes {
+            None => items,
+            Some(max_bytes) => max_bytes.saturating_sub(fixed_bytes) / item_bytes.max(1),
+        }
+        .clamp(1, items.max(1));
+        MemoryReport {
+            items,
+            chunk_size,
+            chunks: items.div_ceil(chunk_size),
+            item_bytes,
+            fixed_bytes,
+            peak_bytes: fixed_bytes + chunk_size.min(items) * item_bytes,
+            budget: *self,
+        }
+    }
+}
+
+/// Accounting of memory used by batch verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MemoryReport {
+    /// Amount of elements in the batch
+    pub items: usize,
+    /// Amount of elements processed at once
+    pub chunk_size: usize,
+    /// Amount of chunks the batch was split into
+    pub chunks: usize,
+    /// Estimated bytes taken by one element while its chunk is processed
+    pub item_bytes: usize,
+    /// Estimated bytes taken regardless of the size of the chunk
+    pub fixed_bytes: usize,
+    /// Estimated peak of memory usage
+    pub peak_bytes: usize,
+    /// Budget that verification was given
+    pub budget: MemoryBudget,
+}
+
+impl MemoryReport {
+    /// Whether estimated peak of memory usage is within the budget
+    pub fn within_budget(&self) -> bool {
+        match self.budget.max_bytes {
+            Some(max_bytes) => self.peak_bytes <= max_bytes,
+            None => true,
+        }
+    }
+}
+
+/// Estimated size of integer modulo `modulus` in bytes
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+pub(crate) fn integer_bytes(modulus: &Integer) -> usize {
+    (modulus.significant_bits() as usize).div_ceil(8)
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemoryBudget;
+
+    #[test]
+    fn chunks_fit_in_budget() {
+        let report = MemoryBudget::UNLIMITED.plan(10, 100, 50);
+        assert_eq!((report.chunk_size, report.chunks), (10, 1));
+        assert_eq!(report.peak_bytes, 1050);
+        assert!(report.within_budget());
+
+        let report = MemoryBudget::bytes(400).plan(10, 100, 50);
+        assert_eq!((report.chunk_size, report.chunks), (3, 4));
+        assert_eq!(report.peak_bytes, 350);
+        assert!(report.within_budget());
+
+        // Elements are processed one by one when none fits
+        let report = MemoryBudget::bytes(100).plan(10, 100, 50);
+        assert_eq!((report.chunk_size, report.chunks), (1, 10));
+        assert!(!report.within_budget());
+
+        let report = MemoryBudget::bytes(100).plan(0, 100, 50);
+        assert_eq!(report.chunks, 0);
+    }
+}
@@ -2,11 +2,72 @@
 //!
 //! Many ZK proofs often require computing `s^x t^y mod N` with s, t, and N being known in advance.
 //! This module provides [`MultiexpTable`] that can compute multiexponent faster.
+//!
+//! ## Tuning
+//! Table stores `s^(2^(w i))` and `t^(2^(w i))` for every `w`-bit digit of the exponents,
+//! where `w` is the window size. Computing a multiexponent takes about
+//! `x_bits / w + y_bits / w + 2^(w+1)` modular multiplications, and the table takes about
+//! `(x_bits + y_bits) / w` integers modulo `N`. By default, [`MultiexpTable::build`] picks the
+//! window with the least number of multiplications, which is 7 bits for 4096-bit exponents
+//! and 6 bits for 1024-bit exponents.
+//!
+//! [`TableParams`] overrides the window size, or limits memory taken by the table, which is
+//! useful in constrained environments like wasm:
+//!
+//! ```rust
+//! use paillier_zk::multiexp::{MultiexpTable, TableParams};
+//! # use paillier_zk::rug::Integer;
+//! # let (s, t, n) = (Integer::from(3), Integer::from(7), Integer::from(100000));
+//! let params = TableParams::default().with_max_table_bytes(64 * 1024);
+//! let table = MultiexpTable::build_with(&s, &t, 1024, 1024, n, &params).unwrap();
+//! assert!(table.size_in_bytes() <= 64 * 1024);
+//! ```
 
 #![allow(non_snake_case)]
 
 use rug::{Complete, Integer};
 
+/// Largest supported window size in bits
+pub const MAX_WINDOW_BITS: u32 = 16;
+
+/// Window size of tables built before the window size became configurable
+#[cfg(feature = "serde")]
+const LEGACY_WINDOW_BITS: u32 = 8;
+
+/// Tuning knobs of [`MultiexpTable`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct TableParams {
+    /// Window size in bits, from 1 to [`MAX_WINDOW_BITS`]
+    ///
+    /// If `None`, the window with the least amount of multiplications that fits into
+    /// [`max_table_bytes`](Self::max_table_bytes) is chosen.
+    pub window_bits: Option<u32>,
+    /// Upper bound on [size](MultiexpTable::size_in_bytes) of the table in bytes
+    ///
+    /// Larger windows make the table smaller at the cost of more multiplications
+    /// per multiexponent.
+    pub max_table_bytes: Option<usize>,
+}
+
+impl TableParams {
+    /// Sets the window size
+    pub fn with_window_bits(self, window_bits: u32) -> Self {
+        Self {
+            window_bits: Some(window_bits),
+            ..self
+        }
+    }
+
+    /// Sets the memory cap
+    pub fn with_max_table_bytes(self, max_table_bytes: usize) -> Self {
+        Self {
+            max_table_bytes: Some(max_table_bytes),
+            ..self
+        }
+    }
+}
+
 /// Precomputed table for performing faster multiexponentiation
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -18,15 +79,39 @@ pub struct MultiexpTable {
     ell_y: Integer,
     t_to_ell_y: Integer,
     N: Integer,
+    #[cfg_attr(feature = "serde", serde(default = "legacy_window_bits"))]
+    window_bits: u32,
+}
+
+#[cfg(feature = "serde")]
+fn legacy_window_bits() -> u32 {
+    LEGACY_WINDOW_BITS
 }
 
 impl MultiexpTable {
     /// Builds a multiexponentiation table to perform `s^x t^y mod N` faster
     /// where `x` and `y` are up to `x_bits` and `y_bits`
     ///
+    /// Uses default [`TableParams`], see [module level docs](self#tuning).
+    ///
     /// Returns `None` is `s` or `t` are non-positive or if any of them are not co-prime to `N` or
     /// if `N` is less than 2.
     pub fn build(s: &Integer, t: &Integer, x_bits: u32, y_bits: u32, N: Integer) -> Option<Self> {
+        Self::build_with(s, t, x_bits, y_bits, N, &TableParams::default())
+    }
+
+    /// Builds a multiexponentiation table with given tuning parameters
+    ///
+    /// Returns `None` in the same cases as [`MultiexpTable::build`], if the window size is out
+    /// of range, or if the table can't fit into the memory cap.
+    pub fn build_with(
+        s: &Integer,
+        t: &Integer,
+        x_bits: u32,
+        y_bits: u32,
+        N: Integer,
+        params: &TableParams,
+    ) -> Option<Self> {
         if s.cmp0().is_le()
             || t.cmp0().is_le()
             || N <= *Integer::ONE
@@ -35,26 +120,39 @@ impl MultiexpTable {
         {
             return None;
         }
-        let k_x = x_bits / 8 + 1;
-        let k_y = y_bits / 8 + 1;
+        let fits = |w: u32| match params.max_table_bytes {
+            Some(max) => estimate_size(x_bits, y_bits, w, &N) <= max,
+            None => true,
+        };
+        let w = match params.window_bits {
+            Some(w) if (1..=MAX_WINDOW_BITS).contains(&w) && fits(w) => w,
+            Some(_) => return None,
+            // On tie, larger window is preferred as it takes less memory
+            None => (1..=MAX_WINDOW_BITS)
+                .filter(|&w| fits(w))
+                .min_by_key(|&w| (multiplications(x_bits, y_bits, w), core::cmp::Reverse(w)))?,
+        };
+
+        let k_x = x_bits / w + 1;
+        let k_y = y_bits / w + 1;
         let mut s_table = Vec::with_capacity(k_x.try_into().ok()?);
         let mut t_table = Vec::with_capacity(k_y.try_into().ok()?);
 
-        let B: u32 = 256;
+        // B = 2^w
         for i in 0..k_x {
-            let B_to_i = Integer::u_pow_u(B, i).complete();
+            let B_to_i = (Integer::ONE << (w * i)).complete();
             s_table.push(s.clone().pow_mod(&B_to_i, &N).ok()?);
         }
         for i in 0..k_y {
-            let B_to_i = Integer::u_pow_u(B, i).complete();
+            let B_to_i = (Integer::ONE << (w * i)).complete();
             t_table.push(t.clone().pow_mod(&B_to_i, &N).ok()?);
         }
 
         // smallest negative value possible for `x`
-        let ell_x = -(Integer::ONE.clone() << (k_x * 8)) + 1;
+        let ell_x = -(Integer::ONE.clone() << (k_x * w)) + 1;
         let s_to_ell_x = s.pow_mod_ref(&ell_x, &N)?.into();
         // smallest negative value possible for `y`
-        let ell_y = -(Integer::ONE.clone() << (k_y * 8)) + 1;
+        let ell_y = -(Integer::ONE.clone() << (k_y * w)) + 1;
         let t_to_ell_y = t.pow_mod_ref(&ell_y, &N)?.into();
 
         Some(Self {
@@ -65,6 +163,7 @@ impl MultiexpTable {
             ell_y,
             t_to_ell_y,
             N,
+            window_bits: w,
         })
     }
 
@@ -72,32 +171,32 @@ impl MultiexpTable {
     ///
     /// Returns `None` if either `x` or `y` do not fit into `x_bits` or `y_bits` provided in [`MultiexpTable::build`].
     pub fn prod_exp(&self, x: &Integer, y: &Integer) -> Option<Integer> {
-        let order = rug::integer::Order::Lsf;
+        let w = self.window_bits;
 
         let x_is_neg = x.cmp0().is_lt();
         // `x_digits` correspond to digits of `x` is it's non-negative, and `x - ell_x` otherwise
         let x_digits = if !x_is_neg {
-            x.to_digits::<u8>(order)
+            to_digits(x, w)
         } else {
             let x = (x - &self.ell_x).complete();
             if x.cmp0().is_lt() {
                 // `x` is less than lower bound
                 return None;
             }
-            x.to_digits::<u8>(order)
+            to_digits(&x, w)
         };
 
         let y_is_neg = y.cmp0().is_lt();
         // `y_digits` correspond to digits of `y` is it's non-negative, and `y - ell_y` otherwise
         let y_digits = if !y_is_neg {
-            y.to_digits::<u8>(order)
+            to_digits(y, w)
         } else {
             let y = (y - &self.ell_y).complete();
             if y.cmp0().is_lt() {
                 // `y` is less than lower bound
                 return None;
             }
-            y.to_digits::<u8>(order)
+            to_digits(&y, w)
         };
 
         if x_digits.len() > self.s.len() || y_digits.len() > self.t.len() {
@@ -105,7 +204,7 @@ impl MultiexpTable {
             return None;
         }
 
-        let mut digits_table = [(); 255].map(|_| None);
+        let mut digits_table = vec![None; (1 << w) - 1];
         build_digits_table(&mut digits_table, &self.s, &x_digits, &self.N);
         build_digits_table(&mut digits_table, &self.t, &y_digits, &self.N);
 
@@ -133,7 +232,13 @@ impl MultiexpTable {
     /// Max exponent size is guaranteed to be equal or greater than `x_bits` and `y_bits`
     /// provided in [MultiexpTable::build]
     pub fn max_exponents_size(&self) -> (usize, usize) {
-        (self.s.len() * 8, self.t.len() * 8)
+        let w = self.window_bits as usize;
+        (self.s.len() * w, self.t.len() * w)
+    }
+
+    /// Returns window size of the table in bits
+    pub fn window_bits(&self) -> u32 {
+        self.window_bits
     }
 
     /// Estimates size of the table in RAM in bytes
@@ -146,6 +251,7 @@ impl MultiexpTable {
             ell_y,
             t_to_ell_y,
             N,
+            window_bits: _,
         } = self;
 
         // A few bytes to encode length of Vec `s` and `t`
@@ -169,10 +275,52 @@ impl MultiexpTable {
     }
 }
 
+/// Upper bound on [`MultiexpTable::size_in_bytes`] of the table with window `w`
+fn estimate_size(x_bits: u32, y_bits: u32, w: u32, N: &Integer) -> usize {
+    type Limb = u32;
+    let limb_bytes = Limb::BITS as usize / 8;
+    let word = usize::BITS as usize / 8;
+    let k_x = (x_bits / w + 1) as usize;
+    let k_y = (y_bits / w + 1) as usize;
+    // `ell_x` and `ell_y` take `k_x * w` and `k_y * w` bits
+    let ell_limbs = |k: usize| (k * w as usize).div_ceil(Limb::BITS as usize);
+    let N_limbs = N.significant_digits::<Limb>();
+
+    2 * word
+        + (5 + k_x + k_y) * word
+        + limb_bytes * ((k_x + k_y + 3) * N_limbs + ell_limbs(k_x) + ell_limbs(k_y))
+}
+
+/// Approximate number of modular multiplications per multiexponent with window `w`
+fn multiplications(x_bits: u32, y_bits: u32, w: u32) -> u64 {
+    u64::from(x_bits / w + 1) + u64::from(y_bits / w + 1) + 2 * ((1u64 << w) - 1)
+}
+
+/// Splits non-negative `x` into `w`-bit digits, least significant first
+fn to_digits(x: &Integer, w: u32) -> Vec<u16> {
+    let limbs = x.to_digits::<u64>(rug::integer::Order::Lsf);
+    let bits = x.significant_bits();
+    let mask = (1u64 << w) - 1;
+    (0..bits.div_ceil(w))
+        .map(|i| {
+            let offset = i * w;
+            let (limb, shift) = ((offset / 64) as usize, offset % 64);
+            let mut digit = limbs[limb] >> shift;
+            if shift + w > 64 {
+                if let Some(next) = limbs.get(limb + 1) {
+                    digit |= next << (64 - shift);
+                }
+            }
+            // `w` is at most 16 bits, so digit fits into `u16`
+            (digit & mask) as u16
+        })
+        .collect()
+}
+
 fn build_digits_table(
-    table: &mut [Option<Integer>; 255],
+    table: &mut [Option<Integer>],
     base: &[Integer],
-    digits: &[u8],
+    digits: &[u16],
     N: &Integer,
 ) {
     for (i, digit) in digits.iter().copied().enumerate() {
@@ -192,19 +340,16 @@ fn build_digits_table(
 mod test {
     use rug::Integer;
 
-    use super::MultiexpTable;
-
-    #[test]
-    fn multiexp_works() {
-        let N = Integer::from(100000);
-        let s = Integer::from(3);
-        let t = Integer::from(7);
-
-        let x_bits = 48;
-        let y_bits = 32;
-
-        let table = MultiexpTable::build(&s, &t, x_bits, y_bits, N.clone()).unwrap();
+    use super::{MultiexpTable, TableParams};
 
+    fn check(
+        table: &MultiexpTable,
+        s: &Integer,
+        t: &Integer,
+        x_bits: u32,
+        y_bits: u32,
+        N: &Integer,
+    ) {
         let mut rng = rug::rand::RandState::new_mersenne_twister();
 
         for _ in 0..100 {
@@ -221,8 +366,56 @@ mod test {
 
             let actual = table.prod_exp(&x, &y).unwrap();
             let expected =
-                (s.clone().pow_mod(&x, &N).unwrap() * t.clone().pow_mod(&y, &N).unwrap()) % &N;
+                (s.clone().pow_mod(&x, N).unwrap() * t.clone().pow_mod(&y, N).unwrap()) % N;
             assert_eq!(actual, expected);
         }
     }
+
+    #[test]
+    fn multiexp_works() {
+        let N = Integer::from(100000);
+        let s = Integer::from(3);
+        let t = Integer::from(7);
+
+        let x_bits = 48;
+        let y_bits = 32;
+
+        let table = MultiexpTable::build(&s, &t, x_bits, y_bits, N.clone()).unwrap();
+        check(&table, &s, &t, x_bits, y_bits, &N);
+    }
+
+    #[test]
+    fn tuning() {
+        let N = Integer::from(100000);
+        let s = Integer::from(3);
+        let t = Integer::from(7);
+
+        for w in 1..=super::MAX_WINDOW_BITS {
+            let params = TableParams::default().with_window_bits(w);
+            let table = MultiexpTable::build_with(&s, &t, 100, 70, N.clone(), &params).unwrap();
+            assert_eq!(table.window_bits(), w);
+            let (max_x, max_y) = table.max_exponents_size();
+            assert!(max_x >= 100 && max_y >= 70);
+            check(&table, &s, &t, 100, 70, &N);
+        }
+        let params = TableParams::default().with_window_bits(super::MAX_WINDOW_BITS + 1);
+        assert!(MultiexpTable::build_with(&s, &t, 100, 70, N.clone(), &params).is_none());
+
+        // Default window minimizes amount of multiplications
+        let table = MultiexpTable::build(&s, &t, 4096, 4096, N.clone()).unwrap();
+        assert_eq!(table.window_bits(), 7);
+
+        // Memory cap forces larger window
+        let unbounded = table.size_in_bytes();
+        let cap = unbounded * 3 / 4;
+        let params = TableParams::default().with_max_table_bytes(cap);
+        let table = MultiexpTable::build_with(&s, &t, 4096, 4096, N.clone(), &params).unwrap();
+        assert!(table.window_bits() > 7);
+        assert!(table.size_in_bytes() <= cap);
+        check(&table, &s, &t, 4096, 4096, &N);
+
+        // Cap that can't be met
+        let params = TableParams::default().with_max_table_bytes(16);
+        assert!(MultiexpTable::build_with(&s, &t, 4096, 4096, N.clone(), &params).is_none());
+    }
 }
@@ -30,11 +30,7 @@
 //! // agree on the level of security
 //!
 //! let aux: p::Aux = pregenerated::verifier_aux();
-//! let security = p::SecurityParams {
-//!     l: 4,
-//!     epsilon: 128,
-//!     q: (Integer::ONE << 128_u32).complete(),
-//! };
+//! let security = p::SecurityParams::new(4, 128, (Integer::ONE << 128_u32).complete());
 //!
 //! // 1. Prover prepares the data to obtain proof about
 //!
@@ -83,12 +79,14 @@ use serde::{Deserialize, Serialize};
 
 use rug::Integer;
 
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
 pub use crate::common::{Aux, InvalidProof};
 
 /// Security parameters for proof. Choosing the values is a tradeoff between
 /// speed and chance of rejecting a valid proof or accepting an invalid proof
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
 pub struct SecurityParams {
     /// l in paper, security parameter for bit size of plaintext: it needs to
     /// differ from sqrt(n) not more than by 2^l
@@ -97,6 +95,66 @@ pub struct SecurityParams {
     pub epsilon: usize,
     /// q in paper. Security parameter for challenge
     pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// Absorbing ring-pedersen parameters (kilobytes) into every challenge is redundant
+    /// when counterparties agree on them once, e.g. per epoch. `aux_digest` must be
+    /// obtained with [`Aux::digest`] from the same aux that is used to prove and verify.
+    /// Prover and verifier must both enable it, otherwise the proof doesn't verify.
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 512, (Integer::ONE << 128_u32).complete())
+    }
 }
 
 /// Public data that both parties know
@@ -115,6 +173,34 @@ pub struct PrivateData<'a> {
     pub q: &'a Integer,
 }
 
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    /// `s^N0 mod N^`
+    s_to_n: Integer,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Does all precomputation that doesn't depend on the proof
+    ///
+    /// `aux` must be the same as used to verify the proof
+    pub fn new(aux: &Aux, data: Data<'a>) -> Result<Self, InvalidProof> {
+        let s_to_n = aux.pow_mod(&aux.s, data.n)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, s_to_n })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
 /// Prover's data accompanying the commitment. Kept as state between rounds in
 /// the interactive protocol.
 #[derive(Debug, Clone)]
@@ -129,6 +215,8 @@ pub struct PrivateCommitment {
     pub y: Integer,
 }
 
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
 /// Prover's first message, obtained by [`interactive::commit`]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -156,19 +244,84 @@ pub struct Proof {
     pub v: Integer,
 }
 
+impl_encode! { Data<'a> { n, n_root } }
+impl_encode! { Commitment { p, q, a, b, t, sigma } }
+impl_decode! { Commitment { p, q, a, b, t, sigma } }
+impl_encode! { Proof { z1, z2, w1, w2, v } }
+impl_decode! { Proof { z1, z2, w1, w2, v } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("p", modulus_bits)
+            .integer("q", modulus_bits)
+            .integer("a", modulus_bits)
+            .integer("b", modulus_bits)
+            .integer("t", modulus_bits)
+            .integer("sigma", security.l + 2 * modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`. Factors of `N0`
+    /// are assumed to be within `+-2^l * sqrt(N0)`, as that's what the proof shows.
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`. Factors of `N0`
+    /// are assumed to be within `+-2^l * sqrt(N0)`, as that's what the proof shows.
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let e = security.epsilon;
+        let root = modulus_bits / 2 + 1;
+        let factor = l + root;
+        let z = (l + e + root).max(q + factor) + 1;
+        let w = (l + e + modulus_bits).max(q + l + modulus_bits) + 1;
+        let sigma_circ = (l + 2 * modulus_bits).max(l + modulus_bits + factor) + 1;
+        let v = (l + e + 2 * modulus_bits).max(q + sigma_circ) + 1;
+        Schema::default()
+            .integer("z1", z)
+            .integer("z2", z)
+            .integer("w1", w)
+            .integer("w2", w)
+            .integer("v", v)
+    }
+}
+
 /// Interactive version of the proof
 pub mod interactive {
     use rand_core::RngCore;
     use rug::{Complete, Integer};
 
     use crate::{
-        common::{fail_if, fail_if_ne, IntegerExt, InvalidProofReason},
+        common::{
+            fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+            fail_if_zero_challenge, IntegerExt, InvalidProofReason,
+        },
         Error,
     };
 
     use super::{
-        Aux, Challenge, Commitment, Data, InvalidProof, PrivateCommitment, PrivateData, Proof,
-        SecurityParams,
+        Aux, Challenge, Commitment, Data, InvalidProof, PreparedStatement, PrivateCommitment,
+        PrivateData, Proof, SecurityParams,
     };
 
     /// Create random commitment
@@ -179,6 +332,7 @@ pub mod interactive {
         security: &SecurityParams,
         mut rng: R,
     ) -> Result<(Commitment, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
         let two_to_l = (Integer::ONE << security.l).complete();
         let two_to_l_plus_e = (Integer::ONE << (security.l + security.epsilon)).complete();
         let n_root_modulo = (&two_to_l_plus_e * data.n_root).complete();
@@ -255,6 +409,44 @@ pub mod interactive {
         challenge: &Challenge,
         proof: &Proof,
     ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(aux, data).map_err(|err| err.with_key(data.n))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.n))
+    }
+
+    fn check_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(
+            &aux.rsa_modulo,
+            [
+                &commitment.p,
+                &commitment.q,
+                &commitment.a,
+                &commitment.b,
+                &commitment.t,
+            ],
+        )?;
         // check 1
         {
             let lhs = aux.combine(&proof.z1, &proof.w1)?;
@@ -262,6 +454,7 @@ pub mod interactive {
             let rhs = (&commitment.a * p_to_e).modulo(&aux.rsa_modulo);
             fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
         }
+        crate::common::lap!(Check("ring-pedersen for p"));
         // check 2
         {
             let lhs = aux.combine(&proof.z2, &proof.w2)?;
@@ -269,9 +462,11 @@ pub mod interactive {
             let rhs = (&commitment.b * q_to_e).modulo(&aux.rsa_modulo);
             fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
         }
+        crate::common::lap!(Check("ring-pedersen for q"));
         // check 3
         {
-            let r = aux.combine(data.n, &commitment.sigma)?;
+            let t_to_sigma = aux.pow_mod(&aux.t, &commitment.sigma)?;
+            let r = (&statement.s_to_n * t_to_sigma).modulo(&aux.rsa_modulo);
             let q_to_z1 = aux.pow_mod(&commitment.q, &proof.z1)?;
             let t_to_v = aux.pow_mod(&aux.t, &proof.v)?;
             let lhs = (q_to_z1 * t_to_v).modulo(&aux.rsa_modulo);
@@ -280,11 +475,14 @@ pub mod interactive {
                 .combine(&commitment.t, Integer::ONE, &r, challenge)?;
             fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
         }
+        crate::common::lap!(Check("ring-pedersen for N0"));
         let range = (Integer::from(1) << (security.l + security.epsilon)) * data.n_root;
         // range check for z1
         fail_if(InvalidProofReason::RangeCheck(1), proof.z1.is_in_pm(&range))?;
+        crate::common::lap!(Check("range of z1"));
         // range check for z2
         fail_if(InvalidProofReason::RangeCheck(2), proof.z2.is_in_pm(&range))?;
+        crate::common::lap!(Check("range of z2"));
 
         Ok(())
     }
@@ -292,12 +490,13 @@ pub mod interactive {
 
 /// Non-interactive version of the proof
 pub mod non_interactive {
-    use digest::{typenum::U32, Digest};
+    use digest::Digest;
     use rand_core::RngCore;
 
+    use crate::transcript::Transcript;
     pub use crate::{Error, InvalidProof};
 
-    pub use super::{Aux, Challenge, Data, PrivateData, SecurityParams};
+    pub use super::{Aux, Challenge, Data, PreparedStatement, PrivateData, SecurityParams};
 
     /// The ZK proof, computed by [`prove`]
     #[derive(Debug, Clone)]
@@ -307,6 +506,37 @@ pub mod non_interactive {
         proof: super::Proof,
     }
 
+    crate::common::encoding::impl_encode! { Proof { commitment, proof } }
+    crate::common::encoding::impl_decode! { Proof { commitment, proof } }
+
+    impl Proof {
+        /// Upper bound on size of [canonically encoded](crate::encoding) proof produced
+        /// by honest prover
+        ///
+        /// See [`Commitment::max_serialized_size`](super::Commitment::max_serialized_size)
+        /// and [`Proof::max_serialized_size`](super::Proof::max_serialized_size)
+        pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+            Self::schema(security, modulus_bits).max_size()
+        }
+
+        /// [Schema](crate::encoding::Schema) of canonically encoded proof produced
+        /// by honest prover
+        ///
+        /// Commitment and proof fields follow each other, see
+        /// [`Commitment::schema`](super::Commitment::schema) and
+        /// [`Proof::schema`](super::Proof::schema)
+        pub fn schema(
+            security: &SecurityParams,
+            modulus_bits: usize,
+        ) -> crate::common::encoding::Schema {
+            let mut schema = super::Commitment::schema(security, modulus_bits);
+            schema
+                .fields
+                .extend(super::Proof::schema(security, modulus_bits).fields);
+            schema
+        }
+    }
+
     /// Compute proof for the given data, producing random commitment and
     /// deriving determenistic challenge.
     ///
@@ -320,11 +550,33 @@ pub mod non_interactive {
         rng: R,
     ) -> Result<Proof, Error>
     where
-        D: Digest<OutputSize = U32>,
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<R: RngCore, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: R,
+    ) -> Result<Proof, Error>
+    where
+        D: Digest,
     {
         let (commitment, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
-        let challenge = challenge(shared_state, aux, data, &commitment, security);
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &commitment, security);
+        crate::common::lap!(Challenge);
         let proof = super::interactive::prove(pdata, &commitment, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
         Ok(Proof { commitment, proof })
     }
 
@@ -339,24 +591,48 @@ pub mod non_interactive {
     where
         D: Digest,
     {
-        let shared_state = shared_state.finalize();
-        let hash = |d: D| {
-            let order = rug::integer::Order::Msf;
-            d.chain_update(&shared_state)
-                .chain_update(aux.s.to_digits::<u8>(order))
-                .chain_update(aux.t.to_digits::<u8>(order))
-                .chain_update(aux.rsa_modulo.to_digits::<u8>(order))
-                .chain_update(data.n.to_digits::<u8>(order))
-                .chain_update(data.n_root.to_digits::<u8>(order))
-                .chain_update(commitment.p.to_digits::<u8>(order))
-                .chain_update(commitment.q.to_digits::<u8>(order))
-                .chain_update(commitment.a.to_digits::<u8>(order))
-                .chain_update(commitment.b.to_digits::<u8>(order))
-                .chain_update(commitment.t.to_digits::<u8>(order))
-                .chain_update(commitment.sigma.to_digits::<u8>(order))
-                .finalize()
-        };
-        let mut rng = crate::common::rng::HashRng::new(hash);
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &super::Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8>
+    where
+        D: Digest,
+    {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &super::Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        transcript.label(b"paillier_zk.no_small_factor");
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb_value(b"N", data.n);
+        transcript.absorb_value(b"sqrt(N)", data.n_root);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
         super::interactive::challenge(security, &mut rng)
     }
 
@@ -369,9 +645,27 @@ pub mod non_interactive {
         proof: &Proof,
     ) -> Result<(), InvalidProof>
     where
-        D: Digest<OutputSize = U32>,
+        D: Digest,
     {
-        let challenge = challenge(shared_state, aux, data, &proof.commitment, security);
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge =
+            challenge_with_transcript(transcript, aux, data, &proof.commitment, security);
+        crate::common::lap!(Challenge);
         super::interactive::verify(
             aux,
             data,
@@ -381,6 +675,70 @@ pub mod non_interactive {
             &proof.proof,
         )
     }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(
+            shared_state,
+            aux,
+            statement.data(),
+            &proof.commitment,
+            security,
+        );
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(
+            aux,
+            statement,
+            &proof.commitment,
+            security,
+            &challenge,
+            &proof.proof,
+        )
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use rug::{Complete, Integer};
+
+    use crate::common::extract::{challenge_diff, ensure, linear_response};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub p: Integer,
+        pub q: Integer,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let p = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        let q = linear_response(&proof1.z2, &proof2.z2, &e_diff)?;
+        ensure((&p * &q).complete() == *data.n)?;
+        Ok(Witness { p, q })
+    }
 }
 
 #[cfg(test)]
@@ -408,6 +766,8 @@ mod test {
             l: 64,
             epsilon: 128,
             q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
         };
         let aux = crate::common::test::aux(&mut rng);
         let shared_state = sha2::Sha256::default();
@@ -427,6 +787,130 @@ mod test {
         }
     }
 
+    #[test]
+    fn serialized_size() {
+        use crate::common::encoding::Encode;
+
+        let mut rng = rand_dev::DevRng::new();
+        let p = generate_blum_prime(&mut rng, 256);
+        let q = generate_blum_prime(&mut rng, 256);
+        let n = (&p * &q).complete();
+        let n_root = n.sqrt_ref().complete();
+        let data = super::Data {
+            n: &n,
+            n_root: &n_root,
+        };
+        let security = super::SecurityParams {
+            l: 64,
+            epsilon: 128,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let proof = super::non_interactive::prove(
+            sha2::Sha256::default(),
+            &aux,
+            data,
+            super::PrivateData { p: &p, q: &q },
+            &security,
+            rng,
+        )
+        .unwrap();
+
+        let modulus_bits = aux.rsa_modulo.significant_bits().max(n.significant_bits());
+        let max_size =
+            super::non_interactive::Proof::max_serialized_size(&security, modulus_bits as usize);
+        assert!(proof.to_canonical_bytes().len() <= max_size);
+    }
+
+    #[test]
+    fn negative_challenge() {
+        let mut rng = rand_dev::DevRng::new();
+        let p = generate_blum_prime(&mut rng, 256);
+        let q = generate_blum_prime(&mut rng, 256);
+        let n = (&p * &q).complete();
+        let n_root = n.sqrt_ref().complete();
+        let data = super::Data {
+            n: &n,
+            n_root: &n_root,
+        };
+        let pdata = super::PrivateData { p: &p, q: &q };
+        let security = super::SecurityParams {
+            l: 64,
+            epsilon: 128,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let challenge = -super::interactive::challenge(&security, &mut rng).abs() - 1;
+        let proof =
+            super::interactive::prove(pdata, &commitment, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof)
+            .expect("proof with negative challenge failed");
+
+        // Proof is bound to the sign of the challenge
+        let r = super::interactive::verify(
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &(-&challenge).complete(),
+            &proof,
+        );
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn degenerate_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let p = generate_blum_prime(&mut rng, 256);
+        let q = generate_blum_prime(&mut rng, 256);
+        let n = (&p * &q).complete();
+        let n_root = n.sqrt_ref().complete();
+        let data = super::Data {
+            n: &n,
+            n_root: &n_root,
+        };
+        let pdata = super::PrivateData { p: &p, q: &q };
+        let security = super::SecurityParams {
+            l: 64,
+            epsilon: 128,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof =
+            super::interactive::prove(pdata, &commitment, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Commitment equal to one is rejected
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof =
+            super::interactive::prove(pdata, &commitment, &pcommitment, &challenge).unwrap();
+        let mut degenerate = commitment.clone();
+        degenerate.t = Integer::from(1);
+        let r = super::interactive::verify(&aux, data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
     #[test]
     fn failing() {
         let mut rng = rand_dev::DevRng::new();
@@ -442,6 +926,8 @@ mod test {
             l: 4,
             epsilon: 128,
             q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
         };
         let aux = crate::common::test::aux(&mut rng);
         let shared_state = sha2::Sha256::default();
@@ -474,4 +960,38 @@ mod test {
         assert_eq!(Integer::from(8).sqrt(), Integer::from(2));
         assert_eq!(Integer::from(9).sqrt(), Integer::from(3));
     }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let p = generate_blum_prime(&mut rng, 256);
+        let q = generate_blum_prime(&mut rng, 256);
+        let n = (&p * &q).complete();
+        let n_root = n.sqrt_ref().complete();
+        let data = super::Data {
+            n: &n,
+            n_root: &n_root,
+        };
+        let pdata = super::PrivateData { p: &p, q: &q };
+        let security = super::SecurityParams {
+            l: 64,
+            epsilon: 128,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(pdata, &commitment, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(pdata, &commitment, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.p, p);
+        assert_eq!(witness.q, q);
+    }
 }
@@ -5,9 +5,12 @@
 //!
 //! A party P performs a paillier affine operation with C, Y, and X
 //! obtaining `D = C*X + Y`. `X` and `Y` are encrypted values of `x` and `y`. P
-//! then wants to prove that `y` and `x` are at most `L` and `L'` bits,
+//! then wants to prove that `x` and `y` are at most `L` and `L'` bits,
 //! correspondingly, and P doesn't want to disclose none of the plaintexts
 //!
+//! The bounds are set separately by `l_x` and `l_y` of [`SecurityParams`]. In
+//! CGGMP21, `y` is masked by a larger value than `x`, so `L'` is bigger than `L`.
+//!
 //! Given:
 //! - `key0`, `pkey0`, `key1`, `pkey1` - pairs of public and private keys in
 //!   paillier cryptosystem
@@ -26,6 +29,10 @@
 //!
 //! Disclosing only: `key0`, `key1`, `C`, `D`, `Y`, `X`
 //!
+//! `x = 0` and `y = 0` are valid witnesses. For `x = 0`, `X` is the identity point and
+//! `D = key0.encrypt(y)` doesn't depend on `C`. Note that `omul` rejects zero scalar,
+//! so `D` has to be obtained without it in this case.
+//!
 //! ## Example
 //!
 //! ```rust
@@ -52,12 +59,12 @@
 //! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
 //!
 //! let aux: p::Aux = pregenerated::verifier_aux();
-//! let security = p::SecurityParams {
-//!     l_x: 256,
-//!     l_y: 848,
-//!     epsilon: 230,
-//!     q: (Integer::ONE << 128_u32).complete(),
-//! };
+//! let security = p::SecurityParams::new(
+//!     256,
+//!     848,
+//!     230,
+//!     (Integer::ONE << 128_u32).complete(),
+//! );
 //!
 //! // 1. Setup: prover prepares the paillier keys
 //!
@@ -159,15 +166,22 @@ use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
 use generic_ec::{Curve, Point};
 use rug::Integer;
 
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::PreparedCiphertext;
+use crate::compatibility::Incompatible;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 pub use crate::common::{Aux, InvalidProof};
 
+pub mod multi_recipient;
+
 /// Security parameters for proof. Choosing the values is a tradeoff between
 /// speed and chance of rejecting a valid proof or accepting an invalid proof
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
 pub struct SecurityParams {
     /// l in paper, bit size of +-x
     pub l_x: usize,
@@ -177,6 +191,126 @@ pub struct SecurityParams {
     pub epsilon: usize,
     /// q in paper. Security parameter for challenge
     pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+    /// Reject proofs where `N0` equals `N1`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_distinct_keys_required`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub require_distinct_keys: bool,
+    /// Return an error at prove time if the bound on `x` exceeds the curve order
+    ///
+    /// Disabled by default, see [`SecurityParams::with_bound_checked_against_curve`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub require_bound_fits_curve: bool,
+    /// Domain separation tag of the application, absorbed into challenges
+    ///
+    /// Disabled by default, see [`SecurityParams::with_domain_tag`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub domain_tag: Option<Vec<u8>>,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l_x: usize, l_y: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l_x,
+            l_y,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_distinct_keys: false,
+            require_bound_fits_curve: false,
+            domain_tag: None,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// Absorbing ring-pedersen parameters (kilobytes) into every challenge is redundant
+    /// when counterparties agree on them once, e.g. per epoch. `aux_digest` must be
+    /// obtained with [`Aux::digest`] from the same aux that is used to prove and verify.
+    /// Prover and verifier must both enable it, otherwise the proof doesn't verify.
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+
+    /// Makes verification reject statements where `key0` and `key1` are the same key
+    ///
+    /// In CGGMP21, `N0` and `N1` belong to different parties. When they're equal,
+    /// the statement has different meaning, e.g. a malicious prover may reuse the
+    /// verifier's own key as its key. Protocols that expect distinct keys should
+    /// enable it to rule that out.
+    pub fn with_distinct_keys_required(self) -> Self {
+        Self {
+            require_distinct_keys: true,
+            ..self
+        }
+    }
+
+    /// Makes proving fail if `x` bound `l_x` has more bits than the curve order
+    ///
+    /// When `x` is ultimately used as a scalar of the curve, proving a bound
+    /// larger than `q` is usually a configuration bug. See
+    /// [`SecurityParams::assert_bound_fits_curve`]
+    pub fn with_bound_checked_against_curve(self) -> Self {
+        Self {
+            require_bound_fits_curve: true,
+            ..self
+        }
+    }
+
+    /// Makes challenges absorb the domain separation tag of the application
+    ///
+    /// Same as [Пenc](crate::paillier_encryption_in_range::SecurityParams::with_domain_tag):
+    /// a proof made with one tag doesn't verify with another one or without a tag.
+    pub fn with_domain_tag(self, domain_tag: impl Into<Vec<u8>>) -> Self {
+        Self {
+            domain_tag: Some(domain_tag.into()),
+            ..self
+        }
+    }
+
+    /// Checks that `x` bound `l_x` doesn't exceed bit size of the order of curve `C`
+    pub fn assert_bound_fits_curve<C: Curve>(&self) -> Result<(), Incompatible> {
+        crate::compatibility::bound_fits_curve::<C>(self.l_x)
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 256, 512, (Integer::ONE << 128_u32).complete())
+    }
 }
 
 /// Public data that both parties know
@@ -209,6 +343,36 @@ pub struct PrivateData<'a> {
     pub nonce_y: &'a Nonce,
 }
 
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a, C: Curve> {
+    data: Data<'a, C>,
+    c: PreparedCiphertext,
+    d: PreparedCiphertext,
+    y: PreparedCiphertext,
+}
+
+impl<'a, C: Curve> PreparedStatement<'a, C> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a, C>) -> Result<Self, InvalidProof> {
+        let c = PreparedCiphertext::new(data.key0, data.c)?;
+        let d = PreparedCiphertext::new(data.key0, data.d)?;
+        let y = PreparedCiphertext::new(data.key1, data.y)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, c, d, y })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a, C> {
+        self.data
+    }
+}
+
 // As described in cggmp21 at page 35
 /// Prover's first message, obtained by [`interactive::commit`]
 #[derive(Debug, Clone)]
@@ -237,6 +401,8 @@ pub struct PrivateCommitment {
     pub mu: Integer,
 }
 
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
 /// Verifier's challenge to prover. Can be obtained deterministically by
 /// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
 pub type Challenge = Integer;
@@ -254,6 +420,63 @@ pub struct Proof {
     pub w_y: Integer,
 }
 
+impl_encode! { Data<'a, C: Curve> { key0, key1, c, d, y, x } }
+impl_encode! { Commitment<C: Curve> { a, b_x, b_y, e, s, f, t } }
+impl_decode! { Commitment<C: Curve> { a, b_x, b_y, e, s, f, t } }
+impl_encode! { Proof { z1, z2, z3, z4, w, w_y } }
+impl_decode! { Proof { z1, z2, z3, z4, w, w_y } }
+
+impl<C: Curve> Commitment<C> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("a", 2 * modulus_bits)
+            .point::<C>("b_x")
+            .integer("b_y", 2 * modulus_bits)
+            .integer("e", modulus_bits)
+            .integer("s", modulus_bits)
+            .integer("f", modulus_bits)
+            .integer("t", modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let z1 = (security.l_x + security.epsilon).max(q + security.l_x) + 1;
+        let z2 = (security.l_y + security.epsilon).max(q + security.l_y) + 1;
+        let z3 = z1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .integer("z2", z2)
+            .integer("z3", z3)
+            .integer("z4", z2 + modulus_bits)
+            .integer("w", modulus_bits)
+            .integer("w_y", modulus_bits)
+    }
+}
+
 /// The interactive version of the ZK proof. Should be completed in 3 rounds:
 /// prover commits to data, verifier responds with a random challenge, and
 /// prover gives proof with commitment and challenge.
@@ -262,7 +485,10 @@ pub mod interactive {
     use rand_core::RngCore;
     use rug::{Complete, Integer};
 
-    use crate::common::{fail_if, fail_if_ne, IntegerExt, InvalidProof, InvalidProofReason};
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProof, InvalidProofReason,
+    };
     use crate::Error;
 
     use super::*;
@@ -273,37 +499,65 @@ pub mod interactive {
         data: Data<C>,
         pdata: PrivateData,
         security: &SecurityParams,
-        mut rng: R,
+        rng: R,
     ) -> Result<(Commitment<C>, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        crate::compatibility::check_bound_fits_curve::<C>(
+            security.l_x,
+            security.require_bound_fits_curve,
+        )?;
+        let pcomm = sample(aux, data.key0.n(), data.key1.n(), security, rng)?;
+        let commitment = commitment_of(aux, data, pdata, &pcomm)?;
+        Ok((commitment, pcomm))
+    }
+
+    /// Samples randomness of the commitment, `n0` and `n1` are `N0` and `N1`
+    pub(super) fn sample<R: RngCore>(
+        aux: &Aux,
+        n0: &Integer,
+        n1: &Integer,
+        security: &SecurityParams,
+        mut rng: R,
+    ) -> Result<PrivateCommitment, Error> {
         let two_to_l = (Integer::ONE << security.l_x).complete();
+        let two_to_l_prime = (Integer::ONE << security.l_y).complete();
         let two_to_l_e = (Integer::ONE << (security.l_x + security.epsilon)).complete();
         let two_to_l_prime_e = (Integer::ONE << (security.l_y + security.epsilon)).complete();
         let hat_n_at_two_to_l_e = (&aux.rsa_modulo * &two_to_l_e).complete();
+        let hat_n_at_two_to_l_prime_e = (&aux.rsa_modulo * &two_to_l_prime_e).complete();
         let hat_n_at_two_to_l = (&aux.rsa_modulo * &two_to_l).complete();
+        let hat_n_at_two_to_l_prime = (&aux.rsa_modulo * &two_to_l_prime).complete();
 
         let alpha = Integer::from_rng_pm(&two_to_l_e, &mut rng);
         let beta = Integer::from_rng_pm(&two_to_l_prime_e, &mut rng);
-        let r = Integer::gen_invertible(data.key0.n(), &mut rng);
-        let r_y = Integer::gen_invertible(data.key1.n(), &mut rng);
+        let r = Integer::gen_invertible(n0, &mut rng);
+        let r_y = Integer::gen_invertible(n1, &mut rng);
         let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_e, &mut rng);
-        let delta = Integer::from_rng_pm(&hat_n_at_two_to_l_e, &mut rng);
+        let delta = Integer::from_rng_pm(&hat_n_at_two_to_l_prime_e, &mut rng);
         let m = Integer::from_rng_pm(&hat_n_at_two_to_l, &mut rng);
-        let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, &mut rng);
+        let mu = Integer::from_rng_pm(&hat_n_at_two_to_l_prime, &mut rng);
+        crate::common::observe_randomness!(&alpha, &beta);
 
-        let beta_enc_key0 = data.key0.encrypt_with(&beta, &r)?;
-        let alpha_at_c = data.key0.omul(&alpha, data.c)?;
-        let a = data.key0.oadd(&alpha_at_c, &beta_enc_key0)?;
+        Ok(PrivateCommitment {
+            alpha,
+            beta,
+            r,
+            r_y,
+            gamma,
+            m,
+            delta,
+            mu,
+        })
+    }
 
-        let commitment = Commitment {
-            a,
-            b_x: Point::<C>::generator() * alpha.to_scalar(),
-            b_y: data.key1.encrypt_with(&beta, &r_y)?,
-            e: aux.combine(&alpha, &gamma)?,
-            s: aux.combine(pdata.x, &m)?,
-            f: aux.combine(&beta, &delta)?,
-            t: aux.combine(pdata.y, &mu)?,
-        };
-        let private_commitment = PrivateCommitment {
+    /// Computes the commitment from randomness sampled by [`sample`]
+    pub(super) fn commitment_of<C: Curve>(
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        pcomm: &PrivateCommitment,
+    ) -> Result<Commitment<C>, Error> {
+        let PrivateCommitment {
             alpha,
             beta,
             r,
@@ -312,8 +566,20 @@ pub mod interactive {
             m,
             delta,
             mu,
-        };
-        Ok((commitment, private_commitment))
+        } = pcomm;
+        let beta_enc_key0 = data.key0.encrypt_with(beta, r)?;
+        let alpha_at_c = data.key0.omul(alpha, data.c)?;
+        let a = data.key0.oadd(&alpha_at_c, &beta_enc_key0)?;
+
+        Ok(Commitment {
+            a,
+            b_x: Point::<C>::generator() * alpha.to_scalar(),
+            b_y: data.key1.encrypt_with(beta, r_y)?,
+            e: aux.combine(alpha, gamma)?,
+            s: aux.combine(pdata.x, m)?,
+            f: aux.combine(beta, delta)?,
+            t: aux.combine(pdata.y, mu)?,
+        })
     }
 
     /// Compute proof for given data and prior protocol values
@@ -348,13 +614,54 @@ pub mod interactive {
         challenge: &Challenge,
         proof: &Proof,
     ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key1.n()))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key1.n()))
+    }
+
+    fn check_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if(
+            InvalidProofReason::SameKeys,
+            !security.require_distinct_keys || data.key0.n() != data.key1.n(),
+        )?;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(
+            &aux.rsa_modulo,
+            [&commitment.e, &commitment.s, &commitment.f, &commitment.t],
+        )?;
+        fail_if_degenerate(data.key0.nn(), [&commitment.a])?;
+        fail_if_degenerate(data.key1.nn(), [&commitment.b_y])?;
+        crate::common::fail_if_not_coprime(data.key0.n(), [&proof.w])?;
+        crate::common::fail_if_not_coprime(data.key1.n(), [&proof.w_y])?;
+        fail_if(
+            InvalidProofReason::DegenerateCommitment,
+            !commitment.b_x.is_zero(),
+        )?;
         // Five equality checks and two range checks
         {
             let lhs = {
-                let z1_at_c = data
-                    .key0
-                    .omul(&proof.z1, data.c)
-                    .map_err(|_| InvalidProofReason::PaillierOp)?;
+                let z1_at_c = statement.c.omul(data.key0, &proof.z1)?;
                 let enc = data
                     .key0
                     .encrypt_with(&proof.z2, &proof.w)
@@ -364,64 +671,76 @@ pub mod interactive {
                     .map_err(|_| InvalidProofReason::PaillierOp)?
             };
             let rhs = {
-                let e_at_d = data
-                    .key0
-                    .omul(challenge, data.d)
-                    .map_err(|_| InvalidProofReason::PaillierOp)?;
+                let e_at_d = statement.d.omul(data.key0, challenge)?;
                 data.key0
                     .oadd(&commitment.a, &e_at_d)
                     .map_err(|_| InvalidProofReason::PaillierOp)?
             };
             fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
         }
+        crate::common::lap!(Check("affine operation under N0"));
         {
             let lhs = Point::<C>::generator() * proof.z1.to_scalar();
             let rhs = commitment.b_x + data.x * challenge.to_scalar();
             fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
         }
+        crate::common::lap!(Check("group element"));
         {
             let lhs = data
                 .key1
                 .encrypt_with(&proof.z2, &proof.w_y)
                 .map_err(|_| InvalidProofReason::PaillierEnc)?;
             let rhs = {
-                let e_at_y = data
-                    .key1
-                    .omul(challenge, data.y)
-                    .map_err(|_| InvalidProofReason::PaillierOp)?;
+                let e_at_y = statement.y.omul(data.key1, challenge)?;
                 data.key1
                     .oadd(&commitment.b_y, &e_at_y)
                     .map_err(|_| InvalidProofReason::PaillierOp)?
             };
             fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
         }
+        crate::common::lap!(Check("paillier encryption under N1"));
         {
             let lhs = aux.combine(&proof.z1, &proof.z3)?;
             let s_to_e = aux.pow_mod(&commitment.s, challenge)?;
             let rhs = (&commitment.e * s_to_e).modulo(&aux.rsa_modulo);
             fail_if_ne(InvalidProofReason::EqualityCheck(4), lhs, rhs)?;
         }
+        crate::common::lap!(Check("ring-pedersen for x"));
         {
             let lhs = aux.combine(&proof.z2, &proof.z4)?;
             let t_to_e = aux.pow_mod(&commitment.t, challenge)?;
             let rhs = (&commitment.f * t_to_e).modulo(&aux.rsa_modulo);
             fail_if_ne(InvalidProofReason::EqualityCheck(5), lhs, rhs)?;
         }
-        fail_if(
-            InvalidProofReason::RangeCheck(6),
-            proof
-                .z1
-                .is_in_pm(&(Integer::ONE << (security.l_x + security.epsilon)).complete()),
-        )?;
-        fail_if(
-            InvalidProofReason::RangeCheck(7),
-            proof
-                .z2
-                .is_in_pm(&(Integer::ONE << (security.l_y + security.epsilon)).complete()),
-        )?;
+        crate::common::lap!(Check("ring-pedersen for y"));
+        check_response_range(6, "x", &proof.z1, security.l_x + security.epsilon)?;
+        crate::common::lap!(Check("range of z1"));
+        check_response_range(7, "y", &proof.z2, security.l_y + security.epsilon)?;
+        crate::common::lap!(Check("range of z2"));
         Ok(())
     }
 
+    /// Checks that response `z` for `witness` is in `[-2^bound_bits; 2^bound_bits]`
+    ///
+    /// `x` and `y` have distinct bounds, so the error names the witness and reports
+    /// the bit length of the response
+    fn check_response_range(
+        check: usize,
+        witness: &'static str,
+        z: &Integer,
+        bound_bits: usize,
+    ) -> Result<(), InvalidProofReason> {
+        fail_if(
+            InvalidProofReason::ResponseOutOfRange {
+                check,
+                witness,
+                bound_bits,
+                bits: z.significant_bits(),
+            },
+            z.is_in_pm(&(Integer::ONE << bound_bits).complete()),
+        )
+    }
+
     /// Generate random challenge
     pub fn challenge<R>(security: &SecurityParams, rng: &mut R) -> Integer
     where
@@ -434,13 +753,16 @@ pub mod interactive {
 /// The non-interactive version of proof. Completed in one round, for example
 /// see the documentation of parent module.
 pub mod non_interactive {
-    use digest::{typenum::U32, Digest};
+    use digest::Digest;
     use generic_ec::Curve;
     use rand_core::RngCore;
 
+    use crate::transcript::Transcript;
     use crate::{Error, InvalidProof};
 
-    use super::{Aux, Challenge, Commitment, Data, PrivateData, Proof, SecurityParams};
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
 
     /// Compute proof for the given data, producing random commitment and
     /// deriving determenistic challenge.
@@ -455,11 +777,33 @@ pub mod non_interactive {
         rng: R,
     ) -> Result<(Commitment<C>, Proof), Error>
     where
-        D: Digest<OutputSize = U32>,
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<C: Curve, R: RngCore, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: R,
+    ) -> Result<(Commitment<C>, Proof), Error>
+    where
+        D: Digest,
     {
         let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
-        let challenge = challenge(shared_state, aux, data, &comm, security);
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
         let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
         Ok((comm, proof))
     }
 
@@ -471,14 +815,41 @@ pub mod non_interactive {
         commitment: &Commitment<C>,
         security: &SecurityParams,
         proof: &Proof,
-    ) -> Result<(), InvalidProof>
-    where
-        D: Digest<OutputSize = U32>,
-    {
-        let challenge = challenge(shared_state, aux, data, commitment, security);
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
         super::interactive::verify(aux, data, commitment, security, &challenge, proof)
     }
 
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+
     /// Deterministically compute challenge based on prior known values in protocol
     pub fn challenge<C: Curve, D: Digest>(
         shared_state: D,
@@ -486,40 +857,117 @@ pub mod non_interactive {
         data: Data<C>,
         commitment: &Commitment<C>,
         security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<C: Curve, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
     ) -> Challenge
     where
         D: Digest,
     {
-        let shared_state = shared_state.finalize();
-        let hash = |d: D| {
-            let order = rug::integer::Order::Msf;
-            d.chain_update(&shared_state)
-                .chain_update(aux.s.to_digits::<u8>(order))
-                .chain_update(aux.t.to_digits::<u8>(order))
-                .chain_update(aux.rsa_modulo.to_digits::<u8>(order))
-                .chain_update((security.l_x as u64).to_le_bytes())
-                .chain_update((security.l_y as u64).to_le_bytes())
-                .chain_update((security.epsilon as u64).to_le_bytes())
-                .chain_update(data.key0.n().to_digits::<u8>(order))
-                .chain_update(data.key1.n().to_digits::<u8>(order))
-                .chain_update(data.c.to_digits::<u8>(order))
-                .chain_update(data.d.to_digits::<u8>(order))
-                .chain_update(data.y.to_digits::<u8>(order))
-                .chain_update(data.x.to_bytes(true))
-                .chain_update(commitment.a.to_digits::<u8>(order))
-                .chain_update(commitment.b_x.to_bytes(true))
-                .chain_update(commitment.b_y.to_digits::<u8>(order))
-                .chain_update(commitment.e.to_digits::<u8>(order))
-                .chain_update(commitment.s.to_digits::<u8>(order))
-                .chain_update(commitment.f.to_digits::<u8>(order))
-                .chain_update(commitment.t.to_digits::<u8>(order))
-                .finalize()
-        };
-        let mut rng = crate::common::rng::HashRng::new(hash);
+        transcript.label(b"paillier_zk.paillier_affine_operation_in_range");
+        transcript.absorb_domain_tag(security.domain_tag.as_deref());
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb(b"l_x", &(security.l_x as u64).to_be_bytes());
+        transcript.absorb(b"l_y", &(security.l_y as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"N0", data.key0.n());
+        transcript.absorb_value(b"N1", data.key1.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_value(b"D", data.d);
+        transcript.absorb_value(b"Y", data.y);
+        transcript.absorb_point(b"X", data.x);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
         super::interactive::challenge(security, &mut rng)
     }
 }
 
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use generic_ec::{Curve, Point};
+    use rug::Integer;
+
+    use crate::common::extract::{
+        challenge_diff, ensure, linear_response, omul, paillier_nonce, ExtractionErrorReason,
+    };
+    use crate::common::IntegerExt;
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub x: Integer,
+        pub y: Integer,
+        pub nonce: Nonce,
+        pub nonce_y: Nonce,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract<C: Curve>(
+        data: Data<C>,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let mismatch = || ExtractionError::from(ExtractionErrorReason::WitnessMismatch);
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let x = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        let y = linear_response(&proof1.z2, &proof2.z2, &e_diff)?;
+        let nonce_y = paillier_nonce(data.key1.n(), data.y, &proof1.w_y, &proof2.w_y, &e_diff)?;
+
+        // D = C^x enc0(y, rho), so D C^(-x) is an encryption of y with nonce rho
+        let x_at_c = omul(data.key0, &x, data.c)?;
+        let y_enc = data.key0.osub(data.d, &x_at_c).map_err(|_| mismatch())?;
+        let nonce = paillier_nonce(data.key0.n(), &y_enc, &proof1.w, &proof2.w, &e_diff)?;
+
+        let enc_y = data.key0.encrypt_with(&y, &nonce).ok();
+        ensure(enc_y == Some(y_enc))?;
+        let enc_y = data.key1.encrypt_with(&y, &nonce_y).ok();
+        ensure(enc_y.as_ref() == Some(data.y))?;
+        ensure(Point::<C>::generator() * x.to_scalar() == *data.x)?;
+        Ok(Witness {
+            x,
+            y,
+            nonce,
+            nonce_y,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use generic_ec::{Curve, Point};
@@ -547,7 +995,8 @@ mod test {
         let (y_enc_ek1, rho_y) = ek1.encrypt_with_random(rng, &y).unwrap();
 
         let (y_enc_ek0, rho) = ek0.encrypt_with_random(rng, &y).unwrap();
-        let x_at_c = ek0.omul(&x, &c).unwrap();
+        // `omul` rejects x = 0
+        let x_at_c = Integer::from(c.pow_mod_ref(&x, ek0.nn()).unwrap());
         let d = ek0.oadd(&x_at_c, &y_enc_ek0).unwrap();
 
         let data = super::Data {
@@ -582,12 +1031,30 @@ mod test {
             l_y: 1024,
             epsilon: 300,
             q: (Integer::ONE << 128_u32).into(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_distinct_keys: false,
+            require_bound_fits_curve: false,
+            domain_tag: None,
         };
         let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
         let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
         run::<_, C>(&mut rng, security, x, y).expect("proof failed");
     }
 
+    fn zero_witness<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security =
+            super::SecurityParams::new(1024, 1024, 300, (Integer::ONE << 128_u32).complete());
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+        // x = 0: X is the identity point, D doesn't depend on C
+        run::<_, C>(&mut rng, security.clone(), Integer::ZERO, y).expect("proof failed");
+        // y = 0: Y encrypts zero
+        run::<_, C>(&mut rng, security.clone(), x, Integer::ZERO).expect("proof failed");
+        run::<_, C>(&mut rng, security, Integer::ZERO, Integer::ZERO).expect("proof failed");
+    }
+
     fn failing_on_additive<C: Curve>() {
         let mut rng = rand_dev::DevRng::new();
         let security = super::SecurityParams {
@@ -595,12 +1062,21 @@ mod test {
             l_y: 1024,
             epsilon: 300,
             q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_distinct_keys: false,
+            require_bound_fits_curve: false,
+            domain_tag: None,
         };
         let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
         let y = (Integer::ONE << (security.l_y + security.epsilon)).complete() + 1;
         let r = run::<_, C>(&mut rng, security, x, y).expect_err("proof should not pass");
         match r.reason() {
-            InvalidProofReason::RangeCheck(7) => (),
+            InvalidProofReason::ResponseOutOfRange {
+                check: 7,
+                witness: "y",
+                ..
+            } => (),
             e => panic!("proof should not fail with: {e:?}"),
         }
     }
@@ -612,21 +1088,389 @@ mod test {
             l_y: 1024,
             epsilon: 300,
             q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_distinct_keys: false,
+            require_bound_fits_curve: false,
+            domain_tag: None,
         };
         let x = (Integer::ONE << (security.l_x + security.epsilon)).complete() + 1;
         let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+        let bound = security.l_x + security.epsilon;
+        let r = run::<_, C>(&mut rng, security, x, y).expect_err("proof should not pass");
+        match r.reason() {
+            InvalidProofReason::ResponseOutOfRange {
+                check: 6,
+                witness: "x",
+                bound_bits,
+                bits,
+            } => {
+                assert_eq!(bound_bits, bound);
+                assert!(bits as usize > bound);
+            }
+            e => panic!("proof should not fail with: {e:?}"),
+        }
+    }
+
+    fn distinct_bounds<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security =
+            super::SecurityParams::new(256, 1024, 300, (Integer::ONE << 128_u32).complete());
+        // y exceeds the bound on x, but is within its own bound
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = (Integer::ONE << (security.l_y - 1)).complete() + 1;
+        run::<_, C>(&mut rng, security.clone(), x, y).expect("proof failed");
+
+        // x within the bound on y is still rejected
+        let x = (Integer::ONE << (security.l_x + security.epsilon)).complete() + 1;
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
         let r = run::<_, C>(&mut rng, security, x, y).expect_err("proof should not pass");
         match r.reason() {
-            InvalidProofReason::RangeCheck(6) => (),
+            InvalidProofReason::ResponseOutOfRange {
+                check: 6,
+                witness: "x",
+                ..
+            } => (),
             e => panic!("proof should not fail with: {e:?}"),
         }
     }
 
+    fn negative_challenge<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l_x: 1024,
+            l_y: 1024,
+            epsilon: 300,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_distinct_keys: false,
+            require_bound_fits_curve: false,
+            domain_tag: None,
+        };
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+
+        let dk0 = random_key(&mut rng).unwrap();
+        let dk1 = random_key(&mut rng).unwrap();
+        let ek0 = dk0.encryption_key().clone();
+        let ek1 = dk1.encryption_key().clone();
+        let (c, _) = {
+            let plaintext = Integer::from_rng_pm(ek0.half_n(), &mut rng);
+            ek0.encrypt_with_random(&mut rng, &plaintext).unwrap()
+        };
+        let (y_enc_ek1, rho_y) = ek1.encrypt_with_random(&mut rng, &y).unwrap();
+        let (y_enc_ek0, rho) = ek0.encrypt_with_random(&mut rng, &y).unwrap();
+        let d = ek0.oadd(&ek0.omul(&x, &c).unwrap(), &y_enc_ek0).unwrap();
+
+        let data = super::Data {
+            key0: &ek0,
+            key1: &ek1,
+            c: &c,
+            d: &d,
+            y: &y_enc_ek1,
+            x: &(x.to_scalar::<C>() * Point::generator()),
+        };
+        let pdata = super::PrivateData {
+            x: &x,
+            y: &y,
+            nonce: &rho,
+            nonce_y: &rho_y,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let challenge = -super::interactive::challenge(&security, &mut rng).abs() - 1;
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof)
+            .expect("proof with negative challenge failed");
+
+        // Proof is bound to the sign of the challenge
+        let r = super::interactive::verify(
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &(-&challenge).complete(),
+            &proof,
+        );
+        assert!(r.is_err());
+    }
+
+    fn degenerate_transcript<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l_x: 1024,
+            l_y: 1024,
+            epsilon: 300,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_distinct_keys: false,
+            require_bound_fits_curve: false,
+            domain_tag: None,
+        };
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+
+        let dk0 = random_key(&mut rng).unwrap();
+        let dk1 = random_key(&mut rng).unwrap();
+        let ek0 = dk0.encryption_key().clone();
+        let ek1 = dk1.encryption_key().clone();
+        let (c, _) = {
+            let plaintext = Integer::from_rng_pm(ek0.half_n(), &mut rng);
+            ek0.encrypt_with_random(&mut rng, &plaintext).unwrap()
+        };
+        let (y_enc_ek1, rho_y) = ek1.encrypt_with_random(&mut rng, &y).unwrap();
+        let (y_enc_ek0, rho) = ek0.encrypt_with_random(&mut rng, &y).unwrap();
+        let d = ek0.oadd(&ek0.omul(&x, &c).unwrap(), &y_enc_ek0).unwrap();
+
+        let data = super::Data {
+            key0: &ek0,
+            key1: &ek1,
+            c: &c,
+            d: &d,
+            y: &y_enc_ek1,
+            x: &(x.to_scalar::<C>() * Point::generator()),
+        };
+        let pdata = super::PrivateData {
+            x: &x,
+            y: &y,
+            nonce: &rho,
+            nonce_y: &rho_y,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Commitment equal to one is rejected
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let mut degenerate = commitment.clone();
+        degenerate.e = Integer::from(1);
+        let r = super::interactive::verify(&aux, data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+
+        // Identity point in commitment is rejected
+        let mut commitment = commitment;
+        commitment.b_x = Point::zero();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[test]
+    fn serialized_size_distinct_bounds() {
+        use crate::common::encoding::Encode;
+        type C = generic_ec::curves::Secp256r1;
+
+        let mut rng = rand_dev::DevRng::new();
+        // Responses for y are wider than responses for x
+        let security =
+            super::SecurityParams::new(256, 1024, 300, (Integer::ONE << 128_u32).complete());
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+
+        let dk0 = random_key(&mut rng).unwrap();
+        let dk1 = random_key(&mut rng).unwrap();
+        let ek0 = dk0.encryption_key().clone();
+        let ek1 = dk1.encryption_key().clone();
+        let (c, _) = {
+            let plaintext = Integer::from_rng_pm(ek0.half_n(), &mut rng);
+            ek0.encrypt_with_random(&mut rng, &plaintext).unwrap()
+        };
+        let (y_enc_ek1, rho_y) = ek1.encrypt_with_random(&mut rng, &y).unwrap();
+        let (y_enc_ek0, rho) = ek0.encrypt_with_random(&mut rng, &y).unwrap();
+        let d = ek0.oadd(&ek0.omul(&x, &c).unwrap(), &y_enc_ek0).unwrap();
+
+        let data = super::Data {
+            key0: &ek0,
+            key1: &ek1,
+            c: &c,
+            d: &d,
+            y: &y_enc_ek1,
+            x: &(x.to_scalar::<C>() * Point::generator()),
+        };
+        let pdata = super::PrivateData {
+            x: &x,
+            y: &y,
+            nonce: &rho,
+            nonce_y: &rho_y,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let (_commitment, proof) = super::non_interactive::prove(
+            sha2::Sha256::default(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        let modulus_bits = aux
+            .rsa_modulo
+            .significant_bits()
+            .max(ek0.n().significant_bits())
+            .max(ek1.n().significant_bits());
+        assert!(
+            proof.to_canonical_bytes().len()
+                <= super::Proof::max_serialized_size(&security, modulus_bits as usize)
+        );
+    }
+
+    #[test]
+    fn distinct_keys() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let security =
+            super::SecurityParams::new(1024, 1024, 300, (Integer::ONE << 128_u32).complete());
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+
+        // Prover reuses the same key as both `N0` and `N1`
+        let dk = random_key(&mut rng).unwrap();
+        let ek = dk.encryption_key();
+        let (c, _) = {
+            let plaintext = Integer::from_rng_pm(ek.half_n(), &mut rng);
+            ek.encrypt_with_random(&mut rng, &plaintext).unwrap()
+        };
+        let (y_enc, rho) = ek.encrypt_with_random(&mut rng, &y).unwrap();
+        let d = ek.oadd(&ek.omul(&x, &c).unwrap(), &y_enc).unwrap();
+        let data = super::Data {
+            key0: ek,
+            key1: ek,
+            c: &c,
+            d: &d,
+            y: &y_enc,
+            x: &(x.to_scalar::<C>() * Point::generator()),
+        };
+        let pdata = super::PrivateData {
+            x: &x,
+            y: &y,
+            nonce: &rho,
+            nonce_y: &rho,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let verify = |security: &super::SecurityParams| {
+            super::non_interactive::verify(
+                shared_state.clone(),
+                &aux,
+                data,
+                &commitment,
+                security,
+                &proof,
+            )
+        };
+
+        verify(&security).expect("same keys are allowed by default");
+        let r = verify(&security.clone().with_distinct_keys_required());
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::SameKeys)
+        );
+
+        let security = security.with_distinct_keys_required();
+        run::<_, C>(&mut rng, security, x, y).expect("distinct keys are accepted");
+    }
+
+    #[test]
+    fn domain_separation() {
+        type C = generic_ec::curves::Secp256k1;
+        let mut rng = rand_dev::DevRng::new();
+        let security =
+            super::SecurityParams::new(1024, 1024, 300, (Integer::ONE << 128_u32).complete());
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+
+        let ek0 = random_key(&mut rng).unwrap().encryption_key().clone();
+        let ek1 = random_key(&mut rng).unwrap().encryption_key().clone();
+        let (c, _) = {
+            let plaintext = Integer::from_rng_pm(ek0.half_n(), &mut rng);
+            ek0.encrypt_with_random(&mut rng, &plaintext).unwrap()
+        };
+        let (y_enc_ek1, rho_y) = ek1.encrypt_with_random(&mut rng, &y).unwrap();
+        let (y_enc_ek0, rho) = ek0.encrypt_with_random(&mut rng, &y).unwrap();
+        let d = ek0.oadd(&ek0.omul(&x, &c).unwrap(), &y_enc_ek0).unwrap();
+        let data = super::Data {
+            key0: &ek0,
+            key1: &ek1,
+            c: &c,
+            d: &d,
+            y: &y_enc_ek1,
+            x: &(x.to_scalar::<C>() * Point::generator()),
+        };
+        let pdata = super::PrivateData {
+            x: &x,
+            y: &y,
+            nonce: &rho,
+            nonce_y: &rho_y,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let app_a = security.clone().with_domain_tag(b"app-a".as_slice());
+        let app_b = security.clone().with_domain_tag(b"app-b".as_slice());
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &app_a,
+            &mut rng,
+        )
+        .unwrap();
+        let verify = |security: &super::SecurityParams| {
+            super::non_interactive::verify(
+                shared_state.clone(),
+                &aux,
+                data,
+                &commitment,
+                security,
+                &proof,
+            )
+        };
+
+        verify(&app_a).expect("proof failed");
+        verify(&app_b).expect_err("proof of another application should not pass");
+        verify(&security).expect_err("proof with a tag should not pass without it");
+    }
+
     #[test]
     fn passing_p256() {
         passing_test::<generic_ec::curves::Secp256r1>()
     }
     #[test]
+    fn zero_witness_p256() {
+        zero_witness::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
     fn failing_p256_add() {
         failing_on_additive::<generic_ec::curves::Secp256r1>()
     }
@@ -634,6 +1478,18 @@ mod test {
     fn failing_p256_mul() {
         failing_on_multiplicative::<generic_ec::curves::Secp256r1>()
     }
+    #[test]
+    fn distinct_bounds_p256() {
+        distinct_bounds::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn negative_challenge_p256() {
+        negative_challenge::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn degenerate_transcript_p256() {
+        degenerate_transcript::<generic_ec::curves::Secp256r1>()
+    }
 
     #[test]
     fn passing_million() {
@@ -647,4 +1503,73 @@ mod test {
     fn failing_million_mul() {
         failing_on_multiplicative::<crate::curve::C>()
     }
+    #[test]
+    fn negative_challenge_million() {
+        negative_challenge::<crate::curve::C>()
+    }
+    #[test]
+    fn degenerate_transcript_million() {
+        degenerate_transcript::<crate::curve::C>()
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l_x: 1024,
+            l_y: 1024,
+            epsilon: 300,
+            q: (Integer::ONE << 128_u32).into(),
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_distinct_keys: false,
+            require_bound_fits_curve: false,
+            domain_tag: None,
+        };
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+        // Zero is a legitimate witness
+        for x in [x, Integer::ZERO] {
+            let ek0 = random_key(&mut rng).unwrap().encryption_key().clone();
+            let ek1 = random_key(&mut rng).unwrap().encryption_key().clone();
+            let (c, _) = {
+                let plaintext = Integer::from_rng_pm(ek0.half_n(), &mut rng);
+                ek0.encrypt_with_random(&mut rng, &plaintext).unwrap()
+            };
+            let (y_enc_ek1, rho_y) = ek1.encrypt_with_random(&mut rng, &y).unwrap();
+            let (y_enc_ek0, rho) = ek0.encrypt_with_random(&mut rng, &y).unwrap();
+            let x_at_c = Integer::from(c.pow_mod_ref(&x, ek0.nn()).unwrap());
+            let d = ek0.oadd(&x_at_c, &y_enc_ek0).unwrap();
+            let data = super::Data {
+                key0: &ek0,
+                key1: &ek1,
+                c: &c,
+                d: &d,
+                y: &y_enc_ek1,
+                x: &(x.to_scalar::<C>() * Point::generator()),
+            };
+            let pdata = super::PrivateData {
+                x: &x,
+                y: &y,
+                nonce: &rho,
+                nonce_y: &rho_y,
+            };
+            let aux = crate::common::test::aux(&mut rng);
+
+            let (_commitment, pcommitment) =
+                super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+            let e1 = super::interactive::challenge(&security, &mut rng);
+            let e2 = super::interactive::challenge(&security, &mut rng);
+            let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+            let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+            let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+            assert_eq!(witness.x, x);
+            assert_eq!(witness.y, y);
+            assert_eq!(witness.nonce, rho);
+            assert_eq!(witness.nonce_y, rho_y);
+        }
+    }
 }
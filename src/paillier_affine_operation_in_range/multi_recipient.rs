@@ -0,0 +1,371 @@
+//! Пaff-g for every counterparty of the MtA broadcast at once
+//!
+//! ## Description
+//!
+//! In presigning of CGGMP21, the prover runs [Пaff-g](super) once per counterparty
+//! with the same `x` and `y`. `key1`, `X` and `Y` are the prover's and the same for all
+//! counterparties, while `key0`, `C` and ring-pedersen parameters belong to the
+//! counterparty. [`non_interactive::prove`] computes `D = C^x enc0(y)` and the proof
+//! for every counterparty, and returns them keyed by the counterparty.
+//!
+//! Randomness of the commitments is never shared between the proofs: each proof is
+//! answered to its own challenge, and two responses `alpha + e x` with the same mask
+//! reveal `x`. Only the witness is shared. Sampling is cheap, so it's done upfront from
+//! the given rng, then commitments and responses, which are dominated by modular
+//! exponentiations, are computed for different counterparties in parallel threads.
+//! Where threads are not available, e.g. in wasm, they're computed one by one.
+//!
+//! Each proof is verified independently by its counterparty with
+//! [`super::non_interactive::verify`].
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use paillier_zk::paillier_affine_operation_in_range::{self as p, multi_recipient as m};
+//! use paillier_zk::IntegerExt;
+//! use generic_ec::{curves::Secp256k1 as E, Point};
+//! # fn doc_fn(
+//! #     security: &p::SecurityParams,
+//! #     my_key: &fast_paillier::EncryptionKey,
+//! #     peers: &[(u16, fast_paillier::EncryptionKey, fast_paillier::Ciphertext, p::Aux)],
+//! #     x: &rug::Integer,
+//! #     y: &rug::Integer,
+//! #     y_enc: &fast_paillier::Ciphertext,
+//! #     nonce_y: &fast_paillier::Nonce,
+//! # ) -> Result<(), paillier_zk::Error> {
+//! # let mut rng = rand_core::OsRng;
+//! let x_point = Point::<E>::generator() * x.to_scalar();
+//! let recipients = peers.iter().map(|(j, key0, c, aux)| {
+//!     let recipient = m::Recipient {
+//!         key0,
+//!         c,
+//!         aux,
+//!         shared_state: sha2::Sha256::default(),
+//!     };
+//!     (*j, recipient)
+//! });
+//! let proofs = m::non_interactive::prove(
+//!     recipients,
+//!     m::Shared { key1: my_key, x: &x_point, y: y_enc },
+//!     m::SharedWitness { x, y, nonce_y },
+//!     security,
+//!     &mut rng,
+//! )?;
+//! for (_j, _proof) in proofs {
+//!     // send `proof.d`, `proof.commitment`, `proof.proof` to the party `j`
+//! }
+//! # Ok(()) }
+//! ```
+
+use fast_paillier::{Ciphertext, EncryptionKey, Nonce};
+use generic_ec::{Curve, Point};
+use rug::Integer;
+
+use super::{Aux, Commitment, Proof};
+
+/// Counterparty's part of the statement
+#[derive(Debug, Clone)]
+pub struct Recipient<'a, D> {
+    /// N0 in paper, counterparty's public key
+    pub key0: &'a EncryptionKey,
+    /// C in paper, ciphertext received from the counterparty, encrypted on `key0`
+    pub c: &'a Ciphertext,
+    /// Ring-pedersen parameters of the counterparty
+    pub aux: &'a Aux,
+    /// Protocol state shared with the counterparty
+    pub shared_state: D,
+}
+
+/// Part of the statement that is the same for every counterparty
+#[derive(Debug, Clone, Copy)]
+pub struct Shared<'a, C: Curve> {
+    /// N1 in paper, prover's public key
+    pub key1: &'a EncryptionKey,
+    /// X in paper, obtained as g^x
+    pub x: &'a Point<C>,
+    /// Y in paper, y encrypted on N1
+    pub y: &'a Ciphertext,
+}
+
+/// Private data of prover that is the same for every counterparty
+#[derive(Clone, Copy)]
+pub struct SharedWitness<'a> {
+    /// x or epsilon in paper, preimage of X
+    pub x: &'a Integer,
+    /// y or delta in paper, preimage of Y
+    pub y: &'a Integer,
+    /// rho_y in paper, nonce in encryption of y to obtain Y
+    pub nonce_y: &'a Nonce,
+}
+
+crate::transcript::assert_private!(SharedWitness<'static>);
+
+/// Messages of the prover to one counterparty
+#[derive(Debug, Clone)]
+pub struct RecipientProof<C: Curve> {
+    /// D in paper, `C^x enc0(y)`
+    pub d: Ciphertext,
+    /// Prover's first message
+    pub commitment: Commitment<C>,
+    /// Prover's response
+    pub proof: Proof,
+}
+
+/// The non-interactive version of proof
+pub mod non_interactive {
+    use std::collections::BTreeMap;
+
+    use digest::Digest;
+    use fast_paillier::Nonce;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+    use rug::Integer;
+
+    use crate::common::IntegerExt;
+    use crate::Error;
+
+    use super::super::{interactive, Data, PrivateCommitment, PrivateData, SecurityParams};
+    use super::{Recipient, RecipientProof, Shared, SharedWitness};
+
+    /// Computes `D` and the proof for every recipient
+    ///
+    /// Returns messages to the recipients keyed the same as `recipients`. If the same
+    /// key appears twice, only the last proof is kept.
+    pub fn prove<'a, K, C, D, R>(
+        recipients: impl IntoIterator<Item = (K, Recipient<'a, D>)>,
+        shared: Shared<C>,
+        witness: SharedWitness,
+        security: &SecurityParams,
+        mut rng: R,
+    ) -> Result<BTreeMap<K, RecipientProof<C>>, Error>
+    where
+        K: Ord,
+        C: Curve,
+        D: Digest + Send,
+        R: RngCore,
+    {
+        crate::compatibility::check_bound_fits_curve::<C>(
+            security.l_x,
+            security.require_bound_fits_curve,
+        )?;
+        let mut keys = vec![];
+        let mut jobs = vec![];
+        for (key, recipient) in recipients {
+            crate::common::check_aux(recipient.aux, security.reject_weak_aux)?;
+            let nonce = Integer::gen_invertible(recipient.key0.n(), &mut rng);
+            let pcomm = interactive::sample(
+                recipient.aux,
+                recipient.key0.n(),
+                shared.key1.n(),
+                security,
+                &mut rng,
+            )?;
+            keys.push(key);
+            jobs.push(Job {
+                recipient,
+                nonce,
+                pcomm,
+            });
+        }
+
+        let threads = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(jobs.len());
+        let proofs = if threads <= 1 {
+            jobs.into_iter()
+                .map(|job| job.run(shared, witness, security))
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            let mut chunks = (0..threads).map(|_| vec![]).collect::<Vec<_>>();
+            for (i, job) in jobs.into_iter().enumerate() {
+                chunks[i % threads].push((i, job));
+            }
+            let mut proofs = std::thread::scope(|s| {
+                let handles = chunks
+                    .into_iter()
+                    .map(|chunk| {
+                        s.spawn(move || {
+                            chunk
+                                .into_iter()
+                                .map(|(i, job)| Ok((i, job.run(shared, witness, security)?)))
+                                .collect::<Result<Vec<_>, Error>>()
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle
+                            .join()
+                            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+            proofs.sort_unstable_by_key(|(i, _)| *i);
+            proofs.into_iter().map(|(_, proof)| proof).collect()
+        };
+
+        Ok(keys.into_iter().zip(proofs).collect())
+    }
+
+    /// Randomness sampled for one recipient
+    struct Job<'a, D> {
+        recipient: Recipient<'a, D>,
+        /// rho in paper
+        nonce: Nonce,
+        pcomm: PrivateCommitment,
+    }
+
+    impl<D: Digest> Job<'_, D> {
+        fn run<C: Curve>(
+            self,
+            shared: Shared<C>,
+            witness: SharedWitness,
+            security: &SecurityParams,
+        ) -> Result<RecipientProof<C>, Error> {
+            let Recipient {
+                key0,
+                c,
+                aux,
+                shared_state,
+            } = self.recipient;
+
+            // `omul` rejects x = 0
+            let x_at_c: Integer = c
+                .pow_mod_ref(witness.x, key0.nn())
+                .ok_or(crate::ErrorReason::Invert)?
+                .into();
+            let y_enc = key0.encrypt_with(witness.y, &self.nonce)?;
+            let d = key0.oadd(&x_at_c, &y_enc)?;
+
+            let data = Data {
+                key0,
+                key1: shared.key1,
+                c,
+                d: &d,
+                y: shared.y,
+                x: shared.x,
+            };
+            let pdata = PrivateData {
+                x: witness.x,
+                y: witness.y,
+                nonce: &self.nonce,
+                nonce_y: witness.nonce_y,
+            };
+            let commitment = interactive::commitment_of(aux, data, pdata, &self.pcomm)?;
+            let challenge = super::super::non_interactive::challenge(
+                shared_state,
+                aux,
+                data,
+                &commitment,
+                security,
+            );
+            let proof = interactive::prove(data, pdata, &self.pcomm, &challenge)?;
+            Ok(RecipientProof {
+                d,
+                commitment,
+                proof,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generic_ec::{curves::Secp256k1 as E, Point};
+    use rug::{Complete, Integer};
+
+    use crate::common::test::random_key;
+    use crate::common::IntegerExt;
+
+    use super::{non_interactive, Recipient, Shared, SharedWitness};
+
+    #[test]
+    fn proves_for_every_recipient() {
+        let mut rng = rand_dev::DevRng::new();
+        let security =
+            super::super::SecurityParams::new(256, 1280, 512, (Integer::ONE << 128_u32).complete());
+
+        let my_key = random_key(&mut rng).unwrap().encryption_key().clone();
+        let peers = (0..3u16)
+            .map(|j| {
+                let key0 = random_key(&mut rng).unwrap().encryption_key().clone();
+                let plaintext = Integer::from_rng_pm(key0.half_n(), &mut rng);
+                let (c, _) = key0.encrypt_with_random(&mut rng, &plaintext).unwrap();
+                let aux = crate::common::test::aux(&mut rng);
+                (j, key0, c, aux)
+            })
+            .collect::<Vec<_>>();
+
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+        let x_point = Point::<E>::generator() * x.to_scalar();
+        let (y_enc, nonce_y) = my_key.encrypt_with_random(&mut rng, &y).unwrap();
+        let shared_state = |j: u16| {
+            use sha2::Digest;
+            sha2::Sha256::default().chain_update(j.to_be_bytes())
+        };
+
+        let recipients = peers.iter().map(|(j, key0, c, aux)| {
+            let recipient = Recipient {
+                key0,
+                c,
+                aux,
+                shared_state: shared_state(*j),
+            };
+            (*j, recipient)
+        });
+        let shared = Shared {
+            key1: &my_key,
+            x: &x_point,
+            y: &y_enc,
+        };
+        let witness = SharedWitness {
+            x: &x,
+            y: &y,
+            nonce_y: &nonce_y,
+        };
+        let proofs =
+            non_interactive::prove(recipients, shared, witness, &security, &mut rng).unwrap();
+        assert_eq!(proofs.len(), peers.len());
+
+        for (j, key0, c, aux) in &peers {
+            let proof = &proofs[j];
+            let data = super::super::Data {
+                key0,
+                key1: &my_key,
+                c,
+                d: &proof.d,
+                y: &y_enc,
+                x: &x_point,
+            };
+            super::super::non_interactive::verify(
+                shared_state(*j),
+                aux,
+                data,
+                &proof.commitment,
+                &security,
+                &proof.proof,
+            )
+            .expect("proof failed");
+
+            // Proof is bound to the recipient
+            let other = (j + 1) % 3;
+            super::super::non_interactive::verify(
+                shared_state(other),
+                aux,
+                data,
+                &proof.commitment,
+                &security,
+                &proof.proof,
+            )
+            .expect_err("proof for another recipient should not pass");
+        }
+
+        // Masks are never shared between recipients
+        assert_ne!(proofs[&0].commitment.b_x, proofs[&1].commitment.b_x);
+    }
+}
@@ -0,0 +1,1161 @@
+//! ZK-proof of paillier operation with paillier commitment in range. Called
+//! Пaff-p or Raff-p in the CGGMP21 paper.
+//!
+//! ## Description
+//!
+//! A party P performs a paillier affine operation with C, Y, and X
+//! obtaining `D = C*X + Y`. `X` and `Y` are encrypted values of `x` and `y`. P
+//! then wants to prove that `y` and `x` are at most `L` and `L'` bits,
+//! correspondingly, and P doesn't want to disclose none of the plaintexts
+//!
+//! Unlike [Пaff-g](crate::paillier_affine_operation_in_range), `X` is a paillier
+//! ciphertext rather than a group element.
+//!
+//! Given:
+//! - `key0`, `pkey0`, `key1`, `pkey1` - pairs of public and private keys in
+//!   paillier cryptosystem
+//! - `nonce_x`, `nonce_y`, `nonce` - nonces in paillier encryption
+//! - `x`, `y` - some numbers
+//! - `C` is some ciphertext encrypted by `key0`
+//! - `X = key1.encrypt(x, nonce_x)`
+//! - `Y = key1.encrypt(y, nonce_y)`
+//! - `D = oadd(enc(y, nonce), omul(x, C))` where `enc`, `oadd` and `omul` are
+//!   paillier encryption, homomorphic addition and multiplication with `key0`
+//!
+//! Prove:
+//! - `bitsize(abs(x)) <= l_x`
+//! - `bitsize(abs(y)) <= l_y`
+//!
+//! Disclosing only: `key0`, `key1`, `C`, `D`, `Y`, `X`
+//!
+//! `x = 0` and `y = 0` are valid witnesses. For `x = 0`, `X` encrypts zero and
+//! `D = key0.encrypt(y)` doesn't depend on `C`. Note that `omul` rejects zero scalar,
+//! so `D` has to be obtained without it in this case.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use paillier_zk::{paillier_affine_operation_with_paillier_commitment as p, IntegerExt};
+//! use rug::{Integer, Complete};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         someone_encryption_key0: fast_paillier::EncryptionKey,
+//! #         someone_encryption_key1: fast_paillier::EncryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//!
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(
+//!     256,
+//!     848,
+//!     230,
+//!     (Integer::ONE << 128_u32).complete(),
+//! );
+//!
+//! // 1. Setup: prover prepares the paillier keys
+//!
+//! // C and D are encrypted by this key
+//! let key0: fast_paillier::EncryptionKey = pregenerated::someone_encryption_key0();
+//! // X and Y are encrypted using this key
+//! let key1: fast_paillier::EncryptionKey = pregenerated::someone_encryption_key1();
+//!
+//! // C is some number encrypted using key0. Neither of parties
+//! // need to know the plaintext
+//! let ciphertext_c = Integer::gen_invertible(&key0.nn(), &mut rng);
+//!
+//! // 2. Setup: prover prepares all plaintexts
+//!
+//! // x in paper
+//! let plaintext_x = Integer::from_rng_pm(
+//!     &(Integer::ONE << security.l_x).complete(),
+//!     &mut rng,
+//! );
+//! // y in paper
+//! let plaintext_y = Integer::from_rng_pm(
+//!     &(Integer::ONE << security.l_y).complete(),
+//!     &mut rng,
+//! );
+//!
+//! // 3. Setup: prover encrypts everything on correct keys and remembers some nonces
+//!
+//! // X and ρ_x in paper
+//! let (ciphertext_x, nonce_x) = key1.encrypt_with_random(
+//!     &mut rng,
+//!     &(plaintext_x.signed_modulo(key1.n())),
+//! )?;
+//! // Y and ρ_y in paper
+//! let (ciphertext_y, nonce_y) = key1.encrypt_with_random(
+//!     &mut rng,
+//!     &(plaintext_y.signed_modulo(key1.n())),
+//! )?;
+//! // nonce is ρ in paper
+//! let (ciphertext_y_by_key0, nonce) = key0.encrypt_with_random(
+//!     &mut rng,
+//!     &(plaintext_y.signed_modulo(key0.n()))
+//! )?;
+//! // D in paper
+//! let ciphertext_d = key0
+//!     .oadd(
+//!         &key0.omul(&plaintext_x, &ciphertext_c)?,
+//!         &ciphertext_y_by_key0,
+//!     )?;
+//!
+//! // 4. Prover computes a non-interactive proof that plaintext_x and
+//! //    plaintext_y are at most `l_x` and `l_y` bits
+//!
+//! let data = p::Data {
+//!     key0: &key0,
+//!     key1: &key1,
+//!     c: &ciphertext_c,
+//!     d: &ciphertext_d,
+//!     x: &ciphertext_x,
+//!     y: &ciphertext_y,
+//! };
+//! let pdata = p::PrivateData {
+//!     x: &plaintext_x,
+//!     y: &plaintext_y,
+//!     nonce: &nonce,
+//!     nonce_x: &nonce_x,
+//!     nonce_y: &nonce_y,
+//! };
+//! let (commitment, proof) =
+//!     p::non_interactive::prove(
+//!         shared_state_prover,
+//!         &aux,
+//!         data,
+//!         pdata,
+//!         &security,
+//!         &mut rng,
+//!     )?;
+//!
+//! // 5. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data, _: &p::Commitment, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 6. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! let r = p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! #
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::PreparedCiphertext;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::{Aux, InvalidProof};
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// l in paper, bit size of +-x
+    pub l_x: usize,
+    /// l' in paper, bit size of +-y
+    pub l_y: usize,
+    /// Epsilon in paper, slackness parameter
+    pub epsilon: usize,
+    /// q in paper. Security parameter for challenge
+    pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+    /// Reject proofs where `N0` equals `N1`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_distinct_keys_required`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub require_distinct_keys: bool,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l_x: usize, l_y: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l_x,
+            l_y,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+            require_distinct_keys: false,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// `aux_digest` must be obtained with [`Aux::digest`] from the same aux that is
+    /// used to prove and verify. Prover and verifier must both enable it, otherwise
+    /// the proof doesn't verify.
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+
+    /// Makes verification reject statements where `key0` and `key1` are the same key
+    ///
+    /// Same as [Пaff-g](crate::paillier_affine_operation_in_range::SecurityParams::with_distinct_keys_required)
+    pub fn with_distinct_keys_required(self) -> Self {
+        Self {
+            require_distinct_keys: true,
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 256, 512, (Integer::ONE << 128_u32).complete())
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N0 in paper, public key that C was encrypted on
+    pub key0: &'a dyn AnyEncryptionKey,
+    /// N1 in paper, public key that x -> X and y -> Y were encrypted on
+    pub key1: &'a dyn AnyEncryptionKey,
+    /// C in paper, some data encrypted on N0
+    pub c: &'a Ciphertext,
+    /// D in paper, result of affine transformation of C with x and y
+    pub d: &'a Integer,
+    /// Y in paper, y encrypted on N1
+    pub y: &'a Ciphertext,
+    /// X in paper, x encrypted on N1
+    pub x: &'a Ciphertext,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// x in paper, plaintext of X
+    pub x: &'a Integer,
+    /// y in paper, plaintext of Y
+    pub y: &'a Integer,
+    /// rho in paper, nonce in encryption of y for additive action
+    pub nonce: &'a Nonce,
+    /// rho_x in paper, nonce in encryption of x to obtain X
+    pub nonce_x: &'a Nonce,
+    /// rho_y in paper, nonce in encryption of y to obtain Y
+    pub nonce_y: &'a Nonce,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    c: PreparedCiphertext,
+    d: PreparedCiphertext,
+    x: PreparedCiphertext,
+    y: PreparedCiphertext,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        let prepare = |key, ciphertext| PreparedCiphertext::new(key, ciphertext);
+        let c = prepare(data.key0, data.c)?;
+        let d = prepare(data.key0, data.d)?;
+        let x = prepare(data.key1, data.x)?;
+        let y = prepare(data.key1, data.y)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, c, d, x, y })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+// As described in cggmp21 at page 69
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment {
+    pub a: Integer,
+    pub b_x: Integer,
+    pub b_y: Integer,
+    pub e: Integer,
+    pub s: Integer,
+    pub f: Integer,
+    pub t: Integer,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub alpha: Integer,
+    pub beta: Integer,
+    pub r: Integer,
+    pub r_x: Integer,
+    pub r_y: Integer,
+    pub gamma: Integer,
+    pub m: Integer,
+    pub delta: Integer,
+    pub mu: Integer,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub z1: Integer,
+    pub z2: Integer,
+    pub z3: Integer,
+    pub z4: Integer,
+    pub w: Integer,
+    pub w_x: Integer,
+    pub w_y: Integer,
+}
+
+impl_encode! { Data<'a> { key0, key1, c, d, y, x } }
+impl_encode! { Commitment { a, b_x, b_y, e, s, f, t } }
+impl_decode! { Commitment { a, b_x, b_y, e, s, f, t } }
+impl_encode! { Proof { z1, z2, z3, z4, w, w_x, w_y } }
+impl_decode! { Proof { z1, z2, z3, z4, w, w_x, w_y } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("a", 2 * modulus_bits)
+            .integer("b_x", 2 * modulus_bits)
+            .integer("b_y", 2 * modulus_bits)
+            .integer("e", modulus_bits)
+            .integer("s", modulus_bits)
+            .integer("f", modulus_bits)
+            .integer("t", modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let z1 = (security.l_x + security.epsilon).max(q + security.l_x) + 1;
+        let z2 = (security.l_y + security.epsilon).max(q + security.l_y) + 1;
+        let z3 = z1 + modulus_bits;
+        let z4 = z2 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .integer("z2", z2)
+            .integer("z3", z3)
+            .integer("z4", z4)
+            .integer("w", modulus_bits)
+            .integer("w_x", modulus_bits)
+            .integer("w_y", modulus_bits)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProof, InvalidProofReason, PreparedCiphertext,
+    };
+    use crate::Error;
+
+    use super::*;
+
+    /// Create random commitment
+    pub fn commit<R: RngCore>(
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        mut rng: R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        let two_to_l = (Integer::ONE << security.l_x).complete();
+        let two_to_l_prime = (Integer::ONE << security.l_y).complete();
+        let two_to_l_e = (Integer::ONE << (security.l_x + security.epsilon)).complete();
+        let two_to_l_prime_e = (Integer::ONE << (security.l_y + security.epsilon)).complete();
+        let hat_n_at_two_to_l_e = (&aux.rsa_modulo * &two_to_l_e).complete();
+        let hat_n_at_two_to_l_prime_e = (&aux.rsa_modulo * &two_to_l_prime_e).complete();
+        let hat_n_at_two_to_l = (&aux.rsa_modulo * &two_to_l).complete();
+        let hat_n_at_two_to_l_prime = (&aux.rsa_modulo * &two_to_l_prime).complete();
+
+        let alpha = Integer::from_rng_pm(&two_to_l_e, &mut rng);
+        let beta = Integer::from_rng_pm(&two_to_l_prime_e, &mut rng);
+        let r = Integer::gen_invertible(data.key0.n(), &mut rng);
+        let r_x = Integer::gen_invertible(data.key1.n(), &mut rng);
+        let r_y = Integer::gen_invertible(data.key1.n(), &mut rng);
+        let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_e, &mut rng);
+        let delta = Integer::from_rng_pm(&hat_n_at_two_to_l_prime_e, &mut rng);
+        let m = Integer::from_rng_pm(&hat_n_at_two_to_l, &mut rng);
+        let mu = Integer::from_rng_pm(&hat_n_at_two_to_l_prime, &mut rng);
+
+        let beta_enc_key0 = data.key0.encrypt_with(&beta, &r)?;
+        let alpha_at_c = data.key0.omul(&alpha, data.c)?;
+        let a = data.key0.oadd(&alpha_at_c, &beta_enc_key0)?;
+
+        let commitment = Commitment {
+            a,
+            b_x: data.key1.encrypt_with(&alpha, &r_x)?,
+            b_y: data.key1.encrypt_with(&beta, &r_y)?,
+            e: aux.combine(&alpha, &gamma)?,
+            s: aux.combine(pdata.x, &m)?,
+            f: aux.combine(&beta, &delta)?,
+            t: aux.combine(pdata.y, &mu)?,
+        };
+        let private_commitment = PrivateCommitment {
+            alpha,
+            beta,
+            r,
+            r_x,
+            r_y,
+            gamma,
+            m,
+            delta,
+            mu,
+        };
+        Ok((commitment, private_commitment))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        data: Data,
+        pdata: PrivateData,
+        pcomm: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        Ok(Proof {
+            z1: (&pcomm.alpha + challenge * pdata.x).complete(),
+            z2: (&pcomm.beta + challenge * pdata.y).complete(),
+            z3: (&pcomm.gamma + challenge * &pcomm.m).complete(),
+            z4: (&pcomm.delta + challenge * &pcomm.mu).complete(),
+            w: data
+                .key0
+                .n()
+                .combine(&pcomm.r, Integer::ONE, pdata.nonce, challenge)?,
+            w_x: data
+                .key1
+                .n()
+                .combine(&pcomm.r_x, Integer::ONE, pdata.nonce_x, challenge)?,
+            w_y: data
+                .key1
+                .n()
+                .combine(&pcomm.r_y, Integer::ONE, pdata.nonce_y, challenge)?,
+        })
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key1.n()))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key1.n()))
+    }
+
+    fn check_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if(
+            InvalidProofReason::SameKeys,
+            !security.require_distinct_keys || data.key0.n() != data.key1.n(),
+        )?;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(
+            &aux.rsa_modulo,
+            [&commitment.e, &commitment.s, &commitment.f, &commitment.t],
+        )?;
+        fail_if_degenerate(data.key0.nn(), [&commitment.a])?;
+        fail_if_degenerate(data.key1.nn(), [&commitment.b_x, &commitment.b_y])?;
+        crate::common::fail_if_not_coprime(data.key0.n(), [&proof.w])?;
+        crate::common::fail_if_not_coprime(data.key1.n(), [&proof.w_x, &proof.w_y])?;
+        // Five equality checks and two range checks
+        {
+            let lhs = {
+                let z1_at_c = statement.c.omul(data.key0, &proof.z1)?;
+                let enc = data
+                    .key0
+                    .encrypt_with(&proof.z2, &proof.w)
+                    .map_err(|_| InvalidProofReason::PaillierEnc)?;
+                data.key0
+                    .oadd(&z1_at_c, &enc)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            let rhs = {
+                let e_at_d = statement.d.omul(data.key0, challenge)?;
+                data.key0
+                    .oadd(&commitment.a, &e_at_d)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("affine operation under N0"));
+        let check_enc = |z: &Integer,
+                         w: &Integer,
+                         b: &Integer,
+                         ciphertext: &PreparedCiphertext,
+                         i: usize|
+         -> Result<(), InvalidProof> {
+            let lhs = data
+                .key1
+                .encrypt_with(z, w)
+                .map_err(|_| InvalidProofReason::PaillierEnc)?;
+            let rhs = {
+                let e_at_ciphertext = ciphertext.omul(data.key1, challenge)?;
+                data.key1
+                    .oadd(b, &e_at_ciphertext)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(i), lhs, rhs)?;
+            Ok(())
+        };
+        check_enc(&proof.z1, &proof.w_x, &commitment.b_x, &statement.x, 2)?;
+        crate::common::lap!(Check("paillier encryption of x under N1"));
+        check_enc(&proof.z2, &proof.w_y, &commitment.b_y, &statement.y, 3)?;
+        crate::common::lap!(Check("paillier encryption of y under N1"));
+        {
+            let lhs = aux.combine(&proof.z1, &proof.z3)?;
+            let s_to_e = aux.pow_mod(&commitment.s, challenge)?;
+            let rhs = (&commitment.e * s_to_e).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(4), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen for x"));
+        {
+            let lhs = aux.combine(&proof.z2, &proof.z4)?;
+            let t_to_e = aux.pow_mod(&commitment.t, challenge)?;
+            let rhs = (&commitment.f * t_to_e).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(5), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen for y"));
+        fail_if(
+            InvalidProofReason::RangeCheck(6),
+            proof
+                .z1
+                .is_in_pm(&(Integer::ONE << (security.l_x + security.epsilon)).complete()),
+        )?;
+        crate::common::lap!(Check("range of z1"));
+        fail_if(
+            InvalidProofReason::RangeCheck(7),
+            proof
+                .z2
+                .is_in_pm(&(Integer::ONE << (security.l_y + security.epsilon)).complete()),
+        )?;
+        crate::common::lap!(Check("range of z2"));
+        Ok(())
+    }
+
+    /// Generate random challenge
+    pub fn challenge<R>(security: &SecurityParams, rng: &mut R) -> Integer
+    where
+        R: RngCore,
+    {
+        Integer::from_rng_pm(&security.q, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<R: RngCore, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<R: RngCore, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        transcript.label(b"paillier_zk.paillier_affine_operation_with_paillier_commitment");
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb(b"l_x", &(security.l_x as u64).to_be_bytes());
+        transcript.absorb(b"l_y", &(security.l_y as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"N0", data.key0.n());
+        transcript.absorb_value(b"N1", data.key1.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_value(b"D", data.d);
+        transcript.absorb_value(b"Y", data.y);
+        transcript.absorb_value(b"X", data.x);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use rug::Integer;
+
+    use crate::common::extract::{
+        challenge_diff, ensure, linear_response, omul, paillier_nonce, ExtractionErrorReason,
+    };
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub x: Integer,
+        pub y: Integer,
+        pub nonce: Nonce,
+        pub nonce_x: Nonce,
+        pub nonce_y: Nonce,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let mismatch = || ExtractionError::from(ExtractionErrorReason::WitnessMismatch);
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let x = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        let y = linear_response(&proof1.z2, &proof2.z2, &e_diff)?;
+        let nonce_x = paillier_nonce(data.key1.n(), data.x, &proof1.w_x, &proof2.w_x, &e_diff)?;
+        let nonce_y = paillier_nonce(data.key1.n(), data.y, &proof1.w_y, &proof2.w_y, &e_diff)?;
+
+        // D = C^x enc0(y, rho), so D C^(-x) is an encryption of y with nonce rho
+        let x_at_c = omul(data.key0, &x, data.c)?;
+        let y_enc = data.key0.osub(data.d, &x_at_c).map_err(|_| mismatch())?;
+        let nonce = paillier_nonce(data.key0.n(), &y_enc, &proof1.w, &proof2.w, &e_diff)?;
+
+        let enc_y = data.key0.encrypt_with(&y, &nonce).ok();
+        ensure(enc_y == Some(y_enc))?;
+        let enc_y = data.key1.encrypt_with(&y, &nonce_y).ok();
+        ensure(enc_y.as_ref() == Some(data.y))?;
+        let enc_x = data.key1.encrypt_with(&x, &nonce_x).ok();
+        ensure(enc_x.as_ref() == Some(data.x))?;
+        Ok(Witness {
+            x,
+            y,
+            nonce,
+            nonce_x,
+            nonce_y,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fast_paillier::EncryptionKey;
+    use rug::{Complete, Integer};
+
+    use crate::common::test::random_key;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    struct Statement {
+        key0: EncryptionKey,
+        key1: EncryptionKey,
+        c: Integer,
+        d: Integer,
+        x_enc: Integer,
+        y_enc: Integer,
+        x: Integer,
+        y: Integer,
+        nonce: Integer,
+        nonce_x: Integer,
+        nonce_y: Integer,
+    }
+
+    impl Statement {
+        fn data(&self) -> super::Data<'_> {
+            super::Data {
+                key0: &self.key0,
+                key1: &self.key1,
+                c: &self.c,
+                d: &self.d,
+                y: &self.y_enc,
+                x: &self.x_enc,
+            }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_> {
+            super::PrivateData {
+                x: &self.x,
+                y: &self.y,
+                nonce: &self.nonce,
+                nonce_x: &self.nonce_x,
+                nonce_y: &self.nonce_y,
+            }
+        }
+    }
+
+    fn statement<R: rand_core::RngCore + rand_core::CryptoRng>(
+        rng: &mut R,
+        x: Integer,
+        y: Integer,
+    ) -> Statement {
+        let key0 = random_key(rng).unwrap().encryption_key().clone();
+        let key1 = random_key(rng).unwrap().encryption_key().clone();
+        let (c, _) = {
+            let plaintext = Integer::from_rng_pm(key0.half_n(), rng);
+            key0.encrypt_with_random(rng, &plaintext).unwrap()
+        };
+        let (x_enc, nonce_x) = key1.encrypt_with_random(rng, &x).unwrap();
+        let (y_enc, nonce_y) = key1.encrypt_with_random(rng, &y).unwrap();
+        let (y_enc_key0, nonce) = key0.encrypt_with_random(rng, &y).unwrap();
+        // `omul` rejects x = 0
+        let x_at_c = Integer::from(c.pow_mod_ref(&x, key0.nn()).unwrap());
+        let d = key0.oadd(&x_at_c, &y_enc_key0).unwrap();
+        Statement {
+            key0,
+            key1,
+            c,
+            d,
+            x_enc,
+            y_enc,
+            x,
+            y,
+            nonce,
+            nonce_x,
+            nonce_y,
+        }
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(1024, 1024, 300, (Integer::ONE << 128_u32).complete())
+    }
+
+    fn run(x: Integer, y: Integer) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng, x, y);
+        let aux = crate::common::test::aux(&mut rng);
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::non_interactive::verify(
+            shared_state,
+            &aux,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+        run(x, y).expect("proof failed");
+    }
+
+    #[test]
+    fn zero_witness() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+        // x = 0: X encrypts zero, D doesn't depend on C
+        run(Integer::ZERO, y).expect("proof failed");
+        // y = 0: Y encrypts zero
+        run(x, Integer::ZERO).expect("proof failed");
+        run(Integer::ZERO, Integer::ZERO).expect("proof failed");
+    }
+
+    #[test]
+    fn failing_on_additive() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = (Integer::ONE << (security.l_y + security.epsilon)).complete() + 1;
+        let r = run(x, y).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::RangeCheck(7));
+    }
+
+    #[test]
+    fn failing_on_multiplicative() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = (Integer::ONE << (security.l_x + security.epsilon)).complete() + 1;
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+        let r = run(x, y).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::RangeCheck(6));
+    }
+
+    #[test]
+    fn wrong_x() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+        let mut statement = statement(&mut rng, x, y);
+        // X encrypts x + 1, while D is obtained with x
+        statement.x_enc = statement
+            .key1
+            .oadd(
+                &statement.x_enc,
+                &statement
+                    .key1
+                    .encrypt_with(Integer::ONE, Integer::ONE)
+                    .unwrap(),
+            )
+            .unwrap();
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) = super::interactive::commit(
+            &aux,
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(
+            statement.data(),
+            statement.pdata(),
+            &pcommitment,
+            &challenge,
+        )
+        .unwrap();
+        let r = super::interactive::verify(
+            &aux,
+            statement.data(),
+            &commitment,
+            &security,
+            &challenge,
+            &proof,
+        );
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::EqualityCheck(2))
+        );
+    }
+
+    #[test]
+    fn degenerate_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+        let statement = statement(&mut rng, x, y);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Negative challenge is fine
+        let challenge = -super::interactive::challenge(&security, &mut rng).abs() - 1;
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof)
+            .expect("proof with negative challenge failed");
+
+        // Commitment equal to one is rejected
+        let mut degenerate = commitment;
+        degenerate.b_x = Integer::from(1);
+        let r = super::interactive::verify(&aux, data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+        // Zero is a legitimate witness
+        for x in [x, Integer::ZERO] {
+            let statement = statement(&mut rng, x, y.clone());
+            let (data, pdata) = (statement.data(), statement.pdata());
+            let aux = crate::common::test::aux(&mut rng);
+
+            let (_commitment, pcommitment) =
+                super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+            let e1 = super::interactive::challenge(&security, &mut rng);
+            let e2 = super::interactive::challenge(&security, &mut rng);
+            let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+            let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+            let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+            assert_eq!(witness.x, statement.x);
+            assert_eq!(witness.y, statement.y);
+            assert_eq!(witness.nonce, statement.nonce);
+            assert_eq!(witness.nonce_x, statement.nonce_x);
+            assert_eq!(witness.nonce_y, statement.nonce_y);
+
+            assert!(super::extract::extract(data, &e1, &proof1, &e1, &proof1).is_err());
+        }
+    }
+}
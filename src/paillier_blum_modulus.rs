@@ -5,6 +5,42 @@
 //! `gcd(N, phi(N)) = 1`. P wants to prove that those equalities about N hold,
 //! without disclosing p and q.
 //!
+//! Unlike other proofs in this crate, this one has no special-soundness extractor
+//! (available under `research` feature): it shows that N belongs to the language of
+//! Paillier-Blum moduli, not that P knows its factorization.
+//!
+//! ## Distributively generated moduli
+//! Prover needs to know the whole factorization of N: responses contain N-th and 4-th
+//! roots modulo N, which are computed from p and q. When N is generated jointly by
+//! two parties, neither of which knows the factorization, use [`two_party`] module.
+//! Parties holding additive shares of `phi(N)/4` and `N^-1 mod phi(N)` produce a
+//! regular proof in three rounds of interaction.
+//!
+//! ## Number of iterations
+//! Soundness error of the proof is `2^-M`, where `M` is the number of iterations. `M`
+//! is a const generic parameter of the functions in this module. When it has to be
+//! chosen at runtime, e.g. from configuration, use [`dynamic`] module, where the proof
+//! carries its own number of iterations and the verifier sets the minimum it accepts.
+//! [`non_interactive::prove_with_soundness`] and [`non_interactive::verify_with_soundness`]
+//! take the target soundness in bits instead, see [`iterations_for_soundness`].
+//!
+//! ## Non-residue `w`
+//! Prover samples `w` with Jacobi symbol `-1` and sends it as the commitment. When
+//! `w` must be agreed on in advance instead, e.g. derived from the transcript or fixed
+//! by the protocol suite, use [`external_w`] module, where `w` is a part of the
+//! statement.
+//!
+//! ## Re-verification
+//! Deriving the `M` challenge values from the transcript is a noticeable part of
+//! verification. Verifier that expects to verify the same proof again (e.g. in blame
+//! flows or audits) can keep the derived challenge along with the proof:
+//! [`non_interactive::expand_challenge`] derives it and authenticates it with a
+//! verifier's secret key, [`non_interactive::verify_expanded`] checks the
+//! authentication tag instead of deriving the challenge once again. Tag is keyed, so
+//! the challenge can be stored in untrusted storage or received from the prover, but
+//! the key must never be revealed: whoever knows it can pick the challenge, and a
+//! proof for a chosen challenge can be forged.
+//!
 //! ## Example
 //! ```rust
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -59,9 +95,25 @@
 
 use rug::Integer;
 
+use crate::common::encoding::{impl_decode, impl_encode, Decode, Encode, Schema};
+use crate::{InvalidProof, InvalidProofReason};
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+pub mod dynamic;
+pub mod external_w;
+pub mod two_party;
+
+/// Number of iterations `M` that achieves `bits` of soundness
+///
+/// Cheating prover passes each iteration with probability at most `1/2`, so the proof
+/// has soundness error `2^-M`. Use it along with [`non_interactive::prove_with_soundness`]
+/// and [`non_interactive::verify_with_soundness`].
+pub fn iterations_for_soundness(bits: usize) -> usize {
+    bits
+}
+
 /// Public data that both parties know: the Paillier-Blum modulus
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -76,6 +128,43 @@ pub struct PrivateData {
     pub q: Integer,
 }
 
+crate::transcript::assert_private!(PrivateData);
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Validating it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone, Copy)]
+pub struct PreparedStatement<'a> {
+    data: &'a Data,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement, checking that the modulus is composite and odd
+    pub fn new(data: &'a Data) -> Result<Self, InvalidProof> {
+        check_modulus(&data.n)?;
+        Ok(Self { data })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> &'a Data {
+        self.data
+    }
+}
+
+/// Checks that the modulus is composite and odd
+fn check_modulus(n: &Integer) -> Result<(), InvalidProof> {
+    if n.is_probably_prime(25) != rug::integer::IsPrime::No {
+        return Err(InvalidProofReason::ModulusIsPrime.into());
+    }
+    if n.is_even() {
+        return Err(InvalidProofReason::ModulusIsEven.into());
+    }
+    crate::common::lap!(Check("modulus is composite and odd"));
+    Ok(())
+}
+
 /// Prover's first message, obtained by [`interactive::commit`]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -92,6 +181,16 @@ pub struct Challenge<const M: usize> {
     pub ys: [Integer; M],
 }
 
+/// Challenge derived by [`non_interactive::expand_challenge`], authenticated with a
+/// verifier's secret key
+#[derive(Debug, Clone)]
+pub struct ExpandedChallenge<const M: usize> {
+    pub challenge: Challenge<M>,
+    /// Authentication tag of the challenge, binding it to the shared state, statement
+    /// and commitment
+    pub tag: [u8; 32],
+}
+
 /// A part of proof. Having enough of those guarantees security
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -115,6 +214,105 @@ pub struct Proof<const M: usize> {
     pub points: [ProofPoint; M],
 }
 
+impl_encode! { Data { n } }
+impl_encode! { Commitment { w } }
+impl_decode! { Commitment { w } }
+impl_encode! { ProofPoint { x, a, b, z } }
+impl_decode! { ProofPoint { x, a, b, z } }
+
+impl<const M: usize> Encode for Proof<M> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.points[..].encode(out)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.points[..].encoded_len()
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        self.points[..].encode_chunks(sink)
+    }
+}
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn max_serialized_size(modulus_bits: usize) -> usize {
+        Self::schema(modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn schema(modulus_bits: usize) -> Schema {
+        Schema::default().integer("w", modulus_bits)
+    }
+}
+
+impl<const M: usize> Decode for Proof<M> {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let points: Vec<ProofPoint> = Decode::decode(bytes)?;
+        Some(Self {
+            points: points.try_into().ok()?,
+        })
+    }
+}
+
+impl<const M: usize> Encode for ExpandedChallenge<M> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.challenge.ys[..].encode(out);
+        out.extend_from_slice(&self.tag);
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.challenge.ys[..].encoded_len() + self.tag.len()
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        self.challenge.ys[..].encode_chunks(sink);
+        sink(&self.tag);
+    }
+}
+
+impl<const M: usize> Decode for ExpandedChallenge<M> {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let ys: Vec<Integer> = Decode::decode(bytes)?;
+        if bytes.len() < 32 {
+            return None;
+        }
+        let (tag, rest) = bytes.split_at(32);
+        *bytes = rest;
+        Some(Self {
+            challenge: Challenge {
+                ys: ys.try_into().ok()?,
+            },
+            tag: tag.try_into().ok()?,
+        })
+    }
+}
+
+impl<const M: usize> Proof<M> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn max_serialized_size(modulus_bits: usize) -> usize {
+        Self::schema(modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn schema(modulus_bits: usize) -> Schema {
+        let point = Schema::default()
+            .integer("x", modulus_bits)
+            .bool("a")
+            .bool("b")
+            .integer("z", modulus_bits);
+        Schema::default().sequence("points", M, point)
+    }
+}
+
 /// The interactive version of the ZK proof. Should be completed in 3 rounds:
 /// prover commits to data, verifier responds with a random challenge, and
 /// prover gives proof with commitment and challenge.
@@ -125,7 +323,7 @@ pub mod interactive {
     use crate::common::sqrt::{blum_sqrt, find_residue, sample_neg_jacobi};
     use crate::{BadExponent, Error, ErrorReason, InvalidProof, InvalidProofReason};
 
-    use super::{Challenge, Commitment, Data, PrivateData, Proof, ProofPoint};
+    use super::{Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, ProofPoint};
 
     /// Create random commitment
     pub fn commit<R: RngCore>(Data { ref n }: &Data, rng: &mut R) -> Commitment {
@@ -136,18 +334,29 @@ pub mod interactive {
 
     /// Compute proof for given data and prior protocol values
     pub fn prove<const M: usize>(
-        Data { ref n }: &Data,
-        PrivateData { ref p, ref q }: &PrivateData,
-        Commitment { ref w }: &Commitment,
+        data: &Data,
+        pdata: &PrivateData,
+        commitment: &Commitment,
         challenge: &Challenge<M>,
     ) -> Result<Proof<M>, Error> {
+        let points = prove_points(&data.n, pdata, &commitment.w, &challenge.ys)?
+            .try_into()
+            .map_err(|_| ErrorReason::Length)?;
+        Ok(Proof { points })
+    }
+
+    /// Computes proof points for the challenge values `ys` and non-residue `w`
+    pub(super) fn prove_points(
+        n: &Integer,
+        PrivateData { ref p, ref q }: &PrivateData,
+        w: &Integer,
+        ys: &[Integer],
+    ) -> Result<Vec<ProofPoint>, Error> {
         let blum_sqrt = |x| blum_sqrt(&x, p, q, n);
         let phi = (p - 1u8).complete() * (q - 1u8).complete();
         let n_inverse = n.invert_ref(&phi).ok_or(ErrorReason::Invert)?.into();
 
-        // We do an extra allocation as workaround while `array::try_map` is not stable
-        let points = challenge
-            .ys
+        let points = ys
             .iter()
             .map(|y| {
                 let z = y
@@ -158,10 +367,8 @@ pub mod interactive {
                 let x = blum_sqrt(blum_sqrt(y_));
                 Ok(ProofPoint { x, a, b, z })
             })
-            .collect::<Result<Vec<_>, ErrorReason>>()?
-            .try_into()
-            .map_err(|_| ErrorReason::Length)?;
-        Ok(Proof { points })
+            .collect::<Result<Vec<_>, ErrorReason>>()?;
+        Ok(points)
     }
 
     /// Verify the proof. If this succeeds, the relation Rmod holds with chance
@@ -172,39 +379,77 @@ pub mod interactive {
         challenge: &Challenge<M>,
         proof: &Proof<M>,
     ) -> Result<(), InvalidProof> {
-        if data.n.is_probably_prime(25) != rug::integer::IsPrime::No {
-            return Err(InvalidProofReason::ModulusIsPrime.into());
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(&data.n))?;
+        verify_prepared(&statement, commitment, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared<const M: usize>(
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        challenge: &Challenge<M>,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(statement, commitment, challenge, proof)
+            .map_err(|err| err.with_key(&statement.data.n))
+    }
+
+    fn check_prepared<const M: usize>(
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        challenge: &Challenge<M>,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        check_points(
+            &statement.data.n,
+            &commitment.w,
+            &challenge.ys,
+            &proof.points,
+        )
+    }
+
+    /// Checks proof points against the challenge values `ys` and non-residue `w`
+    ///
+    /// `n` must be validated in advance, see [`PreparedStatement`]
+    pub(super) fn check_points(
+        n: &Integer,
+        w: &Integer,
+        ys: &[Integer],
+        points: &[ProofPoint],
+    ) -> Result<(), InvalidProof> {
+        if ys.len() != points.len() {
+            return Err(InvalidProofReason::LengthMismatch.into());
+        }
+        if w.jacobi(n) != -1 {
+            return Err(InvalidProofReason::DegenerateCommitment.into());
         }
-        if data.n.is_even() {
-            return Err(InvalidProofReason::ModulusIsEven.into());
+        if ys.iter().any(|y| y.is_divisible(n)) {
+            return Err(InvalidProofReason::ZeroChallenge.into());
         }
-        for (point, y) in proof.points.iter().zip(challenge.ys.iter()) {
+        for (point, y) in points.iter().zip(ys) {
             if Integer::from(
                 point
                     .z
-                    .pow_mod_ref(&data.n, &data.n)
+                    .pow_mod_ref(n, n)
                     .ok_or(InvalidProofReason::ModPow)?,
             ) != *y
             {
                 return Err(InvalidProofReason::IncorrectNthRoot.into());
             }
             let y = y.clone();
-            let y = if point.a { &data.n - y } else { y };
-            let y = if point.b {
-                (y * &commitment.w).modulo(&data.n)
-            } else {
-                y
-            };
+            let y = if point.a { n - y } else { y };
+            let y = if point.b { (y * w).modulo(n) } else { y };
             if Integer::from(
                 point
                     .x
-                    .pow_mod_ref(&4.into(), &data.n)
+                    .pow_mod_ref(&4.into(), n)
                     .ok_or(InvalidProofReason::ModPow)?,
             ) != y
             {
                 return Err(InvalidProofReason::IncorrectFourthRoot.into());
             }
         }
+        crate::common::lap!(Check("nth and 4th roots"));
         Ok(())
     }
 
@@ -226,12 +471,16 @@ pub mod interactive {
 /// The non-interactive version of proof. Completed in one round, for example
 /// see the documentation of parent module.
 pub mod non_interactive {
-    use digest::{typenum::U32, Digest};
+    use digest::Digest;
     use rand_core::RngCore;
+    use rug::Integer;
 
-    use crate::{Error, InvalidProof};
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof, InvalidProofReason};
 
-    use super::{Challenge, Commitment, Data, PrivateData, Proof};
+    use super::{
+        Challenge, Commitment, Data, ExpandedChallenge, PreparedStatement, PrivateData, Proof,
+    };
 
     /// Compute proof for the given data, producing random commitment and
     /// deriving determenistic challenge.
@@ -244,14 +493,67 @@ pub mod non_interactive {
         rng: &mut R,
     ) -> Result<(Commitment, Proof<M>), Error>
     where
-        D: Digest<OutputSize = U32> + Clone,
+        D: Digest + Clone,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<const M: usize, R: RngCore, D>(
+        transcript: &mut Transcript<D>,
+        data: &Data,
+        pdata: &PrivateData,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof<M>), Error>
+    where
+        D: Digest + Clone,
     {
         let commitment = super::interactive::commit(data, rng);
-        let challenge = challenge(shared_state, data, &commitment);
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, data, &commitment);
+        crate::common::lap!(Challenge);
         let proof = super::interactive::prove(data, pdata, &commitment, &challenge)?;
+        crate::common::lap!(Respond);
         Ok((commitment, proof))
     }
 
+    /// Compute proof with soundness error at most `2^-bits`
+    ///
+    /// Number of iterations is derived by [`iterations_for_soundness`](super::iterations_for_soundness),
+    /// the proof carries it, see [`dynamic`](super::dynamic)
+    pub fn prove_with_soundness<R: RngCore, D>(
+        shared_state: D,
+        data: &Data,
+        pdata: &PrivateData,
+        bits: usize,
+        rng: &mut R,
+    ) -> Result<(Commitment, super::dynamic::Proof), Error>
+    where
+        D: Digest,
+    {
+        let m = super::iterations_for_soundness(bits);
+        super::dynamic::non_interactive::prove(shared_state, data, pdata, m, rng)
+    }
+
+    /// Verify the proof, requiring soundness error at most `2^-bits`
+    pub fn verify_with_soundness<D>(
+        shared_state: D,
+        data: &Data,
+        commitment: &Commitment,
+        proof: &super::dynamic::Proof,
+        bits: usize,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let min_m = super::iterations_for_soundness(bits);
+        super::dynamic::non_interactive::verify(shared_state, data, commitment, proof, min_m)
+    }
+
     /// Verify the proof, deriving challenge independently from same data
     pub fn verify<const M: usize, D>(
         shared_state: D,
@@ -260,45 +562,188 @@ pub mod non_interactive {
         proof: &Proof<M>,
     ) -> Result<(), InvalidProof>
     where
-        D: Digest<OutputSize = U32> + Clone,
+        D: Digest + Clone,
     {
-        let challenge = challenge(shared_state, data, commitment);
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, commitment, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<const M: usize, D>(
+        transcript: &mut Transcript<D>,
+        data: &Data,
+        commitment: &Commitment,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest + Clone,
+    {
+        let challenge = challenge_with_transcript(transcript, data, commitment);
+        crate::common::lap!(Challenge);
         super::interactive::verify(data, commitment, &challenge, proof)
     }
 
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<const M: usize, D>(
+        shared_state: D,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest + Clone,
+    {
+        let challenge = challenge(shared_state, statement.data(), commitment);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(statement, commitment, &challenge, proof)
+    }
+
+    /// Derives challenge like [`challenge`] does, and authenticates it with the
+    /// verifier's secret `key`
+    ///
+    /// The challenge can be stored along with the proof and later passed to
+    /// [`verify_expanded`]. `key` must be kept secret, see [module level
+    /// docs](super#re-verification).
+    pub fn expand_challenge<const M: usize, D>(
+        key: &[u8],
+        shared_state: D,
+        data: &Data,
+        commitment: &Commitment,
+    ) -> ExpandedChallenge<M>
+    where
+        D: Digest + Clone,
+    {
+        let challenge = challenge(shared_state.clone(), data, commitment);
+        let tag = challenge_tag(key, shared_state, data, commitment, &challenge);
+        ExpandedChallenge { challenge, tag }
+    }
+
+    /// Verify the proof with the challenge obtained from [`expand_challenge`]
+    ///
+    /// Instead of deriving the challenge, checks that it was derived for the same
+    /// shared state, statement and commitment, and authenticated with the same `key`.
+    pub fn verify_expanded<const M: usize, D>(
+        key: &[u8],
+        shared_state: D,
+        data: &Data,
+        commitment: &Commitment,
+        expanded: &ExpandedChallenge<M>,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let tag = challenge_tag(key, shared_state, data, commitment, &expanded.challenge);
+        // Constant time, so the tag can't be guessed byte by byte
+        let diff = tag
+            .iter()
+            .zip(&expanded.tag)
+            .fold(0, |acc, (a, b)| acc | (a ^ b));
+        if diff != 0 {
+            return Err(InvalidProof::from(InvalidProofReason::ChallengeTag).with_key(&data.n));
+        }
+        crate::common::lap!(Challenge);
+        super::interactive::verify(data, commitment, &expanded.challenge, proof)
+    }
+
+    fn challenge_tag<const M: usize, D: Digest>(
+        key: &[u8],
+        shared_state: D,
+        Data { ref n }: &Data,
+        commitment: &Commitment,
+        challenge: &Challenge<M>,
+    ) -> [u8; 32] {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        transcript.label(b"paillier_zk.paillier_blum_modulus.expanded_challenge");
+        transcript.absorb(b"key", key);
+        transcript.absorb(b"M", &(M as u64).to_be_bytes());
+        transcript.absorb_value(b"N", n);
+        transcript.absorb_value(b"commitment", commitment);
+        transcript.absorb_value(b"challenge", &challenge.ys[..]);
+        let mut tag = [0; 32];
+        transcript.challenge_rng(b"tag").fill_bytes(&mut tag);
+        tag
+    }
+
     /// Deterministically compute challenge based on prior known values in protocol
     pub fn challenge<const M: usize, D>(
         shared_state: D,
+        data: &Data,
+        commitment: &Commitment,
+    ) -> Challenge<M>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data, commitment)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<const M: usize, D>(
+        shared_state: D,
+        data: &Data,
+        commitment: &Commitment,
+    ) -> Vec<u8>
+    where
+        D: Digest,
+    {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript::<M, _>(transcript, data, commitment);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<const M: usize, D>(
+        transcript: &mut Transcript<D>,
         Data { ref n }: &Data,
         commitment: &Commitment,
     ) -> Challenge<M>
     where
         D: Digest,
     {
-        let shared_state = shared_state.finalize();
-        let hash = |d: D| {
-            let order = rug::integer::Order::Msf;
-            d.chain_update(&shared_state)
-                .chain_update(n.to_digits::<u8>(order))
-                .chain_update(commitment.w.to_digits::<u8>(order))
-                .finalize()
-        };
-        let mut rng = crate::common::rng::HashRng::new(hash);
+        let mut ys = derive_ys(transcript, n, &commitment.w, M).into_iter();
         // since we can't use Default and Integer isn't copy, we initialize
         // like this
-        let ys = [(); M].map(|()| {
-            n.random_below_ref(&mut fast_paillier::utils::external_rand(&mut rng))
-                .into()
-        });
+        let ys = [(); M].map(|()| ys.next().unwrap_or_default());
         Challenge { ys }
     }
+
+    /// Derives `m` challenge values from the transcript
+    ///
+    /// `w` is absorbed the same as the commitment that consists of it
+    pub(super) fn derive_ys<D: Digest>(
+        transcript: &mut Transcript<D>,
+        n: &Integer,
+        w: &Integer,
+        m: usize,
+    ) -> Vec<Integer> {
+        transcript.label(b"paillier_zk.paillier_blum_modulus");
+        transcript.absorb(b"M", &(m as u64).to_be_bytes());
+        transcript.absorb_value(b"N", n);
+        transcript.absorb_value(b"commitment", w);
+        let mut rng = transcript.challenge_rng(b"y");
+        (0..m)
+            .map(|_| {
+                n.random_below_ref(&mut fast_paillier::utils::external_rand(&mut rng))
+                    .into()
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use rug::Complete;
+    use rug::{Complete, Integer};
 
     use crate::common::test::{generate_blum_prime, generate_prime};
+    use crate::common::InvalidProofReason;
 
     #[test]
     fn passing() {
@@ -323,6 +768,119 @@ mod test {
         }
     }
 
+    #[test]
+    fn soundness_bits() {
+        let mut rng = rand_dev::DevRng::new();
+        let p = generate_blum_prime(&mut rng, 256);
+        let q = generate_blum_prime(&mut rng, 256);
+        let n = (&p * &q).complete();
+        let data = super::Data { n };
+        let pdata = super::PrivateData { p, q };
+        let shared_state = sha2::Sha256::default();
+
+        assert_eq!(super::iterations_for_soundness(80), 80);
+        let (commitment, proof) = super::non_interactive::prove_with_soundness(
+            shared_state.clone(),
+            &data,
+            &pdata,
+            80,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(proof.iterations(), 80);
+        super::non_interactive::verify_with_soundness(
+            shared_state.clone(),
+            &data,
+            &commitment,
+            &proof,
+            80,
+        )
+        .expect("proof failed");
+        let r = super::non_interactive::verify_with_soundness(
+            shared_state,
+            &data,
+            &commitment,
+            &proof,
+            128,
+        );
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::TooFewIterations(80))
+        );
+    }
+
+    #[test]
+    fn expanded_challenge() {
+        use crate::common::encoding::{Decode, Encode};
+
+        let mut rng = rand_dev::DevRng::new();
+        let p = generate_blum_prime(&mut rng, 256);
+        let q = generate_blum_prime(&mut rng, 256);
+        let n = (&p * &q).complete();
+        let data = super::Data { n };
+        let pdata = super::PrivateData { p, q };
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove::<65, _, _>(
+            shared_state.clone(),
+            &data,
+            &pdata,
+            &mut rng,
+        )
+        .unwrap();
+
+        let key = b"verifier's secret key";
+        let expanded = super::non_interactive::expand_challenge::<65, _>(
+            key,
+            shared_state.clone(),
+            &data,
+            &commitment,
+        );
+        assert_eq!(
+            expanded.challenge,
+            super::non_interactive::challenge(shared_state.clone(), &data, &commitment)
+        );
+        let expanded =
+            super::ExpandedChallenge::<65>::from_canonical_bytes(&expanded.to_canonical_bytes())
+                .unwrap();
+        super::non_interactive::verify_expanded(
+            key,
+            shared_state.clone(),
+            &data,
+            &commitment,
+            &expanded,
+            &proof,
+        )
+        .expect("proof failed");
+
+        // Challenge authenticated with other key, or tampered, is rejected
+        let r = super::non_interactive::verify_expanded(
+            b"other key",
+            shared_state.clone(),
+            &data,
+            &commitment,
+            &expanded,
+            &proof,
+        );
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ChallengeTag)
+        );
+        let mut tampered = expanded.clone();
+        tampered.challenge.ys[0] += 1;
+        let r = super::non_interactive::verify_expanded(
+            key,
+            shared_state,
+            &data,
+            &commitment,
+            &tampered,
+            &proof,
+        );
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ChallengeTag)
+        );
+    }
+
     #[test]
     fn failing() {
         let mut rng = rand_dev::DevRng::new();
@@ -350,4 +908,45 @@ mod test {
             panic!("proof should not pass");
         }
     }
+
+    #[test]
+    fn degenerate_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let p = generate_blum_prime(&mut rng, 256);
+        let q = generate_blum_prime(&mut rng, 256);
+        let n = (&p * &q).complete();
+        let data = super::Data { n };
+        let pdata = super::PrivateData { p, q };
+
+        let commitment = super::interactive::commit(&data, &mut rng);
+        let challenge = super::interactive::challenge::<16, _>(&data, &mut rng);
+        let proof = super::interactive::prove(&data, &pdata, &commitment, &challenge).unwrap();
+        super::interactive::verify(&data, &commitment, &challenge, &proof).unwrap();
+
+        // Zero challenge is answered with zero roots, making the checks vacuous
+        let mut zero_challenge = challenge.clone();
+        let mut zero_proof = proof.clone();
+        zero_challenge.ys[0] = Integer::new();
+        zero_proof.points[0] = super::ProofPoint {
+            x: Integer::new(),
+            a: false,
+            b: false,
+            z: Integer::new(),
+        };
+        let r = super::interactive::verify(&data, &commitment, &zero_challenge, &zero_proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // `w` must have Jacobi symbol -1
+        let degenerate = super::Commitment {
+            w: Integer::from(1),
+        };
+        let r = super::interactive::verify(&data, &degenerate, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
 }
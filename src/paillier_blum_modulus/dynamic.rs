@@ -0,0 +1,389 @@
+//! Пmod with the number of iterations chosen at runtime
+//!
+//! Same proof as [the parent module](super), but `M` is not a const generic parameter.
+//! Prover chooses the number of iterations at prove time, and the proof carries it as
+//! the number of its points. Verifier accepts the proof if it has at least `min_m`
+//! iterations, so soundness error is at most `2^-min_m`. Verification time grows
+//! linearly with the number of iterations.
+//!
+//! Proof with `m` iterations is encoded and verified the same as [`Proof<M>`](super::Proof)
+//! with `M = m`, so proofs can be exchanged with parties that use the const generic
+//! version.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::paillier_blum_modulus::{self as p, dynamic};
+//! # fn doc_fn(data: &p::Data, pdata: &p::PrivateData) -> Result<(), Box<dyn std::error::Error>> {
+//! # let mut rng = rand_core::OsRng;
+//! // Read from configuration
+//! let iterations = 80;
+//! let min_iterations = 80;
+//!
+//! let (commitment, proof) = dynamic::non_interactive::prove(
+//!     sha2::Sha256::default(),
+//!     data,
+//!     pdata,
+//!     iterations,
+//!     &mut rng,
+//! )?;
+//! dynamic::non_interactive::verify(
+//!     sha2::Sha256::default(),
+//!     data,
+//!     &commitment,
+//!     &proof,
+//!     min_iterations,
+//! )?;
+//! # Ok(()) }
+//! ```
+
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::ProofPoint;
+
+/// Verifier's challenge to prover, consists of one value per iteration
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Challenge {
+    pub ys: Vec<Integer>,
+}
+
+/// The ZK proof, consists of one point per iteration
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub points: Vec<ProofPoint>,
+}
+
+impl Proof {
+    /// Number of iterations of the proof
+    pub fn iterations(&self) -> usize {
+        self.points.len()
+    }
+}
+
+impl_encode! { Proof { points } }
+impl_decode! { Proof { points } }
+
+impl<const M: usize> From<super::Proof<M>> for Proof {
+    fn from(proof: super::Proof<M>) -> Self {
+        Self {
+            points: proof.points.into(),
+        }
+    }
+}
+
+/// The interactive version of the ZK proof
+pub mod interactive {
+    use rand_core::RngCore;
+
+    use crate::common::fail_if;
+    use crate::{Error, InvalidProof, InvalidProofReason};
+
+    use super::super::interactive::{check_points, prove_points};
+    use super::super::{Commitment, Data, PreparedStatement, PrivateData};
+    use super::{Challenge, Proof};
+
+    pub use super::super::interactive::commit;
+
+    /// Compute proof for given data and prior protocol values
+    ///
+    /// Proof has as many iterations as the challenge
+    pub fn prove(
+        data: &Data,
+        pdata: &PrivateData,
+        commitment: &Commitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        let points = prove_points(&data.n, pdata, &commitment.w, &challenge.ys)?;
+        Ok(Proof { points })
+    }
+
+    /// Verify the proof. If this succeeds, the relation Rmod holds with chance
+    /// `1/2^min_m`
+    ///
+    /// Fails if the proof has less than `min_m` iterations
+    pub fn verify(
+        data: &Data,
+        commitment: &Commitment,
+        challenge: &Challenge,
+        proof: &Proof,
+        min_m: usize,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(&data.n))?;
+        verify_prepared(&statement, commitment, challenge, proof, min_m)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        challenge: &Challenge,
+        proof: &Proof,
+        min_m: usize,
+    ) -> Result<(), InvalidProof> {
+        let check = || -> Result<(), InvalidProof> {
+            fail_if(
+                InvalidProofReason::TooFewIterations(proof.iterations()),
+                proof.iterations() >= min_m,
+            )?;
+            check_points(
+                &statement.data().n,
+                &commitment.w,
+                &challenge.ys,
+                &proof.points,
+            )
+        };
+        check().map_err(|err| err.with_key(&statement.data().n))
+    }
+
+    /// Generate random challenge with `m` iterations
+    pub fn challenge<R: RngCore>(Data { ref n }: &Data, m: usize, rng: &mut R) -> Challenge {
+        let ys = (0..m)
+            .map(|_| {
+                n.random_below_ref(&mut fast_paillier::utils::external_rand(rng))
+                    .into()
+            })
+            .collect();
+        Challenge { ys }
+    }
+}
+
+/// The non-interactive version of proof
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::super::non_interactive::derive_ys;
+    use super::super::{Commitment, Data, PreparedStatement, PrivateData};
+    use super::{Challenge, Proof};
+
+    /// Compute proof with `m` iterations for the given data, producing random
+    /// commitment and deriving determenistic challenge.
+    pub fn prove<R: RngCore, D>(
+        shared_state: D,
+        data: &Data,
+        pdata: &PrivateData,
+        m: usize,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, m, rng)
+    }
+
+    /// Compute proof with `m` iterations for the given data within the protocol
+    /// transcript
+    pub fn prove_with_transcript<R: RngCore, D>(
+        transcript: &mut Transcript<D>,
+        data: &Data,
+        pdata: &PrivateData,
+        m: usize,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let commitment = super::interactive::commit(data, rng);
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, data, &commitment, m);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &commitment, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((commitment, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    ///
+    /// Number of iterations is taken from the proof, which must have at least `min_m`
+    /// of them
+    pub fn verify<D>(
+        shared_state: D,
+        data: &Data,
+        commitment: &Commitment,
+        proof: &Proof,
+        min_m: usize,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, commitment, proof, min_m)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        data: &Data,
+        commitment: &Commitment,
+        proof: &Proof,
+        min_m: usize,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(&data.n))?;
+        verify_prepared_with_transcript(transcript, &statement, commitment, proof, min_m)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D>(
+        shared_state: D,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        proof: &Proof,
+        min_m: usize,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_prepared_with_transcript(&mut transcript, statement, commitment, proof, min_m)
+    }
+
+    fn verify_prepared_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        proof: &Proof,
+        min_m: usize,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        // Checked before deriving the challenge, which takes time proportional to
+        // the number of iterations
+        if proof.iterations() < min_m {
+            return Err(
+                InvalidProof::from(crate::InvalidProofReason::TooFewIterations(
+                    proof.iterations(),
+                ))
+                .with_key(&statement.data().n),
+            );
+        }
+        let challenge =
+            challenge_with_transcript(transcript, statement.data(), commitment, proof.iterations());
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(statement, commitment, &challenge, proof, min_m)
+    }
+
+    /// Deterministically compute challenge with `m` iterations based on prior known
+    /// values in protocol
+    pub fn challenge<D>(
+        shared_state: D,
+        data: &Data,
+        commitment: &Commitment,
+        m: usize,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data, commitment, m)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D>(
+        shared_state: D,
+        data: &Data,
+        commitment: &Commitment,
+        m: usize,
+    ) -> Vec<u8>
+    where
+        D: Digest,
+    {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, data, commitment, m);
+        })
+    }
+
+    /// Deterministically compute challenge with `m` iterations from the protocol
+    /// transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        Data { ref n }: &Data,
+        commitment: &Commitment,
+        m: usize,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        Challenge {
+            ys: derive_ys(transcript, n, &commitment.w, m),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::Complete;
+
+    use crate::common::encoding::{Decode, Encode};
+    use crate::common::test::generate_blum_prime;
+    use crate::common::InvalidProofReason;
+
+    use super::non_interactive;
+
+    #[test]
+    fn runtime_iterations() {
+        let mut rng = rand_dev::DevRng::new();
+        let p = generate_blum_prime(&mut rng, 256);
+        let q = generate_blum_prime(&mut rng, 256);
+        let n = (&p * &q).complete();
+        let data = super::super::Data { n };
+        let pdata = super::super::PrivateData { p, q };
+        let shared_state = sha2::Sha256::default();
+
+        let (commitment, proof) =
+            non_interactive::prove(shared_state.clone(), &data, &pdata, 40, &mut rng).unwrap();
+        assert_eq!(proof.iterations(), 40);
+        non_interactive::verify(shared_state.clone(), &data, &commitment, &proof, 40)
+            .expect("proof failed");
+        non_interactive::verify(shared_state.clone(), &data, &commitment, &proof, 16)
+            .expect("proof failed");
+
+        // Verifier requires more iterations than the proof has
+        let r = non_interactive::verify(shared_state.clone(), &data, &commitment, &proof, 41);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::TooFewIterations(40))
+        );
+
+        // Dropping iterations changes the challenge
+        let mut truncated = proof.clone();
+        truncated.points.truncate(20);
+        non_interactive::verify(shared_state.clone(), &data, &commitment, &truncated, 16)
+            .expect_err("truncated proof should not pass");
+
+        // Interoperates with the const generic version
+        let bytes = proof.to_canonical_bytes();
+        let proof_m = super::super::Proof::<40>::from_canonical_bytes(&bytes).unwrap();
+        super::super::non_interactive::verify(shared_state.clone(), &data, &commitment, &proof_m)
+            .expect("proof failed");
+        let (commitment, proof_m) = super::super::non_interactive::prove::<24, _, _>(
+            shared_state.clone(),
+            &data,
+            &pdata,
+            &mut rng,
+        )
+        .unwrap();
+        let proof = super::Proof::from(proof_m);
+        non_interactive::verify(shared_state, &data, &commitment, &proof, 24)
+            .expect("proof failed");
+    }
+}
@@ -0,0 +1,304 @@
+//! Пmod over quadratic non-residue `w` which is a part of the statement
+//!
+//! In [the parent module](super), `w` with Jacobi symbol `-1` is sampled by the prover
+//! and sent as the commitment. Here `w` is a part of [`Data`] instead, agreed on by
+//! prover and verifier before the proof. By default it's derived deterministically
+//! from the shared state with [`Data::derive`], so neither party chooses it. Protocol
+//! suites that fix `w` differently, e.g. derive it from their own transcript or take
+//! it from a standard, provide it with [`Data::with_w`].
+//!
+//! Either way, `w` must have Jacobi symbol `-1` modulo `N`: the proof is rejected
+//! with [`WrongJacobiSymbol`](crate::InvalidProofReason::WrongJacobiSymbol) otherwise.
+//!
+//! The proof doesn't have a commitment: prover needs no randomness, and the proof
+//! for the same statement and shared state is always the same. The challenge is
+//! derived exactly like in [the parent module](super) with `Commitment { w }`, so
+//! a proof here is also a valid proof of the parent module with such commitment.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::paillier_blum_modulus::{self as p, external_w};
+//! # fn doc_fn(n: rug::Integer, pdata: &p::PrivateData) -> Result<(), Box<dyn std::error::Error>> {
+//! let shared_state = sha2::Sha256::default();
+//! let data = external_w::Data::derive(shared_state.clone(), n)?;
+//!
+//! let proof: p::Proof<128> =
+//!     external_w::non_interactive::prove(shared_state.clone(), &data, pdata)?;
+//! external_w::non_interactive::verify(shared_state, &data, &proof)?;
+//! # Ok(()) }
+//! ```
+
+use digest::Digest;
+use rug::Integer;
+
+use crate::common::encoding::impl_encode;
+use crate::transcript::Transcript;
+use crate::{InvalidProof, InvalidProofReason};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use super::{Challenge, PrivateData, Proof};
+
+/// Maximum amount of candidates tried by [`Data::derive`]
+///
+/// Half of residues modulo a Paillier-Blum modulus have Jacobi symbol `-1`, so
+/// derivation from a valid modulus fails with probability `2^-256`. The limit makes
+/// derivation terminate for moduli where no such residue exists, e.g. squares.
+pub const MAX_DERIVATION_ATTEMPTS: usize = 256;
+
+/// Public data that both parties know: the Paillier-Blum modulus and the non-residue
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Data {
+    /// N in paper
+    pub n: Integer,
+    /// w in paper, must have Jacobi symbol `-1` modulo `n`
+    pub w: Integer,
+}
+
+impl_encode! { Data { n, w } }
+
+impl Data {
+    /// Statement with `w` derived from the shared state
+    ///
+    /// Prover and verifier derive the same `w` from the same shared state. Fails if
+    /// `n` is not composite and odd, or if no `w` is found, which doesn't happen for
+    /// a valid modulus, see [`MAX_DERIVATION_ATTEMPTS`].
+    pub fn derive<D: Digest>(shared_state: D, n: Integer) -> Result<Self, InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        Self::derive_with_transcript(&mut transcript, n)
+    }
+
+    /// Statement with `w` derived from the protocol transcript
+    pub fn derive_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        n: Integer,
+    ) -> Result<Self, InvalidProof> {
+        super::check_modulus(&n).map_err(|err| err.with_key(&n))?;
+        transcript.label(b"paillier_zk.paillier_blum_modulus.w");
+        transcript.absorb_value(b"N", &n);
+        let mut rng = transcript.challenge_rng(b"w");
+        for _ in 0..MAX_DERIVATION_ATTEMPTS {
+            let w: Integer = n
+                .random_below_ref(&mut fast_paillier::utils::external_rand(&mut rng))
+                .into();
+            if w.jacobi(&n) == -1 {
+                return Ok(Self { n, w });
+            }
+        }
+        Err(InvalidProof::from(InvalidProofReason::WrongJacobiSymbol).with_key(&n))
+    }
+
+    /// Statement with `w` fixed by the protocol
+    ///
+    /// Returns error if `w` doesn't have Jacobi symbol `-1` modulo `n`
+    pub fn with_w(n: Integer, w: Integer) -> Result<Self, InvalidProof> {
+        let data = Self { n, w };
+        data.validate()?;
+        Ok(data)
+    }
+
+    /// Checks that `n` is composite and odd, and `w` has Jacobi symbol `-1` modulo it
+    pub fn validate(&self) -> Result<(), InvalidProof> {
+        let check = || {
+            super::check_modulus(&self.n)?;
+            if self.w.jacobi(&self.n) != -1 {
+                return Err(InvalidProofReason::WrongJacobiSymbol.into());
+            }
+            Ok(())
+        };
+        check().map_err(|err: InvalidProof| err.with_key(&self.n))
+    }
+}
+
+/// The interactive version of the ZK proof. Completed in 2 rounds: verifier sends
+/// a random challenge, and prover responds with the proof.
+pub mod interactive {
+    use rand_core::RngCore;
+
+    use crate::{Error, ErrorReason, InvalidProof};
+
+    use super::super::interactive::{check_points, prove_points};
+    use super::{Challenge, Data, PrivateData, Proof};
+
+    /// Compute proof for the challenge
+    pub fn prove<const M: usize>(
+        data: &Data,
+        pdata: &PrivateData,
+        challenge: &Challenge<M>,
+    ) -> Result<Proof<M>, Error> {
+        let points = prove_points(&data.n, pdata, &data.w, &challenge.ys)?
+            .try_into()
+            .map_err(|_| ErrorReason::Length)?;
+        Ok(Proof { points })
+    }
+
+    /// Verify the proof. If this succeeds, the relation Rmod holds with chance
+    /// `1/2^M`
+    pub fn verify<const M: usize>(
+        data: &Data,
+        challenge: &Challenge<M>,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        data.validate()?;
+        check_points(&data.n, &data.w, &challenge.ys, &proof.points)
+            .map_err(|err| err.with_key(&data.n))
+    }
+
+    /// Generate random challenge
+    pub fn challenge<const M: usize, R: RngCore>(data: &Data, rng: &mut R) -> Challenge<M> {
+        let ys = [(); M].map(|()| {
+            data.n
+                .random_below_ref(&mut fast_paillier::utils::external_rand(rng))
+                .into()
+        });
+        Challenge { ys }
+    }
+}
+
+/// The non-interactive version of proof
+pub mod non_interactive {
+    use digest::Digest;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::super::interactive::check_points;
+    use super::super::non_interactive::derive_ys;
+    use super::{Challenge, Data, PrivateData, Proof};
+
+    /// Compute proof for the given data, deriving determenistic challenge
+    pub fn prove<const M: usize, D: Digest>(
+        shared_state: D,
+        data: &Data,
+        pdata: &PrivateData,
+    ) -> Result<Proof<M>, Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    pub fn prove_with_transcript<const M: usize, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: &Data,
+        pdata: &PrivateData,
+    ) -> Result<Proof<M>, Error> {
+        let challenge = challenge_with_transcript(transcript, data);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok(proof)
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<const M: usize, D: Digest>(
+        shared_state: D,
+        data: &Data,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<const M: usize, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: &Data,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        data.validate()?;
+        let challenge = challenge_with_transcript::<M, _>(transcript, data);
+        crate::common::lap!(Challenge);
+        check_points(&data.n, &data.w, &challenge.ys, &proof.points)
+            .map_err(|err| err.with_key(&data.n))
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<const M: usize, D: Digest>(shared_state: D, data: &Data) -> Challenge<M> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<const M: usize, D: Digest>(
+        shared_state: D,
+        data: &Data,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript::<M, _>(transcript, data);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs the statement into the `transcript`
+    pub fn challenge_with_transcript<const M: usize, D: Digest>(
+        transcript: &mut Transcript<D>,
+        Data { n, w }: &Data,
+    ) -> Challenge<M> {
+        let mut ys = derive_ys(transcript, n, w, M).into_iter();
+        let ys = [(); M].map(|()| ys.next().unwrap_or_default());
+        Challenge { ys }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::test::generate_blum_prime;
+    use crate::common::InvalidProofReason;
+
+    use super::{non_interactive, Data};
+
+    #[test]
+    fn derived_and_fixed_w() {
+        let mut rng = rand_dev::DevRng::new();
+        let p = generate_blum_prime(&mut rng, 256);
+        let q = generate_blum_prime(&mut rng, 256);
+        let n = (&p * &q).complete();
+        let pdata = super::PrivateData { p, q };
+        let shared_state = sha2::Sha256::default();
+
+        let data = Data::derive(shared_state.clone(), n.clone()).unwrap();
+        assert_eq!(data.w.jacobi(&n), -1);
+        // Both parties derive the same `w`
+        assert_eq!(
+            Data::derive(shared_state.clone(), n.clone()).unwrap().w,
+            data.w
+        );
+
+        let proof = non_interactive::prove::<32, _>(shared_state.clone(), &data, &pdata).unwrap();
+        non_interactive::verify(shared_state.clone(), &data, &proof).expect("proof failed");
+
+        // Same proof as the parent module with `w` as commitment
+        let commitment = super::super::Commitment { w: data.w.clone() };
+        let parent_data = super::super::Data { n: n.clone() };
+        super::super::non_interactive::verify(
+            shared_state.clone(),
+            &parent_data,
+            &commitment,
+            &proof,
+        )
+        .expect("proof failed");
+
+        // `w` fixed by the protocol
+        let w = crate::common::sqrt::sample_neg_jacobi(&n, &mut rng);
+        let fixed = Data::with_w(n.clone(), w).unwrap();
+        let proof = non_interactive::prove::<32, _>(shared_state.clone(), &fixed, &pdata).unwrap();
+        non_interactive::verify(shared_state.clone(), &fixed, &proof).expect("proof failed");
+
+        // `w` with Jacobi symbol 1 is rejected
+        let residue = Integer::from(4);
+        let err = Data::with_w(n.clone(), residue.clone()).unwrap_err();
+        assert_eq!(err.reason(), InvalidProofReason::WrongJacobiSymbol);
+        let bad = Data { n, w: residue };
+        let err = non_interactive::verify(shared_state, &bad, &proof).unwrap_err();
+        assert_eq!(err.reason(), InvalidProofReason::WrongJacobiSymbol);
+    }
+}
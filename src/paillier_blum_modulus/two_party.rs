@@ -0,0 +1,495 @@
+//! Пmod produced jointly by two provers holding shares of the factorization
+//!
+//! For moduli generated distributively, e.g. by two-party key generation, no single
+//! party knows `p` and `q`. Here each prover holds a [`KeyShare`]: additive shares
+//! over the integers of `phi(N)/4` and of `N^-1 mod phi(N)`. Provers exchange three
+//! rounds of partial exponentiations, after which each of them obtains the same
+//! [`Proof`]. It's verified with
+//! [`non_interactive::verify`](super::non_interactive::verify) as usual, verifier
+//! doesn't learn that the proof was produced jointly.
+//!
+//! Key generation must output the shares in this form, e.g. compute the share of
+//! `N^-1 mod phi(N)` with a shared inversion protocol. [`KeyShare::deal`] splits a
+//! known factorization, it's meant for a trusted dealer and for tests.
+//!
+//! ## Rounds
+//! Both provers use the same [`Commitment`]: one of them samples it with
+//! [`interactive::commit`](super::interactive::commit) and sends it to the other,
+//! it doesn't depend on the factorization. For every challenge `y`, `b` is chosen so
+//! that `w^b y` has Jacobi symbol `1`, and prover `i` holding `m_i` and `d_i` sends:
+//!
+//! 1. `(w^b y)^m_i` and `y^d_i`. Product of the former is `1` if `w^b y` is a
+//!    quadratic residue and `-1` otherwise, which determines `a`. Product of the latter
+//!    is the `N`-th root `z` of `y`.
+//! 2. `y'^s_i`, where `y' = (-1)^a w^b y` and `s_i = ceil(m_i / 2)`. Their product is
+//!    the square root `r` of `y'` that is a quadratic residue.
+//! 3. `r^s_i`, product of which is the fourth root `x` of `y'`.
+//!
+//! Every value a prover receives is computable from the resulting proof and its own
+//! share. Provers check the products before sending the next message and check the
+//! resulting proof, so malformed messages of the other prover make the protocol fail
+//! instead of producing an invalid proof.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::paillier_blum_modulus::{self as p, two_party};
+//! # fn doc_fn(
+//! #     data: p::Data,
+//! #     commitment: p::Commitment,
+//! #     share: &two_party::KeyShare,
+//! #     exchange1: impl Fn(&two_party::Round1Msg) -> two_party::Round1Msg,
+//! #     exchange2: impl Fn(&two_party::Round2Msg) -> two_party::Round2Msg,
+//! #     exchange3: impl Fn(&two_party::Round3Msg) -> two_party::Round3Msg,
+//! # ) -> Result<(), paillier_zk::Error> {
+//! let shared_state = sha2::Sha256::default();
+//!
+//! let (prover, msg) = two_party::start::<128, _>(shared_state, &data, &commitment, share)?;
+//! let (prover, msg) = prover.receive(&exchange1(&msg))?;
+//! let (prover, msg) = prover.receive(&exchange2(&msg))?;
+//! let proof = prover.finish(&exchange3(&msg))?;
+//! # Ok(()) }
+//! ```
+
+use digest::Digest;
+use rand_core::RngCore;
+use rug::{Complete, Integer};
+
+use crate::transcript::Transcript;
+use crate::{BadExponent, Error, ErrorReason, InvalidProof, InvalidProofReason};
+
+use super::{Challenge, Commitment, Data, PrivateData, Proof, ProofPoint};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Bit size of shares dealt by [`KeyShare::deal`] in excess of the modulus, shares
+/// are statistically close to uniform up to `2^-128`
+const STATISTICAL_SECURITY: u32 = 128;
+
+/// Share of the factorization held by one of the provers
+///
+/// Shares of both provers sum up to the secrets over the integers, not modulo
+/// anything.
+#[derive(Clone)]
+pub struct KeyShare {
+    /// Additive share of `phi(N)/4`
+    pub phi_quarter: Integer,
+    /// Additive share of `N^-1 mod phi(N)`
+    pub n_inverse: Integer,
+}
+
+crate::transcript::assert_private!(KeyShare);
+
+impl KeyShare {
+    /// Splits the factorization of `n` into shares of two provers
+    pub fn deal<R: RngCore>(
+        n: &Integer,
+        pdata: &PrivateData,
+        rng: &mut R,
+    ) -> Result<[Self; 2], Error> {
+        let phi = (&pdata.p - 1u8).complete() * (&pdata.q - 1u8).complete();
+        let n_inverse: Integer = n.invert_ref(&phi).ok_or(ErrorReason::Invert)?.into();
+        let phi_quarter = phi >> 2u32;
+
+        let bits = n.significant_bits() + STATISTICAL_SECURITY;
+        let mut split = |secret: Integer| {
+            let share: Integer =
+                Integer::random_bits(bits, &mut fast_paillier::utils::external_rand(&mut *rng))
+                    .into();
+            let rest = secret - &share;
+            (share, rest)
+        };
+        let (phi_quarter0, phi_quarter1) = split(phi_quarter);
+        let (n_inverse0, n_inverse1) = split(n_inverse);
+        Ok([
+            Self {
+                phi_quarter: phi_quarter0,
+                n_inverse: n_inverse0,
+            },
+            Self {
+                phi_quarter: phi_quarter1,
+                n_inverse: n_inverse1,
+            },
+        ])
+    }
+
+    /// Share of `(phi(N)/4 + 1) / 2`, exponent of the square root
+    ///
+    /// `phi(N)/4` is odd for Paillier-Blum modulus, so exactly one of the shares is
+    /// odd, and shares rounded up sum up to the exponent.
+    fn sqrt_exponent(&self) -> Integer {
+        (&self.phi_quarter + u8::from(self.phi_quarter.is_odd())).complete() >> 1u32
+    }
+}
+
+/// Message of the first round: partial residuosity tests and partial `N`-th roots
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Round1Msg {
+    pub residuosity: Vec<Integer>,
+    pub nth_roots: Vec<Integer>,
+}
+
+/// Message of the second round: partial square roots
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Round2Msg {
+    pub square_roots: Vec<Integer>,
+}
+
+/// Message of the third round: partial fourth roots
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Round3Msg {
+    pub fourth_roots: Vec<Integer>,
+}
+
+/// Prover that has sent [`Round1Msg`], obtained by [`start`]
+pub struct Round1<'a, const M: usize> {
+    data: &'a Data,
+    commitment: &'a Commitment,
+    share: &'a KeyShare,
+    challenge: Challenge<M>,
+    /// `b` and `w^b y` for each challenge
+    residues: Vec<(bool, Integer)>,
+    sent: Round1Msg,
+}
+
+/// Prover that has sent [`Round2Msg`]
+pub struct Round2<'a, const M: usize> {
+    data: &'a Data,
+    commitment: &'a Commitment,
+    share: &'a KeyShare,
+    challenge: Challenge<M>,
+    /// `a`, `b` and `y'` for each challenge
+    residues: Vec<(bool, bool, Integer)>,
+    nth_roots: Vec<Integer>,
+    sent: Round2Msg,
+}
+
+/// Prover that has sent [`Round3Msg`]
+pub struct Round3<'a, const M: usize> {
+    data: &'a Data,
+    commitment: &'a Commitment,
+    challenge: Challenge<M>,
+    residues: Vec<(bool, bool, Integer)>,
+    nth_roots: Vec<Integer>,
+    sent: Round3Msg,
+}
+
+/// Starts the protocol, deriving the challenge from the shared state
+///
+/// Both provers must use the same shared state and commitment, the proof is then
+/// verified with them.
+pub fn start<'a, const M: usize, D: Digest>(
+    shared_state: D,
+    data: &'a Data,
+    commitment: &'a Commitment,
+    share: &'a KeyShare,
+) -> Result<(Round1<'a, M>, Round1Msg), Error> {
+    let mut transcript = Transcript::from_shared_state(shared_state);
+    start_with_transcript(&mut transcript, data, commitment, share)
+}
+
+/// Starts the protocol, deriving the challenge from the protocol transcript
+pub fn start_with_transcript<'a, const M: usize, D: Digest>(
+    transcript: &mut Transcript<D>,
+    data: &'a Data,
+    commitment: &'a Commitment,
+    share: &'a KeyShare,
+) -> Result<(Round1<'a, M>, Round1Msg), Error> {
+    let challenge = super::non_interactive::challenge_with_transcript(transcript, data, commitment);
+    crate::common::lap!(Challenge);
+
+    let (n, w) = (&data.n, &commitment.w);
+    let residues = challenge
+        .ys
+        .iter()
+        .map(|y| {
+            let b = y.jacobi(n) == -1;
+            let y = if b {
+                (y * w).complete().modulo(n)
+            } else {
+                y.clone()
+            };
+            (b, y)
+        })
+        .collect::<Vec<_>>();
+    let residuosity = residues
+        .iter()
+        .map(|(_, y)| pow_mod(y, &share.phi_quarter, n))
+        .collect::<Result<_, _>>()?;
+    let nth_roots = challenge
+        .ys
+        .iter()
+        .map(|y| pow_mod(y, &share.n_inverse, n))
+        .collect::<Result<_, _>>()?;
+    let sent = Round1Msg {
+        residuosity,
+        nth_roots,
+    };
+    Ok((
+        Round1 {
+            data,
+            commitment,
+            share,
+            challenge,
+            residues,
+            sent: sent.clone(),
+        },
+        sent,
+    ))
+}
+
+impl<'a, const M: usize> Round1<'a, M> {
+    /// Receives the first message of the other prover
+    ///
+    /// Fails if the residuosity tests are inconsistent
+    pub fn receive(self, other: &Round1Msg) -> Result<(Round2<'a, M>, Round2Msg), Error> {
+        let n = &self.data.n;
+        let residuosity = combine::<M>(n, &self.sent.residuosity, &other.residuosity)
+            .ok_or(malformed(InvalidProofReason::IncorrectFourthRoot))?;
+        let nth_roots = combine::<M>(n, &self.sent.nth_roots, &other.nth_roots)
+            .ok_or(malformed(InvalidProofReason::IncorrectNthRoot))?;
+        let minus_one = (n - 1u8).complete();
+        let residues = self
+            .residues
+            .into_iter()
+            .zip(residuosity)
+            .map(|((b, y), test)| {
+                if test == 1 {
+                    Ok((false, b, y))
+                } else if test == minus_one {
+                    Ok((true, b, n - y))
+                } else {
+                    Err(malformed(InvalidProofReason::IncorrectFourthRoot))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let exponent = self.share.sqrt_exponent();
+        let square_roots = residues
+            .iter()
+            .map(|(_, _, y)| pow_mod(y, &exponent, n))
+            .collect::<Result<_, _>>()?;
+        let sent = Round2Msg { square_roots };
+        Ok((
+            Round2 {
+                data: self.data,
+                commitment: self.commitment,
+                share: self.share,
+                challenge: self.challenge,
+                residues,
+                nth_roots,
+                sent: sent.clone(),
+            },
+            sent,
+        ))
+    }
+}
+
+impl<'a, const M: usize> Round2<'a, M> {
+    /// Receives the second message of the other prover
+    ///
+    /// Fails if the square roots are incorrect
+    pub fn receive(self, other: &Round2Msg) -> Result<(Round3<'a, M>, Round3Msg), Error> {
+        let n = &self.data.n;
+        let square_roots = combine::<M>(n, &self.sent.square_roots, &other.square_roots)
+            .ok_or(malformed(InvalidProofReason::IncorrectFourthRoot))?;
+        for (r, (_, _, y)) in square_roots.iter().zip(&self.residues) {
+            if r.square_ref().complete().modulo(n) != *y {
+                return Err(malformed(InvalidProofReason::IncorrectFourthRoot));
+            }
+        }
+
+        let exponent = self.share.sqrt_exponent();
+        let fourth_roots = square_roots
+            .iter()
+            .map(|r| pow_mod(r, &exponent, n))
+            .collect::<Result<_, _>>()?;
+        let sent = Round3Msg { fourth_roots };
+        Ok((
+            Round3 {
+                data: self.data,
+                commitment: self.commitment,
+                challenge: self.challenge,
+                residues: self.residues,
+                nth_roots: self.nth_roots,
+                sent: sent.clone(),
+            },
+            sent,
+        ))
+    }
+}
+
+impl<const M: usize> Round3<'_, M> {
+    /// Receives the third message of the other prover and outputs the proof
+    ///
+    /// Fails if the resulting proof doesn't verify
+    pub fn finish(self, other: &Round3Msg) -> Result<Proof<M>, Error> {
+        let n = &self.data.n;
+        let fourth_roots = combine::<M>(n, &self.sent.fourth_roots, &other.fourth_roots)
+            .ok_or(malformed(InvalidProofReason::IncorrectFourthRoot))?;
+        let points = fourth_roots
+            .into_iter()
+            .zip(self.residues)
+            .zip(self.nth_roots)
+            .map(|((x, (a, b, _)), z)| ProofPoint { x, a, b, z })
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|_| ErrorReason::Length)?;
+        let proof = Proof { points };
+        super::interactive::verify(self.data, self.commitment, &self.challenge, &proof)
+            .map_err(|err| Error(ErrorReason::MalformedMessage(err)))?;
+        crate::common::lap!(Check("joint proof"));
+        Ok(proof)
+    }
+}
+
+/// Error caused by a message of the other prover
+fn malformed(reason: InvalidProofReason) -> Error {
+    Error(ErrorReason::MalformedMessage(InvalidProof::from(reason)))
+}
+
+/// Multiplies partial values of both provers element-wise modulo `n`
+///
+/// Returns `None` if there's not exactly one value per challenge
+fn combine<const M: usize>(
+    n: &Integer,
+    own: &[Integer],
+    other: &[Integer],
+) -> Option<Vec<Integer>> {
+    if own.len() != M || other.len() != M {
+        return None;
+    }
+    Some(
+        own.iter()
+            .zip(other)
+            .map(|(own, other)| (own * other).complete().modulo(n))
+            .collect(),
+    )
+}
+
+/// Exponentiation with a share, which may be negative
+fn pow_mod(base: &Integer, exponent: &Integer, n: &Integer) -> Result<Integer, Error> {
+    Ok(base
+        .pow_mod_ref(exponent, n)
+        .ok_or(BadExponent::undefined())?
+        .into())
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::test::generate_blum_prime;
+    use crate::paillier_blum_modulus::{interactive, non_interactive, Commitment, Data};
+
+    use super::{start, KeyShare, Round1, Round1Msg};
+
+    const M: usize = 32;
+
+    struct Setup {
+        data: Data,
+        commitment: Commitment,
+        shares: [KeyShare; 2],
+    }
+
+    fn setup() -> Setup {
+        let mut rng = rand_dev::DevRng::new();
+        let p = generate_blum_prime(&mut rng, 256);
+        let q = generate_blum_prime(&mut rng, 256);
+        let data = Data {
+            n: (&p * &q).complete(),
+        };
+        let pdata = crate::paillier_blum_modulus::PrivateData { p, q };
+        let shares = KeyShare::deal(&data.n, &pdata, &mut rng).unwrap();
+        let commitment = interactive::commit(&data, &mut rng);
+        Setup {
+            data,
+            commitment,
+            shares,
+        }
+    }
+
+    fn start_with(setup: &Setup, i: usize) -> (Round1<'_, M>, Round1Msg) {
+        let shared_state = sha2::Sha256::default();
+        start(
+            shared_state,
+            &setup.data,
+            &setup.commitment,
+            &setup.shares[i],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn joint_proof() {
+        let setup = setup();
+        // One of the shares is negative, so provers exponentiate with it
+        assert!(setup
+            .shares
+            .iter()
+            .any(|share| share.phi_quarter.is_negative()));
+
+        let (prover0, msg0) = start_with(&setup, 0);
+        let (prover1, msg1) = start_with(&setup, 1);
+        let (prover0, msg0_2) = prover0.receive(&msg1).unwrap();
+        let (prover1, msg1_2) = prover1.receive(&msg0).unwrap();
+        let (prover0, msg0_3) = prover0.receive(&msg1_2).unwrap();
+        let (prover1, msg1_3) = prover1.receive(&msg0_2).unwrap();
+        let proof0 = prover0.finish(&msg1_3).unwrap();
+        let proof1 = prover1.finish(&msg0_3).unwrap();
+
+        // Both provers obtain the same regular proof
+        let shared_state = sha2::Sha256::default();
+        non_interactive::verify(shared_state, &setup.data, &setup.commitment, &proof0)
+            .expect("proof failed");
+        for (point0, point1) in proof0.points.iter().zip(&proof1.points) {
+            assert_eq!(point0.x, point1.x);
+            assert_eq!(point0.z, point1.z);
+        }
+    }
+
+    #[test]
+    fn malformed_round1_msg() {
+        let setup = setup();
+        let (_, msg1) = start_with(&setup, 1);
+
+        let mut malformed = msg1.clone();
+        malformed.residuosity[0] += 1;
+        let (prover0, _) = start_with(&setup, 0);
+        assert!(prover0.receive(&malformed).is_err());
+
+        let mut malformed = msg1;
+        malformed.nth_roots.pop();
+        let (prover0, _) = start_with(&setup, 0);
+        assert!(prover0.receive(&malformed).is_err());
+    }
+
+    #[test]
+    fn malformed_round2_msg() {
+        let setup = setup();
+        let (prover0, msg0) = start_with(&setup, 0);
+        let (prover1, msg1) = start_with(&setup, 1);
+        let (prover0, _) = prover0.receive(&msg1).unwrap();
+        let (_, mut malformed) = prover1.receive(&msg0).unwrap();
+
+        malformed.square_roots[0] = Integer::from(2);
+        assert!(prover0.receive(&malformed).is_err());
+    }
+
+    #[test]
+    fn malformed_round3_msg() {
+        let setup = setup();
+        let (prover0, msg0) = start_with(&setup, 0);
+        let (prover1, msg1) = start_with(&setup, 1);
+        let (prover0, msg0_2) = prover0.receive(&msg1).unwrap();
+        let (prover1, msg1_2) = prover1.receive(&msg0).unwrap();
+        let (prover0, _) = prover0.receive(&msg1_2).unwrap();
+        let (_, mut malformed) = prover1.receive(&msg0_2).unwrap();
+
+        malformed.fourth_roots[0] += 1;
+        assert!(prover0.finish(&malformed).is_err());
+    }
+}
@@ -0,0 +1,958 @@
+//! ZK-proof that ciphertexts broadcast to several recipients encrypt the same bounded
+//! plaintext
+//!
+//! ## Description
+//!
+//! A party P has encrypted a number x under paillier keys `N_1, ..., N_n` of n
+//! recipients as `C_1, ..., C_n`. P shares the ciphertexts with V and wants to prove
+//! that all of them encrypt the same plaintext, and that the plaintext (i.e. x) is at
+//! most l bits, without revealing x.
+//!
+//! Given:
+//! - `keys` - public keys `N_1, ..., N_n` in paillier cryptosystem
+//! - `C_i = keys[i].encrypt(x)` - data to obtain proof about
+//!
+//! Prove:
+//! - `decrypt_1(C_1) = ... = decrypt_n(C_n)`
+//! - `bitsize(x) <= l`
+//!
+//! Disclosing only: `keys`, `C_1, ..., C_n`
+//!
+//! Commitment to the plaintext and the range proof are shared by all recipients, so
+//! the proof is about half the size of n separate
+//! [Пenc](crate::paillier_encryption_in_range) proofs. Each recipient still adds one
+//! ciphertext to the commitment and one nonce to the proof.
+//!
+//! All keys must be larger than `2^(l + epsilon)`, otherwise the plaintexts may be
+//! reduced differently modulo different keys.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use fast_paillier::AnyEncryptionKey;
+//! use paillier_zk::{paillier_broadcast_encryption as p, IntegerExt};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #         someone_encryption_key0: fast_paillier::EncryptionKey,
+//! #         someone_encryption_key1: fast_paillier::EncryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover knows paillier keys of all recipients
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key1 = pregenerated::someone_encryption_key0();
+//! let key2 = pregenerated::someone_encryption_key1();
+//! let key0 = private_key.encryption_key();
+//! let keys: [&dyn AnyEncryptionKey; 3] = [key0, &key1, &key2];
+//!
+//! // 2. Setup: prover encrypts some plaintext `x` to every recipient
+//!
+//! let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+//! let (c0, nonce0) = key0.encrypt_with_random(&mut rng, &x)?;
+//! let (c1, nonce1) = key1.encrypt_with_random(&mut rng, &x)?;
+//! let (c2, nonce2) = key2.encrypt_with_random(&mut rng, &x)?;
+//! let (ciphertexts, nonces) = ([c0, c1, c2], [nonce0, nonce1, nonce2]);
+//!
+//! // 3. Prover computes a non-interactive proof that all ciphertexts encrypt the
+//! //    same plaintext:
+//!
+//! let data = p::Data {
+//!     keys: &keys,
+//!     ciphertexts: &ciphertexts,
+//! };
+//! let pdata = p::PrivateData {
+//!     x: &x,
+//!     nonces: &nonces,
+//! };
+//! let (commitment, proof) =
+//!     p::non_interactive::prove(shared_state_prover, &aux, data, pdata, &security, &mut rng)?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data, _: &p::Commitment, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{fail_if, InvalidProof, InvalidProofReason, PreparedCiphertext};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::Aux;
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// l in paper, bit size of +-plaintext
+    pub l: usize,
+    /// Epsilon in paper, slackness parameter
+    pub epsilon: usize,
+    /// q in paper. Security parameter for challenge
+    pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// Absorbing ring-pedersen parameters (kilobytes) into every challenge is redundant
+    /// when counterparties agree on them once, e.g. per epoch. `aux_digest` must be
+    /// obtained with [`Aux::digest`] from the same aux that is used to prove and verify.
+    /// Prover and verifier must both enable it, otherwise the proof doesn't verify.
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 512, (Integer::ONE << 128_u32).complete())
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N_1, ..., N_n, public keys of the recipients
+    pub keys: &'a [&'a dyn AnyEncryptionKey],
+    /// C_1, ..., C_n, x encrypted on N_1, ..., N_n respectively
+    pub ciphertexts: &'a [Ciphertext],
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// x, plaintext of all ciphertexts
+    pub x: &'a Integer,
+    /// Nonces in encryptions x -> C_1, ..., x -> C_n
+    pub nonces: &'a [Nonce],
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    ciphertexts: Vec<PreparedCiphertext>,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        fail_if(
+            InvalidProofReason::LengthMismatch,
+            !data.keys.is_empty() && data.keys.len() == data.ciphertexts.len(),
+        )?;
+        let ciphertexts = data
+            .keys
+            .iter()
+            .zip(data.ciphertexts)
+            .map(|(key, ciphertext)| {
+                PreparedCiphertext::new(*key, ciphertext)
+                    .map_err(|err| InvalidProof::from(err).with_key(key.n()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, ciphertexts })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment {
+    pub s: Integer,
+    /// Encryption of `alpha` to each recipient
+    pub a: Vec<Ciphertext>,
+    pub d: Integer,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub alpha: Integer,
+    pub mu: Integer,
+    pub r: Vec<Nonce>,
+    pub gamma: Integer,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub z1: Integer,
+    /// Nonce for each recipient
+    pub w: Vec<Integer>,
+    pub z3: Integer,
+}
+
+impl_encode! { Data<'a> { keys, ciphertexts } }
+impl_encode! { Commitment { s, a, d } }
+impl_decode! { Commitment { s, a, d } }
+impl_encode! { Proof { z1, w, z3 } }
+impl_decode! { Proof { z1, w, z3 } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of all `N_i` and `N^`
+    pub fn max_serialized_size(
+        security: &SecurityParams,
+        recipients: usize,
+        modulus_bits: usize,
+    ) -> usize {
+        Self::schema(security, recipients, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of all `N_i` and `N^`
+    pub fn schema(_security: &SecurityParams, recipients: usize, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("s", modulus_bits)
+            .sequence(
+                "a",
+                recipients,
+                Schema::default().integer("a", 2 * modulus_bits),
+            )
+            .integer("d", modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of all `N_i` and `N^`
+    pub fn max_serialized_size(
+        security: &SecurityParams,
+        recipients: usize,
+        modulus_bits: usize,
+    ) -> usize {
+        Self::schema(security, recipients, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of all `N_i` and `N^`
+    pub fn schema(security: &SecurityParams, recipients: usize, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        let z3 = z1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .sequence(
+                "w",
+                recipients,
+                Schema::default().integer("w", modulus_bits),
+            )
+            .integer("z3", z3)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProofReason,
+    };
+    use crate::{Error, ErrorReason, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<R: RngCore>(
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        mut rng: R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        if data.keys.len() != data.ciphertexts.len() || data.keys.len() != pdata.nonces.len() {
+            return Err(ErrorReason::Length.into());
+        }
+        let two_to_l_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let hat_n_at_two_to_l = &aux.rsa_modulo * (Integer::ONE << security.l).complete();
+        let hat_n_at_two_to_l_e = (&aux.rsa_modulo * &two_to_l_e).complete();
+
+        let alpha = Integer::from_rng_pm(&two_to_l_e, &mut rng);
+        let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, &mut rng);
+        let r = data
+            .keys
+            .iter()
+            .map(|key| Integer::gen_invertible(key.n(), &mut rng))
+            .collect::<Vec<_>>();
+        let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_e, &mut rng);
+
+        let a = data
+            .keys
+            .iter()
+            .zip(&r)
+            .map(|(key, r)| key.encrypt_with(&alpha, r))
+            .collect::<Result<Vec<_>, _>>()?;
+        let commitment = Commitment {
+            s: aux.combine(pdata.x, &mu)?,
+            a,
+            d: aux.combine(&alpha, &gamma)?,
+        };
+        let private_commitment = PrivateCommitment {
+            alpha,
+            mu,
+            r,
+            gamma,
+        };
+        Ok((commitment, private_commitment))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        data: Data,
+        pdata: PrivateData,
+        pcomm: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        if data.keys.len() != pdata.nonces.len() || data.keys.len() != pcomm.r.len() {
+            return Err(ErrorReason::Length.into());
+        }
+        let w = data
+            .keys
+            .iter()
+            .zip(pdata.nonces)
+            .zip(&pcomm.r)
+            .map(|((key, nonce), r)| key.n().combine(r, Integer::ONE, nonce, challenge))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Proof {
+            z1: (&pcomm.alpha + challenge * pdata.x).complete(),
+            w,
+            z3: (&pcomm.gamma + challenge * &pcomm.mu).complete(),
+        })
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data)?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    ///
+    /// Failed check of the ciphertext is attributed to the key of its recipient
+    pub fn verify_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if(
+            InvalidProofReason::LengthMismatch,
+            commitment.a.len() == data.keys.len() && proof.w.len() == data.keys.len(),
+        )?;
+        fail_if_degenerate(&aux.rsa_modulo, [&commitment.s, &commitment.d])?;
+        for (i, key) in data.keys.iter().enumerate() {
+            check_recipient(
+                *key,
+                &statement.ciphertexts[i],
+                &commitment.a[i],
+                challenge,
+                &proof.z1,
+                &proof.w[i],
+            )
+            .map_err(|err| err.with_key(key.n()))?;
+        }
+        crate::common::lap!(Check("paillier encryptions"));
+        {
+            let lhs = aux.combine(&proof.z1, &proof.z3)?;
+            let s_to_e = aux.pow_mod(&commitment.s, challenge)?;
+            let rhs = (&commitment.d * s_to_e).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen"));
+        fail_if(
+            InvalidProofReason::RangeCheck(3),
+            proof
+                .z1
+                .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete()),
+        )?;
+        crate::common::lap!(Check("range of z1"));
+
+        Ok(())
+    }
+
+    /// Checks that `key.encrypt(z1; w) = a * c^e`
+    fn check_recipient(
+        key: &dyn fast_paillier::AnyEncryptionKey,
+        c: &crate::common::PreparedCiphertext,
+        a: &Integer,
+        challenge: &Challenge,
+        z1: &Integer,
+        w: &Integer,
+    ) -> Result<(), InvalidProof> {
+        fail_if_degenerate(key.nn(), [a])?;
+        crate::common::fail_if_not_coprime(key.n(), [w])?;
+        let lhs = key
+            .encrypt_with(z1, w)
+            .map_err(|_| InvalidProofReason::PaillierEnc)?;
+        let rhs = {
+            let e_at_c = c.omul(key, challenge)?;
+            key.oadd(a, &e_at_c)
+                .map_err(|_| InvalidProofReason::PaillierOp)?
+        };
+        fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        Ok(())
+    }
+
+    /// Generate random challenge
+    pub fn challenge<R>(security: &SecurityParams, rng: &mut R) -> Integer
+    where
+        R: RngCore,
+    {
+        Integer::from_rng_pm(&security.q, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<R: RngCore, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<R: RngCore, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+
+    /// Internal function for deriving challenge from protocol values
+    /// deterministically
+    pub fn challenge<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8>
+    where
+        D: Digest,
+    {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        transcript.label(b"paillier_zk.paillier_broadcast_encryption");
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb(b"l", &(security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_iter(b"N", data.keys);
+        transcript.absorb_iter(b"C", data.ciphertexts);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use rug::Integer;
+
+    use crate::common::extract::{challenge_diff, ensure, linear_response, paillier_nonce};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub x: Integer,
+        pub nonces: Vec<Nonce>,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let x = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        ensure(proof1.w.len() == data.keys.len() && proof2.w.len() == data.keys.len())?;
+        ensure(data.ciphertexts.len() == data.keys.len())?;
+
+        let mut nonces = Vec::with_capacity(data.keys.len());
+        for (i, (key, ciphertext)) in data.keys.iter().zip(data.ciphertexts).enumerate() {
+            let nonce = paillier_nonce(key.n(), ciphertext, &proof1.w[i], &proof2.w[i], &e_diff)?;
+            let c = key.encrypt_with(&x, &nonce).ok();
+            ensure(c.as_ref() == Some(ciphertext))?;
+            nonces.push(nonce);
+        }
+        Ok(Witness { x, nonces })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fast_paillier::{AnyEncryptionKey, EncryptionKey};
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::Encode;
+    use crate::common::test::random_key;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    struct Statement {
+        keys: Vec<EncryptionKey>,
+        ciphertexts: Vec<Integer>,
+        x: Integer,
+        nonces: Vec<Integer>,
+    }
+
+    impl Statement {
+        /// Encrypts `x` to `n` recipients, the last one gets `x_last` instead
+        fn new(rng: &mut rand_dev::DevRng, n: usize, x: Integer, x_last: &Integer) -> Self {
+            let keys = (0..n)
+                .map(|_| random_key(rng).unwrap().encryption_key().clone())
+                .collect::<Vec<_>>();
+            let (ciphertexts, nonces) = keys
+                .iter()
+                .enumerate()
+                .map(|(i, key)| {
+                    let plaintext = if i + 1 == n { x_last } else { &x };
+                    key.encrypt_with_random(rng, plaintext).unwrap()
+                })
+                .unzip();
+            Self {
+                keys,
+                ciphertexts,
+                x,
+                nonces,
+            }
+        }
+
+        fn keys(&self) -> Vec<&dyn AnyEncryptionKey> {
+            self.keys
+                .iter()
+                .map(|key| key as &dyn AnyEncryptionKey)
+                .collect()
+        }
+
+        fn data<'a>(&'a self, keys: &'a [&'a dyn AnyEncryptionKey]) -> super::Data<'a> {
+            super::Data {
+                keys,
+                ciphertexts: &self.ciphertexts,
+            }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_> {
+            super::PrivateData {
+                x: &self.x,
+                nonces: &self.nonces,
+            }
+        }
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete())
+    }
+
+    fn run(n: usize, x: Integer, x_last: Integer) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = Statement::new(&mut rng, n, x, &x_last);
+        let keys = statement.keys();
+        let data = statement.data(&keys);
+        let aux = crate::common::test::aux(&mut rng);
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        let modulus_bits = keys
+            .iter()
+            .map(|key| key.n().significant_bits())
+            .chain([aux.rsa_modulo.significant_bits()])
+            .max()
+            .unwrap() as usize;
+        assert!(super::Commitment::schema(&security, n, modulus_bits)
+            .conforms(&commitment.to_canonical_bytes()));
+
+        let result = super::non_interactive::verify(
+            shared_state,
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        );
+        if result.is_ok() {
+            // Schema bounds proofs of honest prover only
+            assert!(super::Proof::schema(&security, n, modulus_bits)
+                .conforms(&proof.to_canonical_bytes()));
+        }
+        result
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security().l).complete(), &mut rng);
+        for n in [1, 3] {
+            run(n, x.clone(), x.clone()).expect("proof failed");
+        }
+    }
+
+    #[test]
+    fn zero_witness() {
+        // Every ciphertext encrypts zero
+        for n in [1, 3] {
+            run(n, Integer::ZERO, Integer::ZERO).expect("proof failed");
+        }
+    }
+
+    #[test]
+    fn failing_range() {
+        let security = security();
+        let x = (Integer::ONE << (security.l + security.epsilon + 1)).complete();
+        let r = run(2, x.clone(), x).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::RangeCheck(3));
+    }
+
+    #[test]
+    fn failing_equality() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security().l).complete(), &mut rng);
+        let x_last = (&x + 1u8).complete();
+        let r = run(3, x, x_last).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::EqualityCheck(1));
+    }
+
+    #[test]
+    fn length_mismatch() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let statement = Statement::new(&mut rng, 3, x.clone(), &x);
+        let keys = statement.keys();
+        let data = statement.data(&keys);
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, statement.pdata(), &security, &mut rng).unwrap();
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof =
+            super::interactive::prove(data, statement.pdata(), &pcommitment, &challenge).unwrap();
+        let verify = |data, commitment: &super::Commitment, proof: &super::Proof| {
+            super::interactive::verify(&aux, data, commitment, &security, &challenge, proof)
+                .map_err(|e| e.reason())
+        };
+        verify(data, &commitment, &proof).expect("proof failed");
+
+        // Proof for fewer recipients
+        let mut truncated = proof.clone();
+        truncated.w.pop();
+        assert_eq!(
+            verify(data, &commitment, &truncated),
+            Err(InvalidProofReason::LengthMismatch)
+        );
+        // Statement about fewer recipients than keys
+        let fewer = super::Data {
+            ciphertexts: &statement.ciphertexts[1..],
+            ..data
+        };
+        assert_eq!(
+            verify(fewer, &commitment, &proof),
+            Err(InvalidProofReason::LengthMismatch)
+        );
+        // Statement without recipients
+        let empty = super::Data {
+            keys: &[],
+            ciphertexts: &[],
+        };
+        assert_eq!(
+            verify(empty, &commitment, &proof),
+            Err(InvalidProofReason::LengthMismatch)
+        );
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let statement = Statement::new(&mut rng, 3, x.clone(), &x);
+        let keys = statement.keys();
+        let (data, pdata) = (statement.data(&keys), statement.pdata());
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.x, statement.x);
+        assert_eq!(witness.nonces, statement.nonces);
+    }
+}
@@ -0,0 +1,817 @@
+//! ZK-proof of paillier decryption modulo q. Called Пdec or Rdec in the CGGMP21
+//! paper.
+//!
+//! ## Description
+//!
+//! A party P has `key0`, `pkey0` - public and private keys in paillier
+//! cryptosystem, and a ciphertext `C` which decrypts to `y`. P shares `C` and
+//! `x = y mod q`, with q being the order of curve `E`, and wants to prove that `x`
+//! is indeed the decryption of `C` reduced modulo q.
+//!
+//! Given:
+//! - `key0`, `pkey0` - pair of public and private keys in paillier cryptosystem
+//! - Curve `E` of order `q`
+//! - `C = key0.encrypt(y)` and `x = y mod q` - data to obtain proof about
+//!
+//! Prove:
+//! - `decrypt(C) = x mod q`
+//!
+//! Disclosing only: `key0`, `C`, `x`
+//!
+//! `y = 0` is a valid witness: `C` then encrypts zero and `x` is zero scalar.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use generic_ec::curves::Secp256k1 as E;
+//! use paillier_zk::{paillier_decryption_modulo_q as p, IntegerExt};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover prepares the paillier keys
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key0 = private_key.encryption_key();
+//!
+//! // 2. Setup: prover has ciphertext `C` of some `y`, and reveals `x = y mod q`
+//!
+//! let y = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+//! let (C, nonce) = key0.encrypt_with_random(&mut rng, &y)?;
+//! let x = y.to_scalar::<E>();
+//!
+//! // 3. Prover computes a non-interactive proof that `C` decrypts to `x` modulo q:
+//!
+//! let data = p::Data { key0, c: &C, x: &x };
+//! let (commitment, proof) =
+//!     p::non_interactive::prove(
+//!         shared_state_prover,
+//!         &aux,
+//!         data,
+//!         p::PrivateData { y: &y, nonce: &nonce },
+//!         &security,
+//!         &mut rng,
+//!     )?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data<E>, _: &p::Commitment<E>, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use generic_ec::{Curve, Scalar};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{InvalidProof, PreparedCiphertext};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::Aux;
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// l in paper, bit size of plaintext `y`. Determines how well `y` is masked
+    /// in the proof
+    pub l: usize,
+    /// Epsilon in paper, slackness parameter
+    pub epsilon: usize,
+    /// q in paper. Security parameter for challenge
+    pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// See [`paillier_encryption_in_range::SecurityParams::with_aux_digest`](crate::paillier_encryption_in_range::SecurityParams::with_aux_digest)
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 512, (Integer::ONE << 128_u32).complete())
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a, C: Curve> {
+    /// N0 in paper, public key that C was encrypted on
+    pub key0: &'a dyn AnyEncryptionKey,
+    /// C in paper, encryption of y
+    pub c: &'a Ciphertext,
+    /// x in paper, decryption of C reduced modulo curve order
+    pub x: &'a Scalar<C>,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// y in paper, plaintext of C
+    pub y: &'a Integer,
+    /// rho in paper, nonce in encryption y -> C
+    pub nonce: &'a Nonce,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a, C: Curve> {
+    data: Data<'a, C>,
+    c: PreparedCiphertext,
+}
+
+impl<'a, C: Curve> PreparedStatement<'a, C> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a, C>) -> Result<Self, InvalidProof> {
+        let c = PreparedCiphertext::new(data.key0, data.c)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, c })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a, C> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Commitment<C: Curve> {
+    pub s: Integer,
+    pub t: Integer,
+    pub a: Ciphertext,
+    pub gamma: Scalar<C>,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub alpha: Integer,
+    pub mu: Integer,
+    pub nu: Integer,
+    pub r: Nonce,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub z1: Integer,
+    pub z2: Integer,
+    pub w: Integer,
+}
+
+impl_encode! { Data<'a, C: Curve> { key0, c, x } }
+impl_encode! { Commitment<C: Curve> { s, t, a, gamma } }
+impl_decode! { Commitment<C: Curve> { s, t, a, gamma } }
+impl_encode! { Proof { z1, z2, w } }
+impl_decode! { Proof { z1, z2, w } }
+
+impl<C: Curve> Commitment<C> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("s", modulus_bits)
+            .integer("t", modulus_bits)
+            .integer("a", 2 * modulus_bits)
+            .scalar::<C>("gamma")
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        let z2 = z1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .integer("z2", z2)
+            .integer("w", modulus_bits)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne, fail_if_zero_challenge,
+        IntegerExt, InvalidProofReason,
+    };
+    use crate::{BadExponent, Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<C: Curve, R: RngCore>(
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        let two_to_l_plus_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let hat_n_at_two_to_l = (Integer::ONE << security.l).complete() * &aux.rsa_modulo;
+        let hat_n_at_two_to_l_plus_e =
+            (Integer::ONE << (security.l + security.epsilon)).complete() * &aux.rsa_modulo;
+
+        let alpha = Integer::from_rng_pm(&two_to_l_plus_e, rng);
+        let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, rng);
+        let nu = Integer::from_rng_pm(&hat_n_at_two_to_l_plus_e, rng);
+        let r = Integer::gen_invertible(data.key0.n(), rng);
+
+        let s = aux.combine(pdata.y, &mu)?;
+        let t = aux.combine(&alpha, &nu)?;
+        let a = data.key0.encrypt_with(&alpha, &r)?;
+        let gamma = alpha.to_scalar();
+
+        Ok((
+            Commitment { s, t, a, gamma },
+            PrivateCommitment { alpha, mu, nu, r },
+        ))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove<C: Curve>(
+        data: Data<C>,
+        pdata: PrivateData,
+        private_commitment: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        let z1 = (&private_commitment.alpha + challenge * pdata.y).complete();
+        let z2 = (&private_commitment.nu + challenge * &private_commitment.mu).complete();
+        let nonce_to_challenge_mod_n: Integer = pdata
+            .nonce
+            .pow_mod_ref(challenge, data.key0.n())
+            .ok_or(BadExponent::undefined())?
+            .into();
+        let w = (&private_commitment.r * nonce_to_challenge_mod_n).modulo(data.key0.n());
+        Ok(Proof { z1, z2, w })
+    }
+
+    /// Verify the proof
+    pub fn verify<C: Curve>(
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key0.n()))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key0.n()))
+    }
+
+    fn check_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(&aux.rsa_modulo, [&commitment.s, &commitment.t])?;
+        fail_if_degenerate(data.key0.nn(), [&commitment.a])?;
+        crate::common::fail_if_not_coprime(data.key0.n(), [&proof.w])?;
+        {
+            let lhs = data
+                .key0
+                .encrypt_with(&proof.z1, &proof.w)
+                .map_err(|_| InvalidProofReason::PaillierEnc)?;
+            let rhs = {
+                let e_at_c = statement.c.omul(data.key0, challenge)?;
+                data.key0
+                    .oadd(&commitment.a, &e_at_c)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("paillier encryption"));
+
+        {
+            let lhs = proof.z1.to_scalar::<C>();
+            let rhs = commitment.gamma + challenge.to_scalar::<C>() * data.x;
+            fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("decryption modulo q"));
+
+        {
+            let lhs = aux.combine(&proof.z1, &proof.z2)?;
+            let s_to_e = aux.pow_mod(&commitment.s, challenge)?;
+            let rhs = (&commitment.t * s_to_e).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen"));
+
+        Ok(())
+    }
+
+    /// Generate random challenge
+    ///
+    /// `security` parameter is used to generate challenge in correct range
+    pub fn challenge<R: RngCore>(security: &SecurityParams, rng: &mut R) -> Challenge {
+        Integer::from_rng_pm(&security.q, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<C: Curve, R: RngCore, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof), Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<C: Curve, R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof), Error> {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge {
+        transcript.label(b"paillier_zk.paillier_decryption_modulo_q");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb(b"l", &(security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"N0", data.key0.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_value(b"x", data.x);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use generic_ec::Curve;
+    use rug::Integer;
+
+    use crate::common::extract::{challenge_diff, ensure, linear_response, paillier_nonce};
+    use crate::common::IntegerExt;
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub y: Integer,
+        pub nonce: Nonce,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract<C: Curve>(
+        data: Data<C>,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let y = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        let nonce = paillier_nonce(data.key0.n(), data.c, &proof1.w, &proof2.w, &e_diff)?;
+
+        ensure(y.to_scalar::<C>() == *data.x)?;
+        let ciphertext = data.key0.encrypt_with(&y, &nonce).ok();
+        ensure(ciphertext.as_ref() == Some(data.c))?;
+        Ok(Witness { y, nonce })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generic_ec::{Curve, Scalar};
+    use rug::{Complete, Integer};
+
+    use crate::common::test::random_key;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams {
+            l: 1024,
+            epsilon: 300,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+        }
+    }
+
+    fn run<R: rand_core::RngCore + rand_core::CryptoRng, C: Curve>(
+        mut rng: R,
+        security: super::SecurityParams,
+        y: Integer,
+        x: Scalar<C>,
+    ) -> Result<(), crate::common::InvalidProof> {
+        let private_key0 = random_key(&mut rng).unwrap();
+        let key0 = private_key0.encryption_key().clone();
+        let (c, nonce) = key0.encrypt_with_random(&mut rng, &y).unwrap();
+
+        let data = super::Data {
+            key0: &key0,
+            c: &c,
+            x: &x,
+        };
+        let pdata = super::PrivateData {
+            y: &y,
+            nonce: &nonce,
+        };
+
+        let aux = crate::common::test::aux(&mut rng);
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        super::non_interactive::verify(shared_state, &aux, data, &commitment, &security, &proof)
+    }
+
+    fn passing_test<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let x = y.to_scalar::<C>();
+        run(rng, security, y, x).expect("proof failed");
+    }
+
+    fn zero_witness<C: Curve>() {
+        let rng = rand_dev::DevRng::new();
+        // C encrypts zero, x is zero scalar
+        run(rng, security(), Integer::ZERO, Scalar::<C>::zero()).expect("proof failed");
+    }
+
+    fn failing_test<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let x = y.to_scalar::<C>() + Scalar::one();
+        let r = run(rng, security, y, x).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::EqualityCheck(2));
+    }
+
+    #[test]
+    fn passing_p256() {
+        passing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn zero_witness_p256() {
+        zero_witness::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn failing_p256() {
+        failing_test::<generic_ec::curves::Secp256r1>()
+    }
+
+    #[test]
+    fn passing_million() {
+        passing_test::<crate::curve::C>()
+    }
+    #[test]
+    fn failing_million() {
+        failing_test::<crate::curve::C>()
+    }
+
+    #[test]
+    fn degenerate_transcript() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key0 = random_key(&mut rng).unwrap();
+        let key0 = private_key0.encryption_key();
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let (c, nonce) = key0.encrypt_with_random(&mut rng, &y).unwrap();
+        let x = y.to_scalar::<C>();
+        let data = super::Data { key0, c: &c, x: &x };
+        let pdata = super::PrivateData {
+            y: &y,
+            nonce: &nonce,
+        };
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Commitment equal to one is rejected
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let mut degenerate = commitment.clone();
+        degenerate.t = Integer::from(1);
+        let r = super::interactive::verify(&aux, data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key0 = random_key(&mut rng).unwrap();
+        let key0 = private_key0.encryption_key();
+        let y = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let (c, nonce) = key0.encrypt_with_random(&mut rng, &y).unwrap();
+        let x = y.to_scalar::<C>();
+        let data = super::Data { key0, c: &c, x: &x };
+        let pdata = super::PrivateData {
+            y: &y,
+            nonce: &nonce,
+        };
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.y, y);
+        assert_eq!(witness.nonce, nonce);
+
+        assert!(super::extract::extract(data, &e1, &proof1, &e1, &proof1).is_err());
+    }
+}
@@ -33,11 +33,7 @@
 //! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
 //!
 //! let aux: p::Aux = pregenerated::verifier_aux();
-//! let security = p::SecurityParams {
-//!     l: 1024,
-//!     epsilon: 128,
-//!     q: (Integer::ONE << 128_u32).into(),
-//! };
+//! let security = p::SecurityParams::new(1024, 128, (Integer::ONE << 128_u32).into());
 //!
 //! // 1. Setup: prover prepares the paillier keys
 //!
@@ -90,16 +86,24 @@
 use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
 use rug::Integer;
 
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::PreparedCiphertext;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 pub use crate::common::Aux;
 pub use crate::common::InvalidProof;
 
+pub mod batch;
+pub mod interval;
+pub mod linear_combination;
+
 /// Security parameters for proof. Choosing the values is a tradeoff between
 /// speed and chance of rejecting a valid proof or accepting an invalid proof
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
 pub struct SecurityParams {
     /// l in paper, security parameter for bit size of plaintext: it needs to
     /// be in range [-2^l; 2^l] or equivalently 2^l
@@ -108,6 +112,84 @@ pub struct SecurityParams {
     pub epsilon: usize,
     /// q in paper. Security parameter for challenge
     pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+    /// Domain separation tag of the application, absorbed into challenges
+    ///
+    /// Disabled by default, see [`SecurityParams::with_domain_tag`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub domain_tag: Option<Vec<u8>>,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// Absorbing ring-pedersen parameters (kilobytes) into every challenge is redundant
+    /// when counterparties agree on them once, e.g. per epoch. `aux_digest` must be
+    /// obtained with [`Aux::digest`] from the same aux that is used to prove and verify.
+    /// Prover and verifier must both enable it, otherwise the proof doesn't verify.
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+
+    /// Makes challenges absorb the domain separation tag of the application
+    ///
+    /// Applications that share keys and ring-pedersen parameters, but must not
+    /// accept each other's proofs, set distinct tags, e.g. `b"my-wallet/keygen/v1"`.
+    /// A proof made with one tag doesn't verify with another one or without a tag.
+    pub fn with_domain_tag(self, domain_tag: impl Into<Vec<u8>>) -> Self {
+        Self {
+            domain_tag: Some(domain_tag.into()),
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 512, (Integer::ONE << 128_u32).complete())
+    }
 }
 
 /// Public data that both parties know
@@ -120,14 +202,50 @@ pub struct Data<'a> {
 }
 
 /// Private data of prover
-#[derive(Clone, Copy)]
-pub struct PrivateData<'a> {
+///
+/// Plaintext can be kept in external storage, see [`crate::witness`].
+pub struct PrivateData<'a, W: ?Sized = Integer> {
     /// k in paper, plaintext of K
-    pub plaintext: &'a Integer,
+    pub plaintext: &'a W,
     /// rho in paper, nonce of encryption k -> K
     pub nonce: &'a Nonce,
 }
 
+impl<W: ?Sized> Clone for PrivateData<'_, W> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<W: ?Sized> Copy for PrivateData<'_, W> {}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    ciphertext: PreparedCiphertext,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        crate::common::fail_if_not_coprime(data.key.n(), [data.ciphertext])?;
+        let ciphertext = PreparedCiphertext::new(data.key, data.ciphertext)?;
+        crate::common::lap!(Check("ciphertext coprimality"));
+        Ok(Self { data, ciphertext })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
 // As described in cggmp21 at page 33
 /// Prover's first message, obtained by [`interactive::commit`]
 #[derive(Debug, Clone)]
@@ -148,6 +266,8 @@ pub struct PrivateCommitment {
     pub gamma: Integer,
 }
 
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
 /// Verifier's challenge to prover. Can be obtained deterministically by
 /// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
 pub type Challenge = Integer;
@@ -163,32 +283,89 @@ pub struct Proof {
     pub z3: Integer,
 }
 
+impl_encode! { Data<'a> { key, ciphertext } }
+impl_encode! { Commitment { s, a, c } }
+impl_decode! { Commitment { s, a, c } }
+impl_encode! { Proof { z1, z2, z3 } }
+impl_decode! { Proof { z1, z2, z3 } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("s", modulus_bits)
+            .integer("a", 2 * modulus_bits)
+            .integer("c", modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        let z3 = z1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .integer("z2", modulus_bits)
+            .integer("z3", z3)
+    }
+}
+
 /// The interactive version of the ZK proof. Should be completed in 3 rounds:
 /// prover commits to data, verifier responds with a random challenge, and
 /// prover gives proof with commitment and challenge.
 pub mod interactive {
+    use fast_paillier::AnyEncryptionKey;
     use rand_core::RngCore;
     use rug::{Complete, Integer};
 
     use crate::{
-        common::{fail_if, fail_if_ne, InvalidProofReason},
+        common::{
+            fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+            fail_if_zero_challenge, InvalidProofReason, PreparedCiphertext,
+        },
         BadExponent, Error,
     };
 
     use crate::common::{IntegerExt, InvalidProof};
+    use crate::witness::Witness;
 
     use super::{
-        Aux, Challenge, Commitment, Data, PrivateCommitment, PrivateData, Proof, SecurityParams,
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
     };
 
     /// Create random commitment
-    pub fn commit<R: RngCore>(
+    pub fn commit<W: Witness + ?Sized, R: RngCore>(
         aux: &Aux,
         data: Data,
-        pdata: PrivateData,
+        pdata: PrivateData<W>,
         security: &SecurityParams,
         rng: &mut R,
     ) -> Result<(Commitment, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
         let two_to_l_plus_e = (Integer::ONE << (security.l + security.epsilon)).complete();
         let hat_n_at_two_to_l = (Integer::ONE << security.l).complete() * &aux.rsa_modulo;
         let hat_n_at_two_to_l_plus_e =
@@ -198,8 +375,9 @@ pub mod interactive {
         let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, rng);
         let r = Integer::gen_invertible(data.key.n(), rng);
         let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_plus_e, rng);
+        crate::common::observe_randomness!(&alpha);
 
-        let s = aux.combine(pdata.plaintext, &mu)?;
+        let s = pdata.plaintext.ring_pedersen_commit(aux, &mu)?;
         let a = data.key.encrypt_with(&alpha, &r)?;
         let c = aux.combine(&alpha, &gamma)?;
 
@@ -215,13 +393,15 @@ pub mod interactive {
     }
 
     /// Compute proof for given data and prior protocol values
-    pub fn prove(
+    pub fn prove<W: Witness + ?Sized>(
         data: Data,
-        pdata: PrivateData,
+        pdata: PrivateData<W>,
         private_commitment: &PrivateCommitment,
         challenge: &Challenge,
     ) -> Result<Proof, Error> {
-        let z1 = (&private_commitment.alpha + (challenge * pdata.plaintext)).complete();
+        let z1 = pdata
+            .plaintext
+            .linear_response(&private_commitment.alpha, challenge)?;
         let nonce_to_challenge_mod_n: Integer = pdata
             .nonce
             .pow_mod_ref(challenge, data.key.n())
@@ -241,29 +421,59 @@ pub mod interactive {
         challenge: &Challenge,
         proof: &Proof,
     ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key.n()))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let key = statement.data.key;
+        check_prepared(
+            aux,
+            key,
+            &statement.ciphertext,
+            commitment,
+            security,
+            challenge,
+            proof,
+        )
+        .map_err(|err| err.with_key(key.n()))
+    }
+
+    /// Checks the proof about `ciphertext` encrypted under `key`
+    pub(super) fn check_prepared(
+        aux: &Aux,
+        key: &dyn AnyEncryptionKey,
+        ciphertext: &PreparedCiphertext,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(&aux.rsa_modulo, [&commitment.s, &commitment.c])?;
+        fail_if_degenerate(key.nn(), [&commitment.a])?;
+        crate::common::fail_if_not_coprime(key.n(), [&proof.z2])?;
         {
-            fail_if_ne(
-                InvalidProofReason::EqualityCheck(1),
-                &data.ciphertext.gcd_ref(data.key.n()).complete(),
-                Integer::ONE,
-            )?;
-        }
-        {
-            let lhs = data
-                .key
+            let lhs = key
                 .encrypt_with(&proof.z1, &proof.z2)
                 .map_err(|_| InvalidProofReason::PaillierEnc)?;
             let rhs = {
-                let e_at_k = data
-                    .key
-                    .omul(challenge, data.ciphertext)
-                    .map_err(|_| InvalidProofReason::PaillierOp)?;
-                data.key
-                    .oadd(&commitment.a, &e_at_k)
+                let e_at_k = ciphertext.omul(key, challenge)?;
+                key.oadd(&commitment.a, &e_at_k)
                     .map_err(|_| InvalidProofReason::PaillierOp)?
             };
             fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
         }
+        crate::common::lap!(Check("paillier encryption"));
 
         {
             let lhs = aux.combine(&proof.z1, &proof.z3)?;
@@ -271,6 +481,7 @@ pub mod interactive {
             let rhs = (&commitment.c * s_to_e).modulo(&aux.rsa_modulo);
             fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
         }
+        crate::common::lap!(Check("ring-pedersen"));
 
         fail_if(
             InvalidProofReason::RangeCheck(4),
@@ -278,6 +489,7 @@ pub mod interactive {
                 .z1
                 .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete()),
         )?;
+        crate::common::lap!(Check("range of z1"));
 
         Ok(())
     }
@@ -293,31 +505,57 @@ pub mod interactive {
 /// The non-interactive version of proof. Completed in one round, for example
 /// see the documentation of parent module.
 pub mod non_interactive {
-    use digest::{typenum::U32, Digest};
+    use digest::Digest;
     use rand_core::RngCore;
 
+    use crate::transcript::Transcript;
+    use crate::witness::Witness;
     use crate::{Error, InvalidProof};
 
-    use super::{Aux, Challenge, Commitment, Data, PrivateData, Proof, SecurityParams};
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
 
     /// Compute proof for the given data, producing random commitment and
     /// deriving determenistic challenge.
     ///
     /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
-    pub fn prove<D, R: RngCore>(
+    pub fn prove<D, W: Witness + ?Sized, R: RngCore>(
         shared_state: D,
         aux: &Aux,
         data: Data,
-        pdata: PrivateData,
+        pdata: PrivateData<W>,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<D, W: Witness + ?Sized, R: RngCore>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData<W>,
         security: &SecurityParams,
         rng: &mut R,
     ) -> Result<(Commitment, Proof), Error>
     where
-        D: Digest<OutputSize = U32>,
+        D: Digest,
     {
         let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
-        let challenge = challenge(shared_state, aux, data, &comm, security);
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
         let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
         Ok((comm, proof))
     }
 
@@ -332,21 +570,49 @@ pub mod non_interactive {
     where
         D: Digest,
     {
-        let order = rug::integer::Order::Msf;
-        let shared_state = shared_state.finalize();
-        let hash = |d: D| {
-            d.chain_update(&shared_state)
-                .chain_update(aux.s.to_digits(order))
-                .chain_update(aux.t.to_digits(order))
-                .chain_update(aux.rsa_modulo.to_digits(order))
-                .chain_update(data.key.n().to_digits(order))
-                .chain_update(data.ciphertext.to_digits(order))
-                .chain_update(commitment.s.to_digits(order))
-                .chain_update(commitment.a.to_digits(order))
-                .chain_update(commitment.c.to_digits(order))
-                .finalize()
-        };
-        let mut rng = crate::common::rng::HashRng::new(hash);
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8>
+    where
+        D: Digest,
+    {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        transcript.label(b"paillier_zk.paillier_encryption_in_range");
+        transcript.absorb_domain_tag(security.domain_tag.as_deref());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb_value(b"N0", data.key.n());
+        transcript.absorb_value(b"K", data.ciphertext);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
         super::interactive::challenge(security, &mut rng)
     }
 
@@ -360,11 +626,90 @@ pub mod non_interactive {
         proof: &Proof,
     ) -> Result<(), InvalidProof>
     where
-        D: Digest<OutputSize = U32>,
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
     {
-        let challenge = challenge(shared_state, aux, data, commitment, security);
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
         super::interactive::verify(aux, data, commitment, security, &challenge, proof)
     }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use rug::Integer;
+
+    use crate::common::extract::{challenge_diff, ensure, linear_response, paillier_nonce};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub plaintext: Integer,
+        pub nonce: Nonce,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let plaintext = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        let nonce = paillier_nonce(
+            data.key.n(),
+            data.ciphertext,
+            &proof1.z2,
+            &proof2.z2,
+            &e_diff,
+        )?;
+
+        let ciphertext = data.key.encrypt_with(&plaintext, &nonce).ok();
+        ensure(ciphertext.as_ref() == Some(data.ciphertext))?;
+        Ok(Witness { plaintext, nonce })
+    }
 }
 
 #[cfg(test)]
@@ -379,17 +724,8 @@ mod test {
         plaintext: Integer,
     ) -> Result<(), crate::common::InvalidProof> {
         let aux = crate::common::test::aux(&mut rng);
-        let private_key = crate::common::test::random_key(&mut rng).unwrap();
-        let key = private_key.encryption_key();
-        let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
-        let data = super::Data {
-            key,
-            ciphertext: &ciphertext,
-        };
-        let pdata = super::PrivateData {
-            plaintext: &plaintext,
-            nonce: &nonce,
-        };
+        let statement = crate::common::test::EncStatement::new(&mut rng, plaintext);
+        let (data, pdata) = (statement.data(), statement.pdata());
 
         let shared_state = sha2::Sha256::default();
         let (commitment, proof) =
@@ -405,6 +741,9 @@ mod test {
             l: 1024,
             epsilon: 256,
             q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
         };
         let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
         let r = run_with(&mut rng, security, plaintext);
@@ -413,6 +752,188 @@ mod test {
             Err(e) => panic!("{e:?}"),
         }
     }
+    #[test]
+    fn zero_witness() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        // Ciphertext encrypts zero
+        run_with(&mut rng, security, Integer::ZERO).expect("proof failed");
+    }
+    #[test]
+    fn serialized_size() {
+        use crate::common::encoding::Encode;
+
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l: 1024,
+            epsilon: 256,
+            q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let (commitment, proof) = super::non_interactive::prove(
+            sha2::Sha256::default(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        let modulus_bits = aux
+            .rsa_modulo
+            .significant_bits()
+            .max(statement.key.n().significant_bits());
+        let modulus_bits = modulus_bits as usize;
+        assert!(
+            commitment.to_canonical_bytes().len()
+                <= super::Commitment::max_serialized_size(&security, modulus_bits)
+        );
+        assert!(
+            proof.to_canonical_bytes().len()
+                <= super::Proof::max_serialized_size(&security, modulus_bits)
+        );
+    }
+
+    #[test]
+    fn negative_challenge() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l: 1024,
+            epsilon: 256,
+            q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let challenge = -super::interactive::challenge(&security, &mut rng).abs() - 1;
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof)
+            .expect("proof with negative challenge failed");
+
+        // Proof is bound to the sign of the challenge
+        let r = super::interactive::verify(
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &(-&challenge).complete(),
+            &proof,
+        );
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn degenerate_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l: 1024,
+            epsilon: 256,
+            q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Challenge outside of `[-q; q]` is rejected even for an honest proof
+        let above_q = (&security.q + 1u8).complete();
+        for challenge in [-above_q.clone(), above_q] {
+            let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+            let r =
+                super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+            assert_eq!(
+                r.map_err(|e| e.reason()).err(),
+                Some(InvalidProofReason::ChallengeOutOfRange)
+            );
+        }
+
+        // Commitment equal to one is rejected
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let mut degenerate = commitment.clone();
+        degenerate.c = Integer::from(1);
+        let r = super::interactive::verify(&aux, data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[test]
+    fn not_coprime() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l: 1024,
+            epsilon: 256,
+            q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let data = super::Data {
+            key,
+            ciphertext: &ciphertext,
+        };
+        let pdata = super::PrivateData {
+            plaintext: &plaintext,
+            nonce: &nonce,
+        };
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let verify = |commitment: &super::Commitment, proof: &super::Proof| {
+            super::interactive::verify(&aux, data, commitment, &security, &challenge, proof)
+                .map_err(|e| e.reason())
+        };
+
+        // Nonce sharing a factor with N
+        let mut crafted = proof.clone();
+        crafted.z2 = (private_key.p() * 3u32).complete();
+        assert_eq!(
+            verify(&commitment, &crafted),
+            Err(InvalidProofReason::NotCoprime)
+        );
+
+        // Commitment sharing a factor with N^2
+        let mut crafted = commitment.clone();
+        crafted.a = (private_key.q() * key.n()).complete();
+        assert_eq!(
+            verify(&crafted, &proof),
+            Err(InvalidProofReason::NotCoprime)
+        );
+    }
+
     #[test]
     fn failing() {
         let mut rng = rand_dev::DevRng::new();
@@ -420,6 +941,9 @@ mod test {
             l: 1024,
             epsilon: 256,
             q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
         };
         let plaintext = (Integer::ONE << (security.l + security.epsilon)).complete() + 1;
         let r = run_with(&mut rng, security, plaintext);
@@ -429,4 +953,237 @@ mod test {
             Err(e) => panic!("proof should not fail with {e:?}"),
         }
     }
+
+    #[cfg(feature = "key-fingerprints")]
+    #[test]
+    fn key_fingerprint() {
+        let mut rng = rand_dev::DevRng::new();
+        let security =
+            super::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete() - 1);
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let err = super::interactive::verify(
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &(&challenge + 1u8).complete(),
+            &proof,
+        )
+        .expect_err("proof should not pass");
+
+        let fingerprint = crate::KeyFingerprint::of(statement.key.n());
+        assert_eq!(err.key_fingerprint(), Some(fingerprint));
+        assert_eq!(
+            err.to_string(),
+            format!("invalid proof (key {fingerprint})")
+        );
+    }
+
+    #[test]
+    fn prepared_statement() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l: 1024,
+            epsilon: 256,
+            q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        // Statement arrives before the proof
+        let statement = super::PreparedStatement::new(data).unwrap();
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::non_interactive::verify_prepared(
+            shared_state,
+            &aux,
+            &statement,
+            &commitment,
+            &security,
+            &proof,
+        )
+        .expect("proof for prepared statement failed");
+
+        // Ciphertext not coprime with N is rejected upon preparation
+        let ciphertext = data.key.n().clone();
+        let data = super::Data {
+            key: data.key,
+            ciphertext: &ciphertext,
+        };
+        let r = super::PreparedStatement::new(data).map_err(|e| e.reason());
+        assert_eq!(r.err(), Some(InvalidProofReason::NotCoprime));
+    }
+
+    #[test]
+    fn weak_aux() {
+        let mut rng = rand_dev::DevRng::new();
+        let security =
+            super::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete() - 1);
+        let aux = crate::common::test::aux(&mut rng);
+        let weak_aux = super::Aux {
+            s: Integer::from(123),
+            t: Integer::from(321),
+            ..aux
+        };
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let mut prove = |security: &super::SecurityParams| {
+            super::non_interactive::prove(
+                sha2::Sha256::default(),
+                &weak_aux,
+                data,
+                pdata,
+                security,
+                &mut rng,
+            )
+        };
+        // Weak aux is rejected in debug builds only, unless explicitly requested
+        assert_eq!(prove(&security).is_err(), cfg!(debug_assertions));
+        assert!(prove(&security.clone().with_weak_aux_rejected()).is_err());
+    }
+
+    #[test]
+    fn aux_digest() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l: 1024,
+            epsilon: 256,
+            q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let another_aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let digested = security
+            .clone()
+            .with_aux_digest(aux.digest::<sha2::Sha256>().to_vec());
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &digested,
+            &mut rng,
+        )
+        .unwrap();
+        let verify = |aux: &super::Aux, security: &super::SecurityParams| {
+            super::non_interactive::verify(
+                shared_state.clone(),
+                aux,
+                data,
+                &commitment,
+                security,
+                &proof,
+            )
+        };
+
+        verify(&aux, &digested).expect("proof with aux digest failed");
+        // Both parties must absorb the digest
+        assert!(verify(&aux, &security).is_err());
+        // Digest doesn't substitute the aux used in verification
+        assert!(verify(&another_aux, &digested).is_err());
+        // Digest of another aux is rejected
+        let another_digested = security
+            .clone()
+            .with_aux_digest(another_aux.digest::<sha2::Sha256>().to_vec());
+        assert!(verify(&aux, &another_digested).is_err());
+    }
+
+    #[test]
+    fn domain_separation() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let app_a = security.clone().with_domain_tag(b"app-a".as_slice());
+        let app_b = security.clone().with_domain_tag(b"app-b".as_slice());
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &app_a,
+            &mut rng,
+        )
+        .unwrap();
+        super::non_interactive::verify(
+            shared_state.clone(),
+            &aux,
+            data,
+            &commitment,
+            &app_a,
+            &proof,
+        )
+        .expect("proof failed");
+        super::non_interactive::verify(
+            shared_state.clone(),
+            &aux,
+            data,
+            &commitment,
+            &app_b,
+            &proof,
+        )
+        .expect_err("proof of another application should not pass");
+        super::non_interactive::verify(shared_state, &aux, data, &commitment, &security, &proof)
+            .expect_err("proof with a tag should not pass without it");
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = super::SecurityParams {
+            l: 1024,
+            epsilon: 256,
+            q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.plaintext, statement.plaintext);
+        assert_eq!(witness.nonce, statement.nonce);
+
+        assert!(super::extract::extract(data, &e1, &proof1, &e1, &proof1).is_err());
+    }
 }
@@ -0,0 +1,1118 @@
+//! Aggregated Пenc for many ciphertexts under the same key
+//!
+//! ## Description
+//!
+//! A party P has `key` - public key in paillier cryptosystem, plaintexts `x_1, ..., x_n`
+//! and ciphertexts `K_i = key.encrypt_with(x_i, rho_i)`. P wants to prove that every
+//! plaintext is at most `l` bits, without disclosing them.
+//!
+//! Unlike n separate [Пenc](super) proofs, all ciphertexts share a single challenge.
+//! Ring-pedersen equations of the elements are aggregated by random weights derived
+//! along with the challenge, so the verifier does a single exponentiation with full
+//! exponents instead of n of them, and the proof carries a single `z3`. Paillier
+//! equations and range checks are still done per element.
+//!
+//! Verifier prepares every ciphertext before checking the equations. Where memory is
+//! scarce, [`non_interactive::verify_with_budget`] prepares them in chunks instead,
+//! see [`memory`](crate::memory) module.
+//!
+//! ## Example
+//!
+//! ```
+//! use paillier_zk::{paillier_encryption_in_range as p, IntegerExt};
+//! use rug::{Integer, Complete};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).into());
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key = private_key.encryption_key();
+//!
+//! // Prover encrypts several plaintexts
+//! let plaintexts = (0..3)
+//!     .map(|_| Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng))
+//!     .collect::<Vec<_>>();
+//! let (ciphertexts, nonces): (Vec<_>, Vec<_>) = plaintexts
+//!     .iter()
+//!     .map(|x| key.encrypt_with_random(&mut rng, x))
+//!     .collect::<Result<Vec<_>, _>>()?
+//!     .into_iter()
+//!     .unzip();
+//!
+//! // Prover proves that all of them are at most 1024 bits
+//! let data = p::batch::Data { key, ciphertexts: &ciphertexts };
+//! let pdata = p::batch::PrivateData {
+//!     plaintexts: &plaintexts,
+//!     nonces: &nonces,
+//! };
+//! let (commitment, proof) = p::batch::non_interactive::prove(
+//!     shared_state_prover,
+//!     &aux,
+//!     data,
+//!     pdata,
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // Verifier checks the proof
+//! p::batch::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{fail_if, InvalidProofReason, PreparedCiphertext};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use super::{Aux, InvalidProof, SecurityParams};
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N0 in paper, public key that all plaintexts were encrypted on
+    pub key: &'a dyn AnyEncryptionKey,
+    /// K_1, ..., K_n
+    pub ciphertexts: &'a [Ciphertext],
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// k_1, ..., k_n, plaintexts of K_1, ..., K_n
+    pub plaintexts: &'a [Integer],
+    /// rho_1, ..., rho_n, nonces of encryptions k_i -> K_i
+    pub nonces: &'a [Nonce],
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// See [`super::PreparedStatement`]
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    ciphertexts: Vec<PreparedCiphertext>,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        Self::prepare(data).map_err(|err| err.with_key(data.key.n()))
+    }
+
+    fn prepare(data: Data<'a>) -> Result<Self, InvalidProof> {
+        fail_if(
+            InvalidProofReason::LengthMismatch,
+            !data.ciphertexts.is_empty(),
+        )?;
+        crate::common::fail_if_not_coprime(data.key.n(), data.ciphertexts)?;
+        let ciphertexts = data
+            .ciphertexts
+            .iter()
+            .map(|ciphertext| PreparedCiphertext::new(data.key, ciphertext))
+            .collect::<Result<Vec<_>, _>>()?;
+        crate::common::lap!(Check("ciphertexts coprimality"));
+        Ok(Self { data, ciphertexts })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+///
+/// Contains commitment of [Пenc](super::Commitment) for every element
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment {
+    pub s: Vec<Integer>,
+    pub a: Vec<Integer>,
+    pub c: Vec<Integer>,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub alpha: Vec<Integer>,
+    pub mu: Vec<Integer>,
+    pub r: Vec<Integer>,
+    pub gamma: Vec<Integer>,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    /// Challenge shared by all elements
+    pub e: Integer,
+    /// Weights of ring-pedersen equations of the elements, each in `[0; q)`
+    pub weights: Vec<Integer>,
+}
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub z1: Vec<Integer>,
+    pub z2: Vec<Integer>,
+    /// `sum(weight_i * (gamma_i + e * mu_i))`
+    pub z3: Integer,
+}
+
+impl_encode! { Data<'a> { key, ciphertexts } }
+impl_encode! { Commitment { s, a, c } }
+impl_decode! { Commitment { s, a, c } }
+impl_encode! { Proof { z1, z2, z3 } }
+impl_decode! { Proof { z1, z2, z3 } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment to `n`
+    /// elements
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, n: usize, modulus_bits: usize) -> usize {
+        Self::schema(security, n, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment to `n`
+    /// elements
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(_security: &SecurityParams, n: usize, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .sequence("s", n, Schema::default().integer("s", modulus_bits))
+            .sequence("a", n, Schema::default().integer("a", 2 * modulus_bits))
+            .sequence("c", n, Schema::default().integer("c", modulus_bits))
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof about `n`
+    /// elements produced by honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, n: usize, modulus_bits: usize) -> usize {
+        Self::schema(security, n, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof about `n`
+    /// elements produced by honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(security: &SecurityParams, n: usize, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        // z3 is a sum of n terms, each is at most `q * (z1 + modulus_bits)` bits
+        let n_bits = (usize::BITS - n.leading_zeros()) as usize;
+        let z3 = q + z1 + modulus_bits + n_bits;
+        Schema::default()
+            .sequence("z1", n, Schema::default().integer("z1", z1))
+            .sequence("z2", n, Schema::default().integer("z2", modulus_bits))
+            .integer("z3", z3)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProofReason, PreparedCiphertext,
+    };
+    use crate::memory::{MemoryBudget, MemoryReport};
+    use crate::{BadExponent, Error, ErrorReason};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, InvalidProof, PreparedStatement, PrivateCommitment,
+        PrivateData, Proof, SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<R: RngCore>(
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        if pdata.plaintexts.len() != data.ciphertexts.len()
+            || pdata.nonces.len() != data.ciphertexts.len()
+        {
+            return Err(ErrorReason::Length.into());
+        }
+        let two_to_l_plus_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let hat_n_at_two_to_l = (Integer::ONE << security.l).complete() * &aux.rsa_modulo;
+        let hat_n_at_two_to_l_plus_e = (&two_to_l_plus_e * &aux.rsa_modulo).complete();
+
+        let n = data.ciphertexts.len();
+        let mut commitment = Commitment {
+            s: Vec::with_capacity(n),
+            a: Vec::with_capacity(n),
+            c: Vec::with_capacity(n),
+        };
+        let mut private_commitment = PrivateCommitment {
+            alpha: Vec::with_capacity(n),
+            mu: Vec::with_capacity(n),
+            r: Vec::with_capacity(n),
+            gamma: Vec::with_capacity(n),
+        };
+        for plaintext in pdata.plaintexts {
+            let alpha = Integer::from_rng_pm(&two_to_l_plus_e, rng);
+            let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, rng);
+            let r = Integer::gen_invertible(data.key.n(), rng);
+            let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_plus_e, rng);
+
+            commitment.s.push(aux.combine(plaintext, &mu)?);
+            commitment.a.push(data.key.encrypt_with(&alpha, &r)?);
+            commitment.c.push(aux.combine(&alpha, &gamma)?);
+
+            private_commitment.alpha.push(alpha);
+            private_commitment.mu.push(mu);
+            private_commitment.r.push(r);
+            private_commitment.gamma.push(gamma);
+        }
+        Ok((commitment, private_commitment))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        data: Data,
+        pdata: PrivateData,
+        private_commitment: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        let n = data.ciphertexts.len();
+        if [
+            pdata.plaintexts.len(),
+            pdata.nonces.len(),
+            private_commitment.alpha.len(),
+            challenge.weights.len(),
+        ]
+        .iter()
+        .any(|len| *len != n)
+        {
+            return Err(ErrorReason::Length.into());
+        }
+        let e = &challenge.e;
+
+        let mut z1 = Vec::with_capacity(n);
+        let mut z2 = Vec::with_capacity(n);
+        let mut z3 = Integer::ZERO;
+        for i in 0..n {
+            z1.push((&private_commitment.alpha[i] + (e * &pdata.plaintexts[i])).complete());
+            let nonce_to_challenge_mod_n: Integer = pdata.nonces[i]
+                .pow_mod_ref(e, data.key.n())
+                .ok_or(BadExponent::undefined())?
+                .into();
+            z2.push((&private_commitment.r[i] * nonce_to_challenge_mod_n).modulo(data.key.n()));
+            let z3_i = (&private_commitment.gamma[i] + (e * &private_commitment.mu[i])).complete();
+            z3 += &challenge.weights[i] * z3_i;
+        }
+        Ok(Proof { z1, z2, z3 })
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data)?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key.n()))
+    }
+
+    /// Verify the proof, processing ciphertexts in chunks that fit in the `budget`
+    ///
+    /// Same as [`verify`], but instead of preparing all ciphertexts upfront, they're
+    /// prepared one chunk at a time. Returns accounting of the memory used, see
+    /// [`memory`](crate::memory) module.
+    pub fn verify_with_budget(
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+        budget: MemoryBudget,
+    ) -> Result<MemoryReport, InvalidProof> {
+        check_with_budget(aux, data, commitment, security, challenge, proof, budget, 0)
+            .map_err(|err| err.with_key(data.key.n()))
+    }
+
+    /// Same as [`verify_with_budget`], `held_bytes` are accounted as fixed
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn check_with_budget(
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+        budget: MemoryBudget,
+        held_bytes: usize,
+    ) -> Result<MemoryReport, InvalidProof> {
+        // Same checks as in `PreparedStatement::new`, which don't keep anything
+        // per ciphertext
+        fail_if(
+            InvalidProofReason::LengthMismatch,
+            !data.ciphertexts.is_empty(),
+        )?;
+        crate::common::fail_if_not_coprime(data.key.n(), data.ciphertexts)?;
+        fail_if(
+            InvalidProofReason::PaillierOp,
+            data.ciphertexts.iter().all(|c| c.cmp0().is_ge()),
+        )?;
+        crate::common::lap!(Check("ciphertexts coprimality"));
+        check_messages(aux, data, commitment, security, challenge, proof)?;
+
+        // Prepared ciphertext is a ciphertext and its inverse. Checking an element
+        // takes three more ciphertexts, aggregated ring-pedersen equation takes
+        // six integers modulo `N^`.
+        let nn_bytes = crate::memory::integer_bytes(data.key.nn());
+        let aux_bytes = crate::memory::integer_bytes(&aux.rsa_modulo);
+        let report = budget.plan(
+            data.ciphertexts.len(),
+            2 * nn_bytes,
+            held_bytes + 3 * nn_bytes + 6 * aux_bytes,
+        );
+        for (i, ciphertexts) in data.ciphertexts.chunks(report.chunk_size).enumerate() {
+            let prepared = ciphertexts
+                .iter()
+                .map(|ciphertext| PreparedCiphertext::new(data.key, ciphertext))
+                .collect::<Result<Vec<_>, _>>()?;
+            check_encryptions(
+                data,
+                i * report.chunk_size,
+                &prepared,
+                commitment,
+                challenge,
+                proof,
+            )?;
+        }
+        crate::common::lap!(Check("paillier encryptions"));
+        check_aggregated(aux, commitment, security, challenge, proof)?;
+        Ok(report)
+    }
+
+    fn check_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_messages(aux, statement.data, commitment, security, challenge, proof)?;
+        check_encryptions(
+            statement.data,
+            0,
+            &statement.ciphertexts,
+            commitment,
+            challenge,
+            proof,
+        )?;
+        crate::common::lap!(Check("paillier encryptions"));
+        check_aggregated(aux, commitment, security, challenge, proof)
+    }
+
+    /// Checks that prover's messages are about every element and non-degenerate
+    fn check_messages(
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let n = data.ciphertexts.len();
+        fail_if_zero_challenge(&challenge.e)?;
+        fail_if_challenge_out_of_range(&challenge.e, &security.q)?;
+        fail_if(
+            InvalidProofReason::LengthMismatch,
+            [
+                commitment.s.len(),
+                commitment.a.len(),
+                commitment.c.len(),
+                proof.z1.len(),
+                proof.z2.len(),
+                challenge.weights.len(),
+            ]
+            .iter()
+            .all(|len| *len == n),
+        )?;
+        fail_if_degenerate(&aux.rsa_modulo, commitment.s.iter().chain(&commitment.c))?;
+        fail_if_degenerate(data.key.nn(), &commitment.a)?;
+        crate::common::fail_if_not_coprime(data.key.n(), &proof.z2)?;
+        Ok(())
+    }
+
+    /// Checks paillier equations of the elements starting at `offset`, whose
+    /// ciphertexts are `prepared`
+    fn check_encryptions(
+        data: Data,
+        offset: usize,
+        prepared: &[PreparedCiphertext],
+        commitment: &Commitment,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let e = &challenge.e;
+        for (i, ciphertext) in (offset..).zip(prepared) {
+            let lhs = data
+                .key
+                .encrypt_with(&proof.z1[i], &proof.z2[i])
+                .map_err(|_| InvalidProofReason::PaillierEnc)?;
+            let rhs = {
+                let e_at_k = ciphertext.omul(data.key, e)?;
+                data.key
+                    .oadd(&commitment.a[i], &e_at_k)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
+        }
+        Ok(())
+    }
+
+    /// Checks aggregated ring-pedersen equation and ranges of the responses
+    fn check_aggregated(
+        aux: &Aux,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let e = &challenge.e;
+        {
+            // prod (s^z1_i t^z3_i)^w_i = prod (c_i s_i^e)^w_i
+            let weighted_z1 = proof
+                .z1
+                .iter()
+                .zip(&challenge.weights)
+                .map(|(z1, w)| (z1 * w).complete())
+                .sum::<Integer>();
+            let lhs = aux.combine(&weighted_z1, &proof.z3)?;
+            let mut weighted_s = Integer::ONE.clone();
+            let mut weighted_c = Integer::ONE.clone();
+            for ((s, c), w) in commitment
+                .s
+                .iter()
+                .zip(&commitment.c)
+                .zip(&challenge.weights)
+            {
+                weighted_s = (weighted_s * aux.pow_mod(s, w)?).modulo(&aux.rsa_modulo);
+                weighted_c = (weighted_c * aux.pow_mod(c, w)?).modulo(&aux.rsa_modulo);
+            }
+            let rhs = (weighted_c * aux.pow_mod(&weighted_s, e)?).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen"));
+
+        let bound = (Integer::ONE << (security.l + security.epsilon)).complete();
+        fail_if(
+            InvalidProofReason::RangeCheck(4),
+            proof.z1.iter().all(|z1| z1.is_in_pm(&bound)),
+        )?;
+        crate::common::lap!(Check("range of z1"));
+
+        Ok(())
+    }
+
+    /// Generate random challenge for `n` elements
+    ///
+    /// `security` parameter is used to generate challenge in correct range
+    pub fn challenge<R: RngCore>(security: &SecurityParams, n: usize, rng: &mut R) -> Challenge {
+        let e = Integer::from_rng_pm(&security.q, rng);
+        let weights = (0..n)
+            .map(|_| {
+                security
+                    .q
+                    .random_below_ref(&mut fast_paillier::utils::external_rand(rng))
+                    .into()
+            })
+            .collect();
+        Challenge { e, weights }
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::memory::{MemoryBudget, MemoryReport};
+    use crate::transcript::Transcript;
+    use crate::Error;
+
+    use super::{
+        Aux, Challenge, Commitment, Data, InvalidProof, PreparedStatement, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<D, R: RngCore>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<D, R: RngCore>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8>
+    where
+        D: Digest,
+    {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        transcript.label(b"paillier_zk.paillier_encryption_in_range.batch");
+        transcript.absorb_domain_tag(security.domain_tag.as_deref());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb_value(b"N0", data.key.n());
+        transcript.absorb_iter(b"K", data.ciphertexts);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, data.ciphertexts.len(), &mut rng)
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof, deriving challenge independently from same data, and
+    /// processing ciphertexts in chunks that fit in the `budget`
+    ///
+    /// See [`interactive::verify_with_budget`](super::interactive::verify_with_budget).
+    /// The derived challenge is accounted as fixed.
+    pub fn verify_with_budget<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+        budget: MemoryBudget,
+    ) -> Result<MemoryReport, InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(shared_state, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        let challenge_bytes =
+            (challenge.weights.len() + 1) * crate::memory::integer_bytes(&security.q);
+        super::interactive::check_with_budget(
+            aux,
+            data,
+            commitment,
+            security,
+            &challenge,
+            proof,
+            budget,
+            challenge_bytes,
+        )
+        .map_err(|err| err.with_key(data.key.n()))
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use crate::common::extract::{challenge_diff, ensure, linear_response, paillier_nonce};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    pub use super::super::extract::Witness;
+
+    /// Extracts witness of every element from two accepting transcripts that share
+    /// the same commitment but have different challenges `e`
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Vec<Witness>, ExtractionError> {
+        let e_diff = challenge_diff(&challenge1.e, &challenge2.e)?;
+        let n = data.ciphertexts.len();
+        ensure(
+            [
+                proof1.z1.len(),
+                proof1.z2.len(),
+                proof2.z1.len(),
+                proof2.z2.len(),
+            ]
+            .iter()
+            .all(|len| *len == n),
+        )?;
+
+        (0..n)
+            .map(|i| {
+                let plaintext = linear_response(&proof1.z1[i], &proof2.z1[i], &e_diff)?;
+                let nonce = paillier_nonce(
+                    data.key.n(),
+                    &data.ciphertexts[i],
+                    &proof1.z2[i],
+                    &proof2.z2[i],
+                    &e_diff,
+                )?;
+                let ciphertext = data.key.encrypt_with(&plaintext, &nonce).ok();
+                ensure(ciphertext.as_ref() == Some(&data.ciphertexts[i]))?;
+                Ok(Witness { plaintext, nonce })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::Encode;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete())
+    }
+
+    fn run(plaintexts: Vec<Integer>) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (ciphertexts, nonces): (Vec<_>, Vec<_>) = plaintexts
+            .iter()
+            .map(|x| key.encrypt_with_random(&mut rng, x).unwrap())
+            .unzip();
+
+        let data = super::Data {
+            key,
+            ciphertexts: &ciphertexts,
+        };
+        let pdata = super::PrivateData {
+            plaintexts: &plaintexts,
+            nonces: &nonces,
+        };
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        let n = plaintexts.len();
+        let modulus_bits = key
+            .n()
+            .significant_bits()
+            .max(aux.rsa_modulo.significant_bits()) as usize;
+        assert!(super::Commitment::schema(&security, n, modulus_bits)
+            .conforms(&commitment.to_canonical_bytes()));
+
+        let result = super::non_interactive::verify(
+            shared_state,
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        );
+        if result.is_ok() {
+            assert!(super::Proof::schema(&security, n, modulus_bits)
+                .conforms(&proof.to_canonical_bytes()));
+        }
+        result
+    }
+
+    fn random_plaintexts(n: usize, bits: usize) -> Vec<Integer> {
+        let mut rng = rand_dev::DevRng::new();
+        let bound = (Integer::ONE << bits).complete();
+        (0..n)
+            .map(|_| Integer::from_rng_pm(&bound, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn passing() {
+        for n in [1, 4] {
+            run(random_plaintexts(n, security().l)).expect("proof failed");
+        }
+    }
+
+    #[test]
+    fn zero_witness() {
+        // Zero plaintexts, alone and mixed with non-zero ones
+        run(vec![Integer::ZERO; 3]).expect("proof failed");
+        let mut plaintexts = random_plaintexts(3, security().l);
+        plaintexts[1] = Integer::ZERO;
+        run(plaintexts).expect("proof failed");
+    }
+
+    #[test]
+    fn failing() {
+        let security = security();
+        let mut plaintexts = random_plaintexts(3, security.l);
+        plaintexts[1] = (Integer::ONE << (security.l + security.epsilon + 1)).complete();
+        let r = run(plaintexts).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::RangeCheck(4));
+    }
+
+    #[test]
+    fn tampered() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let plaintexts = random_plaintexts(3, security.l);
+        let (ciphertexts, nonces): (Vec<_>, Vec<_>) = plaintexts
+            .iter()
+            .map(|x| key.encrypt_with_random(&mut rng, x).unwrap())
+            .unzip();
+        let data = super::Data {
+            key,
+            ciphertexts: &ciphertexts,
+        };
+        let pdata = super::PrivateData {
+            plaintexts: &plaintexts,
+            nonces: &nonces,
+        };
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let challenge = super::interactive::challenge(&security, 3, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let verify = |commitment: &super::Commitment, proof: &super::Proof| {
+            super::interactive::verify(&aux, data, commitment, &security, &challenge, proof)
+                .map_err(|e| e.reason())
+        };
+        verify(&commitment, &proof).expect("proof failed");
+
+        // Ring-pedersen commitment of one element is swapped with another one
+        let mut swapped = commitment.clone();
+        swapped.s.swap(0, 1);
+        assert_eq!(
+            verify(&swapped, &proof),
+            Err(InvalidProofReason::EqualityCheck(3))
+        );
+
+        // Response for one element is changed
+        let mut changed = proof.clone();
+        changed.z1[2] += 1;
+        assert_eq!(
+            verify(&commitment, &changed),
+            Err(InvalidProofReason::EqualityCheck(2))
+        );
+
+        // Proof about fewer elements
+        let mut truncated = proof.clone();
+        truncated.z2.pop();
+        assert_eq!(
+            verify(&commitment, &truncated),
+            Err(InvalidProofReason::LengthMismatch)
+        );
+
+        // Empty statement
+        let empty = super::Data {
+            key,
+            ciphertexts: &[],
+        };
+        assert_eq!(
+            super::PreparedStatement::new(empty)
+                .map(|_| ())
+                .map_err(|e| e.reason()),
+            Err(InvalidProofReason::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn verification_within_memory_budget() {
+        use crate::memory::MemoryBudget;
+
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let plaintexts = random_plaintexts(5, security.l);
+        let (ciphertexts, nonces): (Vec<_>, Vec<_>) = plaintexts
+            .iter()
+            .map(|x| key.encrypt_with_random(&mut rng, x).unwrap())
+            .unzip();
+        let data = super::Data {
+            key,
+            ciphertexts: &ciphertexts,
+        };
+        let pdata = super::PrivateData {
+            plaintexts: &plaintexts,
+            nonces: &nonces,
+        };
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let verify = |proof: &super::Proof, budget| {
+            super::non_interactive::verify_with_budget(
+                shared_state.clone(),
+                &aux,
+                data,
+                &commitment,
+                &security,
+                proof,
+                budget,
+            )
+        };
+
+        let report = verify(&proof, MemoryBudget::UNLIMITED).unwrap();
+        assert_eq!((report.items, report.chunk_size, report.chunks), (5, 5, 1));
+
+        // Two elements per chunk
+        let budget = MemoryBudget::bytes(report.fixed_bytes + 2 * report.item_bytes);
+        let report = verify(&proof, budget).unwrap();
+        assert_eq!((report.chunk_size, report.chunks), (2, 3));
+        assert!(report.within_budget());
+
+        // Budget too small for a single element
+        let report = verify(&proof, MemoryBudget::bytes(1)).unwrap();
+        assert_eq!((report.chunk_size, report.chunks), (1, 5));
+        assert!(!report.within_budget());
+
+        // Same error as without the budget, wherever the wrong element is
+        for i in [0, 4] {
+            let mut changed = proof.clone();
+            changed.z1[i] += 1;
+            let expected = super::non_interactive::verify(
+                shared_state.clone(),
+                &aux,
+                data,
+                &commitment,
+                &security,
+                &changed,
+            )
+            .unwrap_err()
+            .reason();
+            assert_eq!(expected, InvalidProofReason::EqualityCheck(2));
+            assert_eq!(verify(&changed, budget).unwrap_err().reason(), expected);
+        }
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let plaintexts = random_plaintexts(3, security.l);
+        let (ciphertexts, nonces): (Vec<_>, Vec<_>) = plaintexts
+            .iter()
+            .map(|x| key.encrypt_with_random(&mut rng, x).unwrap())
+            .unzip();
+        let data = super::Data {
+            key,
+            ciphertexts: &ciphertexts,
+        };
+        let pdata = super::PrivateData {
+            plaintexts: &plaintexts,
+            nonces: &nonces,
+        };
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, 3, &mut rng);
+        let e2 = super::interactive::challenge(&security, 3, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witnesses = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        for (witness, (plaintext, nonce)) in witnesses.iter().zip(plaintexts.iter().zip(&nonces)) {
+            assert_eq!(&witness.plaintext, plaintext);
+            assert_eq!(&witness.nonce, nonce);
+        }
+    }
+}
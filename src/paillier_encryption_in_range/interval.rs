@@ -0,0 +1,1044 @@
+//! Пenc for a plaintext in an arbitrary interval `[a, b]`
+//!
+//! ## Description
+//!
+//! A party P has `key` - public key in paillier cryptosystem, `plaintext`, `nonce`
+//! and `ciphertext = key.encrypt_with(plaintext, nonce)`. P wants to prove that
+//! `a <= plaintext <= b` for public integers `a` and `b`, without disclosing
+//! `plaintext` and `nonce`.
+//!
+//! [Пenc](super) only bounds the absolute value of a plaintext, and with a slack: it
+//! can't tell whether `plaintext - a` is non-negative. Instead, P commits to
+//! `plaintext - a` with ring-pedersen parameters and links the commitment to the
+//! ciphertext the same way Пenc does. Then P shows that both `plaintext - a` and
+//! `b - plaintext` are non-negative by proving that `4y + 1` is a sum of three
+//! squares for each of them, which holds for an integer `y` if and only if `y >= 0`.
+//! The commitment to `b - plaintext` is derived from the commitment to
+//! `plaintext - a`, so both are tied to the same ciphertext.
+//!
+//! `l` of [`SecurityParams`] must be at least bit size of `b - a`.
+//!
+//! Zero is a legitimate plaintext as long as it's in the interval, and a plaintext
+//! equal to either of the bounds is proven as any other.
+//!
+//! ## Example
+//!
+//! ```
+//! use paillier_zk::paillier_encryption_in_range as p;
+//! use rug::{Integer, Complete};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).into());
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key = private_key.encryption_key();
+//!
+//! // Prover encrypts a plaintext from the interval [1000, 2000]
+//! let (a, b) = (Integer::from(1000), Integer::from(2000));
+//! let plaintext = Integer::from(1337);
+//! let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext)?;
+//!
+//! // Prover proves that plaintext is in the interval
+//! let data = p::interval::Data { key, ciphertext: &ciphertext, a: &a, b: &b };
+//! let pdata = p::interval::PrivateData {
+//!     plaintext: &plaintext,
+//!     nonce: &nonce,
+//! };
+//! let (commitment, proof) = p::interval::non_interactive::prove(
+//!     shared_state_prover,
+//!     &aux,
+//!     data,
+//!     pdata,
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // Verifier checks the proof
+//! p::interval::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use rug::{Complete, Integer};
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::PreparedCiphertext;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use super::{Aux, Challenge, InvalidProof, SecurityParams};
+
+/// Amount of squares each of `plaintext - a`, `b - plaintext` is decomposed into
+const SQUARES: usize = 3;
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N0 in paper, public key that plaintext was encrypted on
+    pub key: &'a dyn AnyEncryptionKey,
+    /// K in paper, encryption of plaintext
+    pub ciphertext: &'a Ciphertext,
+    /// Lower bound of the interval, inclusive
+    pub a: &'a Integer,
+    /// Upper bound of the interval, inclusive
+    pub b: &'a Integer,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// k in paper, plaintext of K
+    pub plaintext: &'a Integer,
+    /// rho in paper, nonce of encryption k -> K
+    pub nonce: &'a Nonce,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// See [`super::PreparedStatement`]
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    /// `K * (1 + N0)^-a`, encryption of `plaintext - a`
+    shifted: PreparedCiphertext,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        Self::prepare(data).map_err(|err| err.with_key(data.key.n()))
+    }
+
+    fn prepare(data: Data<'a>) -> Result<Self, InvalidProof> {
+        crate::common::fail_if_not_coprime(data.key.n(), [data.ciphertext])?;
+        let shifted = PreparedCiphertext::new(data.key, &shift(data.key, data.ciphertext, data.a))?;
+        crate::common::lap!(Check("ciphertext coprimality"));
+        Ok(Self { data, shifted })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment {
+    /// `s^(k - a) t^mu`
+    pub s: Integer,
+    pub a: Integer,
+    pub c: Integer,
+    /// Commitments to roots of the squares, first three for `k - a`, then three
+    /// for `b - k`
+    pub d: Vec<Integer>,
+    pub f: Vec<Integer>,
+    /// One for each of `k - a` and `b - k`
+    pub g: Vec<Integer>,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub alpha: Integer,
+    pub mu: Integer,
+    pub r: Integer,
+    pub gamma: Integer,
+    /// Roots of the squares
+    pub sigma: Vec<Integer>,
+    pub nu: Vec<Integer>,
+    pub delta: Vec<Integer>,
+    pub eta: Vec<Integer>,
+    pub kappa: Vec<Integer>,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub z1: Integer,
+    pub z2: Integer,
+    pub z3: Integer,
+    pub z4: Vec<Integer>,
+    pub z5: Vec<Integer>,
+    pub z6: Vec<Integer>,
+}
+
+impl_encode! { Data<'a> { key, ciphertext, a, b } }
+impl_encode! { Commitment { s, a, c, d, f, g } }
+impl_decode! { Commitment { s, a, c, d, f, g } }
+impl_encode! { Proof { z1, z2, z3, z4, z5, z6 } }
+impl_decode! { Proof { z1, z2, z3, z4, z5, z6 } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let element = Schema::default().integer("element", modulus_bits);
+        Schema::default()
+            .integer("s", modulus_bits)
+            .integer("a", 2 * modulus_bits)
+            .integer("c", modulus_bits)
+            .sequence("d", 2 * SQUARES, element.clone())
+            .sequence("f", 2 * SQUARES, element.clone())
+            .sequence("g", 2, element)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        let z3 = z1 + modulus_bits;
+        // kappa is sampled from `±2^(2l + epsilon) N^`
+        let z6 = (2 * l + security.epsilon).max(q + 2 * l) + 1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .integer("z2", modulus_bits)
+            .integer("z3", z3)
+            .sequence("z4", 2 * SQUARES, Schema::default().integer("z4", z1))
+            .sequence("z5", 2 * SQUARES, Schema::default().integer("z5", z3))
+            .sequence("z6", 2, Schema::default().integer("z6", z6))
+    }
+}
+
+/// `K * (1 + N0)^-a`, encryption of `plaintext - a` with the same nonce as `K`
+fn shift(key: &dyn AnyEncryptionKey, ciphertext: &Ciphertext, a: &Integer) -> Ciphertext {
+    // (1 + N0)^-a = 1 - a N0 mod N0^2
+    let one_minus_a_n = (Integer::ONE - (a * key.n()).complete()).modulo(key.nn());
+    (ciphertext * one_minus_a_n).modulo(key.nn())
+}
+
+/// Finds `[s1, s2, s3]` such that `s1^2 + s2^2 + s3^2 = n` for `n = 4y + 1`, `y >= 0`
+///
+/// Picks even `s1` such that `n - s1^2` is a prime, which is `1 mod 4` and therefore
+/// a sum of two squares.
+fn three_squares(n: &Integer) -> [Integer; SQUARES] {
+    let (root, remainder) = n.clone().sqrt_rem(Integer::new());
+    if remainder.cmp0().is_eq() {
+        return [root, Integer::new(), Integer::new()];
+    }
+    let mut s1 = root.clone() - root.is_odd() as u8;
+    while s1.cmp0().is_ge() {
+        let p = (n - s1.square_ref()).complete();
+        if p == *Integer::ONE {
+            return [s1, Integer::from(1), Integer::new()];
+        }
+        if p.is_probably_prime(25) != rug::integer::IsPrime::No {
+            let [s2, s3] = two_squares(&p);
+            return [s1, s2, s3];
+        }
+        s1 -= 2;
+    }
+    // Primes are dense enough to make it unreachable for anything but small `n`
+    let mut s1 = Integer::new();
+    loop {
+        let rest = (n - s1.square_ref()).complete();
+        let mut s2 = Integer::new();
+        while s2.square_ref().complete() <= rest {
+            let s3_square = (&rest - s2.square_ref()).complete();
+            if s3_square.is_perfect_square() {
+                return [s1, s2, s3_square.sqrt()];
+            }
+            s2 += 1;
+        }
+        s1 += 1;
+    }
+}
+
+/// Finds `[s2, s3]` such that `s2^2 + s3^2 = p` for prime `p = 1 mod 4`
+fn two_squares(p: &Integer) -> [Integer; 2] {
+    // Square root of -1 modulo p is `c^((p - 1) / 4)` for quadratic non-residue c
+    let exponent = (p - 1u8).complete() >> 2u32;
+    let mut c = Integer::from(2);
+    while c.jacobi(p) != -1 {
+        c += 1;
+    }
+    #[allow(clippy::expect_used)]
+    let root: Integer = c
+        .pow_mod_ref(&exponent, p)
+        .expect("exponent is non-negative")
+        .into();
+
+    // Euclid's algorithm on (p, root) stops at the first remainder below sqrt(p)
+    let (mut x, mut y) = (p.clone(), root);
+    while y.square_ref().complete() > *p {
+        let remainder = x.modulo(&y);
+        x = y;
+        y = remainder;
+    }
+    let s3 = (p - y.square_ref()).complete().sqrt();
+    [y, s3]
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProofReason,
+    };
+    use crate::{BadExponent, Error, ErrorReason};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, InvalidProof, PreparedStatement, PrivateCommitment,
+        PrivateData, Proof, SecurityParams, SQUARES,
+    };
+
+    /// Create random commitment
+    ///
+    /// Returns an error if plaintext is not in the interval
+    pub fn commit<R: RngCore>(
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        let lower = (pdata.plaintext - data.a).complete();
+        let upper = (data.b - pdata.plaintext).complete();
+        if lower.cmp0().is_lt() || upper.cmp0().is_lt() {
+            return Err(ErrorReason::OutOfInterval.into());
+        }
+
+        let two_to_l_plus_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let hat_n_at_two_to_l = (Integer::ONE << security.l).complete() * &aux.rsa_modulo;
+        let hat_n_at_two_to_l_plus_e = (&two_to_l_plus_e * &aux.rsa_modulo).complete();
+        let hat_n_at_two_to_2l_plus_e =
+            (Integer::ONE << (2 * security.l + security.epsilon)).complete() * &aux.rsa_modulo;
+
+        let alpha = Integer::from_rng_pm(&two_to_l_plus_e, rng);
+        let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, rng);
+        let r = Integer::gen_invertible(data.key.n(), rng);
+        let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_plus_e, rng);
+
+        let s = aux.combine(&lower, &mu)?;
+        let a = data.key.encrypt_with(&alpha, &r)?;
+        let c = aux.combine(&alpha, &gamma)?;
+
+        let mut commitment = Commitment {
+            s,
+            a,
+            c,
+            d: Vec::with_capacity(2 * SQUARES),
+            f: Vec::with_capacity(2 * SQUARES),
+            g: Vec::with_capacity(2),
+        };
+        let mut private_commitment = PrivateCommitment {
+            alpha,
+            mu,
+            r,
+            gamma,
+            sigma: Vec::with_capacity(2 * SQUARES),
+            nu: Vec::with_capacity(2 * SQUARES),
+            delta: Vec::with_capacity(2 * SQUARES),
+            eta: Vec::with_capacity(2 * SQUARES),
+            kappa: Vec::with_capacity(2),
+        };
+        for y in [lower, upper] {
+            let kappa = Integer::from_rng_pm(&hat_n_at_two_to_2l_plus_e, rng);
+            // kappa is wider than exponents of other proofs, so it doesn't go through
+            // multiexp table
+            let mut g = aux.pow_mod(&aux.t, &kappa)?;
+            for sigma in super::three_squares(&(y * 4u8 + 1u8)) {
+                let nu = Integer::from_rng_pm(&hat_n_at_two_to_l, rng);
+                let delta = Integer::from_rng_pm(&two_to_l_plus_e, rng);
+                let eta = Integer::from_rng_pm(&hat_n_at_two_to_l_plus_e, rng);
+
+                let d = aux.combine(&sigma, &nu)?;
+                commitment.f.push(aux.combine(&delta, &eta)?);
+                g = (g * aux.pow_mod(&d, &delta)?).modulo(&aux.rsa_modulo);
+                commitment.d.push(d);
+
+                private_commitment.sigma.push(sigma);
+                private_commitment.nu.push(nu);
+                private_commitment.delta.push(delta);
+                private_commitment.eta.push(eta);
+            }
+            commitment.g.push(g);
+            private_commitment.kappa.push(kappa);
+        }
+        Ok((commitment, private_commitment))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        data: Data,
+        pdata: PrivateData,
+        private_commitment: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        let pcomm = private_commitment;
+        if [
+            pcomm.sigma.len(),
+            pcomm.nu.len(),
+            pcomm.delta.len(),
+            pcomm.eta.len(),
+        ]
+        .iter()
+        .any(|len| *len != 2 * SQUARES)
+            || pcomm.kappa.len() != 2
+        {
+            return Err(ErrorReason::Length.into());
+        }
+        let e = challenge;
+
+        let lower = (pdata.plaintext - data.a).complete();
+        let z1 = &pcomm.alpha + (e * &lower).complete();
+        let nonce_to_challenge_mod_n: Integer = pdata
+            .nonce
+            .pow_mod_ref(e, data.key.n())
+            .ok_or(BadExponent::undefined())?
+            .into();
+        let z2 = (&pcomm.r * nonce_to_challenge_mod_n).modulo(data.key.n());
+        let z3 = (&pcomm.gamma + (e * &pcomm.mu)).complete();
+
+        let z4 = pcomm
+            .delta
+            .iter()
+            .zip(&pcomm.sigma)
+            .map(|(delta, sigma)| delta + (e * sigma).complete())
+            .collect();
+        let z5 = pcomm
+            .eta
+            .iter()
+            .zip(&pcomm.nu)
+            .map(|(eta, nu)| eta + (e * nu).complete())
+            .collect();
+        // Commitment to `b - k` is `s^(b - a) / s^(k - a) t^mu`, so its randomness is -mu
+        let z6 = [pcomm.mu.clone(), (-&pcomm.mu).complete()]
+            .into_iter()
+            .enumerate()
+            .map(|(j, mu)| {
+                let squares = j * SQUARES..(j + 1) * SQUARES;
+                // 4 mu - sum sigma_i nu_i, randomness of `Y^4 s` in terms of `D_i`
+                let rho = pcomm.sigma[squares.clone()]
+                    .iter()
+                    .zip(&pcomm.nu[squares])
+                    .fold(mu * 4u8, |acc, (sigma, nu)| acc - (sigma * nu).complete());
+                &pcomm.kappa[j] + e * rho
+            })
+            .collect();
+
+        Ok(Proof {
+            z1,
+            z2,
+            z3,
+            z4,
+            z5,
+            z6,
+        })
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data)?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key.n()))
+    }
+
+    fn check_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        let e = challenge;
+        fail_if_zero_challenge(e)?;
+        fail_if_challenge_out_of_range(e, &security.q)?;
+        fail_if(
+            InvalidProofReason::LengthMismatch,
+            [
+                commitment.d.len(),
+                commitment.f.len(),
+                proof.z4.len(),
+                proof.z5.len(),
+            ]
+            .iter()
+            .all(|len| *len == 2 * SQUARES)
+                && commitment.g.len() == 2
+                && proof.z6.len() == 2,
+        )?;
+        fail_if_degenerate(
+            &aux.rsa_modulo,
+            [&commitment.s, &commitment.c]
+                .into_iter()
+                .chain(&commitment.d)
+                .chain(&commitment.f)
+                .chain(&commitment.g),
+        )?;
+        fail_if_degenerate(data.key.nn(), [&commitment.a])?;
+        crate::common::fail_if_not_coprime(data.key.n(), [&proof.z2])?;
+
+        {
+            let lhs = data
+                .key
+                .encrypt_with(&proof.z1, &proof.z2)
+                .map_err(|_| InvalidProofReason::PaillierEnc)?;
+            let rhs = {
+                let e_at_k = statement.shifted.omul(data.key, e)?;
+                data.key
+                    .oadd(&commitment.a, &e_at_k)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("paillier encryption"));
+
+        {
+            let lhs = aux.combine(&proof.z1, &proof.z3)?;
+            let s_to_e = aux.pow_mod(&commitment.s, e)?;
+            let rhs = (&commitment.c * s_to_e).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen"));
+
+        for ((d, f), (z4, z5)) in commitment
+            .d
+            .iter()
+            .zip(&commitment.f)
+            .zip(proof.z4.iter().zip(&proof.z5))
+        {
+            let lhs = aux.combine(z4, z5)?;
+            let rhs = (f * aux.pow_mod(d, e)?).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("roots of squares"));
+
+        {
+            // `Y^4 s` for Y committing to `k - a` and `b - k`, that is `s^(4y + 1)`
+            // times a power of t if the prover is honest
+            let four = Integer::from(4);
+            let width = (data.b - data.a).complete();
+            let targets = [
+                (aux.pow_mod(&commitment.s, &four)? * &aux.s).modulo(&aux.rsa_modulo),
+                (aux.pow_mod(&commitment.s, &(-four))?
+                    * aux.combine(&(width * 4u8 + 1u8), &Integer::ZERO)?)
+                .modulo(&aux.rsa_modulo),
+            ];
+            for (j, target) in targets.iter().enumerate() {
+                let squares = j * SQUARES..(j + 1) * SQUARES;
+                let mut lhs = aux.pow_mod(&aux.t, &proof.z6[j])?;
+                for (d, z4) in commitment.d[squares.clone()].iter().zip(&proof.z4[squares]) {
+                    lhs = (lhs * aux.pow_mod(d, z4)?).modulo(&aux.rsa_modulo);
+                }
+                let rhs = (&commitment.g[j] * aux.pow_mod(target, e)?).modulo(&aux.rsa_modulo);
+                fail_if_ne(InvalidProofReason::EqualityCheck(4), lhs, rhs)?;
+            }
+        }
+        crate::common::lap!(Check("sums of squares"));
+
+        fail_if(
+            InvalidProofReason::RangeCheck(5),
+            proof
+                .z1
+                .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete()),
+        )?;
+        crate::common::lap!(Check("range of z1"));
+
+        Ok(())
+    }
+
+    /// Generate random challenge
+    ///
+    /// `security` parameter is used to generate challenge in correct range
+    pub fn challenge<R: RngCore>(security: &SecurityParams, rng: &mut R) -> Challenge {
+        super::super::interactive::challenge(security, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::Error;
+
+    use super::{
+        Aux, Challenge, Commitment, Data, InvalidProof, PreparedStatement, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<D, R: RngCore>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<D, R: RngCore>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8>
+    where
+        D: Digest,
+    {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        transcript.label(b"paillier_zk.paillier_encryption_in_range.interval");
+        transcript.absorb_domain_tag(security.domain_tag.as_deref());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb_value(b"N0", data.key.n());
+        transcript.absorb_value(b"K", data.ciphertext);
+        transcript.absorb_value(b"a", data.a);
+        transcript.absorb_value(b"b", data.b);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use crate::common::extract::{challenge_diff, ensure, linear_response, paillier_nonce};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    pub use super::super::extract::Witness;
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let lower = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        let shifted = super::shift(data.key, data.ciphertext, data.a);
+        let nonce = paillier_nonce(data.key.n(), &shifted, &proof1.z2, &proof2.z2, &e_diff)?;
+        let plaintext = lower + data.a;
+
+        ensure(data.a <= &plaintext && &plaintext <= data.b)?;
+        let ciphertext = data.key.encrypt_with(&plaintext, &nonce).ok();
+        ensure(ciphertext.as_ref() == Some(data.ciphertext))?;
+        Ok(Witness { plaintext, nonce })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::Encode;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete())
+    }
+
+    fn run(plaintext: Integer, a: Integer, b: Integer) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+
+        let data = super::Data {
+            key,
+            ciphertext: &ciphertext,
+            a: &a,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            plaintext: &plaintext,
+            nonce: &nonce,
+        };
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        let modulus_bits = key
+            .n()
+            .significant_bits()
+            .max(aux.rsa_modulo.significant_bits()) as usize;
+        assert!(super::Commitment::schema(&security, modulus_bits)
+            .conforms(&commitment.to_canonical_bytes()));
+
+        let result = super::non_interactive::verify(
+            shared_state,
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        );
+        if result.is_ok() {
+            assert!(
+                super::Proof::schema(&security, modulus_bits).conforms(&proof.to_canonical_bytes())
+            );
+        }
+        result
+    }
+
+    fn interval() -> (Integer, Integer) {
+        let mut rng = rand_dev::DevRng::new();
+        let bound = (Integer::ONE << (security().l - 1)).complete();
+        let a = Integer::from_rng_pm(&bound, &mut rng);
+        let width: Integer = bound
+            .random_below_ref(&mut fast_paillier::utils::external_rand(&mut rng))
+            .into();
+        let b = &a + width;
+        (a, b)
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let (a, b) = interval();
+        let width = (&b - &a).complete();
+        let offset: Integer = width
+            .random_below_ref(&mut fast_paillier::utils::external_rand(&mut rng))
+            .into();
+        let plaintext = &a + offset;
+        run(plaintext, a, b).expect("proof failed");
+    }
+
+    #[test]
+    fn bounds() {
+        let (a, b) = interval();
+        run(a.clone(), a.clone(), b.clone()).expect("proof failed");
+        run(b.clone(), a.clone(), b).expect("proof failed");
+        // Interval of a single value
+        run(a.clone(), a.clone(), a).expect("proof failed");
+    }
+
+    #[test]
+    fn zero_witness() {
+        run(Integer::ZERO, Integer::from(-5), Integer::from(5)).expect("proof failed");
+        run(Integer::ZERO, Integer::ZERO, Integer::ZERO).expect("proof failed");
+    }
+
+    #[test]
+    fn failing() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (a, b) = interval();
+        let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &b).unwrap();
+        let data = super::Data {
+            key,
+            ciphertext: &ciphertext,
+            a: &a,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            plaintext: &b,
+            nonce: &nonce,
+        };
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+
+        // Proof that plaintext is in `[a, b]` doesn't convince that it's in `[a, b - 1]`
+        let narrower = (&b - 1u8).complete();
+        let data = super::Data {
+            b: &narrower,
+            ..data
+        };
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof)
+            .expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::EqualityCheck(4));
+    }
+
+    #[test]
+    fn out_of_interval() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (a, b) = interval();
+
+        for plaintext in [(&a - 1u8).complete(), (&b + 1u8).complete()] {
+            let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+            let data = super::Data {
+                key,
+                ciphertext: &ciphertext,
+                a: &a,
+                b: &b,
+            };
+            let pdata = super::PrivateData {
+                plaintext: &plaintext,
+                nonce: &nonce,
+            };
+            let r = super::interactive::commit(&aux, data, pdata, &security, &mut rng);
+            assert!(matches!(
+                r.map(|_| ()).map_err(|e| e.0),
+                Err(crate::ErrorReason::OutOfInterval)
+            ));
+        }
+    }
+
+    #[test]
+    fn three_squares() {
+        let mut rng = rand_dev::DevRng::new();
+        let random = (0..4).map(|_| {
+            (Integer::ONE << 1024_u32)
+                .complete()
+                .random_below(&mut fast_paillier::utils::external_rand(&mut rng))
+        });
+        for y in (0..200).map(Integer::from).chain(random) {
+            let n = y * 4u8 + 1u8;
+            let squares = super::three_squares(&n);
+            let sum = squares
+                .iter()
+                .map(|s| s.square_ref().complete())
+                .sum::<Integer>();
+            assert_eq!(sum, n);
+        }
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (a, b) = interval();
+        for plaintext in [a.clone(), b.clone()] {
+            let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+            let data = super::Data {
+                key,
+                ciphertext: &ciphertext,
+                a: &a,
+                b: &b,
+            };
+            let pdata = super::PrivateData {
+                plaintext: &plaintext,
+                nonce: &nonce,
+            };
+
+            let (_commitment, pcommitment) =
+                super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+            let e1 = super::interactive::challenge(&security, &mut rng);
+            let e2 = super::interactive::challenge(&security, &mut rng);
+            let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+            let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+            let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+            assert_eq!(witness.plaintext, plaintext);
+            assert_eq!(witness.nonce, nonce);
+        }
+    }
+}
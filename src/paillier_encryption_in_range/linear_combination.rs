@@ -0,0 +1,623 @@
+//! Пenc for a public linear combination of ciphertexts
+//!
+//! ## Description
+//!
+//! A party P has `key` - public key in paillier cryptosystem, ciphertexts `C1`, `C2`
+//! and public integers `a`, `b`. P computes
+//! `D = a * C1 + b * C2 + key.encrypt_with(y, nonce)` using homomorphic operations.
+//! P wants to prove that `D` is obtained this way and `y` is at most `l` bits,
+//! without disclosing `y` and `nonce`.
+//!
+//! Unlike [Пaff-g](crate::paillier_affine_operation_in_range), coefficients are
+//! public, so verifier can cancel them out: `D - a * C1 - b * C2` is an encryption
+//! of `y`, and the proof is [Пenc](super) about it. Commitment and proof are the
+//! same as in Пenc, but the challenge is bound to the whole statement. This is the
+//! statement of aggregating MtA outputs, where coefficients are known to all
+//! parties.
+//!
+//! `y` may be zero, as may be either of coefficients.
+//!
+//! ## Example
+//!
+//! ```
+//! use paillier_zk::{paillier_encryption_in_range as p, IntegerExt};
+//! use rug::{Integer, Complete};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).into());
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key = private_key.encryption_key();
+//!
+//! // Ciphertexts known to everyone, e.g. received from other parties
+//! let (c1, _) = key.encrypt_with_random(&mut rng, &Integer::from(1))?;
+//! let (c2, _) = key.encrypt_with_random(&mut rng, &Integer::from(2))?;
+//! let (a, b) = (Integer::from(3), Integer::from(-4));
+//!
+//! // Prover computes the linear combination with a hidden summand
+//! let y = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+//! let (enc_y, nonce) = key.encrypt_with_random(&mut rng, &y)?;
+//! let d = key.oadd(&key.oadd(&key.omul(&a, &c1)?, &key.omul(&b, &c2)?)?, &enc_y)?;
+//!
+//! let data = p::linear_combination::Data {
+//!     key,
+//!     c1: &c1,
+//!     c2: &c2,
+//!     a: &a,
+//!     b: &b,
+//!     d: &d,
+//! };
+//! let pdata = p::linear_combination::PrivateData {
+//!     plaintext: &y,
+//!     nonce: &nonce,
+//! };
+//! let (commitment, proof) = p::linear_combination::non_interactive::prove(
+//!     shared_state_prover,
+//!     &aux,
+//!     data,
+//!     pdata,
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // Verifier checks the proof
+//! p::linear_combination::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use rug::Integer;
+
+use crate::common::encoding::impl_encode;
+use crate::common::{InvalidProofReason, PreparedCiphertext};
+
+pub use super::{
+    Aux, Challenge, Commitment, InvalidProof, PrivateCommitment, Proof, SecurityParams,
+};
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N0 in paper, public key that all ciphertexts are encrypted on
+    pub key: &'a dyn AnyEncryptionKey,
+    /// C1, first ciphertext of the combination
+    pub c1: &'a Ciphertext,
+    /// C2, second ciphertext of the combination
+    pub c2: &'a Ciphertext,
+    /// Coefficient of C1
+    pub a: &'a Integer,
+    /// Coefficient of C2
+    pub b: &'a Integer,
+    /// D = a * C1 + b * C2 + enc(y)
+    pub d: &'a Ciphertext,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// y, plaintext added to the combination
+    pub plaintext: &'a Integer,
+    /// Nonce of encryption of y
+    pub nonce: &'a Nonce,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>);
+
+/// Statement validated and prepared for verification in advance
+///
+/// See [`super::PreparedStatement`]
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    /// `D - a * C1 - b * C2`, encryption of y
+    residual: PreparedCiphertext,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        Self::prepare(data).map_err(|err| err.with_key(data.key.n()))
+    }
+
+    fn prepare(data: Data<'a>) -> Result<Self, InvalidProof> {
+        crate::common::fail_if_not_coprime(data.key.n(), [data.c1, data.c2, data.d])?;
+        let residual = residual(data).ok_or(InvalidProofReason::PaillierOp)?;
+        let residual = PreparedCiphertext::new(data.key, &residual)?;
+        crate::common::lap!(Check("ciphertexts coprimality"));
+        Ok(Self { data, residual })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+impl_encode! { Data<'a> { key, c1, c2, a, b, d } }
+
+/// `D - a * C1 - b * C2`, or `None` if C1 or C2 is not invertible
+fn residual(data: Data) -> Option<Ciphertext> {
+    let nn = data.key.nn();
+    // Raising to the power rather than `omul` as coefficients may be zero
+    let a_at_c1: Integer = data.c1.pow_mod_ref(&-data.a.clone(), nn)?.into();
+    let b_at_c2: Integer = data.c2.pow_mod_ref(&-data.b.clone(), nn)?.into();
+    Some((data.d * a_at_c1 * b_at_c2).modulo(nn))
+}
+
+/// Пenc statement about the residual, which has the same key and nonce
+fn enc_data<'a>(data: Data<'a>, residual: &'a Ciphertext) -> super::Data<'a> {
+    super::Data {
+        key: data.key,
+        ciphertext: residual,
+    }
+}
+
+fn enc_pdata(pdata: PrivateData) -> super::PrivateData {
+    super::PrivateData {
+        plaintext: pdata.plaintext,
+        nonce: pdata.nonce,
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use rand_core::RngCore;
+
+    use crate::{BadExponent, Error};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, InvalidProof, PreparedStatement, PrivateCommitment,
+        PrivateData, Proof, SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<R: RngCore>(
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        let residual = super::residual(data).ok_or(BadExponent::undefined())?;
+        super::super::interactive::commit(
+            aux,
+            super::enc_data(data, &residual),
+            super::enc_pdata(pdata),
+            security,
+            rng,
+        )
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        data: Data,
+        pdata: PrivateData,
+        private_commitment: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        let residual = super::residual(data).ok_or(BadExponent::undefined())?;
+        super::super::interactive::prove(
+            super::enc_data(data, &residual),
+            super::enc_pdata(pdata),
+            private_commitment,
+            challenge,
+        )
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data)?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let key = statement.data.key;
+        super::super::interactive::check_prepared(
+            aux,
+            key,
+            &statement.residual,
+            commitment,
+            security,
+            challenge,
+            proof,
+        )
+        .map_err(|err| err.with_key(key.n()))
+    }
+
+    /// Generate random challenge
+    ///
+    /// `security` parameter is used to generate challenge in correct range
+    pub fn challenge<R: RngCore>(security: &SecurityParams, rng: &mut R) -> Challenge {
+        super::super::interactive::challenge(security, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::Error;
+
+    use super::{
+        Aux, Challenge, Commitment, Data, InvalidProof, PreparedStatement, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<D, R: RngCore>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<D, R: RngCore>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8>
+    where
+        D: Digest,
+    {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        transcript.label(b"paillier_zk.paillier_encryption_in_range.linear_combination");
+        transcript.absorb_domain_tag(security.domain_tag.as_deref());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb_value(b"N0", data.key.n());
+        transcript.absorb_value(b"C1", data.c1);
+        transcript.absorb_value(b"C2", data.c2);
+        transcript.absorb_value(b"a", data.a);
+        transcript.absorb_value(b"b", data.b);
+        transcript.absorb_value(b"D", data.d);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use crate::common::extract::ExtractionErrorReason;
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    pub use super::super::extract::Witness;
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let residual = super::residual(data).ok_or(ExtractionErrorReason::WitnessMismatch)?;
+        super::super::extract::extract(
+            super::enc_data(data, &residual),
+            challenge1,
+            proof1,
+            challenge2,
+            proof2,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete())
+    }
+
+    /// Proves that `D = a * C1 + b * C2 + enc(y)`, and verifies it for coefficients
+    /// `a_verifier`, `b`
+    fn run(
+        y: Integer,
+        a: Integer,
+        b: Integer,
+        a_verifier: Integer,
+    ) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+
+        let x1 = Integer::from_rng_pm(key.half_n(), &mut rng);
+        let x2 = Integer::from_rng_pm(key.half_n(), &mut rng);
+        let (c1, _) = key.encrypt_with_random(&mut rng, &x1).unwrap();
+        let (c2, _) = key.encrypt_with_random(&mut rng, &x2).unwrap();
+        let (enc_y, nonce) = key.encrypt_with_random(&mut rng, &y).unwrap();
+        let a_at_c1: Integer = c1.pow_mod_ref(&a, key.nn()).unwrap().into();
+        let b_at_c2: Integer = c2.pow_mod_ref(&b, key.nn()).unwrap().into();
+        let d = (a_at_c1 * b_at_c2 * enc_y).modulo(key.nn());
+
+        let data = super::Data {
+            key,
+            c1: &c1,
+            c2: &c2,
+            a: &a,
+            b: &b,
+            d: &d,
+        };
+        let pdata = super::PrivateData {
+            plaintext: &y,
+            nonce: &nonce,
+        };
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        let data = super::Data {
+            a: &a_verifier,
+            ..data
+        };
+        let challenge =
+            super::non_interactive::challenge(shared_state, &aux, data, &commitment, &security);
+        super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof)
+    }
+
+    fn random_y() -> Integer {
+        let mut rng = rand_dev::DevRng::new();
+        Integer::from_rng_pm(&(Integer::ONE << security().l).complete(), &mut rng)
+    }
+
+    fn random_coefficient() -> Integer {
+        let mut rng = rand_dev::DevRng::new();
+        Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng)
+    }
+
+    #[test]
+    fn passing() {
+        let a = random_coefficient();
+        run(random_y(), a.clone(), random_coefficient(), a).expect("proof failed");
+    }
+
+    #[test]
+    fn zero_witness() {
+        // Zero summand, as well as zero coefficients
+        let a = random_coefficient();
+        run(Integer::ZERO, a.clone(), random_coefficient(), a).expect("proof failed");
+        run(random_y(), Integer::ZERO, Integer::ZERO, Integer::ZERO).expect("proof failed");
+    }
+
+    #[test]
+    fn failing() {
+        // Summand is out of range
+        let security = security();
+        let y = (Integer::ONE << (security.l + security.epsilon + 1)).complete();
+        let a = random_coefficient();
+        let r = run(y, a.clone(), random_coefficient(), a).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::RangeCheck(4));
+
+        // Verifier expects another coefficient
+        let a = random_coefficient();
+        let r = run(random_y(), a.clone(), random_coefficient(), a + 1)
+            .expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::EqualityCheck(2));
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let aux = crate::common::test::aux(&mut rng);
+        let private_key = crate::common::test::random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+
+        let (c1, _) = key
+            .encrypt_with_random(&mut rng, &Integer::from(1))
+            .unwrap();
+        let (c2, _) = key
+            .encrypt_with_random(&mut rng, &Integer::from(2))
+            .unwrap();
+        let (a, b) = (Integer::from(3), Integer::ZERO);
+        let y = random_y();
+        let (enc_y, nonce) = key.encrypt_with_random(&mut rng, &y).unwrap();
+        let a_at_c1: Integer = c1.pow_mod_ref(&a, key.nn()).unwrap().into();
+        let d = (a_at_c1 * enc_y).modulo(key.nn());
+        let data = super::Data {
+            key,
+            c1: &c1,
+            c2: &c2,
+            a: &a,
+            b: &b,
+            d: &d,
+        };
+        let pdata = super::PrivateData {
+            plaintext: &y,
+            nonce: &nonce,
+        };
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.plaintext, y);
+        assert_eq!(witness.nonce, nonce);
+    }
+}
@@ -0,0 +1,960 @@
+//! ZK-proof of paillier encryption in range bound to ElGamal commitment. Called
+//! Пenc-elg or Renc-elg in the revised (2024) CGGMP paper.
+//!
+//! ## Description
+//!
+//! A party P has encrypted a number x as C, and committed to it with an ElGamal-like
+//! commitment `(A, B, X) = (a G, b G, (a b + x) G)`, G being a generator of curve
+//! `E`. P shares C, A, B and X with V and wants to prove that plaintext of C is the
+//! committed value, and that it's at most l bits.
+//!
+//! Given:
+//! - `key0`, `pkey0` - pair of public and private keys in paillier cryptosystem
+//! - Curve `E`
+//! - `C = key0.encrypt(x)`, `A = a G`, `B = b G`, `X = (a b + x) G` - data to obtain
+//!   proof about
+//!
+//! Prove:
+//! - `decrypt(C) = log (X - b A)`
+//! - `bitsize(x) <= l`
+//!
+//! Disclosing only: `key0`, `C`, `A`, `B`, `X`
+//!
+//! `x = 0` is a valid witness: `C` then encrypts zero and `X = a B`.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use generic_ec::{Point, Scalar, curves::Secp256k1 as E};
+//! use paillier_zk::{paillier_encryption_in_range_with_elgamal as p, IntegerExt};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover prepares the paillier keys
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key0 = private_key.encryption_key();
+//!
+//! // 2. Setup: prover has some plaintext `x`, encrypts it and obtains `C`, and
+//! //    commits to it obtaining `A`, `B`, `X`
+//!
+//! let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+//! let (C, nonce) = key0.encrypt_with_random(&mut rng, &x)?;
+//! let a = Scalar::<E>::random(&mut rng);
+//! let b = Scalar::<E>::random(&mut rng);
+//! let A = Point::generator() * a;
+//! let B = Point::generator() * b;
+//! let X = Point::generator() * (a * b + x.to_scalar());
+//!
+//! // 3. Prover computes a non-interactive proof that plaintext is at most `l` bits:
+//!
+//! let data = p::Data {
+//!     key0,
+//!     c: &C,
+//!     a: &A,
+//!     b: &B,
+//!     x: &X,
+//! };
+//! let (commitment, proof) =
+//!     p::non_interactive::prove(
+//!         shared_state_prover,
+//!         &aux,
+//!         data,
+//!         p::PrivateData { x: &x, nonce: &nonce, b: &b },
+//!         &security,
+//!         &mut rng,
+//!     )?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data<E>, _: &p::Commitment<E>, _: &p::Proof<E>) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use generic_ec::{Curve, Point, Scalar};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{InvalidProof, PreparedCiphertext};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::Aux;
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// l in paper, bit size of +-plaintext
+    pub l: usize,
+    /// Epsilon in paper, slackness parameter
+    pub epsilon: usize,
+    /// q in paper. Security parameter for challenge
+    pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// `aux_digest` must be obtained with [`Aux::digest`] from the same aux that is
+    /// used to prove and verify. Prover and verifier must both enable it, otherwise
+    /// the proof doesn't verify.
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 512, (Integer::ONE << 128_u32).complete())
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a, C: Curve> {
+    /// N0 in paper, public key that C was encrypted on
+    pub key0: &'a dyn AnyEncryptionKey,
+    /// C in paper, x encrypted on N0
+    pub c: &'a Ciphertext,
+    /// A in paper, obtained as `a G`
+    pub a: &'a Point<C>,
+    /// B in paper, obtained as `b G`
+    pub b: &'a Point<C>,
+    /// X in paper, obtained as `(a b + x) G`
+    pub x: &'a Point<C>,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a, C: Curve> {
+    /// x in paper, plaintext of C
+    pub x: &'a Integer,
+    /// rho in paper, nonce in encryption x -> C
+    pub nonce: &'a Nonce,
+    /// b in paper, logarithm of B
+    pub b: &'a Scalar<C>,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a, C: Curve> {
+    data: Data<'a, C>,
+    c: PreparedCiphertext,
+}
+
+impl<'a, C: Curve> PreparedStatement<'a, C> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a, C>) -> Result<Self, InvalidProof> {
+        let c = PreparedCiphertext::new(data.key0, data.c)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, c })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a, C> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Commitment<C: Curve> {
+    pub s: Integer,
+    pub d: Ciphertext,
+    pub y: Point<C>,
+    pub z: Point<C>,
+    pub t: Integer,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment<C: Curve> {
+    pub alpha: Integer,
+    pub mu: Integer,
+    pub r: Nonce,
+    pub beta: Scalar<C>,
+    pub gamma: Integer,
+}
+
+crate::transcript::assert_private!(<C: Curve> PrivateData<'static, C>, PrivateCommitment<C>);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Proof<C: Curve> {
+    pub z1: Integer,
+    pub w: Scalar<C>,
+    pub z2: Integer,
+    pub z3: Integer,
+}
+
+impl_encode! { Data<'a, C: Curve> { key0, c, a, b, x } }
+impl_encode! { Commitment<C: Curve> { s, d, y, z, t } }
+impl_decode! { Commitment<C: Curve> { s, d, y, z, t } }
+impl_encode! { Proof<C: Curve> { z1, w, z2, z3 } }
+impl_decode! { Proof<C: Curve> { z1, w, z2, z3 } }
+
+impl<C: Curve> Commitment<C> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("s", modulus_bits)
+            .integer("d", 2 * modulus_bits)
+            .point::<C>("y")
+            .point::<C>("z")
+            .integer("t", modulus_bits)
+    }
+}
+
+impl<C: Curve> Proof<C> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        let z3 = z1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .scalar::<C>("w")
+            .integer("z2", modulus_bits)
+            .integer("z3", z3)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use generic_ec::{Curve, Point, Scalar};
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProofReason,
+    };
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<C: Curve, R: RngCore>(
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData<C>,
+        security: &SecurityParams,
+        mut rng: R,
+    ) -> Result<(Commitment<C>, PrivateCommitment<C>), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        let two_to_l_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let hat_n_at_two_to_l = &aux.rsa_modulo * (Integer::ONE << security.l).complete();
+        let hat_n_at_two_to_l_e = (&aux.rsa_modulo * &two_to_l_e).complete();
+
+        let alpha = Integer::from_rng_pm(&two_to_l_e, &mut rng);
+        let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, &mut rng);
+        let r = Integer::gen_invertible(data.key0.n(), &mut rng);
+        let beta = Scalar::random(&mut rng);
+        let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_e, &mut rng);
+
+        let commitment = Commitment {
+            s: aux.combine(pdata.x, &mu)?,
+            d: data.key0.encrypt_with(&alpha, &r)?,
+            y: data.a * beta + Point::generator() * alpha.to_scalar(),
+            z: Point::generator() * beta,
+            t: aux.combine(&alpha, &gamma)?,
+        };
+        let private_commitment = PrivateCommitment {
+            alpha,
+            mu,
+            r,
+            beta,
+            gamma,
+        };
+        Ok((commitment, private_commitment))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove<C: Curve>(
+        data: Data<C>,
+        pdata: PrivateData<C>,
+        pcomm: &PrivateCommitment<C>,
+        challenge: &Challenge,
+    ) -> Result<Proof<C>, Error> {
+        Ok(Proof {
+            z1: (&pcomm.alpha + challenge * pdata.x).complete(),
+            w: pcomm.beta + challenge.to_scalar::<C>() * pdata.b,
+            z2: data
+                .key0
+                .n()
+                .combine(&pcomm.r, Integer::ONE, pdata.nonce, challenge)?,
+            z3: (&pcomm.gamma + challenge * &pcomm.mu).complete(),
+        })
+    }
+
+    /// Verify the proof
+    pub fn verify<C: Curve>(
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key0.n()))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key0.n()))
+    }
+
+    fn check_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(&aux.rsa_modulo, [&commitment.s, &commitment.t])?;
+        fail_if_degenerate(data.key0.nn(), [&commitment.d])?;
+        crate::common::fail_if_not_coprime(data.key0.n(), [&proof.z2])?;
+        fail_if(
+            InvalidProofReason::DegenerateCommitment,
+            !commitment.y.is_zero() && !commitment.z.is_zero(),
+        )?;
+        {
+            let lhs = data
+                .key0
+                .encrypt_with(&proof.z1, &proof.z2)
+                .map_err(|_| InvalidProofReason::PaillierEnc)?;
+            let rhs = {
+                let e_at_c = statement.c.omul(data.key0, challenge)?;
+                data.key0
+                    .oadd(&commitment.d, &e_at_c)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("paillier encryption"));
+        let e = challenge.to_scalar::<C>();
+        {
+            let lhs = data.a * proof.w + Point::generator() * proof.z1.to_scalar();
+            let rhs = commitment.y + data.x * e;
+            fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("elgamal commitment"));
+        {
+            let lhs = Point::generator() * proof.w;
+            let rhs = commitment.z + data.b * e;
+            fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("elgamal randomness"));
+        {
+            let lhs = aux.combine(&proof.z1, &proof.z3)?;
+            let s_to_e = aux.pow_mod(&commitment.s, challenge)?;
+            let rhs = (&commitment.t * s_to_e).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(4), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen"));
+        fail_if(
+            InvalidProofReason::RangeCheck(5),
+            proof
+                .z1
+                .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete()),
+        )?;
+        crate::common::lap!(Check("range of z1"));
+
+        Ok(())
+    }
+
+    /// Generate random challenge
+    pub fn challenge<R>(security: &SecurityParams, rng: &mut R) -> Integer
+    where
+        R: RngCore,
+    {
+        Integer::from_rng_pm(&security.q, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<C: Curve, R: RngCore, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData<C>,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof<C>), Error>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<C: Curve, R: RngCore, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData<C>,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof<C>), Error>
+    where
+        D: Digest,
+    {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<C: Curve, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<C: Curve, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<C: Curve, D>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<C: Curve, D: Digest>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge {
+        transcript.label(b"paillier_zk.paillier_encryption_in_range_with_elgamal");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb(b"l", &(security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"N0", data.key0.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_point(b"A", data.a);
+        transcript.absorb_point(b"B", data.b);
+        transcript.absorb_point(b"X", data.x);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use generic_ec::{Curve, NonZero, Point, Scalar};
+    use rug::Integer;
+
+    use crate::common::extract::{
+        challenge_diff, ensure, linear_response, paillier_nonce, ExtractionErrorReason,
+    };
+    use crate::common::IntegerExt;
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness<C: Curve> {
+        pub x: Integer,
+        pub nonce: Nonce,
+        pub b: Scalar<C>,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract<C: Curve>(
+        data: Data<C>,
+        challenge1: &Challenge,
+        proof1: &Proof<C>,
+        challenge2: &Challenge,
+        proof2: &Proof<C>,
+    ) -> Result<Witness<C>, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let x = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        let nonce = paillier_nonce(data.key0.n(), data.c, &proof1.z2, &proof2.z2, &e_diff)?;
+        let e_diff_inv = NonZero::from_scalar(e_diff.to_scalar::<C>())
+            .ok_or(ExtractionErrorReason::NotCoprime)?
+            .invert();
+        let b = (proof1.w - proof2.w) * e_diff_inv;
+
+        let enc = data.key0.encrypt_with(&x, &nonce).ok();
+        ensure(enc.as_ref() == Some(data.c))?;
+        ensure(Point::generator() * b == *data.b)?;
+        ensure(*data.a * b + Point::generator() * x.to_scalar() == *data.x)?;
+        Ok(Witness { x, nonce, b })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fast_paillier::EncryptionKey;
+    use generic_ec::{Curve, Point, Scalar};
+    use rug::{Complete, Integer};
+
+    use crate::common::test::random_key;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    struct Statement<C: Curve> {
+        key0: EncryptionKey,
+        c: Integer,
+        a: Point<C>,
+        b: Point<C>,
+        x: Point<C>,
+        x_plain: Integer,
+        nonce: Integer,
+        b_log: Scalar<C>,
+    }
+
+    impl<C: Curve> Statement<C> {
+        fn data(&self) -> super::Data<'_, C> {
+            super::Data {
+                key0: &self.key0,
+                c: &self.c,
+                a: &self.a,
+                b: &self.b,
+                x: &self.x,
+            }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_, C> {
+            super::PrivateData {
+                x: &self.x_plain,
+                nonce: &self.nonce,
+                b: &self.b_log,
+            }
+        }
+    }
+
+    fn statement<C: Curve, R: rand_core::RngCore + rand_core::CryptoRng>(
+        rng: &mut R,
+        x: Integer,
+    ) -> Statement<C> {
+        let key0 = random_key(rng).unwrap().encryption_key().clone();
+        let (c, nonce) = key0.encrypt_with_random(rng, &x).unwrap();
+        let a = Scalar::<C>::random(rng);
+        let b = Scalar::<C>::random(rng);
+        Statement {
+            a: Point::generator() * a,
+            b: Point::generator() * b,
+            x: Point::generator() * (a * b + x.to_scalar()),
+            key0,
+            c,
+            x_plain: x,
+            nonce,
+            b_log: b,
+        }
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete())
+    }
+
+    fn run<C: Curve>(x: Integer) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement::<C, _>(&mut rng, x);
+        let aux = crate::common::test::aux(&mut rng);
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::non_interactive::verify(
+            shared_state,
+            &aux,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+    }
+
+    fn passing_test<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security().l).complete(), &mut rng);
+        run::<C>(x).expect("proof failed");
+    }
+
+    fn zero_witness<C: Curve>() {
+        // C encrypts zero, X = a B
+        run::<C>(Integer::ZERO).expect("proof failed");
+    }
+
+    fn failing_test<C: Curve>() {
+        let security = security();
+        let x = (Integer::ONE << (security.l + security.epsilon)).complete() + 1;
+        let r = run::<C>(x).expect_err("proof should not pass");
+        match r.reason() {
+            InvalidProofReason::RangeCheck(_) => (),
+            e => panic!("proof should not fail with: {e:?}"),
+        }
+    }
+
+    fn wrong_commitment<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let mut statement = statement::<C, _>(&mut rng, x);
+        // X commits to x + 1
+        statement.x += Point::generator();
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) = super::interactive::commit(
+            &aux,
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(
+            statement.data(),
+            statement.pdata(),
+            &pcommitment,
+            &challenge,
+        )
+        .unwrap();
+        let r = super::interactive::verify(
+            &aux,
+            statement.data(),
+            &commitment,
+            &security,
+            &challenge,
+            &proof,
+        );
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::EqualityCheck(2))
+        );
+    }
+
+    fn degenerate_transcript<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let statement = statement::<C, _>(&mut rng, x);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Negative challenge is fine
+        let challenge = -super::interactive::challenge(&security, &mut rng).abs() - 1;
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof)
+            .expect("proof with negative challenge failed");
+
+        // Identity point in commitment is rejected
+        let mut commitment = commitment;
+        commitment.z = Point::zero();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[test]
+    fn passing_p256() {
+        passing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn zero_witness_p256() {
+        zero_witness::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn failing_p256() {
+        failing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn wrong_commitment_p256() {
+        wrong_commitment::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn degenerate_transcript_p256() {
+        degenerate_transcript::<generic_ec::curves::Secp256r1>()
+    }
+
+    #[test]
+    fn passing_million() {
+        passing_test::<crate::curve::C>()
+    }
+    #[test]
+    fn failing_million() {
+        failing_test::<crate::curve::C>()
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let statement = statement::<C, _>(&mut rng, x);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.x, statement.x_plain);
+        assert_eq!(witness.nonce, statement.nonce);
+        assert_eq!(witness.b, statement.b_log);
+
+        assert!(super::extract::extract(data, &e1, &proof1, &e1, &proof1).is_err());
+    }
+}
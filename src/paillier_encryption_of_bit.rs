@@ -0,0 +1,808 @@
+//! ZK-proof that paillier ciphertext encrypts a bit
+//!
+//! ## Description
+//!
+//! A party P has a ciphertext `C` encrypted under paillier key `N`. P shares `C`
+//! with V and wants to prove that `C` encrypts either 0 or 1, without revealing
+//! which one.
+//!
+//! Given:
+//! - `key` - public key in paillier cryptosystem
+//! - `C = key.encrypt_with(b, rho)` - data to obtain proof about
+//!
+//! Prove:
+//! - `decrypt(C) ∈ {0, 1}`
+//!
+//! Disclosing only: `key`, `C`
+//!
+//! The proof is an OR-composition of two proofs of [encryption of
+//! zero](crate::paillier_encryption_of_zero): one for `C`, and another one for
+//! `C (1 + N)^-1`. Prover simulates the proof for the false statement, and splits the
+//! challenge `e = e0 + e1 mod q` between the two.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use paillier_zk::paillier_encryption_of_bit as p;
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! let security = p::SecurityParams::new((Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover prepares the paillier keys
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key = private_key.encryption_key();
+//!
+//! // 2. Setup: prover encrypts a boolean flag
+//!
+//! let flag = true;
+//! let (C, nonce) = key.encrypt_with_random(&mut rng, &Integer::from(flag))?;
+//!
+//! // 3. Prover computes a non-interactive proof that `C` encrypts a bit:
+//!
+//! let data = p::Data { key, c: &C };
+//! let (commitment, proof) = p::non_interactive::prove(
+//!     shared_state_prover,
+//!     data,
+//!     p::PrivateData { bit: flag, nonce: &nonce },
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data, _: &p::Commitment, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use rug::{Complete, Integer};
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{InvalidProof, PreparedCiphertext};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// q in paper. Security parameter for challenge, which is taken from `[0; q)`
+    pub q: Integer,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(q: Integer) -> Self {
+        Self { q }
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N, public key that C was encrypted on
+    pub key: &'a dyn AnyEncryptionKey,
+    /// C, encryption of a bit
+    pub c: &'a Ciphertext,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// b, plaintext of C
+    pub bit: bool,
+    /// rho, nonce in encryption b -> C
+    pub nonce: &'a Nonce,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    /// `C` and `C (1 + N)^-1`, which encrypt zero if `C` encrypts 0 and 1 respectively
+    c: [PreparedCiphertext; 2],
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        let c0 = PreparedCiphertext::new(data.key, data.c)?;
+        let c1 = PreparedCiphertext::new(data.key, &minus_one(data.key, data.c))?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, c: [c0, c1] })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+/// Homomorphically subtracts 1 from the plaintext of `c`, i.e. computes
+/// `c (1 + N)^-1 = c (1 - N) mod N^2`
+fn minus_one(key: &dyn AnyEncryptionKey, c: &Ciphertext) -> Ciphertext {
+    let one_minus_n = (Integer::ONE - key.n()).complete();
+    (c * one_minus_n).modulo(key.nn())
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment {
+    pub a0: Ciphertext,
+    pub a1: Ciphertext,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    /// Nonce committed to in the true branch
+    pub r: Nonce,
+    /// Challenge of the simulated branch
+    pub e_sim: Integer,
+    /// Response of the simulated branch
+    pub w_sim: Integer,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+///
+/// Challenge of the second branch is `e1 = e - e0 mod q`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub e0: Integer,
+    pub w0: Integer,
+    pub w1: Integer,
+}
+
+impl_encode! { Data<'a> { key, c } }
+impl_encode! { Commitment { a0, a1 } }
+impl_decode! { Commitment { a0, a1 } }
+impl_encode! { Proof { e0, w0, w1 } }
+impl_decode! { Proof { e0, w0, w1 } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("a0", 2 * modulus_bits)
+            .integer("a1", 2 * modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("e0", security.q.significant_bits() as usize)
+            .integer("w0", modulus_bits)
+            .integer("w1", modulus_bits)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use fast_paillier::AnyEncryptionKey;
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_degenerate, fail_if_ne, fail_if_zero_challenge, IntegerExt,
+        InvalidProofReason, PreparedCiphertext,
+    };
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<R: RngCore>(
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        let n = data.key.n();
+        let r = Integer::gen_invertible(n, rng);
+        let a_real = data.key.encrypt_with(&Integer::ZERO, &r)?;
+
+        // Simulated branch: a = w^N c^-e
+        let c_sim = if pdata.bit {
+            data.c.clone()
+        } else {
+            super::minus_one(data.key, data.c)
+        };
+        let e_sim = challenge(security, rng);
+        let w_sim = Integer::gen_invertible(n, rng);
+        let a_sim = data.key.osub(
+            &data.key.encrypt_with(&Integer::ZERO, &w_sim)?,
+            &data.key.omul(&e_sim, &c_sim)?,
+        )?;
+
+        let (a0, a1) = if pdata.bit {
+            (a_sim, a_real)
+        } else {
+            (a_real, a_sim)
+        };
+        Ok((Commitment { a0, a1 }, PrivateCommitment { r, e_sim, w_sim }))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        private_commitment: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        let e_real = (challenge - &private_commitment.e_sim)
+            .complete()
+            .modulo(&security.q);
+        let w_real =
+            data.key
+                .n()
+                .combine(&private_commitment.r, Integer::ONE, pdata.nonce, &e_real)?;
+        let w_sim = private_commitment.w_sim.clone();
+        Ok(if pdata.bit {
+            Proof {
+                e0: private_commitment.e_sim.clone(),
+                w0: w_sim,
+                w1: w_real,
+            }
+        } else {
+            Proof {
+                e0: e_real,
+                w0: w_real,
+                w1: w_sim,
+            }
+        })
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key.n()))?;
+        verify_prepared(&statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key.n()))
+    }
+
+    fn check_prepared(
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if(
+            InvalidProofReason::ChallengeOutOfRange,
+            challenge.cmp0().is_ge() && challenge < &security.q,
+        )?;
+        fail_if_degenerate(data.key.nn(), [&commitment.a0, &commitment.a1])?;
+        crate::common::fail_if_not_coprime(data.key.n(), [&proof.w0, &proof.w1])?;
+        fail_if(
+            InvalidProofReason::RangeCheck(1),
+            proof.e0.cmp0().is_ge() && proof.e0 < security.q,
+        )?;
+        let e1 = (challenge - &proof.e0).complete().modulo(&security.q);
+        crate::common::lap!(Check("challenge split"));
+
+        check_encryption_of_zero(
+            InvalidProofReason::EqualityCheck(2),
+            data.key,
+            &statement.c[0],
+            &commitment.a0,
+            &proof.e0,
+            &proof.w0,
+        )?;
+        crate::common::lap!(Check("plaintext is 0"));
+        check_encryption_of_zero(
+            InvalidProofReason::EqualityCheck(3),
+            data.key,
+            &statement.c[1],
+            &commitment.a1,
+            &e1,
+            &proof.w1,
+        )?;
+        crate::common::lap!(Check("plaintext is 1"));
+
+        Ok(())
+    }
+
+    /// Checks that `key.encrypt(0; w) = a * c^e`
+    fn check_encryption_of_zero(
+        reason: InvalidProofReason,
+        key: &dyn AnyEncryptionKey,
+        c: &PreparedCiphertext,
+        a: &Integer,
+        e: &Integer,
+        w: &Integer,
+    ) -> Result<(), InvalidProof> {
+        let lhs = key
+            .encrypt_with(&Integer::ZERO, w)
+            .map_err(|_| InvalidProofReason::PaillierEnc)?;
+        let rhs = if e.is_zero() {
+            a.clone()
+        } else {
+            let e_at_c = c.omul(key, e)?;
+            key.oadd(a, &e_at_c)
+                .map_err(|_| InvalidProofReason::PaillierOp)?
+        };
+        fail_if_ne(reason, lhs, rhs)?;
+        Ok(())
+    }
+
+    /// Generate random challenge
+    ///
+    /// `security` parameter is used to generate challenge in correct range
+    pub fn challenge<R: RngCore>(security: &SecurityParams, rng: &mut R) -> Challenge {
+        security
+            .q
+            .random_below_ref(&mut fast_paillier::utils::external_rand(rng))
+            .into()
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<R: RngCore, D: Digest>(
+        shared_state: D,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error> {
+        let (comm, pcomm) = super::interactive::commit(data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, security, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D: Digest>(
+        shared_state: D,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(statement, commitment, security, &challenge, proof)
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        transcript.label(b"paillier_zk.paillier_encryption_of_bit");
+        transcript.absorb_value(b"N", data.key.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use rug::{Complete, Integer};
+
+    use crate::common::extract::{challenge_diff, ensure, paillier_nonce};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof, SecurityParams};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub bit: bool,
+        pub nonce: Nonce,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    ///
+    /// Challenges of at least one branch differ, the witness is extracted from that
+    /// branch.
+    pub fn extract(
+        data: Data,
+        security: &SecurityParams,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e1 = |challenge: &Challenge, proof: &Proof| {
+            (challenge - &proof.e0).complete().modulo(&security.q)
+        };
+        let (bit, c, w1, w2, e_diff) = if proof1.e0 != proof2.e0 {
+            let e_diff = challenge_diff(&proof1.e0, &proof2.e0)?;
+            (false, data.c.clone(), &proof1.w0, &proof2.w0, e_diff)
+        } else {
+            let e_diff = challenge_diff(&e1(challenge1, proof1), &e1(challenge2, proof2))?;
+            let c = super::minus_one(data.key, data.c);
+            (true, c, &proof1.w1, &proof2.w1, e_diff)
+        };
+        let nonce = paillier_nonce(data.key.n(), &c, w1, w2, &e_diff)?;
+
+        let expected = data.key.encrypt_with(&Integer::from(bit), &nonce).ok();
+        ensure(expected.as_ref() == Some(data.c))?;
+        Ok(Witness { bit, nonce })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fast_paillier::EncryptionKey;
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::Encode;
+    use crate::common::test::random_key;
+    use crate::common::InvalidProofReason;
+
+    struct Statement {
+        key: EncryptionKey,
+        bit: bool,
+        nonce: Integer,
+        c: Integer,
+    }
+
+    impl Statement {
+        fn data(&self) -> super::Data<'_> {
+            super::Data {
+                key: &self.key,
+                c: &self.c,
+            }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_> {
+            super::PrivateData {
+                bit: self.bit,
+                nonce: &self.nonce,
+            }
+        }
+    }
+
+    /// Encrypts `plaintext`, prover claims it's `bit`
+    fn statement(rng: &mut rand_dev::DevRng, plaintext: u32, bit: bool) -> Statement {
+        let key = random_key(rng).unwrap().encryption_key().clone();
+        let (c, nonce) = key
+            .encrypt_with_random(rng, &Integer::from(plaintext))
+            .unwrap();
+        Statement { key, bit, nonce, c }
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new((Integer::ONE << 128_u32).complete())
+    }
+
+    fn run(statement: &Statement) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        let modulus_bits = statement.key.n().significant_bits() as usize;
+        assert!(super::Commitment::schema(&security, modulus_bits)
+            .conforms(&commitment.to_canonical_bytes()));
+        assert!(super::Proof::schema(&security, modulus_bits).conforms(&proof.to_canonical_bytes()));
+
+        super::non_interactive::verify(
+            shared_state,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        run(&statement(&mut rng, 0, false)).expect("proof for 0 failed");
+        run(&statement(&mut rng, 1, true)).expect("proof for 1 failed");
+    }
+
+    #[test]
+    fn failing() {
+        let mut rng = rand_dev::DevRng::new();
+        for (plaintext, bit) in [(2, false), (2, true), (1, false), (0, true)] {
+            let r = run(&statement(&mut rng, plaintext, bit)).map_err(|e| e.reason());
+            let expected = if bit {
+                InvalidProofReason::EqualityCheck(3)
+            } else {
+                InvalidProofReason::EqualityCheck(2)
+            };
+            assert_eq!(r, Err(expected), "plaintext {plaintext}, claimed {bit}");
+        }
+    }
+
+    #[test]
+    fn challenge_split() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng, 1, true);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(data, pdata, &security, &mut rng).unwrap();
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof =
+            super::interactive::prove(data, pdata, &security, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(data, &commitment, &security, &challenge, &proof)
+            .expect("proof failed");
+
+        // Proof is bound to the challenge
+        let other = (&challenge + 1u8).complete().modulo(&security.q);
+        let r = super::interactive::verify(data, &commitment, &security, &other, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::EqualityCheck(3))
+        );
+
+        // Challenge of the first branch out of range is rejected
+        let mut tampered = proof.clone();
+        tampered.e0 += &security.q;
+        let r = super::interactive::verify(data, &commitment, &security, &challenge, &tampered);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::RangeCheck(1))
+        );
+    }
+
+    #[test]
+    fn degenerate_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng, 0, false);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof =
+            super::interactive::prove(data, pdata, &security, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Challenge outside of `[0; q)` is rejected even for an honest proof
+        for challenge in [Integer::from(-1), security.q.clone()] {
+            let proof = super::interactive::prove(data, pdata, &security, &pcommitment, &challenge)
+                .unwrap();
+            let r = super::interactive::verify(data, &commitment, &security, &challenge, &proof);
+            assert_eq!(
+                r.map_err(|e| e.reason()).err(),
+                Some(InvalidProofReason::ChallengeOutOfRange)
+            );
+        }
+
+        // Commitment equal to one is rejected
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof =
+            super::interactive::prove(data, pdata, &security, &pcommitment, &challenge).unwrap();
+        let mut degenerate = commitment.clone();
+        degenerate.a1 = Integer::from(1);
+        let r = super::interactive::verify(data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        for bit in [false, true] {
+            let statement = statement(&mut rng, bit.into(), bit);
+            let (data, pdata) = (statement.data(), statement.pdata());
+
+            let (_commitment, pcommitment) =
+                super::interactive::commit(data, pdata, &security, &mut rng).unwrap();
+            let e1 = super::interactive::challenge(&security, &mut rng);
+            let e2 = super::interactive::challenge(&security, &mut rng);
+            let proof1 =
+                super::interactive::prove(data, pdata, &security, &pcommitment, &e1).unwrap();
+            let proof2 =
+                super::interactive::prove(data, pdata, &security, &pcommitment, &e2).unwrap();
+
+            let witness =
+                super::extract::extract(data, &security, &e1, &proof1, &e2, &proof2).unwrap();
+            assert_eq!(witness.bit, bit);
+            assert_eq!(witness.nonce, statement.nonce);
+        }
+    }
+}
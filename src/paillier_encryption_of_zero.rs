@@ -0,0 +1,674 @@
+//! ZK-proof that paillier ciphertext encrypts zero
+//!
+//! ## Description
+//!
+//! A party P has a ciphertext `C` encrypted under paillier key `N`. P shares `C`
+//! with V and wants to prove that `C` is an encryption of zero, by showing the
+//! knowledge of the nonce it was encrypted with.
+//!
+//! It can be used to prove that a blinded difference of two values vanishes, or that
+//! one ciphertext is a rerandomization of another (their difference encrypts zero).
+//! The latter is provided by [`rerandomization`] proof. A ciphertext can be opened to
+//! a disclosed plaintext the same way, see [`opening`] proof.
+//!
+//! Given:
+//! - `key` - public key in paillier cryptosystem
+//! - `C = key.encrypt_with(0, rho)` - data to obtain proof about
+//!
+//! Prove:
+//! - `decrypt(C) = 0`
+//!
+//! Disclosing only: `key`, `C`
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use fast_paillier::AnyEncryptionKey;
+//! use paillier_zk::paillier_encryption_of_zero as p;
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! let security = p::SecurityParams::new((Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover prepares the paillier keys
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key = private_key.encryption_key();
+//!
+//! // 2. Setup: prover rerandomizes some ciphertext `X` as `Y`, so `Y - X` encrypts zero
+//!
+//! let (X, _) = key.encrypt_with_random(&mut rng, &Integer::from(42))?;
+//! let (rerandomization, nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO)?;
+//! let Y = key.oadd(&X, &rerandomization)?;
+//! let C = key.osub(&Y, &X)?;
+//!
+//! // 3. Prover computes a non-interactive proof that `C` encrypts zero:
+//!
+//! let data = p::Data { key, c: &C };
+//! let (commitment, proof) = p::non_interactive::prove(
+//!     shared_state_prover,
+//!     data,
+//!     p::PrivateData { nonce: &nonce },
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data, _: &p::Commitment, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{InvalidProof, PreparedCiphertext};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub mod opening;
+pub mod rerandomization;
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// q in paper. Security parameter for challenge
+    pub q: Integer,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(q: Integer) -> Self {
+        Self { q }
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N, public key that C was encrypted on
+    pub key: &'a dyn AnyEncryptionKey,
+    /// C, encryption of zero
+    pub c: &'a Ciphertext,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// rho, nonce in encryption 0 -> C
+    pub nonce: &'a Nonce,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    c: PreparedCiphertext,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        let c = PreparedCiphertext::new(data.key, data.c)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, c })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment {
+    pub a: Ciphertext,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub r: Nonce,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub w: Integer,
+}
+
+impl_encode! { Data<'a> { key, c } }
+impl_encode! { Commitment { a } }
+impl_decode! { Commitment { a } }
+impl_encode! { Proof { w } }
+impl_decode! { Proof { w } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default().integer("a", 2 * modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default().integer("w", modulus_bits)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use fast_paillier::AnyEncryptionKey;
+    use rand_core::RngCore;
+    use rug::Integer;
+
+    use crate::common::{
+        fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne, fail_if_zero_challenge,
+        IntegerExt, InvalidProofReason, PreparedCiphertext,
+    };
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<R: RngCore>(
+        data: Data,
+        _pdata: PrivateData,
+        _security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        let r = Integer::gen_invertible(data.key.n(), rng);
+        crate::common::observe_randomness!(&r);
+        let a = data.key.encrypt_with(&Integer::ZERO, &r)?;
+        Ok((Commitment { a }, PrivateCommitment { r }))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        data: Data,
+        pdata: PrivateData,
+        private_commitment: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        let w =
+            data.key
+                .n()
+                .combine(&private_commitment.r, Integer::ONE, pdata.nonce, challenge)?;
+        Ok(Proof { w })
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key.n()))?;
+        verify_prepared(&statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(
+            statement.data.key,
+            &statement.c,
+            commitment,
+            security,
+            challenge,
+            proof,
+        )
+        .map_err(|err| err.with_key(statement.data.key.n()))
+    }
+
+    /// Checks the proof that prepared ciphertext `c` encrypts zero under `key`
+    pub(super) fn check_prepared(
+        key: &dyn AnyEncryptionKey,
+        c: &PreparedCiphertext,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(key.nn(), [&commitment.a])?;
+        crate::common::fail_if_not_coprime(key.n(), [&proof.w])?;
+        {
+            let lhs = key
+                .encrypt_with(&Integer::ZERO, &proof.w)
+                .map_err(|_| InvalidProofReason::PaillierEnc)?;
+            let rhs = {
+                let e_at_c = c.omul(key, challenge)?;
+                key.oadd(&commitment.a, &e_at_c)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("encryption of zero"));
+
+        Ok(())
+    }
+
+    /// Generate random challenge
+    ///
+    /// `security` parameter is used to generate challenge in correct range
+    pub fn challenge<R: RngCore>(security: &SecurityParams, rng: &mut R) -> Challenge {
+        Integer::from_rng_pm(&security.q, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<R: RngCore, D: Digest>(
+        shared_state: D,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error> {
+        let (comm, pcomm) = super::interactive::commit(data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D: Digest>(
+        shared_state: D,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(statement, commitment, security, &challenge, proof)
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        transcript.label(b"paillier_zk.paillier_encryption_of_zero");
+        transcript.absorb_value(b"N", data.key.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use rug::Integer;
+
+    use crate::common::extract::{challenge_diff, ensure, paillier_nonce};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub nonce: Nonce,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let nonce = paillier_nonce(data.key.n(), data.c, &proof1.w, &proof2.w, &e_diff)?;
+
+        let c = data.key.encrypt_with(&Integer::ZERO, &nonce).ok();
+        ensure(c.as_ref() == Some(data.c))?;
+        Ok(Witness { nonce })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fast_paillier::EncryptionKey;
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::Encode;
+    use crate::common::test::random_key;
+    use crate::common::InvalidProofReason;
+
+    struct Statement {
+        key: EncryptionKey,
+        nonce: Integer,
+        c: Integer,
+    }
+
+    impl Statement {
+        fn data(&self) -> super::Data<'_> {
+            super::Data {
+                key: &self.key,
+                c: &self.c,
+            }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_> {
+            super::PrivateData { nonce: &self.nonce }
+        }
+    }
+
+    fn statement(rng: &mut rand_dev::DevRng, plaintext: Integer) -> Statement {
+        let key = random_key(rng).unwrap().encryption_key().clone();
+        let (c, nonce) = key.encrypt_with_random(rng, &plaintext).unwrap();
+        Statement { key, nonce, c }
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new((Integer::ONE << 128_u32).complete())
+    }
+
+    fn run(statement: &Statement) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        let modulus_bits = statement.key.n().significant_bits() as usize;
+        assert!(super::Commitment::schema(&security, modulus_bits)
+            .conforms(&commitment.to_canonical_bytes()));
+        assert!(super::Proof::schema(&security, modulus_bits).conforms(&proof.to_canonical_bytes()));
+
+        super::non_interactive::verify(
+            shared_state,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let statement = statement(&mut rng, Integer::ZERO);
+        run(&statement).expect("proof failed");
+    }
+
+    #[test]
+    fn failing() {
+        let mut rng = rand_dev::DevRng::new();
+        let statement = statement(&mut rng, Integer::from(1));
+        let r = run(&statement).map_err(|e| e.reason());
+        assert_eq!(r, Err(InvalidProofReason::EqualityCheck(1)));
+    }
+
+    #[test]
+    fn negative_challenge() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng, Integer::ZERO);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(data, pdata, &security, &mut rng).unwrap();
+        let challenge = -super::interactive::challenge(&security, &mut rng).abs() - 1;
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(data, &commitment, &security, &challenge, &proof)
+            .expect("proof with negative challenge failed");
+
+        // Proof is bound to the sign of the challenge
+        let r = super::interactive::verify(
+            data,
+            &commitment,
+            &security,
+            &(-&challenge).complete(),
+            &proof,
+        );
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn degenerate_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng, Integer::ZERO);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Challenge outside of `[-q; q]` is rejected even for an honest proof
+        let above_q = (&security.q + 1u8).complete();
+        for challenge in [-above_q.clone(), above_q] {
+            let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+            let r = super::interactive::verify(data, &commitment, &security, &challenge, &proof);
+            assert_eq!(
+                r.map_err(|e| e.reason()).err(),
+                Some(InvalidProofReason::ChallengeOutOfRange)
+            );
+        }
+
+        // Commitment equal to one is rejected
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let mut degenerate = commitment.clone();
+        degenerate.a = Integer::from(1);
+        let r = super::interactive::verify(data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng, Integer::ZERO);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.nonce, statement.nonce);
+    }
+}
@@ -0,0 +1,501 @@
+//! ZK-proof that paillier ciphertext is a rerandomization of another one
+//!
+//! ## Description
+//!
+//! A party P has a ciphertext `C` encrypted under paillier key `N`, and refreshes it
+//! as `C' = C + key.encrypt_with(0, rho)` using homomorphic addition. P wants to
+//! prove that `C'` encrypts the same plaintext as `C`, without disclosing `rho` nor
+//! the plaintext.
+//!
+//! `C' - C` is an encryption of zero with nonce `rho`, so the proof is the
+//! [encryption of zero](super) proof about it. Commitment and proof are the same,
+//! but the challenge is bound to both ciphertexts.
+//!
+//! Given:
+//! - `key` - public key in paillier cryptosystem
+//! - `C` - original ciphertext
+//! - `C' = C + key.encrypt_with(0, rho)` - data to obtain proof about
+//!
+//! Prove:
+//! - `decrypt(C') = decrypt(C)`
+//!
+//! Disclosing only: `key`, `C`, `C'`
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use paillier_zk::paillier_encryption_of_zero as p;
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! let security = p::SecurityParams::new((Integer::ONE << 128_u32).complete());
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key = private_key.encryption_key();
+//!
+//! // Prover refreshes the ciphertext between protocol rounds
+//! let (c, _) = key.encrypt_with_random(&mut rng, &Integer::from(42))?;
+//! let (rerandomization, nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO)?;
+//! let c_prime = key.oadd(&c, &rerandomization)?;
+//!
+//! let data = p::rerandomization::Data {
+//!     key,
+//!     c: &c,
+//!     c_prime: &c_prime,
+//! };
+//! let (commitment, proof) = p::rerandomization::non_interactive::prove(
+//!     shared_state_prover,
+//!     data,
+//!     p::rerandomization::PrivateData { nonce: &nonce },
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // Verifier checks the proof
+//! p::rerandomization::non_interactive::verify(
+//!     shared_state_verifier,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+
+use crate::common::encoding::impl_encode;
+use crate::common::{InvalidProofReason, PreparedCiphertext};
+use crate::InvalidProof;
+
+pub use super::{Challenge, Commitment, PrivateCommitment, Proof, SecurityParams};
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N, public key that both ciphertexts are encrypted on
+    pub key: &'a dyn AnyEncryptionKey,
+    /// C, original ciphertext
+    pub c: &'a Ciphertext,
+    /// C', rerandomized ciphertext
+    pub c_prime: &'a Ciphertext,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// rho, nonce in encryption 0 -> C' - C
+    pub nonce: &'a Nonce,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>);
+
+/// Statement validated and prepared for verification in advance
+///
+/// See [`super::PreparedStatement`]
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    /// `C' - C`, encryption of zero
+    difference: PreparedCiphertext,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        Self::prepare(data).map_err(|err| err.with_key(data.key.n()))
+    }
+
+    fn prepare(data: Data<'a>) -> Result<Self, InvalidProof> {
+        crate::common::fail_if_not_coprime(data.key.n(), [data.c, data.c_prime])?;
+        let difference = difference(data).map_err(|_| InvalidProofReason::PaillierOp)?;
+        let difference = PreparedCiphertext::new(data.key, &difference)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, difference })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+impl_encode! { Data<'a> { key, c, c_prime } }
+
+/// `C' - C`
+fn difference(data: Data) -> Result<Ciphertext, fast_paillier::Error> {
+    data.key.osub(data.c_prime, data.c)
+}
+
+/// Statement of encryption of zero about the difference, which has nonce `rho`
+fn zero_data<'a>(data: Data<'a>, difference: &'a Ciphertext) -> super::Data<'a> {
+    super::Data {
+        key: data.key,
+        c: difference,
+    }
+}
+
+fn zero_pdata(pdata: PrivateData) -> super::PrivateData {
+    super::PrivateData { nonce: pdata.nonce }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use rand_core::RngCore;
+
+    use crate::Error;
+
+    use super::{
+        Challenge, Commitment, Data, InvalidProof, PreparedStatement, PrivateCommitment,
+        PrivateData, Proof, SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<R: RngCore>(
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        let difference = super::difference(data)?;
+        super::super::interactive::commit(
+            super::zero_data(data, &difference),
+            super::zero_pdata(pdata),
+            security,
+            rng,
+        )
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        data: Data,
+        pdata: PrivateData,
+        private_commitment: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        let difference = super::difference(data)?;
+        super::super::interactive::prove(
+            super::zero_data(data, &difference),
+            super::zero_pdata(pdata),
+            private_commitment,
+            challenge,
+        )
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data)?;
+        verify_prepared(&statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let key = statement.data.key;
+        super::super::interactive::check_prepared(
+            key,
+            &statement.difference,
+            commitment,
+            security,
+            challenge,
+            proof,
+        )
+        .map_err(|err| err.with_key(key.n()))
+    }
+
+    /// Generate random challenge
+    ///
+    /// `security` parameter is used to generate challenge in correct range
+    pub fn challenge<R: RngCore>(security: &SecurityParams, rng: &mut R) -> Challenge {
+        super::super::interactive::challenge(security, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::Error;
+
+    use super::{
+        Challenge, Commitment, Data, InvalidProof, PreparedStatement, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<R: RngCore, D: Digest>(
+        shared_state: D,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error> {
+        let (comm, pcomm) = super::interactive::commit(data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D: Digest>(
+        shared_state: D,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(statement, commitment, security, &challenge, proof)
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        transcript.label(b"paillier_zk.paillier_encryption_of_zero.rerandomization");
+        transcript.absorb_value(b"N", data.key.n());
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_value(b"C'", data.c_prime);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use crate::common::extract::ExtractionErrorReason;
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    pub use super::super::extract::Witness;
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let difference =
+            super::difference(data).map_err(|_| ExtractionErrorReason::WitnessMismatch)?;
+        super::super::extract::extract(
+            super::zero_data(data, &difference),
+            challenge1,
+            proof1,
+            challenge2,
+            proof2,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::test::random_key;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new((Integer::ONE << 128_u32).complete())
+    }
+
+    /// Rerandomizes encryption of a random plaintext, adding `shift` to it, and proves
+    /// that the result is a rerandomization
+    fn run(shift: Integer) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let private_key = random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+
+        let plaintext = Integer::from_rng_pm(key.half_n(), &mut rng);
+        let (c, _) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let (rerandomization, nonce) = key.encrypt_with_random(&mut rng, &shift).unwrap();
+        let c_prime = key.oadd(&c, &rerandomization).unwrap();
+
+        let data = super::Data {
+            key,
+            c: &c,
+            c_prime: &c_prime,
+        };
+        let pdata = super::PrivateData { nonce: &nonce };
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) =
+            super::non_interactive::prove(shared_state.clone(), data, pdata, &security, &mut rng)
+                .unwrap();
+        super::non_interactive::verify(shared_state, data, &commitment, &security, &proof)
+    }
+
+    #[test]
+    fn passing() {
+        run(Integer::ZERO).expect("proof failed");
+    }
+
+    #[test]
+    fn failing() {
+        let r = run(Integer::from(1)).map_err(|e| e.reason());
+        assert_eq!(r, Err(InvalidProofReason::EqualityCheck(1)));
+    }
+
+    #[test]
+    fn not_coprime() {
+        let mut rng = rand_dev::DevRng::new();
+        let private_key = random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+        let (c, _) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+
+        // C' divisible by N can't be a rerandomization of anything
+        let c_prime = key.n() * Integer::from(2);
+        let data = super::Data {
+            key,
+            c: &c,
+            c_prime: &c_prime,
+        };
+        let r = super::PreparedStatement::new(data).map_err(|e| e.reason());
+        assert_eq!(r.err(), Some(InvalidProofReason::NotCoprime));
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let private_key = random_key(&mut rng).unwrap();
+        let key = private_key.encryption_key();
+
+        let (c, _) = key
+            .encrypt_with_random(&mut rng, &Integer::from(42))
+            .unwrap();
+        let (rerandomization, nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+        let c_prime = key.oadd(&c, &rerandomization).unwrap();
+        let data = super::Data {
+            key,
+            c: &c,
+            c_prime: &c_prime,
+        };
+        let pdata = super::PrivateData { nonce: &nonce };
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.nonce, nonce);
+    }
+}
@@ -0,0 +1,755 @@
+//! ZK-proof of paillier multiplication. Called Пmul or Rmul in the CGGMP21 paper.
+//!
+//! ## Description
+//!
+//! A party P has `key`, `pkey` - public and private keys in paillier
+//! cryptosystem, ciphertext `X = key.encrypt(x)`, and some ciphertext `Y`. P
+//! obtains `C` by multiplying `Y` at `x` homomorphically and rerandomizing it, so
+//! that `C` encrypts the product of plaintexts of `X` and `Y`.
+//!
+//! Given:
+//! - `key`, `pkey` - pair of public and private keys in paillier cryptosystem
+//! - `X = key.encrypt_with(x, rho_x)`
+//! - `Y`
+//! - `C = Y^x rho^N mod N^2`
+//!
+//! Prove:
+//! - `decrypt(C) = decrypt(X) * decrypt(Y) mod N`
+//!
+//! Disclosing only: `key`, `X`, `Y`, `C`
+//!
+//! `x = 0` is a valid witness: `X` and `C = rho^N` then encrypt zero. Note that `omul`
+//! rejects zero scalar, so `C` has to be obtained with `encrypt_with(0, rho)` in this
+//! case.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use fast_paillier::AnyEncryptionKey;
+//! use paillier_zk::{paillier_multiplication as p, IntegerExt};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! let security = p::SecurityParams::new((Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover prepares the paillier keys
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key = private_key.encryption_key();
+//!
+//! // 2. Setup: prover encrypts `x` as `X`, and multiplies some `Y` at `x`
+//!
+//! let x = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+//! let (X, nonce_x) = key.encrypt_with_random(&mut rng, &x)?;
+//! let (Y, _) = key.encrypt_with_random(&mut rng, &Integer::from(42))?;
+//! let (rerandomization, nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO)?;
+//! let C = key.oadd(&key.omul(&x, &Y)?, &rerandomization)?;
+//!
+//! // 3. Prover computes a non-interactive proof that `C` encrypts the product:
+//!
+//! let data = p::Data { key, x: &X, y: &Y, c: &C };
+//! let (commitment, proof) = p::non_interactive::prove(
+//!     shared_state_prover,
+//!     data,
+//!     p::PrivateData { x: &x, nonce: &nonce, nonce_x: &nonce_x },
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data, _: &p::Commitment, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{InvalidProof, PreparedCiphertext};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// q in paper. Security parameter for challenge
+    pub q: Integer,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(q: Integer) -> Self {
+        Self { q }
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N in paper, public key that X, Y and C were encrypted on
+    pub key: &'a dyn AnyEncryptionKey,
+    /// X in paper, encryption of x
+    pub x: &'a Ciphertext,
+    /// Y in paper
+    pub y: &'a Ciphertext,
+    /// C in paper, Y multiplied at x and rerandomized
+    pub c: &'a Ciphertext,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// x in paper, plaintext of X
+    pub x: &'a Integer,
+    /// rho in paper, nonce that rerandomizes C
+    pub nonce: &'a Nonce,
+    /// rho_x in paper, nonce in encryption x -> X
+    pub nonce_x: &'a Nonce,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    x: PreparedCiphertext,
+    c: PreparedCiphertext,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        let x = PreparedCiphertext::new(data.key, data.x)?;
+        let c = PreparedCiphertext::new(data.key, data.c)?;
+        PreparedCiphertext::new(data.key, data.y)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, x, c })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment {
+    pub a: Ciphertext,
+    pub b: Ciphertext,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub alpha: Integer,
+    pub r: Nonce,
+    pub s: Nonce,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub z: Integer,
+    pub u: Integer,
+    pub v: Integer,
+}
+
+impl_encode! { Data<'a> { key, x, y, c } }
+impl_encode! { Commitment { a, b } }
+impl_decode! { Commitment { a, b } }
+impl_encode! { Proof { z, u, v } }
+impl_decode! { Proof { z, u, v } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("a", 2 * modulus_bits)
+            .integer("b", 2 * modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("z", modulus_bits)
+            .integer("u", modulus_bits)
+            .integer("v", modulus_bits)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne, fail_if_zero_challenge,
+        IntegerExt, InvalidProofReason,
+    };
+    use crate::{BadExponent, Error, InvalidProof};
+
+    use super::{
+        Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<R: RngCore>(
+        data: Data,
+        _pdata: PrivateData,
+        _security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        let n = data.key.n();
+        let alpha = Integer::from_rng_pm(&(n >> 1_u32).complete(), rng);
+        let r = Integer::gen_invertible(n, rng);
+        let s = Integer::gen_invertible(n, rng);
+        crate::common::observe_randomness!(&alpha);
+
+        let a = data.key.oadd(
+            &data.key.omul(&alpha, data.y)?,
+            &data.key.encrypt_with(&Integer::ZERO, &r)?,
+        )?;
+        let b = data.key.encrypt_with(&alpha, &s)?;
+
+        Ok((Commitment { a, b }, PrivateCommitment { alpha, r, s }))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        data: Data,
+        pdata: PrivateData,
+        private_commitment: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        let n = data.key.n();
+        // z = alpha + e x is reduced modulo N to hide x. Reduction subtracts k N from
+        // the exponent of Y, which is compensated by multiplying u at Y^k
+        let z_unreduced = (&private_commitment.alpha + challenge * pdata.x).complete();
+        let mut z = z_unreduced.modulo_ref(n).complete();
+        if z > (n >> 1_u32).complete() {
+            z -= n;
+        }
+        let k = (z_unreduced - &z) / n;
+
+        let pow_mod_n = |x: &Integer, e: &Integer| -> Result<Integer, BadExponent> {
+            Ok(x.pow_mod_ref(e, n).ok_or(BadExponent::undefined())?.into())
+        };
+        let u = (&private_commitment.r
+            * pow_mod_n(pdata.nonce, challenge)?
+            * pow_mod_n(&data.y.modulo_ref(n).complete(), &k)?)
+        .modulo(n);
+        let v = (&private_commitment.s * pow_mod_n(pdata.nonce_x, challenge)?).modulo(n);
+        Ok(Proof { z, u, v })
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key.n()))?;
+        verify_prepared(&statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key.n()))
+    }
+
+    fn check_prepared(
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(data.key.nn(), [&commitment.a, &commitment.b])?;
+        crate::common::fail_if_not_coprime(data.key.n(), [&proof.u, &proof.v])?;
+        {
+            let lhs = {
+                let y_to_z: Integer = data
+                    .y
+                    .pow_mod_ref(&proof.z, data.key.nn())
+                    .ok_or(InvalidProofReason::PaillierOp)?
+                    .into();
+                let u_to_n = data
+                    .key
+                    .encrypt_with(&Integer::ZERO, &proof.u)
+                    .map_err(|_| InvalidProofReason::PaillierEnc)?;
+                data.key
+                    .oadd(&y_to_z, &u_to_n)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            let rhs = {
+                let e_at_c = statement.c.omul(data.key, challenge)?;
+                data.key
+                    .oadd(&commitment.a, &e_at_c)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("multiplication"));
+
+        {
+            let lhs = data
+                .key
+                .encrypt_with(&proof.z, &proof.v)
+                .map_err(|_| InvalidProofReason::PaillierEnc)?;
+            let rhs = {
+                let e_at_x = statement.x.omul(data.key, challenge)?;
+                data.key
+                    .oadd(&commitment.b, &e_at_x)
+                    .map_err(|_| InvalidProofReason::PaillierOp)?
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(2), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("paillier encryption"));
+
+        Ok(())
+    }
+
+    /// Generate random challenge
+    ///
+    /// `security` parameter is used to generate challenge in correct range
+    pub fn challenge<R: RngCore>(security: &SecurityParams, rng: &mut R) -> Challenge {
+        Integer::from_rng_pm(&security.q, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<R: RngCore, D: Digest>(
+        shared_state: D,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error> {
+        let (comm, pcomm) = super::interactive::commit(data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D: Digest>(
+        shared_state: D,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(statement, commitment, security, &challenge, proof)
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge {
+        transcript.label(b"paillier_zk.paillier_multiplication");
+        transcript.absorb_value(b"N", data.key.n());
+        transcript.absorb_value(b"X", data.x);
+        transcript.absorb_value(b"Y", data.y);
+        transcript.absorb_value(b"C", data.c);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fast_paillier::EncryptionKey;
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::Encode;
+    use crate::common::test::random_key;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    struct Statement {
+        key: EncryptionKey,
+        x: Integer,
+        nonce: Integer,
+        nonce_x: Integer,
+        cx: Integer,
+        cy: Integer,
+        c: Integer,
+    }
+
+    impl Statement {
+        fn data(&self) -> super::Data<'_> {
+            super::Data {
+                key: &self.key,
+                x: &self.cx,
+                y: &self.cy,
+                c: &self.c,
+            }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_> {
+            super::PrivateData {
+                x: &self.x,
+                nonce: &self.nonce,
+                nonce_x: &self.nonce_x,
+            }
+        }
+    }
+
+    fn statement<R: rand_core::RngCore + rand_core::CryptoRng>(
+        rng: &mut R,
+        y_factor: Integer,
+    ) -> Statement {
+        let key = random_key(rng).unwrap().encryption_key().clone();
+        let x = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), rng);
+        let y = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), rng);
+        let (cx, nonce_x) = key.encrypt_with_random(rng, &x).unwrap();
+        let (cy, _) = key.encrypt_with_random(rng, &y).unwrap();
+        let cy_multiplied = key.omul(&y_factor, &cy).unwrap();
+        let nonce = Integer::gen_invertible(key.n(), rng);
+        let c = key
+            .oadd(
+                &key.omul(&x, &cy_multiplied).unwrap(),
+                &key.encrypt_with(&Integer::ZERO, &nonce).unwrap(),
+            )
+            .unwrap();
+        Statement {
+            key,
+            x,
+            nonce,
+            nonce_x,
+            cx,
+            cy,
+            c,
+        }
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new((Integer::ONE << 128_u32).complete())
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng, Integer::from(1));
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::non_interactive::verify(
+            shared_state,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+        .expect("proof failed");
+
+        let modulus_bits = statement.key.n().significant_bits() as usize;
+        assert!(super::Commitment::schema(&security, modulus_bits)
+            .conforms(&commitment.to_canonical_bytes()));
+        assert!(super::Proof::schema(&security, modulus_bits).conforms(&proof.to_canonical_bytes()));
+    }
+
+    #[test]
+    fn zero_witness() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        // x = 0: both X and C encrypt zero
+        let mut statement = statement(&mut rng, Integer::from(1));
+        let (cx, nonce_x) = statement
+            .key
+            .encrypt_with_random(&mut rng, &Integer::ZERO)
+            .unwrap();
+        statement.x = Integer::ZERO;
+        statement.cx = cx;
+        statement.nonce_x = nonce_x;
+        statement.c = statement
+            .key
+            .encrypt_with(&Integer::ZERO, &statement.nonce)
+            .unwrap();
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::non_interactive::verify(
+            shared_state,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+        .expect("proof failed");
+    }
+
+    #[test]
+    fn failing() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        // C encrypts x * 2y instead of x * y
+        let statement = statement(&mut rng, Integer::from(2));
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let r = super::non_interactive::verify(
+            shared_state,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+        .map_err(|e| e.reason());
+        assert_eq!(r, Err(InvalidProofReason::EqualityCheck(1)));
+    }
+
+    #[test]
+    fn negative_challenge() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng, Integer::from(1));
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(data, pdata, &security, &mut rng).unwrap();
+        let challenge = -super::interactive::challenge(&security, &mut rng).abs() - 1;
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        super::interactive::verify(data, &commitment, &security, &challenge, &proof)
+            .expect("proof with negative challenge failed");
+
+        // Proof is bound to the sign of the challenge
+        let r = super::interactive::verify(
+            data,
+            &commitment,
+            &security,
+            &(-&challenge).complete(),
+            &proof,
+        );
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn degenerate_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = statement(&mut rng, Integer::from(1));
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Commitment equal to one is rejected
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let mut degenerate = commitment.clone();
+        degenerate.b = Integer::from(1);
+        let r = super::interactive::verify(data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+}
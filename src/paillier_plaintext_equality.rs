@@ -0,0 +1,896 @@
+//! ZK-proof of plaintext equality under two different paillier keys
+//!
+//! ## Description
+//!
+//! A party P has encrypted a number x as C0 under key N0 and as C1 under key N1. P
+//! shares C0 and C1 with V and wants to prove that both ciphertexts encrypt the same
+//! plaintext, and that the plaintext (i.e. x) is at most l bits, without revealing x.
+//!
+//! Given:
+//! - `key0`, `key1` - public keys in paillier cryptosystem
+//! - `C0 = key0.encrypt(x)` and `C1 = key1.encrypt(x)` - data to obtain proof about
+//!
+//! Prove:
+//! - `decrypt0(C0) = decrypt1(C1)`
+//! - `bitsize(x) <= l`
+//!
+//! Disclosing only: `key0`, `key1`, `C0`, `C1`
+//!
+//! Both keys must be larger than `2^(l + epsilon)`, otherwise the plaintexts may be
+//! reduced differently modulo N0 and N1.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use paillier_zk::{paillier_plaintext_equality as p, IntegerExt};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #         someone_encryption_key0: fast_paillier::EncryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover knows both paillier keys
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key0 = private_key.encryption_key();
+//! let key1 = pregenerated::someone_encryption_key0();
+//!
+//! // 2. Setup: prover has some plaintext `x` and encrypts it under both keys
+//!
+//! let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+//! let (c0, nonce0) = key0.encrypt_with_random(&mut rng, &x)?;
+//! let (c1, nonce1) = key1.encrypt_with_random(&mut rng, &x)?;
+//!
+//! // 3. Prover computes a non-interactive proof that both ciphertexts encrypt the
+//! //    same plaintext:
+//!
+//! let data = p::Data {
+//!     key0,
+//!     c0: &c0,
+//!     key1: &key1,
+//!     c1: &c1,
+//! };
+//! let pdata = p::PrivateData {
+//!     x: &x,
+//!     nonce0: &nonce0,
+//!     nonce1: &nonce1,
+//! };
+//! let (commitment, proof) =
+//!     p::non_interactive::prove(shared_state_prover, &aux, data, pdata, &security, &mut rng)?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data, _: &p::Commitment, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{InvalidProof, PreparedCiphertext};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::Aux;
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// l in paper, bit size of +-plaintext
+    pub l: usize,
+    /// Epsilon in paper, slackness parameter
+    pub epsilon: usize,
+    /// q in paper. Security parameter for challenge
+    pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// Absorbing ring-pedersen parameters (kilobytes) into every challenge is redundant
+    /// when counterparties agree on them once, e.g. per epoch. `aux_digest` must be
+    /// obtained with [`Aux::digest`] from the same aux that is used to prove and verify.
+    /// Prover and verifier must both enable it, otherwise the proof doesn't verify.
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 512, (Integer::ONE << 128_u32).complete())
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N0, public key that C0 was encrypted on
+    pub key0: &'a dyn AnyEncryptionKey,
+    /// C0, x encrypted on N0
+    pub c0: &'a Ciphertext,
+    /// N1, public key that C1 was encrypted on
+    pub key1: &'a dyn AnyEncryptionKey,
+    /// C1, x encrypted on N1
+    pub c1: &'a Ciphertext,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// x, plaintext of C0 and C1
+    pub x: &'a Integer,
+    /// Nonce in encryption x -> C0
+    pub nonce0: &'a Nonce,
+    /// Nonce in encryption x -> C1
+    pub nonce1: &'a Nonce,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+    c0: PreparedCiphertext,
+    c1: PreparedCiphertext,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        let c0 = PreparedCiphertext::new(data.key0, data.c0)?;
+        let c1 = PreparedCiphertext::new(data.key1, data.c1)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, c0, c1 })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment {
+    pub s: Integer,
+    pub a0: Ciphertext,
+    pub a1: Ciphertext,
+    pub d: Integer,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub alpha: Integer,
+    pub mu: Integer,
+    pub r0: Nonce,
+    pub r1: Nonce,
+    pub gamma: Integer,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub z1: Integer,
+    pub w0: Integer,
+    pub w1: Integer,
+    pub z3: Integer,
+}
+
+impl_encode! { Data<'a> { key0, c0, key1, c1 } }
+impl_encode! { Commitment { s, a0, a1, d } }
+impl_decode! { Commitment { s, a0, a1, d } }
+impl_encode! { Proof { z1, w0, w1, z3 } }
+impl_decode! { Proof { z1, w0, w1, z3 } }
+
+impl Commitment {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("s", modulus_bits)
+            .integer("a0", 2 * modulus_bits)
+            .integer("a1", 2 * modulus_bits)
+            .integer("d", modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        let z3 = z1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .integer("w0", modulus_bits)
+            .integer("w1", modulus_bits)
+            .integer("z3", z3)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use fast_paillier::{AnyEncryptionKey, Ciphertext};
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProofReason,
+    };
+    use crate::{Error, InvalidProof};
+
+    use crate::common::PreparedCiphertext;
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<R: RngCore>(
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        mut rng: R,
+    ) -> Result<(Commitment, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        let two_to_l_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let hat_n_at_two_to_l = &aux.rsa_modulo * (Integer::ONE << security.l).complete();
+        let hat_n_at_two_to_l_e = (&aux.rsa_modulo * &two_to_l_e).complete();
+
+        let alpha = Integer::from_rng_pm(&two_to_l_e, &mut rng);
+        let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, &mut rng);
+        let r0 = Integer::gen_invertible(data.key0.n(), &mut rng);
+        let r1 = Integer::gen_invertible(data.key1.n(), &mut rng);
+        let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_e, &mut rng);
+
+        let commitment = Commitment {
+            s: aux.combine(pdata.x, &mu)?,
+            a0: data.key0.encrypt_with(&alpha, &r0)?,
+            a1: data.key1.encrypt_with(&alpha, &r1)?,
+            d: aux.combine(&alpha, &gamma)?,
+        };
+        let private_commitment = PrivateCommitment {
+            alpha,
+            mu,
+            r0,
+            r1,
+            gamma,
+        };
+        Ok((commitment, private_commitment))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove(
+        data: Data,
+        pdata: PrivateData,
+        pcomm: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        Ok(Proof {
+            z1: (&pcomm.alpha + challenge * pdata.x).complete(),
+            w0: data
+                .key0
+                .n()
+                .combine(&pcomm.r0, Integer::ONE, pdata.nonce0, challenge)?,
+            w1: data
+                .key1
+                .n()
+                .combine(&pcomm.r1, Integer::ONE, pdata.nonce1, challenge)?,
+            z3: (&pcomm.gamma + challenge * &pcomm.mu).complete(),
+        })
+    }
+
+    /// Verify the proof
+    pub fn verify(
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key0.n()))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key0.n()))
+    }
+
+    fn check_prepared(
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(&aux.rsa_modulo, [&commitment.s, &commitment.d])?;
+        fail_if_degenerate(data.key0.nn(), [&commitment.a0])?;
+        fail_if_degenerate(data.key1.nn(), [&commitment.a1])?;
+        crate::common::fail_if_not_coprime(data.key0.n(), [&proof.w0])?;
+        crate::common::fail_if_not_coprime(data.key1.n(), [&proof.w1])?;
+        check_encryption(
+            InvalidProofReason::EqualityCheck(1),
+            data.key0,
+            &statement.c0,
+            &commitment.a0,
+            challenge,
+            &proof.z1,
+            &proof.w0,
+        )?;
+        crate::common::lap!(Check("paillier encryption under N0"));
+        check_encryption(
+            InvalidProofReason::EqualityCheck(2),
+            data.key1,
+            &statement.c1,
+            &commitment.a1,
+            challenge,
+            &proof.z1,
+            &proof.w1,
+        )?;
+        crate::common::lap!(Check("paillier encryption under N1"));
+        {
+            let lhs = aux.combine(&proof.z1, &proof.z3)?;
+            let s_to_e = aux.pow_mod(&commitment.s, challenge)?;
+            let rhs = (&commitment.d * s_to_e).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen"));
+        fail_if(
+            InvalidProofReason::RangeCheck(4),
+            proof
+                .z1
+                .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete()),
+        )?;
+        crate::common::lap!(Check("range of z1"));
+
+        Ok(())
+    }
+
+    /// Checks that `key.encrypt(z; w) = a * c^e`
+    fn check_encryption(
+        reason: InvalidProofReason,
+        key: &dyn AnyEncryptionKey,
+        c: &PreparedCiphertext,
+        a: &Ciphertext,
+        challenge: &Challenge,
+        z: &Integer,
+        w: &Integer,
+    ) -> Result<(), InvalidProof> {
+        let lhs = key
+            .encrypt_with(z, w)
+            .map_err(|_| InvalidProofReason::PaillierEnc)?;
+        let rhs = {
+            let e_at_c = c.omul(key, challenge)?;
+            key.oadd(a, &e_at_c)
+                .map_err(|_| InvalidProofReason::PaillierOp)?
+        };
+        fail_if_ne(reason, lhs, rhs)?;
+        Ok(())
+    }
+
+    /// Generate random challenge
+    pub fn challenge<R>(security: &SecurityParams, rng: &mut R) -> Integer
+    where
+        R: RngCore,
+    {
+        Integer::from_rng_pm(&security.q, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<R: RngCore, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<R: RngCore, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment, Proof), Error>
+    where
+        D: Digest,
+    {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<D>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement,
+        commitment: &Commitment,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+
+    /// Internal function for deriving challenge from protocol values
+    /// deterministically
+    pub fn challenge<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Vec<u8>
+    where
+        D: Digest,
+    {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data,
+        commitment: &Commitment,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        transcript.label(b"paillier_zk.paillier_plaintext_equality");
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb(b"l", &(security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"N0", data.key0.n());
+        transcript.absorb_value(b"C0", data.c0);
+        transcript.absorb_value(b"N1", data.key1.n());
+        transcript.absorb_value(b"C1", data.c1);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use rug::Integer;
+
+    use crate::common::extract::{challenge_diff, ensure, linear_response, paillier_nonce};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub x: Integer,
+        pub nonce0: Nonce,
+        pub nonce1: Nonce,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract(
+        data: Data,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let x = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        let nonce0 = paillier_nonce(data.key0.n(), data.c0, &proof1.w0, &proof2.w0, &e_diff)?;
+        let nonce1 = paillier_nonce(data.key1.n(), data.c1, &proof1.w1, &proof2.w1, &e_diff)?;
+
+        let c0 = data.key0.encrypt_with(&x, &nonce0).ok();
+        ensure(c0.as_ref() == Some(data.c0))?;
+        let c1 = data.key1.encrypt_with(&x, &nonce1).ok();
+        ensure(c1.as_ref() == Some(data.c1))?;
+        Ok(Witness { x, nonce0, nonce1 })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fast_paillier::EncryptionKey;
+    use rug::{Complete, Integer};
+
+    use crate::common::test::random_key;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    struct Statement {
+        key0: EncryptionKey,
+        c0: Integer,
+        key1: EncryptionKey,
+        c1: Integer,
+        x: Integer,
+        nonce0: Integer,
+        nonce1: Integer,
+    }
+
+    impl Statement {
+        fn new(rng: &mut rand_dev::DevRng, x: Integer, x1: &Integer) -> Self {
+            let key0 = random_key(rng).unwrap().encryption_key().clone();
+            let key1 = random_key(rng).unwrap().encryption_key().clone();
+            let (c0, nonce0) = key0.encrypt_with_random(rng, &x).unwrap();
+            let (c1, nonce1) = key1.encrypt_with_random(rng, x1).unwrap();
+            Self {
+                key0,
+                c0,
+                key1,
+                c1,
+                x,
+                nonce0,
+                nonce1,
+            }
+        }
+
+        fn data(&self) -> super::Data<'_> {
+            super::Data {
+                key0: &self.key0,
+                c0: &self.c0,
+                key1: &self.key1,
+                c1: &self.c1,
+            }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_> {
+            super::PrivateData {
+                x: &self.x,
+                nonce0: &self.nonce0,
+                nonce1: &self.nonce1,
+            }
+        }
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete())
+    }
+
+    fn run(x: Integer, x1: Integer) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let statement = Statement::new(&mut rng, x, &x1);
+        let aux = crate::common::test::aux(&mut rng);
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            statement.data(),
+            statement.pdata(),
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::non_interactive::verify(
+            shared_state,
+            &aux,
+            statement.data(),
+            &commitment,
+            &security,
+            &proof,
+        )
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security().l).complete(), &mut rng);
+        run(x.clone(), x).expect("proof failed");
+    }
+
+    #[test]
+    fn zero_witness() {
+        // Both ciphertexts encrypt zero
+        run(Integer::ZERO, Integer::ZERO).expect("proof failed");
+    }
+
+    #[test]
+    fn failing_range() {
+        let security = security();
+        let x = (Integer::ONE << (security.l + security.epsilon + 1)).complete();
+        let r = run(x.clone(), x).expect_err("proof should not pass");
+        match r.reason() {
+            InvalidProofReason::RangeCheck(_) => (),
+            e => panic!("proof should not fail with: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn failing_equality() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security().l).complete(), &mut rng);
+        let x1 = (&x + 1u8).complete();
+        let r = run(x, x1).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::EqualityCheck(2));
+    }
+
+    #[test]
+    fn degenerate_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let statement = Statement::new(&mut rng, x.clone(), &x);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Commitment equal to one is rejected
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let mut commitment = commitment;
+        commitment.a1 = Integer::from(1);
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let statement = Statement::new(&mut rng, x.clone(), &x);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.x, statement.x);
+        assert_eq!(witness.nonce0, statement.nonce0);
+        assert_eq!(witness.nonce1, statement.nonce1);
+    }
+}
@@ -0,0 +1,557 @@
+//! Deployment policies consulted before verification
+//!
+//! Proof verification accepts any statement it's given: any key size, any curve,
+//! proofs of any size. Deployments usually have business rules on top of that, e.g.
+//! only 2048-bit keys and secp256k1 are allowed. [`verify_with_policy`] checks such
+//! rules in one place, before the costly verification is performed: it describes the
+//! statement and the received proof as [`ProofInfo`] and lets [`VerifyPolicy`] veto
+//! it.
+//!
+//! [`Limits`] covers common rules, custom rules can be implemented as a closure or
+//! by implementing [`VerifyPolicy`].
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{paillier_encryption_in_range as p, policy};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     commitment: &p::Commitment,
+//! #     security: &p::SecurityParams,
+//! #     proof: &p::Proof,
+//! # ) -> Result<(), policy::PolicyError> {
+//! let policy = policy::Limits::default()
+//!     .with_modulus_bits(2048..=2048)
+//!     .with_max_proof_size(8 * 1024);
+//!
+//! policy::verify_with_policy(&policy, &data, &[commitment, proof], || {
+//!     p::non_interactive::verify(sha2::Sha256::default(), aux, data, commitment, security, proof)
+//! })?;
+//! # Ok(()) }
+//! ```
+
+use std::ops::RangeInclusive;
+
+use generic_ec::Curve;
+use rug::Integer;
+
+use crate::common::encoding::Encode;
+use crate::common::InvalidProof;
+
+/// Public description of a statement, available to policies
+pub trait Statement {
+    /// Name of the proof module, e.g. `"paillier_encryption_in_range"`
+    const PROOF: &'static str;
+
+    /// Name of the curve, if the statement involves one
+    fn curve(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Paillier moduli involved in the statement
+    fn moduli(&self) -> Vec<&Integer>;
+
+    /// Short id of the statement, same for prover and verifier
+    ///
+    /// See [`StatementId`](crate::StatementId)
+    #[cfg(feature = "statement-ids")]
+    fn statement_id(&self) -> crate::StatementId
+    where
+        Self: Encode + Sized,
+    {
+        crate::StatementId::of(self)
+    }
+}
+
+/// Statement and received proof as seen by [`VerifyPolicy`]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ProofInfo<'a> {
+    /// Name of the proof module, see [`Statement::PROOF`]
+    pub proof: &'static str,
+    /// Name of the curve, if the statement involves one
+    pub curve: Option<&'static str>,
+    /// Paillier moduli involved in the statement
+    pub moduli: Vec<&'a Integer>,
+    /// Total size of [canonically encoded](crate::encoding) messages of the prover,
+    /// i.e. commitment and proof
+    pub proof_size: usize,
+    /// Id of the statement, see [`Statement::statement_id`]
+    #[cfg(feature = "statement-ids")]
+    pub statement_id: crate::StatementId,
+}
+
+impl<'a> ProofInfo<'a> {
+    /// Describes the `statement` and prover's `messages`
+    pub fn new<S: Statement + Encode>(statement: &'a S, messages: &[&dyn Encode]) -> Self {
+        Self {
+            proof: S::PROOF,
+            curve: statement.curve(),
+            moduli: statement.moduli(),
+            proof_size: messages.iter().map(|m| m.to_canonical_bytes().len()).sum(),
+            #[cfg(feature = "statement-ids")]
+            statement_id: statement.statement_id(),
+        }
+    }
+}
+
+/// Policy deciding whether a proof may be verified at all
+pub trait VerifyPolicy {
+    /// Returns an error if the proof must be rejected without verification
+    fn check(&self, info: &ProofInfo) -> Result<(), Rejected>;
+}
+
+impl<F> VerifyPolicy for F
+where
+    F: Fn(&ProofInfo) -> Result<(), Rejected>,
+{
+    fn check(&self, info: &ProofInfo) -> Result<(), Rejected> {
+        self(info)
+    }
+}
+
+/// Common rules: allowed proofs, curves, key sizes, and proof sizes
+///
+/// Everything is allowed by default
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    proofs: Option<Vec<&'static str>>,
+    curves: Option<Vec<&'static str>>,
+    modulus_bits: Option<RangeInclusive<u32>>,
+    max_proof_size: Option<usize>,
+}
+
+impl Limits {
+    /// Allows only the listed proofs, see [`Statement::PROOF`]
+    pub fn with_proofs(self, proofs: impl IntoIterator<Item = &'static str>) -> Self {
+        Self {
+            proofs: Some(proofs.into_iter().collect()),
+            ..self
+        }
+    }
+
+    /// Allows curve `C`
+    ///
+    /// Once any curve is allowed, statements on other curves are rejected. Statements
+    /// that don't involve curves are not affected.
+    pub fn with_curve<C: Curve>(self) -> Self {
+        let mut curves = self.curves.unwrap_or_default();
+        curves.push(C::CURVE_NAME);
+        Self {
+            curves: Some(curves),
+            ..self
+        }
+    }
+
+    /// Allows only paillier moduli of bit size within `bits`
+    pub fn with_modulus_bits(self, bits: RangeInclusive<u32>) -> Self {
+        Self {
+            modulus_bits: Some(bits),
+            ..self
+        }
+    }
+
+    /// Rejects proofs which are larger than `size` bytes in canonical encoding
+    pub fn with_max_proof_size(self, size: usize) -> Self {
+        Self {
+            max_proof_size: Some(size),
+            ..self
+        }
+    }
+}
+
+impl VerifyPolicy for Limits {
+    fn check(&self, info: &ProofInfo) -> Result<(), Rejected> {
+        if let Some(proofs) = &self.proofs {
+            if !proofs.contains(&info.proof) {
+                return Err(Rejected::new(format!(
+                    "proof {} is not allowed",
+                    info.proof
+                )));
+            }
+        }
+        if let (Some(curves), Some(curve)) = (&self.curves, info.curve) {
+            if !curves.contains(&curve) {
+                return Err(Rejected::new(format!("curve {curve} is not allowed")));
+            }
+        }
+        if let Some(bits) = &self.modulus_bits {
+            if let Some(n) = info
+                .moduli
+                .iter()
+                .find(|n| !bits.contains(&n.significant_bits()))
+            {
+                return Err(Rejected::new(format!(
+                    "{}-bit modulus is not allowed",
+                    n.significant_bits()
+                )));
+            }
+        }
+        if let Some(max) = self.max_proof_size {
+            if info.proof_size > max {
+                return Err(Rejected::new(format!(
+                    "proof size {} exceeds {max} bytes",
+                    info.proof_size
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Consults the `policy` and, if it allows, runs `verify`
+///
+/// `messages` are the messages received from the prover, i.e. commitment and proof.
+/// They're only encoded to learn their size.
+///
+/// With `statement-ids` feature, error of `verify` is attributed to the
+/// [id](Statement::statement_id) of the `statement`, so policies can log the same id
+/// that appears in the error.
+pub fn verify_with_policy<S, F>(
+    policy: &impl VerifyPolicy,
+    statement: &S,
+    messages: &[&dyn Encode],
+    verify: F,
+) -> Result<(), PolicyError>
+where
+    S: Statement + Encode,
+    F: FnOnce() -> Result<(), InvalidProof>,
+{
+    policy.check(&ProofInfo::new(statement, messages))?;
+    #[cfg(feature = "statement-ids")]
+    let verify = || verify().map_err(|err| err.with_statement(statement));
+    verify()?;
+    Ok(())
+}
+
+/// Reason the policy rejected the proof
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{0}")]
+pub struct Rejected(String);
+
+impl Rejected {
+    /// Constructs the error with a human-readable reason
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self(reason.into())
+    }
+}
+
+/// Error returned by [`verify_with_policy`]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PolicyError {
+    /// Policy rejected the proof, it wasn't verified
+    #[error("rejected by policy: {0}")]
+    Rejected(
+        #[source]
+        #[from]
+        Rejected,
+    ),
+    /// Policy allowed the proof, but it's invalid
+    #[error(transparent)]
+    InvalidProof(#[from] InvalidProof),
+}
+
+macro_rules! impl_statement {
+    ($(#[$attr:meta] $module:ident$(<$c:ident>)? => |$data:ident| [$($modulus:expr),+];)+) => {$(
+        #[$attr]
+        impl<$($c: Curve)?> Statement for crate::$module::Data<'_, $($c)?> {
+            const PROOF: &'static str = stringify!($module);
+
+            $(
+            fn curve(&self) -> Option<&'static str> {
+                Some($c::CURVE_NAME)
+            }
+            )?
+
+            fn moduli(&self) -> Vec<&Integer> {
+                let $data = self;
+                vec![$($modulus),+]
+            }
+        }
+    )+};
+}
+
+impl_statement! {
+    #[cfg(feature = "paillier-encryption-in-range")]
+    paillier_encryption_in_range => |data| [data.key.n()];
+    #[cfg(feature = "paillier-encryption-in-range-with-elgamal")]
+    paillier_encryption_in_range_with_elgamal<C> => |data| [data.key0.n()];
+    #[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+    group_element_vs_paillier_encryption_in_range<C> => |data| [data.key0.n()];
+    #[cfg(feature = "group-element-vs-paillier-multiplication")]
+    group_element_vs_paillier_multiplication<C> => |data| [data.key0.n()];
+    #[cfg(feature = "paillier-affine-operation-in-range")]
+    paillier_affine_operation_in_range<C> => |data| [data.key0.n(), data.key1.n()];
+    #[cfg(feature = "paillier-affine-operation-with-paillier-commitment")]
+    paillier_affine_operation_with_paillier_commitment => |data| [data.key0.n(), data.key1.n()];
+    #[cfg(feature = "paillier-decryption-modulo-q")]
+    paillier_decryption_modulo_q<C> => |data| [data.key0.n()];
+    #[cfg(feature = "paillier-encryption-of-bit")]
+    paillier_encryption_of_bit => |data| [data.key.n()];
+    #[cfg(feature = "paillier-encryption-of-zero")]
+    paillier_encryption_of_zero => |data| [data.key.n()];
+    #[cfg(feature = "paillier-multiplication")]
+    paillier_multiplication => |data| [data.key.n()];
+    #[cfg(feature = "paillier-plaintext-equality")]
+    paillier_plaintext_equality => |data| [data.key0.n(), data.key1.n()];
+    #[cfg(feature = "paillier-plaintext-knowledge")]
+    paillier_plaintext_knowledge => |data| [data.key.n()];
+    #[cfg(feature = "paillier-scalar-multiplication-in-range")]
+    paillier_scalar_multiplication_in_range => |data| [data.key.n()];
+    #[cfg(feature = "pedersen-commitment-vs-paillier-encryption-in-range")]
+    pedersen_commitment_vs_paillier_encryption_in_range<C> => |data| [data.key0.n()];
+    #[cfg(feature = "witness-escrow")]
+    witness_escrow<C> => |data| [data.key0.n(), data.key1.n()];
+    #[cfg(feature = "no-small-factor")]
+    no_small_factor => |data| [data.n];
+    #[cfg(feature = "factorization-knowledge")]
+    factorization_knowledge => |data| [data.n];
+    #[cfg(feature = "exact-range")]
+    exact_range => |data| [data.key.n()];
+}
+
+#[cfg(feature = "dlog-vs-elgamal-commitment")]
+impl<C: Curve> Statement for crate::dlog_vs_elgamal_commitment::Data<'_, C> {
+    const PROOF: &'static str = "dlog_vs_elgamal_commitment";
+
+    fn curve(&self) -> Option<&'static str> {
+        Some(C::CURVE_NAME)
+    }
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![]
+    }
+}
+
+#[cfg(feature = "joint-enc-log-star")]
+impl<C: Curve> Statement for crate::joint_enc_log_star::Data<'_, C> {
+    const PROOF: &'static str = "joint_enc_log_star";
+
+    fn curve(&self) -> Option<&'static str> {
+        Some(C::CURVE_NAME)
+    }
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![self.enc.key.n()]
+    }
+}
+
+#[cfg(feature = "paillier-encryption-in-range")]
+impl Statement for crate::paillier_encryption_in_range::batch::Data<'_> {
+    const PROOF: &'static str = "paillier_encryption_in_range::batch";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![self.key.n()]
+    }
+}
+
+#[cfg(feature = "paillier-encryption-in-range")]
+impl Statement for crate::paillier_encryption_in_range::interval::Data<'_> {
+    const PROOF: &'static str = "paillier_encryption_in_range::interval";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![self.key.n()]
+    }
+}
+
+#[cfg(feature = "paillier-encryption-in-range")]
+impl Statement for crate::paillier_encryption_in_range::linear_combination::Data<'_> {
+    const PROOF: &'static str = "paillier_encryption_in_range::linear_combination";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![self.key.n()]
+    }
+}
+
+#[cfg(feature = "paillier-encryption-of-zero")]
+impl Statement for crate::paillier_encryption_of_zero::opening::Data<'_> {
+    const PROOF: &'static str = "paillier_encryption_of_zero::opening";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![self.key.n()]
+    }
+}
+
+#[cfg(feature = "paillier-encryption-of-zero")]
+impl Statement for crate::paillier_encryption_of_zero::rerandomization::Data<'_> {
+    const PROOF: &'static str = "paillier_encryption_of_zero::rerandomization";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![self.key.n()]
+    }
+}
+
+#[cfg(feature = "paillier-blum-modulus")]
+impl Statement for crate::paillier_blum_modulus::Data {
+    const PROOF: &'static str = "paillier_blum_modulus";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![&self.n]
+    }
+}
+
+#[cfg(feature = "paillier-blum-modulus")]
+impl Statement for crate::paillier_blum_modulus::external_w::Data {
+    const PROOF: &'static str = "paillier_blum_modulus::external_w";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![&self.n]
+    }
+}
+
+#[cfg(feature = "paillier-broadcast-encryption")]
+impl Statement for crate::paillier_broadcast_encryption::Data<'_> {
+    const PROOF: &'static str = "paillier_broadcast_encryption";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        self.keys.iter().map(|key| key.n()).collect()
+    }
+}
+
+#[cfg(feature = "presigning-proofs")]
+impl Statement for crate::presigning_proofs::round1::Data<'_> {
+    const PROOF: &'static str = "presigning_proofs::round1";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![self.key.n()]
+    }
+}
+
+#[cfg(feature = "presigning-proofs")]
+impl<C: Curve> Statement for crate::presigning_proofs::round2::Data<'_, C> {
+    const PROOF: &'static str = "presigning_proofs::round2";
+
+    fn curve(&self) -> Option<&'static str> {
+        Some(C::CURVE_NAME)
+    }
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![self.key.n(), self.verifier_key.n()]
+    }
+}
+
+#[cfg(feature = "presigning-proofs")]
+impl<C: Curve> Statement for crate::presigning_proofs::round3::Data<'_, C> {
+    const PROOF: &'static str = "presigning_proofs::round3";
+
+    fn curve(&self) -> Option<&'static str> {
+        Some(C::CURVE_NAME)
+    }
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![self.key.n()]
+    }
+}
+
+#[cfg(feature = "ring-pedersen-parameters")]
+impl Statement for crate::ring_pedersen_parameters::Data<'_> {
+    const PROOF: &'static str = "ring_pedersen_parameters";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![&self.aux.rsa_modulo]
+    }
+}
+
+#[cfg(feature = "schnorr-pok")]
+impl<C: Curve> Statement for crate::schnorr_pok::Data<'_, C> {
+    const PROOF: &'static str = "schnorr_pok";
+
+    fn curve(&self) -> Option<&'static str> {
+        Some(C::CURVE_NAME)
+    }
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![]
+    }
+}
+
+#[cfg(feature = "setup-proofs")]
+impl Statement for crate::setup_proofs::Data<'_> {
+    const PROOF: &'static str = "setup_proofs";
+
+    fn moduli(&self) -> Vec<&Integer> {
+        vec![self.n, &self.aux.rsa_modulo]
+    }
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::paillier_encryption_in_range as p;
+
+    use super::{verify_with_policy, Limits, PolicyError, ProofInfo, Rejected, Statement};
+
+    #[test]
+    fn limits() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) =
+            p::non_interactive::prove(shared_state.clone(), &aux, data, pdata, &security, &mut rng)
+                .unwrap();
+        let run = |policy: &Limits| {
+            verify_with_policy(policy, &data, &[&commitment, &proof], || {
+                p::non_interactive::verify(
+                    shared_state.clone(),
+                    &aux,
+                    data,
+                    &commitment,
+                    &security,
+                    &proof,
+                )
+            })
+        };
+
+        let info = ProofInfo::new(&data, &[&commitment, &proof]);
+        assert_eq!(info.proof, <p::Data as Statement>::PROOF);
+        assert_eq!(info.curve, None);
+        assert_eq!(info.moduli, [statement.key.n()]);
+
+        run(&Limits::default()).expect("everything is allowed by default");
+        run(&Limits::default()
+            .with_proofs([p::Data::PROOF])
+            .with_curve::<generic_ec::curves::Secp256r1>()
+            .with_modulus_bits(2047..=2048)
+            .with_max_proof_size(info.proof_size))
+        .expect("proof is within limits");
+
+        let rejected = [
+            Limits::default().with_proofs(["paillier_blum_modulus"]),
+            Limits::default().with_modulus_bits(3072..=4096),
+            Limits::default().with_max_proof_size(info.proof_size - 1),
+        ];
+        for policy in &rejected {
+            assert!(matches!(run(policy), Err(PolicyError::Rejected(_))));
+        }
+
+        // Errors are attributed to the statement
+        #[cfg(feature = "statement-ids")]
+        {
+            let err = verify_with_policy(&Limits::default(), &data, &[], || {
+                Err(crate::common::InvalidProofReason::EqualityCheck(1).into())
+            })
+            .unwrap_err();
+            let PolicyError::InvalidProof(err) = err else {
+                panic!("unexpected error: {err}")
+            };
+            assert_eq!(err.statement_id(), Some(data.statement_id()));
+            assert_eq!(
+                err.to_string(),
+                format!("invalid proof (statement {})", data.statement_id())
+            );
+        }
+
+        // Closures are policies too
+        let policy = |_: &ProofInfo| Err(Rejected::new("maintenance"));
+        let r = verify_with_policy(&policy, &data, &[], || unreachable!());
+        assert_eq!(
+            r.unwrap_err().to_string(),
+            "rejected by policy: maintenance"
+        );
+    }
+}
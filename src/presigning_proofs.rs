@@ -0,0 +1,1255 @@
+//! Proofs of CGGMP21 presigning rounds, one bundle per round and counterparty
+//!
+//! ## Description
+//! In the three-round presigning of CGGMP21 a party P sends to each other party V:
+//! - in [round 1](round1): `K = enc(k)` and `G = enc(γ)` on the key of P, along with
+//!   [Пenc](crate::paillier_encryption_in_range) for `K`
+//! - in [round 2](round2): `Γ = γ G`, the results `D, F` and `D^, F^` of affine
+//!   operations on `K` of V, along with [Пaff-g](crate::paillier_affine_operation_in_range)
+//!   for both of them and [Пlog*](crate::group_element_vs_paillier_encryption_in_range)
+//!   for `Γ` and `G`
+//! - in [round 3](round3): `Δ = k Γ`, along with
+//!   [Пlog*](crate::group_element_vs_paillier_encryption_in_range) for `Δ` and `K`
+//!
+//! Every proof is computed on ring-pedersen parameters of V. Each round module takes
+//! the statements and witnesses of the round under their names from the paper,
+//! assigns them to the right proofs, and proves or verifies all of them on a single
+//! Fiat-Shamir transcript. Proofs of one bundle are bound to each other, and can't
+//! be mixed with proofs of another round or counterparty.
+//!
+//! [`SecurityParams`] derives parameters of all the proofs from `l`, `l'` and
+//! epsilon of the paper, so they can't get out of sync.
+//!
+//! Round 2 can alternatively be proven in [joint mode](round2::joint), which derives
+//! a single challenge over all statements and commitments of the message instead of
+//! chaining the challenges of the proofs one after another.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{presigning_proofs as p, Integer};
+//! use generic_ec::curves::Secp256k1 as E;
+//! # fn doc_fn(
+//! #     verifier_aux: &p::Aux,
+//! #     data: p::round2::Data<E>,
+//! #     pdata: p::round2::PrivateData,
+//! # ) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut rng = rand_core::OsRng;
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//!
+//! let security = p::SecurityParams::new(256, 1280, 512, (Integer::ONE << 256_u32).into());
+//!
+//! // Prover computes all proofs of round 2 for one verifier
+//! let bundle = p::round2::prove(
+//!     shared_state_prover,
+//!     verifier_aux,
+//!     data,
+//!     pdata,
+//!     &security,
+//!     &mut rng,
+//! )?;
+//!
+//! // Verifier checks all of them at once
+//! p::round2::verify(shared_state_verifier, verifier_aux, data, &security, &bundle)?;
+//! # Ok(()) }
+//! ```
+
+use rug::Integer;
+
+use crate::{
+    group_element_vs_paillier_encryption_in_range as log_star,
+    paillier_affine_operation_in_range as aff_g, paillier_encryption_in_range as enc,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::Aux;
+
+/// Security parameters of all proofs in presigning
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SecurityParams {
+    /// Parameters of Пenc in round 1
+    pub enc: enc::SecurityParams,
+    /// Parameters of Пaff-g in round 2
+    pub aff_g: aff_g::SecurityParams,
+    /// Parameters of Пlog* in rounds 2 and 3
+    pub log_star: log_star::SecurityParams,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters of all the proofs
+    ///
+    /// `l` is the bit size of `k`, `γ` and `x`, `l_prime` is the bit size of `β`,
+    /// `epsilon` is the slackness parameter and `q` is the bound on challenges.
+    /// CGGMP21 suggests `l' = 5l` and `epsilon = 2l`.
+    pub fn new(l: usize, l_prime: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            enc: enc::SecurityParams::new(l, epsilon, q.clone()),
+            aff_g: aff_g::SecurityParams::new(l, l_prime, epsilon, q.clone()),
+            log_star: log_star::SecurityParams::new(l, epsilon, q),
+        }
+    }
+
+    /// Absorbs pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^` in all the proofs
+    ///
+    /// See [`enc::SecurityParams::with_aux_digest`]
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        let aux_digest = aux_digest.into();
+        Self {
+            enc: self.enc.with_aux_digest(aux_digest.clone()),
+            aff_g: self.aff_g.with_aux_digest(aux_digest.clone()),
+            log_star: self.log_star.with_aux_digest(aux_digest),
+        }
+    }
+}
+
+/// Round 1: Пenc for `K`
+pub mod round1 {
+    use digest::Digest;
+    use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+    use rand_core::RngCore;
+    use rug::Integer;
+
+    use crate::common::encoding::{impl_decode, impl_encode};
+    use crate::paillier_encryption_in_range as enc;
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+
+    use super::{Aux, SecurityParams};
+
+    /// Public data of round 1
+    #[derive(Debug, Clone, Copy)]
+    pub struct Data<'a> {
+        /// N in paper, public key of prover
+        pub key: &'a dyn AnyEncryptionKey,
+        /// K in paper, `k` encrypted on `key`
+        pub k: &'a Ciphertext,
+    }
+
+    /// Private data of prover in round 1
+    #[derive(Clone, Copy)]
+    pub struct PrivateData<'a> {
+        /// k in paper, plaintext of `K`
+        pub k: &'a Integer,
+        /// rho in paper, nonce of encryption `k -> K`
+        pub rho: &'a Nonce,
+    }
+
+    crate::transcript::assert_private!(PrivateData<'static>);
+
+    /// Proofs of round 1, computed by [`prove`]
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Bundle {
+        pub enc_commitment: enc::Commitment,
+        pub enc_proof: enc::Proof,
+    }
+
+    impl_encode! { Data<'a> { key, k } }
+    impl_encode! { Bundle { enc_commitment, enc_proof } }
+    impl_decode! { Bundle { enc_commitment, enc_proof } }
+
+    impl Bundle {
+        /// Upper bound on size of [canonically encoded](crate::encoding) bundle produced
+        /// by honest prover
+        ///
+        /// `modulus_bits` is an upper bound on bit size of `N` and `N^`
+        pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+            enc::Commitment::max_serialized_size(&security.enc, modulus_bits)
+                + enc::Proof::max_serialized_size(&security.enc, modulus_bits)
+        }
+    }
+
+    fn enc_data(data: Data) -> enc::Data {
+        enc::Data {
+            key: data.key,
+            ciphertext: data.k,
+        }
+    }
+
+    /// Computes proofs of round 1 for the verifier with ring-pedersen parameters
+    /// `verifier_aux`
+    pub fn prove<D: Digest, R: RngCore>(
+        shared_state: D,
+        verifier_aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<Bundle, Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, verifier_aux, data, pdata, security, rng)
+    }
+
+    /// Computes proofs of round 1 within the protocol transcript
+    pub fn prove_with_transcript<D: Digest, R: RngCore>(
+        transcript: &mut Transcript<D>,
+        verifier_aux: &Aux,
+        data: Data,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<Bundle, Error> {
+        transcript.label(b"paillier_zk.presigning_proofs.round1");
+        let (enc_commitment, enc_proof) = enc::non_interactive::prove_with_transcript(
+            transcript,
+            verifier_aux,
+            enc_data(data),
+            enc::PrivateData {
+                plaintext: pdata.k,
+                nonce: pdata.rho,
+            },
+            &security.enc,
+            rng,
+        )?;
+        Ok(Bundle {
+            enc_commitment,
+            enc_proof,
+        })
+    }
+
+    /// Verifies proofs of round 1, `verifier_aux` are ring-pedersen parameters of the
+    /// verifier
+    pub fn verify<D: Digest>(
+        shared_state: D,
+        verifier_aux: &Aux,
+        data: Data,
+        security: &SecurityParams,
+        bundle: &Bundle,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, verifier_aux, data, security, bundle)
+    }
+
+    /// Verifies proofs of round 1 within the protocol transcript
+    pub fn verify_with_transcript<D: Digest>(
+        transcript: &mut Transcript<D>,
+        verifier_aux: &Aux,
+        data: Data,
+        security: &SecurityParams,
+        bundle: &Bundle,
+    ) -> Result<(), InvalidProof> {
+        transcript.label(b"paillier_zk.presigning_proofs.round1");
+        enc::non_interactive::verify_with_transcript(
+            transcript,
+            verifier_aux,
+            enc_data(data),
+            &bundle.enc_commitment,
+            &security.enc,
+            &bundle.enc_proof,
+        )
+    }
+}
+
+/// Round 2: Пaff-g for `D, F` and `D^, F^`, Пlog* for `Γ` and `G`
+pub mod round2 {
+    use digest::Digest;
+    use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+    use generic_ec::{Curve, Point};
+    use rand_core::RngCore;
+    use rug::Integer;
+
+    use crate::common::encoding::{impl_decode, impl_encode};
+    use crate::transcript::Transcript;
+    use crate::{
+        group_element_vs_paillier_encryption_in_range as log_star,
+        paillier_affine_operation_in_range as aff_g,
+    };
+    use crate::{Error, InvalidProof};
+
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+
+    use super::{Aux, SecurityParams};
+
+    /// Public data of round 2
+    #[derive(Debug, Clone, Copy)]
+    pub struct Data<'a, C: Curve> {
+        /// N_i in paper, public key of prover
+        pub key: &'a dyn AnyEncryptionKey,
+        /// N_j in paper, public key of verifier
+        pub verifier_key: &'a dyn AnyEncryptionKey,
+        /// K_j in paper, ciphertext sent by verifier in round 1, encrypted on
+        /// `verifier_key`
+        pub verifier_k: &'a Ciphertext,
+        /// G_i in paper, `γ` encrypted on `key`
+        pub g: &'a Ciphertext,
+        /// Γ_i in paper, `γ G`
+        pub gamma: &'a Point<C>,
+        /// X_i in paper, public key share of prover `x G`
+        pub x: &'a Point<C>,
+        /// D_{j,i} in paper, `γ K_j + β` encrypted on `verifier_key`
+        pub d: &'a Ciphertext,
+        /// F_{j,i} in paper, `β` encrypted on `key`
+        pub f: &'a Ciphertext,
+        /// D^_{j,i} in paper, `x K_j + β^` encrypted on `verifier_key`
+        pub d_hat: &'a Ciphertext,
+        /// F^_{j,i} in paper, `β^` encrypted on `key`
+        pub f_hat: &'a Ciphertext,
+    }
+
+    /// Private data of prover in round 2
+    #[derive(Clone, Copy)]
+    pub struct PrivateData<'a> {
+        /// γ_i in paper, plaintext of `G` and logarithm of `Γ`
+        pub gamma: &'a Integer,
+        /// ν_i in paper, nonce of encryption `γ -> G`
+        pub nu: &'a Nonce,
+        /// x_i in paper, secret key share of prover
+        pub x: &'a Integer,
+        /// β_{i,j} in paper, as it's added to `D` and encrypted in `F`
+        pub beta: &'a Integer,
+        /// s_{i,j} in paper, nonce of encryption of `β` added to `D`
+        pub s: &'a Nonce,
+        /// r_{i,j} in paper, nonce of encryption `β -> F`
+        pub r: &'a Nonce,
+        /// β^_{i,j} in paper, as it's added to `D^` and encrypted in `F^`
+        pub beta_hat: &'a Integer,
+        /// s^_{i,j} in paper, nonce of encryption of `β^` added to `D^`
+        pub s_hat: &'a Nonce,
+        /// r^_{i,j} in paper, nonce of encryption `β^ -> F^`
+        pub r_hat: &'a Nonce,
+    }
+
+    crate::transcript::assert_private!(PrivateData<'static>);
+
+    /// Proofs of round 2, computed by [`prove`]
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+    pub struct Bundle<C: Curve> {
+        /// Пaff-g for `D`, `F` and `Γ`
+        pub aff_g_commitment: aff_g::Commitment<C>,
+        pub aff_g_proof: aff_g::Proof,
+        /// Пaff-g for `D^`, `F^` and `X`
+        pub aff_g_hat_commitment: aff_g::Commitment<C>,
+        pub aff_g_hat_proof: aff_g::Proof,
+        /// Пlog* for `G` and `Γ`
+        pub log_star_commitment: log_star::Commitment<C>,
+        pub log_star_proof: log_star::Proof,
+    }
+
+    impl_encode! { Data<'a, C: Curve> { key, verifier_key, verifier_k, g, gamma, x, d, f, d_hat, f_hat } }
+    impl_encode! { Bundle<C: Curve> {
+        aff_g_commitment, aff_g_proof,
+        aff_g_hat_commitment, aff_g_hat_proof,
+        log_star_commitment, log_star_proof,
+    } }
+    impl_decode! { Bundle<C: Curve> {
+        aff_g_commitment, aff_g_proof,
+        aff_g_hat_commitment, aff_g_hat_proof,
+        log_star_commitment, log_star_proof,
+    } }
+
+    impl<C: Curve> Bundle<C> {
+        /// Upper bound on size of [canonically encoded](crate::encoding) bundle produced
+        /// by honest prover
+        ///
+        /// `modulus_bits` is an upper bound on bit size of `N_i`, `N_j` and `N^`
+        pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+            2 * (aff_g::Commitment::<C>::max_serialized_size(&security.aff_g, modulus_bits)
+                + aff_g::Proof::max_serialized_size(&security.aff_g, modulus_bits))
+                + log_star::Commitment::<C>::max_serialized_size(&security.log_star, modulus_bits)
+                + log_star::Proof::max_serialized_size(&security.log_star, modulus_bits)
+        }
+    }
+
+    impl<'a, C: Curve> Data<'a, C> {
+        fn aff_g(&self) -> aff_g::Data<'a, C> {
+            aff_g::Data {
+                key0: self.verifier_key,
+                key1: self.key,
+                c: self.verifier_k,
+                d: self.d,
+                y: self.f,
+                x: self.gamma,
+            }
+        }
+
+        fn aff_g_hat(&self) -> aff_g::Data<'a, C> {
+            aff_g::Data {
+                key0: self.verifier_key,
+                key1: self.key,
+                c: self.verifier_k,
+                d: self.d_hat,
+                y: self.f_hat,
+                x: self.x,
+            }
+        }
+
+        fn log_star(&self, generator: &'a Point<C>) -> log_star::Data<'a, C> {
+            log_star::Data {
+                key0: self.key,
+                c: self.g,
+                b: generator,
+                x: self.gamma,
+            }
+        }
+    }
+
+    /// Computes proofs of round 2 for the verifier with ring-pedersen parameters
+    /// `verifier_aux`
+    pub fn prove<C: Curve, D: Digest, R: RngCore>(
+        shared_state: D,
+        verifier_aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<Bundle<C>, Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, verifier_aux, data, pdata, security, rng)
+    }
+
+    /// Computes proofs of round 2 within the protocol transcript
+    pub fn prove_with_transcript<C: Curve, D: Digest, R: RngCore>(
+        transcript: &mut Transcript<D>,
+        verifier_aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<Bundle<C>, Error> {
+        transcript.label(b"paillier_zk.presigning_proofs.round2");
+        let (aff_g_commitment, aff_g_proof) = aff_g::non_interactive::prove_with_transcript(
+            transcript,
+            verifier_aux,
+            data.aff_g(),
+            aff_g::PrivateData {
+                x: pdata.gamma,
+                y: pdata.beta,
+                nonce: pdata.s,
+                nonce_y: pdata.r,
+            },
+            &security.aff_g,
+            &mut *rng,
+        )?;
+        let (aff_g_hat_commitment, aff_g_hat_proof) =
+            aff_g::non_interactive::prove_with_transcript(
+                transcript,
+                verifier_aux,
+                data.aff_g_hat(),
+                aff_g::PrivateData {
+                    x: pdata.x,
+                    y: pdata.beta_hat,
+                    nonce: pdata.s_hat,
+                    nonce_y: pdata.r_hat,
+                },
+                &security.aff_g,
+                &mut *rng,
+            )?;
+        let generator = Point::generator().to_point();
+        let (log_star_commitment, log_star_proof) =
+            log_star::non_interactive::prove_with_transcript(
+                transcript,
+                verifier_aux,
+                data.log_star(&generator),
+                log_star::PrivateData {
+                    x: pdata.gamma,
+                    nonce: pdata.nu,
+                },
+                &security.log_star,
+                rng,
+            )?;
+        Ok(Bundle {
+            aff_g_commitment,
+            aff_g_proof,
+            aff_g_hat_commitment,
+            aff_g_hat_proof,
+            log_star_commitment,
+            log_star_proof,
+        })
+    }
+
+    /// Verifies proofs of round 2, `verifier_aux` are ring-pedersen parameters of the
+    /// verifier
+    pub fn verify<C: Curve, D: Digest>(
+        shared_state: D,
+        verifier_aux: &Aux,
+        data: Data<C>,
+        security: &SecurityParams,
+        bundle: &Bundle<C>,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, verifier_aux, data, security, bundle)
+    }
+
+    /// Verifies proofs of round 2 within the protocol transcript
+    pub fn verify_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        verifier_aux: &Aux,
+        data: Data<C>,
+        security: &SecurityParams,
+        bundle: &Bundle<C>,
+    ) -> Result<(), InvalidProof> {
+        transcript.label(b"paillier_zk.presigning_proofs.round2");
+        aff_g::non_interactive::verify_with_transcript(
+            transcript,
+            verifier_aux,
+            data.aff_g(),
+            &bundle.aff_g_commitment,
+            &security.aff_g,
+            &bundle.aff_g_proof,
+        )?;
+        aff_g::non_interactive::verify_with_transcript(
+            transcript,
+            verifier_aux,
+            data.aff_g_hat(),
+            &bundle.aff_g_hat_commitment,
+            &security.aff_g,
+            &bundle.aff_g_hat_proof,
+        )?;
+        let generator = Point::generator().to_point();
+        log_star::non_interactive::verify_with_transcript(
+            transcript,
+            verifier_aux,
+            data.log_star(&generator),
+            &bundle.log_star_commitment,
+            &security.log_star,
+            &bundle.log_star_proof,
+        )
+    }
+
+    /// Round 2 with a single challenge for the whole message
+    ///
+    /// All statements and commitments of the round are absorbed before the challenge
+    /// is derived, so every proof is bound to the entire message rather than to the
+    /// proofs that precede it. Challenges of the individual proofs are sampled, in
+    /// order, from the one challenge squeezed out of the transcript.
+    ///
+    /// The bundle has the same layout as in
+    /// [sequential mode](crate::presigning_proofs::round2::prove), but bundles of one
+    /// mode don't verify in the other.
+    pub mod joint {
+        use digest::Digest;
+        use generic_ec::{Curve, Point};
+        use rand_core::RngCore;
+
+        use crate::transcript::Transcript;
+        use crate::{
+            group_element_vs_paillier_encryption_in_range as log_star,
+            paillier_affine_operation_in_range as aff_g,
+        };
+        use crate::{Error, InvalidProof};
+
+        use super::{Aux, Bundle, Data, PrivateData, SecurityParams};
+
+        /// Challenges of the proofs in round 2
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct Challenges {
+            pub aff_g: aff_g::Challenge,
+            pub aff_g_hat: aff_g::Challenge,
+            pub log_star: log_star::Challenge,
+        }
+
+        /// Computes proofs of round 2 for the verifier with ring-pedersen parameters
+        /// `verifier_aux`
+        pub fn prove<C: Curve, D: Digest, R: RngCore>(
+            shared_state: D,
+            verifier_aux: &Aux,
+            data: Data<C>,
+            pdata: PrivateData,
+            security: &SecurityParams,
+            rng: &mut R,
+        ) -> Result<Bundle<C>, Error> {
+            let mut transcript = Transcript::from_shared_state(shared_state);
+            prove_with_transcript(&mut transcript, verifier_aux, data, pdata, security, rng)
+        }
+
+        /// Computes proofs of round 2 within the protocol transcript
+        pub fn prove_with_transcript<C: Curve, D: Digest, R: RngCore>(
+            transcript: &mut Transcript<D>,
+            verifier_aux: &Aux,
+            data: Data<C>,
+            pdata: PrivateData,
+            security: &SecurityParams,
+            rng: &mut R,
+        ) -> Result<Bundle<C>, Error> {
+            let aff_g_pdata = aff_g::PrivateData {
+                x: pdata.gamma,
+                y: pdata.beta,
+                nonce: pdata.s,
+                nonce_y: pdata.r,
+            };
+            let aff_g_hat_pdata = aff_g::PrivateData {
+                x: pdata.x,
+                y: pdata.beta_hat,
+                nonce: pdata.s_hat,
+                nonce_y: pdata.r_hat,
+            };
+            let log_star_pdata = log_star::PrivateData {
+                x: pdata.gamma,
+                nonce: pdata.nu,
+            };
+            let generator = Point::generator().to_point();
+
+            let (aff_g_commitment, aff_g_pcomm) = aff_g::interactive::commit(
+                verifier_aux,
+                data.aff_g(),
+                aff_g_pdata,
+                &security.aff_g,
+                &mut *rng,
+            )?;
+            let (aff_g_hat_commitment, aff_g_hat_pcomm) = aff_g::interactive::commit(
+                verifier_aux,
+                data.aff_g_hat(),
+                aff_g_hat_pdata,
+                &security.aff_g,
+                &mut *rng,
+            )?;
+            let (log_star_commitment, log_star_pcomm) = log_star::interactive::commit(
+                verifier_aux,
+                data.log_star(&generator),
+                log_star_pdata,
+                &security.log_star,
+                rng,
+            )?;
+            crate::common::lap!(Commit);
+
+            let challenges = challenge_with_transcript(
+                transcript,
+                verifier_aux,
+                data,
+                &aff_g_commitment,
+                &aff_g_hat_commitment,
+                &log_star_commitment,
+                security,
+            );
+            crate::common::lap!(Challenge);
+
+            let aff_g_proof = aff_g::interactive::prove(
+                data.aff_g(),
+                aff_g_pdata,
+                &aff_g_pcomm,
+                &challenges.aff_g,
+            )?;
+            let aff_g_hat_proof = aff_g::interactive::prove(
+                data.aff_g_hat(),
+                aff_g_hat_pdata,
+                &aff_g_hat_pcomm,
+                &challenges.aff_g_hat,
+            )?;
+            let log_star_proof = log_star::interactive::prove(
+                data.log_star(&generator),
+                log_star_pdata,
+                &log_star_pcomm,
+                &challenges.log_star,
+            )?;
+            crate::common::lap!(Respond);
+
+            Ok(Bundle {
+                aff_g_commitment,
+                aff_g_proof,
+                aff_g_hat_commitment,
+                aff_g_hat_proof,
+                log_star_commitment,
+                log_star_proof,
+            })
+        }
+
+        /// Verifies proofs of round 2, `verifier_aux` are ring-pedersen parameters of
+        /// the verifier
+        pub fn verify<C: Curve, D: Digest>(
+            shared_state: D,
+            verifier_aux: &Aux,
+            data: Data<C>,
+            security: &SecurityParams,
+            bundle: &Bundle<C>,
+        ) -> Result<(), InvalidProof> {
+            let mut transcript = Transcript::from_shared_state(shared_state);
+            verify_with_transcript(&mut transcript, verifier_aux, data, security, bundle)
+        }
+
+        /// Verifies proofs of round 2 within the protocol transcript
+        pub fn verify_with_transcript<C: Curve, D: Digest>(
+            transcript: &mut Transcript<D>,
+            verifier_aux: &Aux,
+            data: Data<C>,
+            security: &SecurityParams,
+            bundle: &Bundle<C>,
+        ) -> Result<(), InvalidProof> {
+            let challenges = challenge_with_transcript(
+                transcript,
+                verifier_aux,
+                data,
+                &bundle.aff_g_commitment,
+                &bundle.aff_g_hat_commitment,
+                &bundle.log_star_commitment,
+                security,
+            );
+            crate::common::lap!(Challenge);
+
+            aff_g::interactive::verify(
+                verifier_aux,
+                data.aff_g(),
+                &bundle.aff_g_commitment,
+                &security.aff_g,
+                &challenges.aff_g,
+                &bundle.aff_g_proof,
+            )?;
+            aff_g::interactive::verify(
+                verifier_aux,
+                data.aff_g_hat(),
+                &bundle.aff_g_hat_commitment,
+                &security.aff_g,
+                &challenges.aff_g_hat,
+                &bundle.aff_g_hat_proof,
+            )?;
+            let generator = Point::generator().to_point();
+            log_star::interactive::verify(
+                verifier_aux,
+                data.log_star(&generator),
+                &bundle.log_star_commitment,
+                &security.log_star,
+                &challenges.log_star,
+                &bundle.log_star_proof,
+            )
+        }
+
+        /// Deterministically computes challenges of round 2 from the protocol
+        /// transcript
+        ///
+        /// Absorbs ring-pedersen parameters of the verifier, statements and
+        /// commitments of all the proofs into the `transcript`. Pre-agreed digest of
+        /// the parameters is taken from `security.aff_g`, see
+        /// [`crate::presigning_proofs::SecurityParams::with_aux_digest`].
+        pub fn challenge_with_transcript<C: Curve, D: Digest>(
+            transcript: &mut Transcript<D>,
+            verifier_aux: &Aux,
+            data: Data<C>,
+            aff_g_commitment: &aff_g::Commitment<C>,
+            aff_g_hat_commitment: &aff_g::Commitment<C>,
+            log_star_commitment: &log_star::Commitment<C>,
+            security: &SecurityParams,
+        ) -> Challenges {
+            transcript.label(b"paillier_zk.presigning_proofs.round2.joint");
+            transcript.absorb_aux(verifier_aux, security.aff_g.aux_digest.as_deref());
+            transcript.absorb_value(b"data", &data);
+            transcript.absorb_value(b"aff_g_commitment", aff_g_commitment);
+            transcript.absorb_value(b"aff_g_hat_commitment", aff_g_hat_commitment);
+            transcript.absorb_value(b"log_star_commitment", log_star_commitment);
+            let mut rng = transcript.challenge_rng(b"e");
+            Challenges {
+                aff_g: aff_g::interactive::challenge(&security.aff_g, &mut rng),
+                aff_g_hat: aff_g::interactive::challenge(&security.aff_g, &mut rng),
+                log_star: log_star::interactive::challenge(&security.log_star, &mut rng),
+            }
+        }
+    }
+}
+
+/// Round 3: Пlog* for `Δ` and `K`
+pub mod round3 {
+    use digest::Digest;
+    use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+    use generic_ec::{Curve, Point};
+    use rand_core::RngCore;
+    use rug::Integer;
+
+    use crate::common::encoding::{impl_decode, impl_encode};
+    use crate::group_element_vs_paillier_encryption_in_range as log_star;
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+
+    use super::{Aux, SecurityParams};
+
+    /// Public data of round 3
+    #[derive(Debug, Clone, Copy)]
+    pub struct Data<'a, C: Curve> {
+        /// N_i in paper, public key of prover
+        pub key: &'a dyn AnyEncryptionKey,
+        /// K_i in paper, `k` encrypted on `key` in round 1
+        pub k: &'a Ciphertext,
+        /// Γ in paper, sum of `Γ_j` of all parties
+        pub gamma: &'a Point<C>,
+        /// Δ_i in paper, `k Γ`
+        pub delta: &'a Point<C>,
+    }
+
+    /// Private data of prover in round 3
+    #[derive(Clone, Copy)]
+    pub struct PrivateData<'a> {
+        /// k_i in paper, plaintext of `K` and logarithm of `Δ` to base `Γ`
+        pub k: &'a Integer,
+        /// rho_i in paper, nonce of encryption `k -> K`
+        pub rho: &'a Nonce,
+    }
+
+    crate::transcript::assert_private!(PrivateData<'static>);
+
+    /// Proofs of round 3, computed by [`prove`]
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+    pub struct Bundle<C: Curve> {
+        pub log_star_commitment: log_star::Commitment<C>,
+        pub log_star_proof: log_star::Proof,
+    }
+
+    impl_encode! { Data<'a, C: Curve> { key, k, gamma, delta } }
+    impl_encode! { Bundle<C: Curve> { log_star_commitment, log_star_proof } }
+    impl_decode! { Bundle<C: Curve> { log_star_commitment, log_star_proof } }
+
+    impl<C: Curve> Bundle<C> {
+        /// Upper bound on size of [canonically encoded](crate::encoding) bundle produced
+        /// by honest prover
+        ///
+        /// `modulus_bits` is an upper bound on bit size of `N_i` and `N^`
+        pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+            log_star::Commitment::<C>::max_serialized_size(&security.log_star, modulus_bits)
+                + log_star::Proof::max_serialized_size(&security.log_star, modulus_bits)
+        }
+    }
+
+    impl<'a, C: Curve> Data<'a, C> {
+        fn log_star(&self) -> log_star::Data<'a, C> {
+            log_star::Data {
+                key0: self.key,
+                c: self.k,
+                b: self.gamma,
+                x: self.delta,
+            }
+        }
+    }
+
+    /// Computes proofs of round 3 for the verifier with ring-pedersen parameters
+    /// `verifier_aux`
+    pub fn prove<C: Curve, D: Digest, R: RngCore>(
+        shared_state: D,
+        verifier_aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<Bundle<C>, Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, verifier_aux, data, pdata, security, rng)
+    }
+
+    /// Computes proofs of round 3 within the protocol transcript
+    pub fn prove_with_transcript<C: Curve, D: Digest, R: RngCore>(
+        transcript: &mut Transcript<D>,
+        verifier_aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<Bundle<C>, Error> {
+        transcript.label(b"paillier_zk.presigning_proofs.round3");
+        let (log_star_commitment, log_star_proof) =
+            log_star::non_interactive::prove_with_transcript(
+                transcript,
+                verifier_aux,
+                data.log_star(),
+                log_star::PrivateData {
+                    x: pdata.k,
+                    nonce: pdata.rho,
+                },
+                &security.log_star,
+                rng,
+            )?;
+        Ok(Bundle {
+            log_star_commitment,
+            log_star_proof,
+        })
+    }
+
+    /// Verifies proofs of round 3, `verifier_aux` are ring-pedersen parameters of the
+    /// verifier
+    pub fn verify<C: Curve, D: Digest>(
+        shared_state: D,
+        verifier_aux: &Aux,
+        data: Data<C>,
+        security: &SecurityParams,
+        bundle: &Bundle<C>,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, verifier_aux, data, security, bundle)
+    }
+
+    /// Verifies proofs of round 3 within the protocol transcript
+    pub fn verify_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        verifier_aux: &Aux,
+        data: Data<C>,
+        security: &SecurityParams,
+        bundle: &Bundle<C>,
+    ) -> Result<(), InvalidProof> {
+        transcript.label(b"paillier_zk.presigning_proofs.round3");
+        log_star::non_interactive::verify_with_transcript(
+            transcript,
+            verifier_aux,
+            data.log_star(),
+            &bundle.log_star_commitment,
+            &security.log_star,
+            &bundle.log_star_proof,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fast_paillier::{DecryptionKey, EncryptionKey};
+    use generic_ec::{Curve, Point};
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::{Decode, Encode};
+    use crate::common::test::{aux, random_key};
+    use crate::common::IntegerExt;
+
+    type E = generic_ec::curves::Secp256r1;
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(256, 1280, 512, (Integer::ONE << 128_u32).complete())
+    }
+
+    /// Values of prover `i` and verifier `j` after all rounds of presigning
+    struct Session<C: Curve> {
+        key_i: EncryptionKey,
+        key_j: EncryptionKey,
+        k_i: Integer,
+        rho_i: Integer,
+        big_k_i: Integer,
+        big_k_j: Integer,
+        gamma_i: Integer,
+        nu_i: Integer,
+        big_g_i: Integer,
+        big_gamma_i: Point<C>,
+        x_i: Integer,
+        big_x_i: Point<C>,
+        beta: Integer,
+        s: Integer,
+        r: Integer,
+        d: Integer,
+        f: Integer,
+        beta_hat: Integer,
+        s_hat: Integer,
+        r_hat: Integer,
+        d_hat: Integer,
+        f_hat: Integer,
+        big_gamma: Point<C>,
+        delta_i: Point<C>,
+    }
+
+    impl<C: Curve> Session<C> {
+        fn new(rng: &mut rand_dev::DevRng, security: &super::SecurityParams) -> Self {
+            let key_i = random_key(rng).unwrap().encryption_key().clone();
+            let dk_j: DecryptionKey = random_key(rng).unwrap();
+            let key_j = dk_j.encryption_key().clone();
+            let l = (Integer::ONE << security.aff_g.l_x).complete();
+            let l_prime = (Integer::ONE << security.aff_g.l_y).complete();
+
+            let k_i = Integer::from_rng_pm(&l, rng);
+            let (big_k_i, rho_i) = key_i.encrypt_with_random(rng, &k_i).unwrap();
+            let k_j = Integer::from_rng_pm(&l, rng);
+            let (big_k_j, _) = key_j.encrypt_with_random(rng, &k_j).unwrap();
+            let gamma_i = Integer::from_rng_pm(&l, rng);
+            let (big_g_i, nu_i) = key_i.encrypt_with_random(rng, &gamma_i).unwrap();
+            let big_gamma_i = Point::generator() * gamma_i.to_scalar::<C>();
+            let x_i = Integer::from_rng_pm(&l, rng);
+            let big_x_i = Point::generator() * x_i.to_scalar::<C>();
+
+            let mut affine = |x: &Integer| {
+                let beta = Integer::from_rng_pm(&l_prime, rng);
+                let (f, r) = key_i.encrypt_with_random(rng, &beta).unwrap();
+                let (beta_enc, s) = key_j.encrypt_with_random(rng, &beta).unwrap();
+                let d = key_j
+                    .oadd(&key_j.omul(x, &big_k_j).unwrap(), &beta_enc)
+                    .unwrap();
+                (beta, s, r, d, f)
+            };
+            let (beta, s, r, d, f) = affine(&gamma_i);
+            let (beta_hat, s_hat, r_hat, d_hat, f_hat) = affine(&x_i);
+
+            let big_gamma = big_gamma_i + Point::generator() * generic_ec::Scalar::random(rng);
+            let delta_i = big_gamma * k_i.to_scalar::<C>();
+
+            Self {
+                key_i,
+                key_j,
+                k_i,
+                rho_i,
+                big_k_i,
+                big_k_j,
+                gamma_i,
+                nu_i,
+                big_g_i,
+                big_gamma_i,
+                x_i,
+                big_x_i,
+                beta,
+                s,
+                r,
+                d,
+                f,
+                beta_hat,
+                s_hat,
+                r_hat,
+                d_hat,
+                f_hat,
+                big_gamma,
+                delta_i,
+            }
+        }
+
+        fn round1(&self) -> (super::round1::Data<'_>, super::round1::PrivateData<'_>) {
+            let data = super::round1::Data {
+                key: &self.key_i,
+                k: &self.big_k_i,
+            };
+            let pdata = super::round1::PrivateData {
+                k: &self.k_i,
+                rho: &self.rho_i,
+            };
+            (data, pdata)
+        }
+
+        fn round2(&self) -> (super::round2::Data<'_, C>, super::round2::PrivateData<'_>) {
+            let data = super::round2::Data {
+                key: &self.key_i,
+                verifier_key: &self.key_j,
+                verifier_k: &self.big_k_j,
+                g: &self.big_g_i,
+                gamma: &self.big_gamma_i,
+                x: &self.big_x_i,
+                d: &self.d,
+                f: &self.f,
+                d_hat: &self.d_hat,
+                f_hat: &self.f_hat,
+            };
+            let pdata = super::round2::PrivateData {
+                gamma: &self.gamma_i,
+                nu: &self.nu_i,
+                x: &self.x_i,
+                beta: &self.beta,
+                s: &self.s,
+                r: &self.r,
+                beta_hat: &self.beta_hat,
+                s_hat: &self.s_hat,
+                r_hat: &self.r_hat,
+            };
+            (data, pdata)
+        }
+
+        fn round3(&self) -> (super::round3::Data<'_, C>, super::round3::PrivateData<'_>) {
+            let data = super::round3::Data {
+                key: &self.key_i,
+                k: &self.big_k_i,
+                gamma: &self.big_gamma,
+                delta: &self.delta_i,
+            };
+            let pdata = super::round3::PrivateData {
+                k: &self.k_i,
+                rho: &self.rho_i,
+            };
+            (data, pdata)
+        }
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let verifier_aux = aux(&mut rng);
+        let session = Session::<E>::new(&mut rng, &security);
+        let shared_state = sha2::Sha256::default();
+
+        let (data, pdata) = session.round1();
+        let bundle = super::round1::prove(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::round1::verify(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            &security,
+            &bundle,
+        )
+        .expect("round 1 failed");
+
+        let (data, pdata) = session.round2();
+        let bundle = super::round2::prove(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::round2::verify(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            &security,
+            &bundle,
+        )
+        .expect("round 2 failed");
+
+        let bytes = bundle.to_canonical_bytes();
+        assert!(bytes.len() <= super::round2::Bundle::<E>::max_serialized_size(&security, 2048));
+        let decoded = super::round2::Bundle::<E>::from_canonical_bytes(&bytes).unwrap();
+        super::round2::verify(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            &security,
+            &decoded,
+        )
+        .expect("decoded round 2 failed");
+
+        let (data, pdata) = session.round3();
+        let bundle = super::round3::prove(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::round3::verify(shared_state, &verifier_aux, data, &security, &bundle)
+            .expect("round 3 failed");
+    }
+
+    #[test]
+    fn proofs_are_bound_together() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let verifier_aux = aux(&mut rng);
+        let session = Session::<E>::new(&mut rng, &security);
+        let shared_state = sha2::Sha256::default();
+
+        let (data, pdata) = session.round2();
+        let mut prove = || {
+            super::round2::prove(
+                shared_state.clone(),
+                &verifier_aux,
+                data,
+                pdata,
+                &security,
+                &mut rng,
+            )
+            .unwrap()
+        };
+        let bundle1 = prove();
+        let bundle2 = prove();
+
+        // Each proof is valid on its own, but bound to the other proofs of its bundle
+        let mixed = super::round2::Bundle {
+            log_star_commitment: bundle2.log_star_commitment,
+            log_star_proof: bundle2.log_star_proof,
+            ..bundle1.clone()
+        };
+        super::round2::verify(shared_state.clone(), &verifier_aux, data, &security, &mixed)
+            .expect_err("mixed bundle should not pass");
+
+        // Affine operations can't be swapped
+        let swapped = super::round2::Data {
+            d: data.d_hat,
+            f: data.f_hat,
+            d_hat: data.d,
+            f_hat: data.f,
+            ..data
+        };
+        super::round2::verify(
+            shared_state.clone(),
+            &verifier_aux,
+            swapped,
+            &security,
+            &bundle1,
+        )
+        .expect_err("swapped statements should not pass");
+
+        // Bundle is made for particular verifier
+        let other_verifier_aux = aux(&mut rng);
+        super::round2::verify(shared_state, &other_verifier_aux, data, &security, &bundle1)
+            .expect_err("proof for other verifier should not pass");
+    }
+
+    #[test]
+    fn joint_challenge() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let verifier_aux = aux(&mut rng);
+        let session = Session::<E>::new(&mut rng, &security);
+        let shared_state = sha2::Sha256::default();
+
+        let (data, pdata) = session.round2();
+        let bundle = super::round2::joint::prove(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::round2::joint::verify(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            &security,
+            &bundle,
+        )
+        .expect("joint round 2 failed");
+
+        // Modes can't be mixed up
+        super::round2::verify(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            &security,
+            &bundle,
+        )
+        .expect_err("joint bundle should not pass sequential verification");
+        let sequential = super::round2::prove(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::round2::joint::verify(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            &security,
+            &sequential,
+        )
+        .expect_err("sequential bundle should not pass joint verification");
+
+        // The first proof is bound to the commitments that follow it
+        let other = super::round2::joint::prove(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let mixed = super::round2::Bundle {
+            log_star_commitment: other.log_star_commitment,
+            log_star_proof: other.log_star_proof,
+            ..bundle
+        };
+        super::round2::joint::verify(shared_state, &verifier_aux, data, &security, &mixed)
+            .expect_err("mixed bundle should not pass");
+    }
+}
@@ -0,0 +1,239 @@
+//! Redundant verification on heterogeneous arithmetic backends
+//!
+//! Proof verification may use different algorithms for the same arithmetic: naive
+//! exponentiation, precomputed multiexponentiation tables, or CRT-based exponentiation
+//! (see [`Aux`]). A bug in any of them could make a verifier accept an invalid proof or
+//! reject a valid one. [`redundant_verify`] hedges against that by running the same
+//! verification with two differently configured [`Aux`] and comparing the outcomes.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{paillier_encryption_in_range as p, redundant::redundant_verify};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     commitment: &p::Commitment,
+//! #     security: &p::SecurityParams,
+//! #     proof: &p::Proof,
+//! # ) -> Result<(), paillier_zk::redundant::RedundantVerifyError> {
+//! let shared_state = sha2::Sha256::default();
+//!
+//! // `aux` has precomputations enabled, another backend evaluates exponents naively
+//! let naive_aux = aux.without_precomputations();
+//! redundant_verify(aux, &naive_aux, |aux| {
+//!     p::non_interactive::verify(shared_state.clone(), aux, data, commitment, security, proof)
+//! })?;
+//! # Ok(()) }
+//! ```
+//!
+//! Prover can hedge against faults as well: [`compute_proof_checked`] verifies the
+//! freshly produced proof before returning it, so a fault of RNG or hardware (e.g. a
+//! bit flip) doesn't result in sending an invalid proof, which would trigger a costly
+//! abort round downstream.
+//!
+//! ```rust,no_run
+//! use paillier_zk::{paillier_encryption_in_range as p, redundant::compute_proof_checked};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     pdata: p::PrivateData,
+//! #     security: &p::SecurityParams,
+//! # ) -> Result<(), paillier_zk::Error> {
+//! let shared_state = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//!
+//! // Statement is prepared once, before proving
+//! let statement = p::PreparedStatement::new(data)?;
+//! let (commitment, proof) = compute_proof_checked(
+//!     || p::non_interactive::prove(shared_state.clone(), aux, data, pdata, security, &mut rng),
+//!     |(commitment, proof)| {
+//!         p::non_interactive::verify_prepared(
+//!             shared_state.clone(),
+//!             aux,
+//!             &statement,
+//!             commitment,
+//!             security,
+//!             proof,
+//!         )
+//!     },
+//! )?;
+//! # Ok(()) }
+//! ```
+
+use crate::common::{Aux, InvalidProof};
+use crate::{Error, ErrorReason};
+
+/// Runs `verify` with `primary` and `secondary` aux data and compares the outcomes
+///
+/// Returns `Ok(())` if both verifications succeeded, and
+/// [`RedundantVerifyError::InvalidProof`] if both rejected the proof for the same
+/// reason. If outcomes differ, [`RedundantVerifyError::Discrepancy`] is returned: it
+/// indicates a bug in one of the backends rather than an invalid proof.
+///
+/// `primary` and `secondary` must describe the same ring-pedersen parameters.
+pub fn redundant_verify<F>(
+    primary: &Aux,
+    secondary: &Aux,
+    verify: F,
+) -> Result<(), RedundantVerifyError>
+where
+    F: Fn(&Aux) -> Result<(), InvalidProof>,
+{
+    let primary = verify(primary);
+    let secondary = verify(secondary);
+    match (primary, secondary) {
+        (Ok(()), Ok(())) => Ok(()),
+        (Err(primary), Err(secondary)) if primary.reason() == secondary.reason() => {
+            Err(RedundantVerifyError::InvalidProof(primary))
+        }
+        (primary, secondary) => Err(RedundantVerifyError::Discrepancy {
+            primary: primary.err().map(Box::new),
+            secondary: secondary.err().map(Box::new),
+        }),
+    }
+}
+
+/// Runs `prove` and checks its output with `verify` before returning it
+///
+/// `verify` is called on prover's messages, i.e. commitment and proof. If they don't
+/// verify, an error is returned instead of them. Costs one extra verification, which
+/// can be made cheaper by preparing the statement in advance and verifying it with
+/// `verify_prepared`.
+pub fn compute_proof_checked<M, P, V>(prove: P, verify: V) -> Result<M, Error>
+where
+    P: FnOnce() -> Result<M, Error>,
+    V: FnOnce(&M) -> Result<(), InvalidProof>,
+{
+    let messages = prove()?;
+    verify(&messages).map_err(ErrorReason::SelfCheck)?;
+    Ok(messages)
+}
+
+/// Error returned by [`redundant_verify`]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum RedundantVerifyError {
+    /// Both backends rejected the proof
+    #[error(transparent)]
+    InvalidProof(InvalidProof),
+    /// Backends disagree on verification outcome
+    #[error("verification outcome differs between backends (primary: {primary:?}, secondary: {secondary:?})")]
+    Discrepancy {
+        /// Error returned by the primary backend, if any
+        primary: Option<Box<InvalidProof>>,
+        /// Error returned by the secondary backend, if any
+        secondary: Option<Box<InvalidProof>>,
+    },
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use std::sync::Arc;
+
+    use rug::{Complete, Integer};
+
+    use crate::multiexp::MultiexpTable;
+    use crate::paillier_encryption_in_range as p;
+
+    use super::{compute_proof_checked, redundant_verify, RedundantVerifyError};
+
+    #[test]
+    fn detects_discrepancy() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+
+        let naive_aux = crate::common::test::aux(&mut rng);
+        let mut aux = naive_aux.clone();
+        aux.multiexp = Some(Arc::new(
+            MultiexpTable::build(&aux.s, &aux.t, 1600, 3400, aux.rsa_modulo.clone()).unwrap(),
+        ));
+
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) =
+            p::non_interactive::prove(shared_state.clone(), &aux, data, pdata, &security, &mut rng)
+                .unwrap();
+        let verify = |aux: &p::Aux| {
+            p::non_interactive::verify(
+                shared_state.clone(),
+                aux,
+                data,
+                &commitment,
+                &security,
+                &proof,
+            )
+        };
+
+        redundant_verify(&aux, &naive_aux, verify).unwrap();
+
+        // Backend with broken precomputations
+        let mut broken_aux = aux.clone();
+        broken_aux.multiexp = Some(Arc::new(
+            MultiexpTable::build(&aux.t, &aux.s, 1600, 3400, aux.rsa_modulo.clone()).unwrap(),
+        ));
+        match redundant_verify(&naive_aux, &broken_aux, verify) {
+            Err(RedundantVerifyError::Discrepancy {
+                primary: None,
+                secondary: Some(_),
+            }) => (),
+            r => panic!("unexpected result: {r:?}"),
+        }
+    }
+
+    #[test]
+    fn checked_proof() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let shared_state = sha2::Sha256::default();
+        let verify = |(commitment, proof): &(p::Commitment, p::Proof)| {
+            p::non_interactive::verify(
+                shared_state.clone(),
+                &aux,
+                data,
+                commitment,
+                &security,
+                proof,
+            )
+        };
+
+        compute_proof_checked(
+            || {
+                p::non_interactive::prove(
+                    shared_state.clone(),
+                    &aux,
+                    data,
+                    pdata,
+                    &security,
+                    &mut rng,
+                )
+            },
+            verify,
+        )
+        .unwrap();
+
+        // Prover with a fault
+        let err = compute_proof_checked(
+            || {
+                let (commitment, mut proof) = p::non_interactive::prove(
+                    shared_state.clone(),
+                    &aux,
+                    data,
+                    pdata,
+                    &security,
+                    &mut rng,
+                )?;
+                proof.z1.toggle_bit(0);
+                Ok((commitment, proof))
+            },
+            verify,
+        )
+        .unwrap_err();
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(source.downcast_ref::<crate::InvalidProof>().is_some());
+    }
+}
@@ -0,0 +1,333 @@
+//! Re-proving under security parameters of the verifier
+//!
+//! Prover and verifier each hold their own security parameters. When they disagree,
+//! e.g. verifier enforces smaller `l`, an honest proof is rejected, and the prover
+//! has to prove the same statement again under the parameters of the verifier.
+//! [`Verifier`] exposes the parameters it enforces with [`Verifier::params`], and
+//! [`Prover`] keeps the statement and witness so it can [re-prove](Prover::reprove_with)
+//! without the caller assembling them again.
+//!
+//! Re-proving is safe as long as every proof is computed from fresh randomness: two
+//! responses computed from the same commitment to different challenges reveal the
+//! witness. [`Prover`] never keeps randomness between attempts, each attempt samples a
+//! new commitment. Parameters of the verifier are [validated](crate::compatibility)
+//! against the statement before they're adopted, so the verifier can't make the prover
+//! use parameters under which proofs don't hide the witness, e.g. slackness too
+//! small for the challenges.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{paillier_encryption_in_range as p, reprove};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     pdata: p::PrivateData,
+//! #     my_security: p::SecurityParams,
+//! #     verifier: &reprove::Verifier<p::Data>,
+//! # ) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut rng = rand_core::OsRng;
+//! let mut prover = reprove::Prover::new(data, aux, pdata, my_security);
+//! let (commitment, proof) = prover.prove(sha2::Sha256::default(), &mut rng)?;
+//! if verifier
+//!     .verify(sha2::Sha256::default(), &commitment, &proof)
+//!     .is_err()
+//! {
+//!     // Prove again under the parameters the verifier enforces
+//!     let (commitment, proof) =
+//!         prover.reprove_with(verifier.params().clone(), sha2::Sha256::default(), &mut rng)?;
+//!     verifier.verify(sha2::Sha256::default(), &commitment, &proof)?;
+//! }
+//! # Ok(()) }
+//! ```
+
+use digest::Digest;
+use rand_core::RngCore;
+
+use crate::common::{Aux, InvalidProof};
+use crate::compatibility::{Incompatible, RangeSecurity};
+use crate::policy::Statement;
+use crate::{Error, ErrorReason};
+
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+use crate::group_element_vs_paillier_encryption_in_range as log_star;
+#[cfg(feature = "paillier-affine-operation-in-range")]
+use crate::paillier_affine_operation_in_range as aff_g;
+#[cfg(feature = "paillier-encryption-in-range")]
+use crate::paillier_encryption_in_range as enc;
+
+/// Proof that can be re-proven under other parameters, implemented for its statement
+pub trait Reprovable: Statement {
+    /// Security parameters of the proof
+    type SecurityParams: RangeSecurity + Clone;
+    /// Private data of prover
+    type Witness: Copy;
+    /// Prover's first message
+    type Commitment;
+    /// Prover's response
+    type Proof;
+
+    /// Checks that `security` can be used to prove the statement, see
+    /// [`validate_parameters`](crate::compatibility::validate_parameters)
+    fn validate(&self, aux: &Aux, security: &Self::SecurityParams) -> Result<(), Incompatible>;
+
+    /// Computes the non-interactive proof
+    fn prove<D: Digest, R: RngCore>(
+        &self,
+        shared_state: D,
+        aux: &Aux,
+        witness: Self::Witness,
+        security: &Self::SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Proof), Error>;
+
+    /// Verifies the non-interactive proof
+    fn verify<D: Digest>(
+        &self,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof>;
+}
+
+/// Verifier of the statement along with the parameters it enforces
+#[derive(Debug, Clone)]
+pub struct Verifier<'a, S: Reprovable> {
+    statement: S,
+    aux: &'a Aux,
+    params: S::SecurityParams,
+}
+
+impl<'a, S: Reprovable> Verifier<'a, S> {
+    /// Constructs the verifier, `aux` are ring-pedersen parameters of the verifier
+    pub fn new(statement: S, aux: &'a Aux, params: S::SecurityParams) -> Self {
+        Self {
+            statement,
+            aux,
+            params,
+        }
+    }
+
+    /// Security parameters the verifier enforces
+    ///
+    /// Prover whose proof was rejected can obtain them and
+    /// [re-prove](Prover::reprove_with) under them
+    pub fn params(&self) -> &S::SecurityParams {
+        &self.params
+    }
+
+    /// Verifies the proof under [parameters of the verifier](Self::params)
+    pub fn verify<D: Digest>(
+        &self,
+        shared_state: D,
+        commitment: &S::Commitment,
+        proof: &S::Proof,
+    ) -> Result<(), InvalidProof> {
+        self.statement
+            .verify(shared_state, self.aux, &self.params, commitment, proof)
+    }
+}
+
+/// Prover holding the statement and witness between attempts
+pub struct Prover<'a, S: Reprovable> {
+    statement: S,
+    aux: &'a Aux,
+    witness: S::Witness,
+    params: S::SecurityParams,
+}
+
+impl<'a, S: Reprovable> Prover<'a, S> {
+    /// Constructs the prover, `aux` are ring-pedersen parameters of the verifier
+    pub fn new(statement: S, aux: &'a Aux, witness: S::Witness, params: S::SecurityParams) -> Self {
+        Self {
+            statement,
+            aux,
+            witness,
+            params,
+        }
+    }
+
+    /// Security parameters the prover currently uses
+    pub fn params(&self) -> &S::SecurityParams {
+        &self.params
+    }
+
+    /// Computes the proof under [current parameters](Self::params)
+    ///
+    /// Every call samples a fresh commitment
+    pub fn prove<D: Digest, R: RngCore>(
+        &self,
+        shared_state: D,
+        rng: &mut R,
+    ) -> Result<(S::Commitment, S::Proof), Error> {
+        self.statement
+            .prove(shared_state, self.aux, self.witness, &self.params, rng)
+    }
+
+    /// Adopts `params` of the verifier and computes the proof under them
+    ///
+    /// `params` are validated against the statement first. If they're incompatible,
+    /// an error is returned and the prover keeps using the previous parameters.
+    pub fn reprove_with<D: Digest, R: RngCore>(
+        &mut self,
+        params: S::SecurityParams,
+        shared_state: D,
+        rng: &mut R,
+    ) -> Result<(S::Commitment, S::Proof), Error> {
+        self.statement
+            .validate(self.aux, &params)
+            .map_err(ErrorReason::IncompatibleParams)?;
+        self.params = params;
+        self.prove(shared_state, rng)
+    }
+}
+
+#[cfg(feature = "paillier-encryption-in-range")]
+impl<'a> Reprovable for enc::Data<'a> {
+    type SecurityParams = enc::SecurityParams;
+    type Witness = enc::PrivateData<'a>;
+    type Commitment = enc::Commitment;
+    type Proof = enc::Proof;
+
+    fn validate(&self, aux: &Aux, security: &Self::SecurityParams) -> Result<(), Incompatible> {
+        crate::compatibility::validate_statement(self, None, aux, security)
+    }
+
+    fn prove<D: Digest, R: RngCore>(
+        &self,
+        shared_state: D,
+        aux: &Aux,
+        witness: Self::Witness,
+        security: &Self::SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Proof), Error> {
+        enc::non_interactive::prove(shared_state, aux, *self, witness, security, rng)
+    }
+
+    fn verify<D: Digest>(
+        &self,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        enc::non_interactive::verify(shared_state, aux, *self, commitment, security, proof)
+    }
+}
+
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+impl<'a, C: generic_ec::Curve> Reprovable for log_star::Data<'a, C> {
+    type SecurityParams = log_star::SecurityParams;
+    type Witness = log_star::PrivateData<'a>;
+    type Commitment = log_star::Commitment<C>;
+    type Proof = log_star::Proof;
+
+    fn validate(&self, aux: &Aux, security: &Self::SecurityParams) -> Result<(), Incompatible> {
+        crate::compatibility::validate_parameters::<C, _>(self, aux, security)
+    }
+
+    fn prove<D: Digest, R: RngCore>(
+        &self,
+        shared_state: D,
+        aux: &Aux,
+        witness: Self::Witness,
+        security: &Self::SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Proof), Error> {
+        log_star::non_interactive::prove(shared_state, aux, *self, witness, security, rng)
+    }
+
+    fn verify<D: Digest>(
+        &self,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        log_star::non_interactive::verify(shared_state, aux, *self, commitment, security, proof)
+    }
+}
+
+#[cfg(feature = "paillier-affine-operation-in-range")]
+impl<'a, C: generic_ec::Curve> Reprovable for aff_g::Data<'a, C> {
+    type SecurityParams = aff_g::SecurityParams;
+    type Witness = aff_g::PrivateData<'a>;
+    type Commitment = aff_g::Commitment<C>;
+    type Proof = aff_g::Proof;
+
+    fn validate(&self, aux: &Aux, security: &Self::SecurityParams) -> Result<(), Incompatible> {
+        crate::compatibility::validate_parameters::<C, _>(self, aux, security)
+    }
+
+    fn prove<D: Digest, R: RngCore>(
+        &self,
+        shared_state: D,
+        aux: &Aux,
+        witness: Self::Witness,
+        security: &Self::SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Proof), Error> {
+        aff_g::non_interactive::prove(shared_state, aux, *self, witness, security, rng)
+    }
+
+    fn verify<D: Digest>(
+        &self,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        aff_g::non_interactive::verify(shared_state, aux, *self, commitment, security, proof)
+    }
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::paillier_encryption_in_range as p;
+
+    use super::{Prover, Verifier};
+
+    #[test]
+    fn reprove_under_verifier_params() {
+        let mut rng = rand_dev::DevRng::new();
+        let q = (Integer::ONE << 128_u32).complete();
+        let prover_security = p::SecurityParams::new(1024, 256, q.clone());
+        let verifier_security = p::SecurityParams::new(512, 256, q.clone());
+
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, 256);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let verifier = Verifier::new(data, &aux, verifier_security);
+        let mut prover = Prover::new(data, &aux, pdata, prover_security);
+
+        // Verifier enforces smaller `l`
+        let (commitment, proof) = prover.prove(sha2::Sha256::default(), &mut rng).unwrap();
+        verifier
+            .verify(sha2::Sha256::default(), &commitment, &proof)
+            .expect_err("proof should not pass");
+
+        let (commitment, proof) = prover
+            .reprove_with(verifier.params().clone(), sha2::Sha256::default(), &mut rng)
+            .unwrap();
+        verifier
+            .verify(sha2::Sha256::default(), &commitment, &proof)
+            .expect("proof failed");
+        assert_eq!(prover.params().l, 512);
+
+        // Parameters that don't hide the witness are not adopted
+        let weak = p::SecurityParams::new(512, 64, q);
+        let r = prover.reprove_with(weak, sha2::Sha256::default(), &mut rng);
+        assert!(matches!(
+            r.map(|_| ()).map_err(|e| e.0),
+            Err(crate::ErrorReason::IncompatibleParams(_))
+        ));
+        assert_eq!(prover.params().epsilon, 256);
+    }
+}
@@ -0,0 +1,677 @@
+//! ZK-proof of ring-pedersen parameters. Called Пprm or Rprm in the CGGMP21 paper.
+//!
+//! ## Description
+//! A party P has generated ring-pedersen parameters [`Aux`]: modulus `N^` and
+//! `s = t^λ mod N^`. P wants to prove that `s` belongs to the group generated by `t`,
+//! without disclosing `λ`. Other proofs of this crate rely on that: if `s` is not
+//! a power of `t`, commitments `s^x t^y mod N^` may be binding in a way that leaks
+//! `x` to the party that generated the parameters.
+//!
+//! Each of `M` repetitions has binary challenge, so soundness error is `1/2^M`.
+//! CGGMP21 suggests `M = 80`.
+//!
+//! Given:
+//! - `aux` - ring-pedersen parameters `N^`, `s`, `t`
+//!
+//! Prove:
+//! - `s = t^λ mod N^` for some `λ`
+//!
+//! Disclosing only: `aux`
+//!
+//! ## Example
+//! ```rust
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use rug::{Integer, Complete};
+//! use paillier_zk::{ring_pedersen_parameters as p, IntegerExt};
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 0. Prover P generates ring-pedersen parameters
+//! let p = fast_paillier::utils::generate_safe_prime(&mut rng, 256);
+//! let q = fast_paillier::utils::generate_safe_prime(&mut rng, 256);
+//! let n = (&p * &q).complete();
+//! let phi = (&p - 1u8).complete() * (&q - 1u8).complete();
+//! let t = Integer::gen_invertible(&n, &mut rng).square().modulo(&n);
+//! let lambda = phi.random_below(&mut fast_paillier::utils::external_rand(&mut rng));
+//! let s = t.pow_mod_ref(&lambda, &n).unwrap().into();
+//! let aux = p::Aux::new(s, t, n);
+//!
+//! // 1. P computes a non-interactive proof that `s` is a power of `t`:
+//!
+//! // Security parameter
+//! const SECURITY: usize = 80;
+//! // Verifier and prover share the same state
+//! let prover_shared_state = sha2::Sha256::default();
+//! let verifier_shared_state = sha2::Sha256::default();
+//!
+//! let data = p::Data { aux: &aux };
+//! let pdata = p::PrivateData { p: &p, q: &q, lambda: &lambda };
+//!
+//! let (commitment, proof) =
+//!     p::non_interactive::prove::<{SECURITY}, _, _>(
+//!         prover_shared_state,
+//!         data,
+//!         pdata,
+//!         &mut rng,
+//!     )?;
+//!
+//! // 2. P sends `aux, commitment, proof` to the verifier V
+//!
+//! # fn send(_: &p::Aux, _: &p::Commitment<{SECURITY}>, _: &p::Proof<{SECURITY}>) { }
+//! send(&aux, &commitment, &proof);
+//!
+//! // 3. V receives and verifies the proof:
+//!
+//! # let recv = || (aux, commitment, proof);
+//! let (aux, commitment, proof) = recv();
+//!
+//! p::non_interactive::verify::<{SECURITY}, _>(
+//!     verifier_shared_state,
+//!     p::Data { aux: &aux },
+//!     &commitment,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//! If the verification succeeded, V can use `aux` in proofs where P is the verifier
+
+use rug::Integer;
+
+use crate::aux_generation::AuxSecrets;
+use crate::common::encoding::{impl_encode, Decode, Encode, Schema};
+use crate::{InvalidProof, InvalidProofReason};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::Aux;
+
+/// Public data that both parties know: the ring-pedersen parameters
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    pub aux: &'a Aux,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// Factor of `N^`
+    pub p: &'a Integer,
+    /// Factor of `N^`
+    pub q: &'a Integer,
+    /// Discrete logarithm of `s` base `t`
+    pub lambda: &'a Integer,
+}
+
+impl<'a> From<&'a AuxSecrets> for PrivateData<'a> {
+    fn from(secrets: &'a AuxSecrets) -> Self {
+        Self {
+            p: &secrets.p,
+            q: &secrets.q,
+            lambda: &secrets.lambda,
+        }
+    }
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Validating it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone, Copy)]
+pub struct PreparedStatement<'a> {
+    data: Data<'a>,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Validates the statement, checking that the modulus is composite and odd, and
+    /// that `s` and `t` are invertible
+    pub fn new(data: Data<'a>) -> Result<Self, InvalidProof> {
+        let n = &data.aux.rsa_modulo;
+        if n.is_probably_prime(25) != rug::integer::IsPrime::No {
+            return Err(InvalidProofReason::ModulusIsPrime.into());
+        }
+        if n.is_even() {
+            return Err(InvalidProofReason::ModulusIsEven.into());
+        }
+        crate::common::fail_if_not_coprime(n, [&data.aux.s, &data.aux.t])?;
+        crate::common::lap!(Check("modulus is composite and odd"));
+        Ok(Self { data })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment<const M: usize> {
+    /// `A_i = t^(a_i) mod N^`
+    #[cfg_attr(
+        // A trick to serialize arbitrary size arrays
+        feature = "serde",
+        serde(with = "serde_with::As::<[serde_with::Same; M]>")
+    )]
+    pub a: [Integer; M],
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment<const M: usize> {
+    pub a: [Integer; M],
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment<1>);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+///
+/// Consists of `M` binary challenges
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Challenge<const M: usize> {
+    pub es: [bool; M],
+}
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]. Consists of M responses for each challenge
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof<const M: usize> {
+    /// `z_i = a_i + e_i λ mod phi(N^)`
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_with::As::<[serde_with::Same; M]>")
+    )]
+    pub z: [Integer; M],
+}
+
+impl_encode! { Data<'a> { aux } }
+
+impl<const M: usize> Encode for Commitment<M> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.a[..].encode(out)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.a[..].encoded_len()
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        self.a[..].encode_chunks(sink)
+    }
+}
+
+impl<const M: usize> Decode for Commitment<M> {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let a: Vec<Integer> = Decode::decode(bytes)?;
+        Some(Self {
+            a: a.try_into().ok()?,
+        })
+    }
+}
+
+impl<const M: usize> Encode for Proof<M> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.z[..].encode(out)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.z[..].encoded_len()
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        self.z[..].encode_chunks(sink)
+    }
+}
+
+impl<const M: usize> Decode for Proof<M> {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let z: Vec<Integer> = Decode::decode(bytes)?;
+        Some(Self {
+            z: z.try_into().ok()?,
+        })
+    }
+}
+
+impl<const M: usize> Commitment<M> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N^`
+    pub fn max_serialized_size(modulus_bits: usize) -> usize {
+        Self::schema(modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N^`
+    pub fn schema(modulus_bits: usize) -> Schema {
+        let a = Schema::default().integer("a", modulus_bits);
+        Schema::default().sequence("a", M, a)
+    }
+}
+
+impl<const M: usize> Proof<M> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N^`
+    pub fn max_serialized_size(modulus_bits: usize) -> usize {
+        Self::schema(modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N^`
+    pub fn schema(modulus_bits: usize) -> Schema {
+        let z = Schema::default().integer("z", modulus_bits);
+        Schema::default().sequence("z", M, z)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{fail_if, fail_if_degenerate, fail_if_ne, InvalidProofReason};
+    use crate::{Error, ErrorReason, InvalidProof};
+
+    use super::{
+        Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+    };
+
+    /// Create random commitment
+    pub fn commit<const M: usize, R: RngCore>(
+        data: Data,
+        pdata: PrivateData,
+        rng: &mut R,
+    ) -> Result<(Commitment<M>, PrivateCommitment<M>), Error> {
+        let phi = (pdata.p - 1u8).complete() * (pdata.q - 1u8).complete();
+        let a = [(); M].map(|()| -> Integer {
+            phi.random_below_ref(&mut fast_paillier::utils::external_rand(rng))
+                .into()
+        });
+        // We do an extra allocation as workaround while `array::try_map` is not stable
+        let commitment = a
+            .iter()
+            .map(|a| data.aux.pow_mod(&data.aux.t, a))
+            .collect::<Result<Vec<_>, _>>()?
+            .try_into()
+            .map_err(|_| ErrorReason::Length)?;
+        Ok((Commitment { a: commitment }, PrivateCommitment { a }))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove<const M: usize>(
+        pdata: PrivateData,
+        pcomm: &PrivateCommitment<M>,
+        challenge: &Challenge<M>,
+    ) -> Result<Proof<M>, Error> {
+        let phi = (pdata.p - 1u8).complete() * (pdata.q - 1u8).complete();
+        let mut z = pcomm.a.clone();
+        for (z, e) in z.iter_mut().zip(&challenge.es) {
+            if *e {
+                *z += pdata.lambda;
+                *z %= &phi;
+            }
+        }
+        Ok(Proof { z })
+    }
+
+    /// Verify the proof. If this succeeds, `s` is a power of `t` with chance
+    /// `1 - 1/2^M`
+    pub fn verify<const M: usize>(
+        data: Data,
+        commitment: &Commitment<M>,
+        challenge: &Challenge<M>,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data)?;
+        verify_prepared(&statement, commitment, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared<const M: usize>(
+        statement: &PreparedStatement,
+        commitment: &Commitment<M>,
+        challenge: &Challenge<M>,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        let aux = statement.data.aux;
+        // All-zero challenge doesn't involve `s`, making the checks vacuous
+        fail_if(
+            InvalidProofReason::ZeroChallenge,
+            challenge.es.iter().any(|e| *e),
+        )?;
+        fail_if_degenerate(&aux.rsa_modulo, &commitment.a)?;
+        for ((a, z), e) in commitment.a.iter().zip(&proof.z).zip(&challenge.es) {
+            let lhs = aux.pow_mod(&aux.t, z)?;
+            let rhs = if *e {
+                (a * &aux.s).complete().modulo(&aux.rsa_modulo)
+            } else {
+                a.clone()
+            };
+            fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("powers of t"));
+        Ok(())
+    }
+
+    /// Generate random challenge
+    pub fn challenge<const M: usize, R: RngCore>(rng: &mut R) -> Challenge<M> {
+        Challenge {
+            es: [(); M].map(|()| rng.next_u32() & 1 == 1),
+        }
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof};
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<const M: usize, R: RngCore, D: Digest>(
+        shared_state: D,
+        data: Data,
+        pdata: PrivateData,
+        rng: &mut R,
+    ) -> Result<(Commitment<M>, Proof<M>), Error> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<const M: usize, R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        pdata: PrivateData,
+        rng: &mut R,
+    ) -> Result<(Commitment<M>, Proof<M>), Error> {
+        let (commitment, pcomm) = super::interactive::commit(data, pdata, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, data, &commitment);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((commitment, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<const M: usize, D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment<M>,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, commitment, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<const M: usize, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment<M>,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, data, commitment);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(data, commitment, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<const M: usize, D: Digest>(
+        shared_state: D,
+        statement: &PreparedStatement,
+        commitment: &Commitment<M>,
+        proof: &Proof<M>,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge(shared_state, statement.data(), commitment);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(statement, commitment, &challenge, proof)
+    }
+
+    /// Deterministically compute challenge based on prior known values in protocol
+    pub fn challenge<const M: usize, D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment<M>,
+    ) -> Challenge<M> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data, commitment)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<const M: usize, D: Digest>(
+        shared_state: D,
+        data: Data,
+        commitment: &Commitment<M>,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript::<M, _>(transcript, data, commitment);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<const M: usize, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data,
+        commitment: &Commitment<M>,
+    ) -> Challenge<M> {
+        transcript.label(b"paillier_zk.ring_pedersen_parameters");
+        transcript.absorb(b"M", &(M as u64).to_be_bytes());
+        transcript.absorb_value(b"aux", data.aux);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(&mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use rug::{Complete, Integer};
+
+    use crate::common::extract::{ensure, ExtractionErrorReason};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        /// Discrete logarithm of `s` base `t`, not necessarily reduced
+        pub lambda: Integer,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    ///
+    /// Challenges must differ in at least one bit, whose responses give `λ`
+    pub fn extract<const M: usize>(
+        data: Data,
+        challenge1: &Challenge<M>,
+        proof1: &Proof<M>,
+        challenge2: &Challenge<M>,
+        proof2: &Proof<M>,
+    ) -> Result<Witness, ExtractionError> {
+        let i = challenge1
+            .es
+            .iter()
+            .zip(&challenge2.es)
+            .position(|(e1, e2)| e1 != e2)
+            .ok_or(ExtractionErrorReason::SameChallenge)?;
+        let lambda = if challenge1.es[i] {
+            (&proof1.z[i] - &proof2.z[i]).complete()
+        } else {
+            (&proof2.z[i] - &proof1.z[i]).complete()
+        };
+        let aux = data.aux;
+        let s = aux
+            .t
+            .pow_mod_ref(&lambda, &aux.rsa_modulo)
+            .map(Integer::from);
+        ensure(s.as_ref() == Some(&aux.s))?;
+        Ok(Witness { lambda })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::test::generate_blum_prime;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    const M: usize = 80;
+
+    struct Statement {
+        aux: super::Aux,
+        p: Integer,
+        q: Integer,
+        lambda: Integer,
+    }
+
+    impl Statement {
+        fn data(&self) -> super::Data<'_> {
+            super::Data { aux: &self.aux }
+        }
+
+        fn pdata(&self) -> super::PrivateData<'_> {
+            super::PrivateData {
+                p: &self.p,
+                q: &self.q,
+                lambda: &self.lambda,
+            }
+        }
+    }
+
+    fn statement(rng: &mut impl rand_core::RngCore) -> Statement {
+        let p = generate_blum_prime(rng, 256);
+        let q = generate_blum_prime(rng, 256);
+        let n = (&p * &q).complete();
+        let phi = (&p - 1u8).complete() * (&q - 1u8).complete();
+        let t = Integer::gen_invertible(&n, rng).square().modulo(&n);
+        let lambda = phi.random_below(&mut fast_paillier::utils::external_rand(rng));
+        let s = t.pow_mod_ref(&lambda, &n).unwrap().into();
+        Statement {
+            aux: super::Aux::new(s, t, n),
+            p,
+            q,
+            lambda,
+        }
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let statement = statement(&mut rng);
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove::<M, _, _>(
+            shared_state.clone(),
+            statement.data(),
+            statement.pdata(),
+            &mut rng,
+        )
+        .unwrap();
+        super::non_interactive::verify(shared_state, statement.data(), &commitment, &proof)
+            .expect("proof failed");
+    }
+
+    #[test]
+    fn failing() {
+        let mut rng = rand_dev::DevRng::new();
+        let mut statement = statement(&mut rng);
+        // `s` is not a power of `t` with overwhelming probability
+        statement.aux.s = Integer::gen_invertible(&statement.aux.rsa_modulo, &mut rng);
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::non_interactive::prove::<M, _, _>(
+            shared_state.clone(),
+            statement.data(),
+            statement.pdata(),
+            &mut rng,
+        )
+        .unwrap();
+        let r = super::non_interactive::verify(shared_state, statement.data(), &commitment, &proof)
+            .expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::EqualityCheck(1));
+    }
+
+    #[test]
+    fn degenerate_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let statement = statement(&mut rng);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, pcomm) =
+            super::interactive::commit::<M, _>(data, pdata, &mut rng).unwrap();
+        let mut challenge = super::interactive::challenge(&mut rng);
+        challenge.es[0] = true;
+        let proof = super::interactive::prove(pdata, &pcomm, &challenge).unwrap();
+        super::interactive::verify(data, &commitment, &challenge, &proof).unwrap();
+
+        // All-zero challenge doesn't involve `s`
+        let zero_challenge = super::Challenge { es: [false; M] };
+        let zero_proof = super::interactive::prove(pdata, &pcomm, &zero_challenge).unwrap();
+        let r = super::interactive::verify(data, &commitment, &zero_challenge, &zero_proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        let mut degenerate = commitment;
+        degenerate.a[1] = Integer::from(1);
+        let r = super::interactive::verify(data, &degenerate, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        let mut rng = rand_dev::DevRng::new();
+        let statement = statement(&mut rng);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (_commitment, pcomm) =
+            super::interactive::commit::<M, _>(data, pdata, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&mut rng);
+        let mut e2 = e1.clone();
+        e2.es[3] = !e2.es[3];
+        let proof1 = super::interactive::prove(pdata, &pcomm, &e1).unwrap();
+        let proof2 = super::interactive::prove(pdata, &pcomm, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        let phi = (&statement.p - 1u8).complete() * (&statement.q - 1u8).complete();
+        assert_eq!(witness.lambda.modulo(&phi), statement.lambda);
+
+        assert!(super::extract::extract(data, &e1, &proof1, &e1, &proof1).is_err());
+    }
+}
@@ -0,0 +1,456 @@
+//! Schnorr proof of knowledge of discrete logarithm. Called Пsch or Rsch in the
+//! CGGMP21 paper.
+//!
+//! ## Description
+//!
+//! A party P has a number `X = x G`, with G being a generator of curve `E`. P
+//! shares X with V and wants to prove that it knows x.
+//!
+//! Given:
+//! - Curve `E`
+//! - `X = x G` - data to obtain proof about
+//!
+//! Prove:
+//! - P knows `log X`
+//!
+//! Disclosing only: `X`
+//!
+//! `x = 0` is a valid witness: `X` is then the identity point.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use generic_ec::{Point, Scalar, curves::Secp256k1 as E};
+//! use paillier_zk::schnorr_pok as p;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 1. Setup: prover has some secret `x` and computes `X`
+//!
+//! let x = Scalar::<E>::random(&mut rng);
+//! let X = Point::generator() * x;
+//!
+//! // 2. Prover computes a non-interactive proof that it knows `x`:
+//!
+//! let data = p::Data { x: &X };
+//! let (commitment, proof) =
+//!     p::non_interactive::prove(shared_state_prover, data, p::PrivateData { x: &x }, &mut rng);
+//!
+//! // 3. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data<E>, _: &p::Commitment<E>, _: &p::Proof<E>) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 4. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(shared_state_verifier, data, &commitment, &proof)?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use generic_ec::{Curve, Point, Scalar};
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a, C: Curve> {
+    /// X in paper, exponent of x
+    pub x: &'a Point<C>,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a, C: Curve> {
+    /// x in paper, logarithm of X
+    pub x: &'a Scalar<C>,
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Commitment<C: Curve> {
+    pub a: Point<C>,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment<C: Curve> {
+    pub alpha: Scalar<C>,
+}
+
+crate::transcript::assert_private!(<C: Curve> PrivateData<'static, C>, PrivateCommitment<C>);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge<C> = Scalar<C>;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Proof<C: Curve> {
+    pub z: Scalar<C>,
+}
+
+impl_encode! { Data<'a, C: Curve> { x } }
+impl_encode! { Commitment<C: Curve> { a } }
+impl_decode! { Commitment<C: Curve> { a } }
+impl_encode! { Proof<C: Curve> { z } }
+impl_decode! { Proof<C: Curve> { z } }
+
+impl<C: Curve> Commitment<C> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    pub fn max_serialized_size() -> usize {
+        Self::schema().max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    pub fn schema() -> Schema {
+        Schema::default().point::<C>("a")
+    }
+}
+
+impl<C: Curve> Proof<C> {
+    /// Size of [canonically encoded](crate::encoding) proof
+    pub fn max_serialized_size() -> usize {
+        Self::schema().max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof
+    pub fn schema() -> Schema {
+        Schema::default().scalar::<C>("z")
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use generic_ec::{traits::IsZero, Curve, Point, Scalar};
+    use rand_core::RngCore;
+
+    use crate::common::{fail_if, fail_if_ne, InvalidProofReason};
+    use crate::InvalidProof;
+
+    use super::{Challenge, Commitment, Data, PrivateCommitment, PrivateData, Proof};
+
+    /// Create random commitment
+    pub fn commit<C: Curve, R: RngCore>(rng: &mut R) -> (Commitment<C>, PrivateCommitment<C>) {
+        let alpha = Scalar::random(rng);
+        let commitment = Commitment {
+            a: Point::generator() * alpha,
+        };
+        (commitment, PrivateCommitment { alpha })
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove<C: Curve>(
+        pdata: PrivateData<C>,
+        pcomm: &PrivateCommitment<C>,
+        challenge: &Challenge<C>,
+    ) -> Proof<C> {
+        Proof {
+            z: pcomm.alpha + challenge * pdata.x,
+        }
+    }
+
+    /// Verify the proof
+    pub fn verify<C: Curve>(
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        challenge: &Challenge<C>,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof> {
+        fail_if(InvalidProofReason::ZeroChallenge, !challenge.is_zero())?;
+        fail_if(
+            InvalidProofReason::DegenerateCommitment,
+            !commitment.a.is_zero(),
+        )?;
+        let lhs = Point::generator() * proof.z;
+        let rhs = commitment.a + data.x * challenge;
+        fail_if_ne(InvalidProofReason::EqualityCheck(1), lhs, rhs)?;
+        crate::common::lap!(Check("group element"));
+        Ok(())
+    }
+
+    /// Generate random challenge
+    pub fn challenge<C: Curve, R>(rng: &mut R) -> Challenge<C>
+    where
+        R: RngCore,
+    {
+        Scalar::random(rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::InvalidProof;
+
+    use super::{Challenge, Commitment, Data, PrivateData, Proof};
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<C: Curve, R: RngCore, D: Digest>(
+        shared_state: D,
+        data: Data<C>,
+        pdata: PrivateData<C>,
+        rng: &mut R,
+    ) -> (Commitment<C>, Proof<C>) {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, data, pdata, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<C: Curve, R: RngCore, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data<C>,
+        pdata: PrivateData<C>,
+        rng: &mut R,
+    ) -> (Commitment<C>, Proof<C>) {
+        let (comm, pcomm) = super::interactive::commit(rng);
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, data, &comm);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(pdata, &pcomm, &challenge);
+        crate::common::lap!(Respond);
+        (comm, proof)
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<C: Curve, D: Digest>(
+        shared_state: D,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, data, commitment, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        proof: &Proof<C>,
+    ) -> Result<(), InvalidProof> {
+        let challenge = challenge_with_transcript(transcript, data, commitment);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(data, commitment, &challenge, proof)
+    }
+
+    /// Internal function for deriving challenge from protocol values
+    /// deterministically
+    pub fn challenge<C: Curve, D: Digest>(
+        shared_state: D,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+    ) -> Challenge<C> {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, data, commitment)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<C: Curve, D: Digest>(
+        shared_state: D,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+    ) -> Vec<u8> {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, data, commitment);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<C: Curve, D: Digest>(
+        transcript: &mut Transcript<D>,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+    ) -> Challenge<C> {
+        transcript.label(b"paillier_zk.schnorr_pok");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_point(b"X", data.x);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(&mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use generic_ec::{Curve, NonZero, Point, Scalar};
+
+    use crate::common::extract::{ensure, ExtractionErrorReason};
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness<C: Curve> {
+        pub x: Scalar<C>,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract<C: Curve>(
+        data: Data<C>,
+        challenge1: &Challenge<C>,
+        proof1: &Proof<C>,
+        challenge2: &Challenge<C>,
+        proof2: &Proof<C>,
+    ) -> Result<Witness<C>, ExtractionError> {
+        let e_diff = NonZero::from_scalar(challenge1 - challenge2)
+            .ok_or(ExtractionErrorReason::SameChallenge)?;
+        let x = (proof1.z - proof2.z) * e_diff.invert();
+        ensure(Point::generator() * x == *data.x)?;
+        Ok(Witness { x })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generic_ec::{Curve, Point, Scalar};
+
+    use crate::common::InvalidProofReason;
+
+    fn run<C: Curve>(x: Scalar<C>, x_pub: Point<C>) -> Result<(), crate::common::InvalidProof> {
+        let mut rng = rand_dev::DevRng::new();
+        let data = super::Data { x: &x_pub };
+        let pdata = super::PrivateData { x: &x };
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) =
+            super::non_interactive::prove(shared_state.clone(), data, pdata, &mut rng);
+        super::non_interactive::verify(shared_state, data, &commitment, &proof)
+    }
+
+    fn passing_test<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Scalar::<C>::random(&mut rng);
+        run(x, Point::generator() * x).expect("proof failed");
+    }
+
+    fn zero_witness<C: Curve>() {
+        // X is the identity point
+        run::<C>(Scalar::zero(), Point::zero()).expect("proof failed");
+    }
+
+    fn failing_test<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Scalar::<C>::random(&mut rng);
+        let r =
+            run(x, Point::generator() * (x + Scalar::one())).expect_err("proof should not pass");
+        assert_eq!(r.reason(), InvalidProofReason::EqualityCheck(1));
+    }
+
+    fn degenerate_transcript<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let x = Scalar::<C>::random(&mut rng);
+        let x_pub = Point::generator() * x;
+        let data = super::Data { x: &x_pub };
+        let pdata = super::PrivateData { x: &x };
+
+        let (commitment, pcommitment) = super::interactive::commit::<C, _>(&mut rng);
+        // Zero challenge makes the check vacuous
+        let challenge = Scalar::zero();
+        let proof = super::interactive::prove(pdata, &pcommitment, &challenge);
+        let r = super::interactive::verify(data, &commitment, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Identity point in commitment is rejected
+        let challenge = super::interactive::challenge(&mut rng);
+        let commitment = super::Commitment { a: Point::zero() };
+        let pcommitment = super::PrivateCommitment {
+            alpha: Scalar::zero(),
+        };
+        let proof = super::interactive::prove(pdata, &pcommitment, &challenge);
+        let r = super::interactive::verify(data, &commitment, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[test]
+    fn passing_p256() {
+        passing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn zero_witness_p256() {
+        zero_witness::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn failing_p256() {
+        failing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn degenerate_transcript_p256() {
+        degenerate_transcript::<generic_ec::curves::Secp256r1>()
+    }
+
+    #[test]
+    fn passing_million() {
+        passing_test::<crate::curve::C>()
+    }
+    #[test]
+    fn failing_million() {
+        failing_test::<crate::curve::C>()
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let x = Scalar::<C>::random(&mut rng);
+        let x_pub = Point::generator() * x;
+        let data = super::Data { x: &x_pub };
+        let pdata = super::PrivateData { x: &x };
+
+        let (_commitment, pcommitment) = super::interactive::commit::<C, _>(&mut rng);
+        let e1 = super::interactive::challenge(&mut rng);
+        let e2 = super::interactive::challenge(&mut rng);
+        let proof1 = super::interactive::prove(pdata, &pcommitment, &e1);
+        let proof2 = super::interactive::prove(pdata, &pcommitment, &e2);
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.x, x);
+
+        assert!(super::extract::extract(data, &e1, &proof1, &e1, &proof1).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Security levels recommended by CGGMP21
+//!
+//! Every proof takes its own security parameters, and the parameters of proofs used
+//! together must agree with each other and with the size of the keys. [`SecurityLevel`]
+//! fixes all of them for a target level of security, and derives parameters of each
+//! proof from it.
+//!
+//! Parameters follow CGGMP21 for a curve with 256-bit order:
+//! - `l = 256` is the bit size of secrets which are scalars of the curve
+//! - `l' = 5l` and `epsilon = 2l`
+//! - challenges are sampled in `±2^kappa`, and Пmod and Пprm are repeated `m = kappa`
+//!   times, so soundness error of every proof is `2^-kappa`
+//! - paillier and ring-pedersen moduli are at least 3072 bits for 128 bits of security,
+//!   and 2048 bits for 112 bits of security, which is the level of the RSA problem
+//!   for these sizes
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::security_level::SecurityLevel;
+//! use paillier_zk::paillier_encryption_in_range as enc;
+//! # fn doc_fn(
+//! #     aux: &enc::Aux,
+//! #     data: enc::Data,
+//! #     commitment: &enc::Commitment,
+//! #     proof: &enc::Proof,
+//! # ) -> Result<(), Box<dyn std::error::Error>> {
+//! let level = SecurityLevel::recommended_128();
+//! enc::non_interactive::verify(
+//!     sha2::Sha256::default(),
+//!     aux,
+//!     data,
+//!     commitment,
+//!     &level.enc(),
+//!     proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+
+use rug::{Complete, Integer};
+
+/// Parameters of all the proofs for a target level of security
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SecurityLevel {
+    /// Bits of security
+    pub kappa: usize,
+    /// l in paper, bit size of secrets
+    pub l: usize,
+    /// l' in paper, bit size of masks, e.g. `y` of
+    /// [Пaff-g](crate::paillier_affine_operation_in_range)
+    pub l_prime: usize,
+    /// Epsilon in paper, slackness parameter
+    pub epsilon: usize,
+    /// M in paper, amount of repetitions of Пmod and Пprm
+    pub m: usize,
+    /// q in paper, challenges are sampled in `±q`
+    pub q: Integer,
+    /// Minimal bit size of paillier moduli
+    pub min_modulus_bits: u32,
+    /// Minimal bit size of ring-pedersen modulus `N^`
+    pub min_aux_modulus_bits: u32,
+}
+
+impl SecurityLevel {
+    /// 128 bits of security
+    pub fn recommended_128() -> Self {
+        Self::cggmp21(128, 3072)
+    }
+
+    /// 112 bits of security
+    pub fn recommended_112() -> Self {
+        Self::cggmp21(112, 2048)
+    }
+
+    fn cggmp21(kappa: usize, modulus_bits: u32) -> Self {
+        let l = 256;
+        Self {
+            kappa,
+            l,
+            l_prime: 5 * l,
+            epsilon: 2 * l,
+            m: kappa,
+            q: (Integer::ONE << kappa).complete(),
+            min_modulus_bits: modulus_bits,
+            min_aux_modulus_bits: modulus_bits,
+        }
+    }
+
+    /// Parameters of [Пenc](crate::paillier_encryption_in_range)
+    #[cfg(feature = "paillier-encryption-in-range")]
+    pub fn enc(&self) -> crate::paillier_encryption_in_range::SecurityParams {
+        crate::paillier_encryption_in_range::SecurityParams::new(
+            self.l,
+            self.epsilon,
+            self.q.clone(),
+        )
+    }
+
+    /// Parameters of [Пlog*](crate::group_element_vs_paillier_encryption_in_range)
+    #[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+    pub fn log_star(&self) -> crate::group_element_vs_paillier_encryption_in_range::SecurityParams {
+        crate::group_element_vs_paillier_encryption_in_range::SecurityParams::new(
+            self.l,
+            self.epsilon,
+            self.q.clone(),
+        )
+    }
+
+    /// Parameters of [Пaff-g](crate::paillier_affine_operation_in_range)
+    #[cfg(feature = "paillier-affine-operation-in-range")]
+    pub fn aff_g(&self) -> crate::paillier_affine_operation_in_range::SecurityParams {
+        crate::paillier_affine_operation_in_range::SecurityParams::new(
+            self.l,
+            self.l_prime,
+            self.epsilon,
+            self.q.clone(),
+        )
+    }
+
+    /// Parameters of [Пmul*](crate::group_element_vs_paillier_multiplication)
+    #[cfg(feature = "group-element-vs-paillier-multiplication")]
+    pub fn mul_star(&self) -> crate::group_element_vs_paillier_multiplication::SecurityParams {
+        crate::group_element_vs_paillier_multiplication::SecurityParams::new(
+            self.l,
+            self.epsilon,
+            self.q.clone(),
+        )
+    }
+
+    /// Parameters of [Пmul](crate::paillier_multiplication)
+    #[cfg(feature = "paillier-multiplication")]
+    pub fn mul(&self) -> crate::paillier_multiplication::SecurityParams {
+        crate::paillier_multiplication::SecurityParams::new(self.q.clone())
+    }
+
+    /// Parameters of [Пdec](crate::paillier_decryption_modulo_q)
+    #[cfg(feature = "paillier-decryption-modulo-q")]
+    pub fn dec(&self) -> crate::paillier_decryption_modulo_q::SecurityParams {
+        crate::paillier_decryption_modulo_q::SecurityParams::new(
+            self.l,
+            self.epsilon,
+            self.q.clone(),
+        )
+    }
+
+    /// Parameters of [Пfac](crate::no_small_factor)
+    #[cfg(feature = "no-small-factor")]
+    pub fn no_small_factor(&self) -> crate::no_small_factor::SecurityParams {
+        crate::no_small_factor::SecurityParams::new(self.l, self.epsilon, self.q.clone())
+    }
+
+    /// Parameters of all [presigning proofs](crate::presigning_proofs)
+    #[cfg(feature = "presigning-proofs")]
+    pub fn presigning(&self) -> crate::presigning_proofs::SecurityParams {
+        crate::presigning_proofs::SecurityParams::new(
+            self.l,
+            self.l_prime,
+            self.epsilon,
+            self.q.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SecurityLevel;
+
+    #[test]
+    fn presets() {
+        for (level, kappa, modulus_bits) in [
+            (SecurityLevel::recommended_128(), 128, 3072),
+            (SecurityLevel::recommended_112(), 112, 2048),
+        ] {
+            assert_eq!(level.kappa, kappa);
+            assert_eq!(level.m, kappa);
+            assert_eq!(level.q.significant_bits() as usize, kappa + 1);
+            assert_eq!(level.min_modulus_bits, modulus_bits);
+            // Largest masked value of Пaff-g fits into the paillier plaintext space
+            assert!(level.l_prime + level.epsilon < modulus_bits as usize - 1);
+        }
+    }
+}
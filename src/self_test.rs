@@ -0,0 +1,1320 @@
+//! Randomized self-test of the proofs
+//!
+//! [`self_test`] generates small instances of every proof enabled by crate features,
+//! proves and verifies them, and checks that each verifier rejects the proof when
+//! given a tampered statement. It's meant to be run as a health check at startup: it
+//! catches miscompiled or misconfigured builds (e.g. a broken bignum backend) before
+//! they produce or accept bad proofs in a real session.
+//!
+//! Instances use 1024-bit Paillier keys and ring-pedersen modulus, so the test takes
+//! a fraction of a second. Keys and security parameters are **insecure** and generated
+//! within the call, they must never be used outside of it.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let report = paillier_zk::self_test::<generic_ec::curves::Secp256k1, sha2::Sha256>(
+//!     &mut rand_core::OsRng,
+//! )?;
+//! for failure in report.failures() {
+//!     eprintln!("self-test of {} failed: {:?}", failure.proof, failure.result);
+//! }
+//! assert!(report.passed());
+//! # Ok::<_, paillier_zk::Error>(())
+//! ```
+
+use digest::Digest;
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_imports))]
+use fast_paillier::{AnyEncryptionKey, DecryptionKey};
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_imports))]
+use generic_ec::{Curve, Point, Scalar};
+use rand_core::{CryptoRng, RngCore};
+use rug::{Complete, Integer};
+
+use crate::aux_generation::AuxSecrets;
+use crate::common::{Aux, BadExponent, IntegerExt, InvalidProof};
+use crate::Error;
+
+/// Bit size of Paillier keys and ring-pedersen modulus of self-test instances
+const MODULUS_BITS: u32 = 1024;
+
+/// Report of [`self_test`]
+#[derive(Debug)]
+pub struct SelfTestReport {
+    /// Outcome for every proof enabled by crate features, in alphabetical order
+    pub proofs: Vec<ProofReport>,
+}
+
+impl SelfTestReport {
+    /// Whether every proof passed the self-test
+    pub fn passed(&self) -> bool {
+        self.proofs.iter().all(|p| p.result.is_ok())
+    }
+
+    /// Proofs that failed the self-test
+    pub fn failures(&self) -> impl Iterator<Item = &ProofReport> {
+        self.proofs.iter().filter(|p| p.result.is_err())
+    }
+}
+
+/// Outcome of self-test of a single proof
+#[derive(Debug)]
+pub struct ProofReport {
+    /// Name of the proof module, e.g. `"paillier_encryption_in_range"`
+    pub proof: &'static str,
+    /// Whether the proof passed
+    pub result: Result<(), SelfTestFailure>,
+}
+
+/// Reason why a proof failed the self-test
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SelfTestFailure {
+    /// Couldn't construct the instance or the proof
+    #[error("couldn't prove the statement")]
+    Prove(
+        #[source]
+        #[from]
+        Error,
+    ),
+    /// Proof of a valid statement was rejected
+    #[error("valid proof was rejected")]
+    Rejected(#[source] InvalidProof),
+    /// Proof was accepted for a statement it wasn't computed for
+    #[error("proof was accepted for a tampered statement")]
+    AcceptedTampered,
+}
+
+impl From<fast_paillier::Error> for SelfTestFailure {
+    fn from(err: fast_paillier::Error) -> Self {
+        Self::Prove(err.into())
+    }
+}
+
+/// Proves and verifies a small instance of every proof enabled by crate features
+///
+/// Returns an error only if keys for the instances couldn't be generated. Failures of
+/// specific proofs are listed in the report, see [`SelfTestReport::passed`].
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_variables, unused_mut))]
+pub fn self_test<C: Curve, D: Digest + Clone>(
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<SelfTestReport, Error> {
+    let setup = Setup::generate(rng)?;
+    let mut proofs = vec![];
+    let mut run = |proof, result| proofs.push(ProofReport { proof, result });
+
+    #[cfg(feature = "dlog-vs-elgamal-commitment")]
+    run("dlog_vs_elgamal_commitment", dlog_vs_elgamal::<C, D>(rng));
+    #[cfg(feature = "exact-range")]
+    run("exact_range", exact_range::<D>(&setup, rng));
+    #[cfg(feature = "factorization-knowledge")]
+    run(
+        "factorization_knowledge",
+        factorization_knowledge::<D>(&setup, rng),
+    );
+    #[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+    run(
+        "group_element_vs_paillier_encryption_in_range",
+        log_star::<C, D>(&setup, rng),
+    );
+    #[cfg(feature = "group-element-vs-paillier-multiplication")]
+    run(
+        "group_element_vs_paillier_multiplication",
+        mul_star::<C, D>(&setup, rng),
+    );
+    #[cfg(feature = "joint-enc-log-star")]
+    run(
+        "joint_enc_log_star",
+        joint_enc_log_star::<C, D>(&setup, rng),
+    );
+    #[cfg(feature = "no-small-factor")]
+    run("no_small_factor", no_small_factor::<D>(&setup, rng));
+    #[cfg(feature = "paillier-affine-operation-in-range")]
+    run(
+        "paillier_affine_operation_in_range",
+        aff_g::<C, D>(&setup, rng),
+    );
+    #[cfg(feature = "paillier-affine-operation-with-paillier-commitment")]
+    run(
+        "paillier_affine_operation_with_paillier_commitment",
+        aff_p::<D>(&setup, rng),
+    );
+    #[cfg(feature = "paillier-blum-modulus")]
+    run("paillier_blum_modulus", blum_modulus::<D>(&setup, rng));
+    #[cfg(feature = "paillier-broadcast-encryption")]
+    run("paillier_broadcast_encryption", broadcast::<D>(&setup, rng));
+    #[cfg(feature = "paillier-decryption-modulo-q")]
+    run("paillier_decryption_modulo_q", dec::<C, D>(&setup, rng));
+    #[cfg(feature = "paillier-encryption-in-range")]
+    run("paillier_encryption_in_range", enc::<D>(&setup, rng));
+    #[cfg(feature = "paillier-encryption-in-range-with-elgamal")]
+    run(
+        "paillier_encryption_in_range_with_elgamal",
+        enc_elg::<C, D>(&setup, rng),
+    );
+    #[cfg(feature = "paillier-encryption-of-bit")]
+    run("paillier_encryption_of_bit", enc_bit::<D>(&setup, rng));
+    #[cfg(feature = "paillier-encryption-of-zero")]
+    run("paillier_encryption_of_zero", enc_zero::<D>(&setup, rng));
+    #[cfg(feature = "paillier-multiplication")]
+    run("paillier_multiplication", mul::<D>(&setup, rng));
+    #[cfg(feature = "paillier-plaintext-equality")]
+    run(
+        "paillier_plaintext_equality",
+        plaintext_eq::<D>(&setup, rng),
+    );
+    #[cfg(feature = "paillier-plaintext-knowledge")]
+    run(
+        "paillier_plaintext_knowledge",
+        plaintext_knowledge::<D>(&setup, rng),
+    );
+    #[cfg(feature = "paillier-scalar-multiplication-in-range")]
+    run(
+        "paillier_scalar_multiplication_in_range",
+        scalar_mul::<D>(&setup, rng),
+    );
+    #[cfg(feature = "pedersen-commitment-vs-paillier-encryption-in-range")]
+    run(
+        "pedersen_commitment_vs_paillier_encryption_in_range",
+        ped::<C, D>(&setup, rng),
+    );
+    #[cfg(feature = "presigning-proofs")]
+    run("presigning_proofs", presigning::<C, D>(&setup, rng));
+    #[cfg(feature = "ring-pedersen-parameters")]
+    run(
+        "ring_pedersen_parameters",
+        ring_pedersen_parameters::<D>(&setup, rng),
+    );
+    #[cfg(feature = "schnorr-pok")]
+    run("schnorr_pok", schnorr_pok::<C, D>(rng));
+    #[cfg(feature = "setup-proofs")]
+    run("setup_proofs", setup_proofs::<D>(&setup, rng));
+    #[cfg(feature = "witness-escrow")]
+    run("witness_escrow", witness_escrow::<C, D>(&setup, rng));
+
+    Ok(SelfTestReport { proofs })
+}
+
+/// Keys and ring-pedersen parameters shared by the instances
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+struct Setup {
+    aux: Aux,
+    aux_secrets: AuxSecrets,
+    key0: DecryptionKey,
+    key1: DecryptionKey,
+}
+
+impl Setup {
+    fn generate(rng: &mut (impl RngCore + CryptoRng)) -> Result<Self, Error> {
+        let p = blum_prime(rng, MODULUS_BITS / 2);
+        let q = blum_prime(rng, MODULUS_BITS / 2);
+        let n = (&p * &q).complete();
+        let phi_n = (&p - 1u8).complete() * (&q - 1u8).complete();
+        let t = Integer::gen_invertible(&n, rng).square().modulo(&n);
+        let lambda: Integer = phi_n
+            .random_below_ref(&mut fast_paillier::utils::external_rand(rng))
+            .into();
+        let s: Integer = t
+            .pow_mod_ref(&lambda, &n)
+            .ok_or(BadExponent::undefined())?
+            .into();
+        let aux = Aux::new(s, t, n);
+
+        let mut key = || {
+            DecryptionKey::from_primes(
+                blum_prime(rng, MODULUS_BITS / 2),
+                blum_prime(rng, MODULUS_BITS / 2),
+            )
+        };
+        Ok(Self {
+            aux,
+            aux_secrets: AuxSecrets { p, q, lambda },
+            key0: key()?,
+            key1: key()?,
+        })
+    }
+}
+
+fn blum_prime(rng: &mut impl RngCore, bits: u32) -> Integer {
+    loop {
+        let mut n: Integer =
+            Integer::random_bits(bits, &mut fast_paillier::utils::external_rand(rng)).into();
+        // Two top bits are set, so that product of two primes has exactly `2 * bits` bits
+        n.set_bit(bits - 1, true);
+        n.set_bit(bits - 2, true);
+        n.next_prime_mut();
+        if n.mod_u(4) == 3 {
+            break n;
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn q() -> Integer {
+    (Integer::ONE << 128_u32).complete()
+}
+
+/// Samples `x` in `[-2^l; 2^l]`
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn plaintext(rng: &mut impl RngCore, l: usize) -> Integer {
+    Integer::from_rng_pm(&(Integer::ONE << l).complete(), rng)
+}
+
+/// Honest proof must be accepted, and proof for a tampered statement rejected
+#[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+fn check(
+    honest: Result<(), InvalidProof>,
+    tampered: Result<(), InvalidProof>,
+) -> Result<(), SelfTestFailure> {
+    honest.map_err(SelfTestFailure::Rejected)?;
+    match tampered {
+        Ok(()) => Err(SelfTestFailure::AcceptedTampered),
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(feature = "dlog-vs-elgamal-commitment")]
+fn dlog_vs_elgamal<C: Curve, D: Digest + Clone>(
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::dlog_vs_elgamal_commitment as p;
+
+    let y = Scalar::<C>::random(rng);
+    let lambda = Scalar::<C>::random(rng);
+    let point_x = Point::generator() * Scalar::random(rng);
+    let h = Point::generator() * Scalar::random(rng);
+    let l = Point::generator() * lambda;
+    let m = Point::generator() * y + point_x * lambda;
+    let point_y = h * y;
+    let data = p::Data {
+        l: &l,
+        m: &m,
+        x: &point_x,
+        y: &point_y,
+        h: &h,
+    };
+    let pdata = p::PrivateData {
+        y: &y,
+        lambda: &lambda,
+    };
+    let (commitment, proof) = p::non_interactive::prove(D::new(), data, pdata, rng);
+
+    let other_y = point_y + Point::generator();
+    let tampered = p::Data {
+        y: &other_y,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), data, &commitment, &proof),
+        p::non_interactive::verify(D::new(), tampered, &commitment, &proof),
+    )
+}
+
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+fn log_star<C: Curve, D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::group_element_vs_paillier_encryption_in_range as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let key0 = setup.key0.encryption_key();
+    let x = plaintext(rng, security.l);
+    let (c, nonce) = key0.encrypt_with_random(rng, &x)?;
+    let b = Point::<C>::generator() * Scalar::random(rng);
+    let point_x = b * x.to_scalar();
+    let data = p::Data {
+        key0,
+        c: &c,
+        b: &b,
+        x: &point_x,
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_x = point_x + b;
+    let tampered = p::Data {
+        x: &other_x,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "group-element-vs-paillier-multiplication")]
+fn mul_star<C: Curve, D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::group_element_vs_paillier_multiplication as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let key0 = setup.key0.encryption_key();
+    let x = plaintext(rng, security.l);
+    let multiplicand = Integer::from_rng_pm(key0.half_n(), rng);
+    let (c, _) = key0.encrypt_with_random(rng, &multiplicand)?;
+    let (rerandomization, nonce) = key0.encrypt_with_random(rng, &Integer::ZERO)?;
+    let d = key0.oadd(&key0.omul(&x, &c)?, &rerandomization)?;
+    let point_x = Point::<C>::generator() * x.to_scalar();
+    let data = p::Data {
+        key0,
+        c: &c,
+        d: &d,
+        x: &point_x,
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_x = point_x + Point::generator();
+    let tampered = p::Data {
+        x: &other_x,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "joint-enc-log-star")]
+fn joint_enc_log_star<C: Curve, D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::{
+        group_element_vs_paillier_encryption_in_range as log_star, joint_enc_log_star as p,
+        paillier_encryption_in_range as enc,
+    };
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let (key_enc, key0) = (setup.key0.encryption_key(), setup.key1.encryption_key());
+    let x = plaintext(rng, security.l);
+    let (k, nonce_enc) = key_enc.encrypt_with_random(rng, &x)?;
+    let (c, nonce_log_star) = key0.encrypt_with_random(rng, &x)?;
+    let b = Point::<C>::generator() * Scalar::random(rng);
+    let point_x = b * x.to_scalar();
+    let data = p::Data {
+        enc: enc::Data {
+            key: key_enc,
+            ciphertext: &k,
+        },
+        log_star: log_star::Data {
+            key0,
+            c: &c,
+            b: &b,
+            x: &point_x,
+        },
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        nonce_enc: &nonce_enc,
+        nonce_log_star: &nonce_log_star,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_x = point_x + b;
+    let tampered = p::Data {
+        log_star: log_star::Data {
+            x: &other_x,
+            ..data.log_star
+        },
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "exact-range")]
+fn exact_range<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::exact_range as p;
+
+    let security = p::SecurityParams::new(16, q());
+    let key = setup.key0.encryption_key();
+    let x = plaintext(rng, security.l - 1).abs();
+    let (c, nonce) = key.encrypt_with_random(rng, &x)?;
+    let data = p::Data { key, c: &c };
+    let pdata = p::PrivateData {
+        plaintext: &x,
+        nonce: &nonce,
+    };
+    let proof = p::non_interactive::prove(D::new(), data, pdata, &security, rng)?;
+
+    let other_c = key.encrypt_with(&(&x + 1u8).complete(), &nonce)?;
+    let tampered = p::Data {
+        c: &other_c,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), data, &security, &proof),
+        p::non_interactive::verify(D::new(), tampered, &security, &proof),
+    )
+}
+
+#[cfg(feature = "factorization-knowledge")]
+fn factorization_knowledge<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::factorization_knowledge as p;
+
+    let security = p::SecurityParams::new(128, 80, 1);
+    let data = p::Data { n: setup.key0.n() };
+    let pdata = p::PrivateData {
+        p: setup.key0.p(),
+        q: setup.key0.q(),
+    };
+    let (commitment, proof) = p::non_interactive::prove(D::new(), data, pdata, &security, rng)?;
+
+    let tampered = p::Data { n: setup.key1.n() };
+    check(
+        p::non_interactive::verify(D::new(), data, &commitment, &security, &proof),
+        p::non_interactive::verify(D::new(), tampered, &commitment, &security, &proof),
+    )
+}
+
+#[cfg(feature = "no-small-factor")]
+fn no_small_factor<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::no_small_factor as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let n = setup.key0.n();
+    let n_root = n.sqrt_ref().complete();
+    let data = p::Data { n, n_root: &n_root };
+    let pdata = p::PrivateData {
+        p: setup.key0.p(),
+        q: setup.key0.q(),
+    };
+    let proof = p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_n = setup.key1.n();
+    let tampered = p::Data { n: other_n, ..data };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &security, &proof),
+        p::non_interactive::verify(D::new(), &setup.aux, tampered, &security, &proof),
+    )
+}
+
+#[cfg(feature = "paillier-affine-operation-in-range")]
+fn aff_g<C: Curve, D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_affine_operation_in_range as p;
+
+    let security = p::SecurityParams::new(256, 256, 512, q());
+    let key0 = setup.key0.encryption_key();
+    let key1 = setup.key1.encryption_key();
+    let x = plaintext(rng, security.l_x);
+    let y = plaintext(rng, security.l_y);
+    let multiplicand = Integer::from_rng_pm(key0.half_n(), rng);
+    let (c, _) = key0.encrypt_with_random(rng, &multiplicand)?;
+    let (y_enc_key1, nonce_y) = key1.encrypt_with_random(rng, &y)?;
+    let (y_enc_key0, nonce) = key0.encrypt_with_random(rng, &y)?;
+    let d = key0.oadd(&key0.omul(&x, &c)?, &y_enc_key0)?;
+    let point_x = Point::<C>::generator() * x.to_scalar();
+    let data = p::Data {
+        key0,
+        key1,
+        c: &c,
+        d: &d,
+        y: &y_enc_key1,
+        x: &point_x,
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        y: &y,
+        nonce: &nonce,
+        nonce_y: &nonce_y,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_d = key0.oadd(&d, &c)?;
+    let tampered = p::Data {
+        d: &other_d,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "paillier-affine-operation-with-paillier-commitment")]
+fn aff_p<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_affine_operation_with_paillier_commitment as p;
+
+    let security = p::SecurityParams::new(256, 256, 512, q());
+    let key0 = setup.key0.encryption_key();
+    let key1 = setup.key1.encryption_key();
+    let x = plaintext(rng, security.l_x);
+    let y = plaintext(rng, security.l_y);
+    let multiplicand = Integer::from_rng_pm(key0.half_n(), rng);
+    let (c, _) = key0.encrypt_with_random(rng, &multiplicand)?;
+    let (x_enc_key1, nonce_x) = key1.encrypt_with_random(rng, &x)?;
+    let (y_enc_key1, nonce_y) = key1.encrypt_with_random(rng, &y)?;
+    let (y_enc_key0, nonce) = key0.encrypt_with_random(rng, &y)?;
+    let d = key0.oadd(&key0.omul(&x, &c)?, &y_enc_key0)?;
+    let data = p::Data {
+        key0,
+        key1,
+        c: &c,
+        d: &d,
+        y: &y_enc_key1,
+        x: &x_enc_key1,
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        y: &y,
+        nonce: &nonce,
+        nonce_x: &nonce_x,
+        nonce_y: &nonce_y,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_d = key0.oadd(&d, &c)?;
+    let tampered = p::Data {
+        d: &other_d,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "paillier-blum-modulus")]
+fn blum_modulus<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_blum_modulus as p;
+
+    let data = p::Data {
+        n: setup.key0.n().clone(),
+    };
+    let pdata = p::PrivateData {
+        p: setup.key0.p().clone(),
+        q: setup.key0.q().clone(),
+    };
+    let (commitment, proof) = p::non_interactive::prove::<65, _, _>(D::new(), &data, &pdata, rng)?;
+
+    let tampered = p::Data {
+        n: setup.key1.n().clone(),
+    };
+    check(
+        p::non_interactive::verify(D::new(), &data, &commitment, &proof),
+        p::non_interactive::verify(D::new(), &tampered, &commitment, &proof),
+    )
+}
+
+#[cfg(feature = "paillier-broadcast-encryption")]
+fn broadcast<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_broadcast_encryption as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let key0 = setup.key0.encryption_key();
+    let key1 = setup.key1.encryption_key();
+    let keys: [&dyn AnyEncryptionKey; 2] = [key0, key1];
+    let x = plaintext(rng, security.l);
+    let (c0, nonce0) = key0.encrypt_with_random(rng, &x)?;
+    let (c1, nonce1) = key1.encrypt_with_random(rng, &x)?;
+    let (ciphertexts, nonces) = ([c0.clone(), c1], [nonce0, nonce1]);
+    let data = p::Data {
+        keys: &keys,
+        ciphertexts: &ciphertexts,
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        nonces: &nonces,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let (other_c1, _) = key1.encrypt_with_random(rng, &x)?;
+    let other_ciphertexts = [c0, other_c1];
+    let tampered = p::Data {
+        ciphertexts: &other_ciphertexts,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "paillier-decryption-modulo-q")]
+fn dec<C: Curve, D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_decryption_modulo_q as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let key0 = setup.key0.encryption_key();
+    let y = plaintext(rng, security.l);
+    let (c, nonce) = key0.encrypt_with_random(rng, &y)?;
+    let x = y.to_scalar::<C>();
+    let data = p::Data { key0, c: &c, x: &x };
+    let pdata = p::PrivateData {
+        y: &y,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_x = x + Scalar::one();
+    let tampered = p::Data {
+        x: &other_x,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "paillier-encryption-in-range")]
+fn enc<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_encryption_in_range as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let key = setup.key0.encryption_key();
+    let x = plaintext(rng, security.l);
+    let (ciphertext, nonce) = key.encrypt_with_random(rng, &x)?;
+    let data = p::Data {
+        key,
+        ciphertext: &ciphertext,
+    };
+    let pdata = p::PrivateData {
+        plaintext: &x,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_ciphertext = key.oadd(&ciphertext, &ciphertext)?;
+    let tampered = p::Data {
+        ciphertext: &other_ciphertext,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "paillier-encryption-in-range-with-elgamal")]
+fn enc_elg<C: Curve, D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_encryption_in_range_with_elgamal as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let key0 = setup.key0.encryption_key();
+    let x = plaintext(rng, security.l);
+    let (c, nonce) = key0.encrypt_with_random(rng, &x)?;
+    let a = Scalar::<C>::random(rng);
+    let b = Scalar::<C>::random(rng);
+    let point_a = Point::generator() * a;
+    let point_b = Point::generator() * b;
+    let point_x = Point::generator() * (a * b + x.to_scalar());
+    let data = p::Data {
+        key0,
+        c: &c,
+        a: &point_a,
+        b: &point_b,
+        x: &point_x,
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        nonce: &nonce,
+        b: &b,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_x = point_x + Point::generator();
+    let tampered = p::Data {
+        x: &other_x,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "paillier-encryption-of-bit")]
+fn enc_bit<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_encryption_of_bit as p;
+
+    let security = p::SecurityParams::new(q());
+    let key = setup.key0.encryption_key();
+    let (c, nonce) = key.encrypt_with_random(rng, Integer::ONE)?;
+    let data = p::Data { key, c: &c };
+    let pdata = p::PrivateData {
+        bit: true,
+        nonce: &nonce,
+    };
+    let (commitment, proof) = p::non_interactive::prove(D::new(), data, pdata, &security, rng)?;
+
+    let other_c = key.encrypt_with(&Integer::from(2), &nonce)?;
+    let tampered = p::Data {
+        c: &other_c,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), data, &commitment, &security, &proof),
+        p::non_interactive::verify(D::new(), tampered, &commitment, &security, &proof),
+    )
+}
+
+#[cfg(feature = "paillier-encryption-of-zero")]
+fn enc_zero<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_encryption_of_zero as p;
+
+    let security = p::SecurityParams::new(q());
+    let key = setup.key0.encryption_key();
+    let (c, nonce) = key.encrypt_with_random(rng, &Integer::ZERO)?;
+    let data = p::Data { key, c: &c };
+    let pdata = p::PrivateData { nonce: &nonce };
+    let (commitment, proof) = p::non_interactive::prove(D::new(), data, pdata, &security, rng)?;
+
+    let other_c = key.encrypt_with(Integer::ONE, &nonce)?;
+    let tampered = p::Data {
+        c: &other_c,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), data, &commitment, &security, &proof),
+        p::non_interactive::verify(D::new(), tampered, &commitment, &security, &proof),
+    )
+}
+
+#[cfg(feature = "paillier-multiplication")]
+fn mul<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_multiplication as p;
+
+    let security = p::SecurityParams::new(q());
+    let key = setup.key0.encryption_key();
+    let x = plaintext(rng, 256);
+    let y = plaintext(rng, 256);
+    let (cx, nonce_x) = key.encrypt_with_random(rng, &x)?;
+    let (cy, _) = key.encrypt_with_random(rng, &y)?;
+    let (rerandomization, nonce) = key.encrypt_with_random(rng, &Integer::ZERO)?;
+    let c = key.oadd(&key.omul(&x, &cy)?, &rerandomization)?;
+    let data = p::Data {
+        key,
+        x: &cx,
+        y: &cy,
+        c: &c,
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        nonce: &nonce,
+        nonce_x: &nonce_x,
+    };
+    let (commitment, proof) = p::non_interactive::prove(D::new(), data, pdata, &security, rng)?;
+
+    let other_c = key.oadd(&c, &cy)?;
+    let tampered = p::Data {
+        c: &other_c,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), data, &commitment, &security, &proof),
+        p::non_interactive::verify(D::new(), tampered, &commitment, &security, &proof),
+    )
+}
+
+#[cfg(feature = "paillier-plaintext-equality")]
+fn plaintext_eq<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_plaintext_equality as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let key0 = setup.key0.encryption_key();
+    let key1 = setup.key1.encryption_key();
+    let x = plaintext(rng, security.l);
+    let (c0, nonce0) = key0.encrypt_with_random(rng, &x)?;
+    let (c1, nonce1) = key1.encrypt_with_random(rng, &x)?;
+    let data = p::Data {
+        key0,
+        c0: &c0,
+        key1,
+        c1: &c1,
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        nonce0: &nonce0,
+        nonce1: &nonce1,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let (other_c1, _) = key1.encrypt_with_random(rng, &x)?;
+    let tampered = p::Data {
+        c1: &other_c1,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "paillier-plaintext-knowledge")]
+fn plaintext_knowledge<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_plaintext_knowledge as p;
+
+    let security = p::SecurityParams::new(q());
+    let key = setup.key0.encryption_key();
+    let x = plaintext(rng, 256);
+    let (c, nonce) = key.encrypt_with_random(rng, &x)?;
+    let data = p::Data { key, c: &c };
+    let pdata = p::PrivateData {
+        plaintext: &x,
+        nonce: &nonce,
+    };
+    let (commitment, proof) = p::non_interactive::prove(D::new(), data, pdata, &security, rng)?;
+
+    let other_c = key.encrypt_with(&(&x + 1u8).complete(), &nonce)?;
+    let tampered = p::Data {
+        c: &other_c,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), data, &commitment, &security, &proof),
+        p::non_interactive::verify(D::new(), tampered, &commitment, &security, &proof),
+    )
+}
+
+#[cfg(feature = "paillier-scalar-multiplication-in-range")]
+fn scalar_mul<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::paillier_scalar_multiplication_in_range as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let key = setup.key0.encryption_key();
+    let k = plaintext(rng, security.l);
+    let y = plaintext(rng, 256);
+    let (c, _) = key.encrypt_with_random(rng, &y)?;
+    let c_prime = key.omul(&k, &c)?;
+    let data = p::Data {
+        key,
+        c: &c,
+        c_prime: &c_prime,
+    };
+    let pdata = p::PrivateData { k: &k };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_c_prime = key.oadd(&c_prime, &c)?;
+    let tampered = p::Data {
+        c_prime: &other_c_prime,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "pedersen-commitment-vs-paillier-encryption-in-range")]
+fn ped<C: Curve, D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::pedersen_commitment_vs_paillier_encryption_in_range as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let key0 = setup.key0.encryption_key();
+    let x = plaintext(rng, security.l);
+    let (c, nonce) = key0.encrypt_with_random(rng, &x)?;
+    let g = Point::<C>::generator().to_point();
+    let h = Point::<C>::generator() * Scalar::random(rng);
+    let r = Scalar::random(rng);
+    let point_x = g * x.to_scalar() + h * r;
+    let data = p::Data {
+        key0,
+        c: &c,
+        g: &g,
+        h: &h,
+        x: &point_x,
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        r: &r,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let other_x = point_x + h;
+    let tampered = p::Data {
+        x: &other_x,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(feature = "ring-pedersen-parameters")]
+fn ring_pedersen_parameters<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::ring_pedersen_parameters as p;
+
+    let data = p::Data { aux: &setup.aux };
+    let pdata = p::PrivateData::from(&setup.aux_secrets);
+    let (commitment, proof) = p::non_interactive::prove::<80, _, _>(D::new(), data, pdata, rng)?;
+
+    let other_s = (&setup.aux.s * &setup.aux.t)
+        .complete()
+        .modulo(&setup.aux.rsa_modulo);
+    let other_aux = Aux::new(other_s, setup.aux.t.clone(), setup.aux.rsa_modulo.clone());
+    let tampered = p::Data { aux: &other_aux };
+    check(
+        p::non_interactive::verify(D::new(), data, &commitment, &proof),
+        p::non_interactive::verify(D::new(), tampered, &commitment, &proof),
+    )
+}
+
+#[cfg(feature = "schnorr-pok")]
+fn schnorr_pok<C: Curve, D: Digest + Clone>(
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::schnorr_pok as p;
+
+    let x = Scalar::<C>::random(rng);
+    let point_x = Point::generator() * x;
+    let data = p::Data { x: &point_x };
+    let pdata = p::PrivateData { x: &x };
+    let (commitment, proof) = p::non_interactive::prove(D::new(), data, pdata, rng);
+
+    let other_x = point_x + Point::generator();
+    let tampered = p::Data { x: &other_x };
+    check(
+        p::non_interactive::verify(D::new(), data, &commitment, &proof),
+        p::non_interactive::verify(D::new(), tampered, &commitment, &proof),
+    )
+}
+
+#[cfg(feature = "presigning-proofs")]
+fn presigning<C: Curve, D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::presigning_proofs as p;
+
+    let security = p::SecurityParams::new(256, 256, 512, q());
+    // Prover encrypts on `key1`, verifier on `key0`
+    let key = setup.key1.encryption_key();
+    let verifier_key = setup.key0.encryption_key();
+    let k = plaintext(rng, security.log_star.l);
+    let (big_k, rho) = key.encrypt_with_random(rng, &k)?;
+    let verifier_multiplicand = plaintext(rng, security.enc.l);
+    let (verifier_k, _) = verifier_key.encrypt_with_random(rng, &verifier_multiplicand)?;
+    let gamma = plaintext(rng, security.log_star.l);
+    let (g, nu) = key.encrypt_with_random(rng, &gamma)?;
+    let point_gamma = Point::<C>::generator() * gamma.to_scalar();
+    let x = plaintext(rng, security.aff_g.l_x);
+    let point_x = Point::<C>::generator() * x.to_scalar();
+    let mut affine = |x: &Integer| -> Result<_, SelfTestFailure> {
+        let beta = plaintext(rng, security.aff_g.l_y);
+        let (f, r) = key.encrypt_with_random(rng, &beta)?;
+        let (beta_enc, s) = verifier_key.encrypt_with_random(rng, &beta)?;
+        let d = verifier_key.oadd(&verifier_key.omul(x, &verifier_k)?, &beta_enc)?;
+        Ok((beta, s, r, d, f))
+    };
+    let (beta, s, r, d, f) = affine(&gamma)?;
+    let (beta_hat, s_hat, r_hat, d_hat, f_hat) = affine(&x)?;
+    let point_delta = point_gamma * k.to_scalar();
+
+    let data1 = p::round1::Data { key, k: &big_k };
+    let pdata1 = p::round1::PrivateData { k: &k, rho: &rho };
+    let data2 = p::round2::Data {
+        key,
+        verifier_key,
+        verifier_k: &verifier_k,
+        g: &g,
+        gamma: &point_gamma,
+        x: &point_x,
+        d: &d,
+        f: &f,
+        d_hat: &d_hat,
+        f_hat: &f_hat,
+    };
+    let pdata2 = p::round2::PrivateData {
+        gamma: &gamma,
+        nu: &nu,
+        x: &x,
+        beta: &beta,
+        s: &s,
+        r: &r,
+        beta_hat: &beta_hat,
+        s_hat: &s_hat,
+        r_hat: &r_hat,
+    };
+    let data3 = p::round3::Data {
+        key,
+        k: &big_k,
+        gamma: &point_gamma,
+        delta: &point_delta,
+    };
+    let pdata3 = p::round3::PrivateData { k: &k, rho: &rho };
+    let bundle1 = p::round1::prove(D::new(), &setup.aux, data1, pdata1, &security, rng)?;
+    let bundle2 = p::round2::prove(D::new(), &setup.aux, data2, pdata2, &security, rng)?;
+    let bundle3 = p::round3::prove(D::new(), &setup.aux, data3, pdata3, &security, rng)?;
+
+    let tampered = p::round2::Data {
+        d: &d_hat,
+        d_hat: &d,
+        ..data2
+    };
+    check(
+        p::round1::verify(D::new(), &setup.aux, data1, &security, &bundle1)
+            .and(p::round2::verify(
+                D::new(),
+                &setup.aux,
+                data2,
+                &security,
+                &bundle2,
+            ))
+            .and(p::round3::verify(
+                D::new(),
+                &setup.aux,
+                data3,
+                &security,
+                &bundle3,
+            )),
+        p::round2::verify(D::new(), &setup.aux, tampered, &security, &bundle2),
+    )
+}
+
+#[cfg(feature = "setup-proofs")]
+fn setup_proofs<D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::setup_proofs as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let data = p::Data {
+        n: setup.key0.n(),
+        aux: &setup.aux,
+    };
+    let pdata = p::PrivateData {
+        p: setup.key0.p(),
+        q: setup.key0.q(),
+        aux_secrets: &setup.aux_secrets,
+    };
+    let bundle = p::prove_all::<80, _, _>(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let tampered = p::Data {
+        n: setup.key1.n(),
+        ..data
+    };
+    check(
+        p::verify_all(D::new(), &setup.aux, data, &security, &bundle),
+        p::verify_all(D::new(), &setup.aux, tampered, &security, &bundle),
+    )
+}
+
+#[cfg(feature = "witness-escrow")]
+fn witness_escrow<C: Curve, D: Digest + Clone>(
+    setup: &Setup,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(), SelfTestFailure> {
+    use crate::witness_escrow as p;
+
+    let security = p::SecurityParams::new(256, 512, q());
+    let key0 = setup.key0.encryption_key();
+    let key1 = setup.key1.encryption_key();
+    let x = plaintext(rng, security.l);
+    let (c0, nonce0) = key0.encrypt_with_random(rng, &x)?;
+    let (c1, nonce1) = key1.encrypt_with_random(rng, &x)?;
+    let b = Point::<C>::generator().to_point();
+    let point_x = b * x.to_scalar();
+    let data = p::Data {
+        key0,
+        c0: &c0,
+        key1,
+        c1: &c1,
+        b: &b,
+        x: &point_x,
+    };
+    let pdata = p::PrivateData {
+        x: &x,
+        nonce0: &nonce0,
+        nonce1: &nonce1,
+    };
+    let (commitment, proof) =
+        p::non_interactive::prove(D::new(), &setup.aux, data, pdata, &security, rng)?;
+
+    let (other_c1, _) = key1.encrypt_with_random(rng, &x)?;
+    let tampered = p::Data {
+        c1: &other_c1,
+        ..data
+    };
+    check(
+        p::non_interactive::verify(D::new(), &setup.aux, data, &commitment, &security, &proof),
+        p::non_interactive::verify(
+            D::new(),
+            &setup.aux,
+            tampered,
+            &commitment,
+            &security,
+            &proof,
+        ),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn self_test_passes() {
+        let mut rng = rand_dev::DevRng::new();
+        let report =
+            super::self_test::<generic_ec::curves::Secp256k1, sha2::Sha256>(&mut rng).unwrap();
+        assert!(!report.proofs.is_empty());
+        assert!(
+            report.passed(),
+            "{:?}",
+            report.failures().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! Proofs of key setup: Пmod, Пprm and Пfac under a single transcript
+//!
+//! ## Description
+//! During key generation and key refresh a party P publishes its Paillier-Blum
+//! modulus `N` along with ring-pedersen parameters `aux`, and proves to each
+//! verifier V that:
+//! - `N` is a Paillier-Blum modulus, see [Пmod](crate::paillier_blum_modulus)
+//! - `s` is a power of `t` in `aux`, see [Пprm](crate::ring_pedersen_parameters)
+//! - `N` has no small factors, see [Пfac](crate::no_small_factor). Note that Пfac is
+//!   proven with ring-pedersen parameters of the verifier
+//!
+//! [`prove_all`] runs the three proofs one after another on a single Fiat-Shamir
+//! transcript, so each proof is bound to the ones before it, and returns them as one
+//! [`Bundle`]. [`verify_all`] accepts the bundle only if all three proofs are valid.
+//!
+//! `M` is the amount of repetitions of Пmod and Пprm, soundness error of each of
+//! them is `1/2^M`. CGGMP21 suggests `M = 80`.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{aux_generation::AuxSecrets, setup_proofs as p};
+//! # fn doc_fn(
+//! #     n: &paillier_zk::Integer,
+//! #     (primes_p, primes_q): (&paillier_zk::Integer, &paillier_zk::Integer),
+//! #     (aux, aux_secrets): (&p::Aux, &AuxSecrets),
+//! #     verifier_aux: &p::Aux,
+//! #     security: &p::SecurityParams,
+//! # ) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut rng = rand_core::OsRng;
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//!
+//! // Prover proves that its modulus `n` and ring-pedersen parameters `aux` are sound
+//! let data = p::Data { n, aux };
+//! let pdata = p::PrivateData {
+//!     p: primes_p,
+//!     q: primes_q,
+//!     aux_secrets,
+//! };
+//! let bundle = p::prove_all::<80, _, _>(
+//!     shared_state_prover,
+//!     verifier_aux,
+//!     data,
+//!     pdata,
+//!     security,
+//!     &mut rng,
+//! )?;
+//!
+//! // Verifier checks all the proofs at once
+//! p::verify_all(shared_state_verifier, verifier_aux, data, security, &bundle)?;
+//! # Ok(()) }
+//! ```
+
+use digest::Digest;
+use rand_core::RngCore;
+use rug::{Complete, Integer};
+
+use crate::aux_generation::AuxSecrets;
+use crate::common::encoding::{impl_encode, Decode, Encode};
+use crate::transcript::Transcript;
+use crate::{
+    no_small_factor as fac, paillier_blum_modulus as blum, ring_pedersen_parameters as prm,
+};
+use crate::{Error, InvalidProof};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::Aux;
+/// Security parameters of Пfac
+pub use crate::no_small_factor::SecurityParams;
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a> {
+    /// N, Paillier-Blum modulus of prover
+    pub n: &'a Integer,
+    /// Ring-pedersen parameters of prover
+    pub aux: &'a Aux,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// Factor of `N`
+    pub p: &'a Integer,
+    /// Factor of `N`
+    pub q: &'a Integer,
+    /// Secrets behind prover's ring-pedersen parameters
+    pub aux_secrets: &'a AuxSecrets,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>);
+
+/// Proofs of key setup, computed by [`prove_all`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Bundle<const M: usize> {
+    pub mod_commitment: blum::Commitment,
+    pub mod_proof: blum::Proof<M>,
+    pub prm_commitment: prm::Commitment<M>,
+    pub prm_proof: prm::Proof<M>,
+    pub fac_proof: fac::non_interactive::Proof,
+}
+
+impl_encode! { Data<'a> { n, aux } }
+
+impl<const M: usize> Encode for Bundle<M> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.mod_commitment.encode(out);
+        self.mod_proof.encode(out);
+        self.prm_commitment.encode(out);
+        self.prm_proof.encode(out);
+        self.fac_proof.encode(out);
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.mod_commitment.encoded_len()
+            + self.mod_proof.encoded_len()
+            + self.prm_commitment.encoded_len()
+            + self.prm_proof.encoded_len()
+            + self.fac_proof.encoded_len()
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        self.mod_commitment.encode_chunks(sink);
+        self.mod_proof.encode_chunks(sink);
+        self.prm_commitment.encode_chunks(sink);
+        self.prm_proof.encode_chunks(sink);
+        self.fac_proof.encode_chunks(sink);
+    }
+}
+
+impl<const M: usize> Decode for Bundle<M> {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        Some(Self {
+            mod_commitment: Decode::decode(bytes)?,
+            mod_proof: Decode::decode(bytes)?,
+            prm_commitment: Decode::decode(bytes)?,
+            prm_proof: Decode::decode(bytes)?,
+            fac_proof: Decode::decode(bytes)?,
+        })
+    }
+}
+
+impl<const M: usize> Bundle<M> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) bundle produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N` and `N^`. Bundle is the
+    /// concatenation of the proofs, see their schemas for the exact layout.
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        blum::Commitment::max_serialized_size(modulus_bits)
+            + blum::Proof::<M>::max_serialized_size(modulus_bits)
+            + prm::Commitment::<M>::max_serialized_size(modulus_bits)
+            + prm::Proof::<M>::max_serialized_size(modulus_bits)
+            + fac::non_interactive::Proof::max_serialized_size(security, modulus_bits)
+    }
+}
+
+/// Computes proofs of key setup for the verifier with ring-pedersen parameters
+/// `verifier_aux`
+pub fn prove_all<const M: usize, R: RngCore, D>(
+    shared_state: D,
+    verifier_aux: &Aux,
+    data: Data,
+    pdata: PrivateData,
+    security: &SecurityParams,
+    rng: &mut R,
+) -> Result<Bundle<M>, Error>
+where
+    D: Digest + Clone,
+{
+    let mut transcript = Transcript::from_shared_state(shared_state);
+    prove_all_with_transcript(&mut transcript, verifier_aux, data, pdata, security, rng)
+}
+
+/// Computes proofs of key setup within the protocol transcript
+///
+/// Statements and commitments of all proofs are absorbed into the `transcript`, so
+/// subsequent challenges derived from it are bound to the bundle.
+pub fn prove_all_with_transcript<const M: usize, R: RngCore, D>(
+    transcript: &mut Transcript<D>,
+    verifier_aux: &Aux,
+    data: Data,
+    pdata: PrivateData,
+    security: &SecurityParams,
+    rng: &mut R,
+) -> Result<Bundle<M>, Error>
+where
+    D: Digest + Clone,
+{
+    transcript.label(b"paillier_zk.setup_proofs");
+
+    let mod_data = blum::Data { n: data.n.clone() };
+    let mod_pdata = blum::PrivateData {
+        p: pdata.p.clone(),
+        q: pdata.q.clone(),
+    };
+    let (mod_commitment, mod_proof) =
+        blum::non_interactive::prove_with_transcript(transcript, &mod_data, &mod_pdata, rng)?;
+
+    let prm_data = prm::Data { aux: data.aux };
+    let (prm_commitment, prm_proof) = prm::non_interactive::prove_with_transcript(
+        transcript,
+        prm_data,
+        pdata.aux_secrets.into(),
+        rng,
+    )?;
+
+    let n_root = data.n.sqrt_ref().complete();
+    let fac_data = fac::Data {
+        n: data.n,
+        n_root: &n_root,
+    };
+    let fac_pdata = fac::PrivateData {
+        p: pdata.p,
+        q: pdata.q,
+    };
+    let fac_proof = fac::non_interactive::prove_with_transcript(
+        transcript,
+        verifier_aux,
+        fac_data,
+        fac_pdata,
+        security,
+        rng,
+    )?;
+
+    Ok(Bundle {
+        mod_commitment,
+        mod_proof,
+        prm_commitment,
+        prm_proof,
+        fac_proof,
+    })
+}
+
+/// Verifies proofs of key setup, `verifier_aux` are ring-pedersen parameters of the
+/// verifier
+pub fn verify_all<const M: usize, D>(
+    shared_state: D,
+    verifier_aux: &Aux,
+    data: Data,
+    security: &SecurityParams,
+    bundle: &Bundle<M>,
+) -> Result<(), InvalidProof>
+where
+    D: Digest + Clone,
+{
+    let mut transcript = Transcript::from_shared_state(shared_state);
+    verify_all_with_transcript(&mut transcript, verifier_aux, data, security, bundle)
+}
+
+/// Verifies proofs of key setup within the protocol transcript
+pub fn verify_all_with_transcript<const M: usize, D>(
+    transcript: &mut Transcript<D>,
+    verifier_aux: &Aux,
+    data: Data,
+    security: &SecurityParams,
+    bundle: &Bundle<M>,
+) -> Result<(), InvalidProof>
+where
+    D: Digest + Clone,
+{
+    transcript.label(b"paillier_zk.setup_proofs");
+
+    let mod_data = blum::Data { n: data.n.clone() };
+    blum::non_interactive::verify_with_transcript(
+        transcript,
+        &mod_data,
+        &bundle.mod_commitment,
+        &bundle.mod_proof,
+    )?;
+
+    let prm_data = prm::Data { aux: data.aux };
+    prm::non_interactive::verify_with_transcript(
+        transcript,
+        prm_data,
+        &bundle.prm_commitment,
+        &bundle.prm_proof,
+    )?;
+
+    let n_root = data.n.sqrt_ref().complete();
+    let fac_data = fac::Data {
+        n: data.n,
+        n_root: &n_root,
+    };
+    fac::non_interactive::verify_with_transcript(
+        transcript,
+        verifier_aux,
+        fac_data,
+        security,
+        &bundle.fac_proof,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::common::encoding::{Decode, Encode};
+    use crate::common::test::{aux, aux_with_secrets, generate_blum_prime};
+
+    const M: usize = 80;
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams::new(64, 128, (Integer::ONE << 128_u32).complete())
+    }
+
+    #[test]
+    fn passing() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let verifier_aux = aux(&mut rng);
+        let (own_aux, aux_secrets) = aux_with_secrets(&mut rng);
+        let p = generate_blum_prime(&mut rng, 512);
+        let q = generate_blum_prime(&mut rng, 512);
+        let n = (&p * &q).complete();
+        let data = super::Data {
+            n: &n,
+            aux: &own_aux,
+        };
+        let pdata = super::PrivateData {
+            p: &p,
+            q: &q,
+            aux_secrets: &aux_secrets,
+        };
+
+        let shared_state = sha2::Sha256::default();
+        let bundle = super::prove_all::<M, _, _>(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        super::verify_all(
+            shared_state.clone(),
+            &verifier_aux,
+            data,
+            &security,
+            &bundle,
+        )
+        .expect("proofs failed");
+
+        let bytes = bundle.to_canonical_bytes();
+        assert!(bytes.len() <= super::Bundle::<M>::max_serialized_size(&security, 2048));
+        let decoded = super::Bundle::<M>::from_canonical_bytes(&bytes).unwrap();
+        super::verify_all(shared_state, &verifier_aux, data, &security, &decoded)
+            .expect("decoded proofs failed");
+    }
+
+    #[test]
+    fn proofs_are_bound_together() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let verifier_aux = aux(&mut rng);
+        let (own_aux, aux_secrets) = aux_with_secrets(&mut rng);
+        let p = generate_blum_prime(&mut rng, 512);
+        let q = generate_blum_prime(&mut rng, 512);
+        let n = (&p * &q).complete();
+        let data = super::Data {
+            n: &n,
+            aux: &own_aux,
+        };
+        let pdata = super::PrivateData {
+            p: &p,
+            q: &q,
+            aux_secrets: &aux_secrets,
+        };
+
+        let shared_state = sha2::Sha256::default();
+        let mut prove = || {
+            super::prove_all::<M, _, _>(
+                shared_state.clone(),
+                &verifier_aux,
+                data,
+                pdata,
+                &security,
+                &mut rng,
+            )
+            .unwrap()
+        };
+        let bundle1 = prove();
+        let bundle2 = prove();
+
+        // Each proof is valid on its own, but bound to the other proofs of its bundle
+        let mixed = super::Bundle {
+            fac_proof: bundle2.fac_proof,
+            ..bundle1.clone()
+        };
+        super::verify_all(shared_state.clone(), &verifier_aux, data, &security, &mixed)
+            .expect_err("mixed bundle should not pass");
+
+        // Bundle is made for particular verifier
+        let other_verifier_aux = aux(&mut rng);
+        super::verify_all(shared_state, &other_verifier_aux, data, &security, &bundle1)
+            .expect_err("bundle should not pass for other verifier");
+    }
+}
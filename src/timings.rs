@@ -0,0 +1,143 @@
+//! Timing breakdown of proving and verification
+//!
+//! [`measure`] runs a closure and attributes the time spent by proofs evaluated within
+//! it to the protocol stages: computing commitment, deriving challenge, computing
+//! response, and each check done by verifier. It helps attributing latency to specific
+//! exponentiations when tuning parameters.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{paillier_encryption_in_range as p, timings};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     commitment: &p::Commitment,
+//! #     security: &p::SecurityParams,
+//! #     proof: &p::Proof,
+//! # ) {
+//! let shared_state = sha2::Sha256::default();
+//! let (result, timings) = timings::measure(|| {
+//!     p::non_interactive::verify(shared_state, aux, data, commitment, security, proof)
+//! });
+//! println!("challenge derived in {:?}", timings.challenge);
+//! for (check, duration) in &timings.checks {
+//!     println!("{check} took {duration:?}");
+//! }
+//! # }
+//! ```
+//!
+//! Timings are collected per thread. Time spent within nested [`measure`] is not
+//! accounted in the outer one.
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+/// Time spent on each stage of proving or verification
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    /// Computing prover's commitment
+    pub commit: Duration,
+    /// Deriving challenge
+    pub challenge: Duration,
+    /// Computing prover's response
+    pub respond: Duration,
+    /// Checks done by verifier, in order of evaluation
+    pub checks: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    /// Total time of all stages
+    pub fn total(&self) -> Duration {
+        self.commit
+            + self.challenge
+            + self.respond
+            + self.checks.iter().map(|(_, d)| *d).sum::<Duration>()
+    }
+}
+
+/// Runs `f` and returns timing breakdown of proofs evaluated in it
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Timings) {
+    let recorder = Recorder {
+        last: Instant::now(),
+        timings: Timings::default(),
+    };
+    let outer = RECORDER.with(|r| r.replace(Some(recorder)));
+    let result = f();
+    let recorder = RECORDER.with(|r| r.replace(outer));
+    let timings = recorder.map(|r| r.timings).unwrap_or_default();
+    (result, timings)
+}
+
+/// Stage of proving or verification
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Stage {
+    Commit,
+    Challenge,
+    Respond,
+    Check(&'static str),
+}
+
+/// Attributes time elapsed since previous stage to the `stage`
+pub(crate) fn lap(stage: Stage) {
+    RECORDER.with(|r| {
+        if let Some(recorder) = r.borrow_mut().as_mut() {
+            let now = Instant::now();
+            let elapsed = now - recorder.last;
+            recorder.last = now;
+            let timings = &mut recorder.timings;
+            match stage {
+                Stage::Commit => timings.commit += elapsed,
+                Stage::Challenge => timings.challenge += elapsed,
+                Stage::Respond => timings.respond += elapsed,
+                Stage::Check(name) => timings.checks.push((name, elapsed)),
+            }
+        }
+    })
+}
+
+struct Recorder {
+    last: Instant,
+    timings: Timings,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::paillier_encryption_in_range as p;
+
+    #[test]
+    fn breakdown() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams {
+            l: 1024,
+            epsilon: 256,
+            q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let shared_state = sha2::Sha256::default();
+        let ((commitment, proof), timings) = super::measure(|| {
+            p::non_interactive::prove(shared_state.clone(), &aux, data, pdata, &security, &mut rng)
+                .unwrap()
+        });
+        assert!(timings.commit > std::time::Duration::ZERO);
+        assert!(timings.checks.is_empty());
+
+        let (result, timings) = super::measure(|| {
+            p::non_interactive::verify(shared_state, &aux, data, &commitment, &security, &proof)
+        });
+        result.unwrap();
+        assert_eq!(timings.commit, std::time::Duration::ZERO);
+        assert_eq!(timings.checks.len(), 4);
+    }
+}
@@ -0,0 +1,1196 @@
+//! Transcript of a protocol for deriving Fiat-Shamir challenges
+//!
+//! All non-interactive proofs derive their challenges from a [`Transcript`]: a proof
+//! absorbs its parameters, statement and commitment into the transcript, and then
+//! squeezes the challenge out of it. Every absorbed value is prefixed with its label
+//! and length, so two different sequences of absorbed values never produce the same
+//! challenge.
+//!
+//! Normally, proofs create a fresh transcript from `shared_state` provided by the
+//! caller (see [`Transcript::from_shared_state`]). A protocol consisting of several
+//! rounds may instead keep one transcript for the entire execution: it absorbs the
+//! messages of each round and passes the same transcript to the `*_with_transcript`
+//! functions of the proofs. A proof then binds to everything absorbed before it, and
+//! everything absorbed after it binds to the proof, without absorbing the statement
+//! twice.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{paillier_encryption_in_range as p, transcript::Transcript};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     security: &p::SecurityParams,
+//! #     commitment: &p::Commitment,
+//! #     proof: &p::Proof,
+//! #     round1_msg: &[u8],
+//! # ) -> Result<(), p::InvalidProof> {
+//! let mut transcript = Transcript::<sha2::Sha256>::new(b"my protocol");
+//! transcript.absorb(b"round1", round1_msg);
+//! p::non_interactive::verify_with_transcript(
+//!     &mut transcript,
+//!     aux,
+//!     data,
+//!     commitment,
+//!     security,
+//!     proof,
+//! )?;
+//! // Challenges of the following rounds are bound to the proof
+//! let mut round3_challenge = [0u8; 32];
+//! transcript.challenge_bytes(b"round3", &mut round3_challenge);
+//! # Ok(()) }
+//! ```
+//!
+//! ## Associated data
+//! Proof verifies only with the same transcript as it was made with, so a proof can't
+//! be replayed in a context that the transcript is bound to. Context that isn't a part
+//! of the shared state, e.g. hash of the message being signed or identifier of the
+//! protocol round, is bound with [`Transcript::from_shared_state_with_aad`], and the
+//! transcript is passed to `*_with_transcript` functions of any proof:
+//! ```rust,no_run
+//! use paillier_zk::{paillier_encryption_in_range as p, transcript::Transcript};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     security: &p::SecurityParams,
+//! #     commitment: &p::Commitment,
+//! #     proof: &p::Proof,
+//! #     message_hash: &[u8],
+//! # ) -> Result<(), p::InvalidProof> {
+//! let mut transcript =
+//!     Transcript::from_shared_state_with_aad(sha2::Sha256::default(), message_hash);
+//! p::non_interactive::verify_with_transcript(
+//!     &mut transcript,
+//!     aux,
+//!     data,
+//!     commitment,
+//!     security,
+//!     proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! ## Sessions
+//! CGGMP21 requires every challenge to be bound to the session identifier `sid` and to
+//! the index `i` of the prover. A transcript created with [`Transcript::for_session`]
+//! absorbs both before anything else, so proofs made within it by `*_with_transcript`
+//! functions of any proof are bound to them:
+//! ```rust,no_run
+//! use paillier_zk::{paillier_blum_modulus as p, transcript::Transcript};
+//! # fn doc_fn(
+//! #     data: &p::Data,
+//! #     commitment: &p::Commitment,
+//! #     proof: &p::Proof<128>,
+//! #     sid: &[u8],
+//! # ) -> Result<(), paillier_zk::InvalidProof> {
+//! // Proof received from the party with index 2
+//! let mut transcript = Transcript::<sha2::Sha256>::for_session(sid, 2);
+//! p::non_interactive::verify_with_transcript(&mut transcript, data, commitment, proof)?;
+//! # Ok(()) }
+//! ```
+//!
+//! ## Hash function
+//! Transcript, and every proof deriving challenges from it, is generic over the hash
+//! function `D`: any [`Digest`] can be used, e.g. SHA-256, SHA-512, SHA3 or BLAKE2,
+//! regardless of its output size. Prover and verifier must use the same one, which
+//! is determined by the type of `shared_state`. Challenges derived with a given hash
+//! function don't change when other ones are used elsewhere.
+//!
+//! ## Auditing
+//! Other implementations reproduce challenges bit-for-bit from the bytes hashed to
+//! derive them, returned by `non_interactive::challenge_transcript_bytes` of each
+//! proof, or by [`challenge_transcript_bytes`] for challenges derived within any
+//! transcript. The bytes are the concatenation of:
+//!
+//! * `paillier_zk.transcript`
+//! * for every [label](Transcript::label): byte `0`, length of the label as `u64` in
+//!   big-endian, the label
+//! * for every absorbed value: byte `1`, length of the label as `u64` in big-endian,
+//!   the label, length of the value as `u64` in big-endian, the value in its
+//!   [canonical encoding](crate::encoding)
+//! * for the challenge: byte `2`, length of its label as `u64` in big-endian, the label
+//!
+//! The seed is the hash of these bytes with `D`. Challenge bytes are the stream
+//! `D(0 || seed) || D(1 || seed) || ...`, where the counter is `u64` in
+//! little-endian, from which every proof samples its challenge in
+//! `interactive::challenge`. Hashing then starts anew from `paillier_zk.transcript`
+//! and the seed framed like a challenge label, so challenges squeezed later are
+//! bound to the previous ones.
+//!
+//! ## Merlin
+//! With `merlin` feature enabled, a transcript can continue a `merlin::Transcript`
+//! of the enclosing protocol, see `Transcript::from_merlin`. Every operation then
+//! appends Merlin messages instead of hashing:
+//!
+//! * [`Transcript::label`] appends the label under `dom-sep`
+//! * absorbing appends the label under `label`, then the bytes or [canonical
+//!   encoding](crate::encoding) of the value under `value`
+//! * squeezing a challenge appends its label under `challenge` and takes 32 bytes
+//!   of `seed` challenge. Challenge bytes are then taken from a fresh transcript
+//!   `paillier_zk.challenge_rng` with the seed appended under `seed`, by repeated
+//!   `bytes` challenges.
+//!
+//! Challenges derived this way differ from those derived by hashing, so prover and
+//! verifier must both use Merlin. Values are encoded in memory before they're
+//! appended, as Merlin messages can't be split.
+//!
+//! ## Custom transcripts
+//! Protocols that derive their challenges differently, e.g. with a sponge or with
+//! their own domain separation, plug their transcript by implementing [`Backend`] and
+//! passing it to [`Transcript::from_backend`]. Every value is then absorbed with a
+//! single [`Backend::absorb`] call, labeled the same as with the default backend,
+//! which hashes the values with `D`. Challenges of arbitrary size are expanded with
+//! `D` from a seed squeezed from the backend.
+//!
+//! ```rust,no_run
+//! use paillier_zk::transcript::{Backend, Transcript};
+//! use sha2::{Digest, Sha512};
+//!
+//! struct MyTranscript(Sha512);
+//!
+//! impl MyTranscript {
+//!     fn frame(&mut self, tag: u8, bytes: &[u8]) {
+//!         self.0.update([tag]);
+//!         self.0.update((bytes.len() as u64).to_be_bytes());
+//!         self.0.update(bytes);
+//!     }
+//! }
+//!
+//! impl Backend for MyTranscript {
+//!     fn label(&mut self, label: &[u8]) {
+//!         self.frame(0, label)
+//!     }
+//!     fn absorb(&mut self, label: &[u8], bytes: &[u8]) {
+//!         self.frame(1, label);
+//!         self.frame(2, bytes)
+//!     }
+//!     fn squeeze(&mut self, label: &[u8], dest: &mut [u8]) {
+//!         self.frame(3, label);
+//!         let challenge = self.0.clone().finalize();
+//!         self.frame(4, &challenge);
+//!         dest.copy_from_slice(&challenge[..dest.len()])
+//!     }
+//! }
+//!
+//! let transcript =
+//!     Transcript::<Sha512>::from_backend(MyTranscript(Sha512::new_with_prefix(b"my protocol")));
+//! ```
+//!
+//! ## Large statements
+//! Values are hashed incrementally: sequences are [encoded](Encode::encode_chunks)
+//! and absorbed element by element, so absorbing a statement with thousands of
+//! ciphertexts never builds its whole encoding in memory. Sequences that aren't
+//! stored contiguously can be absorbed with [`Transcript::absorb_iter`].
+//!
+//! ## Private data
+//! Challenge functions of the proofs take only public values: parameters, statement
+//! and commitment. Witness types (`PrivateData` and `PrivateCommitment` of each proof)
+//! don't implement [`Encode`], which is checked at compile time, so they can't be
+//! absorbed into a transcript as a whole.
+//!
+//! ## Elliptic points
+//! Points are absorbed in a single fixed encoding described by [`PointEncoding`],
+//! regardless of how they are encoded elsewhere. Implementations deriving the same
+//! challenges must encode points with [`PointEncoding::CURRENT`].
+
+use digest::Digest;
+use generic_ec::{errors::InvalidPoint, Curve, Point};
+use rand_core::RngCore;
+
+use crate::common::encoding::Encode;
+use crate::common::rng::HashRng;
+use crate::common::Aux;
+
+const DOMAIN: &[u8] = b"paillier_zk.transcript";
+const SHARED_STATE_LABEL: &[u8] = b"paillier_zk.shared_state";
+const SESSION_LABEL: &[u8] = b"paillier_zk.session";
+
+const OP_LABEL: u8 = 0;
+const OP_ABSORB: u8 = 1;
+const OP_CHALLENGE: u8 = 2;
+
+/// Transcript of a protocol
+///
+/// Challenges squeezed from the transcript depend on all values absorbed before,
+/// including the previously squeezed challenges.
+pub struct Transcript<D: Digest> {
+    state: State<D>,
+}
+
+enum State<D> {
+    Hash(Hasher<D>),
+    Custom(Box<dyn Backend + Send>),
+    /// Boxed, as Merlin state is much larger than a hash state
+    #[cfg(feature = "merlin")]
+    Merlin(Box<merlin::Transcript>),
+}
+
+impl<D: Digest> Transcript<D> {
+    /// Starts a new transcript
+    ///
+    /// `label` separates transcripts of different protocols
+    pub fn new(label: &[u8]) -> Self {
+        Self::with_hasher(Hasher::new(false), label)
+    }
+
+    fn with_hasher(hasher: Hasher<D>, label: &[u8]) -> Self {
+        let mut transcript = Self {
+            state: State::Hash(hasher),
+        };
+        transcript.label(label);
+        transcript
+    }
+
+    /// Starts a new transcript from the shared state
+    ///
+    /// This is how `non_interactive` functions of the proofs that take `shared_state`
+    /// obtain their transcript.
+    pub fn from_shared_state(shared_state: D) -> Self {
+        let mut transcript = Self::new(SHARED_STATE_LABEL);
+        transcript.absorb(b"shared_state", &shared_state.finalize());
+        transcript
+    }
+
+    /// Starts a new transcript from the shared state, like
+    /// [`Transcript::from_shared_state`], that records the bytes it hashes
+    ///
+    /// Challenges are the same as without recording. See
+    /// [`Transcript::challenge_input`] and [auditing](self#auditing).
+    pub fn recording_from_shared_state(shared_state: D) -> Self {
+        let mut transcript = Self::with_hasher(Hasher::new(true), SHARED_STATE_LABEL);
+        transcript.absorb(b"shared_state", &shared_state.finalize());
+        transcript
+    }
+
+    /// Starts a new transcript from the shared state and associated data
+    ///
+    /// `aad` is arbitrary context that the proofs made within the transcript are bound
+    /// to, e.g. hash of the message being signed or identifier of the protocol round.
+    /// See [associated data](self#associated-data).
+    pub fn from_shared_state_with_aad(shared_state: D, aad: &[u8]) -> Self {
+        let mut transcript = Self::from_shared_state(shared_state);
+        transcript.absorb(b"aad", aad);
+        transcript
+    }
+
+    /// Starts a new transcript bound to the session and the prover, as CGGMP21 requires
+    ///
+    /// `sid` identifies the session, and `prover_id` is the index `i` of the party
+    /// that proves. Verifier creates the transcript with the prover's index, not its
+    /// own. See [sessions](self#sessions).
+    pub fn for_session(sid: &[u8], prover_id: u16) -> Self {
+        let mut transcript = Self::new(SESSION_LABEL);
+        transcript.absorb(b"sid", sid);
+        transcript.absorb(b"prover_id", &prover_id.to_be_bytes());
+        transcript
+    }
+
+    /// Continues a Merlin transcript of the enclosing protocol
+    ///
+    /// Values are appended to `transcript` as Merlin messages, and challenges are
+    /// squeezed with [`merlin::Transcript::challenge_bytes`], so the proofs are bound
+    /// to everything appended to `transcript` before, and the enclosing protocol, once
+    /// it [takes the transcript back](Self::into_merlin), to the proofs. The hash
+    /// function `D` isn't used.
+    ///
+    /// Merlin labels are static, while labels of this transcript aren't: each label
+    /// is appended as a message of its own, followed by the value. See the
+    /// [module docs](self#merlin) for the exact messages.
+    #[cfg(feature = "merlin")]
+    pub fn from_merlin(transcript: merlin::Transcript) -> Self {
+        let mut transcript = Self {
+            state: State::Merlin(Box::new(transcript)),
+        };
+        transcript.label(DOMAIN);
+        transcript
+    }
+
+    /// Continues a custom transcript
+    ///
+    /// Every value is absorbed into `backend` with [`Backend::absorb`], see
+    /// [custom transcripts](self#custom-transcripts). Challenges are expanded from a
+    /// seed of `D::output_size()` bytes squeezed from `backend`, with the hash
+    /// function `D`.
+    pub fn from_backend(backend: impl Backend + Send + 'static) -> Self {
+        let mut transcript = Self {
+            state: State::Custom(Box::new(backend)),
+        };
+        transcript.label(DOMAIN);
+        transcript
+    }
+
+    /// Returns the Merlin transcript that this transcript continues, or `None` if it
+    /// wasn't created with [`Transcript::from_merlin`]
+    #[cfg(feature = "merlin")]
+    pub fn into_merlin(self) -> Option<merlin::Transcript> {
+        match self.state {
+            State::Merlin(transcript) => Some(*transcript),
+            _ => None,
+        }
+    }
+
+    /// Marks the beginning of a (sub)protocol
+    pub fn label(&mut self, label: &[u8]) {
+        match &mut self.state {
+            State::Hash(hasher) => op(hasher, OP_LABEL, label),
+            State::Custom(backend) => backend.label(label),
+            #[cfg(feature = "merlin")]
+            State::Merlin(transcript) => transcript.append_message(b"dom-sep", label),
+        }
+    }
+
+    /// Absorbs bytes
+    pub fn absorb(&mut self, label: &[u8], bytes: &[u8]) {
+        match &mut self.state {
+            State::Hash(hasher) => {
+                op(hasher, OP_ABSORB, label);
+                hasher.update((bytes.len() as u64).to_be_bytes());
+                hasher.update(bytes);
+            }
+            State::Custom(backend) => backend.absorb(label, bytes),
+            #[cfg(feature = "merlin")]
+            State::Merlin(transcript) => {
+                transcript.append_message(b"label", label);
+                transcript.append_message(b"value", bytes);
+            }
+        }
+    }
+
+    /// Absorbs value in its [canonical encoding](crate::encoding)
+    ///
+    /// Same as absorbing the encoded value with [`Transcript::absorb`], but the value
+    /// is hashed incrementally, without encoding it into one buffer
+    pub fn absorb_value(&mut self, label: &[u8], value: &(impl Encode + ?Sized)) {
+        match &mut self.state {
+            State::Hash(hasher) => {
+                op(hasher, OP_ABSORB, label);
+                hasher.update((value.encoded_len() as u64).to_be_bytes());
+                value.encode_chunks(&mut |chunk| hasher.update(chunk));
+            }
+            // Other backends absorb the whole value at once
+            _ => self.absorb(label, &value.to_canonical_bytes()),
+        }
+    }
+
+    /// Absorbs sequence of values, element by element
+    ///
+    /// Same as absorbing a slice of the values with [`Transcript::absorb_value`], but
+    /// the values don't need to be collected: `items` is iterated twice, first to
+    /// compute size of the encoding, then to hash the elements.
+    pub fn absorb_iter<T, I>(&mut self, label: &[u8], items: I)
+    where
+        T: Encode,
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator + Clone,
+    {
+        let items = items.into_iter();
+        match &mut self.state {
+            State::Hash(hasher) => {
+                let len = crate::common::encoding::sequence_len(items.clone());
+                op(hasher, OP_ABSORB, label);
+                hasher.update((len as u64).to_be_bytes());
+                crate::common::encoding::encode_sequence_chunks(items, &mut |chunk| {
+                    hasher.update(chunk)
+                });
+            }
+            _ => {
+                let mut bytes = vec![];
+                crate::common::encoding::encode_sequence_chunks(items, &mut |chunk| {
+                    bytes.extend_from_slice(chunk)
+                });
+                self.absorb(label, &bytes)
+            }
+        }
+    }
+
+    /// Absorbs point in [current encoding](PointEncoding::CURRENT)
+    ///
+    /// Same as absorbing the point with [`Transcript::absorb_value`]
+    pub fn absorb_point<C: Curve>(&mut self, label: &[u8], point: &Point<C>) {
+        self.absorb_value(label, point)
+    }
+
+    /// Absorbs point given in any encoding supported by [`Point::from_bytes`], e.g.
+    /// compressed or uncompressed
+    ///
+    /// The point is decoded and absorbed in [current encoding](PointEncoding::CURRENT),
+    /// so it doesn't matter how it was encoded by the caller. Returns error if `bytes`
+    /// is not a valid point, in which case nothing is absorbed.
+    pub fn absorb_encoded_point<C: Curve>(
+        &mut self,
+        label: &[u8],
+        bytes: &[u8],
+    ) -> Result<(), InvalidPoint> {
+        let point = Point::<C>::from_bytes(bytes)?;
+        self.absorb_point(label, &point);
+        Ok(())
+    }
+
+    /// Fills `dest` with challenge bytes
+    pub fn challenge_bytes(&mut self, label: &[u8], dest: &mut [u8]) {
+        self.challenge_rng(label).fill_bytes(dest)
+    }
+
+    /// Squeezes challenge in form of a deterministic RNG, which can be used to sample
+    /// challenges of arbitrary structure
+    pub(crate) fn challenge_rng(&mut self, label: &[u8]) -> impl RngCore {
+        let seed = match &mut self.state {
+            State::Hash(hasher) => {
+                op(hasher, OP_CHALLENGE, label);
+                let seed = hasher.finalize_reset();
+                op(hasher, OP_CHALLENGE, &seed);
+                seed.to_vec()
+            }
+            State::Custom(backend) => {
+                let mut seed = vec![0u8; <D as Digest>::output_size()];
+                backend.squeeze(label, &mut seed);
+                seed
+            }
+            #[cfg(feature = "merlin")]
+            State::Merlin(transcript) => {
+                transcript.append_message(b"challenge", label);
+                let mut seed = [0u8; 32];
+                transcript.challenge_bytes(b"seed", &mut seed);
+                let mut rng = merlin::Transcript::new(b"paillier_zk.challenge_rng");
+                rng.append_message(b"seed", &seed);
+                return ChallengeRng::Merlin(Box::new(rng));
+            }
+        };
+        ChallengeRng::Hash(HashRng::new(move |d: D| d.chain_update(&seed).finalize()))
+    }
+
+    /// Bytes hashed to derive the last challenge squeezed from the transcript
+    ///
+    /// Seed of the challenge is the hash of these bytes, see [auditing](self#auditing).
+    /// Returns `None` if no challenge was squeezed yet, or if the transcript wasn't
+    /// created with [`Transcript::recording_from_shared_state`].
+    pub fn challenge_input(&self) -> Option<&[u8]> {
+        match &self.state {
+            State::Hash(hasher) => hasher.record.as_ref()?.challenge_input.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Absorbs ring-pedersen parameters, or their pre-agreed digest if present
+    ///
+    /// Note that `aux_digest` is trusted to be [`Aux::digest`] of `aux`, it's
+    /// responsibility of the caller to ensure that.
+    #[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+    pub(crate) fn absorb_aux(&mut self, aux: &Aux, aux_digest: Option<&[u8]>) {
+        match aux_digest {
+            Some(aux_digest) => self.absorb(b"aux_digest", aux_digest),
+            None => self.absorb_value(b"aux", aux),
+        }
+    }
+
+    /// Absorbs domain separation tag of the application, if present
+    ///
+    /// Nothing is absorbed without the tag, so challenges stay the same as before
+    /// tags were introduced.
+    #[cfg_attr(not(feature = "all-proofs"), allow(dead_code))]
+    pub(crate) fn absorb_domain_tag(&mut self, domain_tag: Option<&[u8]>) {
+        if let Some(domain_tag) = domain_tag {
+            self.absorb(b"domain_tag", domain_tag);
+        }
+    }
+}
+
+/// Custom transcript, see [custom transcripts](self#custom-transcripts)
+///
+/// Implementation must bind every squeezed challenge to all labels and values
+/// absorbed before it, including the previously squeezed challenges, and must
+/// separate labels from values, so that different sequences of calls never result
+/// in the same challenge.
+pub trait Backend {
+    /// Marks the beginning of a (sub)protocol
+    fn label(&mut self, label: &[u8]);
+    /// Absorbs labeled bytes
+    fn absorb(&mut self, label: &[u8], bytes: &[u8]);
+    /// Fills `dest` with challenge bytes
+    fn squeeze(&mut self, label: &[u8], dest: &mut [u8]);
+}
+
+/// Bytes hashed to derive the challenge of a proof
+///
+/// `derive` is given a transcript started from `shared_state`, like the `challenge`
+/// functions of the proofs do, and is expected to squeeze one challenge from it, e.g.
+/// by calling `challenge_with_transcript` of a proof. Returns the bytes hashed to
+/// derive the last challenge it squeezed, empty if none. See [auditing](self#auditing).
+pub fn challenge_transcript_bytes<D: Digest>(
+    shared_state: D,
+    derive: impl FnOnce(&mut Transcript<D>),
+) -> Vec<u8> {
+    let mut transcript = Transcript::recording_from_shared_state(shared_state);
+    derive(&mut transcript);
+    transcript.challenge_input().unwrap_or_default().to_vec()
+}
+
+/// Hash function of the default backend, optionally recording the hashed bytes
+struct Hasher<D> {
+    digest: D,
+    record: Option<Record>,
+}
+
+#[derive(Default)]
+struct Record {
+    /// Bytes hashed since the last challenge
+    pending: Vec<u8>,
+    /// Bytes hashed to derive the last challenge
+    challenge_input: Option<Vec<u8>>,
+}
+
+impl<D: Digest> Hasher<D> {
+    fn new(record: bool) -> Self {
+        let mut hasher = Self {
+            digest: D::new(),
+            record: record.then(Record::default),
+        };
+        hasher.update(DOMAIN);
+        hasher
+    }
+
+    fn update(&mut self, bytes: impl AsRef<[u8]>) {
+        let bytes = bytes.as_ref();
+        self.digest.update(bytes);
+        if let Some(record) = &mut self.record {
+            record.pending.extend_from_slice(bytes);
+        }
+    }
+
+    /// Returns hash of everything hashed so far, and starts hashing anew
+    fn finalize_reset(&mut self) -> digest::Output<D> {
+        let seed = std::mem::replace(&mut self.digest, D::new()).finalize();
+        if let Some(record) = &mut self.record {
+            record.challenge_input = Some(std::mem::take(&mut record.pending));
+        }
+        self.update(DOMAIN);
+        seed
+    }
+}
+
+fn op<D: Digest>(hasher: &mut Hasher<D>, op: u8, label: &[u8]) {
+    hasher.update([op]);
+    hasher.update((label.len() as u64).to_be_bytes());
+    hasher.update(label);
+}
+
+/// Challenge squeezed from either backend of the transcript
+enum ChallengeRng<H> {
+    Hash(H),
+    /// Bytes are squeezed from a dedicated Merlin transcript seeded with the challenge
+    #[cfg(feature = "merlin")]
+    Merlin(Box<merlin::Transcript>),
+}
+
+impl<H: RngCore> RngCore for ChallengeRng<H> {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Self::Hash(rng) => rng.next_u32(),
+            #[cfg(feature = "merlin")]
+            Self::Merlin(_) => rand_core::impls::next_u32_via_fill(self),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Self::Hash(rng) => rng.next_u64(),
+            #[cfg(feature = "merlin")]
+            Self::Merlin(_) => rand_core::impls::next_u64_via_fill(self),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            Self::Hash(rng) => rng.fill_bytes(dest),
+            #[cfg(feature = "merlin")]
+            Self::Merlin(transcript) => transcript.challenge_bytes(b"bytes", dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Encoding of elliptic points absorbed into transcripts and
+/// [canonical encoding](crate::encoding) of proofs
+///
+/// Changing the encoding changes every challenge that depends on a point, so each
+/// encoding is a separate version, and the one in use is [`PointEncoding::CURRENT`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PointEncoding {
+    /// Version 1: compressed point, as returned by `Point::to_bytes(true)`
+    ///
+    /// For curves with SEC1 encoding that's `0x02` or `0x03` followed by `x`
+    /// coordinate. Prefixed with its length as `u32` in big-endian when absorbed.
+    CompressedV1,
+}
+
+impl PointEncoding {
+    /// Encoding used by this version of the library
+    pub const CURRENT: Self = Self::CompressedV1;
+
+    /// Version number of the encoding
+    pub fn version(self) -> u8 {
+        match self {
+            Self::CompressedV1 => 1,
+        }
+    }
+
+    /// Encodes the point, without length prefix
+    pub fn encode<C: Curve>(self, point: &Point<C>) -> Vec<u8> {
+        match self {
+            Self::CompressedV1 => point.to_bytes(true).to_vec(),
+        }
+    }
+}
+
+/// Fails to compile if any of the listed types implements [`Encode`]
+///
+/// Used to make sure that witness material of the proofs can't be absorbed into
+/// a transcript. Generic types are checked for all instantiations:
+/// `assert_private!(<C: Curve> PrivateData<'static, C>, PrivateCommitment<C>)`
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_macros))]
+macro_rules! assert_private {
+    (<$($generic:ident: $bound:path),*> $($ty:ty),+ $(,)?) => {
+        const _: () = {
+            trait AmbiguousIfEncode<A> {
+                fn some_item() {}
+            }
+            impl<T: ?Sized> AmbiguousIfEncode<()> for T {}
+            struct Encodable;
+            impl<T: ?Sized + $crate::common::encoding::Encode> AmbiguousIfEncode<Encodable> for T {}
+
+            #[allow(dead_code)]
+            fn assert_not_encodable<$($generic: $bound),*>() {
+                $(let _ = <$ty as AmbiguousIfEncode<_>>::some_item;)+
+            }
+        };
+    };
+    ($($ty:ty),+ $(,)?) => {
+        $crate::transcript::assert_private!(<> $($ty),+);
+    };
+}
+#[cfg_attr(not(feature = "all-proofs"), allow(unused_imports))]
+pub(crate) use assert_private;
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::paillier_encryption_in_range as p;
+
+    type Transcript = super::Transcript<sha2::Sha256>;
+
+    fn challenge(transcript: &mut Transcript) -> [u8; 32] {
+        let mut challenge = [0u8; 32];
+        transcript.challenge_bytes(b"challenge", &mut challenge);
+        challenge
+    }
+
+    #[test]
+    fn absorption_is_unambiguous() {
+        let mut t1 = Transcript::new(b"test");
+        t1.absorb(b"a", b"xy");
+        t1.absorb(b"b", b"z");
+        let mut t2 = Transcript::new(b"test");
+        t2.absorb(b"a", b"x");
+        t2.absorb(b"b", b"yz");
+        let mut t3 = Transcript::new(b"test");
+        t3.absorb(b"ab", b"xyz");
+        let mut t4 = Transcript::new(b"test");
+        t4.label(b"a");
+        t4.absorb(b"b", b"xyz");
+
+        let challenges = [t1, t2, t3, t4].map(|mut t| challenge(&mut t));
+        for (i, c1) in challenges.iter().enumerate() {
+            for c2 in &challenges[i + 1..] {
+                assert_ne!(c1, c2);
+            }
+        }
+    }
+
+    #[test]
+    fn challenges_are_chained() {
+        let mut t1 = Transcript::new(b"test");
+        t1.absorb(b"a", b"x");
+        let mut t2 = Transcript::new(b"test");
+        t2.absorb(b"a", b"x");
+        assert_eq!(challenge(&mut t1), challenge(&mut t2));
+
+        // Subsequent challenges differ from the first one and depend on everything
+        // absorbed before
+        let c1 = challenge(&mut t1);
+        t2.absorb(b"b", b"y");
+        let c2 = challenge(&mut t2);
+        assert_ne!(c1, c2);
+
+        let mut t3 = Transcript::new(b"test");
+        t3.absorb(b"b", b"y");
+        assert_ne!(challenge(&mut t3), c2);
+    }
+
+    #[test]
+    fn proofs_within_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams {
+            l: 1024,
+            epsilon: 256,
+            q: (Integer::ONE << 128_u32).complete() - 1,
+            aux_digest: None,
+            reject_weak_aux: false,
+            domain_tag: None,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let mut prover = Transcript::new(b"protocol");
+        prover.absorb(b"round1", b"hello");
+        let (commitment, proof) = p::non_interactive::prove_with_transcript(
+            &mut prover,
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let prover_challenge = challenge(&mut prover);
+
+        let verify = |round1: &[u8]| {
+            let mut verifier = Transcript::new(b"protocol");
+            verifier.absorb(b"round1", round1);
+            p::non_interactive::verify_with_transcript(
+                &mut verifier,
+                &aux,
+                data,
+                &commitment,
+                &security,
+                &proof,
+            )?;
+            Ok::<_, crate::InvalidProof>(challenge(&mut verifier))
+        };
+
+        // Challenges after the proof are bound to it
+        assert_eq!(verify(b"hello").unwrap(), prover_challenge);
+        // Proof is bound to the messages absorbed before it
+        assert!(verify(b"bye").is_err());
+        // Proof made within the transcript doesn't verify standalone
+        assert!(p::non_interactive::verify(
+            sha2::Sha256::default(),
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn proofs_bound_to_aad() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let transcript =
+            |aad: &[u8]| Transcript::from_shared_state_with_aad(sha2::Sha256::default(), aad);
+
+        let (commitment, proof) = p::non_interactive::prove_with_transcript(
+            &mut transcript(b"round 1"),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let verify = |transcript: &mut Transcript| {
+            p::non_interactive::verify_with_transcript(
+                transcript,
+                &aux,
+                data,
+                &commitment,
+                &security,
+                &proof,
+            )
+        };
+        verify(&mut transcript(b"round 1")).expect("proof failed");
+        // Proof can't be replayed in another context
+        assert!(verify(&mut transcript(b"round 2")).is_err());
+        assert!(verify(&mut transcript(b"")).is_err());
+        assert!(verify(&mut Transcript::from_shared_state(sha2::Sha256::default())).is_err());
+    }
+
+    #[test]
+    fn challenge_reproduced_from_transcript_bytes() {
+        use sha2::Digest;
+
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+        let shared_state = sha2::Sha256::default();
+        let (commitment, _proof) =
+            p::non_interactive::prove(shared_state.clone(), &aux, data, pdata, &security, &mut rng)
+                .unwrap();
+
+        let bytes = p::non_interactive::challenge_transcript_bytes(
+            shared_state.clone(),
+            &aux,
+            data,
+            &commitment,
+            &security,
+        );
+        assert!(bytes.starts_with(super::DOMAIN));
+        let mut challenge_label = vec![super::OP_CHALLENGE];
+        challenge_label.extend_from_slice(&1u64.to_be_bytes());
+        challenge_label.push(b'e');
+        assert!(bytes.ends_with(&challenge_label));
+
+        // Challenge is sampled from the hash of the bytes
+        let seed = sha2::Sha256::digest(&bytes);
+        let mut rng =
+            crate::common::rng::HashRng::new(|d: sha2::Sha256| d.chain_update(seed).finalize());
+        assert_eq!(
+            p::interactive::challenge(&security, &mut rng),
+            p::non_interactive::challenge(shared_state, &aux, data, &commitment, &security)
+        );
+    }
+
+    #[test]
+    fn proofs_bound_to_session() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, proof) = p::non_interactive::prove_with_transcript(
+            &mut Transcript::for_session(b"session 1", 1),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let verify = |sid: &[u8], prover_id| {
+            p::non_interactive::verify_with_transcript(
+                &mut Transcript::for_session(sid, prover_id),
+                &aux,
+                data,
+                &commitment,
+                &security,
+                &proof,
+            )
+        };
+        verify(b"session 1", 1).expect("proof failed");
+        // Proof is bound to the session and to the prover
+        assert!(verify(b"session 2", 1).is_err());
+        assert!(verify(b"session 1", 2).is_err());
+    }
+
+    #[test]
+    fn proofs_with_wide_digest() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let (commitment, proof) = p::non_interactive::prove(
+            sha2::Sha512::default(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        p::non_interactive::verify(
+            sha2::Sha512::default(),
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        )
+        .expect("proof failed");
+        // Challenge depends on the hash function
+        assert!(p::non_interactive::verify(
+            sha2::Sha256::default(),
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "merlin")]
+    #[test]
+    fn proofs_within_merlin_transcript() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let merlin_transcript = |round1: &[u8]| {
+            let mut t = merlin::Transcript::new(b"protocol");
+            t.append_message(b"round1", round1);
+            Transcript::from_merlin(t)
+        };
+
+        let mut prover = merlin_transcript(b"hello");
+        let (commitment, proof) = p::non_interactive::prove_with_transcript(
+            &mut prover,
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+        let mut prover = prover.into_merlin().unwrap();
+        let mut prover_challenge = [0u8; 32];
+        prover.challenge_bytes(b"round3", &mut prover_challenge);
+
+        let verify = |round1: &[u8]| {
+            let mut verifier = merlin_transcript(round1);
+            p::non_interactive::verify_with_transcript(
+                &mut verifier,
+                &aux,
+                data,
+                &commitment,
+                &security,
+                &proof,
+            )?;
+            let mut challenge = [0u8; 32];
+            verifier
+                .into_merlin()
+                .unwrap()
+                .challenge_bytes(b"round3", &mut challenge);
+            Ok::<_, crate::InvalidProof>(challenge)
+        };
+
+        // The enclosing protocol is bound to the proof
+        assert_eq!(verify(b"hello").unwrap(), prover_challenge);
+        // Proof is bound to the messages appended before it
+        assert!(verify(b"bye").is_err());
+        // Challenges differ from the ones derived by hashing
+        let mut hashed = Transcript::new(b"protocol");
+        hashed.absorb(b"round1", b"hello");
+        assert!(p::non_interactive::verify_with_transcript(
+            &mut hashed,
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        )
+        .is_err());
+        assert!(Transcript::new(b"protocol").into_merlin().is_none());
+    }
+
+    #[test]
+    fn proofs_within_custom_transcript() {
+        use sha2::Digest;
+
+        /// Keeps every absorbed value, hashes them all on squeeze
+        #[derive(Clone, Default)]
+        struct Log(Vec<Vec<u8>>);
+        impl super::Backend for Log {
+            fn label(&mut self, label: &[u8]) {
+                self.0.push([b"label:", label].concat())
+            }
+            fn absorb(&mut self, label: &[u8], bytes: &[u8]) {
+                self.0.push([b"absorb:", label].concat());
+                self.0.push(bytes.to_vec())
+            }
+            fn squeeze(&mut self, label: &[u8], dest: &mut [u8]) {
+                self.0.push([b"squeeze:", label].concat());
+                let mut hash = sha2::Sha256::new();
+                for entry in &self.0 {
+                    hash.update((entry.len() as u64).to_be_bytes());
+                    hash.update(entry);
+                }
+                let challenge = hash.finalize();
+                dest.copy_from_slice(&challenge[..dest.len()]);
+                self.0.push(challenge.to_vec())
+            }
+        }
+
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let mut prover = Transcript::from_backend(Log::default());
+        let (commitment, proof) = p::non_interactive::prove_with_transcript(
+            &mut prover,
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        let verify = |backend: Log| {
+            let mut verifier = Transcript::from_backend(backend);
+            p::non_interactive::verify_with_transcript(
+                &mut verifier,
+                &aux,
+                data,
+                &commitment,
+                &security,
+                &proof,
+            )
+        };
+        verify(Log::default()).expect("proof failed");
+        // Proof is bound to the messages absorbed before it
+        assert!(verify(Log(vec![b"round1".to_vec()])).is_err());
+        // Challenges differ from the ones derived by the default backend
+        assert!(p::non_interactive::verify_with_transcript(
+            &mut Transcript::new(b"protocol"),
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn incremental_absorption() {
+        use crate::common::encoding::Encode;
+
+        let values = [Integer::from(1), Integer::from(-0x1234), Integer::ZERO];
+
+        let mut expected = Transcript::new(b"test");
+        expected.absorb(b"values", &values[..].to_canonical_bytes());
+        let expected = challenge(&mut expected);
+
+        let mut t = Transcript::new(b"test");
+        t.absorb_value(b"values", &values[..]);
+        assert_eq!(challenge(&mut t), expected);
+        let mut t = Transcript::new(b"test");
+        t.absorb_iter(b"values", &values);
+        assert_eq!(challenge(&mut t), expected);
+        let mut t = Transcript::new(b"test");
+        t.absorb_iter(b"values", values.iter().cloned());
+        assert_eq!(challenge(&mut t), expected);
+    }
+
+    #[test]
+    fn labels_separate_transcripts() {
+        let mut t1 = Transcript::new(b"protocol 1");
+        let mut t2 = Transcript::new(b"protocol 2");
+        assert_ne!(challenge(&mut t1), challenge(&mut t2));
+    }
+
+    #[test]
+    fn point_encoding_independence() {
+        use super::PointEncoding;
+        use generic_ec::{Point, Scalar};
+        type E = generic_ec::curves::Secp256k1;
+
+        let mut rng = rand_dev::DevRng::new();
+        let point = Point::<E>::generator() * Scalar::random(&mut rng);
+        assert_eq!(PointEncoding::CURRENT.version(), 1);
+        assert_eq!(
+            PointEncoding::CURRENT.encode(&point),
+            point.to_bytes(true).to_vec()
+        );
+
+        let mut expected = Transcript::new(b"test");
+        expected.absorb_value(b"X", &point);
+        let expected = challenge(&mut expected);
+
+        let mut t = Transcript::new(b"test");
+        t.absorb_point(b"X", &point);
+        assert_eq!(challenge(&mut t), expected);
+        for compressed in [true, false] {
+            let mut t = Transcript::new(b"test");
+            t.absorb_encoded_point::<E>(b"X", &point.to_bytes(compressed))
+                .unwrap();
+            assert_eq!(challenge(&mut t), expected);
+        }
+
+        let mut t = Transcript::new(b"test");
+        assert!(t.absorb_encoded_point::<E>(b"X", &[4, 1, 2, 3]).is_err());
+    }
+
+    /// Guards proofs added in future: every proof checks its witness types with
+    /// `assert_private!`, and its challenge functions don't mention them
+    #[test]
+    fn challenges_take_public_values_only() {
+        fn sources(dir: &std::path::Path, out: &mut Vec<(std::path::PathBuf, String)>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    sources(&path, out)
+                } else if path.extension().is_some_and(|ext| ext == "rs") {
+                    let source = std::fs::read_to_string(&path).unwrap();
+                    out.push((path, source))
+                }
+            }
+        }
+        /// Signature and body of the function that `source` starts with
+        fn function(source: &str) -> &str {
+            let mut depth = 0;
+            for (i, c) in source.char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' if depth == 1 => return &source[..=i],
+                    '}' => depth -= 1,
+                    _ => (),
+                }
+            }
+            panic!("unterminated function")
+        }
+
+        let mut files = vec![];
+        sources(
+            &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut files,
+        );
+        let mut proofs = 0;
+        for (path, source) in &files {
+            let source = match source.find("\nmod test {") {
+                Some(tests) => &source[..tests],
+                None => source,
+            };
+            if !source.contains("pub struct PrivateData") {
+                continue;
+            }
+            proofs += 1;
+            assert!(
+                source.contains("assert_private!("),
+                "{}: witness types aren't checked with `assert_private!`",
+                path.display()
+            );
+            for (i, _) in source.match_indices("fn challenge") {
+                let function = function(&source[i..]);
+                for private in ["PrivateData", "PrivateCommitment", "pdata", "pcomm"] {
+                    assert!(
+                        !function.contains(private),
+                        "{}: challenge depends on `{private}`: {function}",
+                        path.display()
+                    );
+                }
+            }
+        }
+        assert!(proofs > 0);
+    }
+}
@@ -0,0 +1,475 @@
+//! Proofs with security parameters fixed at compile time
+//!
+//! Runtime [`SecurityParams`](crate::paillier_encryption_in_range::SecurityParams)
+//! are passed to prover and verifier separately, and nothing but a failed
+//! verification tells that they disagree. Here the security level is a type
+//! implementing [`SecurityLevel`], and commitments and proofs are wrapped into
+//! [`Typed`] carrying it. Verifier expecting another level can't accept them: mixing
+//! the parameters is a type error.
+//!
+//! Typed wrappers are provided for the proofs of CGGMP21 that are parameterized by
+//! `L`, `EPSILON` and `M`: [Пenc](enc), [Пlog*](log_star), [Пaff-g](aff_g) and
+//! [Пmod](blum). Amount of repetitions of Пmod is the const generic of its proof,
+//! which must equal [`SecurityLevel::M`]: proving or verifying with another one
+//! fails to compile.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::paillier_encryption_in_range as p;
+//! use paillier_zk::typed::{self, SecurityLevel128};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     pdata: p::PrivateData,
+//! # ) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut rng = rand_core::OsRng;
+//! let (commitment, proof) = typed::enc::prove::<SecurityLevel128, _, _>(
+//!     sha2::Sha256::default(),
+//!     aux,
+//!     data,
+//!     pdata,
+//!     &mut rng,
+//! )?;
+//! // Level of the verifier is inferred from the types of commitment and proof
+//! typed::enc::verify(sha2::Sha256::default(), aux, data, &commitment, &proof)?;
+//! # Ok(()) }
+//! ```
+
+use std::marker::PhantomData;
+
+use rug::{Complete, Integer};
+
+use crate::common::encoding::{Decode, Encode};
+
+/// Security level known at compile time
+///
+/// Values of the constants match [`SecurityLevel`](crate::security_level::SecurityLevel),
+/// see [`SecurityLevel::runtime`].
+pub trait SecurityLevel {
+    /// Bits of security
+    const KAPPA: usize;
+    /// l in paper, bit size of secrets
+    const L: usize;
+    /// l' in paper, bit size of masks
+    const L_PRIME: usize;
+    /// Epsilon in paper, slackness parameter
+    const EPSILON: usize;
+    /// M in paper, amount of repetitions of Пmod and Пprm
+    const M: usize;
+
+    /// q in paper, challenges are sampled in `±q`
+    fn q() -> Integer {
+        (Integer::ONE << Self::KAPPA).complete()
+    }
+
+    /// Same parameters as a runtime value
+    fn runtime() -> crate::security_level::SecurityLevel {
+        let mut level = crate::security_level::SecurityLevel::recommended_128();
+        level.kappa = Self::KAPPA;
+        level.l = Self::L;
+        level.l_prime = Self::L_PRIME;
+        level.epsilon = Self::EPSILON;
+        level.m = Self::M;
+        level.q = Self::q();
+        level
+    }
+}
+
+/// 128 bits of security, same as
+/// [`SecurityLevel::recommended_128`](crate::security_level::SecurityLevel::recommended_128)
+#[derive(Debug, Clone, Copy)]
+pub enum SecurityLevel128 {}
+
+impl SecurityLevel for SecurityLevel128 {
+    const KAPPA: usize = 128;
+    const L: usize = 256;
+    const L_PRIME: usize = 5 * 256;
+    const EPSILON: usize = 2 * 256;
+    const M: usize = 128;
+
+    fn runtime() -> crate::security_level::SecurityLevel {
+        crate::security_level::SecurityLevel::recommended_128()
+    }
+}
+
+/// 112 bits of security, same as
+/// [`SecurityLevel::recommended_112`](crate::security_level::SecurityLevel::recommended_112)
+#[derive(Debug, Clone, Copy)]
+pub enum SecurityLevel112 {}
+
+impl SecurityLevel for SecurityLevel112 {
+    const KAPPA: usize = 112;
+    const L: usize = 256;
+    const L_PRIME: usize = 5 * 256;
+    const EPSILON: usize = 2 * 256;
+    const M: usize = 112;
+
+    fn runtime() -> crate::security_level::SecurityLevel {
+        crate::security_level::SecurityLevel::recommended_112()
+    }
+}
+
+/// Message of the prover computed at security level `L`
+///
+/// Encoded the same way as the wrapped message.
+pub struct Typed<T, L: SecurityLevel> {
+    inner: T,
+    _level: PhantomData<fn() -> L>,
+}
+
+impl<T, L: SecurityLevel> Typed<T, L> {
+    /// Wraps a message that was computed at level `L`, e.g. received from prover
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            _level: PhantomData,
+        }
+    }
+
+    /// Wrapped message
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwraps the message
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Clone, L: SecurityLevel> Clone for Typed<T, L> {
+    fn clone(&self) -> Self {
+        Self::new(self.inner.clone())
+    }
+}
+
+impl<T: std::fmt::Debug, L: SecurityLevel> std::fmt::Debug for Typed<T, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<T: Encode, L: SecurityLevel> Encode for Typed<T, L> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.inner.encode(out)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.inner.encoded_len()
+    }
+
+    fn encode_chunks(&self, sink: &mut dyn FnMut(&[u8])) {
+        self.inner.encode_chunks(sink)
+    }
+}
+
+impl<T: Decode, L: SecurityLevel> Decode for Typed<T, L> {
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        T::decode(bytes).map(Self::new)
+    }
+}
+
+/// [Пenc](crate::paillier_encryption_in_range) at a fixed security level
+#[cfg(feature = "paillier-encryption-in-range")]
+pub mod enc {
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::paillier_encryption_in_range::{
+        non_interactive, Aux, Commitment, Data, PrivateData, Proof, SecurityParams,
+    };
+    use crate::witness::Witness;
+    use crate::{Error, InvalidProof};
+
+    use super::{SecurityLevel, Typed};
+
+    /// Parameters of the proof at level `L`
+    pub fn security<L: SecurityLevel>() -> SecurityParams {
+        SecurityParams::new(L::L, L::EPSILON, L::q())
+    }
+
+    /// Computes the proof, see [`non_interactive::prove`]
+    #[allow(clippy::type_complexity)]
+    pub fn prove<L: SecurityLevel, D, R: RngCore>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        pdata: PrivateData<impl Witness + ?Sized>,
+        rng: &mut R,
+    ) -> Result<(Typed<Commitment, L>, Typed<Proof, L>), Error>
+    where
+        D: Digest,
+    {
+        let (commitment, proof) =
+            non_interactive::prove(shared_state, aux, data, pdata, &security::<L>(), rng)?;
+        Ok((Typed::new(commitment), Typed::new(proof)))
+    }
+
+    /// Verifies the proof, see [`non_interactive::verify`]
+    pub fn verify<L: SecurityLevel, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data,
+        commitment: &Typed<Commitment, L>,
+        proof: &Typed<Proof, L>,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        non_interactive::verify(
+            shared_state,
+            aux,
+            data,
+            commitment.inner(),
+            &security::<L>(),
+            proof.inner(),
+        )
+    }
+}
+
+/// [Пlog*](crate::group_element_vs_paillier_encryption_in_range) at a fixed security
+/// level
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+pub mod log_star {
+    use digest::Digest;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+
+    use crate::group_element_vs_paillier_encryption_in_range::{
+        non_interactive, Aux, Commitment, Data, PrivateData, Proof, SecurityParams,
+    };
+    use crate::witness::Witness;
+    use crate::{Error, InvalidProof};
+
+    use super::{SecurityLevel, Typed};
+
+    /// Parameters of the proof at level `L`
+    pub fn security<L: SecurityLevel>() -> SecurityParams {
+        SecurityParams::new(L::L, L::EPSILON, L::q())
+    }
+
+    /// Computes the proof, see [`non_interactive::prove`]
+    #[allow(clippy::type_complexity)]
+    pub fn prove<L: SecurityLevel, C: Curve, R: RngCore, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData<impl Witness + ?Sized>,
+        rng: &mut R,
+    ) -> Result<(Typed<Commitment<C>, L>, Typed<Proof, L>), Error>
+    where
+        D: Digest,
+    {
+        let (commitment, proof) =
+            non_interactive::prove(shared_state, aux, data, pdata, &security::<L>(), rng)?;
+        Ok((Typed::new(commitment), Typed::new(proof)))
+    }
+
+    /// Verifies the proof, see [`non_interactive::verify`]
+    pub fn verify<L: SecurityLevel, C: Curve, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Typed<Commitment<C>, L>,
+        proof: &Typed<Proof, L>,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        non_interactive::verify(
+            shared_state,
+            aux,
+            data,
+            commitment.inner(),
+            &security::<L>(),
+            proof.inner(),
+        )
+    }
+}
+
+/// [Пaff-g](crate::paillier_affine_operation_in_range) at a fixed security level
+#[cfg(feature = "paillier-affine-operation-in-range")]
+pub mod aff_g {
+    use digest::Digest;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+
+    use crate::paillier_affine_operation_in_range::{
+        non_interactive, Aux, Commitment, Data, PrivateData, Proof, SecurityParams,
+    };
+    use crate::{Error, InvalidProof};
+
+    use super::{SecurityLevel, Typed};
+
+    /// Parameters of the proof at level `L`
+    pub fn security<L: SecurityLevel>() -> SecurityParams {
+        SecurityParams::new(L::L, L::L_PRIME, L::EPSILON, L::q())
+    }
+
+    /// Computes the proof, see [`non_interactive::prove`]
+    #[allow(clippy::type_complexity)]
+    pub fn prove<L: SecurityLevel, C: Curve, R: RngCore, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        rng: R,
+    ) -> Result<(Typed<Commitment<C>, L>, Typed<Proof, L>), Error>
+    where
+        D: Digest,
+    {
+        let (commitment, proof) =
+            non_interactive::prove(shared_state, aux, data, pdata, &security::<L>(), rng)?;
+        Ok((Typed::new(commitment), Typed::new(proof)))
+    }
+
+    /// Verifies the proof, see [`non_interactive::verify`]
+    pub fn verify<L: SecurityLevel, C: Curve, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Typed<Commitment<C>, L>,
+        proof: &Typed<Proof, L>,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        non_interactive::verify(
+            shared_state,
+            aux,
+            data,
+            commitment.inner(),
+            &security::<L>(),
+            proof.inner(),
+        )
+    }
+}
+
+/// [Пmod](crate::paillier_blum_modulus) at a fixed security level
+///
+/// `M` of the proof must equal [`SecurityLevel::M`], otherwise it doesn't compile:
+///
+/// ```rust,compile_fail
+/// # use paillier_zk::paillier_blum_modulus as p;
+/// # use paillier_zk::typed::{self, SecurityLevel128};
+/// # fn doc_fn(data: &p::Data, pdata: &p::PrivateData) -> Result<(), paillier_zk::Error> {
+/// let (commitment, proof) = typed::blum::prove::<SecurityLevel128, 80, _, _>(
+///     sha2::Sha256::default(),
+///     data,
+///     pdata,
+///     &mut rand_core::OsRng,
+/// )?;
+/// # Ok(()) }
+/// # // The check is evaluated once the function is instantiated
+/// # let _: fn(&p::Data, &p::PrivateData) -> Result<(), paillier_zk::Error> = doc_fn;
+/// ```
+#[cfg(feature = "paillier-blum-modulus")]
+pub mod blum {
+    use std::marker::PhantomData;
+
+    use digest::Digest;
+    use rand_core::RngCore;
+
+    use crate::paillier_blum_modulus::{non_interactive, Commitment, Data, PrivateData, Proof};
+    use crate::{Error, InvalidProof};
+
+    use super::{SecurityLevel, Typed};
+
+    /// Fails to compile if `M` doesn't match the level
+    struct AssertM<L, const M: usize>(PhantomData<L>);
+
+    impl<L: SecurityLevel, const M: usize> AssertM<L, M> {
+        const OK: () = assert!(L::M == M, "M doesn't match the security level");
+    }
+
+    /// Computes the proof, see [`non_interactive::prove`]
+    #[allow(clippy::type_complexity)]
+    pub fn prove<L: SecurityLevel, const M: usize, R: RngCore, D>(
+        shared_state: D,
+        data: &Data,
+        pdata: &PrivateData,
+        rng: &mut R,
+    ) -> Result<(Typed<Commitment, L>, Typed<Proof<M>, L>), Error>
+    where
+        D: Digest + Clone,
+    {
+        #[allow(clippy::let_unit_value)]
+        let () = AssertM::<L, M>::OK;
+        let (commitment, proof) = non_interactive::prove(shared_state, data, pdata, rng)?;
+        Ok((Typed::new(commitment), Typed::new(proof)))
+    }
+
+    /// Verifies the proof, see [`non_interactive::verify`]
+    pub fn verify<L: SecurityLevel, const M: usize, D>(
+        shared_state: D,
+        data: &Data,
+        commitment: &Typed<Commitment, L>,
+        proof: &Typed<Proof<M>, L>,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest + Clone,
+    {
+        #[allow(clippy::let_unit_value)]
+        let () = AssertM::<L, M>::OK;
+        non_interactive::verify(shared_state, data, commitment.inner(), proof.inner())
+    }
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use crate::paillier_encryption_in_range as p;
+
+    use super::{SecurityLevel, SecurityLevel112, SecurityLevel128, Typed};
+
+    #[test]
+    fn typed_enc() {
+        let mut rng = rand_dev::DevRng::new();
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, SecurityLevel128::L);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) = super::enc::prove::<SecurityLevel128, _, _>(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &mut rng,
+        )
+        .unwrap();
+        super::enc::verify(shared_state.clone(), &aux, data, &commitment, &proof)
+            .expect("proof failed");
+
+        // Untyped proof verifies with the same parameters
+        p::non_interactive::verify(
+            shared_state.clone(),
+            &aux,
+            data,
+            commitment.inner(),
+            &super::enc::security::<SecurityLevel128>(),
+            proof.inner(),
+        )
+        .expect("proof failed");
+
+        // Re-labeling the proof with another level doesn't make it valid there
+        let commitment = Typed::<_, SecurityLevel112>::new(commitment.into_inner());
+        let proof = Typed::<_, SecurityLevel112>::new(proof.into_inner());
+        super::enc::verify(shared_state, &aux, data, &commitment, &proof)
+            .expect_err("proof at another level should not pass");
+    }
+
+    #[test]
+    fn runtime_levels_match() {
+        fn check<L: SecurityLevel>() {
+            let level = L::runtime();
+            assert_eq!(level.kappa, L::KAPPA);
+            assert_eq!(level.l, L::L);
+            assert_eq!(level.l_prime, L::L_PRIME);
+            assert_eq!(level.epsilon, L::EPSILON);
+            assert_eq!(level.m, L::M);
+            assert_eq!(level.q, L::q());
+        }
+        check::<SecurityLevel128>();
+        check::<SecurityLevel112>();
+    }
+}
@@ -0,0 +1,39 @@
+//! Utilities for building protocols on top of the proofs
+//!
+//! This module gathers helpers that the proofs are built from and that are useful for
+//! the protocols using them: sampling of integers, canonical encoding, ring-pedersen
+//! parameters and error types.
+//!
+//! ## Stability
+//! Items exported from this module are part of the public API and follow semver: they
+//! are not removed or changed in incompatible way without bumping the major version.
+//! [`IntegerExt`](sampling::IntegerExt) is sealed, so new methods may be added to it in
+//! minor releases. Anything not reachable through this module or the proof modules is
+//! an implementation detail.
+//!
+//! Shortcuts exported from the crate root (e.g. [`crate::IntegerExt`],
+//! [`crate::InvalidProof`]) refer to the same items.
+
+/// Sampling of random integers and deterministic randomness
+pub mod sampling {
+    pub use crate::common::{rng::HashRng, IntegerExt};
+}
+
+/// Canonical binary encoding of proofs
+pub mod encoding {
+    pub use crate::common::encoding::{Decode, Encode, Field, FieldType, Schema};
+}
+
+/// Ring-pedersen parameters shared by prover and verifier
+pub mod ring_pedersen {
+    pub use crate::common::{Aux, MIN_AUX_MODULUS_BITS};
+    pub use crate::multiexp::MultiexpTable;
+}
+
+/// Errors returned by the proofs
+pub mod errors {
+    #[cfg(feature = "research")]
+    pub use crate::common::extract::ExtractionError;
+    pub use crate::common::{BadExponent, InvalidProof, PaillierError, WeakAux};
+    pub use crate::Error;
+}
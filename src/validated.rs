@@ -0,0 +1,240 @@
+//! Statements validated once for repeated verification
+//!
+//! Some checks don't depend on the proof: security parameters must fit the statement
+//! (see [`validate_parameters`](crate::compatibility::validate_parameters)), and
+//! ciphertexts of the statement must be in `Z*_{N^2}` (see `PreparedStatement` of
+//! each proof). When the same statement is proven repeatedly, e.g. by the same party
+//! in every session, [`ValidatedStatement`] performs them once at construction, and
+//! [`ValidatedStatement::verify`] only does the work that depends on the proof.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::{paillier_encryption_in_range as p, validated::ValidatedStatement};
+//! # fn doc_fn(
+//! #     aux: &p::Aux,
+//! #     data: p::Data,
+//! #     security: p::SecurityParams,
+//! #     proofs: &[(p::Commitment, p::Proof)],
+//! # ) -> Result<(), paillier_zk::Error> {
+//! let statement = ValidatedStatement::new(data, aux, security)?;
+//! for (commitment, proof) in proofs {
+//!     statement.verify(sha2::Sha256::default(), commitment, proof)?;
+//! }
+//! # Ok(()) }
+//! ```
+
+use digest::Digest;
+
+use crate::common::{Aux, InvalidProof};
+use crate::reprove::Reprovable;
+use crate::{Error, ErrorReason};
+
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+use crate::group_element_vs_paillier_encryption_in_range as log_star;
+#[cfg(feature = "paillier-affine-operation-in-range")]
+use crate::paillier_affine_operation_in_range as aff_g;
+#[cfg(feature = "paillier-encryption-in-range")]
+use crate::paillier_encryption_in_range as enc;
+
+/// Statement that can be prepared for verification, implemented for its statement
+pub trait Validatable: Reprovable {
+    /// Statement validated and prepared for verification
+    type Prepared;
+
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    fn prepare(&self) -> Result<Self::Prepared, InvalidProof>;
+
+    /// Verifies the non-interactive proof for the prepared statement
+    fn verify_prepared<D: Digest>(
+        prepared: &Self::Prepared,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof>;
+}
+
+/// Statement along with parameters validated for it
+#[derive(Debug, Clone)]
+pub struct ValidatedStatement<'a, S: Validatable> {
+    prepared: S::Prepared,
+    aux: &'a Aux,
+    security: S::SecurityParams,
+}
+
+impl<'a, S: Validatable> ValidatedStatement<'a, S> {
+    /// Validates `security` and `aux` against the statement, and prepares it for
+    /// verification
+    ///
+    /// Fails if the parameters are [incompatible](crate::compatibility) with the
+    /// statement, or if the statement itself is malformed.
+    pub fn new(statement: S, aux: &'a Aux, security: S::SecurityParams) -> Result<Self, Error> {
+        statement
+            .validate(aux, &security)
+            .map_err(ErrorReason::IncompatibleParams)?;
+        let prepared = statement.prepare()?;
+        Ok(Self {
+            prepared,
+            aux,
+            security,
+        })
+    }
+
+    /// Security parameters the statement was validated with
+    pub fn security(&self) -> &S::SecurityParams {
+        &self.security
+    }
+
+    /// Verifies the non-interactive proof without validating the statement again
+    pub fn verify<D: Digest>(
+        &self,
+        shared_state: D,
+        commitment: &S::Commitment,
+        proof: &S::Proof,
+    ) -> Result<(), InvalidProof> {
+        S::verify_prepared(
+            &self.prepared,
+            shared_state,
+            self.aux,
+            &self.security,
+            commitment,
+            proof,
+        )
+    }
+}
+
+#[cfg(feature = "paillier-encryption-in-range")]
+impl<'a> Validatable for enc::Data<'a> {
+    type Prepared = enc::PreparedStatement<'a>;
+
+    fn prepare(&self) -> Result<Self::Prepared, InvalidProof> {
+        enc::PreparedStatement::new(*self).map_err(|err| err.with_key(self.key.n()))
+    }
+
+    fn verify_prepared<D: Digest>(
+        prepared: &Self::Prepared,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        enc::non_interactive::verify_prepared(
+            shared_state,
+            aux,
+            prepared,
+            commitment,
+            security,
+            proof,
+        )
+    }
+}
+
+#[cfg(feature = "group-element-vs-paillier-encryption-in-range")]
+impl<'a, C: generic_ec::Curve> Validatable for log_star::Data<'a, C> {
+    type Prepared = log_star::PreparedStatement<'a, C>;
+
+    fn prepare(&self) -> Result<Self::Prepared, InvalidProof> {
+        log_star::PreparedStatement::new(*self).map_err(|err| err.with_key(self.key0.n()))
+    }
+
+    fn verify_prepared<D: Digest>(
+        prepared: &Self::Prepared,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        log_star::non_interactive::verify_prepared(
+            shared_state,
+            aux,
+            prepared,
+            commitment,
+            security,
+            proof,
+        )
+    }
+}
+
+#[cfg(feature = "paillier-affine-operation-in-range")]
+impl<'a, C: generic_ec::Curve> Validatable for aff_g::Data<'a, C> {
+    type Prepared = aff_g::PreparedStatement<'a, C>;
+
+    fn prepare(&self) -> Result<Self::Prepared, InvalidProof> {
+        aff_g::PreparedStatement::new(*self).map_err(|err| err.with_key(self.key1.n()))
+    }
+
+    fn verify_prepared<D: Digest>(
+        prepared: &Self::Prepared,
+        shared_state: D,
+        aux: &Aux,
+        security: &Self::SecurityParams,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), InvalidProof> {
+        aff_g::non_interactive::verify_prepared(
+            shared_state,
+            aux,
+            prepared,
+            commitment,
+            security,
+            proof,
+        )
+    }
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use rug::{Complete, Integer};
+
+    use crate::paillier_encryption_in_range as p;
+
+    use super::ValidatedStatement;
+
+    #[test]
+    fn verify_validated_statement() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let (data, pdata) = (statement.data(), statement.pdata());
+
+        let statement = ValidatedStatement::new(data, &aux, security.clone()).unwrap();
+        for i in 0u8..3 {
+            use sha2::Digest;
+            let shared_state = sha2::Sha256::default().chain_update([i]);
+            let (commitment, proof) = p::non_interactive::prove(
+                shared_state.clone(),
+                &aux,
+                data,
+                pdata,
+                &security,
+                &mut rng,
+            )
+            .unwrap();
+            statement
+                .verify(shared_state, &commitment, &proof)
+                .expect("proof failed");
+        }
+
+        // Parameters that don't hide the witness are rejected upfront
+        let weak = p::SecurityParams::new(1024, 64, (Integer::ONE << 128_u32).complete());
+        let r = ValidatedStatement::new(data, &aux, weak);
+        assert!(matches!(
+            r.map(|_| ()).map_err(|e| e.0),
+            Err(crate::ErrorReason::IncompatibleParams(_))
+        ));
+
+        // So is the ciphertext out of `Z*_{N^2}`
+        let zero = Integer::ZERO;
+        let malformed = p::Data {
+            key: data.key,
+            ciphertext: &zero,
+        };
+        let r = ValidatedStatement::new(malformed, &aux, security);
+        assert!(r.map(|_| ()).unwrap_err().invalid_proof().is_some());
+    }
+}
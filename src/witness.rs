@@ -0,0 +1,161 @@
+//! Witnesses kept in external storage
+//!
+//! Witness of a proof isn't always available to the prover: e.g. it can be kept in a
+//! hardware security module, which never reveals it but computes values derived from
+//! it. [`Witness`] abstracts the operations that proofs do with the witness, so such
+//! storage can be plugged in. [`Integer`] implements it in memory, which is what
+//! proofs use by default.
+//!
+//! Witness can be kept in external storage in:
+//! - [Пenc](crate::paillier_encryption_in_range), `plaintext` of `PrivateData`
+//! - [Пlog*](crate::group_element_vs_paillier_encryption_in_range), `x` of
+//!   `PrivateData`
+//!
+//! Nonces of encryptions are still kept in memory.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use paillier_zk::utils::ring_pedersen::Aux;
+//! use paillier_zk::witness::{Witness, WitnessError};
+//! use paillier_zk::{Error, Integer};
+//! # fn hsm_pow_mod(_: u64, _: &Integer, _: &Integer) -> std::io::Result<Integer> { todo!() }
+//! # fn hsm_linear(_: u64, _: &Integer, _: &Integer) -> std::io::Result<Integer> { todo!() }
+//!
+//! /// Handle of the key stored in HSM
+//! struct HsmKey(u64);
+//!
+//! impl Witness for HsmKey {
+//!     fn ring_pedersen_commit(&self, aux: &Aux, randomness: &Integer) -> Result<Integer, Error> {
+//!         // `s^x mod N^` is computed by HSM, `t^randomness` doesn't depend on the key
+//!         let s_to_x = hsm_pow_mod(self.0, &aux.s, &aux.rsa_modulo).map_err(WitnessError::new)?;
+//!         let t_to_randomness = aux.pow_mod(&aux.t, randomness)?;
+//!         Ok((s_to_x * t_to_randomness).modulo(&aux.rsa_modulo))
+//!     }
+//!
+//!     fn linear_response(&self, mask: &Integer, challenge: &Integer) -> Result<Integer, Error> {
+//!         Ok(hsm_linear(self.0, mask, challenge).map_err(WitnessError::new)?)
+//!     }
+//! }
+//! ```
+
+use rug::{Complete, Integer};
+
+use crate::common::Aux;
+use crate::Error;
+
+/// Secret value known to prover
+///
+/// Implementations must not reveal anything but the results of the operations.
+pub trait Witness {
+    /// Computes ring-pedersen commitment `s^self t^randomness mod N^`
+    fn ring_pedersen_commit(&self, aux: &Aux, randomness: &Integer) -> Result<Integer, Error>;
+
+    /// Computes response `mask + challenge * self` over integers
+    fn linear_response(&self, mask: &Integer, challenge: &Integer) -> Result<Integer, Error>;
+}
+
+impl Witness for Integer {
+    fn ring_pedersen_commit(&self, aux: &Aux, randomness: &Integer) -> Result<Integer, Error> {
+        Ok(aux.combine(self, randomness)?)
+    }
+
+    fn linear_response(&self, mask: &Integer, challenge: &Integer) -> Result<Integer, Error> {
+        Ok((mask + challenge * self).complete())
+    }
+}
+
+/// Failure of external witness storage
+#[derive(Debug, thiserror::Error)]
+#[error("witness storage failed")]
+pub struct WitnessError(#[source] Box<dyn std::error::Error + Send + Sync>);
+
+impl WitnessError {
+    /// Wraps error of the storage
+    pub fn new(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self(err.into())
+    }
+}
+
+#[cfg(all(test, feature = "paillier-encryption-in-range"))]
+mod test {
+    use std::cell::Cell;
+
+    use rug::{Complete, Integer};
+
+    use crate::common::Aux;
+    use crate::paillier_encryption_in_range as p;
+    use crate::Error;
+
+    /// Storage that computes with the witness, counting the operations
+    struct Storage {
+        x: Integer,
+        operations: Cell<usize>,
+    }
+
+    impl super::Witness for Storage {
+        fn ring_pedersen_commit(&self, aux: &Aux, randomness: &Integer) -> Result<Integer, Error> {
+            self.operations.set(self.operations.get() + 1);
+            let s_to_x = aux.pow_mod(&aux.s, &self.x)?;
+            let t_to_randomness = aux.pow_mod(&aux.t, randomness)?;
+            Ok((s_to_x * t_to_randomness).modulo(&aux.rsa_modulo))
+        }
+
+        fn linear_response(&self, mask: &Integer, challenge: &Integer) -> Result<Integer, Error> {
+            self.operations.set(self.operations.get() + 1);
+            Ok((mask + challenge * &self.x).complete())
+        }
+    }
+
+    /// Storage that is unavailable
+    struct Unavailable;
+
+    impl super::Witness for Unavailable {
+        fn ring_pedersen_commit(&self, _: &Aux, _: &Integer) -> Result<Integer, Error> {
+            Err(super::WitnessError::new("storage is offline").into())
+        }
+
+        fn linear_response(&self, _: &Integer, _: &Integer) -> Result<Integer, Error> {
+            Err(super::WitnessError::new("storage is offline").into())
+        }
+    }
+
+    #[test]
+    fn external_witness() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = p::SecurityParams::new(1024, 256, (Integer::ONE << 128_u32).complete());
+        let aux = crate::common::test::aux(&mut rng);
+        let statement = crate::common::test::EncStatement::random(&mut rng, security.l);
+        let data = statement.data();
+        let storage = Storage {
+            x: statement.plaintext.clone(),
+            operations: Cell::new(0),
+        };
+        let pdata = p::PrivateData {
+            plaintext: &storage,
+            nonce: &statement.nonce,
+        };
+
+        let shared_state = sha2::Sha256::default();
+        let (commitment, proof) =
+            p::non_interactive::prove(shared_state.clone(), &aux, data, pdata, &security, &mut rng)
+                .unwrap();
+        assert_eq!(storage.operations.get(), 2);
+        p::non_interactive::verify(
+            shared_state.clone(),
+            &aux,
+            data,
+            &commitment,
+            &security,
+            &proof,
+        )
+        .expect("proof failed");
+
+        // Failure of the storage is propagated
+        let pdata = p::PrivateData {
+            plaintext: &Unavailable,
+            nonce: &statement.nonce,
+        };
+        p::non_interactive::prove(shared_state, &aux, data, pdata, &security, &mut rng)
+            .expect_err("storage failure should be propagated");
+    }
+}
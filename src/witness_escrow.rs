@@ -0,0 +1,1006 @@
+//! ZK-proof of verifiable encryption of a witness to an auditor. Derived from
+//! Пlog* of the CGGMP21 paper.
+//!
+//! ## Description
+//!
+//! A party P has a number `X = x G`, with G being a generator of curve `E`. P has
+//! encrypted x as C0 under its own key, and escrowed x as C1 under the key of an
+//! auditor. P shares X, C0 and C1 with V and wants to prove that the logarithm of X
+//! is the plaintext of both C0 and C1, and that the plaintext (i.e. x) is at most l
+//! bits. Once the proof is verified, the auditor is able to recover x (e.g. a
+//! signing share) by decrypting C1.
+//!
+//! Given:
+//! - `key0`, `pkey0` - pair of public and private keys in paillier cryptosystem
+//! - `key1` - public key of the auditor
+//! - Curve `E`
+//! - `X = x G`, `C0 = key0.encrypt(x)` and `C1 = key1.encrypt(x)` - data to obtain
+//!   proof about
+//!
+//! Prove:
+//! - `decrypt0(C0) = decrypt1(C1) = log X`
+//! - `bitsize(x) <= l`
+//!
+//! Disclosing only: `key0`, `key1`, `C0`, `C1`, `X`
+//!
+//! `x = 0` is a valid witness: `X` is then the identity point, `C0` and `C1` encrypt
+//! zero.
+//!
+//! Both keys must be larger than `2^(l + epsilon)`, otherwise the plaintexts may be
+//! reduced differently modulo N0 and N1.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rug::{Integer, Complete};
+//! use generic_ec::{Point, curves::Secp256k1 as E};
+//! use paillier_zk::{witness_escrow as p, IntegerExt};
+//! # mod pregenerated {
+//! #     use super::*;
+//! #     paillier_zk::load_pregenerated_data!(
+//! #         verifier_aux: p::Aux,
+//! #         prover_decryption_key: fast_paillier::DecryptionKey,
+//! #         someone_encryption_key0: fast_paillier::EncryptionKey,
+//! #     );
+//! # }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Prover and verifier have a shared protocol state
+//! let shared_state_prover = sha2::Sha256::default();
+//! let shared_state_verifier = sha2::Sha256::default();
+//! let mut rng = rand_core::OsRng;
+//! # let mut rng = rand_dev::DevRng::new();
+//!
+//! // 0. Setup: prover and verifier share common Ring-Pedersen parameters:
+//!
+//! let aux: p::Aux = pregenerated::verifier_aux();
+//! let security = p::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+//!
+//! // 1. Setup: prover has its paillier key and knows the auditor's key
+//!
+//! let private_key: fast_paillier::DecryptionKey =
+//!     pregenerated::prover_decryption_key();
+//! let key0 = private_key.encryption_key();
+//! let key1 = pregenerated::someone_encryption_key0();
+//!
+//! // 2. Setup: prover has some plaintext `x`, encrypts it under both keys, and
+//! //    computes `X`
+//!
+//! let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+//! let (c0, nonce0) = key0.encrypt_with_random(&mut rng, &x)?;
+//! let (c1, nonce1) = key1.encrypt_with_random(&mut rng, &x)?;
+//! let X = Point::<E>::generator() * x.to_scalar();
+//!
+//! // 3. Prover computes a non-interactive proof that both ciphertexts encrypt `log X`:
+//!
+//! let data = p::Data {
+//!     key0,
+//!     c0: &c0,
+//!     key1: &key1,
+//!     c1: &c1,
+//!     x: &X,
+//!     b: &Point::<E>::generator().into(),
+//! };
+//! let pdata = p::PrivateData {
+//!     x: &x,
+//!     nonce0: &nonce0,
+//!     nonce1: &nonce1,
+//! };
+//! let (commitment, proof) =
+//!     p::non_interactive::prove(shared_state_prover, &aux, data, pdata, &security, &mut rng)?;
+//!
+//! // 4. Prover sends this data to verifier
+//!
+//! # fn send(_: &p::Data<E>, _: &p::Commitment<E>, _: &p::Proof) {  }
+//! send(&data, &commitment, &proof);
+//!
+//! // 5. Verifier receives the data and the proof and verifies it
+//!
+//! # let recv = || (data, commitment, proof);
+//! let (data, commitment, proof) = recv();
+//! p::non_interactive::verify(
+//!     shared_state_verifier,
+//!     &aux,
+//!     data,
+//!     &commitment,
+//!     &security,
+//!     &proof,
+//! )?;
+//! # Ok(()) }
+//! ```
+//!
+//! If the verification succeeded, verifier can continue communication with prover
+
+use fast_paillier::{AnyEncryptionKey, Ciphertext, Nonce};
+use generic_ec::{Curve, Point};
+use rug::Integer;
+
+use crate::common::encoding::{impl_decode, impl_encode, Schema};
+use crate::common::{InvalidProof, PreparedCiphertext};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::common::Aux;
+
+/// Security parameters for proof. Choosing the values is a tradeoff between
+/// speed and chance of rejecting a valid proof or accepting an invalid proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SecurityParams {
+    /// l in paper, bit size of +-plaintext
+    pub l: usize,
+    /// Epsilon in paper, slackness parameter
+    pub epsilon: usize,
+    /// q in paper. Security parameter for challenge
+    pub q: Integer,
+    /// Pre-agreed digest of [`Aux`] absorbed into challenges instead of `s`, `t`, `N^`
+    ///
+    /// Disabled by default, see [`SecurityParams::with_aux_digest`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_digest: Option<Vec<u8>>,
+    /// Return an error at prove time if [`Aux`] looks like toy parameters
+    ///
+    /// Weak aux is always rejected when debug assertions are enabled, this flag
+    /// makes it a hard error in release builds as well. See [`Aux::check_sanity`]
+    /// and [`SecurityParams::with_weak_aux_rejected`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reject_weak_aux: bool,
+}
+
+impl SecurityParams {
+    /// Constructs security parameters
+    pub fn new(l: usize, epsilon: usize, q: Integer) -> Self {
+        Self {
+            l,
+            epsilon,
+            q,
+            aux_digest: None,
+            reject_weak_aux: false,
+        }
+    }
+
+    /// Makes challenges absorb pre-agreed digest of [`Aux`] instead of `s`, `t`, `N^`
+    ///
+    /// Absorbing ring-pedersen parameters (kilobytes) into every challenge is redundant
+    /// when counterparties agree on them once, e.g. per epoch. `aux_digest` must be
+    /// obtained with [`Aux::digest`] from the same aux that is used to prove and verify.
+    /// Prover and verifier must both enable it, otherwise the proof doesn't verify.
+    pub fn with_aux_digest(self, aux_digest: impl Into<Vec<u8>>) -> Self {
+        Self {
+            aux_digest: Some(aux_digest.into()),
+            ..self
+        }
+    }
+
+    /// Makes proving fail if [`Aux`] looks like toy parameters, regardless of whether
+    /// debug assertions are enabled
+    pub fn with_weak_aux_rejected(self) -> Self {
+        Self {
+            reject_weak_aux: true,
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "insecure-small-keys")]
+impl SecurityParams {
+    /// **Insecure** parameters for 1024-bit Paillier keys and ring-pedersen modulus
+    ///
+    /// Meant exclusively for fast tests. Available under `insecure-small-keys` feature,
+    /// which fails to compile in release builds so it can't end up in production.
+    /// Enable the feature for dev-dependencies only.
+    pub fn insecure_small_keys() -> Self {
+        use rug::Complete;
+        Self::new(256, 512, (Integer::ONE << 128_u32).complete())
+    }
+}
+
+/// Public data that both parties know
+#[derive(Debug, Clone, Copy)]
+pub struct Data<'a, C: Curve> {
+    /// N0, public key of prover that C0 was encrypted on
+    pub key0: &'a dyn AnyEncryptionKey,
+    /// C0, logarithm of X encrypted on N0
+    pub c0: &'a Ciphertext,
+    /// N1, public key of auditor that C1 was encrypted on
+    pub key1: &'a dyn AnyEncryptionKey,
+    /// C1, logarithm of X encrypted on N1
+    pub c1: &'a Ciphertext,
+    /// A basepoint, generator in group
+    pub b: &'a Point<C>,
+    /// X, exponent of plaintext of C0 and C1
+    pub x: &'a Point<C>,
+}
+
+/// Private data of prover
+#[derive(Clone, Copy)]
+pub struct PrivateData<'a> {
+    /// x, logarithm of X and plaintext of C0 and C1
+    pub x: &'a Integer,
+    /// Nonce in encryption x -> C0
+    pub nonce0: &'a Nonce,
+    /// Nonce in encryption x -> C1
+    pub nonce1: &'a Nonce,
+}
+
+/// Statement validated and prepared for verification in advance
+///
+/// Statement often arrives a round before the proof. Preparing it upon arrival leaves
+/// only proof-dependent work to [`interactive::verify_prepared`] and
+/// [`non_interactive::verify_prepared`].
+#[derive(Debug, Clone)]
+pub struct PreparedStatement<'a, C: Curve> {
+    data: Data<'a, C>,
+    c0: PreparedCiphertext,
+    c1: PreparedCiphertext,
+}
+
+impl<'a, C: Curve> PreparedStatement<'a, C> {
+    /// Validates the statement and does all precomputation that doesn't depend on
+    /// the proof
+    pub fn new(data: Data<'a, C>) -> Result<Self, InvalidProof> {
+        let c0 = PreparedCiphertext::new(data.key0, data.c0)?;
+        let c1 = PreparedCiphertext::new(data.key1, data.c1)?;
+        crate::common::lap!(Check("statement preparation"));
+        Ok(Self { data, c0, c1 })
+    }
+
+    /// Statement that was prepared
+    pub fn data(&self) -> Data<'a, C> {
+        self.data
+    }
+}
+
+/// Prover's first message, obtained by [`interactive::commit`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Commitment<C: Curve> {
+    pub s: Integer,
+    pub a0: Ciphertext,
+    pub a1: Ciphertext,
+    pub y: Point<C>,
+    pub d: Integer,
+}
+
+/// Prover's data accompanying the commitment. Kept as state between rounds in
+/// the interactive protocol.
+#[derive(Clone)]
+pub struct PrivateCommitment {
+    pub alpha: Integer,
+    pub mu: Integer,
+    pub r0: Nonce,
+    pub r1: Nonce,
+    pub gamma: Integer,
+}
+
+crate::transcript::assert_private!(PrivateData<'static>, PrivateCommitment);
+
+/// Verifier's challenge to prover. Can be obtained deterministically by
+/// [`non_interactive::challenge`] or randomly by [`interactive::challenge`]
+pub type Challenge = Integer;
+
+/// The ZK proof. Computed by [`interactive::prove`] or
+/// [`non_interactive::prove`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Proof {
+    pub z1: Integer,
+    pub w0: Integer,
+    pub w1: Integer,
+    pub z3: Integer,
+}
+
+impl_encode! { Data<'a, C: Curve> { key0, c0, key1, c1, b, x } }
+impl_encode! { Commitment<C: Curve> { s, a0, a1, y, d } }
+impl_decode! { Commitment<C: Curve> { s, a0, a1, y, d } }
+impl_encode! { Proof { z1, w0, w1, z3 } }
+impl_decode! { Proof { z1, w0, w1, z3 } }
+
+impl<C: Curve> Commitment<C> {
+    /// Upper bound on size of [canonically encoded](crate::encoding) commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded commitment
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn schema(_security: &SecurityParams, modulus_bits: usize) -> Schema {
+        Schema::default()
+            .integer("s", modulus_bits)
+            .integer("a0", 2 * modulus_bits)
+            .integer("a1", 2 * modulus_bits)
+            .point::<C>("y")
+            .integer("d", modulus_bits)
+    }
+}
+
+impl Proof {
+    /// Upper bound on size of [canonically encoded](crate::encoding) proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn max_serialized_size(security: &SecurityParams, modulus_bits: usize) -> usize {
+        Self::schema(security, modulus_bits).max_size()
+    }
+
+    /// [Schema](crate::encoding::Schema) of canonically encoded proof produced by
+    /// honest prover
+    ///
+    /// `modulus_bits` is an upper bound on bit size of `N0`, `N1` and `N^`
+    pub fn schema(security: &SecurityParams, modulus_bits: usize) -> Schema {
+        let q = security.q.significant_bits() as usize;
+        let l = security.l;
+        let z1 = (l + security.epsilon).max(q + l) + 1;
+        let z3 = z1 + modulus_bits;
+        Schema::default()
+            .integer("z1", z1)
+            .integer("w0", modulus_bits)
+            .integer("w1", modulus_bits)
+            .integer("z3", z3)
+    }
+}
+
+/// The interactive version of the ZK proof. Should be completed in 3 rounds:
+/// prover commits to data, verifier responds with a random challenge, and
+/// prover gives proof with commitment and challenge.
+pub mod interactive {
+    use fast_paillier::{AnyEncryptionKey, Ciphertext};
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+    use rug::{Complete, Integer};
+
+    use crate::common::{
+        fail_if, fail_if_challenge_out_of_range, fail_if_degenerate, fail_if_ne,
+        fail_if_zero_challenge, IntegerExt, InvalidProofReason,
+    };
+    use crate::{Error, InvalidProof};
+
+    use crate::common::PreparedCiphertext;
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateCommitment, PrivateData, Proof,
+        SecurityParams,
+    };
+
+    /// Create random commitment
+    pub fn commit<C: Curve, R: RngCore>(
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        mut rng: R,
+    ) -> Result<(Commitment<C>, PrivateCommitment), Error> {
+        crate::common::check_aux(aux, security.reject_weak_aux)?;
+        let two_to_l_e = (Integer::ONE << (security.l + security.epsilon)).complete();
+        let hat_n_at_two_to_l = &aux.rsa_modulo * (Integer::ONE << security.l).complete();
+        let hat_n_at_two_to_l_e = (&aux.rsa_modulo * &two_to_l_e).complete();
+
+        let alpha = Integer::from_rng_pm(&two_to_l_e, &mut rng);
+        let mu = Integer::from_rng_pm(&hat_n_at_two_to_l, &mut rng);
+        let r0 = Integer::gen_invertible(data.key0.n(), &mut rng);
+        let r1 = Integer::gen_invertible(data.key1.n(), &mut rng);
+        let gamma = Integer::from_rng_pm(&hat_n_at_two_to_l_e, &mut rng);
+
+        let commitment = Commitment {
+            s: aux.combine(pdata.x, &mu)?,
+            a0: data.key0.encrypt_with(&alpha, &r0)?,
+            a1: data.key1.encrypt_with(&alpha, &r1)?,
+            y: data.b * alpha.to_scalar(),
+            d: aux.combine(&alpha, &gamma)?,
+        };
+        let private_commitment = PrivateCommitment {
+            alpha,
+            mu,
+            r0,
+            r1,
+            gamma,
+        };
+        Ok((commitment, private_commitment))
+    }
+
+    /// Compute proof for given data and prior protocol values
+    pub fn prove<C: Curve>(
+        data: Data<C>,
+        pdata: PrivateData,
+        pcomm: &PrivateCommitment,
+        challenge: &Challenge,
+    ) -> Result<Proof, Error> {
+        Ok(Proof {
+            z1: (&pcomm.alpha + challenge * pdata.x).complete(),
+            w0: data
+                .key0
+                .n()
+                .combine(&pcomm.r0, Integer::ONE, pdata.nonce0, challenge)?,
+            w1: data
+                .key1
+                .n()
+                .combine(&pcomm.r1, Integer::ONE, pdata.nonce1, challenge)?,
+            z3: (&pcomm.gamma + challenge * &pcomm.mu).complete(),
+        })
+    }
+
+    /// Verify the proof
+    pub fn verify<C: Curve>(
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let statement = PreparedStatement::new(data).map_err(|err| err.with_key(data.key0.n()))?;
+        verify_prepared(aux, &statement, commitment, security, challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance
+    pub fn verify_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        check_prepared(aux, statement, commitment, security, challenge, proof)
+            .map_err(|err| err.with_key(statement.data.key0.n()))
+    }
+
+    fn check_prepared<C: Curve>(
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        challenge: &Challenge,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof> {
+        let data = statement.data;
+        fail_if_zero_challenge(challenge)?;
+        fail_if_challenge_out_of_range(challenge, &security.q)?;
+        fail_if_degenerate(&aux.rsa_modulo, [&commitment.s, &commitment.d])?;
+        fail_if_degenerate(data.key0.nn(), [&commitment.a0])?;
+        fail_if_degenerate(data.key1.nn(), [&commitment.a1])?;
+        crate::common::fail_if_not_coprime(data.key0.n(), [&proof.w0])?;
+        crate::common::fail_if_not_coprime(data.key1.n(), [&proof.w1])?;
+        fail_if(
+            InvalidProofReason::DegenerateCommitment,
+            !commitment.y.is_zero(),
+        )?;
+        check_encryption(
+            InvalidProofReason::EqualityCheck(1),
+            data.key0,
+            &statement.c0,
+            &commitment.a0,
+            challenge,
+            &proof.z1,
+            &proof.w0,
+        )?;
+        crate::common::lap!(Check("paillier encryption under N0"));
+        check_encryption(
+            InvalidProofReason::EqualityCheck(2),
+            data.key1,
+            &statement.c1,
+            &commitment.a1,
+            challenge,
+            &proof.z1,
+            &proof.w1,
+        )?;
+        crate::common::lap!(Check("paillier encryption under N1"));
+        {
+            let lhs = data.b * proof.z1.to_scalar();
+            let rhs = commitment.y + data.x * challenge.to_scalar();
+            fail_if_ne(InvalidProofReason::EqualityCheck(3), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("group element"));
+        {
+            let lhs = aux.combine(&proof.z1, &proof.z3)?;
+            let s_to_e = aux.pow_mod(&commitment.s, challenge)?;
+            let rhs = (&commitment.d * s_to_e).modulo(&aux.rsa_modulo);
+            fail_if_ne(InvalidProofReason::EqualityCheck(4), lhs, rhs)?;
+        }
+        crate::common::lap!(Check("ring-pedersen"));
+        fail_if(
+            InvalidProofReason::RangeCheck(5),
+            proof
+                .z1
+                .is_in_pm(&(Integer::ONE << (security.l + security.epsilon)).complete()),
+        )?;
+        crate::common::lap!(Check("range of z1"));
+
+        Ok(())
+    }
+
+    /// Checks that `key.encrypt(z; w) = a * c^e`
+    fn check_encryption(
+        reason: InvalidProofReason,
+        key: &dyn AnyEncryptionKey,
+        c: &PreparedCiphertext,
+        a: &Ciphertext,
+        challenge: &Challenge,
+        z: &Integer,
+        w: &Integer,
+    ) -> Result<(), InvalidProof> {
+        let lhs = key
+            .encrypt_with(z, w)
+            .map_err(|_| InvalidProofReason::PaillierEnc)?;
+        let rhs = {
+            let e_at_c = c.omul(key, challenge)?;
+            key.oadd(a, &e_at_c)
+                .map_err(|_| InvalidProofReason::PaillierOp)?
+        };
+        fail_if_ne(reason, lhs, rhs)?;
+        Ok(())
+    }
+
+    /// Generate random challenge
+    pub fn challenge<R>(security: &SecurityParams, rng: &mut R) -> Integer
+    where
+        R: RngCore,
+    {
+        Integer::from_rng_pm(&security.q, rng)
+    }
+}
+
+/// The non-interactive version of proof. Completed in one round, for example
+/// see the documentation of parent module.
+pub mod non_interactive {
+    use digest::Digest;
+    use generic_ec::Curve;
+    use rand_core::RngCore;
+
+    use crate::transcript::Transcript;
+    use crate::{Error, InvalidProof};
+
+    use super::{
+        Aux, Challenge, Commitment, Data, PreparedStatement, PrivateData, Proof, SecurityParams,
+    };
+
+    /// Compute proof for the given data, producing random commitment and
+    /// deriving determenistic challenge.
+    ///
+    /// Obtained from the above interactive proof via Fiat-Shamir heuristic.
+    pub fn prove<C: Curve, R: RngCore, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof), Error>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        prove_with_transcript(&mut transcript, aux, data, pdata, security, rng)
+    }
+
+    /// Compute proof for the given data within the protocol transcript
+    ///
+    /// Statement and commitment are absorbed into the `transcript`, so subsequent
+    /// challenges derived from it are bound to the proof.
+    pub fn prove_with_transcript<C: Curve, R: RngCore, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        pdata: PrivateData,
+        security: &SecurityParams,
+        rng: &mut R,
+    ) -> Result<(Commitment<C>, Proof), Error>
+    where
+        D: Digest,
+    {
+        let (comm, pcomm) = super::interactive::commit(aux, data, pdata, security, rng)?;
+        crate::common::lap!(Commit);
+        let challenge = challenge_with_transcript(transcript, aux, data, &comm, security);
+        crate::common::lap!(Challenge);
+        let proof = super::interactive::prove(data, pdata, &pcomm, &challenge)?;
+        crate::common::lap!(Respond);
+        Ok((comm, proof))
+    }
+
+    /// Verify the proof, deriving challenge independently from same data
+    pub fn verify<C: Curve, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        verify_with_transcript(&mut transcript, aux, data, commitment, security, proof)
+    }
+
+    /// Verify the proof within the protocol transcript, deriving challenge
+    /// independently from same data
+    pub fn verify_with_transcript<C: Curve, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge_with_transcript(transcript, aux, data, commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify(aux, data, commitment, security, &challenge, proof)
+    }
+
+    /// Verify the proof for the statement prepared in advance, deriving challenge
+    /// independently from same data
+    pub fn verify_prepared<C: Curve, D>(
+        shared_state: D,
+        aux: &Aux,
+        statement: &PreparedStatement<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+        proof: &Proof,
+    ) -> Result<(), InvalidProof>
+    where
+        D: Digest,
+    {
+        let challenge = challenge(shared_state, aux, statement.data(), commitment, security);
+        crate::common::lap!(Challenge);
+        super::interactive::verify_prepared(aux, statement, commitment, security, &challenge, proof)
+    }
+
+    /// Internal function for deriving challenge from protocol values
+    /// deterministically
+    pub fn challenge<C: Curve, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        let mut transcript = Transcript::from_shared_state(shared_state);
+        challenge_with_transcript(&mut transcript, aux, data, commitment, security)
+    }
+
+    /// Bytes hashed to derive the challenge, see
+    /// [auditing](crate::transcript#auditing)
+    ///
+    /// Lets other implementations reproduce [`challenge`] bit-for-bit
+    pub fn challenge_transcript_bytes<C: Curve, D>(
+        shared_state: D,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Vec<u8>
+    where
+        D: Digest,
+    {
+        crate::transcript::challenge_transcript_bytes(shared_state, |transcript| {
+            challenge_with_transcript(transcript, aux, data, commitment, security);
+        })
+    }
+
+    /// Deterministically compute challenge from the protocol transcript
+    ///
+    /// Absorbs statement and commitment into the `transcript`
+    pub fn challenge_with_transcript<C: Curve, D>(
+        transcript: &mut Transcript<D>,
+        aux: &Aux,
+        data: Data<C>,
+        commitment: &Commitment<C>,
+        security: &SecurityParams,
+    ) -> Challenge
+    where
+        D: Digest,
+    {
+        transcript.label(b"paillier_zk.witness_escrow");
+        transcript.absorb(b"curve", C::CURVE_NAME.as_bytes());
+        transcript.absorb_aux(aux, security.aux_digest.as_deref());
+        transcript.absorb(b"l", &(security.l as u64).to_be_bytes());
+        transcript.absorb(b"epsilon", &(security.epsilon as u64).to_be_bytes());
+        transcript.absorb_value(b"N0", data.key0.n());
+        transcript.absorb_value(b"C0", data.c0);
+        transcript.absorb_value(b"N1", data.key1.n());
+        transcript.absorb_value(b"C1", data.c1);
+        transcript.absorb_point(b"X", data.x);
+        transcript.absorb_point(b"g", data.b);
+        transcript.absorb_value(b"commitment", commitment);
+        let mut rng = transcript.challenge_rng(b"e");
+        super::interactive::challenge(security, &mut rng)
+    }
+}
+
+/// Special-soundness extractor
+#[cfg(feature = "research")]
+pub mod extract {
+    use fast_paillier::Nonce;
+    use generic_ec::Curve;
+    use rug::Integer;
+
+    use crate::common::extract::{challenge_diff, ensure, linear_response, paillier_nonce};
+    use crate::common::IntegerExt;
+    use crate::ExtractionError;
+
+    use super::{Challenge, Data, Proof};
+
+    /// Witness obtained by [`extract`]
+    #[derive(Debug, Clone)]
+    pub struct Witness {
+        pub x: Integer,
+        pub nonce0: Nonce,
+        pub nonce1: Nonce,
+    }
+
+    /// Extracts the witness from two accepting transcripts that share the same
+    /// commitment but have different challenges
+    pub fn extract<C: Curve>(
+        data: Data<C>,
+        challenge1: &Challenge,
+        proof1: &Proof,
+        challenge2: &Challenge,
+        proof2: &Proof,
+    ) -> Result<Witness, ExtractionError> {
+        let e_diff = challenge_diff(challenge1, challenge2)?;
+        let x = linear_response(&proof1.z1, &proof2.z1, &e_diff)?;
+        let nonce0 = paillier_nonce(data.key0.n(), data.c0, &proof1.w0, &proof2.w0, &e_diff)?;
+        let nonce1 = paillier_nonce(data.key1.n(), data.c1, &proof1.w1, &proof2.w1, &e_diff)?;
+
+        let c0 = data.key0.encrypt_with(&x, &nonce0).ok();
+        ensure(c0.as_ref() == Some(data.c0))?;
+        let c1 = data.key1.encrypt_with(&x, &nonce1).ok();
+        ensure(c1.as_ref() == Some(data.c1))?;
+        ensure(data.b * x.to_scalar() == *data.x)?;
+        Ok(Witness { x, nonce0, nonce1 })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generic_ec::{Curve, Point, Scalar};
+    use rug::{Complete, Integer};
+
+    use crate::common::test::random_key;
+    use crate::common::{IntegerExt, InvalidProofReason};
+
+    fn run<R: rand_core::RngCore + rand_core::CryptoRng, C: Curve>(
+        mut rng: R,
+        security: super::SecurityParams,
+        plaintext: Integer,
+        escrowed_plaintext: Integer,
+    ) -> Result<(), crate::common::InvalidProof> {
+        let private_key0 = random_key(&mut rng).unwrap();
+        let key0 = private_key0.encryption_key().clone();
+        let private_key1 = random_key(&mut rng).unwrap();
+        let key1 = private_key1.encryption_key().clone();
+
+        let (c0, nonce0) = key0.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let (c1, nonce1) = key1
+            .encrypt_with_random(&mut rng, &escrowed_plaintext)
+            .unwrap();
+        let b = Point::<C>::generator() * Scalar::random(&mut rng);
+        let x = b * plaintext.to_scalar();
+
+        let data = super::Data {
+            key0: &key0,
+            c0: &c0,
+            key1: &key1,
+            c1: &c1,
+            x: &x,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            x: &plaintext,
+            nonce0: &nonce0,
+            nonce1: &nonce1,
+        };
+
+        let aux = crate::common::test::aux(&mut rng);
+
+        let shared_state = sha2::Sha256::default();
+
+        let (commitment, proof) = super::non_interactive::prove(
+            shared_state.clone(),
+            &aux,
+            data,
+            pdata,
+            &security,
+            &mut rng,
+        )
+        .unwrap();
+
+        super::non_interactive::verify(shared_state, &aux, data, &commitment, &security, &proof)
+    }
+
+    fn security() -> super::SecurityParams {
+        super::SecurityParams {
+            l: 1024,
+            epsilon: 300,
+            q: (Integer::ONE << 128_u32).complete(),
+            aux_digest: None,
+            reject_weak_aux: false,
+        }
+    }
+
+    fn passing_test<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        run::<_, C>(rng, security, plaintext.clone(), plaintext).expect("proof failed");
+    }
+
+    fn zero_witness<C: Curve>() {
+        let rng = rand_dev::DevRng::new();
+        // X is the identity point, both ciphertexts encrypt zero
+        run::<_, C>(rng, security(), Integer::ZERO, Integer::ZERO).expect("proof failed");
+    }
+
+    fn failing_range<C: Curve>() {
+        let rng = rand_dev::DevRng::new();
+        let security = security();
+        let plaintext = (Integer::ONE << (security.l + security.epsilon + 1)).complete();
+        let r = run::<_, C>(rng, security, plaintext.clone(), plaintext)
+            .expect_err("proof should not pass");
+        match r.reason() {
+            InvalidProofReason::RangeCheck(_) => (),
+            e => panic!("proof should not fail with: {e:?}"),
+        }
+    }
+
+    fn failing_escrow<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let escrowed_plaintext = (&plaintext + 1u8).complete();
+        let r = run::<_, C>(rng, security, plaintext, escrowed_plaintext)
+            .expect_err("proof should not pass");
+        match r.reason() {
+            InvalidProofReason::EqualityCheck(2) => (),
+            e => panic!("proof should not fail with: {e:?}"),
+        }
+    }
+
+    fn degenerate_transcript<C: Curve>() {
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let key0 = random_key(&mut rng).unwrap().encryption_key().clone();
+        let key1 = random_key(&mut rng).unwrap().encryption_key().clone();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let (c0, nonce0) = key0.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let (c1, nonce1) = key1.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let b = Point::<C>::generator() * Scalar::random(&mut rng);
+        let x = b * plaintext.to_scalar();
+        let data = super::Data {
+            key0: &key0,
+            c0: &c0,
+            key1: &key1,
+            c1: &c1,
+            x: &x,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            x: &plaintext,
+            nonce0: &nonce0,
+            nonce1: &nonce1,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        // Zero challenge makes the checks vacuous
+        let challenge = Integer::new();
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::ZeroChallenge)
+        );
+
+        // Commitment equal to one is rejected
+        let challenge = super::interactive::challenge(&security, &mut rng);
+        let proof = super::interactive::prove(data, pdata, &pcommitment, &challenge).unwrap();
+        let mut degenerate = commitment.clone();
+        degenerate.a1 = Integer::from(1);
+        let r = super::interactive::verify(&aux, data, &degenerate, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+
+        // Identity point in commitment is rejected
+        let mut commitment = commitment;
+        commitment.y = Point::zero();
+        let r = super::interactive::verify(&aux, data, &commitment, &security, &challenge, &proof);
+        assert_eq!(
+            r.map_err(|e| e.reason()).err(),
+            Some(InvalidProofReason::DegenerateCommitment)
+        );
+    }
+
+    #[test]
+    fn passing_p256() {
+        passing_test::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn zero_witness_p256() {
+        zero_witness::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn failing_range_p256() {
+        failing_range::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn failing_escrow_p256() {
+        failing_escrow::<generic_ec::curves::Secp256r1>()
+    }
+    #[test]
+    fn degenerate_transcript_p256() {
+        degenerate_transcript::<generic_ec::curves::Secp256r1>()
+    }
+
+    #[test]
+    fn passing_million() {
+        passing_test::<crate::curve::C>()
+    }
+    #[test]
+    fn failing_range_million() {
+        failing_range::<crate::curve::C>()
+    }
+    #[test]
+    fn failing_escrow_million() {
+        failing_escrow::<crate::curve::C>()
+    }
+    #[test]
+    fn degenerate_transcript_million() {
+        degenerate_transcript::<crate::curve::C>()
+    }
+
+    #[cfg(feature = "research")]
+    #[test]
+    fn extraction() {
+        type C = generic_ec::curves::Secp256r1;
+        let mut rng = rand_dev::DevRng::new();
+        let security = security();
+        let key0 = random_key(&mut rng).unwrap().encryption_key().clone();
+        let key1 = random_key(&mut rng).unwrap().encryption_key().clone();
+        let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+        let (c0, nonce0) = key0.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let (c1, nonce1) = key1.encrypt_with_random(&mut rng, &plaintext).unwrap();
+        let b = Point::<C>::generator() * Scalar::random(&mut rng);
+        let x = b * plaintext.to_scalar();
+        let data = super::Data {
+            key0: &key0,
+            c0: &c0,
+            key1: &key1,
+            c1: &c1,
+            x: &x,
+            b: &b,
+        };
+        let pdata = super::PrivateData {
+            x: &plaintext,
+            nonce0: &nonce0,
+            nonce1: &nonce1,
+        };
+        let aux = crate::common::test::aux(&mut rng);
+
+        let (_commitment, pcommitment) =
+            super::interactive::commit(&aux, data, pdata, &security, &mut rng).unwrap();
+        let e1 = super::interactive::challenge(&security, &mut rng);
+        let e2 = super::interactive::challenge(&security, &mut rng);
+        let proof1 = super::interactive::prove(data, pdata, &pcommitment, &e1).unwrap();
+        let proof2 = super::interactive::prove(data, pdata, &pcommitment, &e2).unwrap();
+
+        let witness = super::extract::extract(data, &e1, &proof1, &e2, &proof2).unwrap();
+        assert_eq!(witness.x, plaintext);
+        assert_eq!(witness.nonce0, nonce0);
+        assert_eq!(witness.nonce1, nonce1);
+    }
+}
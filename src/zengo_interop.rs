@@ -0,0 +1,209 @@
+//! Decoders for proofs produced by ZenGo's implementation of CGGMP21
+//!
+//! During migration from ZenGo's stack ([`cggmp-threshold-ecdsa`]), legacy proofs
+//! serialized by it need to be verified with this crate. This module provides serde
+//! mirrors of their proof structs and conversions into this crate's types.
+//!
+//! Integers are expected in `curv` serialization format, i.e. hex strings with an
+//! optional leading `-`.
+//!
+//! [`cggmp-threshold-ecdsa`]: https://github.com/ZenGo-X/cggmp-threshold-ecdsa
+//!
+//! ## Supported proofs
+//!
+//! * Пenc (`PaillierEncryptionInRangeProof`) maps onto [`paillier_encryption_in_range`].
+//!   Statement (`N0`, `K`, ring-pedersen parameters) coincides.
+//! * Пmod (`PaillierBlumModulusProof`) maps onto [`paillier_blum_modulus`]. Statement
+//!   coincides.
+//!
+//! ## Gaps
+//!
+//! * Fiat-Shamir challenges are derived differently. ZenGo hashes the statement with
+//!   `curv`'s `Digest::chain_bigint` which is not reproduced here, so the decoded proofs
+//!   can only be checked with `interactive::verify` given the challenge that was
+//!   recomputed by the caller according to ZenGo's derivation rules. Using
+//!   `non_interactive::verify` on decoded proofs will fail.
+//! * GG18/GG20 proofs (`NiCorrectKeyProof`, `RangeProofNi`, `PDLwSlackProof`) prove
+//!   different statements (e.g. `gcd(N, phi(N)) = 1` instead of `N` being a
+//!   Paillier-Blum modulus, or Boudot-style range proofs) and have no counterpart in this
+//!   crate. They cannot be decoded.
+
+use rug::Integer;
+use serde::Deserialize;
+
+use crate::{paillier_blum_modulus, paillier_encryption_in_range};
+
+/// Пenc commitment as serialized by ZenGo
+#[derive(Debug, Clone, Deserialize)]
+#[allow(non_snake_case)]
+pub struct EncCommitment {
+    #[serde(deserialize_with = "hex_integer::deserialize")]
+    pub S: Integer,
+    #[serde(deserialize_with = "hex_integer::deserialize")]
+    pub A: Integer,
+    #[serde(deserialize_with = "hex_integer::deserialize")]
+    pub C: Integer,
+}
+
+/// Пenc proof as serialized by ZenGo
+#[derive(Debug, Clone, Deserialize)]
+pub struct EncProof {
+    #[serde(deserialize_with = "hex_integer::deserialize")]
+    pub z_1: Integer,
+    #[serde(deserialize_with = "hex_integer::deserialize")]
+    pub z_2: Integer,
+    #[serde(deserialize_with = "hex_integer::deserialize")]
+    pub z_3: Integer,
+    pub commitment: EncCommitment,
+}
+
+impl From<EncProof>
+    for (
+        paillier_encryption_in_range::Commitment,
+        paillier_encryption_in_range::Proof,
+    )
+{
+    fn from(proof: EncProof) -> Self {
+        let commitment = paillier_encryption_in_range::Commitment {
+            s: proof.commitment.S,
+            a: proof.commitment.A,
+            c: proof.commitment.C,
+        };
+        let proof = paillier_encryption_in_range::Proof {
+            z1: proof.z_1,
+            z2: proof.z_2,
+            z3: proof.z_3,
+        };
+        (commitment, proof)
+    }
+}
+
+/// Пmod proof as serialized by ZenGo
+#[derive(Debug, Clone, Deserialize)]
+#[allow(non_snake_case)]
+pub struct ModProof {
+    #[serde(deserialize_with = "hex_integer::deserialize")]
+    pub N: Integer,
+    #[serde(deserialize_with = "hex_integer::deserialize")]
+    pub w: Integer,
+    #[serde(deserialize_with = "hex_integer::deserialize_vec")]
+    pub x: Vec<Integer>,
+    pub a: Vec<bool>,
+    pub b: Vec<bool>,
+    #[serde(deserialize_with = "hex_integer::deserialize_vec")]
+    pub z: Vec<Integer>,
+}
+
+impl ModProof {
+    /// Converts the proof into statement, commitment and proof of
+    /// [`paillier_blum_modulus`]
+    ///
+    /// Returns error if proof doesn't consist of exactly `M` points
+    pub fn decode<const M: usize>(
+        self,
+    ) -> Result<
+        (
+            paillier_blum_modulus::Data,
+            paillier_blum_modulus::Commitment,
+            paillier_blum_modulus::Proof<M>,
+        ),
+        DecodeError,
+    > {
+        let lengths = [self.x.len(), self.a.len(), self.b.len(), self.z.len()];
+        if let Some(&actual) = lengths.iter().find(|&&len| len != M) {
+            return Err(DecodeError::Length {
+                expected: M,
+                actual,
+            });
+        }
+
+        let points = self
+            .x
+            .into_iter()
+            .zip(self.a)
+            .zip(self.b)
+            .zip(self.z)
+            .map(|(((x, a), b), z)| paillier_blum_modulus::ProofPoint { x, a, b, z })
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|points: Vec<_>| DecodeError::Length {
+                expected: M,
+                actual: points.len(),
+            })?;
+
+        Ok((
+            paillier_blum_modulus::Data { n: self.N },
+            paillier_blum_modulus::Commitment { w: self.w },
+            paillier_blum_modulus::Proof { points },
+        ))
+    }
+}
+
+/// Error indicating that legacy proof cannot be mapped into this crate's types
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// Proof consists of unexpected amount of elements
+    #[error("unexpected amount of elements: expected {expected}, actual {actual}")]
+    Length { expected: usize, actual: usize },
+}
+
+/// Deserializes integers in `curv` format
+mod hex_integer {
+    use rug::Integer;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Integer, D::Error> {
+        let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        parse(&s)
+    }
+
+    pub fn deserialize_vec<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Integer>, D::Error> {
+        let hexes = <Vec<std::borrow::Cow<str>>>::deserialize(deserializer)?;
+        hexes.iter().map(|s| parse(s)).collect()
+    }
+
+    fn parse<E: serde::de::Error>(s: &str) -> Result<Integer, E> {
+        Integer::from_str_radix(s, 16).map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::Integer;
+
+    #[test]
+    fn decode_enc() {
+        let json = r#"{
+            "z_1": "-1f",
+            "z_2": "aa",
+            "z_3": "10",
+            "commitment": { "S": "1", "A": "2", "C": "ff" }
+        }"#;
+        let proof: super::EncProof = serde_json::from_str(json).unwrap();
+        let (commitment, proof): (
+            crate::paillier_encryption_in_range::Commitment,
+            crate::paillier_encryption_in_range::Proof,
+        ) = proof.into();
+        assert_eq!(commitment.c, 255);
+        assert_eq!(proof.z1, Integer::from(-31));
+        assert_eq!(proof.z2, 170);
+    }
+
+    #[test]
+    fn decode_mod() {
+        let json = r#"{
+            "N": "d", "w": "2",
+            "x": ["1", "3"], "a": [true, false], "b": [false, false], "z": ["5", "6"]
+        }"#;
+        let proof: super::ModProof = serde_json::from_str(json).unwrap();
+        let (data, _commitment, proof) = proof.decode::<2>().unwrap();
+        assert_eq!(data.n, 13);
+        assert!(proof.points[0].a);
+
+        let proof: super::ModProof = serde_json::from_str(json).unwrap();
+        assert!(proof.decode::<3>().is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Stress tests of proving and verifying from many threads at once with shared `Aux`,
+//! precomputed tables and prepared statements
+//!
+//! With `entropy-check` feature enabled, provers additionally share the detector of
+//! repeated randomness, which must not report fresh randomness of concurrent
+//! proofs.
+
+use std::sync::Arc;
+
+use fast_paillier::DecryptionKey;
+use paillier_zk::{
+    multiexp::MultiexpTable,
+    paillier_encryption_in_range as enc, paillier_encryption_of_zero as enc_zero,
+    rug::{Complete, Integer},
+    utils::ring_pedersen::Aux,
+    IntegerExt,
+};
+use sha2::Sha256;
+
+const THREADS: usize = 8;
+/// Number of proofs computed by each thread
+const PROOFS_PER_THREAD: usize = 16;
+/// Number of times each thread verifies the same proof
+const VERIFICATIONS_PER_THREAD: usize = 256;
+
+/// Every type shared between threads in these tests must be `Send + Sync`
+#[allow(dead_code)]
+fn assert_shareable() {
+    fn shareable<T: Send + Sync>() {}
+    shareable::<Aux>();
+    shareable::<MultiexpTable>();
+    shareable::<fast_paillier::utils::CrtExp>();
+    shareable::<fast_paillier::EncryptionKey>();
+    shareable::<enc::SecurityParams>();
+}
+
+fn setup(rng: &mut impl rand_core::RngCore) -> (Aux, DecryptionKey) {
+    let p = generate_blum_prime(rng, 1024);
+    let q = generate_blum_prime(rng, 1024);
+    let n = (&p * &q).complete();
+    let phi_n = (p.clone() - 1u8) * (q.clone() - 1u8);
+    let r = Integer::gen_invertible(&n, rng);
+    let lambda = phi_n.random_below(&mut fast_paillier::utils::external_rand(rng));
+    let t = r.square().modulo(&n);
+    let s = t.pow_mod_ref(&lambda, &n).unwrap().into();
+    let aux = Aux::new(s, t, n);
+
+    let table = MultiexpTable::build(&aux.s, &aux.t, 4096, 4096, aux.rsa_modulo.clone())
+        .expect("build multiexp table");
+    let crt = fast_paillier::utils::CrtExp::build_n(&p, &q).expect("build crt");
+    let aux = aux.with_multiexp(Arc::new(table)).with_crt(crt);
+
+    let key = DecryptionKey::from_primes(
+        generate_blum_prime(rng, 1024),
+        generate_blum_prime(rng, 1024),
+    )
+    .unwrap();
+    (aux, key)
+}
+
+fn generate_blum_prime(rng: &mut impl rand_core::RngCore, bits_size: u32) -> Integer {
+    loop {
+        let mut n: Integer =
+            Integer::random_bits(bits_size, &mut fast_paillier::utils::external_rand(rng)).into();
+        n.set_bit(bits_size - 1, true);
+        n.next_prime_mut();
+        if n.mod_u(4) == 3 {
+            break n;
+        }
+    }
+}
+
+#[test]
+fn concurrent_proving_with_shared_aux() {
+    let mut rng = rand_dev::DevRng::new();
+    let (aux, key) = setup(&mut rng);
+    let key = key.encryption_key();
+    let security = enc::SecurityParams::new(1024, 300, (Integer::ONE << 128_u32).complete());
+
+    std::thread::scope(|s| {
+        let threads = (0..THREADS)
+            .map(|_| {
+                let mut rng = rand_dev::DevRng::new();
+                let (aux, security) = (&aux, &security);
+                s.spawn(move || {
+                    for _ in 0..PROOFS_PER_THREAD {
+                        let plaintext = Integer::from_rng_pm(
+                            &(Integer::ONE << security.l).complete(),
+                            &mut rng,
+                        );
+                        let (ciphertext, nonce) =
+                            key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+                        let data = enc::Data {
+                            key,
+                            ciphertext: &ciphertext,
+                        };
+                        let pdata = enc::PrivateData {
+                            plaintext: &plaintext,
+                            nonce: &nonce,
+                        };
+                        let (commitment, proof) = enc::non_interactive::prove(
+                            Sha256::default(),
+                            aux,
+                            data,
+                            pdata,
+                            security,
+                            &mut rng,
+                        )
+                        .expect("proving failed");
+                        enc::non_interactive::verify(
+                            Sha256::default(),
+                            aux,
+                            data,
+                            &commitment,
+                            security,
+                            &proof,
+                        )
+                        .expect("proof failed");
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().expect("thread panicked");
+        }
+    });
+}
+
+#[test]
+fn concurrent_verification_of_prepared_statement() {
+    let mut rng = rand_dev::DevRng::new();
+    let (_, key) = setup(&mut rng);
+    let key = key.encryption_key();
+    let security = enc_zero::SecurityParams::new((Integer::ONE << 128_u32).complete());
+
+    let (c, nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+    let data = enc_zero::Data { key, c: &c };
+    let pdata = enc_zero::PrivateData { nonce: &nonce };
+    let (commitment, proof) =
+        enc_zero::non_interactive::prove(Sha256::default(), data, pdata, &security, &mut rng)
+            .unwrap();
+
+    // Proof is also verified for another statement, which must fail in every thread
+    let (other_c, _) = key.encrypt_with_random(&mut rng, Integer::ONE).unwrap();
+
+    std::thread::scope(|s| {
+        let threads = (0..THREADS)
+            .map(|_| {
+                s.spawn(|| {
+                    // Statements borrow the key as `dyn AnyEncryptionKey`, which isn't
+                    // `Sync`, so each thread prepares its own
+                    let statement =
+                        enc_zero::PreparedStatement::new(enc_zero::Data { key, c: &c }).unwrap();
+                    let other_statement =
+                        enc_zero::PreparedStatement::new(enc_zero::Data { key, c: &other_c })
+                            .unwrap();
+                    for _ in 0..VERIFICATIONS_PER_THREAD {
+                        enc_zero::non_interactive::verify_prepared(
+                            Sha256::default(),
+                            &statement,
+                            &commitment,
+                            &security,
+                            &proof,
+                        )
+                        .expect("proof failed");
+                        enc_zero::non_interactive::verify_prepared(
+                            Sha256::default(),
+                            &other_statement,
+                            &commitment,
+                            &security,
+                            &proof,
+                        )
+                        .expect_err("proof of other statement should not pass");
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().expect("thread panicked");
+        }
+    });
+}
@@ -0,0 +1,263 @@
+//! Every proof instantiated with 1024-bit moduli and `SecurityParams::insecure_small_keys`
+#![cfg(feature = "insecure-small-keys")]
+
+use fast_paillier::DecryptionKey;
+use generic_ec::{curves::Secp256r1 as C, Point};
+use paillier_zk::{
+    group_element_vs_paillier_encryption_in_range as log_star, no_small_factor,
+    paillier_affine_operation_in_range as aff_g, paillier_blum_modulus,
+    paillier_encryption_in_range as enc,
+    rug::{Complete, Integer},
+    utils::ring_pedersen::Aux,
+    witness_escrow, IntegerExt,
+};
+use sha2::Sha256;
+
+const PRIME_BITS: u32 = 512;
+
+fn aux(rng: &mut impl rand_core::RngCore) -> Aux {
+    // `N^` smaller than 1024 bits is rejected by `Aux::check_sanity`
+    let (p, q, n) = loop {
+        let p = generate_blum_prime(rng, PRIME_BITS);
+        let q = generate_blum_prime(rng, PRIME_BITS);
+        let n = (&p * &q).complete();
+        if n.significant_bits() == 2 * PRIME_BITS {
+            break (p, q, n);
+        }
+    };
+    let phi_n = (p - 1u8) * (q - 1u8);
+    let r = Integer::gen_invertible(&n, rng);
+    let lambda = phi_n.random_below(&mut fast_paillier::utils::external_rand(rng));
+    let t = r.square().modulo(&n);
+    let s = t.pow_mod_ref(&lambda, &n).unwrap().into();
+    Aux::new(s, t, n)
+}
+
+fn random_key(rng: &mut impl rand_core::RngCore) -> DecryptionKey {
+    let p = generate_blum_prime(rng, PRIME_BITS);
+    let q = generate_blum_prime(rng, PRIME_BITS);
+    DecryptionKey::from_primes(p, q).unwrap()
+}
+
+fn generate_blum_prime(rng: &mut impl rand_core::RngCore, bits_size: u32) -> Integer {
+    loop {
+        let mut n: Integer =
+            Integer::random_bits(bits_size, &mut fast_paillier::utils::external_rand(rng)).into();
+        n.set_bit(bits_size - 1, true);
+        n.next_prime_mut();
+        if n.mod_u(4) == 3 {
+            break n;
+        }
+    }
+}
+
+#[test]
+fn enc() {
+    let mut rng = rand_dev::DevRng::new();
+    let aux = aux(&mut rng);
+    let security = enc::SecurityParams::insecure_small_keys();
+    let key = random_key(&mut rng);
+    let key = key.encryption_key();
+
+    let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+    let data = enc::Data {
+        key,
+        ciphertext: &ciphertext,
+    };
+    let pdata = enc::PrivateData {
+        plaintext: &plaintext,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        enc::non_interactive::prove(Sha256::default(), &aux, data, pdata, &security, &mut rng)
+            .unwrap();
+    enc::non_interactive::verify(
+        Sha256::default(),
+        &aux,
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )
+    .unwrap();
+}
+
+#[test]
+fn log_star() {
+    let mut rng = rand_dev::DevRng::new();
+    let aux = aux(&mut rng);
+    let security = log_star::SecurityParams::insecure_small_keys();
+    let key0 = random_key(&mut rng);
+    let key0 = key0.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (c, nonce) = key0.encrypt_with_random(&mut rng, &x).unwrap();
+    let b = Point::<C>::generator().to_point();
+    let point_x = b * x.to_scalar();
+    let data = log_star::Data {
+        key0,
+        c: &c,
+        b: &b,
+        x: &point_x,
+    };
+    let pdata = log_star::PrivateData {
+        x: &x,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        log_star::non_interactive::prove(Sha256::default(), &aux, data, pdata, &security, &mut rng)
+            .unwrap();
+    log_star::non_interactive::verify(
+        Sha256::default(),
+        &aux,
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )
+    .unwrap();
+}
+
+#[test]
+fn aff_g() {
+    let mut rng = rand_dev::DevRng::new();
+    let aux = aux(&mut rng);
+    let security = aff_g::SecurityParams::insecure_small_keys();
+    let key0 = random_key(&mut rng);
+    let key0 = key0.encryption_key();
+    let key1 = random_key(&mut rng);
+    let key1 = key1.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+    let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+    let (c, _) = {
+        let plaintext = Integer::from_rng_pm(key0.half_n(), &mut rng);
+        key0.encrypt_with_random(&mut rng, &plaintext).unwrap()
+    };
+    let (y_enc_key1, nonce_y) = key1.encrypt_with_random(&mut rng, &y).unwrap();
+    let (y_enc_key0, nonce) = key0.encrypt_with_random(&mut rng, &y).unwrap();
+    let d = key0.oadd(&key0.omul(&x, &c).unwrap(), &y_enc_key0).unwrap();
+    let point_x = Point::<C>::generator() * x.to_scalar();
+    let data = aff_g::Data {
+        key0,
+        key1,
+        c: &c,
+        d: &d,
+        y: &y_enc_key1,
+        x: &point_x,
+    };
+    let pdata = aff_g::PrivateData {
+        x: &x,
+        y: &y,
+        nonce: &nonce,
+        nonce_y: &nonce_y,
+    };
+    let (commitment, proof) =
+        aff_g::non_interactive::prove(Sha256::default(), &aux, data, pdata, &security, &mut rng)
+            .unwrap();
+    aff_g::non_interactive::verify(
+        Sha256::default(),
+        &aux,
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )
+    .unwrap();
+}
+
+#[test]
+fn witness_escrow() {
+    let mut rng = rand_dev::DevRng::new();
+    let aux = aux(&mut rng);
+    let security = witness_escrow::SecurityParams::insecure_small_keys();
+    let key0 = random_key(&mut rng);
+    let key0 = key0.encryption_key();
+    let key1 = random_key(&mut rng);
+    let key1 = key1.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (c0, nonce0) = key0.encrypt_with_random(&mut rng, &x).unwrap();
+    let (c1, nonce1) = key1.encrypt_with_random(&mut rng, &x).unwrap();
+    let b = Point::<C>::generator().to_point();
+    let point_x = b * x.to_scalar();
+    let data = witness_escrow::Data {
+        key0,
+        c0: &c0,
+        key1,
+        c1: &c1,
+        b: &b,
+        x: &point_x,
+    };
+    let pdata = witness_escrow::PrivateData {
+        x: &x,
+        nonce0: &nonce0,
+        nonce1: &nonce1,
+    };
+    let (commitment, proof) = witness_escrow::non_interactive::prove(
+        Sha256::default(),
+        &aux,
+        data,
+        pdata,
+        &security,
+        &mut rng,
+    )
+    .unwrap();
+    witness_escrow::non_interactive::verify(
+        Sha256::default(),
+        &aux,
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )
+    .unwrap();
+}
+
+#[test]
+fn no_small_factor() {
+    let mut rng = rand_dev::DevRng::new();
+    let aux = aux(&mut rng);
+    let security = no_small_factor::SecurityParams::insecure_small_keys();
+    let p = generate_blum_prime(&mut rng, PRIME_BITS);
+    let q = generate_blum_prime(&mut rng, PRIME_BITS);
+    let n = (&p * &q).complete();
+    let n_root = n.sqrt_ref().complete();
+    let data = no_small_factor::Data {
+        n: &n,
+        n_root: &n_root,
+    };
+    let pdata = no_small_factor::PrivateData { p: &p, q: &q };
+    let proof = no_small_factor::non_interactive::prove(
+        Sha256::default(),
+        &aux,
+        data,
+        pdata,
+        &security,
+        &mut rng,
+    )
+    .unwrap();
+    no_small_factor::non_interactive::verify(Sha256::default(), &aux, data, &security, &proof)
+        .unwrap();
+}
+
+#[test]
+fn paillier_blum_modulus() {
+    let mut rng = rand_dev::DevRng::new();
+    let p = generate_blum_prime(&mut rng, PRIME_BITS);
+    let q = generate_blum_prime(&mut rng, PRIME_BITS);
+    let data = paillier_blum_modulus::Data {
+        n: (&p * &q).complete(),
+    };
+    let pdata = paillier_blum_modulus::PrivateData { p, q };
+    let (commitment, proof) = paillier_blum_modulus::non_interactive::prove::<80, _, _>(
+        Sha256::default(),
+        &data,
+        &pdata,
+        &mut rng,
+    )
+    .unwrap();
+    paillier_blum_modulus::non_interactive::verify(Sha256::default(), &data, &commitment, &proof)
+        .unwrap();
+}
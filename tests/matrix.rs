@@ -0,0 +1,1623 @@
+//! Smoke tests of every proof instantiated for each supported combination of curve,
+//! hash function, and arithmetic backend
+//!
+//! `rug` (GMP) is the only bignum implementation, so backends differ in how
+//! exponentiations modulo `N^` are evaluated: naively or with precomputations
+//! (multiexponentiation table and CRT), see [`Aux`].
+
+use std::sync::OnceLock;
+
+use digest::{typenum::U32, Digest};
+use fast_paillier::{AnyEncryptionKey, DecryptionKey};
+use generic_ec::{Curve, Point, Scalar};
+use paillier_zk::{
+    aux_generation::AuxSecrets,
+    exact_range, factorization_knowledge,
+    group_element_vs_paillier_encryption_in_range as log_star,
+    group_element_vs_paillier_multiplication as mul_star,
+    multiexp::MultiexpTable,
+    no_small_factor, paillier_affine_operation_in_range as aff_g,
+    paillier_affine_operation_with_paillier_commitment as aff_p, paillier_blum_modulus,
+    paillier_broadcast_encryption as broadcast, paillier_decryption_modulo_q as dec,
+    paillier_encryption_in_range as enc, paillier_encryption_in_range_with_elgamal as enc_elg,
+    paillier_encryption_of_bit as enc_bit, paillier_encryption_of_zero as enc_zero,
+    paillier_multiplication as mul, paillier_plaintext_equality as plaintext_eq,
+    paillier_plaintext_knowledge as plaintext_knowledge,
+    paillier_scalar_multiplication_in_range as scalar_mul,
+    pedersen_commitment_vs_paillier_encryption_in_range as ped, presigning_proofs,
+    ring_pedersen_parameters,
+    rug::{Complete, Integer},
+    setup_proofs,
+    utils::encoding::{Encode, Schema},
+    utils::ring_pedersen::Aux,
+    witness_escrow, IntegerExt, InvalidProof,
+};
+
+struct Setup {
+    aux: Aux,
+    aux_precomputed: Aux,
+    aux_secrets: AuxSecrets,
+    key0: DecryptionKey,
+    key1: DecryptionKey,
+}
+
+fn setup() -> &'static Setup {
+    static SETUP: OnceLock<Setup> = OnceLock::new();
+    SETUP.get_or_init(|| {
+        let mut rng = rand_dev::DevRng::new();
+
+        let p = generate_blum_prime(&mut rng, 1024);
+        let q = generate_blum_prime(&mut rng, 1024);
+        let n = (&p * &q).complete();
+        let (s, t, lambda) = {
+            let phi_n = (p.clone() - 1u8) * (q.clone() - 1u8);
+            let r = Integer::gen_invertible(&n, &mut rng);
+            let lambda = phi_n.random_below(&mut fast_paillier::utils::external_rand(&mut rng));
+            let t = r.square().modulo(&n);
+            let s = t.pow_mod_ref(&lambda, &n).unwrap().into();
+            (s, t, lambda)
+        };
+        let aux = Aux::new(s, t, n);
+        let table = MultiexpTable::build(&aux.s, &aux.t, 4096, 4096, aux.rsa_modulo.clone())
+            .expect("build multiexp table");
+        let crt = fast_paillier::utils::CrtExp::build_n(&p, &q).expect("build crt");
+        let aux_precomputed = aux.clone().with_multiexp(table.into()).with_crt(crt);
+
+        Setup {
+            aux,
+            aux_precomputed,
+            aux_secrets: AuxSecrets { p, q, lambda },
+            key0: random_key(&mut rng),
+            key1: random_key(&mut rng),
+        }
+    })
+}
+
+fn naive() -> &'static Aux {
+    &setup().aux
+}
+
+fn precomputed() -> &'static Aux {
+    &setup().aux_precomputed
+}
+
+fn random_key(rng: &mut impl rand_core::RngCore) -> DecryptionKey {
+    let p = generate_blum_prime(rng, 1024);
+    let q = generate_blum_prime(rng, 1024);
+    DecryptionKey::from_primes(p, q).unwrap()
+}
+
+fn generate_blum_prime(rng: &mut impl rand_core::RngCore, bits_size: u32) -> Integer {
+    loop {
+        let mut n: Integer =
+            Integer::random_bits(bits_size, &mut fast_paillier::utils::external_rand(rng)).into();
+        n.set_bit(bits_size - 1, true);
+        n.next_prime_mut();
+        if n.mod_u(4) == 3 {
+            break n;
+        }
+    }
+}
+
+fn q() -> Integer {
+    (Integer::ONE << 128_u32).complete()
+}
+
+/// Upper bound on bit size of Paillier keys and `N^`
+const MODULUS_BITS: usize = 2048;
+
+fn assert_conforms(value: &impl Encode, schema: Schema) {
+    assert!(schema.conforms(&value.to_canonical_bytes()));
+}
+
+/// Proves a statement and verifies the proof. If `tamper` is set, verifier is given
+/// a different statement.
+fn enc<D: Digest<OutputSize = U32> + Clone>(aux: &Aux, tamper: bool) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = enc::SecurityParams::new(1024, 300, q());
+    let key = setup().key0.encryption_key();
+
+    let plaintext = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+    let data = enc::Data {
+        key,
+        ciphertext: &ciphertext,
+    };
+    let pdata = enc::PrivateData {
+        plaintext: &plaintext,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        enc::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        enc::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, enc::Proof::schema(&security, MODULUS_BITS));
+
+    let other_ciphertext = key.oadd(&ciphertext, &ciphertext).unwrap();
+    let data = if tamper {
+        enc::Data {
+            key,
+            ciphertext: &other_ciphertext,
+        }
+    } else {
+        data
+    };
+    enc::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn enc_batch<D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = enc::SecurityParams::new(1024, 300, q());
+    let key = setup().key0.encryption_key();
+
+    let plaintexts = (0..3)
+        .map(|_| Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng))
+        .collect::<Vec<_>>();
+    let (ciphertexts, nonces): (Vec<_>, Vec<_>) = plaintexts
+        .iter()
+        .map(|x| key.encrypt_with_random(&mut rng, x).unwrap())
+        .unzip();
+    let data = enc::batch::Data {
+        key,
+        ciphertexts: &ciphertexts,
+    };
+    let pdata = enc::batch::PrivateData {
+        plaintexts: &plaintexts,
+        nonces: &nonces,
+    };
+    let (commitment, proof) =
+        enc::batch::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng)
+            .unwrap();
+    assert_conforms(
+        &commitment,
+        enc::batch::Commitment::schema(&security, 3, MODULUS_BITS),
+    );
+    assert_conforms(
+        &proof,
+        enc::batch::Proof::schema(&security, 3, MODULUS_BITS),
+    );
+
+    let mut other_ciphertexts = ciphertexts.clone();
+    other_ciphertexts[2] = key.oadd(&ciphertexts[2], &ciphertexts[2]).unwrap();
+    let data = if tamper {
+        enc::batch::Data {
+            key,
+            ciphertexts: &other_ciphertexts,
+        }
+    } else {
+        data
+    };
+    enc::batch::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn enc_interval<D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = enc::SecurityParams::new(1024, 300, q());
+    let key = setup().key0.encryption_key();
+
+    let a = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let b = &a + (Integer::ONE << (security.l - 1)).complete();
+    let plaintext = (&a + 1u8).complete();
+    let (ciphertext, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+    let data = enc::interval::Data {
+        key,
+        ciphertext: &ciphertext,
+        a: &a,
+        b: &b,
+    };
+    let pdata = enc::interval::PrivateData {
+        plaintext: &plaintext,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        enc::interval::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng)
+            .unwrap();
+    assert_conforms(
+        &commitment,
+        enc::interval::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(
+        &proof,
+        enc::interval::Proof::schema(&security, MODULUS_BITS),
+    );
+
+    let other_a = (&a + 2u8).complete();
+    let data = if tamper {
+        enc::interval::Data {
+            a: &other_a,
+            ..data
+        }
+    } else {
+        data
+    };
+    enc::interval::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn enc_linear_combination<D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = enc::SecurityParams::new(1024, 300, q());
+    let key = setup().key0.encryption_key();
+
+    let (c1, _) = key
+        .encrypt_with_random(&mut rng, &Integer::from(1))
+        .unwrap();
+    let (c2, _) = key
+        .encrypt_with_random(&mut rng, &Integer::from(2))
+        .unwrap();
+    let (a, b) = (Integer::from(3), Integer::from(-4));
+    let y = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (enc_y, nonce) = key.encrypt_with_random(&mut rng, &y).unwrap();
+    let d = key
+        .oadd(
+            &key.oadd(&key.omul(&a, &c1).unwrap(), &key.omul(&b, &c2).unwrap())
+                .unwrap(),
+            &enc_y,
+        )
+        .unwrap();
+    let data = enc::linear_combination::Data {
+        key,
+        c1: &c1,
+        c2: &c2,
+        a: &a,
+        b: &b,
+        d: &d,
+    };
+    let pdata = enc::linear_combination::PrivateData {
+        plaintext: &y,
+        nonce: &nonce,
+    };
+    let (commitment, proof) = enc::linear_combination::non_interactive::prove(
+        D::new(),
+        aux,
+        data,
+        pdata,
+        &security,
+        &mut rng,
+    )
+    .unwrap();
+    assert_conforms(
+        &commitment,
+        enc::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, enc::Proof::schema(&security, MODULUS_BITS));
+
+    let other_b = Integer::from(4);
+    let data = if tamper {
+        enc::linear_combination::Data {
+            b: &other_b,
+            ..data
+        }
+    } else {
+        data
+    };
+    enc::linear_combination::non_interactive::verify(
+        D::new(),
+        aux,
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )
+}
+
+fn enc_elg<C: Curve, D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = enc_elg::SecurityParams::new(1024, 300, q());
+    let key0 = setup().key0.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (c, nonce) = key0.encrypt_with_random(&mut rng, &x).unwrap();
+    let a = Scalar::<C>::random(&mut rng);
+    let b = Scalar::<C>::random(&mut rng);
+    let point_a = Point::generator() * a;
+    let point_b = Point::generator() * b;
+    let point_x = Point::generator() * (a * b + x.to_scalar());
+    let data = enc_elg::Data {
+        key0,
+        c: &c,
+        a: &point_a,
+        b: &point_b,
+        x: &point_x,
+    };
+    let pdata = enc_elg::PrivateData {
+        x: &x,
+        nonce: &nonce,
+        b: &b,
+    };
+    let (commitment, proof) =
+        enc_elg::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        enc_elg::Commitment::<C>::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, enc_elg::Proof::<C>::schema(&security, MODULUS_BITS));
+
+    let other_x = point_x + Point::generator();
+    let data = if tamper {
+        enc_elg::Data {
+            x: &other_x,
+            ..data
+        }
+    } else {
+        data
+    };
+    enc_elg::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn log_star<C: Curve, D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = log_star::SecurityParams::new(1024, 300, q());
+    let key0 = setup().key0.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (c, nonce) = key0.encrypt_with_random(&mut rng, &x).unwrap();
+    let b = Point::<C>::generator() * Scalar::random(&mut rng);
+    let point_x = b * x.to_scalar();
+    let data = log_star::Data {
+        key0,
+        c: &c,
+        b: &b,
+        x: &point_x,
+    };
+    let pdata = log_star::PrivateData {
+        x: &x,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        log_star::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        log_star::Commitment::<C>::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, log_star::Proof::schema(&security, MODULUS_BITS));
+
+    let other_x = point_x + b;
+    let data = if tamper {
+        log_star::Data {
+            x: &other_x,
+            ..data
+        }
+    } else {
+        data
+    };
+    log_star::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn ped<C: Curve, D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = ped::SecurityParams::new(1024, 300, q());
+    let key0 = setup().key0.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (c, nonce) = key0.encrypt_with_random(&mut rng, &x).unwrap();
+    let g = Point::<C>::generator().to_point();
+    let h = Point::<C>::generator() * Scalar::random(&mut rng);
+    let r = Scalar::random(&mut rng);
+    let point_x = g * x.to_scalar() + h * r;
+    let data = ped::Data {
+        key0,
+        c: &c,
+        g: &g,
+        h: &h,
+        x: &point_x,
+    };
+    let pdata = ped::PrivateData {
+        x: &x,
+        r: &r,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        ped::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        ped::Commitment::<C>::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, ped::Proof::<C>::schema(&security, MODULUS_BITS));
+
+    let other_x = point_x + h;
+    let data = if tamper {
+        ped::Data {
+            x: &other_x,
+            ..data
+        }
+    } else {
+        data
+    };
+    ped::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn mul_star<C: Curve, D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = mul_star::SecurityParams::new(1024, 300, q());
+    let key0 = setup().key0.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (c, _) = {
+        let plaintext = Integer::from_rng_pm(key0.half_n(), &mut rng);
+        key0.encrypt_with_random(&mut rng, &plaintext).unwrap()
+    };
+    let (rerandomization, nonce) = key0.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+    let d = key0
+        .oadd(&key0.omul(&x, &c).unwrap(), &rerandomization)
+        .unwrap();
+    let point_x = Point::<C>::generator() * x.to_scalar();
+    let data = mul_star::Data {
+        key0,
+        c: &c,
+        d: &d,
+        x: &point_x,
+    };
+    let pdata = mul_star::PrivateData {
+        x: &x,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        mul_star::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        mul_star::Commitment::<C>::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, mul_star::Proof::schema(&security, MODULUS_BITS));
+
+    let other_x = point_x + Point::generator();
+    let data = if tamper {
+        mul_star::Data {
+            x: &other_x,
+            ..data
+        }
+    } else {
+        data
+    };
+    mul_star::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn dec<C: Curve, D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = dec::SecurityParams::new(1024, 300, q());
+    let key0 = setup().key0.encryption_key();
+
+    let y = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (c, nonce) = key0.encrypt_with_random(&mut rng, &y).unwrap();
+    let x = y.to_scalar::<C>();
+    let data = dec::Data { key0, c: &c, x: &x };
+    let pdata = dec::PrivateData {
+        y: &y,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        dec::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        dec::Commitment::<C>::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, dec::Proof::schema(&security, MODULUS_BITS));
+
+    let other_x = x + Scalar::one();
+    let data = if tamper {
+        dec::Data {
+            x: &other_x,
+            ..data
+        }
+    } else {
+        data
+    };
+    dec::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn aff_g<C: Curve, D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = aff_g::SecurityParams::new(1024, 1024, 300, q());
+    let key0 = setup().key0.encryption_key();
+    let key1 = setup().key1.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+    let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+    let (c, _) = {
+        let plaintext = Integer::from_rng_pm(key0.half_n(), &mut rng);
+        key0.encrypt_with_random(&mut rng, &plaintext).unwrap()
+    };
+    let (y_enc_key1, nonce_y) = key1.encrypt_with_random(&mut rng, &y).unwrap();
+    let (y_enc_key0, nonce) = key0.encrypt_with_random(&mut rng, &y).unwrap();
+    let d = key0.oadd(&key0.omul(&x, &c).unwrap(), &y_enc_key0).unwrap();
+    let point_x = Point::<C>::generator() * x.to_scalar();
+
+    let data = aff_g::Data {
+        key0,
+        key1,
+        c: &c,
+        d: &d,
+        y: &y_enc_key1,
+        x: &point_x,
+    };
+    let pdata = aff_g::PrivateData {
+        x: &x,
+        y: &y,
+        nonce: &nonce,
+        nonce_y: &nonce_y,
+    };
+    let (commitment, proof) =
+        aff_g::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        aff_g::Commitment::<C>::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, aff_g::Proof::schema(&security, MODULUS_BITS));
+
+    let other_d = key0.oadd(&d, &c).unwrap();
+    let data = if tamper {
+        aff_g::Data {
+            d: &other_d,
+            ..data
+        }
+    } else {
+        data
+    };
+    aff_g::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn aff_p<D: Digest<OutputSize = U32> + Clone>(aux: &Aux, tamper: bool) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = aff_p::SecurityParams::new(1024, 1024, 300, q());
+    let key0 = setup().key0.encryption_key();
+    let key1 = setup().key1.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l_x).complete(), &mut rng);
+    let y = Integer::from_rng_pm(&(Integer::ONE << security.l_y).complete(), &mut rng);
+    let (c, _) = {
+        let plaintext = Integer::from_rng_pm(key0.half_n(), &mut rng);
+        key0.encrypt_with_random(&mut rng, &plaintext).unwrap()
+    };
+    let (x_enc_key1, nonce_x) = key1.encrypt_with_random(&mut rng, &x).unwrap();
+    let (y_enc_key1, nonce_y) = key1.encrypt_with_random(&mut rng, &y).unwrap();
+    let (y_enc_key0, nonce) = key0.encrypt_with_random(&mut rng, &y).unwrap();
+    let d = key0.oadd(&key0.omul(&x, &c).unwrap(), &y_enc_key0).unwrap();
+
+    let data = aff_p::Data {
+        key0,
+        key1,
+        c: &c,
+        d: &d,
+        y: &y_enc_key1,
+        x: &x_enc_key1,
+    };
+    let pdata = aff_p::PrivateData {
+        x: &x,
+        y: &y,
+        nonce: &nonce,
+        nonce_x: &nonce_x,
+        nonce_y: &nonce_y,
+    };
+    let (commitment, proof) =
+        aff_p::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        aff_p::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, aff_p::Proof::schema(&security, MODULUS_BITS));
+
+    let other_d = key0.oadd(&d, &c).unwrap();
+    let data = if tamper {
+        aff_p::Data {
+            d: &other_d,
+            ..data
+        }
+    } else {
+        data
+    };
+    aff_p::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn witness_escrow<C: Curve, D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = witness_escrow::SecurityParams::new(1024, 300, q());
+    let key0 = setup().key0.encryption_key();
+    let key1 = setup().key1.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (c0, nonce0) = key0.encrypt_with_random(&mut rng, &x).unwrap();
+    let (c1, nonce1) = key1.encrypt_with_random(&mut rng, &x).unwrap();
+    let b = Point::<C>::generator().to_point();
+    let point_x = b * x.to_scalar();
+    let data = witness_escrow::Data {
+        key0,
+        c0: &c0,
+        key1,
+        c1: &c1,
+        b: &b,
+        x: &point_x,
+    };
+    let pdata = witness_escrow::PrivateData {
+        x: &x,
+        nonce0: &nonce0,
+        nonce1: &nonce1,
+    };
+    let (commitment, proof) =
+        witness_escrow::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng)
+            .unwrap();
+    assert_conforms(
+        &commitment,
+        witness_escrow::Commitment::<C>::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(
+        &proof,
+        witness_escrow::Proof::schema(&security, MODULUS_BITS),
+    );
+
+    let (other_c1, _) = key1.encrypt_with_random(&mut rng, &x).unwrap();
+    let data = if tamper {
+        witness_escrow::Data {
+            c1: &other_c1,
+            ..data
+        }
+    } else {
+        data
+    };
+    witness_escrow::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn plaintext_eq<D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = plaintext_eq::SecurityParams::new(1024, 300, q());
+    let key0 = setup().key0.encryption_key();
+    let key1 = setup().key1.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (c0, nonce0) = key0.encrypt_with_random(&mut rng, &x).unwrap();
+    let (c1, nonce1) = key1.encrypt_with_random(&mut rng, &x).unwrap();
+    let data = plaintext_eq::Data {
+        key0,
+        c0: &c0,
+        key1,
+        c1: &c1,
+    };
+    let pdata = plaintext_eq::PrivateData {
+        x: &x,
+        nonce0: &nonce0,
+        nonce1: &nonce1,
+    };
+    let (commitment, proof) =
+        plaintext_eq::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng)
+            .unwrap();
+    assert_conforms(
+        &commitment,
+        plaintext_eq::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, plaintext_eq::Proof::schema(&security, MODULUS_BITS));
+
+    let (other_c1, _) = key1.encrypt_with_random(&mut rng, &x).unwrap();
+    let data = if tamper {
+        plaintext_eq::Data {
+            c1: &other_c1,
+            ..data
+        }
+    } else {
+        data
+    };
+    plaintext_eq::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn scalar_mul<D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = scalar_mul::SecurityParams::new(1024, 300, q());
+    let key = setup().key0.encryption_key();
+
+    let k = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let y = Integer::from_rng_pm(key.half_n(), &mut rng);
+    let (c, _) = key.encrypt_with_random(&mut rng, &y).unwrap();
+    let c_prime = key.omul(&k, &c).unwrap();
+    let data = scalar_mul::Data {
+        key,
+        c: &c,
+        c_prime: &c_prime,
+    };
+    let pdata = scalar_mul::PrivateData { k: &k };
+    let (commitment, proof) =
+        scalar_mul::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng)
+            .unwrap();
+    assert_conforms(
+        &commitment,
+        scalar_mul::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, scalar_mul::Proof::schema(&security, MODULUS_BITS));
+
+    let other_c_prime = key.oadd(&c_prime, &c).unwrap();
+    let data = if tamper {
+        scalar_mul::Data {
+            c_prime: &other_c_prime,
+            ..data
+        }
+    } else {
+        data
+    };
+    scalar_mul::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn broadcast<D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = broadcast::SecurityParams::new(1024, 300, q());
+    let key0 = setup().key0.encryption_key();
+    let key1 = setup().key1.encryption_key();
+    let keys: [&dyn AnyEncryptionKey; 2] = [key0, key1];
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << security.l).complete(), &mut rng);
+    let (c0, nonce0) = key0.encrypt_with_random(&mut rng, &x).unwrap();
+    let (c1, nonce1) = key1.encrypt_with_random(&mut rng, &x).unwrap();
+    let (ciphertexts, nonces) = ([c0.clone(), c1], [nonce0, nonce1]);
+    let data = broadcast::Data {
+        keys: &keys,
+        ciphertexts: &ciphertexts,
+    };
+    let pdata = broadcast::PrivateData {
+        x: &x,
+        nonces: &nonces,
+    };
+    let (commitment, proof) =
+        broadcast::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        broadcast::Commitment::schema(&security, keys.len(), MODULUS_BITS),
+    );
+    assert_conforms(
+        &proof,
+        broadcast::Proof::schema(&security, keys.len(), MODULUS_BITS),
+    );
+
+    let (other_c1, _) = key1.encrypt_with_random(&mut rng, &x).unwrap();
+    let other_ciphertexts = [c0, other_c1];
+    let data = if tamper {
+        broadcast::Data {
+            ciphertexts: &other_ciphertexts,
+            ..data
+        }
+    } else {
+        data
+    };
+    broadcast::non_interactive::verify(D::new(), aux, data, &commitment, &security, &proof)
+}
+
+fn paillier_blum_modulus<D: Digest<OutputSize = U32> + Clone>(
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let p = generate_blum_prime(&mut rng, 256);
+    let q = generate_blum_prime(&mut rng, 256);
+    let data = paillier_blum_modulus::Data {
+        n: (&p * &q).complete(),
+    };
+    let pdata = paillier_blum_modulus::PrivateData { p, q };
+    let (commitment, proof) = paillier_blum_modulus::non_interactive::prove::<65, _, _>(
+        D::new(),
+        &data,
+        &pdata,
+        &mut rng,
+    )
+    .unwrap();
+    assert_conforms(&commitment, paillier_blum_modulus::Commitment::schema(512));
+    assert_conforms(&proof, paillier_blum_modulus::Proof::<65>::schema(512));
+
+    let data = if tamper {
+        paillier_blum_modulus::Data { n: data.n + 2u8 }
+    } else {
+        data
+    };
+    paillier_blum_modulus::non_interactive::verify(D::new(), &data, &commitment, &proof)
+}
+
+fn ring_pedersen_parameters<D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let data = ring_pedersen_parameters::Data { aux };
+    let pdata = ring_pedersen_parameters::PrivateData::from(&setup().aux_secrets);
+    let (commitment, proof) = ring_pedersen_parameters::non_interactive::prove::<80, _, _>(
+        D::new(),
+        data,
+        pdata,
+        &mut rng,
+    )
+    .unwrap();
+    assert_conforms(
+        &commitment,
+        ring_pedersen_parameters::Commitment::<80>::schema(MODULUS_BITS),
+    );
+    assert_conforms(
+        &proof,
+        ring_pedersen_parameters::Proof::<80>::schema(MODULUS_BITS),
+    );
+
+    let other_s = (&aux.s * &aux.t).complete().modulo(&aux.rsa_modulo);
+    let other_aux = Aux::new(other_s, aux.t.clone(), aux.rsa_modulo.clone());
+    let data = if tamper {
+        ring_pedersen_parameters::Data { aux: &other_aux }
+    } else {
+        data
+    };
+    ring_pedersen_parameters::non_interactive::verify(D::new(), data, &commitment, &proof)
+}
+
+fn mul<D: Digest<OutputSize = U32> + Clone>(tamper: bool) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = mul::SecurityParams::new(q());
+    let key = setup().key0.encryption_key();
+
+    let x = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+    let y = Integer::from_rng_pm(&(Integer::ONE << 256_u32).complete(), &mut rng);
+    let (cx, nonce_x) = key.encrypt_with_random(&mut rng, &x).unwrap();
+    let (cy, _) = key.encrypt_with_random(&mut rng, &y).unwrap();
+    let (rerandomization, nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+    let c = key
+        .oadd(&key.omul(&x, &cy).unwrap(), &rerandomization)
+        .unwrap();
+    let data = mul::Data {
+        key,
+        x: &cx,
+        y: &cy,
+        c: &c,
+    };
+    let pdata = mul::PrivateData {
+        x: &x,
+        nonce: &nonce,
+        nonce_x: &nonce_x,
+    };
+    let (commitment, proof) =
+        mul::non_interactive::prove(D::new(), data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        mul::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, mul::Proof::schema(&security, MODULUS_BITS));
+
+    let other_c = key.oadd(&c, &cy).unwrap();
+    let data = if tamper {
+        mul::Data {
+            c: &other_c,
+            ..data
+        }
+    } else {
+        data
+    };
+    mul::non_interactive::verify(D::new(), data, &commitment, &security, &proof)
+}
+
+fn enc_bit<D: Digest<OutputSize = U32> + Clone>(tamper: bool) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = enc_bit::SecurityParams::new(q());
+    let key = setup().key0.encryption_key();
+
+    let (c, nonce) = key.encrypt_with_random(&mut rng, Integer::ONE).unwrap();
+    let data = enc_bit::Data { key, c: &c };
+    let pdata = enc_bit::PrivateData {
+        bit: true,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        enc_bit::non_interactive::prove(D::new(), data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        enc_bit::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, enc_bit::Proof::schema(&security, MODULUS_BITS));
+
+    let other_c = key.encrypt_with(&Integer::from(2), &nonce).unwrap();
+    let data = if tamper {
+        enc_bit::Data {
+            c: &other_c,
+            ..data
+        }
+    } else {
+        data
+    };
+    enc_bit::non_interactive::verify(D::new(), data, &commitment, &security, &proof)
+}
+
+fn enc_zero<D: Digest<OutputSize = U32> + Clone>(tamper: bool) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = enc_zero::SecurityParams::new(q());
+    let key = setup().key0.encryption_key();
+
+    let (c, nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+    let data = enc_zero::Data { key, c: &c };
+    let pdata = enc_zero::PrivateData { nonce: &nonce };
+    let (commitment, proof) =
+        enc_zero::non_interactive::prove(D::new(), data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &commitment,
+        enc_zero::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, enc_zero::Proof::schema(&security, MODULUS_BITS));
+
+    let other_c = key.encrypt_with(Integer::ONE, &nonce).unwrap();
+    let data = if tamper {
+        enc_zero::Data {
+            c: &other_c,
+            ..data
+        }
+    } else {
+        data
+    };
+    enc_zero::non_interactive::verify(D::new(), data, &commitment, &security, &proof)
+}
+
+fn enc_zero_opening<D: Digest<OutputSize = U32> + Clone>(tamper: bool) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = enc_zero::SecurityParams::new(q());
+    let key = setup().key0.encryption_key();
+
+    let v = Integer::from(42);
+    let (c, nonce) = key.encrypt_with_random(&mut rng, &v).unwrap();
+    let data = enc_zero::opening::Data { key, c: &c, v: &v };
+    let pdata = enc_zero::opening::PrivateData { nonce: &nonce };
+    let (commitment, proof) =
+        enc_zero::opening::non_interactive::prove(D::new(), data, pdata, &security, &mut rng)
+            .unwrap();
+    assert_conforms(
+        &commitment,
+        enc_zero::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, enc_zero::Proof::schema(&security, MODULUS_BITS));
+
+    let other_v = Integer::from(43);
+    let data = if tamper {
+        enc_zero::opening::Data {
+            v: &other_v,
+            ..data
+        }
+    } else {
+        data
+    };
+    enc_zero::opening::non_interactive::verify(D::new(), data, &commitment, &security, &proof)
+}
+
+fn enc_zero_rerandomization<D: Digest<OutputSize = U32> + Clone>(
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = enc_zero::SecurityParams::new(q());
+    let key = setup().key0.encryption_key();
+
+    let (c, _) = key
+        .encrypt_with_random(&mut rng, &Integer::from(42))
+        .unwrap();
+    let (rerandomization, nonce) = key.encrypt_with_random(&mut rng, &Integer::ZERO).unwrap();
+    let c_prime = key.oadd(&c, &rerandomization).unwrap();
+    let data = enc_zero::rerandomization::Data {
+        key,
+        c: &c,
+        c_prime: &c_prime,
+    };
+    let pdata = enc_zero::rerandomization::PrivateData { nonce: &nonce };
+    let (commitment, proof) = enc_zero::rerandomization::non_interactive::prove(
+        D::new(),
+        data,
+        pdata,
+        &security,
+        &mut rng,
+    )
+    .unwrap();
+    assert_conforms(
+        &commitment,
+        enc_zero::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(&proof, enc_zero::Proof::schema(&security, MODULUS_BITS));
+
+    let other_c = key.oadd(&c, &c).unwrap();
+    let data = if tamper {
+        enc_zero::rerandomization::Data {
+            c: &other_c,
+            ..data
+        }
+    } else {
+        data
+    };
+    enc_zero::rerandomization::non_interactive::verify(
+        D::new(),
+        data,
+        &commitment,
+        &security,
+        &proof,
+    )
+}
+
+fn plaintext_knowledge<D: Digest<OutputSize = U32> + Clone>(
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = plaintext_knowledge::SecurityParams::new(q());
+    let key = setup().key0.encryption_key();
+
+    let plaintext = Integer::from_rng_pm(&(key.n() >> 1u32).complete(), &mut rng);
+    let (c, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+    let data = plaintext_knowledge::Data { key, c: &c };
+    let pdata = plaintext_knowledge::PrivateData {
+        plaintext: &plaintext,
+        nonce: &nonce,
+    };
+    let (commitment, proof) =
+        plaintext_knowledge::non_interactive::prove(D::new(), data, pdata, &security, &mut rng)
+            .unwrap();
+    assert_conforms(
+        &commitment,
+        plaintext_knowledge::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(
+        &proof,
+        plaintext_knowledge::Proof::schema(&security, MODULUS_BITS),
+    );
+
+    let other_c = key.oadd(&c, &c).unwrap();
+    let data = if tamper {
+        plaintext_knowledge::Data {
+            c: &other_c,
+            ..data
+        }
+    } else {
+        data
+    };
+    plaintext_knowledge::non_interactive::verify(D::new(), data, &commitment, &security, &proof)
+}
+
+fn exact_range<D: Digest<OutputSize = U32> + Clone>(tamper: bool) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = exact_range::SecurityParams::new(16, q());
+    let key = setup().key0.encryption_key();
+
+    let plaintext = Integer::from(0xbeef);
+    let (c, nonce) = key.encrypt_with_random(&mut rng, &plaintext).unwrap();
+    let data = exact_range::Data { key, c: &c };
+    let pdata = exact_range::PrivateData {
+        plaintext: &plaintext,
+        nonce: &nonce,
+    };
+    let proof =
+        exact_range::non_interactive::prove(D::new(), data, pdata, &security, &mut rng).unwrap();
+    assert_conforms(
+        &proof,
+        exact_range::non_interactive::Proof::schema(&security, MODULUS_BITS),
+    );
+
+    let other_c = key.oadd(&c, &c).unwrap();
+    let data = if tamper {
+        exact_range::Data {
+            c: &other_c,
+            ..data
+        }
+    } else {
+        data
+    };
+    exact_range::non_interactive::verify(D::new(), data, &security, &proof)
+}
+
+fn factorization_knowledge<D: Digest<OutputSize = U32> + Clone>(
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = factorization_knowledge::SecurityParams::new(128, 80, 1);
+    let key = &setup().key0;
+    let data = factorization_knowledge::Data { n: key.n() };
+    let pdata = factorization_knowledge::PrivateData {
+        p: key.p(),
+        q: key.q(),
+    };
+    let (commitment, proof) =
+        factorization_knowledge::non_interactive::prove(D::new(), data, pdata, &security, &mut rng)
+            .unwrap();
+    assert_conforms(
+        &commitment,
+        factorization_knowledge::Commitment::schema(&security, MODULUS_BITS),
+    );
+    assert_conforms(
+        &proof,
+        factorization_knowledge::Proof::schema(&security, MODULUS_BITS),
+    );
+
+    let data = if tamper {
+        factorization_knowledge::Data {
+            n: setup().key1.n(),
+        }
+    } else {
+        data
+    };
+    factorization_knowledge::non_interactive::verify(D::new(), data, &commitment, &security, &proof)
+}
+
+fn no_small_factor<D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = no_small_factor::SecurityParams::new(64, 128, q());
+    let p = generate_blum_prime(&mut rng, 256);
+    let q = generate_blum_prime(&mut rng, 256);
+    let n = (&p * &q).complete();
+    let n_root = n.sqrt_ref().complete();
+    let data = no_small_factor::Data {
+        n: &n,
+        n_root: &n_root,
+    };
+    let pdata = no_small_factor::PrivateData { p: &p, q: &q };
+    let proof =
+        no_small_factor::non_interactive::prove(D::new(), aux, data, pdata, &security, &mut rng)
+            .unwrap();
+    assert_conforms(
+        &proof,
+        no_small_factor::non_interactive::Proof::schema(&security, MODULUS_BITS),
+    );
+
+    let other_n = (&n + 2u8).complete();
+    let data = if tamper {
+        no_small_factor::Data {
+            n: &other_n,
+            ..data
+        }
+    } else {
+        data
+    };
+    no_small_factor::non_interactive::verify(D::new(), aux, data, &security, &proof)
+}
+
+fn presigning<C: Curve, D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = presigning_proofs::SecurityParams::new(256, 1024, 300, q());
+    let key = setup().key1.encryption_key();
+    let verifier_key = setup().key0.encryption_key();
+    let l = (Integer::ONE << security.aff_g.l_x).complete();
+    let l_prime = (Integer::ONE << security.aff_g.l_y).complete();
+
+    let k = Integer::from_rng_pm(&l, &mut rng);
+    let (big_k, rho) = key.encrypt_with_random(&mut rng, &k).unwrap();
+    let (verifier_k, _) = {
+        let plaintext = Integer::from_rng_pm(&l, &mut rng);
+        verifier_key
+            .encrypt_with_random(&mut rng, &plaintext)
+            .unwrap()
+    };
+    let gamma = Integer::from_rng_pm(&l, &mut rng);
+    let (g, nu) = key.encrypt_with_random(&mut rng, &gamma).unwrap();
+    let point_gamma = Point::<C>::generator() * gamma.to_scalar();
+    let x = Integer::from_rng_pm(&l, &mut rng);
+    let point_x = Point::<C>::generator() * x.to_scalar();
+    let mut affine = |x: &Integer| {
+        let beta = Integer::from_rng_pm(&l_prime, &mut rng);
+        let (f, r) = key.encrypt_with_random(&mut rng, &beta).unwrap();
+        let (beta_enc, s) = verifier_key.encrypt_with_random(&mut rng, &beta).unwrap();
+        let d = verifier_key
+            .oadd(&verifier_key.omul(x, &verifier_k).unwrap(), &beta_enc)
+            .unwrap();
+        (beta, s, r, d, f)
+    };
+    let (beta, s, r, d, f) = affine(&gamma);
+    let (beta_hat, s_hat, r_hat, d_hat, f_hat) = affine(&x);
+    let point_delta = point_gamma * k.to_scalar();
+
+    let data1 = presigning_proofs::round1::Data { key, k: &big_k };
+    let pdata1 = presigning_proofs::round1::PrivateData { k: &k, rho: &rho };
+    let bundle1 =
+        presigning_proofs::round1::prove(D::new(), aux, data1, pdata1, &security, &mut rng)
+            .unwrap();
+    presigning_proofs::round1::verify(D::new(), aux, data1, &security, &bundle1)?;
+
+    let data2 = presigning_proofs::round2::Data {
+        key,
+        verifier_key,
+        verifier_k: &verifier_k,
+        g: &g,
+        gamma: &point_gamma,
+        x: &point_x,
+        d: &d,
+        f: &f,
+        d_hat: &d_hat,
+        f_hat: &f_hat,
+    };
+    let pdata2 = presigning_proofs::round2::PrivateData {
+        gamma: &gamma,
+        nu: &nu,
+        x: &x,
+        beta: &beta,
+        s: &s,
+        r: &r,
+        beta_hat: &beta_hat,
+        s_hat: &s_hat,
+        r_hat: &r_hat,
+    };
+    let bundle2 =
+        presigning_proofs::round2::prove(D::new(), aux, data2, pdata2, &security, &mut rng)
+            .unwrap();
+    assert!(
+        bundle2.to_canonical_bytes().len()
+            <= presigning_proofs::round2::Bundle::<C>::max_serialized_size(&security, MODULUS_BITS)
+    );
+
+    let data3 = presigning_proofs::round3::Data {
+        key,
+        k: &big_k,
+        gamma: &point_gamma,
+        delta: &point_delta,
+    };
+    let pdata3 = presigning_proofs::round3::PrivateData { k: &k, rho: &rho };
+    let bundle3 =
+        presigning_proofs::round3::prove(D::new(), aux, data3, pdata3, &security, &mut rng)
+            .unwrap();
+    presigning_proofs::round3::verify(D::new(), aux, data3, &security, &bundle3)?;
+
+    let data2 = if tamper {
+        presigning_proofs::round2::Data {
+            d: &d_hat,
+            d_hat: &d,
+            ..data2
+        }
+    } else {
+        data2
+    };
+    presigning_proofs::round2::verify(D::new(), aux, data2, &security, &bundle2)
+}
+
+fn setup_proofs<D: Digest<OutputSize = U32> + Clone>(
+    aux: &Aux,
+    tamper: bool,
+) -> Result<(), InvalidProof> {
+    let mut rng = rand_dev::DevRng::new();
+    let security = setup_proofs::SecurityParams::new(64, 128, q());
+    let p = generate_blum_prime(&mut rng, 256);
+    let q = generate_blum_prime(&mut rng, 256);
+    let n = (&p * &q).complete();
+    let data = setup_proofs::Data {
+        n: &n,
+        aux: &setup().aux,
+    };
+    let pdata = setup_proofs::PrivateData {
+        p: &p,
+        q: &q,
+        aux_secrets: &setup().aux_secrets,
+    };
+    let bundle =
+        setup_proofs::prove_all::<80, _, _>(D::new(), aux, data, pdata, &security, &mut rng)
+            .unwrap();
+    assert!(
+        bundle.to_canonical_bytes().len()
+            <= setup_proofs::Bundle::<80>::max_serialized_size(&security, MODULUS_BITS)
+    );
+
+    let other_n = (&n + 2u8).complete();
+    let data = if tamper {
+        setup_proofs::Data {
+            n: &other_n,
+            ..data
+        }
+    } else {
+        data
+    };
+    setup_proofs::verify_all(D::new(), aux, data, &security, &bundle)
+}
+
+/// Instantiates happy and sad path of every proof for each listed combination
+macro_rules! matrix {
+    ($($name:ident: ($curve:ty, $hash:ty, $backend:ident);)+) => {$(
+        mod $name {
+            #[test]
+            fn enc_passing() {
+                super::enc::<$hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn enc_failing() {
+                super::enc::<$hash>(super::$backend(), true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn enc_batch_passing() {
+                super::enc_batch::<$hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn enc_batch_failing() {
+                super::enc_batch::<$hash>(super::$backend(), true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn enc_interval_passing() {
+                super::enc_interval::<$hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn enc_interval_failing() {
+                super::enc_interval::<$hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn enc_linear_combination_passing() {
+                super::enc_linear_combination::<$hash>(super::$backend(), false)
+                    .expect("proof failed")
+            }
+            #[test]
+            fn enc_linear_combination_failing() {
+                super::enc_linear_combination::<$hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn enc_elg_passing() {
+                super::enc_elg::<$curve, $hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn enc_elg_failing() {
+                super::enc_elg::<$curve, $hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn log_star_passing() {
+                super::log_star::<$curve, $hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn log_star_failing() {
+                super::log_star::<$curve, $hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn ped_passing() {
+                super::ped::<$curve, $hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn ped_failing() {
+                super::ped::<$curve, $hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn mul_star_passing() {
+                super::mul_star::<$curve, $hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn mul_star_failing() {
+                super::mul_star::<$curve, $hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn dec_passing() {
+                super::dec::<$curve, $hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn dec_failing() {
+                super::dec::<$curve, $hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn aff_g_passing() {
+                super::aff_g::<$curve, $hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn aff_g_failing() {
+                super::aff_g::<$curve, $hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn aff_p_passing() {
+                super::aff_p::<$hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn aff_p_failing() {
+                super::aff_p::<$hash>(super::$backend(), true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn witness_escrow_passing() {
+                super::witness_escrow::<$curve, $hash>(super::$backend(), false)
+                    .expect("proof failed")
+            }
+            #[test]
+            fn witness_escrow_failing() {
+                super::witness_escrow::<$curve, $hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn plaintext_eq_passing() {
+                super::plaintext_eq::<$hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn plaintext_eq_failing() {
+                super::plaintext_eq::<$hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn scalar_mul_passing() {
+                super::scalar_mul::<$hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn scalar_mul_failing() {
+                super::scalar_mul::<$hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn broadcast_passing() {
+                super::broadcast::<$hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn broadcast_failing() {
+                super::broadcast::<$hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn paillier_blum_modulus_passing() {
+                super::paillier_blum_modulus::<$hash>(false).expect("proof failed")
+            }
+            #[test]
+            fn paillier_blum_modulus_failing() {
+                super::paillier_blum_modulus::<$hash>(true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn ring_pedersen_parameters_passing() {
+                super::ring_pedersen_parameters::<$hash>(super::$backend(), false)
+                    .expect("proof failed")
+            }
+            #[test]
+            fn ring_pedersen_parameters_failing() {
+                super::ring_pedersen_parameters::<$hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn mul_passing() {
+                super::mul::<$hash>(false).expect("proof failed")
+            }
+            #[test]
+            fn mul_failing() {
+                super::mul::<$hash>(true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn enc_bit_passing() {
+                super::enc_bit::<$hash>(false).expect("proof failed")
+            }
+            #[test]
+            fn enc_bit_failing() {
+                super::enc_bit::<$hash>(true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn enc_zero_passing() {
+                super::enc_zero::<$hash>(false).expect("proof failed")
+            }
+            #[test]
+            fn enc_zero_failing() {
+                super::enc_zero::<$hash>(true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn enc_zero_opening_passing() {
+                super::enc_zero_opening::<$hash>(false).expect("proof failed")
+            }
+            #[test]
+            fn enc_zero_opening_failing() {
+                super::enc_zero_opening::<$hash>(true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn enc_zero_rerandomization_passing() {
+                super::enc_zero_rerandomization::<$hash>(false).expect("proof failed")
+            }
+            #[test]
+            fn enc_zero_rerandomization_failing() {
+                super::enc_zero_rerandomization::<$hash>(true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn plaintext_knowledge_passing() {
+                super::plaintext_knowledge::<$hash>(false).expect("proof failed")
+            }
+            #[test]
+            fn plaintext_knowledge_failing() {
+                super::plaintext_knowledge::<$hash>(true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn exact_range_passing() {
+                super::exact_range::<$hash>(false).expect("proof failed")
+            }
+            #[test]
+            fn exact_range_failing() {
+                super::exact_range::<$hash>(true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn factorization_knowledge_passing() {
+                super::factorization_knowledge::<$hash>(false).expect("proof failed")
+            }
+            #[test]
+            fn factorization_knowledge_failing() {
+                super::factorization_knowledge::<$hash>(true).expect_err("proof should fail");
+            }
+
+            #[test]
+            fn no_small_factor_passing() {
+                super::no_small_factor::<$hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn no_small_factor_failing() {
+                super::no_small_factor::<$hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn presigning_passing() {
+                super::presigning::<$curve, $hash>(super::$backend(), false)
+                    .expect("proof failed")
+            }
+            #[test]
+            fn presigning_failing() {
+                super::presigning::<$curve, $hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+
+            #[test]
+            fn setup_proofs_passing() {
+                super::setup_proofs::<$hash>(super::$backend(), false).expect("proof failed")
+            }
+            #[test]
+            fn setup_proofs_failing() {
+                super::setup_proofs::<$hash>(super::$backend(), true)
+                    .expect_err("proof should fail");
+            }
+        }
+    )+};
+}
+
+matrix! {
+    secp256k1_sha256_naive: (generic_ec::curves::Secp256k1, sha2::Sha256, naive);
+    secp256k1_sha256_precomputed: (generic_ec::curves::Secp256k1, sha2::Sha256, precomputed);
+    secp256k1_sha512_256_naive: (generic_ec::curves::Secp256k1, sha2::Sha512_256, naive);
+    secp256r1_sha256_naive: (generic_ec::curves::Secp256r1, sha2::Sha256, naive);
+    secp256r1_sha256_precomputed: (generic_ec::curves::Secp256r1, sha2::Sha256, precomputed);
+    secp256r1_sha512_256_precomputed: (generic_ec::curves::Secp256r1, sha2::Sha512_256, precomputed);
+}